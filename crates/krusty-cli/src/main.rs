@@ -11,6 +11,7 @@ use clap::{Parser, Subcommand};
 // Re-export core modules for TUI usage
 use krusty_core::{acp, agent, ai, constants, extensions, paths, plan, process, storage, tools};
 
+mod sessions;
 mod tui;
 
 /// Krusty - AI Coding Assistant
@@ -34,6 +35,10 @@ enum Commands {
     /// - KRUSTY_PROVIDER + KRUSTY_API_KEY (+ optional KRUSTY_MODEL)
     /// - Or provider-specific: ANTHROPIC_API_KEY, OPENROUTER_API_KEY, etc.
     Acp,
+
+    /// Work with saved sessions
+    #[command(subcommand)]
+    Sessions(sessions::SessionsCommand),
 }
 
 /// Restore terminal state - called on panic or unexpected exit
@@ -115,6 +120,7 @@ async fn main() -> Result<()> {
             let server = acp::AcpServer::new()?;
             server.run().await?;
         }
+        Some(Commands::Sessions(command)) => sessions::run(command)?,
         None => {
             // Default: Start TUI chat
             let mut app = tui::App::new().await;
@@ -0,0 +1,215 @@
+//! `krusty sessions` subcommands
+
+use std::io::IsTerminal;
+use std::path::PathBuf;
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Days, Local, NaiveDate, Utc};
+use clap::{Subcommand, ValueEnum};
+
+use crate::paths;
+use crate::storage::{
+    Database, MessageSearchFilter, MessageSearchHit, SessionExport, SessionManager,
+};
+
+#[derive(Subcommand)]
+pub enum SessionsCommand {
+    /// Search the text of past sessions
+    ///
+    /// Every word has to appear in a message; words match by prefix and
+    /// stem, so `migrat` finds "migration" and "migrations".
+    Search {
+        /// Words to look for
+        #[arg(required = true)]
+        query: Vec<String>,
+        /// Only sessions started in the current directory (or below it)
+        #[arg(long, conflicts_with = "dir")]
+        here: bool,
+        /// Only sessions started in this directory (or below it)
+        #[arg(long)]
+        dir: Option<PathBuf>,
+        /// Only messages from this day on (YYYY-MM-DD)
+        #[arg(long, value_parser = parse_date)]
+        since: Option<NaiveDate>,
+        /// Only messages up to and including this day (YYYY-MM-DD)
+        #[arg(long, value_parser = parse_date)]
+        until: Option<NaiveDate>,
+        /// Maximum number of results
+        #[arg(long, default_value_t = 20)]
+        limit: usize,
+    },
+    /// Write a session out as Markdown, HTML or JSON
+    ///
+    /// API keys, tokens and passwords in Markdown and HTML are masked unless
+    /// --no-mask is given. JSON exports are kept as they are, so `krusty
+    /// sessions import` restores them exactly; --mask masks them too.
+    Export {
+        /// Session ID (as shown by `krusty sessions search`)
+        id: String,
+        #[arg(long, value_enum, default_value_t = ExportFormat::Md)]
+        format: ExportFormat,
+        /// Write to this file instead of stdout
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+        /// Keep secrets as they are
+        #[arg(long)]
+        no_mask: bool,
+        /// Mask secrets in a JSON export as well
+        #[arg(long, conflicts_with = "no_mask")]
+        mask: bool,
+    },
+    /// Restore a session from a JSON export
+    Import {
+        /// File written by `krusty sessions export --format json`
+        file: PathBuf,
+        /// Attach the session to the current directory instead of the
+        /// exported one, so it shows up in /load here
+        #[arg(long)]
+        here: bool,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum ExportFormat {
+    Md,
+    Html,
+    Json,
+}
+
+/// Run a `krusty sessions` subcommand
+pub fn run(command: SessionsCommand) -> Result<()> {
+    match command {
+        SessionsCommand::Search {
+            query,
+            here,
+            dir,
+            since,
+            until,
+            limit,
+        } => {
+            let working_dir = match (here, dir) {
+                (true, _) => Some(std::env::current_dir()?),
+                (false, Some(dir)) => Some(std::fs::canonicalize(&dir).unwrap_or(dir)),
+                (false, None) => None,
+            };
+            let filter = MessageSearchFilter {
+                working_dir: working_dir.map(|d| d.to_string_lossy().into_owned()),
+                since: since.and_then(start_of_day),
+                until: until
+                    .and_then(|d| d.checked_add_days(Days::new(1)))
+                    .and_then(start_of_day),
+            };
+            search(&query.join(" "), &filter, limit)
+        }
+        SessionsCommand::Export {
+            id,
+            format,
+            output,
+            no_mask,
+            mask,
+        } => {
+            let mask = match format {
+                ExportFormat::Json => mask,
+                ExportFormat::Md | ExportFormat::Html => !no_mask,
+            };
+            export(&id, format, output, mask)
+        }
+        SessionsCommand::Import { file, here } => {
+            let working_dir = if here {
+                Some(std::env::current_dir()?.to_string_lossy().into_owned())
+            } else {
+                None
+            };
+            let export: SessionExport = serde_json::from_str(&std::fs::read_to_string(&file)?)
+                .map_err(|e| anyhow!("{} is not a session export: {}", file.display(), e))?;
+            let id = open_sessions()?.import_session(&export, working_dir.as_deref())?;
+            println!(
+                "Imported \"{}\" ({} messages) as session {}",
+                export.session.title,
+                export.messages.len(),
+                id
+            );
+            Ok(())
+        }
+    }
+}
+
+fn open_sessions() -> Result<SessionManager> {
+    let db = Database::new(&paths::config_dir().join("krusty.db"))?;
+    Ok(SessionManager::new(db))
+}
+
+fn search(query: &str, filter: &MessageSearchFilter, limit: usize) -> Result<()> {
+    let hits = open_sessions()?.search_messages(query, filter, limit)?;
+    if hits.is_empty() {
+        println!("No messages match \"{}\"", query);
+        return Ok(());
+    }
+
+    let highlight = std::io::stdout().is_terminal();
+    for hit in &hits {
+        println!(
+            "{}  ({}, {})",
+            hit.session_title,
+            hit.created_at
+                .with_timezone(&Local)
+                .format("%Y-%m-%d %H:%M"),
+            hit.working_dir.as_deref().unwrap_or("no directory"),
+        );
+        println!("  {}: {}", hit.role, render_snippet(hit, highlight));
+        println!(
+            "  session {} · message {}",
+            hit.session_id,
+            hit.message_index + 1
+        );
+        println!();
+    }
+    Ok(())
+}
+
+fn export(id: &str, format: ExportFormat, output: Option<PathBuf>, mask: bool) -> Result<()> {
+    let mut export = open_sessions()?.export_session(id)?;
+    if mask {
+        export.mask_secrets()?;
+    }
+    let text = match format {
+        ExportFormat::Md => export.to_markdown(),
+        ExportFormat::Html => export.to_html(),
+        ExportFormat::Json => serde_json::to_string_pretty(&export)? + "\n",
+    };
+    match output {
+        Some(path) => {
+            std::fs::write(&path, text)?;
+            eprintln!("Wrote {}", path.display());
+        }
+        None => print!("{}", text),
+    }
+    Ok(())
+}
+
+/// Snippet on one line, matches in bold when writing to a terminal
+fn render_snippet(hit: &MessageSearchHit, highlight: bool) -> String {
+    hit.snippet_parts()
+        .into_iter()
+        .map(|(text, is_match)| {
+            let text = text.replace(['\n', '\r'], " ");
+            if is_match && highlight {
+                format!("\x1b[1m{}\x1b[0m", text)
+            } else {
+                text
+            }
+        })
+        .collect()
+}
+
+fn parse_date(s: &str) -> Result<NaiveDate> {
+    NaiveDate::parse_from_str(s, "%Y-%m-%d").map_err(|_| anyhow!("expected YYYY-MM-DD"))
+}
+
+/// Midnight at the start of a local day, in UTC
+fn start_of_day(day: NaiveDate) -> Option<DateTime<Utc>> {
+    day.and_hms_opt(0, 0, 0)?
+        .and_local_timezone(Local)
+        .earliest()
+        .map(|t| t.with_timezone(&Utc))
+}
@@ -15,30 +15,45 @@ use crossterm::{
 };
 use futures::StreamExt;
 use ratatui::{backend::CrosstermBackend, Terminal};
-use std::{io, path::PathBuf, sync::Arc, time::Duration};
+use std::{
+    io,
+    path::PathBuf,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 use tokio::sync::RwLock;
 
-use crate::agent::{AgentCancellation, AgentConfig, AgentEventBus, AgentState, UserHookManager};
+use crate::agent::subagent::AgentControls;
+use crate::agent::{
+    AgentCancellation, AgentConfig, AgentEventBus, AgentState, AgentsManager, Compactor,
+    UserHookManager,
+};
 use crate::ai::client::AiClient;
 use crate::ai::models::SharedModelRegistry;
 use crate::ai::providers::ProviderId;
+use crate::ai::routing::ModelRouting;
 use crate::ai::types::{AiTool, AiToolCall, Content};
 use crate::extensions::WasmHost;
-use crate::plan::{PlanFile, PlanManager};
+use crate::plan::{PlanFile, PlanManager, PlanSync};
 use crate::process::ProcessRegistry;
-use crate::storage::{CredentialStore, Preferences, SessionManager};
+use crate::storage::{CredentialStore, Preferences, SessionManager, SharedDatabase};
 use crate::tools::ToolRegistry;
 use crate::tui::animation::MenuAnimator;
-use crate::tui::input::{AutocompletePopup, MultiLineInput};
+use crate::tui::input::{AutocompletePopup, EditMode, Keymap, MultiLineInput};
 use crate::tui::markdown::MarkdownCache;
 use crate::tui::polling::{
-    poll_background_processes, poll_init_exploration, poll_mcp_status, poll_oauth_status,
+    poll_background_processes, poll_docs_status, poll_init_exploration, poll_mcp_status,
+    poll_oauth_status,
 };
 use crate::tui::state::{
     BlockManager, BlockUiStates, ChatState, PopupState, ScrollSystem, ToolResultCache,
 };
 use crate::tui::streaming::StreamingManager;
-use crate::tui::utils::{AsyncChannels, TitleEditor};
+use crate::tui::utils::{
+    AgentSteer, AsyncChannels, BranchNav, Delivery, PromptQueue, TaskCheckResult, TitleEditor,
+};
+use krusty_core::commands::{CommandDefinition, CommandsManager};
+use krusty_core::docs::DocsManager;
 use krusty_core::skills::SkillsManager;
 
 /// View types
@@ -57,12 +72,16 @@ pub enum Popup {
     ThemeSelect,
     Help,
     SessionList,
+    SessionSearch,
     McpBrowser,
     ProcessList,
     Pinch,
     FilePreview,
     SkillsBrowser,
     Hooks,
+    DocsBrowser,
+    Transcript,
+    ChangeReview,
 }
 
 /// Work mode - BUILD (coding) or PLAN (planning)
@@ -87,6 +106,8 @@ pub struct AppServices {
     pub plan_manager: PlanManager,
     pub session_manager: Option<SessionManager>,
     pub preferences: Option<Preferences>,
+    /// Connection sub-agents write their transcripts to
+    pub transcript_db: Option<SharedDatabase>,
 
     // Credentials/models
     pub credential_store: CredentialStore,
@@ -101,11 +122,17 @@ pub struct AppServices {
     #[allow(dead_code)]
     pub wasm_host: Option<Arc<WasmHost>>,
 
-    // Skills/MCP
+    // Skills/agents/MCP
     pub skills_manager: Arc<RwLock<SkillsManager>>,
+    pub agents_manager: Arc<RwLock<AgentsManager>>,
+    pub commands_manager: CommandsManager,
     pub mcp_manager: Arc<krusty_core::mcp::McpManager>,
     pub mcp_status_tx: tokio::sync::mpsc::UnboundedSender<crate::tui::utils::McpStatusUpdate>,
     pub oauth_status_tx: tokio::sync::mpsc::UnboundedSender<crate::tui::utils::OAuthStatusUpdate>,
+
+    // Indexed docs
+    pub docs_manager: Arc<DocsManager>,
+    pub docs_status_tx: tokio::sync::mpsc::UnboundedSender<crate::tui::utils::DocsStatusUpdate>,
 }
 
 /// UI-only state (view, popups, inputs, rendering, animations)
@@ -120,6 +147,8 @@ pub struct AppUi {
     pub theme: Arc<crate::tui::themes::Theme>,
     /// Theme name for display/saving
     pub theme_name: String,
+    /// Key bindings (defaults plus ~/.krusty/keymap.toml)
+    pub keymap: Keymap,
     /// Pending view change to apply at end of event loop
     pub pending_view_change: Option<View>,
     /// Plan sidebar component state
@@ -162,6 +191,7 @@ impl AppUi {
             work_mode: WorkMode::Build,
             theme,
             theme_name,
+            keymap: Keymap::load(&crate::paths::keymap_path()),
             pending_view_change: None,
             plan_sidebar: crate::tui::components::PlanSidebarState::default(),
             plugin_window: crate::tui::components::PluginWindowState::default(),
@@ -188,12 +218,16 @@ pub struct AppRuntime {
     pub chat: ChatState,
     /// Current model identifier
     pub current_model: String,
+    /// Per-role model routes (explore, build, title, summarizer)
+    pub model_routing: ModelRouting,
     /// Token usage tracking
     pub context_tokens_used: usize,
     /// Flag to trigger auto-pinch after response completes
     pub pending_auto_pinch: bool,
     /// Auto-pinch in progress (bypasses popup when AI is busy)
     pub auto_pinch_in_progress: bool,
+    /// Compacts the conversation in place as it nears the context window
+    pub compactor: Option<Compactor>,
     /// AI client
     pub ai_client: Option<AiClient>,
     /// API key
@@ -218,12 +252,50 @@ pub struct AppRuntime {
     pub channels: AsyncChannels,
     /// /init exploration ID
     pub init_explore_id: Option<String>,
+    /// BuildBlock ID of the running /plan run
+    pub plan_run_id: Option<String>,
+    /// Cancels the running /plan run
+    pub plan_run_cancel: Option<AgentCancellation>,
+    /// Per-agent controls of sub-agent runs, keyed by tool call or plan run ID
+    pub agent_controls: std::collections::HashMap<String, AgentControls>,
+    /// Sub-agent the input box is writing a note or restart prompt for
+    pub agent_steer: Option<AgentSteer>,
+    /// Prompts submitted while the agent was working
+    pub prompt_queue: PromptQueue,
+    /// Earlier-message selection and sibling branches of the loaded session
+    pub branches: BranchNav,
+    /// Custom command whose tool allowlist and model apply until the next prompt
+    pub active_command: Option<CommandDefinition>,
+    /// Project command and its invocation waiting for its shell commands to be confirmed
+    pub pending_command_shell: Option<(CommandDefinition, String)>,
+    /// Project command files (and templates) whose shell commands may run this session
+    pub approved_command_shells: std::collections::HashSet<(PathBuf, String)>,
+    /// Plan version when /plan revisions was last shown (guards /plan restore)
+    pub plan_revision_seen: Option<u64>,
+    /// Repo file the plan is synced with (`/plan sync`)
+    pub plan_sync: Option<PlanSync>,
+    /// Plan read from the sync file that conflicts with the stored plan
+    pub plan_sync_conflict: Option<PlanFile>,
+    /// Whether the conflict prompt was already shown for `plan_sync_conflict`
+    pub plan_sync_prompted: bool,
+    /// Last time the sync file was checked
+    pub plan_sync_polled: Instant,
+    /// Theme files and modification times at the last reload
+    pub themes_stamp: Vec<(PathBuf, Option<std::time::SystemTime>)>,
+    /// Last time the themes directory was checked
+    pub themes_polled: Instant,
     /// Cached languages for /init
     pub cached_init_languages: Option<Vec<String>>,
     /// Queued tool calls waiting for explore
     pub queued_tools: Vec<AiToolCall>,
     /// Pending tool results to combine
     pub pending_tool_results: Vec<Content>,
+    /// task_complete calls waiting on acceptance checks
+    pub pending_task_checks: usize,
+    /// Sender for acceptance check results (receiver lives in `channels`)
+    pub task_check_tx: tokio::sync::mpsc::UnboundedSender<TaskCheckResult>,
+    /// MCP server request shown in the decision prompt
+    pub active_mcp_request: Option<crate::tui::utils::McpUserRequest>,
     /// Agent event bus
     pub event_bus: AgentEventBus,
     /// Agent state
@@ -238,6 +310,8 @@ pub struct AppRuntime {
     pub streaming: StreamingManager,
     /// Clipboard images pending resolution
     pub pending_clipboard_images: std::collections::HashMap<String, (usize, usize, Vec<u8>)>,
+    /// Prompt should be handed to $VISUAL/$EDITOR on the next loop pass
+    pub external_edit_requested: bool,
     /// Block manager (owns all block types)
     pub blocks: BlockManager,
     /// Tool result cache for rendering
@@ -261,13 +335,19 @@ impl AppRuntime {
         working_dir: PathBuf,
         process_registry: Arc<ProcessRegistry>,
     ) -> Self {
+        let (task_check_tx, task_check_rx) = tokio::sync::mpsc::unbounded_channel();
+        let mut channels = AsyncChannels::new();
+        channels.task_checks = Some(task_check_rx);
+
         Self {
             active_plan: None,
             chat: ChatState::new(),
             current_model,
+            model_routing: ModelRouting::default(),
             context_tokens_used: 0,
             pending_auto_pinch: false,
             auto_pinch_in_progress: false,
+            compactor: None,
             ai_client: None,
             api_key: None,
             active_provider,
@@ -278,11 +358,30 @@ impl AppRuntime {
             current_session_id: None,
             session_title: None,
             title_editor: TitleEditor::new(),
-            channels: AsyncChannels::new(),
+            channels,
             init_explore_id: None,
+            plan_run_id: None,
+            plan_run_cancel: None,
+            agent_controls: std::collections::HashMap::new(),
+            agent_steer: None,
+            prompt_queue: PromptQueue::default(),
+            branches: BranchNav::default(),
+            active_command: None,
+            pending_command_shell: None,
+            approved_command_shells: std::collections::HashSet::new(),
+            plan_revision_seen: None,
+            plan_sync: None,
+            plan_sync_conflict: None,
+            plan_sync_prompted: false,
+            plan_sync_polled: Instant::now(),
+            themes_stamp: crate::tui::themes::user::themes_stamp(&crate::paths::themes_dir()),
+            themes_polled: Instant::now(),
             cached_init_languages: None,
             queued_tools: Vec::new(),
             pending_tool_results: Vec::new(),
+            pending_task_checks: 0,
+            task_check_tx,
+            active_mcp_request: None,
             event_bus: AgentEventBus::new(),
             agent_state: AgentState::new(),
             agent_config: AgentConfig::default(),
@@ -290,6 +389,7 @@ impl AppRuntime {
             thinking_enabled: false,
             streaming: StreamingManager::new(),
             pending_clipboard_images: std::collections::HashMap::new(),
+            external_edit_requested: false,
             blocks: BlockManager::new(),
             tool_results: ToolResultCache::new(),
             attached_files: std::collections::HashMap::new(),
@@ -318,7 +418,7 @@ impl App {
 
         // Initialize all services via builder
         let (
-            services,
+            mut services,
             channels,
             process_registry,
             current_model,
@@ -327,7 +427,7 @@ impl App {
             active_provider,
         ) = crate::tui::app_builder::init_services(&working_dir).await;
 
-        let ui = AppUi::new(theme, theme_name, working_dir.clone());
+        let mut ui = AppUi::new(theme, theme_name, working_dir.clone());
         let runtime = AppRuntime::new(
             current_model,
             active_provider,
@@ -336,11 +436,39 @@ impl App {
         );
 
         // Manually set channels that were initialized in init_services
-        let runtime = AppRuntime {
+        let model_routing = services
+            .preferences
+            .as_ref()
+            .map(|p| p.get_model_routing())
+            .unwrap_or_default();
+        let mut runtime = AppRuntime {
             channels,
+            model_routing,
             ..runtime
         };
 
+        // Custom slash commands in autocomplete
+        let custom_commands = services.commands_manager.list_commands();
+        ui.autocomplete.set_custom_commands(&custom_commands);
+
+        // Editing mode chosen with /editmode
+        if let Some(mode) = services
+            .preferences
+            .as_ref()
+            .and_then(|p| EditMode::from_name(&p.get_edit_mode()))
+        {
+            ui.input.set_edit_mode(mode);
+        }
+
+        // Delivery for queued prompts chosen with /queue
+        if let Some(delivery) = services
+            .preferences
+            .as_ref()
+            .and_then(|p| Delivery::from_name(&p.get_queue_delivery()))
+        {
+            runtime.prompt_queue.default_delivery = delivery;
+        }
+
         Self {
             ui,
             runtime,
@@ -377,11 +505,35 @@ impl App {
 
     /// Clear the active plan and sync UI state
     pub fn clear_plan(&mut self) {
+        self.stop_plan_run();
         self.runtime.active_plan = None;
+        self.runtime.plan_revision_seen = None;
+        self.runtime.plan_sync_conflict = None;
         self.ui.work_mode = WorkMode::Build;
         self.ui.plan_sidebar.reset();
     }
 
+    /// Cancel a running /plan run and detach it from the UI
+    fn stop_plan_run(&mut self) {
+        if let Some(cancel) = self.runtime.plan_run_cancel.take() {
+            cancel.cancel();
+        }
+        if let Some(run_id) = self.runtime.plan_run_id.take() {
+            if let Some(block) = self
+                .runtime
+                .blocks
+                .build
+                .iter_mut()
+                .find(|b| b.tool_use_id() == Some(run_id.as_str()))
+            {
+                block.complete("**Plan Run**: cancelled".to_string());
+            }
+            self.release_agent_controls(&run_id);
+        }
+        self.runtime.channels.plan_run = None;
+        self.runtime.channels.plan_run_progress = None;
+    }
+
     /// Set the active plan without changing work mode
     ///
     /// Callers are responsible for setting the appropriate WorkMode:
@@ -590,6 +742,12 @@ impl App {
         // Check for pending update from previous session (cleans up stale files)
         self.check_pending_update();
 
+        // Pick up the plan file sync configured for this directory
+        self.load_plan_sync();
+
+        // Say if keymap.toml has mistakes or clashing keys
+        self.report_keymap_problems();
+
         // Check for updates in background
         self.start_update_check();
 
@@ -613,22 +771,8 @@ impl App {
             }
         }
 
-        enable_raw_mode()?;
         let mut stdout = io::stdout();
-        execute!(
-            stdout,
-            EnterAlternateScreen,
-            EnableMouseCapture,
-            EnableBracketedPaste,
-            // Enable Kitty keyboard protocol for better key detection
-            // - DISAMBIGUATE_ESCAPE_CODES: Better escape sequence handling
-            // - REPORT_EVENT_TYPES: Enables key release detection (needed for games)
-            // Note: REPORT_ALL_KEYS_AS_ESCAPE_CODES breaks Shift+key for special chars
-            PushKeyboardEnhancementFlags(
-                KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES
-                    | KeyboardEnhancementFlags::REPORT_EVENT_TYPES
-            )
-        )?;
+        enter_tui(&mut stdout)?;
         let backend = CrosstermBackend::new(stdout);
         let mut terminal = Terminal::new(backend)?;
 
@@ -641,14 +785,7 @@ impl App {
         // Kill all background processes on shutdown
         self.runtime.process_registry.kill_all().await;
 
-        disable_raw_mode()?;
-        execute!(
-            terminal.backend_mut(),
-            PopKeyboardEnhancementFlags,
-            LeaveAlternateScreen,
-            DisableMouseCapture,
-            DisableBracketedPaste
-        )?;
+        leave_tui(terminal.backend_mut())?;
         terminal.show_cursor()?;
         result
     }
@@ -736,6 +873,9 @@ impl App {
                 }
             }
 
+            // Deliver task_complete results whose acceptance checks finished
+            self.poll_task_checks();
+
             // Poll async operations
             self.poll_openrouter_fetch();
             self.poll_title_generation();
@@ -767,6 +907,13 @@ impl App {
             // Poll build progress channel for builder updates
             self.poll_build_progress();
 
+            // Poll /plan run task events and builder progress
+            let plan_run_result = self.poll_plan_run();
+            if plan_run_result.needs_redraw {
+                self.ui.needs_redraw = true;
+            }
+            self.process_poll_actions(plan_run_result);
+
             // Poll /init exploration progress and result
             // Clone cached languages to avoid borrow conflict (cleared on completion)
             let languages = self
@@ -794,6 +941,23 @@ impl App {
             }
             self.process_poll_actions(mcp_result);
 
+            // Show sampling/elicitation requests from MCP servers
+            self.poll_mcp_requests();
+
+            // Keep the plan and its repo file in step
+            self.poll_plan_sync();
+
+            // Pick up edits to user theme files
+            self.poll_user_themes();
+
+            // Poll docs suggest/index updates from background tasks
+            let docs_result =
+                poll_docs_status(&mut self.runtime.channels, &mut self.ui.popups.docs);
+            if docs_result.needs_redraw {
+                self.ui.needs_redraw = true;
+            }
+            self.process_poll_actions(docs_result);
+
             // Poll OAuth status updates from background tasks
             let oauth_result = poll_oauth_status(
                 &mut self.runtime.channels,
@@ -835,6 +999,15 @@ impl App {
             // Only triggers when idle (not streaming, not executing tools)
             self.trigger_pending_auto_pinch();
 
+            // Apply in-place compaction done before the current request
+            self.poll_compaction();
+
+            // Send an expanded custom command once the agent is free
+            self.poll_custom_command();
+
+            // Send the next queued prompt once the turn is over
+            self.poll_prompt_queue();
+
             // Process continuous edge scrolling during selection
             if self.ui.scroll_system.edge_scroll.direction.is_some() {
                 self.process_edge_scroll();
@@ -888,6 +1061,15 @@ impl App {
             // Apply any deferred view changes (after popup handling)
             self.apply_pending_view_change();
 
+            // Hand the prompt to $EDITOR. The event stream is dropped first so
+            // it doesn't read the editor's keystrokes
+            if std::mem::take(&mut self.runtime.external_edit_requested) {
+                drop(event_stream);
+                self.edit_input_externally(terminal);
+                event_stream = EventStream::new();
+                self.ui.needs_redraw = true;
+            }
+
             if self.runtime.should_quit {
                 // Save session state before exiting
                 self.save_session_token_count();
@@ -898,3 +1080,35 @@ impl App {
         Ok(())
     }
 }
+
+/// Switch the terminal to TUI mode: raw input, alternate screen, mouse,
+/// bracketed paste and the Kitty keyboard protocol
+pub(crate) fn enter_tui(out: &mut impl io::Write) -> io::Result<()> {
+    enable_raw_mode()?;
+    execute!(
+        out,
+        EnterAlternateScreen,
+        EnableMouseCapture,
+        EnableBracketedPaste,
+        // Enable Kitty keyboard protocol for better key detection
+        // - DISAMBIGUATE_ESCAPE_CODES: Better escape sequence handling
+        // - REPORT_EVENT_TYPES: Enables key release detection (needed for games)
+        // Note: REPORT_ALL_KEYS_AS_ESCAPE_CODES breaks Shift+key for special chars
+        PushKeyboardEnhancementFlags(
+            KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES
+                | KeyboardEnhancementFlags::REPORT_EVENT_TYPES
+        )
+    )
+}
+
+/// Undo [`enter_tui`]
+pub(crate) fn leave_tui(out: &mut impl io::Write) -> io::Result<()> {
+    disable_raw_mode()?;
+    execute!(
+        out,
+        PopKeyboardEnhancementFlags,
+        LeaveAlternateScreen,
+        DisableMouseCapture,
+        DisableBracketedPaste
+    )
+}
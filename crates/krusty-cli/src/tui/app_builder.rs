@@ -2,11 +2,11 @@
 //!
 //! Breaks up the 300+ line App::new() constructor into focused helper functions.
 
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
-use crate::agent::{UserHookManager, UserPostToolHook, UserPreToolHook};
+use crate::agent::{AgentsManager, UserHookManager, UserPostToolHook, UserPreToolHook};
 use crate::ai::models::{create_model_registry, ModelMetadata, SharedModelRegistry};
 use crate::ai::providers::{builtin_providers, ProviderId};
 use crate::extensions::WasmHost;
@@ -14,10 +14,12 @@ use crate::paths;
 use crate::plan::PlanManager;
 use crate::process::ProcessRegistry;
 use crate::storage::{CredentialStore, Database, Preferences, SessionManager};
-use crate::tools::{register_all_tools, ToolRegistry};
+use crate::tools::{register_all_tools, register_docs_search_tool, ToolRegistry};
 use crate::tui::app::AppServices;
 use crate::tui::themes::{Theme, THEME_REGISTRY};
-use crate::tui::utils::{AsyncChannels, McpStatusUpdate};
+use crate::tui::utils::{AppWorktreeDelegate, AsyncChannels, McpPromptDelegate, McpStatusUpdate};
+use krusty_core::commands::CommandsManager;
+use krusty_core::docs::DocsManager;
 use krusty_core::skills::SkillsManager;
 
 /// Initialize core services (tools, extensions, etc.)
@@ -48,11 +50,15 @@ pub async fn init_services(
 
     // Tool registry with hooks
     let tool_registry = init_tool_registry(&user_hook_manager).await;
+
+    // Docs manager (registers docs_search when docs are already indexed)
+    let docs_manager =
+        init_docs_manager(&db_path, wasm_host.clone(), extensions_dir, &tool_registry).await;
     let cached_ai_tools = tool_registry.get_ai_tools().await;
 
     // Preferences and theme
     let (preferences, theme_name) = init_preferences(&db_path);
-    let theme = THEME_REGISTRY.get_or_default(&theme_name);
+    let theme = THEME_REGISTRY.read().get_or_default(&theme_name).clone();
 
     // Session manager
     let session_manager = init_session_manager(&db_path);
@@ -60,6 +66,16 @@ pub async fn init_services(
     // Plan manager
     let plan_manager = init_plan_manager(&db_path);
 
+    // Sub-agent transcripts are written from agent tasks, so they get their
+    // own shared connection
+    let transcript_db = match Database::shared(&db_path) {
+        Ok(db) => Some(db),
+        Err(e) => {
+            tracing::warn!("Failed to open transcript database: {}", e);
+            None
+        }
+    };
+
     // Credentials and active provider
     let credential_store = CredentialStore::load().unwrap_or_else(|e| {
         tracing::warn!("Failed to load credential store: {}", e);
@@ -84,23 +100,50 @@ pub async fn init_services(
         project_skills_dir,
     )));
 
+    // Custom agents manager (same global/project layout as skills)
+    let agents_manager = Arc::new(RwLock::new(AgentsManager::new(
+        paths::config_dir().join("agents"),
+        Some(working_dir.join(".krusty").join("agents")),
+    )));
+
+    // Custom slash commands (same global/project layout)
+    let commands_manager = CommandsManager::for_working_dir(working_dir);
+
     // MCP manager and channels
     let mcp_manager = Arc::new(krusty_core::mcp::McpManager::new(working_dir.to_path_buf()));
+    mcp_manager.spawn_supervisor(tool_registry.clone());
+    let mcp_events_rx = mcp_manager.subscribe();
+    let (mcp_requests_tx, mcp_requests_rx) = tokio::sync::mpsc::unbounded_channel();
+    mcp_manager
+        .set_client_delegate(Arc::new(McpPromptDelegate::new(mcp_requests_tx)))
+        .await;
     let (mcp_status_tx, mcp_status_rx) = tokio::sync::mpsc::unbounded_channel();
     let (oauth_status_tx, oauth_status_rx) = tokio::sync::mpsc::unbounded_channel();
+    let (docs_status_tx, docs_status_rx) = tokio::sync::mpsc::unbounded_channel();
 
     // Connect MCP servers in background
-    spawn_mcp_connections(&mcp_manager, &tool_registry, &mcp_status_tx).await;
+    spawn_mcp_connections(
+        &mcp_manager,
+        wasm_host.as_ref(),
+        working_dir,
+        &tool_registry,
+        &mcp_status_tx,
+    )
+    .await;
 
     // Set up channels
     let mut channels = AsyncChannels::new();
     channels.mcp_status = Some(mcp_status_rx);
+    channels.mcp_events = Some(mcp_events_rx);
+    channels.mcp_requests = Some(mcp_requests_rx);
     channels.oauth_status = Some(oauth_status_rx);
+    channels.docs_status = Some(docs_status_rx);
 
     let services = AppServices {
         plan_manager,
         session_manager,
         preferences,
+        transcript_db,
         credential_store,
         model_registry,
         tool_registry,
@@ -108,9 +151,13 @@ pub async fn init_services(
         user_hook_manager,
         wasm_host,
         skills_manager,
+        agents_manager,
+        commands_manager,
         mcp_manager,
         mcp_status_tx,
         oauth_status_tx,
+        docs_manager,
+        docs_status_tx,
     };
 
     (
@@ -118,7 +165,7 @@ pub async fn init_services(
         channels,
         process_registry,
         current_model,
-        Arc::new(theme.clone()),
+        Arc::new(theme),
         theme_name,
         active_provider,
     )
@@ -155,6 +202,30 @@ async fn init_tool_registry(user_hook_manager: &Arc<RwLock<UserHookManager>>) ->
     tool_registry
 }
 
+/// Initialize docs manager, exposing docs_search if anything is indexed
+async fn init_docs_manager(
+    db_path: &Path,
+    wasm_host: Option<Arc<WasmHost>>,
+    extensions_dir: PathBuf,
+    tool_registry: &Arc<ToolRegistry>,
+) -> Arc<DocsManager> {
+    let docs_manager = Arc::new(
+        DocsManager::new(db_path.to_path_buf(), wasm_host, extensions_dir)
+            .expect("Failed to create docs manager"),
+    );
+
+    match docs_manager.indexed_packages() {
+        Ok(packages) if !packages.is_empty() => {
+            register_docs_search_tool(tool_registry, docs_manager.clone()).await;
+            tracing::info!("Docs search enabled ({} indexed packages)", packages.len());
+        }
+        Ok(_) => {}
+        Err(e) => tracing::warn!("Failed to list indexed docs: {}", e),
+    }
+
+    docs_manager
+}
+
 /// Initialize preferences and get theme name
 fn init_preferences(db_path: &Path) -> (Option<Preferences>, String) {
     match Database::new(db_path) {
@@ -258,25 +329,48 @@ fn init_model_registry(preferences: &Option<Preferences>) -> SharedModelRegistry
 }
 
 /// Spawn MCP server connections in background
+///
+/// Context servers from installed extensions are resolved through the WASM
+/// host inside the background task, since loading extensions compiles WASM.
 async fn spawn_mcp_connections(
     mcp_manager: &Arc<krusty_core::mcp::McpManager>,
+    wasm_host: Option<&Arc<WasmHost>>,
+    working_dir: &Path,
     tool_registry: &Arc<ToolRegistry>,
     status_tx: &tokio::sync::mpsc::UnboundedSender<McpStatusUpdate>,
 ) {
     if let Err(e) = mcp_manager.load_config().await {
-        tracing::warn!("Failed to load MCP config: {}", e);
-        return;
+        tracing::warn!("Failed to load MCP config: {:#}", e);
+        let _ = status_tx.send(McpStatusUpdate {
+            success: false,
+            message: format!("MCP config: {:#}", e),
+        });
     }
 
-    if !mcp_manager.has_servers().await {
+    let extensions_dir = paths::extensions_dir();
+    let extension_host = wasm_host
+        .filter(|_| krusty_core::mcp::has_extension_servers(&extensions_dir))
+        .cloned();
+
+    if !mcp_manager.has_servers().await && extension_host.is_none() {
         return;
     }
 
     let mcp = mcp_manager.clone();
     let registry = tool_registry.clone();
     let status_tx = status_tx.clone();
+    let project = AppWorktreeDelegate::new(working_dir.to_path_buf());
 
     tokio::spawn(async move {
+        if let Some(host) = extension_host {
+            let added = mcp
+                .load_extension_servers(&host, &extensions_dir, project)
+                .await;
+            if added > 0 {
+                tracing::info!("Added {} MCP servers from extensions", added);
+            }
+        }
+
         if let Err(e) = mcp.connect_all().await {
             tracing::warn!("MCP server connection errors: {}", e);
         }
@@ -16,7 +16,10 @@ use std::collections::HashMap;
 use std::time::{Duration, Instant};
 use unicode_width::UnicodeWidthStr;
 
-use super::{ClipContext, EventResult, StreamBlock};
+use super::{
+    agent_row_control_at, render_agent_row_controls, transcript_control_at, BlockEvent,
+    ClipContext, EventResult, StreamBlock, AGENT_ROW_CONTROLS_WIDTH, TRANSCRIPT_CONTROL_WIDTH,
+};
 use crate::agent::subagent::{AgentProgress, AgentProgressStatus};
 use crate::tui::themes::Theme;
 
@@ -72,6 +75,9 @@ struct BuilderEntry {
 
 const LERP_SPEED: f32 = 0.15;
 
+/// Width of the block, capped at the area width
+const CONTENT_WIDTH: u16 = 68;
+
 impl BuilderEntry {
     fn from_progress(progress: &AgentProgress) -> Self {
         let mut entry = Self {
@@ -241,6 +247,25 @@ impl BuildBlock {
         self.tool_use_id.as_deref()
     }
 
+    /// Builder whose row (or expanded output) is on `line` of the builders area
+    ///
+    /// The flag is true when `line` is the row itself.
+    fn builder_at_line(&self, line: u16) -> Option<(&String, bool)> {
+        let mut y = 0u16;
+        for task_id in &self.builder_order {
+            let builder = self.builders.get(task_id)?;
+            let row = y;
+            y += 1;
+            if builder.expanded && !builder.output.is_empty() {
+                y += builder.output.lines().count().min(4) as u16;
+            }
+            if line < y {
+                return Some((task_id, line == row));
+            }
+        }
+        None
+    }
+
     /// Update builder state from progress
     pub fn update_progress(&mut self, progress: AgentProgress) {
         // Update line diff stats from progress
@@ -415,7 +440,13 @@ impl BuildBlock {
             let tokens_str = format!("{:>5}", builder.format_tokens());
 
             let action = builder.current_action.as_deref().unwrap_or("");
-            let action_max = (area.width as usize).saturating_sub(38);
+            let running = builder.status == AgentProgressStatus::Running;
+            let controls_width = if running {
+                AGENT_ROW_CONTROLS_WIDTH
+            } else {
+                TRANSCRIPT_CONTROL_WIDTH
+            };
+            let action_max = (area.width as usize).saturating_sub(38 + controls_width);
             // Truncate action safely at char boundary
             let action_display = if action.len() > action_max && action_max > 3 {
                 let truncated: String = action.chars().take(action_max.saturating_sub(3)).collect();
@@ -459,6 +490,8 @@ impl BuildBlock {
 
             buf.set_string(x, y, &action_display, line_style);
 
+            render_agent_row_controls(buf, area.x + area.width - 1, y, running, theme);
+
             buf.set_string(area.x + area.width - 1, y, "│", border_style);
 
             y += 1;
@@ -587,8 +620,7 @@ impl StreamBlock for BuildBlock {
 
         let (clip_top, _clip_bottom) = clip.map(|c| (c.clip_top, c.clip_bottom)).unwrap_or((0, 0));
 
-        let content_width = 68u16;
-        let width = content_width.min(area.width);
+        let width = CONTENT_WIDTH.min(area.width);
 
         if clip_top == 0 {
             self.render_header(Rect::new(area.x, area.y, width, 1), buf, theme);
@@ -651,28 +683,52 @@ impl StreamBlock for BuildBlock {
         &mut self,
         event: &Event,
         area: Rect,
-        _clip: Option<ClipContext>,
+        clip: Option<ClipContext>,
     ) -> EventResult {
         match event {
             Event::Mouse(MouseEvent {
                 kind: MouseEventKind::Down(MouseButton::Left),
+                column,
                 row,
                 ..
             }) => {
-                let y = *row;
+                // Line within the whole block, counting rows scrolled off the top
+                let line = row.saturating_sub(area.y) + clip.map_or(0, |c| c.clip_top);
 
-                if y == area.y {
+                // Header click toggles collapse
+                if line == 0 {
                     self.collapsed = !self.collapsed;
                     return EventResult::Consumed;
                 }
 
-                if !self.collapsed && y > area.y && y < area.y + area.height - 2 {
-                    let idx = (y - area.y - 1) as usize;
-                    if idx < self.builder_order.len() {
-                        if let Some(builder) = self.builders.get_mut(&self.builder_order[idx]) {
-                            builder.expanded = !builder.expanded;
-                            return EventResult::Consumed;
+                // Row click toggles expanded; its controls open the transcript or
+                // act on a running builder
+                if !self.collapsed {
+                    if let Some((task_id, true)) = self.builder_at_line(line - 1) {
+                        let task_id = task_id.clone();
+                        let right_border = area.x + CONTENT_WIDTH.min(area.width) - 1;
+                        let Some(builder) = self.builders.get_mut(&task_id) else {
+                            return EventResult::Ignored;
+                        };
+                        let name = builder.name.clone();
+                        if transcript_control_at(*column, right_border) {
+                            return EventResult::Action(BlockEvent::OpenTranscript {
+                                task_id,
+                                name,
+                            });
                         }
+                        let action = (builder.status == AgentProgressStatus::Running)
+                            .then(|| agent_row_control_at(*column, right_border))
+                            .flatten();
+                        if let Some(action) = action {
+                            return EventResult::Action(BlockEvent::AgentAction {
+                                task_id,
+                                name,
+                                action,
+                            });
+                        }
+                        builder.expanded = !builder.expanded;
+                        return EventResult::Consumed;
                     }
                 }
 
@@ -13,7 +13,10 @@ use std::collections::HashMap;
 use std::time::{Duration, Instant};
 use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
-use super::{ClipContext, EventResult, StreamBlock};
+use super::{
+    agent_row_control_at, render_agent_row_controls, transcript_control_at, BlockEvent,
+    ClipContext, EventResult, StreamBlock, AGENT_ROW_CONTROLS_WIDTH, TRANSCRIPT_CONTROL_WIDTH,
+};
 use crate::agent::subagent::{AgentProgress, AgentProgressStatus};
 use crate::tui::themes::Theme;
 
@@ -73,6 +76,9 @@ struct AgentEntry {
 /// Lower = faster catch-up. 0.15 gives smooth ~100ms transitions.
 const LERP_SPEED: f32 = 0.15;
 
+/// Width of the block, capped at the area width
+const CONTENT_WIDTH: u16 = 68;
+
 impl AgentEntry {
     fn from_progress(progress: &AgentProgress) -> Self {
         let mut entry = Self {
@@ -257,6 +263,25 @@ impl ExploreBlock {
         self.tool_use_id.as_deref()
     }
 
+    /// Agent whose row (or expanded output) is on `line` of the agents area
+    ///
+    /// The flag is true when `line` is the row itself.
+    fn agent_at_line(&self, line: u16) -> Option<(&String, bool)> {
+        let mut y = 0u16;
+        for task_id in &self.agent_order {
+            let agent = self.agents.get(task_id)?;
+            let row = y;
+            y += 1;
+            if agent.expanded && !agent.output.is_empty() {
+                y += agent.output.lines().count().min(4) as u16;
+            }
+            if line < y {
+                return Some((task_id, line == row));
+            }
+        }
+        None
+    }
+
     /// Update agent state from progress
     pub fn update_progress(&mut self, progress: AgentProgress) {
         let task_id = progress.task_id.clone();
@@ -444,7 +469,13 @@ impl ExploreBlock {
 
             let action = agent.current_action.as_deref().unwrap_or("");
             // Account for: border(2) + spinner(2) + name(9) + 3 dividers(6) + tools(9) + tokens(6) + border(1) = 35
-            let action_max = (area.width as usize).saturating_sub(38);
+            let running = agent.status == AgentProgressStatus::Running;
+            let controls_width = if running {
+                AGENT_ROW_CONTROLS_WIDTH
+            } else {
+                TRANSCRIPT_CONTROL_WIDTH
+            };
+            let action_max = (area.width as usize).saturating_sub(38 + controls_width);
             // Truncate action safely at char boundary
             let action_display = if action.len() > action_max && action_max > 3 {
                 let truncated: String = action.chars().take(action_max.saturating_sub(3)).collect();
@@ -498,6 +529,8 @@ impl ExploreBlock {
             // Action
             buf.set_string(x, y, &action_display, line_style);
 
+            render_agent_row_controls(buf, area.x + area.width - 1, y, running, theme);
+
             // Right border
             buf.set_string(area.x + area.width - 1, y, "│", border_style);
 
@@ -622,8 +655,7 @@ impl StreamBlock for ExploreBlock {
 
         // Calculate fitted width based on content, capped at area.width
         // Agent row: border(2) + spinner(2) + name(9) + div(2) + tools(9) + div(2) + tokens(6) + div(2) + action(~25) + border(1) = ~60
-        let content_width = 68u16;
-        let width = content_width.min(area.width);
+        let width = CONTENT_WIDTH.min(area.width);
 
         // Header (line 0) - skip if clipped
         if clip_top == 0 {
@@ -694,30 +726,52 @@ impl StreamBlock for ExploreBlock {
         &mut self,
         event: &Event,
         area: Rect,
-        _clip: Option<ClipContext>,
+        clip: Option<ClipContext>,
     ) -> EventResult {
         match event {
             Event::Mouse(MouseEvent {
                 kind: MouseEventKind::Down(MouseButton::Left),
+                column,
                 row,
                 ..
             }) => {
-                let y = *row;
+                // Line within the whole block, counting rows scrolled off the top
+                let line = row.saturating_sub(area.y) + clip.map_or(0, |c| c.clip_top);
 
                 // Header click toggles collapse
-                if y == area.y {
+                if line == 0 {
                     self.collapsed = !self.collapsed;
                     return EventResult::Consumed;
                 }
 
-                // Agent click toggles expanded
-                if !self.collapsed && y > area.y && y < area.y + area.height - 2 {
-                    let idx = (y - area.y - 1) as usize;
-                    if idx < self.agent_order.len() {
-                        if let Some(agent) = self.agents.get_mut(&self.agent_order[idx]) {
-                            agent.expanded = !agent.expanded;
-                            return EventResult::Consumed;
+                // Row click toggles expanded; its controls open the transcript or
+                // act on a running agent
+                if !self.collapsed {
+                    if let Some((task_id, true)) = self.agent_at_line(line - 1) {
+                        let task_id = task_id.clone();
+                        let right_border = area.x + CONTENT_WIDTH.min(area.width) - 1;
+                        let Some(agent) = self.agents.get_mut(&task_id) else {
+                            return EventResult::Ignored;
+                        };
+                        let name = agent.name.clone();
+                        if transcript_control_at(*column, right_border) {
+                            return EventResult::Action(BlockEvent::OpenTranscript {
+                                task_id,
+                                name,
+                            });
                         }
+                        let action = (agent.status == AgentProgressStatus::Running)
+                            .then(|| agent_row_control_at(*column, right_border))
+                            .flatten();
+                        if let Some(action) = action {
+                            return EventResult::Action(BlockEvent::AgentAction {
+                                task_id,
+                                name,
+                                action,
+                            });
+                        }
+                        agent.expanded = !agent.expanded;
+                        return EventResult::Consumed;
                     }
                 }
 
@@ -18,7 +18,7 @@ pub mod web_search;
 pub mod write;
 
 use crossterm::event::Event;
-use ratatui::{buffer::Buffer, layout::Rect};
+use ratatui::{buffer::Buffer, layout::Rect, style::Style};
 
 use crate::tui::themes::Theme;
 
@@ -88,6 +88,74 @@ pub enum BlockEvent {
     Pinned(bool),
     /// Toggle global diff display mode (unified <-> side-by-side)
     ToggleDiffMode,
+    /// Open the transcript of a sub-agent (explore/build blocks)
+    OpenTranscript { task_id: String, name: String },
+    /// A control on a running sub-agent's row was clicked (explore/build blocks)
+    AgentAction {
+        task_id: String,
+        name: String,
+        action: AgentRowAction,
+    },
+}
+
+/// Controls shown on a running sub-agent's row
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AgentRowAction {
+    /// Stop this agent only
+    Stop,
+    /// Stop it and run it again with an edited prompt
+    Restart,
+    /// Send it a note for its next turn
+    Note,
+}
+
+/// Row controls, drawn right to left after the transcript control
+const AGENT_ROW_CONTROLS: [(&str, AgentRowAction); 3] = [
+    ("✎", AgentRowAction::Note),
+    ("↻", AgentRowAction::Restart),
+    ("■", AgentRowAction::Stop),
+];
+
+/// Opens the agent's transcript; shown on every row next to the right border
+const TRANSCRIPT_CONTROL: &str = "≡";
+
+/// Columns the controls of a running agent's row take, including the gap before them
+pub(crate) const AGENT_ROW_CONTROLS_WIDTH: usize = 9;
+
+/// Columns the transcript control alone takes, including the gap before it
+pub(crate) const TRANSCRIPT_CONTROL_WIDTH: usize = 3;
+
+/// Draw an agent row's controls ending before `right_border`
+pub(crate) fn render_agent_row_controls(
+    buf: &mut Buffer,
+    right_border: u16,
+    y: u16,
+    running: bool,
+    theme: &Theme,
+) {
+    let style = Style::default().fg(theme.dim_color);
+    buf.set_string(right_border.saturating_sub(2), y, TRANSCRIPT_CONTROL, style);
+    if !running {
+        return;
+    }
+    for (i, (glyph, _)) in AGENT_ROW_CONTROLS.iter().enumerate() {
+        let x = right_border.saturating_sub(2 * (i as u16 + 2));
+        buf.set_string(x, y, *glyph, style);
+    }
+}
+
+/// Whether `column` is the transcript control of a row whose right border is at `right_border`
+pub(crate) fn transcript_control_at(column: u16, right_border: u16) -> bool {
+    right_border.checked_sub(2) == Some(column)
+}
+
+/// The row control at `column`, for a row whose right border is at `right_border`
+pub(crate) fn agent_row_control_at(column: u16, right_border: u16) -> Option<AgentRowAction> {
+    AGENT_ROW_CONTROLS
+        .iter()
+        .enumerate()
+        .find(|(i, _)| right_border.checked_sub(2 * (*i as u16 + 2)) == Some(column))
+        .map(|(_, (_, action))| *action)
 }
 
 /// Simple scrolling for blocks with fixed-line content (no width dependency)
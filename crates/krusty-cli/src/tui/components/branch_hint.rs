@@ -0,0 +1,34 @@
+//! Branch hint - keys for picking and rewriting earlier messages
+
+use ratatui::{
+    layout::Rect,
+    style::Style,
+    text::{Line, Span},
+    widgets::Paragraph,
+    Frame,
+};
+
+use crate::tui::themes::Theme;
+use crate::tui::utils::BranchNav;
+
+/// Height of the hint row: 1 while a message is picked or being rewritten
+pub fn branch_hint_height(branches: &BranchNav) -> u16 {
+    u16::from(branches.selected.is_some() || branches.editing.is_some())
+}
+
+/// Render the keys for the current branch action
+pub fn render_branch_hint(f: &mut Frame, area: Rect, theme: &Theme, branches: &BranchNav) {
+    if area.height == 0 {
+        return;
+    }
+    let hint = if branches.editing.is_some() {
+        "Editing an earlier message · Enter sends it on a new branch · Esc cancels"
+    } else {
+        "↑/↓ message · ←/→ branch · Enter edit · m resend to another model · Esc back"
+    };
+    let line = Line::from(Span::styled(
+        format!(" {}", hint),
+        Style::default().fg(theme.dim_color),
+    ));
+    f.render_widget(Paragraph::new(line), area);
+}
@@ -3,6 +3,8 @@
 //! A unified prompt widget for user decisions:
 //! - Plan confirmation (Execute/Modify/Abandon)
 //! - AskUserQuestion tool (Claude's questions with options)
+//! - MCP sampling approval and elicitation forms
+//! - Plan file sync conflicts (file vs. plan)
 
 use ratatui::{
     buffer::Buffer,
@@ -22,6 +24,14 @@ pub enum PromptType {
     PlanConfirm,
     /// AskUserQuestion tool from Claude
     AskUserQuestion,
+    /// MCP server wants an LLM completion
+    McpSampling,
+    /// MCP server wants structured input
+    McpElicitation,
+    /// Synced plan file and plan both changed
+    PlanSyncConflict,
+    /// Project custom command wants to run shell commands
+    CommandShell,
 }
 
 /// A single option in a question
@@ -150,6 +160,62 @@ impl DecisionPrompt {
         self.visible = true;
     }
 
+    /// Show the choice for a plan file that changed alongside the plan
+    pub fn show_plan_sync_conflict(&mut self, path: &str, plan_version: u64, file_version: u64) {
+        self.questions = vec![PromptQuestion::new(
+            "Plan file changed",
+            format!(
+                "{} was edited from revision {}, but the plan is at revision {}",
+                path, file_version, plan_version
+            ),
+        )
+        .add_option(
+            PromptOption::new("Use file").with_description("Replace the plan with the file"),
+        )
+        .add_option(PromptOption::new("Keep plan").with_description("Overwrite the file"))
+        .add_option(
+            PromptOption::new("Merge").with_description("Add the file's tasks and completions"),
+        )];
+
+        self.current_index = 0;
+        self.selected_option = 0;
+        self.scroll_offset = 0;
+        self.toggled_options.clear();
+        self.answers.clear();
+        self.prompt_type = PromptType::PlanSyncConflict;
+        self.tool_use_id = None;
+        self.custom_input_mode = false;
+        self.visible = true;
+    }
+
+    /// Ask before a project command's `` !`cmd` `` spans run
+    pub fn show_command_shell_confirm(&mut self, name: &str, commands: &[&str]) {
+        self.questions = vec![PromptQuestion::new(
+            "Run project commands?",
+            format!(
+                "/{} from .krusty/commands runs: {}",
+                name,
+                commands.join("; ")
+            ),
+        )
+        .add_option(PromptOption::new("Run").with_description("Run them this time"))
+        .add_option(
+            PromptOption::new("Always run")
+                .with_description("Don't ask again for this command this session"),
+        )
+        .add_option(PromptOption::new("Cancel").with_description("Don't run the command"))];
+
+        self.current_index = 0;
+        self.selected_option = 0;
+        self.scroll_offset = 0;
+        self.toggled_options.clear();
+        self.answers.clear();
+        self.prompt_type = PromptType::CommandShell;
+        self.tool_use_id = None;
+        self.custom_input_mode = false;
+        self.visible = true;
+    }
+
     /// Show AskUserQuestion prompt
     pub fn show_ask_user(&mut self, questions: Vec<PromptQuestion>, tool_use_id: String) {
         self.questions = questions;
@@ -164,6 +230,20 @@ impl DecisionPrompt {
         self.visible = true;
     }
 
+    /// Show questions for an MCP server request
+    pub fn show_mcp_request(&mut self, prompt_type: PromptType, questions: Vec<PromptQuestion>) {
+        self.questions = questions;
+        self.current_index = 0;
+        self.selected_option = 0;
+        self.scroll_offset = 0;
+        self.toggled_options.clear();
+        self.answers.clear();
+        self.prompt_type = prompt_type;
+        self.tool_use_id = None;
+        self.custom_input_mode = false;
+        self.visible = true;
+    }
+
     /// Hide the prompt
     pub fn hide(&mut self) {
         self.visible = false;
@@ -479,6 +559,15 @@ impl DecisionPrompt {
                 }
             } else if self.prompt_type == PromptType::PlanConfirm {
                 "press 1/2, click, or type to modify plan"
+            } else if self.prompt_type == PromptType::McpSampling {
+                "press 1/2 or click (Esc to deny)"
+            } else if self.prompt_type == PromptType::PlanSyncConflict {
+                "press 1/2/3 or click (Esc to decide later)"
+            } else if self.prompt_type == PromptType::CommandShell {
+                "press 1/2/3 or click (Esc to cancel)"
+            } else if self.prompt_type == PromptType::McpElicitation && question.options.is_empty()
+            {
+                "type a response and press Enter (Esc to go back)"
             } else {
                 "type number, click, or enter custom response"
             };
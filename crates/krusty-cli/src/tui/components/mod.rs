@@ -1,18 +1,22 @@
 //! UI components for Krusty TUI
 //!
-//! Reusable rendering components: toolbar, status bar, scrollbars, plan sidebar, plugin window, toasts, etc.
+//! Reusable rendering components: toolbar, status bar, scrollbars, plan sidebar, plugin window, queued prompts, branch hints, toasts, etc.
 
+pub mod branch_hint;
 pub mod decision_prompt;
 pub mod plan_sidebar;
 pub mod plugin_window;
+pub mod prompt_queue;
 pub mod scrollbars;
 pub mod status_bar;
 pub mod toast;
 pub mod toolbar;
 
+pub use branch_hint::{branch_hint_height, render_branch_hint};
 pub use decision_prompt::{DecisionPrompt, PromptAnswer, PromptOption, PromptQuestion, PromptType};
 pub use plan_sidebar::{render_plan_sidebar, PlanSidebarState, MIN_TERMINAL_WIDTH};
 pub use plugin_window::{render_plugin_window, PluginWindowState};
+pub use prompt_queue::{prompt_queue_height, render_prompt_queue};
 pub use scrollbars::{render_input_scrollbar, render_messages_scrollbar};
 pub use status_bar::render_status_bar;
 pub use toast::{render_toasts, Toast, ToastQueue};
@@ -51,6 +51,8 @@ pub struct PlanSidebarState {
     pub total_lines: usize,
     /// Pending plan clear after collapse animation completes
     pending_clear: bool,
+    /// Live status of a `/plan run` in progress, shown under the title
+    pub run_status: Option<String>,
 
     // === Caching fields ===
     /// Cached rendered lines (avoids rebuilding every frame)
@@ -100,6 +102,7 @@ impl PlanSidebarState {
         self.scroll_offset = 0;
         self.total_lines = 0;
         self.pending_clear = false;
+        self.run_status = None;
         // Clear cache
         self.cached_lines.clear();
         self.cached_plan_hash = 0;
@@ -232,7 +235,7 @@ pub fn render_plan_sidebar(
     }
 
     // Check if we need to rebuild the cache
-    let plan_hash = hash_plan(plan);
+    let plan_hash = hash_plan(plan, state.run_status.as_deref());
     let cache_valid =
         state.cached_plan_hash == plan_hash && state.cached_width == wrap_width as u16;
 
@@ -258,6 +261,16 @@ pub fn render_plan_sidebar(
             Style::default().fg(theme.border_color),
         )));
 
+        // Run status line while /plan run is executing
+        if let Some(status) = &state.run_status {
+            for wrapped_line in wrap_text(status, wrap_width) {
+                state.cached_lines.push(Line::from(Span::styled(
+                    wrapped_line,
+                    Style::default().fg(theme.accent_color),
+                )));
+            }
+        }
+
         // Blank line after separator
         state.cached_lines.push(Line::from(""));
 
@@ -390,9 +403,10 @@ fn render_line(buf: &mut Buffer, x: u16, y: u16, max_x: u16, line: &Line) {
 }
 
 /// Compute a hash of the plan content for cache invalidation
-fn hash_plan(plan: &PlanFile) -> u64 {
+fn hash_plan(plan: &PlanFile, run_status: Option<&str>) -> u64 {
     use std::collections::hash_map::DefaultHasher;
     let mut hasher = DefaultHasher::new();
+    run_status.hash(&mut hasher);
     // Hash title and phase count and each phase's content
     plan.title.hash(&mut hasher);
     plan.phases.len().hash(&mut hasher);
@@ -0,0 +1,106 @@
+//! Queued prompt chips - prompts waiting above the input while the agent works
+
+use ratatui::{
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::Paragraph,
+    Frame,
+};
+use unicode_width::UnicodeWidthStr;
+
+use crate::tui::themes::Theme;
+use crate::tui::utils::{truncate_ellipsis, Delivery, PromptQueue};
+
+/// Rows of chips shown before the rest collapse into "+N more"
+const MAX_CHIP_ROWS: usize = 3;
+/// Display width of a chip's prompt preview
+const CHIP_TEXT_WIDTH: usize = 32;
+
+/// Height of the queue area: chip rows plus a hint row (0 when empty)
+pub fn prompt_queue_height(queue: &PromptQueue, width: u16) -> u16 {
+    if queue.is_empty() {
+        return 0;
+    }
+    chip_rows(queue, width, None).len() as u16 + 1
+}
+
+/// Render the queued prompts as chips with a key hint underneath
+pub fn render_prompt_queue(f: &mut Frame, area: Rect, theme: &Theme, queue: &PromptQueue) {
+    if queue.is_empty() || area.height == 0 {
+        return;
+    }
+
+    let mut lines = chip_rows(queue, area.width, Some(theme));
+    let hint = if queue.selected.is_some() {
+        "←/→ select · Tab next turn/inject · Enter edit · Del remove · Esc back"
+    } else if queue.paused {
+        "Queue paused · Enter on an empty input resumes · ↑ to edit · /queue clear"
+    } else {
+        "↑ to edit queued prompts"
+    };
+    lines.push(Line::from(Span::styled(
+        format!(" {}", hint),
+        Style::default().fg(theme.dim_color),
+    )));
+    f.render_widget(Paragraph::new(lines), area);
+}
+
+/// Lay the chips out left to right, wrapping at `width`. Without a theme
+/// the lines are only measured
+fn chip_rows(queue: &PromptQueue, width: u16, theme: Option<&Theme>) -> Vec<Line<'static>> {
+    let width = width as usize;
+    let mut rows: Vec<Line<'static>> = Vec::new();
+    let mut row: Vec<Span<'static>> = vec![Span::raw(" ")];
+    let mut row_width = 1;
+
+    for (i, prompt) in queue.items().iter().enumerate() {
+        let preview = prompt.text.lines().next().unwrap_or("").trim();
+        let text = format!(" {} ", truncate_ellipsis(preview, CHIP_TEXT_WIDTH));
+        let label = format!(" {} ", prompt.delivery.name());
+        let chip_width = label.width() + text.width() + 1;
+
+        if row_width + chip_width > width && row.len() > 1 {
+            if rows.len() + 1 == MAX_CHIP_ROWS {
+                let more = format!("+{} more", queue.items().len() - i);
+                row.push(Span::styled(
+                    more,
+                    theme.map_or_else(Style::default, |t| Style::default().fg(t.dim_color)),
+                ));
+                break;
+            }
+            rows.push(Line::from(std::mem::replace(
+                &mut row,
+                vec![Span::raw(" ")],
+            )));
+            row_width = 1;
+        }
+
+        let (label_style, text_style) = match theme {
+            Some(t) if queue.selected == Some(i) => {
+                let style = Style::default()
+                    .fg(t.bg_color)
+                    .bg(t.accent_color)
+                    .add_modifier(Modifier::BOLD);
+                (style, style)
+            }
+            Some(t) => {
+                let label_fg = match prompt.delivery {
+                    Delivery::NextTurn => t.dim_color,
+                    Delivery::Inject => t.accent_color,
+                };
+                (
+                    Style::default().fg(label_fg).bg(t.user_msg_bg_color),
+                    Style::default().fg(t.text_color).bg(t.user_msg_bg_color),
+                )
+            }
+            None => (Style::default(), Style::default()),
+        };
+        row.push(Span::styled(label, label_style));
+        row.push(Span::styled(text, text_style));
+        row.push(Span::raw(" "));
+        row_width += chip_width;
+    }
+    rows.push(Line::from(row));
+    rows
+}
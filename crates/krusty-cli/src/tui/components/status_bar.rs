@@ -2,7 +2,7 @@
 
 use ratatui::{
     layout::{Alignment, Constraint, Direction, Layout, Rect},
-    style::Style,
+    style::{Modifier, Style},
     text::{Line, Span},
     widgets::Paragraph,
     Frame,
@@ -23,6 +23,7 @@ pub fn render_status_bar(
     context_tokens: Option<(usize, usize)>, // (used, max)
     running_processes: usize,
     process_elapsed: Option<Duration>,
+    edit_mode: Option<&str>, // Vim/Emacs mode label
 ) {
     // Background
     let bg = Paragraph::new("").style(Style::default().bg(theme.status_bar_bg_color));
@@ -32,15 +33,31 @@ pub fn render_status_bar(
     let model_short = shorten_model_name(model);
     let cwd_display = shorten_path(cwd, 30);
 
-    let mut left_spans = vec![
-        Span::raw(" "),
+    let mut left_spans = vec![Span::raw(" ")];
+    let mut left_width: u16 = 1;
+
+    // Editing mode badge (NORMAL, INSERT, EMACS, ...)
+    if let Some(label) = edit_mode {
+        let badge = format!(" {} ", label);
+        left_width += badge.width() as u16 + 1;
+        left_spans.push(Span::styled(
+            badge,
+            Style::default()
+                .fg(theme.bg_color)
+                .bg(theme.accent_color)
+                .add_modifier(Modifier::BOLD),
+        ));
+        left_spans.push(Span::raw(" "));
+    }
+
+    left_spans.extend([
         Span::styled(&cwd_display, Style::default().fg(theme.dim_color)),
         Span::styled(" │ ", Style::default().fg(theme.dim_color)),
         Span::styled(&model_short, Style::default().fg(theme.dim_color)),
-    ];
+    ]);
 
-    // Calculate left width: space + cwd + " │ " + model
-    let mut left_width: u16 = 1 + cwd_display.width() as u16 + 3 + model_short.width() as u16;
+    // Calculate left width: cwd + " │ " + model
+    left_width += cwd_display.width() as u16 + 3 + model_short.width() as u16;
 
     // Add context indicator if available (fixed width to prevent flashing)
     if let Some((used, max)) = context_tokens {
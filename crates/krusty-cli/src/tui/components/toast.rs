@@ -3,6 +3,7 @@
 //! Simple notifications in the top-right corner for:
 //! - Update alerts (new version available, updated successfully)
 //! - Confirmations (copied, saved)
+//! - Warnings (config problems)
 
 use ratatui::{
     buffer::Buffer,
@@ -35,18 +36,22 @@ const TOAST_GAP: u16 = 1;
 pub enum ToastType {
     /// Positive confirmation (copied, saved, updated)
     Success,
+    /// Something needs attention (bad config)
+    Warning,
 }
 
 impl ToastType {
     fn color(&self, theme: &Theme) -> Color {
         match self {
             ToastType::Success => theme.success_color,
+            ToastType::Warning => theme.warning_color,
         }
     }
 
     fn icon(&self) -> &'static str {
         match self {
             ToastType::Success => "✓",
+            ToastType::Warning => "!",
         }
     }
 }
@@ -70,6 +75,11 @@ impl Toast {
         Self::new(message, ToastType::Success)
     }
 
+    /// Create a new warning toast
+    pub fn warning(message: impl Into<String>) -> Self {
+        Self::new(message, ToastType::Warning)
+    }
+
     fn new(message: impl Into<String>, toast_type: ToastType) -> Self {
         Self {
             message: message.into(),
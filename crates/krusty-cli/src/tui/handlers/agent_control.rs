@@ -0,0 +1,115 @@
+//! Sub-agent controls
+//!
+//! Running rows in explore and build blocks have stop, restart and note
+//! controls. Stop acts right away; restart and note switch the input box to
+//! steering that agent until Enter sends or Esc cancels.
+
+use crate::agent::subagent::AgentControls;
+use crate::tui::app::App;
+use crate::tui::blocks::AgentRowAction;
+use crate::tui::utils::{AgentSteer, SteerMode};
+
+impl App {
+    /// Controls for the agents started by one tool call or plan run
+    ///
+    /// They stay registered until the tool result arrives or the plan run
+    /// ends, so agents still waiting for a slot can be steered once they start.
+    pub(crate) fn register_agent_controls(&mut self, run_id: &str) -> AgentControls {
+        let controls = AgentControls::new();
+        self.runtime
+            .agent_controls
+            .insert(run_id.to_string(), controls.clone());
+        controls
+    }
+
+    /// Drop the controls of a tool call or plan run that has finished
+    pub(crate) fn release_agent_controls(&mut self, run_id: &str) {
+        self.runtime.agent_controls.remove(run_id);
+    }
+
+    /// Handle a click on a control of a running agent's row
+    pub(crate) fn handle_agent_action(
+        &mut self,
+        run_id: &str,
+        task_id: &str,
+        name: &str,
+        action: AgentRowAction,
+    ) {
+        let Some(controls) = self
+            .runtime
+            .agent_controls
+            .get(run_id)
+            .filter(|c| c.is_running(task_id))
+            .cloned()
+        else {
+            self.runtime.chat.messages.push((
+                "system".to_string(),
+                format!("{} is no longer running.", name),
+            ));
+            return;
+        };
+
+        let mode = match action {
+            AgentRowAction::Stop => {
+                controls.stop(task_id);
+                self.runtime
+                    .chat
+                    .messages
+                    .push(("system".to_string(), format!("Stopping {}.", name)));
+                return;
+            }
+            AgentRowAction::Restart => SteerMode::Restart,
+            AgentRowAction::Note => SteerMode::Note,
+        };
+
+        // Keep whatever was being typed; it comes back when steering ends
+        let draft = match self.runtime.agent_steer.take() {
+            Some(steer) => steer.draft,
+            None => self.ui.input.content().to_string(),
+        };
+        self.ui.input.clear();
+        self.ui.autocomplete.hide();
+        if mode == SteerMode::Restart {
+            if let Some(prompt) = controls.prompt(task_id) {
+                self.ui.input.insert_text(&prompt);
+            }
+        }
+        self.runtime.agent_steer = Some(AgentSteer {
+            run_id: run_id.to_string(),
+            task_id: task_id.to_string(),
+            name: name.to_string(),
+            mode,
+            draft,
+        });
+    }
+
+    /// Send the input to the steered agent
+    pub(crate) fn submit_agent_steer(&mut self, text: String) {
+        let Some(steer) = self.runtime.agent_steer.clone() else {
+            return;
+        };
+        let sent =
+            self.runtime
+                .agent_controls
+                .get(&steer.run_id)
+                .is_some_and(|controls| match steer.mode {
+                    SteerMode::Note => controls.send_note(&steer.task_id, text),
+                    SteerMode::Restart => controls.restart(&steer.task_id, text),
+                });
+        let msg = match (sent, steer.mode) {
+            (false, _) => format!("{} is no longer running.", steer.name),
+            (true, SteerMode::Note) => format!("Note queued for {}'s next turn.", steer.name),
+            (true, SteerMode::Restart) => format!("Restarting {} with the new prompt.", steer.name),
+        };
+        self.runtime.chat.messages.push(("system".to_string(), msg));
+        self.end_agent_steer();
+    }
+
+    /// Stop steering and put back what was being typed before
+    pub(crate) fn end_agent_steer(&mut self) {
+        if let Some(steer) = self.runtime.agent_steer.take() {
+            self.ui.input.clear();
+            self.ui.input.insert_text(&steer.draft);
+        }
+    }
+}
@@ -0,0 +1,267 @@
+//! Conversation branches
+//!
+//! Editing an earlier user message or resending it to another model forks
+//! the session before that message, so the original reply stays one ←/→
+//! away. Messages with more than one version are labelled `‹ 2/3 ›`.
+
+use crossterm::event::KeyCode;
+
+use crate::ai::types::{Content, Role};
+use crate::tui::app::App;
+use crate::tui::components::Toast;
+use crate::tui::input::Action;
+use crate::tui::utils::{BranchLabel, UserMessage};
+
+impl App {
+    /// Recompute the user messages and branch labels of the loaded session
+    pub(crate) fn refresh_branches(&mut self) {
+        self.runtime.branches.reset();
+
+        let user_turns: Vec<usize> = self
+            .runtime
+            .chat
+            .conversation
+            .iter()
+            .enumerate()
+            .filter(|(_, msg)| {
+                msg.role == Role::User
+                    && !msg
+                        .content
+                        .iter()
+                        .any(|c| matches!(c, Content::ToolResult { .. }))
+            })
+            .map(|(idx, _)| idx)
+            .collect();
+        let messages: Vec<UserMessage> = user_turns
+            .into_iter()
+            .filter_map(|conv_idx| {
+                let display_idx = self.display_index_for_message(conv_idx)?;
+                (self.runtime.chat.messages[display_idx].0 == "user").then_some(UserMessage {
+                    display_idx,
+                    conv_idx,
+                })
+            })
+            .collect();
+
+        let points = match (
+            &self.services.session_manager,
+            &self.runtime.current_session_id,
+        ) {
+            (Some(sm), Some(id)) => sm.branch_points(id).unwrap_or_else(|e| {
+                tracing::warn!("Failed to load branches: {}", e);
+                Vec::new()
+            }),
+            _ => Vec::new(),
+        };
+        let labels = points
+            .into_iter()
+            .filter_map(|point| {
+                let message = messages
+                    .iter()
+                    .find(|m| m.conv_idx == point.message_index)?;
+                Some(BranchLabel {
+                    display_idx: message.display_idx,
+                    current: point.current + 1,
+                    total: point.sessions.len(),
+                    model: point.models.get(point.current).cloned().flatten(),
+                })
+            })
+            .collect();
+
+        self.runtime.branches.messages = messages;
+        self.runtime.branches.labels = labels;
+    }
+
+    /// Keys for picking an earlier message. Returns true if the key was used
+    pub(crate) fn handle_branch_key(&mut self, code: KeyCode, action: Option<Action>) -> bool {
+        // Esc while rewriting a message drops the edit, not the conversation
+        if self.runtime.branches.editing.is_some()
+            && action == Some(Action::Interrupt)
+            && !self.ui.autocomplete.visible
+            && !self.is_busy()
+        {
+            self.runtime.branches.editing = None;
+            self.ui.input.clear();
+            return true;
+        }
+
+        if self.runtime.branches.selected.is_none() {
+            if action != Some(Action::SelectMessage)
+                || self.is_busy()
+                || self.runtime.current_session_id.is_none()
+            {
+                return false;
+            }
+            self.refresh_branches();
+            if self.runtime.branches.select_last() {
+                self.scroll_to_selected_message();
+            }
+            return true;
+        }
+
+        match code {
+            KeyCode::Up | KeyCode::Char('k') => self.runtime.branches.select_prev(),
+            KeyCode::Down | KeyCode::Char('j') => self.runtime.branches.select_next(),
+            KeyCode::Left | KeyCode::Char('h') => {
+                self.switch_branch(false);
+                return true;
+            }
+            KeyCode::Right | KeyCode::Char('l') => {
+                self.switch_branch(true);
+                return true;
+            }
+            KeyCode::Enter | KeyCode::Char('e') => {
+                self.edit_selected_message();
+                return true;
+            }
+            KeyCode::Char('m') => {
+                if let Some(message) = self.runtime.branches.selected_message() {
+                    self.runtime.branches.selected = None;
+                    self.runtime.branches.resend = Some(message.conv_idx);
+                    self.handle_slash_command("/model");
+                }
+                return true;
+            }
+            KeyCode::Esc => self.runtime.branches.selected = None,
+            _ if action == Some(Action::SelectMessage) => self.runtime.branches.selected = None,
+            // Anything else goes back to typing in the input
+            _ => {
+                self.runtime.branches.selected = None;
+                return false;
+            }
+        }
+        self.scroll_to_selected_message();
+        true
+    }
+
+    fn scroll_to_selected_message(&mut self) {
+        if let Some(message) = self.runtime.branches.selected_message() {
+            self.ui
+                .scroll_system
+                .scroll
+                .request_scroll_to_message(message.display_idx);
+        }
+    }
+
+    /// Load the previous or next version of the selected message
+    fn switch_branch(&mut self, forward: bool) {
+        let (Some(message), Some(session_id)) = (
+            self.runtime.branches.selected_message(),
+            self.runtime.current_session_id.clone(),
+        ) else {
+            return;
+        };
+        let Some(sm) = &self.services.session_manager else {
+            return;
+        };
+        let siblings = sm.branch_siblings(&session_id, message.conv_idx);
+        let siblings = match siblings {
+            Ok(siblings) => siblings,
+            Err(e) => {
+                tracing::warn!("Failed to load branches: {}", e);
+                return;
+            }
+        };
+        let target = if forward {
+            siblings.current + 1
+        } else {
+            match siblings.current.checked_sub(1) {
+                Some(target) => target,
+                None => return,
+            }
+        };
+        let Some(target_id) = siblings.sessions.get(target).cloned() else {
+            return;
+        };
+
+        self.save_block_ui_states();
+        if let Err(e) = self.load_session(&target_id) {
+            self.show_toast(Toast::warning(format!("Failed to load branch: {}", e)));
+            return;
+        }
+        self.runtime
+            .branches
+            .select_conversation_index(message.conv_idx);
+        self.scroll_to_selected_message();
+    }
+
+    /// Take the selected message back into the input; sending it branches
+    fn edit_selected_message(&mut self) {
+        let Some(message) = self.runtime.branches.selected_message() else {
+            return;
+        };
+        if !self.ui.input.content().is_empty() {
+            self.show_toast(Toast::warning(
+                "Send or clear the input to edit an earlier message",
+            ));
+            return;
+        }
+        let text = self.runtime.chat.messages[message.display_idx].1.clone();
+        self.runtime.branches.selected = None;
+        self.runtime.branches.editing = Some(message.conv_idx);
+        self.ui.input.insert_text(&text);
+    }
+
+    /// Send `text` in place of message `at` on a new branch
+    pub(crate) fn submit_edited_message(&mut self, at: usize, text: String) {
+        if self.fork_before(at, None) {
+            self.submit_user_message(text, None);
+            self.refresh_branches();
+        }
+    }
+
+    /// Resend the message picked with `m` to the model just selected
+    pub(crate) fn resend_to_current_model(&mut self) {
+        let Some(at) = self.runtime.branches.resend.take() else {
+            return;
+        };
+        let Some(message) = self
+            .runtime
+            .branches
+            .messages
+            .iter()
+            .find(|m| m.conv_idx == at)
+            .copied()
+        else {
+            return;
+        };
+        if self.is_busy() {
+            self.show_toast(Toast::warning(
+                "Wait for the current response before resending",
+            ));
+            return;
+        }
+        let text = self.runtime.chat.messages[message.display_idx].1.clone();
+        let model = self.runtime.current_model.clone();
+        if self.fork_before(at, Some(&model)) {
+            self.submit_user_message(text, None);
+            self.refresh_branches();
+        }
+    }
+
+    /// Fork the current session before message `at` and switch to the fork
+    fn fork_before(&mut self, at: usize, model: Option<&str>) -> bool {
+        let (Some(sm), Some(session_id)) = (
+            &self.services.session_manager,
+            &self.runtime.current_session_id,
+        ) else {
+            return false;
+        };
+        let model = model.unwrap_or(&self.runtime.current_model);
+        let forked = sm.fork_session(session_id, at, Some(model));
+        let fork_id = match forked {
+            Ok(id) => id,
+            Err(e) => {
+                self.show_toast(Toast::warning(format!("Failed to branch: {}", e)));
+                return false;
+            }
+        };
+
+        self.save_block_ui_states();
+        if let Err(e) = self.load_session(&fork_id) {
+            self.show_toast(Toast::warning(format!("Failed to load branch: {}", e)));
+            return false;
+        }
+        true
+    }
+}
@@ -2,7 +2,10 @@
 //!
 //! Handles /command parsing and execution.
 
+use crate::agent::custom::AgentSource;
 use crate::tui::app::{App, Popup, View};
+use crate::tui::input::Action;
+use crate::tui::utils::DocsStatusUpdate;
 
 impl App {
     /// Handle slash commands
@@ -16,6 +19,8 @@ impl App {
                 self.runtime.chat.messages.clear();
                 self.runtime.chat.streaming_assistant_idx = None;
                 self.runtime.chat.conversation.clear();
+                self.runtime.prompt_queue.clear();
+                self.runtime.branches.reset();
                 self.clear_plan();
                 self.ui.view = View::StartMenu;
             }
@@ -38,6 +43,11 @@ impl App {
                 self.ui.popups.session.set_sessions(sessions);
                 self.ui.popup = Popup::SessionList;
             }
+            "/search" => {
+                let query = parts.get(1..).unwrap_or_default().join(" ");
+                self.open_session_search(&query);
+            }
+            "/changes" => self.open_change_review(),
             "/model" => {
                 // Populate model list from registry (non-blocking)
                 let configured = self.configured_providers();
@@ -61,6 +71,7 @@ impl App {
                     self.ui.popups.model.set_models(recent_models, models_vec);
                 }
 
+                self.ui.popups.model.role = crate::ai::routing::ModelRole::Main;
                 self.ui.popup = Popup::ModelSelect;
 
                 // If OpenRouter is configured but has no models, trigger fetch
@@ -86,8 +97,21 @@ impl App {
                 self.ui.popup = Popup::ProcessList;
             }
             "/theme" => {
-                self.ui.popups.theme.open(&self.ui.theme_name);
-                self.ui.popup = Popup::ThemeSelect;
+                if parts.get(1) == Some(&"import") {
+                    let force = parts.get(2) == Some(&"--force");
+                    let path_start = if force { 3 } else { 2 };
+                    match parts.get(path_start..).map(|p| p.join(" ")) {
+                        Some(path) if !path.is_empty() => self.handle_theme_import(&path, force),
+                        _ => self.runtime.chat.messages.push((
+                            "system".to_string(),
+                            "Usage: /theme import [--force] <file.yaml|file.json|file.toml>"
+                                .to_string(),
+                        )),
+                    }
+                } else {
+                    self.ui.popups.theme.open(&self.ui.theme_name);
+                    self.ui.popup = Popup::ThemeSelect;
+                }
             }
             "/clear" => {
                 self.runtime.chat.messages.clear();
@@ -95,6 +119,8 @@ impl App {
                 self.runtime.blocks = crate::tui::state::BlockManager::new();
             }
             "/cmd" => self.ui.popup = Popup::Help,
+            "/editmode" => self.handle_edit_mode_command(parts.get(1).copied()),
+            "/queue" => self.handle_queue_command(parts.get(1).copied()),
             "/init" => {
                 self.handle_init_command();
             }
@@ -105,14 +131,23 @@ impl App {
                 self.handle_terminal_command(parts.get(1).copied());
             }
             "/plan" => {
-                self.handle_plan_command(parts.get(1).copied());
+                self.handle_plan_command(parts.get(1).copied(), parts.get(2).copied());
             }
             "/skills" => {
                 self.open_skills_browser();
             }
+            "/agents" => {
+                self.handle_agents_command();
+            }
+            "/commands" => {
+                self.handle_commands_command();
+            }
             "/mcp" => {
                 self.open_mcp_browser();
             }
+            "/docs" => {
+                self.open_docs_browser();
+            }
             "/hooks" => {
                 self.open_hooks_popup();
             }
@@ -120,10 +155,12 @@ impl App {
                 self.start_update_check();
             }
             _ => {
-                self.runtime
-                    .chat
-                    .messages
-                    .push(("system".to_string(), format!("Unknown command: {}", cmd)));
+                if !self.run_custom_command(cmd) {
+                    self.runtime
+                        .chat
+                        .messages
+                        .push(("system".to_string(), format!("Unknown command: {}", cmd)));
+                }
             }
         }
     }
@@ -516,10 +553,26 @@ impl App {
     }
 
     /// Handle /plan command
-    fn handle_plan_command(&mut self, subcommand: Option<&str>) {
+    fn handle_plan_command(&mut self, subcommand: Option<&str>, arg: Option<&str>) {
         use crate::plan::PlanStatus;
 
         match subcommand {
+            Some("run") => {
+                self.start_plan_run(arg);
+            }
+            Some("stop") => {
+                let msg = match self.runtime.plan_run_cancel {
+                    Some(ref cancel) => {
+                        cancel.cancel();
+                        "Stopping plan run after the running builders wind down..."
+                    }
+                    None => "No plan run in progress.",
+                };
+                self.runtime
+                    .chat
+                    .messages
+                    .push(("system".to_string(), msg.to_string()));
+            }
             Some("clear") | Some("abandon") => {
                 if let Some(ref mut plan) = self.runtime.active_plan {
                     // Mark as abandoned and save
@@ -583,15 +636,33 @@ impl App {
                     }
                 }
             }
+            Some("revisions") => {
+                self.show_plan_revisions();
+            }
+            Some("diff") => {
+                self.show_plan_diff(arg);
+            }
+            Some("restore") => {
+                self.restore_plan_revision(arg);
+            }
+            Some("sync") => {
+                self.handle_plan_sync_command(arg);
+            }
             Some("show") | None => {
                 if let Some(ref plan) = self.runtime.active_plan {
                     let (completed, total) = plan.progress();
+                    let version = plan
+                        .session_id
+                        .as_deref()
+                        .and_then(|id| self.services.plan_manager.get_plan(id).ok().flatten())
+                        .map(|p| p.version)
+                        .unwrap_or(plan.version);
                     let status_icon = if completed == total { "✓" } else { "◐" };
                     self.runtime.chat.messages.push((
                         "system".to_string(),
                         format!(
-                            "{} '{}' ({}/{} tasks)\nUse Ctrl+T to toggle sidebar, /plan clear to abandon.",
-                            status_icon, plan.title, completed, total
+                            "{} '{}' ({}/{} tasks, revision {})\nUse Ctrl+T to toggle sidebar, /plan revisions for history, /plan clear to abandon.",
+                            status_icon, plan.title, completed, total, version
                         ),
                     ));
                     // Show sidebar if not visible
@@ -601,10 +672,12 @@ impl App {
                 } else {
                     self.runtime.chat.messages.push((
                         "system".to_string(),
-                        "No active plan.\n\
-                        • Enter PLAN mode (Ctrl+B) and ask the AI to create a plan\n\
-                        • Use /plan list to see completed plans"
-                            .to_string(),
+                        format!(
+                            "No active plan.\n\
+                            • Enter PLAN mode{} and ask the AI to create a plan\n\
+                            • Use /plan list to see completed plans",
+                            self.global_key_hint(Action::ToggleWorkMode)
+                        ),
                     ));
                 }
             }
@@ -612,7 +685,7 @@ impl App {
                 self.runtime.chat.messages.push((
                     "system".to_string(),
                     format!(
-                        "Unknown: /plan {}. Use: /plan, /plan list, /plan clear",
+                        "Unknown: /plan {}. Use: /plan, /plan run [N], /plan stop, /plan list, /plan revisions, /plan diff [N], /plan restore N, /plan sync [path|off], /plan clear",
                         unknown
                     ),
                 ));
@@ -620,6 +693,267 @@ impl App {
         }
     }
 
+    /// List saved revisions of the active plan
+    ///
+    /// Remembers the current version so `/plan restore` can refuse when the
+    /// plan changed after the list was shown.
+    fn show_plan_revisions(&mut self) {
+        let Some(session_id) = self.active_plan_session() else {
+            return;
+        };
+        let msg = match self.services.plan_manager.list_revisions(&session_id) {
+            Ok(revisions) if revisions.is_empty() => "No plan revisions saved yet.".to_string(),
+            Ok(revisions) => {
+                self.runtime.plan_revision_seen = Some(revisions[0].version);
+                let mut msg = String::from("Plan revisions (newest first):\n");
+                for revision in revisions.iter().take(10) {
+                    let date = chrono::DateTime::parse_from_rfc3339(&revision.created_at)
+                        .map(|dt| {
+                            dt.with_timezone(&chrono::Local)
+                                .format("%Y-%m-%d %H:%M")
+                                .to_string()
+                        })
+                        .unwrap_or_else(|_| revision.created_at.clone());
+                    msg.push_str(&format!(
+                        "  {:>3}. {} [{}] - {}\n",
+                        revision.version, revision.title, revision.status, date
+                    ));
+                }
+                if revisions.len() > 10 {
+                    msg.push_str(&format!("  ... and {} older\n", revisions.len() - 10));
+                }
+                msg.push_str("Use /plan diff N to see a change, /plan restore N to roll back.");
+                msg
+            }
+            Err(e) => format!("Failed to list plan revisions: {}", e),
+        };
+        self.runtime.chat.messages.push(("system".to_string(), msg));
+    }
+
+    /// Show what a revision changed (latest revision by default)
+    fn show_plan_diff(&mut self, version: Option<&str>) {
+        let Some(session_id) = self.active_plan_session() else {
+            return;
+        };
+        let version = match version.map(str::parse::<u64>) {
+            // The in-memory plan's version is not bumped on save
+            None => self
+                .services
+                .plan_manager
+                .get_plan(&session_id)
+                .ok()
+                .flatten()
+                .map(|p| p.version)
+                .unwrap_or(0),
+            Some(Ok(n)) => n,
+            Some(Err(_)) => {
+                self.runtime.chat.messages.push((
+                    "system".to_string(),
+                    "Usage: /plan diff [N] - N is a revision number".to_string(),
+                ));
+                return;
+            }
+        };
+        let msg = match self
+            .services
+            .plan_manager
+            .revision_diff(&session_id, version)
+        {
+            Ok(Some(diff)) if diff.trim().is_empty() => {
+                format!("Revision {} has no changes.", version)
+            }
+            Ok(Some(diff)) => format!("Revision {}:\n```diff\n{}```", version, diff),
+            Ok(None) => format!("No revision {}. Use /plan revisions to list them.", version),
+            Err(e) => format!("Failed to diff plan revision: {}", e),
+        };
+        self.runtime.chat.messages.push(("system".to_string(), msg));
+    }
+
+    /// Roll the active plan back to a revision
+    ///
+    /// Goes through the manager's version check against the version seen in
+    /// the last `/plan revisions` listing.
+    fn restore_plan_revision(&mut self, version: Option<&str>) {
+        let Some(version) = version.and_then(|v| v.parse::<u64>().ok()) else {
+            self.runtime.chat.messages.push((
+                "system".to_string(),
+                "Usage: /plan restore N - see /plan revisions for numbers".to_string(),
+            ));
+            return;
+        };
+        if self.runtime.plan_run_id.is_some() {
+            self.runtime.chat.messages.push((
+                "system".to_string(),
+                "A plan run is in progress. Use /plan stop first.".to_string(),
+            ));
+            return;
+        }
+        let Some(session_id) = self.active_plan_session() else {
+            return;
+        };
+        let Some(expected) = self.runtime.plan_revision_seen else {
+            self.runtime.chat.messages.push((
+                "system".to_string(),
+                "Use /plan revisions before restoring a revision.".to_string(),
+            ));
+            return;
+        };
+
+        let msg = match self
+            .services
+            .plan_manager
+            .restore_revision(&session_id, version, expected)
+        {
+            Ok(plan) => {
+                let msg = format!(
+                    "Restored plan '{}' to revision {} (saved as revision {}).",
+                    plan.title, version, plan.version
+                );
+                self.runtime.plan_revision_seen = Some(plan.version);
+                self.runtime.active_plan = Some(plan);
+                msg
+            }
+            Err(e) => format!(
+                "Failed to restore revision {}: {}. Run /plan revisions to refresh.",
+                version, e
+            ),
+        };
+        self.runtime.chat.messages.push(("system".to_string(), msg));
+    }
+
+    /// Session id of the active plan, or a "no plan" message and `None`
+    fn active_plan_session(&mut self) -> Option<String> {
+        let session_id = self
+            .runtime
+            .active_plan
+            .as_ref()
+            .and_then(|p| p.session_id.clone());
+        if session_id.is_none() {
+            self.runtime
+                .chat
+                .messages
+                .push(("system".to_string(), "No active plan.".to_string()));
+        }
+        session_id
+    }
+
+    /// Run the active plan's ready tasks through builder agents
+    ///
+    /// `/plan run [N]` keeps up to N builders (default 3) working until the
+    /// plan is done, a task fails, or `/plan stop` is used. Task events and
+    /// builder progress are picked up by `poll_plan_run`.
+    fn start_plan_run(&mut self, max_parallel: Option<&str>) {
+        use crate::agent::AgentCancellation;
+        use crate::plan::{PlanRunner, DEFAULT_MAX_PARALLEL};
+        use crate::tui::app::WorkMode;
+        use std::sync::Arc;
+
+        let max_parallel = match max_parallel.map(str::parse::<usize>) {
+            None => DEFAULT_MAX_PARALLEL,
+            Some(Ok(n)) if n > 0 => n,
+            _ => {
+                self.runtime.chat.messages.push((
+                    "system".to_string(),
+                    "Usage: /plan run [max parallel builders]".to_string(),
+                ));
+                return;
+            }
+        };
+
+        let refusal = if self.runtime.plan_run_id.is_some() {
+            Some("A plan run is already in progress. Use /plan stop to cancel it.".to_string())
+        } else if self.is_busy() {
+            Some("Wait for the current response to finish before running the plan.".to_string())
+        } else if self.ui.work_mode == WorkMode::Plan {
+            Some(format!(
+                "Approve the plan first: switch to BUILD mode{}, then /plan run.",
+                self.global_key_hint(Action::ToggleWorkMode)
+            ))
+        } else {
+            match self.runtime.active_plan {
+                None => Some("No active plan to run.".to_string()),
+                Some(ref plan) if plan.is_complete() => {
+                    Some("All tasks in the plan are already complete.".to_string())
+                }
+                Some(_) => None,
+            }
+        };
+        if let Some(msg) = refusal {
+            self.runtime.chat.messages.push(("system".to_string(), msg));
+            return;
+        }
+
+        // Builders follow the build route
+        let client = match self.create_role_client(crate::ai::routing::ModelRole::Build) {
+            Some(c) => Arc::new(c),
+            None => {
+                self.runtime.chat.messages.push((
+                    "system".to_string(),
+                    "Failed to create AI client for the plan run".to_string(),
+                ));
+                return;
+            }
+        };
+        let Some(plan) = self.runtime.active_plan.clone() else {
+            return;
+        };
+
+        // Builders show up in a build block like a build tool call
+        let run_id = format!("plan-run-{}", uuid::Uuid::new_v4());
+
+        let cancellation = AgentCancellation::new();
+        let controls = self.register_agent_controls(&run_id);
+        let runner = PlanRunner::new(
+            client,
+            cancellation.clone(),
+            self.services.tool_registry.clone(),
+            self.runtime.working_dir.clone(),
+        )
+        .with_max_parallel(max_parallel)
+        .with_model(Some(
+            self.routed_model(crate::ai::routing::ModelRole::Build)
+                .unwrap_or_else(|| self.runtime.current_model.clone()),
+        ))
+        .with_transcript(self.transcript_sink(&run_id))
+        .with_file_changes(self.file_change_sink())
+        .with_controls(Some(controls));
+
+        let (events_tx, events_rx) = tokio::sync::mpsc::unbounded_channel();
+        let (progress_tx, progress_rx) = tokio::sync::mpsc::unbounded_channel();
+        self.runtime.channels.plan_run = Some(events_rx);
+        self.runtime.channels.plan_run_progress = Some(progress_rx);
+
+        self.runtime
+            .blocks
+            .build
+            .push(crate::tui::blocks::BuildBlock::with_tool_id(
+                format!("Running plan '{}'", plan.title),
+                run_id.clone(),
+            ));
+        self.runtime
+            .chat
+            .messages
+            .push(("build".to_string(), run_id.clone()));
+        self.runtime.plan_run_id = Some(run_id);
+        self.runtime.plan_run_cancel = Some(cancellation);
+
+        let (completed, total) = plan.progress();
+        self.ui.plan_sidebar.run_status = Some(format!(
+            "▶ Running · 0 active · {}/{} done",
+            completed, total
+        ));
+        if !self.ui.plan_sidebar.visible {
+            self.ui.plan_sidebar.toggle();
+        }
+        if self.ui.scroll_system.scroll.auto_scroll {
+            self.ui.scroll_system.scroll.request_scroll_to_bottom();
+        }
+
+        tokio::spawn(async move {
+            runner.run(plan, events_tx, progress_tx).await;
+        });
+    }
+
     /// Open skills browser popup
     fn open_skills_browser(&mut self) {
         // Load skills and populate popup
@@ -650,6 +984,73 @@ impl App {
         self.ui.popups.skills.set_skills(skills);
     }
 
+    /// Reload custom agents and list them
+    fn handle_agents_command(&mut self) {
+        let agents_manager = self.services.agents_manager.clone();
+
+        // Re-register delegate so its description and schema list the current agents
+        if let Some(client) = self.create_ai_client() {
+            let registry = self.services.tool_registry.clone();
+            futures::executor::block_on(crate::tools::register_delegate_tool(
+                &registry,
+                std::sync::Arc::new(client),
+                self.runtime.cancellation.clone(),
+                agents_manager.clone(),
+            ));
+            self.services.cached_ai_tools = futures::executor::block_on(registry.get_ai_tools());
+        }
+
+        let (agents, global_dir) = match agents_manager.try_write() {
+            Ok(mut guard) => {
+                guard.refresh();
+                (guard.list_agents(), guard.global_dir().clone())
+            }
+            Err(_) => {
+                self.runtime.chat.messages.push((
+                    "system".to_string(),
+                    "Agents manager is busy, try again.".to_string(),
+                ));
+                return;
+            }
+        };
+
+        let message = if agents.is_empty() {
+            format!(
+                "No custom agents. Add markdown files to {} or .krusty/agents/ \
+                 (frontmatter: description, tools, model, max_turns, timeout).",
+                global_dir.display()
+            )
+        } else {
+            let lines: Vec<String> = agents
+                .iter()
+                .map(|a| {
+                    let source = match a.source {
+                        AgentSource::Global => "global",
+                        AgentSource::Project => "project",
+                    };
+                    format!(
+                        "• {} [{}] - {}\n  tools: {} · model: {} · {} turns · {}s",
+                        a.name,
+                        source,
+                        a.description,
+                        a.tools.join(", "),
+                        a.model.as_deref().unwrap_or("current"),
+                        a.max_turns,
+                        a.timeout_secs
+                    )
+                })
+                .collect();
+            format!(
+                "Custom agents (run with the delegate tool):\n{}",
+                lines.join("\n")
+            )
+        };
+        self.runtime
+            .chat
+            .messages
+            .push(("system".to_string(), message));
+    }
+
     /// Open MCP server browser popup
     fn open_mcp_browser(&mut self) {
         // Update the popup with current server state
@@ -664,6 +1065,35 @@ impl App {
         self.ui.popups.mcp.update(servers);
     }
 
+    /// Open docs browser popup and ask providers for package suggestions
+    fn open_docs_browser(&mut self) {
+        let docs = self.services.docs_manager.clone();
+        let providers = docs.providers();
+        let indexed = docs.indexed_packages().unwrap_or_else(|e| {
+            tracing::warn!("Failed to list indexed docs: {}", e);
+            Vec::new()
+        });
+        self.ui.popups.docs.set_packages(providers.clone(), indexed);
+        self.ui.popup = Popup::DocsBrowser;
+
+        for provider in providers {
+            let docs = docs.clone();
+            let status_tx = self.services.docs_status_tx.clone();
+            tokio::spawn(async move {
+                let update = match docs.suggest_packages(&provider).await {
+                    Ok(packages) => DocsStatusUpdate::Suggestions {
+                        provider: provider.name,
+                        packages,
+                    },
+                    Err(e) => DocsStatusUpdate::Failed {
+                        message: format!("{}: {:#}", provider.name, e),
+                    },
+                };
+                let _ = status_tx.send(update);
+            });
+        }
+    }
+
     /// Open hooks configuration popup
     fn open_hooks_popup(&mut self) {
         let hooks: Vec<_> = futures::executor::block_on(async {
@@ -0,0 +1,75 @@
+//! In-place compaction of the main conversation
+//!
+//! Before each request the conversation is compacted like a sub-agent's
+//! once it nears the model's context window. The request goes out with the
+//! compacted messages, and they replace the older part of the stored
+//! conversation when the update arrives here. Thresholds are re-read from
+//! `~/.krusty/compaction.toml` for each request.
+
+use crate::agent::{CompactionConfig, CompactionThresholds, Compactor};
+use crate::ai::client::AiClient;
+use crate::tui::app::App;
+
+impl App {
+    /// Compactor for `client`'s model, keeping the one used on earlier turns
+    pub(crate) fn take_compactor(&mut self, client: &AiClient) -> Compactor {
+        let config = CompactionConfig::for_model_with(
+            client.provider_id(),
+            &client.config().model,
+            &CompactionThresholds::load(),
+        );
+        match self.runtime.compactor.take() {
+            Some(compactor) if *compactor.config() == config => compactor,
+            _ => Compactor::new(config),
+        }
+    }
+
+    /// Swap in the conversation compacted before the current request
+    pub(crate) fn poll_compaction(&mut self) {
+        let Some(ref mut rx) = self.runtime.channels.compaction else {
+            return;
+        };
+        let update = match rx.try_recv() {
+            Ok(update) => update,
+            Err(tokio::sync::oneshot::error::TryRecvError::Empty) => return,
+            Err(tokio::sync::oneshot::error::TryRecvError::Closed) => {
+                self.runtime.channels.compaction = None;
+                return;
+            }
+        };
+        self.runtime.channels.compaction = None;
+        self.runtime.compactor = Some(update.compactor);
+
+        let (messages, report) = match update.result {
+            Ok(Some(compacted)) => compacted,
+            Ok(None) => return,
+            Err(e) => {
+                tracing::warn!("Context compaction failed: {}", e);
+                return;
+            }
+        };
+        // The session changed or was cut short since the request was sent
+        let conversation = &mut self.runtime.chat.conversation;
+        if update.session_id != self.runtime.current_session_id
+            || conversation.len() < update.replaces
+        {
+            return;
+        }
+        let newer = conversation.split_off(update.replaces);
+        *conversation = messages;
+        conversation.extend(newer);
+
+        if report.messages_summarized > 0 {
+            self.runtime.chat.messages.push((
+                "system".to_string(),
+                format!(
+                    "Compacted {} earlier messages into a summary (~{}k → ~{}k tokens).",
+                    report.messages_summarized,
+                    report.tokens_before / 1000,
+                    report.tokens_after / 1000
+                ),
+            ));
+            self.ui.needs_redraw = true;
+        }
+    }
+}
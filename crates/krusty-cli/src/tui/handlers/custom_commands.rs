@@ -0,0 +1,222 @@
+//! Custom slash commands
+//!
+//! Runs commands defined in `.krusty/commands/*.md` and
+//! `~/.krusty/commands/*.md`. Expansion can run shell commands, so it
+//! happens in the background and the prompt is sent once it's ready.
+//! Shell commands of project commands run only after the user agrees.
+
+use krusty_core::commands::{CommandDefinition, CommandSource};
+
+use crate::tools::ToolContext;
+use crate::tui::app::App;
+use crate::tui::components::PromptAnswer;
+use crate::tui::utils::CustomCommandUpdate;
+
+impl App {
+    /// Run `/name args` if it names a custom command. Returns false otherwise
+    pub(crate) fn run_custom_command(&mut self, cmd: &str) -> bool {
+        let rest = cmd.trim().trim_start_matches('/');
+        let (name, arguments) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+        let Some(command) = self
+            .services
+            .commands_manager
+            .get_command(&name.to_lowercase())
+            .cloned()
+        else {
+            return false;
+        };
+
+        if self.runtime.channels.custom_command.is_some() {
+            self.runtime.chat.messages.push((
+                "system".to_string(),
+                "Another command is still being prepared.".to_string(),
+            ));
+            return true;
+        }
+
+        let invocation = cmd.trim().to_string();
+        let approval = (command.path.clone(), command.template.clone());
+        if command.needs_shell_confirmation()
+            && !self.runtime.approved_command_shells.contains(&approval)
+        {
+            if self.ui.decision_prompt.visible {
+                self.runtime.chat.messages.push((
+                    "system".to_string(),
+                    "Answer the open prompt first.".to_string(),
+                ));
+                return true;
+            }
+            let commands = command.shell_commands();
+            let list: Vec<String> = commands.iter().map(|c| format!("  {}", c)).collect();
+            self.runtime.chat.messages.push((
+                "system".to_string(),
+                format!(
+                    "/{} ({}) wants to run:\n{}",
+                    command.name,
+                    command.path.display(),
+                    list.join("\n")
+                ),
+            ));
+            self.ui
+                .decision_prompt
+                .show_command_shell_confirm(&command.name, &commands);
+            self.runtime.pending_command_shell = Some((command, invocation));
+            self.ui.needs_redraw = true;
+            return true;
+        }
+
+        self.expand_custom_command(command, invocation, arguments.to_string());
+        true
+    }
+
+    /// Run or drop the project command waiting on its shell confirmation
+    pub(crate) fn handle_command_shell_answer(&mut self, answers: &[PromptAnswer]) {
+        let Some((command, invocation)) = self.runtime.pending_command_shell.take() else {
+            return;
+        };
+        match answers.first() {
+            Some(PromptAnswer::Selected(0)) => {}
+            Some(PromptAnswer::Selected(1)) => {
+                self.runtime
+                    .approved_command_shells
+                    .insert((command.path.clone(), command.template.clone()));
+            }
+            _ => {
+                self.runtime.chat.messages.push((
+                    "system".to_string(),
+                    format!("/{} cancelled.", command.name),
+                ));
+                return;
+            }
+        }
+        let rest = invocation.trim_start_matches('/');
+        let arguments = rest
+            .split_once(char::is_whitespace)
+            .map(|(_, arguments)| arguments.to_string())
+            .unwrap_or_default();
+        self.expand_custom_command(command, invocation, arguments);
+    }
+
+    /// Expand `command` in the background and queue the result
+    fn expand_custom_command(
+        &mut self,
+        command: CommandDefinition,
+        invocation: String,
+        arguments: String,
+    ) {
+        if self.runtime.channels.custom_command.is_some() {
+            self.runtime.chat.messages.push((
+                "system".to_string(),
+                "Another command is still being prepared.".to_string(),
+            ));
+            return;
+        }
+
+        tracing::info!(command = %command.name, "Expanding custom command");
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        // Shell commands run through the bash tool so its hooks can block them
+        let tool_registry = self.services.tool_registry.clone();
+        let ctx = ToolContext::with_process_registry(
+            self.runtime.working_dir.clone(),
+            self.runtime.process_registry.clone(),
+        );
+        tokio::spawn(async move {
+            let result = command
+                .expand(&arguments, &tool_registry, &ctx)
+                .await
+                .map_err(|e| e.to_string());
+            let _ = tx.send(CustomCommandUpdate {
+                command,
+                invocation,
+                result,
+            });
+        });
+        self.runtime.channels.custom_command = Some(rx);
+    }
+
+    /// Send an expanded custom command once the agent is free
+    pub(crate) fn poll_custom_command(&mut self) {
+        if self.is_busy() {
+            return;
+        }
+        let Some(ref mut rx) = self.runtime.channels.custom_command else {
+            return;
+        };
+        let update = match rx.try_recv() {
+            Ok(update) => update,
+            Err(tokio::sync::oneshot::error::TryRecvError::Empty) => return,
+            Err(tokio::sync::oneshot::error::TryRecvError::Closed) => {
+                self.runtime.channels.custom_command = None;
+                return;
+            }
+        };
+        self.runtime.channels.custom_command = None;
+
+        match update.result {
+            Ok(prompt) => {
+                self.runtime.active_command = Some(update.command);
+                self.submit_user_message(prompt, Some(update.invocation));
+            }
+            Err(e) => self.runtime.chat.messages.push((
+                "system".to_string(),
+                format!("/{} failed: {}", update.command.name, e),
+            )),
+        }
+        self.ui.needs_redraw = true;
+    }
+
+    /// Whether picking `/name` from autocomplete should wait for arguments
+    ///
+    /// Autocomplete only offers commands from a loaded list, so the
+    /// manager's cache is already filled
+    pub(crate) fn custom_command_takes_arguments(&self, primary: &str) -> bool {
+        self.services
+            .commands_manager
+            .loaded_command(primary.trim_start_matches('/'))
+            .is_some_and(|c| c.takes_arguments())
+    }
+
+    /// `/commands`: reload custom commands and list them
+    pub(crate) fn handle_commands_command(&mut self) {
+        let manager = &mut self.services.commands_manager;
+        manager.refresh();
+        let commands = manager.list_commands();
+        let global_dir = manager.global_dir().clone();
+        self.ui.autocomplete.set_custom_commands(&commands);
+
+        let message = if commands.is_empty() {
+            format!(
+                "No custom commands. Add markdown files to {} or .krusty/commands/ \
+                 (frontmatter: description, argument-hint, allowed-tools, model).",
+                global_dir.display()
+            )
+        } else {
+            let lines: Vec<String> = commands
+                .iter()
+                .map(|c| {
+                    let source = match c.source {
+                        CommandSource::Global => "global",
+                        CommandSource::Project => "project",
+                    };
+                    let mut line = format!("• /{}", c.name);
+                    if let Some(ref hint) = c.argument_hint {
+                        line.push_str(&format!(" {}", hint));
+                    }
+                    line.push_str(&format!(" [{}] - {}", source, c.description));
+                    if let Some(ref tools) = c.allowed_tools {
+                        line.push_str(&format!("\n  tools: {}", tools.join(", ")));
+                    }
+                    if let Some(ref model) = c.model {
+                        line.push_str(&format!("\n  model: {}", model));
+                    }
+                    line
+                })
+                .collect();
+            format!("Custom commands:\n{}", lines.join("\n"))
+        };
+        self.runtime
+            .chat
+            .messages
+            .push(("system".to_string(), message));
+    }
+}
@@ -4,7 +4,8 @@
 
 use crate::tui::app::{App, View};
 use crate::tui::polling::{
-    poll_bash_output, poll_build_progress, poll_explore_progress, PollAction, PollResult,
+    poll_bash_output, poll_build_progress, poll_explore_progress, poll_plan_run, PollAction,
+    PollResult,
 };
 
 impl App {
@@ -33,6 +34,26 @@ impl App {
         )
     }
 
+    /// Poll /plan run events and route builder progress to its BuildBlock
+    pub(crate) fn poll_plan_run(&mut self) -> PollResult {
+        let run_id = self.runtime.plan_run_id.clone();
+        let result = poll_plan_run(
+            &mut self.runtime.channels,
+            &mut self.runtime.blocks.build,
+            &mut self.runtime.active_plan,
+            &self.services.plan_manager,
+            &mut self.runtime.plan_run_id,
+            &mut self.ui.plan_sidebar,
+        );
+        if self.runtime.plan_run_id.is_none() {
+            self.runtime.plan_run_cancel = None;
+            if let Some(run_id) = run_id {
+                self.release_agent_controls(&run_id);
+            }
+        }
+        result
+    }
+
     /// Poll terminal panes for PTY output and update cursor animations
     pub(crate) fn poll_terminal_panes(&mut self) {
         self.runtime.blocks.poll_terminals();
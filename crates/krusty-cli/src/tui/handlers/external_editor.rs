@@ -0,0 +1,233 @@
+//! External editor
+//!
+//! Suspends the TUI, opens the prompt in `$VISUAL`/`$EDITOR` and loads the
+//! saved file back into the input.
+
+use std::collections::HashSet;
+use std::io::{self, Write};
+use std::path::Path;
+use std::process::{Command, ExitStatus};
+
+use ratatui::{backend::CrosstermBackend, Terminal};
+
+use crate::tui::app::{enter_tui, leave_tui, App};
+use crate::tui::components::Toast;
+use crate::tui::input::{parse_input, InputSegment};
+
+impl App {
+    /// Edit the current input in an external editor
+    pub(crate) fn edit_input_externally(
+        &mut self,
+        terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    ) {
+        let editor = editor_command();
+        if let Err(e) = leave_tui(terminal.backend_mut()) {
+            tracing::warn!("Failed to restore terminal for editor: {}", e);
+        }
+        let _ = terminal.show_cursor();
+        tracing::info!(editor = %editor, "Opening prompt in external editor");
+        let edited = edit_text(&editor, self.ui.input.content());
+        if let Err(e) = enter_tui(terminal.backend_mut()) {
+            tracing::warn!("Failed to re-enter TUI after editor: {}", e);
+        }
+        // The editor drew over the screen; repaint everything
+        let _ = terminal.clear();
+
+        match edited {
+            Ok(text) => self.load_edited_input(&text),
+            Err(EditError::Create(e)) => {
+                tracing::warn!("Failed to write prompt file: {}", e);
+                self.show_toast(Toast::warning(format!(
+                    "Couldn't create prompt file: {}",
+                    e
+                )));
+            }
+            Err(EditError::Start(e)) => self.show_toast(Toast::warning(format!(
+                "Couldn't start '{}': {} (set $VISUAL or $EDITOR)",
+                editor, e
+            ))),
+            Err(EditError::Exited(status)) => self.show_toast(Toast::warning(format!(
+                "{} exited with {}; input unchanged",
+                editor, status
+            ))),
+            Err(EditError::Read(e)) => {
+                self.show_toast(Toast::warning(format!("Couldn't read prompt: {}", e)))
+            }
+        }
+    }
+
+    fn load_edited_input(&mut self, text: &str) {
+        // Editors end the file with a newline
+        let text = text.trim_end_matches(['\n', '\r']);
+        self.ui.input.clear();
+        self.ui.input.insert_text(text);
+        self.check_input_references(text);
+        self.update_autocomplete();
+    }
+
+    /// Re-parse file references after an outside edit: forget clipboard
+    /// images whose placeholder was deleted and warn about missing files
+    fn check_input_references(&mut self, text: &str) {
+        let segments = parse_input(text, &self.runtime.working_dir);
+
+        let clipboard_ids: HashSet<&str> = segments
+            .iter()
+            .filter_map(|segment| match segment {
+                InputSegment::ClipboardImage(id) => {
+                    Some(id.strip_prefix("clipboard:").unwrap_or(id))
+                }
+                _ => None,
+            })
+            .collect();
+        self.runtime
+            .pending_clipboard_images
+            .retain(|id, _| clipboard_ids.contains(id.as_str()));
+
+        let missing: Vec<String> = segments
+            .iter()
+            .filter_map(|segment| match segment {
+                InputSegment::ImagePath(path) if !path.exists() => Some(path.display().to_string()),
+                _ => None,
+            })
+            .collect();
+        if !missing.is_empty() {
+            self.show_toast(Toast::warning(format!(
+                "File not found: {}",
+                missing.join(", ")
+            )));
+        }
+    }
+}
+
+/// Why an outside edit left the input unchanged
+#[derive(Debug)]
+enum EditError {
+    Create(io::Error),
+    Start(io::Error),
+    Exited(ExitStatus),
+    Read(io::Error),
+}
+
+/// Write `text` to a temp file, open it in `editor` and read it back
+///
+/// The file is removed afterwards, whatever the outcome.
+fn edit_text(editor: &str, text: &str) -> Result<String, EditError> {
+    let file = prompt_file(text).map_err(EditError::Create)?;
+    let status = run_editor(editor, file.path());
+    let edited = std::fs::read_to_string(file.path());
+    let _ = file.close();
+    match status {
+        Ok(status) if status.success() => edited.map_err(EditError::Read),
+        Ok(status) => Err(EditError::Exited(status)),
+        Err(e) => Err(EditError::Start(e)),
+    }
+}
+
+/// A new, randomly named temp file only the user can read (0600 on Unix)
+/// holding `text`
+fn prompt_file(text: &str) -> io::Result<tempfile::NamedTempFile> {
+    let mut file = tempfile::Builder::new()
+        .prefix("krusty-prompt-")
+        .suffix(".md")
+        .tempfile()?;
+    file.write_all(text.as_bytes())?;
+    file.flush()?;
+    Ok(file)
+}
+
+/// `$VISUAL`, then `$EDITOR`, then a platform default
+fn editor_command() -> String {
+    pick_editor(std::env::var("VISUAL").ok(), std::env::var("EDITOR").ok())
+}
+
+/// The first of `visual` and `editor` that is set and not blank
+fn pick_editor(visual: Option<String>, editor: Option<String>) -> String {
+    [visual, editor]
+        .into_iter()
+        .flatten()
+        .find(|value| !value.trim().is_empty())
+        .unwrap_or_else(|| if cfg!(windows) { "notepad" } else { "vi" }.to_string())
+}
+
+/// Run the editor on `path` and wait for it; the command may carry
+/// arguments (`code --wait`)
+fn run_editor(editor: &str, path: &Path) -> io::Result<ExitStatus> {
+    let mut parts = editor.split_whitespace();
+    let program = parts.next().unwrap_or("vi");
+    Command::new(program).args(parts).arg(path).status()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_editor_fallback_order() {
+        let some = |s: &str| Some(s.to_string());
+        assert_eq!(
+            pick_editor(some("code --wait"), some("nano")),
+            "code --wait"
+        );
+        assert_eq!(pick_editor(None, some("nano")), "nano");
+        assert_eq!(pick_editor(some("  "), some("nano")), "nano");
+        let default = if cfg!(windows) { "notepad" } else { "vi" };
+        assert_eq!(pick_editor(None, None), default);
+        assert_eq!(pick_editor(some(""), some("")), default);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_edit_round_trip() {
+        use std::os::unix::fs::PermissionsExt;
+
+        // An "editor" that appends a line and notes which file it was given
+        let dir = tempfile::tempdir().unwrap();
+        let seen = dir.path().join("seen");
+        let script = dir.path().join("editor.sh");
+        std::fs::write(
+            &script,
+            format!(
+                "#!/bin/sh\nprintf '%s' \"$1\" > {}\nprintf 'second line\\n' >> \"$1\"\n",
+                seen.display()
+            ),
+        )
+        .unwrap();
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let edited = edit_text(script.to_str().unwrap(), "first line\n").unwrap();
+        assert_eq!(edited, "first line\nsecond line\n");
+
+        // The temp file is gone afterwards
+        let path = std::fs::read_to_string(&seen).unwrap();
+        assert!(path.contains("krusty-prompt-"));
+        assert!(!Path::new(&path).exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_prompt_file_is_private() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let file = prompt_file("draft").unwrap();
+        let mode = file.as_file().metadata().unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        assert_eq!(std::fs::read_to_string(file.path()).unwrap(), "draft");
+
+        // Two prompts never share a name
+        let other = prompt_file("draft").unwrap();
+        assert_ne!(file.path(), other.path());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_failed_editor_leaves_input() {
+        assert!(matches!(
+            edit_text("false", "draft"),
+            Err(EditError::Exited(status)) if !status.success()
+        ));
+        assert!(matches!(
+            edit_text("krusty-no-such-editor", "draft"),
+            Err(EditError::Start(_))
+        ));
+    }
+}
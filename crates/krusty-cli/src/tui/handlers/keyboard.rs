@@ -6,7 +6,8 @@ use crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
 
 use crate::agent::{AgentEvent, InterruptReason};
 use crate::tui::app::{App, Popup, View};
-use crate::tui::input::InputAction;
+use crate::tui::handlers::prompt_queue::BusySubmit;
+use crate::tui::input::{Action, InputAction, KeyContext};
 use crate::tui::utils::TitleAction;
 
 impl App {
@@ -32,32 +33,31 @@ impl App {
         // Handle popups first (ignore Release events)
         if self.ui.popup != Popup::None {
             if is_press {
-                self.handle_popup_key(code, modifiers);
+                // Popups close on Esc; keys bound to close_popup act as Esc
+                if self.ui.keymap.action(KeyContext::Popup, code, modifiers)
+                    == Some(Action::ClosePopup)
+                {
+                    self.handle_popup_key(KeyCode::Esc, KeyModifiers::NONE);
+                } else {
+                    self.handle_popup_key(code, modifiers);
+                }
             }
             return;
         }
 
         // Handle plugin window focus - route keys to plugin
         if self.ui.plugin_window.focused {
-            // Delete unfocuses the plugin window (Esc is used by plugin menus)
-            if code == KeyCode::Delete {
-                self.ui.plugin_window.unfocus();
-                return;
-            }
-            // Ctrl+Q still quits
-            if modifiers.contains(KeyModifiers::CONTROL) && code == KeyCode::Char('q') {
-                self.runtime.should_quit = true;
-                return;
-            }
-            // Ctrl+P toggles plugin window visibility
-            if modifiers.contains(KeyModifiers::CONTROL) && code == KeyCode::Char('p') {
-                // Load preferred plugin from preferences on first open
-                let preferred = self
-                    .services
-                    .preferences
-                    .as_ref()
-                    .and_then(|p| p.get_active_plugin());
-                self.ui.plugin_window.toggle(preferred.as_deref());
+            // Delete unfocuses by default (Esc is used by plugin menus)
+            // (bound keys never reach the plugin, releases included)
+            if let Some(action) = self.ui.keymap.action(KeyContext::Plugin, code, modifiers) {
+                if is_press {
+                    match action {
+                        Action::UnfocusPlugin => self.ui.plugin_window.unfocus(),
+                        Action::Quit => self.runtime.should_quit = true,
+                        Action::TogglePluginWindow => self.toggle_plugin_window(),
+                        _ => {}
+                    }
+                }
                 return;
             }
             // Forward all other keys to the plugin (pass full event for key release detection)
@@ -75,17 +75,18 @@ impl App {
             return;
         }
 
-        // Forward keys to focused terminal (except Esc and Ctrl+Q)
+        // Forward keys to focused terminal (except its own bindings)
         if let Some(idx) = self.runtime.blocks.focused_terminal {
-            // ESC unfocuses terminal
-            if code == KeyCode::Esc {
-                self.runtime.blocks.clear_all_terminal_focus();
-                return;
-            }
-            // Ctrl+Q still quits
-            if modifiers.contains(KeyModifiers::CONTROL) && code == KeyCode::Char('q') {
-                self.runtime.should_quit = true;
-                return;
+            match self.ui.keymap.action(KeyContext::Terminal, code, modifiers) {
+                Some(Action::UnfocusTerminal) => {
+                    self.runtime.blocks.clear_all_terminal_focus();
+                    return;
+                }
+                Some(Action::Quit) => {
+                    self.runtime.should_quit = true;
+                    return;
+                }
+                _ => {}
             }
             // Forward all other keys to the terminal
             if let Some(tp) = self.runtime.blocks.terminal.get_mut(idx) {
@@ -110,11 +111,22 @@ impl App {
                     self.ui.autocomplete.prev();
                     return;
                 }
-                // Only plain Enter selects autocomplete - Shift+Enter should insert newline
-                KeyCode::Enter if modifiers.is_empty() => {
+                // Only plain Enter selects autocomplete - Shift+Enter should insert newline.
+                // Once arguments are typed, Enter submits the line as written
+                KeyCode::Enter
+                    if modifiers.is_empty()
+                        && !self.ui.input.content().trim().contains(char::is_whitespace) =>
+                {
                     if let Some(cmd) = self.ui.autocomplete.get_selected() {
-                        self.handle_slash_command(cmd.primary);
-                        self.ui.input.clear();
+                        let command = cmd.primary.to_string();
+                        if self.custom_command_takes_arguments(&command) {
+                            // Leave room for the arguments
+                            self.ui.input.clear();
+                            self.ui.input.insert_text(&format!("{} ", command));
+                        } else {
+                            self.handle_slash_command(&command);
+                            self.ui.input.clear();
+                        }
                         self.ui.autocomplete.hide();
                     }
                     return;
@@ -176,44 +188,13 @@ impl App {
             }
         }
 
-        // Ctrl+Q to quit
-        if modifiers.contains(KeyModifiers::CONTROL) && code == KeyCode::Char('q') {
-            self.runtime.should_quit = true;
-            return;
-        }
-
-        // Ctrl+B to open process list
-        if modifiers.contains(KeyModifiers::CONTROL) && code == KeyCode::Char('b') {
-            self.refresh_process_popup();
-            self.ui.popup = Popup::ProcessList;
-            return;
-        }
-
-        // Ctrl+T to toggle plan/tasks sidebar (only if we have an active plan)
-        if modifiers.contains(KeyModifiers::CONTROL) && code == KeyCode::Char('t') {
-            if self.runtime.active_plan.is_some() {
-                self.ui.plan_sidebar.toggle();
+        // Shortcuts from the keymap (Ctrl+Q, Ctrl+G, ...), unless the
+        // editing mode uses the key (Emacs' Ctrl+B, ...)
+        if !self.ui.input.claims_key(code, modifiers) {
+            if let Some(action) = self.ui.keymap.action(KeyContext::Global, code, modifiers) {
+                self.run_global_action(action);
+                return;
             }
-            return;
-        }
-
-        // Ctrl+P to toggle plugin window
-        if modifiers.contains(KeyModifiers::CONTROL) && code == KeyCode::Char('p') {
-            let preferred = self
-                .services
-                .preferences
-                .as_ref()
-                .and_then(|p| p.get_active_plugin());
-            self.ui.plugin_window.toggle(preferred.as_deref());
-            return;
-        }
-
-        // Ctrl+G to toggle work mode (BUILD/PLAN)
-        if modifiers.contains(KeyModifiers::CONTROL) && code == KeyCode::Char('g') {
-            let old_mode = self.ui.work_mode;
-            self.ui.work_mode = self.ui.work_mode.toggle();
-            tracing::info!(from = ?old_mode, to = ?self.ui.work_mode, "Work mode toggled via Ctrl+G");
-            return;
         }
 
         match self.ui.view {
@@ -272,8 +253,20 @@ impl App {
 
     /// Handle start menu keyboard events
     pub fn handle_start_menu_key(&mut self, code: KeyCode, modifiers: KeyModifiers) {
+        let claimed = self.ui.input.claims_key(code, modifiers);
+        let action = if claimed {
+            None
+        } else {
+            self.ui.keymap.action(KeyContext::Chat, code, modifiers)
+        };
+
+        if action == Some(Action::OpenEditor) {
+            self.runtime.external_edit_requested = true;
+            return;
+        }
+
         // Tab - toggle extended thinking mode (when not in autocomplete)
-        if code == KeyCode::Tab && !self.ui.autocomplete.visible {
+        if action == Some(Action::ToggleThinking) && !self.ui.autocomplete.visible {
             self.runtime.thinking_enabled = !self.runtime.thinking_enabled;
             tracing::info!(
                 "Extended thinking {}",
@@ -309,7 +302,7 @@ impl App {
             InputAction::Continue | InputAction::ContentChanged => {
                 self.update_autocomplete();
                 // Escape clears input on start menu
-                if code == KeyCode::Esc && !self.ui.autocomplete.visible {
+                if code == KeyCode::Esc && !claimed && !self.ui.autocomplete.visible {
                     self.ui.input.clear();
                 }
             }
@@ -325,9 +318,37 @@ impl App {
         }
         // Fall through to input for custom response typing
 
+        // Queued prompt chips (picked with ↑ from an empty input)
+        if !self.ui.decision_prompt.visible && self.handle_prompt_queue_key(code, modifiers) {
+            return;
+        }
+
+        // Keys the editing mode uses (Esc leaving Vim insert mode) skip the keymap
+        let action = if self.ui.input.claims_key(code, modifiers) {
+            None
+        } else {
+            self.ui.keymap.action(KeyContext::Chat, code, modifiers)
+        };
+
+        // Picking an earlier message to edit, resend or switch branch
+        if !self.ui.decision_prompt.visible && self.handle_branch_key(code, action) {
+            return;
+        }
+
+        // Esc while writing to a sub-agent cancels that, not the whole run
+        if action == Some(Action::Interrupt)
+            && !self.ui.autocomplete.visible
+            && self.runtime.agent_steer.is_some()
+        {
+            self.end_agent_steer();
+            return;
+        }
+
         // Esc interrupts AI processing (use /home to return to start menu)
         // Only if decision prompt is NOT visible (handled above)
-        if code == KeyCode::Esc && !self.ui.autocomplete.visible && !self.ui.decision_prompt.visible
+        if action == Some(Action::Interrupt)
+            && !self.ui.autocomplete.visible
+            && !self.ui.decision_prompt.visible
         {
             if self.is_busy() {
                 // Cancel the background task
@@ -348,13 +369,33 @@ impl App {
                     .chat
                     .messages
                     .push(("system".to_string(), "Interrupted.".to_string()));
+
+                // Hold queued prompts rather than sending them right away
+                let queued = self.runtime.prompt_queue.items().len();
+                if queued > 0 {
+                    self.runtime.prompt_queue.paused = true;
+                    self.runtime.chat.messages.push((
+                        "system".to_string(),
+                        format!(
+                            "{} queued prompt(s) held. Press Enter on an empty input to send them, \
+                             or /queue clear to drop them.",
+                            queued
+                        ),
+                    ));
+                }
             }
             return;
         }
 
+        // Compose in $VISUAL/$EDITOR (runs from the main loop, which owns the terminal)
+        if action == Some(Action::OpenEditor) {
+            self.runtime.external_edit_requested = true;
+            return;
+        }
+
         // Tab - toggle extended thinking mode (when not in autocomplete)
         // Can toggle during streaming - takes effect after current stream completes
-        if code == KeyCode::Tab && !self.ui.autocomplete.visible {
+        if action == Some(Action::ToggleThinking) && !self.ui.autocomplete.visible {
             self.runtime.thinking_enabled = !self.runtime.thinking_enabled;
             tracing::info!(
                 "Extended thinking {}",
@@ -391,13 +432,13 @@ impl App {
             }
         }
 
-        // PageUp - show older content (decrease offset toward 0/top)
-        if code == KeyCode::PageUp {
+        // Scroll up - show older content (decrease offset toward 0/top)
+        if action == Some(Action::ScrollUp) {
             self.ui.scroll_system.scroll.scroll_up(5);
             return;
         }
-        // PageDown - show newer content (increase offset toward MAX/bottom)
-        if code == KeyCode::PageDown {
+        // Scroll down - show newer content (increase offset toward MAX/bottom)
+        if action == Some(Action::ScrollDown) {
             self.ui.scroll_system.scroll.scroll_down(5);
             return;
         }
@@ -407,19 +448,34 @@ impl App {
                 // Check if we're in decision prompt custom input mode
                 if self.ui.decision_prompt.visible && self.ui.decision_prompt.custom_input_mode {
                     if !text.is_empty() {
-                        let all_done = self.ui.decision_prompt.submit_custom(text);
+                        let all_done = self.ui.decision_prompt.submit_custom(text)
+                            || self.mcp_request_declined();
                         self.ui.input.clear();
                         if all_done {
                             self.handle_decision_prompt_complete();
                         }
                     }
+                } else if self.runtime.agent_steer.is_some() {
+                    if !text.is_empty() {
+                        self.ui.autocomplete.hide();
+                        self.submit_agent_steer(text);
+                    }
                 } else if !text.is_empty() {
                     if self.is_busy() {
-                        self.runtime.chat.messages.push((
-                            "system".to_string(),
-                            "Please wait for the current response to complete.".to_string(),
-                        ));
+                        match Self::busy_submit(&text) {
+                            BusySubmit::Queue => self.queue_prompt(text),
+                            BusySubmit::Run => {
+                                self.ui.input.clear();
+                                self.ui.autocomplete.hide();
+                                self.handle_slash_command(&text);
+                            }
+                            BusySubmit::Wait => self.runtime.chat.messages.push((
+                                "system".to_string(),
+                                "Please wait for the current response to complete.".to_string(),
+                            )),
+                        }
                     } else {
+                        self.runtime.prompt_queue.finish_edit();
                         self.ui.input.clear();
                         self.ui.autocomplete.hide();
                         self.handle_input_submit(text);
@@ -571,7 +627,8 @@ impl App {
             }
             // Enter confirms current selection
             KeyCode::Enter if modifiers.is_empty() => {
-                let all_done = self.ui.decision_prompt.confirm_selection();
+                let all_done =
+                    self.ui.decision_prompt.confirm_selection() || self.mcp_request_declined();
                 if all_done {
                     self.handle_decision_prompt_complete();
                 }
@@ -591,7 +648,15 @@ impl App {
             KeyCode::Esc => {
                 if !self.ui.decision_prompt.go_back() {
                     // No previous question - close the prompt
+                    let prompt_type = self.ui.decision_prompt.prompt_type.clone();
                     self.ui.decision_prompt.hide();
+                    self.dismiss_mcp_request();
+                    if prompt_type == crate::tui::components::PromptType::PlanSyncConflict {
+                        self.defer_plan_sync_conflict();
+                    }
+                    if prompt_type == crate::tui::components::PromptType::CommandShell {
+                        self.handle_command_shell_answer(&[]);
+                    }
                 }
                 true
             }
@@ -623,6 +688,15 @@ impl App {
                     self.handle_ask_user_answer(id, &answers);
                 }
             }
+            PromptType::McpSampling | PromptType::McpElicitation => {
+                self.handle_mcp_prompt_answer(&answers);
+            }
+            PromptType::PlanSyncConflict => {
+                self.handle_plan_sync_answer(&answers);
+            }
+            PromptType::CommandShell => {
+                self.handle_command_shell_answer(&answers);
+            }
         }
     }
 
@@ -0,0 +1,140 @@
+//! Keymap actions
+//!
+//! Runs the actions bound in the global context, reports problems in
+//! `~/.krusty/keymap.toml` at startup and switches the prompt editing mode.
+
+use crate::tui::app::{App, Popup};
+use crate::tui::components::Toast;
+use crate::tui::input::{Action, EditMode, KeyContext, Keymap, MultiLineInput};
+
+impl App {
+    /// Run an action from the global context
+    pub(crate) fn run_global_action(&mut self, action: Action) {
+        match action {
+            Action::Quit => self.runtime.should_quit = true,
+            Action::OpenProcessList => {
+                self.refresh_process_popup();
+                self.ui.popup = Popup::ProcessList;
+            }
+            // Only if we have an active plan
+            Action::TogglePlanSidebar if self.runtime.active_plan.is_some() => {
+                self.ui.plan_sidebar.toggle();
+            }
+            Action::TogglePluginWindow => self.toggle_plugin_window(),
+            Action::ToggleWorkMode => {
+                let old_mode = self.ui.work_mode;
+                self.ui.work_mode = self.ui.work_mode.toggle();
+                tracing::info!(from = ?old_mode, to = ?self.ui.work_mode, "Work mode toggled via keymap");
+            }
+            Action::OpenModelSelect => self.handle_slash_command("/model"),
+            Action::OpenHelp => self.ui.popup = Popup::Help,
+            // Other contexts' actions are handled where their keys are read,
+            // and the sidebar needs a plan
+            _ => {}
+        }
+    }
+
+    /// Key bound to a global action, as " (Ctrl+G)" for hints in messages
+    ///
+    /// Empty when the keymap leaves the action unbound.
+    pub(crate) fn global_key_hint(&self, action: Action) -> String {
+        self.ui
+            .keymap
+            .keys(KeyContext::Global, action)
+            .first()
+            .map(|key| format!(" ({})", key))
+            .unwrap_or_default()
+    }
+
+    /// Show or hide the plugin window
+    pub(crate) fn toggle_plugin_window(&mut self) {
+        // Load preferred plugin from preferences on first open
+        let preferred = self
+            .services
+            .preferences
+            .as_ref()
+            .and_then(|p| p.get_active_plugin());
+        self.ui.plugin_window.toggle(preferred.as_deref());
+    }
+
+    /// `/editmode [standard|vim|emacs]`: switch the prompt key set (no
+    /// argument cycles) and save it
+    pub(crate) fn handle_edit_mode_command(&mut self, arg: Option<&str>) {
+        let current = self.ui.input.edit_mode();
+        let mode = match arg {
+            None => {
+                let next = EditMode::ALL
+                    .iter()
+                    .position(|&m| m == current)
+                    .map_or(0, |i| (i + 1) % EditMode::ALL.len());
+                EditMode::ALL[next]
+            }
+            Some(name) => match EditMode::from_name(name) {
+                Some(mode) => mode,
+                None => {
+                    self.runtime.chat.messages.push((
+                        "system".to_string(),
+                        format!(
+                            "Unknown editing mode '{}'. Usage: /editmode [standard|vim|emacs]",
+                            name
+                        ),
+                    ));
+                    return;
+                }
+            },
+        };
+
+        self.ui.input.set_edit_mode(mode);
+        if let Some(prefs) = &self.services.preferences {
+            if let Err(e) = prefs.set_edit_mode(mode.name()) {
+                tracing::warn!("Failed to save editing mode: {}", e);
+            }
+        }
+
+        let mut msg = format!("Editing mode: {}", mode.name());
+        if mode == EditMode::Vim {
+            msg.push_str("\nEsc leaves insert mode; in normal mode it works as before.");
+        }
+        let shadowed = shadowed_keys(&self.ui.keymap, mode);
+        if !shadowed.is_empty() {
+            msg.push_str(&format!(
+                "\nThe editor now takes these keys first: {}",
+                shadowed.join(", ")
+            ));
+        }
+        self.runtime.chat.messages.push(("system".to_string(), msg));
+    }
+
+    /// Log keymap problems and point at the list in /cmd
+    pub(crate) fn report_keymap_problems(&mut self) {
+        let problems = self.ui.keymap.problems().to_vec();
+        if problems.is_empty() {
+            return;
+        }
+        for problem in &problems {
+            tracing::warn!("Keymap: {}", problem);
+        }
+        self.show_toast(Toast::warning(format!(
+            "keymap.toml: {} problem{} (see /cmd)",
+            problems.len(),
+            if problems.len() == 1 { "" } else { "s" }
+        )));
+    }
+}
+
+/// Global and chat shortcuts a fresh input in `mode` handles itself
+fn shadowed_keys(keymap: &Keymap, mode: EditMode) -> Vec<String> {
+    let mut probe = MultiLineInput::new(1);
+    probe.set_edit_mode(mode);
+    let mut shadowed = Vec::new();
+    for context in [KeyContext::Global, KeyContext::Chat] {
+        for action in Keymap::actions(context) {
+            for key in keymap.keys(context, action) {
+                if probe.claims_key(key.code, key.modifiers) {
+                    shadowed.push(format!("{} ({})", key, action.name()));
+                }
+            }
+        }
+    }
+    shadowed
+}
@@ -0,0 +1,248 @@
+//! MCP server requests
+//!
+//! Shows sampling approvals and elicitation forms from MCP servers in the
+//! decision prompt, one at a time, and sends the user's answer back.
+
+use krusty_core::mcp::{
+    elicit_content, sample_with_client, sampling_prompt, ElicitAction, ElicitField,
+    ElicitFieldKind, ElicitResult,
+};
+use serde_json::Value;
+
+use crate::ai::types::{ModelMessage, Role};
+use crate::tui::app::App;
+use crate::tui::components::{PromptAnswer, PromptOption, PromptQuestion, PromptType};
+use crate::tui::utils::McpUserRequest;
+
+/// Characters of the sampling prompt shown for approval
+const SAMPLING_PREVIEW_CHARS: usize = 200;
+
+impl App {
+    /// Show the next queued server request once the decision prompt is free
+    pub(crate) fn poll_mcp_requests(&mut self) {
+        if self.ui.decision_prompt.visible || self.runtime.active_mcp_request.is_some() {
+            return;
+        }
+        let Some(rx) = self.runtime.channels.mcp_requests.as_mut() else {
+            return;
+        };
+        let Ok(request) = rx.try_recv() else {
+            return;
+        };
+
+        let (prompt_type, questions) = match &request {
+            McpUserRequest::Sampling { server, params, .. } => {
+                let preview = match sampling_prompt(params) {
+                    Ok(prompt) => prompt,
+                    Err(e) => e.to_string(),
+                };
+                let preview: String = preview
+                    .split_whitespace()
+                    .collect::<Vec<_>>()
+                    .join(" ")
+                    .chars()
+                    .take(SAMPLING_PREVIEW_CHARS)
+                    .collect();
+                let question = PromptQuestion::new(format!("MCP sampling · {}", server), preview)
+                    .add_option(PromptOption::new("Approve").with_description(format!(
+                        "Generate a reply with {}",
+                        self.runtime.current_model
+                    )))
+                    .add_option(PromptOption::new("Deny").with_description("Reject the request"));
+                (PromptType::McpSampling, vec![question])
+            }
+            McpUserRequest::Elicitation { server, params, .. } => {
+                let mut questions = vec![PromptQuestion::new(
+                    format!("MCP input · {}", server),
+                    params.message.clone(),
+                )
+                .add_option(PromptOption::new("Respond").with_description("Fill in the form"))
+                .add_option(PromptOption::new("Decline").with_description("Don't share"))];
+                // Only asked after "Respond"; declining ends the prompt
+                // (see `mcp_request_declined`)
+                questions.extend(params.fields().iter().map(elicit_question));
+                (PromptType::McpElicitation, questions)
+            }
+        };
+
+        self.runtime.active_mcp_request = Some(request);
+        self.ui
+            .decision_prompt
+            .show_mcp_request(prompt_type, questions);
+        self.ui.needs_redraw = true;
+    }
+
+    /// Whether the user just declined an elicitation, so its field
+    /// questions are skipped
+    pub(crate) fn mcp_request_declined(&self) -> bool {
+        let prompt = &self.ui.decision_prompt;
+        let [first] = prompt.answers.as_slice() else {
+            return false;
+        };
+        prompt.prompt_type == PromptType::McpElicitation
+            && !matches!(first, PromptAnswer::Selected(0))
+    }
+
+    /// Answer the active server request from the completed prompt
+    pub(crate) fn handle_mcp_prompt_answer(&mut self, answers: &[PromptAnswer]) {
+        let Some(request) = self.runtime.active_mcp_request.take() else {
+            return;
+        };
+
+        match request {
+            McpUserRequest::Sampling {
+                server,
+                params,
+                respond,
+            } => {
+                if !matches!(answers.first(), Some(PromptAnswer::Selected(0))) {
+                    let _ = respond.send(Err("User rejected the sampling request".to_string()));
+                    self.resume_deferred_tool_results();
+                    return;
+                }
+                let Some(client) = self.create_ai_client() else {
+                    let _ = respond.send(Err("No AI provider is configured".to_string()));
+                    self.resume_deferred_tool_results();
+                    return;
+                };
+                self.runtime.chat.messages.push((
+                    "system".to_string(),
+                    format!("Generating a reply for MCP server {}...", server),
+                ));
+                tokio::spawn(async move {
+                    let result = sample_with_client(&client, &params)
+                        .await
+                        .map_err(|e| e.to_string());
+                    let _ = respond.send(result);
+                });
+            }
+            McpUserRequest::Elicitation {
+                server,
+                params,
+                respond,
+            } => {
+                let result = if !matches!(answers.first(), Some(PromptAnswer::Selected(0))) {
+                    ElicitResult {
+                        action: ElicitAction::Decline,
+                        content: None,
+                    }
+                } else {
+                    let fields = params.fields();
+                    let values: Vec<Option<Value>> = fields
+                        .iter()
+                        .zip(answers.iter().skip(1))
+                        .map(|(field, answer)| elicit_value(field, answer))
+                        .collect();
+                    match elicit_content(&fields, &values) {
+                        Ok(content) => ElicitResult {
+                            action: ElicitAction::Accept,
+                            content: Some(content),
+                        },
+                        Err(missing) => {
+                            self.runtime.chat.messages.push((
+                                "system".to_string(),
+                                format!(
+                                    "MCP server {} request cancelled: missing or invalid {}",
+                                    server,
+                                    missing.join(", ")
+                                ),
+                            ));
+                            ElicitResult {
+                                action: ElicitAction::Cancel,
+                                content: None,
+                            }
+                        }
+                    }
+                };
+                let _ = respond.send(result);
+            }
+        }
+        self.resume_deferred_tool_results();
+    }
+
+    /// The prompt was closed without finishing: cancel the active request
+    pub(crate) fn dismiss_mcp_request(&mut self) {
+        match self.runtime.active_mcp_request.take() {
+            Some(McpUserRequest::Sampling { respond, .. }) => {
+                let _ = respond.send(Err("User rejected the sampling request".to_string()));
+            }
+            Some(McpUserRequest::Elicitation { respond, .. }) => {
+                let _ = respond.send(ElicitResult {
+                    action: ElicitAction::Cancel,
+                    content: None,
+                });
+            }
+            None => return,
+        }
+        self.resume_deferred_tool_results();
+    }
+
+    /// Send tool results that were deferred while the prompt was open
+    fn resume_deferred_tool_results(&mut self) {
+        // Still executing: pending results belong to the running batch
+        if self.is_busy() {
+            return;
+        }
+        let pending = std::mem::take(&mut self.runtime.pending_tool_results);
+        if pending.is_empty() {
+            return;
+        }
+        let msg = ModelMessage {
+            role: Role::User,
+            content: pending,
+        };
+        self.runtime.chat.conversation.push(msg.clone());
+        self.save_model_message(&msg);
+        self.send_to_ai();
+    }
+}
+
+/// Decision prompt question for one form field
+fn elicit_question(field: &ElicitField) -> PromptQuestion {
+    let header = if field.required {
+        format!("{} *", field.title)
+    } else {
+        field.title.clone()
+    };
+    let text = field
+        .description
+        .clone()
+        .unwrap_or_else(|| format!("Enter {}", field.title));
+
+    let mut question = PromptQuestion::new(header, text);
+    match &field.kind {
+        ElicitFieldKind::Boolean => {
+            question = question
+                .add_option(PromptOption::new("Yes"))
+                .add_option(PromptOption::new("No"));
+        }
+        ElicitFieldKind::Enum { labels, .. } => {
+            for label in labels {
+                question = question.add_option(PromptOption::new(label.clone()));
+            }
+        }
+        ElicitFieldKind::Text | ElicitFieldKind::Number { .. } => {}
+    }
+    if !field.required {
+        question = question.add_option(PromptOption::new("Skip"));
+    }
+    question
+}
+
+/// JSON value for a field's answer (None when skipped or invalid)
+fn elicit_value(field: &ElicitField, answer: &PromptAnswer) -> Option<Value> {
+    match answer {
+        PromptAnswer::Custom(text) => field.parse_value(text),
+        PromptAnswer::Selected(idx) => match &field.kind {
+            ElicitFieldKind::Boolean => match idx {
+                0 => Some(Value::Bool(true)),
+                1 => Some(Value::Bool(false)),
+                _ => None,
+            },
+            ElicitFieldKind::Enum { values, .. } => values.get(*idx).cloned().map(Value::String),
+            // Only "Skip" (or nothing) can be selected for typed fields
+            ElicitFieldKind::Text | ElicitFieldKind::Number { .. } => None,
+        },
+        PromptAnswer::MultiSelected(_) => None,
+    }
+}
@@ -2,14 +2,23 @@
 //!
 //! All event handling logic extracted from app.rs for better organization.
 
+pub mod agent_control;
+pub mod branches;
 pub mod commands;
+pub mod compaction;
+pub mod custom_commands;
 pub mod event_loop;
+pub mod external_editor;
 pub mod hit_test;
 pub mod keyboard;
+pub mod keymap;
+pub mod mcp_requests;
 pub mod models;
 pub mod mouse;
 pub mod pinch;
+pub mod plan_sync;
 pub mod popup_keys;
+pub mod prompt_queue;
 pub mod provider;
 pub mod rendering;
 pub mod scrollbar;
@@ -19,4 +28,5 @@ pub mod stream_events;
 pub mod streaming;
 pub mod terminal;
 pub mod themes;
+pub mod transcripts;
 pub mod update;
@@ -627,7 +627,32 @@ impl App {
                 }
             }
             BlockType::Explore | BlockType::Build => {
-                // These blocks don't have click interaction yet
+                let result = if hit.block_type == BlockType::Explore {
+                    self.runtime.blocks.explore.get_mut(idx).map(|block| {
+                        let id = block.tool_use_id().map(str::to_string);
+                        (block.handle_event(&event, block_area, clip), id)
+                    })
+                } else {
+                    self.runtime.blocks.build.get_mut(idx).map(|block| {
+                        let id = block.tool_use_id().map(str::to_string);
+                        (block.handle_event(&event, block_area, clip), id)
+                    })
+                };
+                match result {
+                    Some((
+                        EventResult::Action(BlockEvent::OpenTranscript { task_id, name }),
+                        Some(tool_use_id),
+                    )) => self.open_transcript(&tool_use_id, &task_id, &name),
+                    Some((
+                        EventResult::Action(BlockEvent::AgentAction {
+                            task_id,
+                            name,
+                            action,
+                        }),
+                        Some(tool_use_id),
+                    )) => self.handle_agent_action(&tool_use_id, &task_id, &name, action),
+                    _ => {}
+                }
             }
         }
 
@@ -140,9 +140,9 @@ impl App {
         None
     }
 
-    /// Create AI client for summarization
+    /// Create AI client for summarization (follows the summarizer route)
     fn create_summarization_client(&self) -> Option<AiClient> {
-        self.create_ai_client()
+        self.create_role_client(crate::ai::routing::ModelRole::Summarizer)
     }
 
     /// Poll for summarization results
@@ -628,8 +628,8 @@ impl App {
         });
     }
 
-    /// Create AI client for pinch title generation
+    /// Create AI client for pinch title generation (follows the title route)
     fn create_pinch_title_client(&self) -> Option<AiClient> {
-        self.create_ai_client()
+        self.create_role_client(crate::ai::routing::ModelRole::Title)
     }
 }
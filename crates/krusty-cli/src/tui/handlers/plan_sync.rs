@@ -0,0 +1,266 @@
+//! Plan file sync
+//!
+//! Keeps the active plan in step with the repo file chosen with
+//! `/plan sync <path>`, and asks in the decision prompt when the file and
+//! the plan both changed.
+
+use std::time::{Duration, Instant};
+
+use crate::plan::{resolve_conflict, PlanFile, PlanSync, SyncEvent, SyncResolution};
+use crate::tui::app::App;
+use crate::tui::components::PromptAnswer;
+
+/// How often the sync file is checked
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+impl App {
+    /// Load the sync file configured for this working directory
+    pub(crate) fn load_plan_sync(&mut self) {
+        let Some(ref prefs) = self.services.preferences else {
+            return;
+        };
+        let working_dir = self.runtime.working_dir.to_string_lossy();
+        self.runtime.plan_sync = prefs
+            .get_plan_sync_path(&working_dir)
+            .map(|path| PlanSync::new(&self.runtime.working_dir, path));
+    }
+
+    /// `/plan sync [path|off]`: show, enable or disable plan file sync
+    pub(crate) fn handle_plan_sync_command(&mut self, arg: Option<&str>) {
+        let working_dir = self.runtime.working_dir.to_string_lossy().into_owned();
+        let msg = match arg {
+            None => match (&self.runtime.plan_sync, &self.runtime.plan_sync_conflict) {
+                (Some(sync), Some(_)) => {
+                    // Ask again on the next poll
+                    self.runtime.plan_sync_prompted = false;
+                    format!(
+                        "Plan sync with {} is paused on a conflict.",
+                        sync.path().display()
+                    )
+                }
+                (Some(sync), None) => format!("Plan syncs with {}.", sync.path().display()),
+                (None, _) => "Plan sync is off. Use /plan sync <path> (e.g. docs/plan.md) to \
+                              keep the plan in a repo file."
+                    .to_string(),
+            },
+            Some("off") => {
+                if let Some(ref prefs) = self.services.preferences {
+                    if let Err(e) = prefs.set_plan_sync_path(&working_dir, None) {
+                        tracing::warn!("Failed to save plan sync setting: {}", e);
+                    }
+                }
+                self.runtime.plan_sync = None;
+                self.runtime.plan_sync_conflict = None;
+                "Plan sync turned off. The file is left as it is.".to_string()
+            }
+            Some(path) => {
+                if let Some(ref prefs) = self.services.preferences {
+                    if let Err(e) = prefs.set_plan_sync_path(&working_dir, Some(path)) {
+                        tracing::warn!("Failed to save plan sync setting: {}", e);
+                    }
+                }
+                let sync = PlanSync::new(&self.runtime.working_dir, path);
+                let msg = format!(
+                    "Plan will sync with {}. Edits to the file are picked up automatically.",
+                    sync.path().display()
+                );
+                self.runtime.plan_sync = Some(sync);
+                self.runtime.plan_sync_conflict = None;
+                msg
+            }
+        };
+        self.runtime.chat.messages.push(("system".to_string(), msg));
+    }
+
+    /// Write plan changes to the sync file and pick up edits made to it
+    pub(crate) fn poll_plan_sync(&mut self) {
+        if self.runtime.plan_sync.is_none() {
+            return;
+        }
+        if self.runtime.plan_sync_conflict.is_some() {
+            self.show_plan_sync_conflict();
+            return;
+        }
+        if self.runtime.plan_sync_polled.elapsed() < POLL_INTERVAL {
+            return;
+        }
+        self.runtime.plan_sync_polled = Instant::now();
+
+        // Only load the plan when its version or the file changed
+        let Some(session_id) = self
+            .runtime
+            .active_plan
+            .as_ref()
+            .and_then(|p| p.session_id.clone())
+        else {
+            return;
+        };
+        let version = match self.services.plan_manager.plan_version(&session_id) {
+            Ok(Some(version)) => version,
+            Ok(None) => return,
+            Err(e) => {
+                tracing::warn!("Failed to read plan version for sync: {}", e);
+                return;
+            }
+        };
+        if self
+            .runtime
+            .plan_sync
+            .as_ref()
+            .is_some_and(|sync| !sync.needs_poll(&session_id, version))
+        {
+            return;
+        }
+
+        let Some(stored) = self.stored_active_plan() else {
+            return;
+        };
+        let Some(ref mut sync) = self.runtime.plan_sync else {
+            return;
+        };
+        let plan_manager = &self.services.plan_manager;
+        let revision = |version| match plan_manager.get_revision(&session_id, version) {
+            Ok(revision) => revision.map(|r| r.content),
+            Err(e) => {
+                tracing::warn!("Failed to load plan revision {}: {}", version, e);
+                None
+            }
+        };
+        let path = sync.path().display().to_string();
+        let event = match sync.poll(&stored, revision) {
+            Ok(event) => event,
+            Err(e) => {
+                tracing::warn!("Plan sync with {} failed: {:#}", path, e);
+                return;
+            }
+        };
+
+        match event {
+            SyncEvent::Idle | SyncEvent::Written => {}
+            SyncEvent::Updated(plan) => {
+                self.apply_synced_plan(plan, format!("Plan updated from {}.", path));
+            }
+            SyncEvent::Conflict(file_plan) => {
+                self.runtime.plan_sync_conflict = Some(file_plan);
+                self.runtime.plan_sync_prompted = false;
+                self.show_plan_sync_conflict();
+            }
+            SyncEvent::Invalid(e) => {
+                self.runtime.chat.messages.push((
+                    "system".to_string(),
+                    format!(
+                        "{} is not a valid plan ({}); keeping the current plan.",
+                        path, e
+                    ),
+                ));
+                self.ui.needs_redraw = true;
+            }
+        }
+    }
+
+    /// Ask how to settle a pending conflict once the prompt is free
+    fn show_plan_sync_conflict(&mut self) {
+        if self.runtime.plan_sync_prompted || self.ui.decision_prompt.visible || self.is_busy() {
+            return;
+        }
+        let (Some(sync), Some(file_plan)) =
+            (&self.runtime.plan_sync, &self.runtime.plan_sync_conflict)
+        else {
+            return;
+        };
+        let path = sync.path().display().to_string();
+        let file_version = file_plan.version;
+        let Some(stored) = self.stored_active_plan() else {
+            // The plan is gone; nothing left to conflict with
+            self.runtime.plan_sync_conflict = None;
+            return;
+        };
+
+        self.ui
+            .decision_prompt
+            .show_plan_sync_conflict(&path, stored.version, file_version);
+        self.runtime.plan_sync_prompted = true;
+        self.ui.needs_redraw = true;
+    }
+
+    /// Settle the conflict with the option picked in the prompt
+    pub(crate) fn handle_plan_sync_answer(&mut self, answers: &[PromptAnswer]) {
+        let resolution = match answers.first() {
+            Some(PromptAnswer::Selected(0)) => SyncResolution::UseFile,
+            Some(PromptAnswer::Selected(1)) => SyncResolution::KeepPlan,
+            Some(PromptAnswer::Selected(2)) => SyncResolution::Merge,
+            _ => {
+                self.defer_plan_sync_conflict();
+                return;
+            }
+        };
+        let Some(file_plan) = self.runtime.plan_sync_conflict.take() else {
+            return;
+        };
+        let Some(stored) = self.stored_active_plan() else {
+            return;
+        };
+        let path = self
+            .runtime
+            .plan_sync
+            .as_ref()
+            .map(|s| s.path().display().to_string())
+            .unwrap_or_default();
+
+        match resolution {
+            SyncResolution::KeepPlan => {
+                if let Some(ref mut sync) = self.runtime.plan_sync {
+                    sync.mark_stale();
+                }
+                self.runtime.chat.messages.push((
+                    "system".to_string(),
+                    format!("Kept the plan; {} will be overwritten.", path),
+                ));
+            }
+            SyncResolution::UseFile | SyncResolution::Merge => {
+                let plan = resolve_conflict(&stored, &file_plan, resolution);
+                let msg = if resolution == SyncResolution::Merge {
+                    format!("Merged {} into the plan.", path)
+                } else {
+                    format!("Plan replaced with {}.", path)
+                };
+                self.apply_synced_plan(plan, msg);
+            }
+        }
+    }
+
+    /// The conflict prompt was closed without a choice
+    pub(crate) fn defer_plan_sync_conflict(&mut self) {
+        if self.runtime.plan_sync_conflict.is_none() {
+            return;
+        }
+        self.runtime.chat.messages.push((
+            "system".to_string(),
+            "Plan sync is paused until the conflict is settled. Use /plan sync to decide."
+                .to_string(),
+        ));
+    }
+
+    /// The active plan as last saved (its version is the stored revision)
+    fn stored_active_plan(&self) -> Option<PlanFile> {
+        let session_id = self.runtime.active_plan.as_ref()?.session_id.clone()?;
+        match self.services.plan_manager.get_plan(&session_id) {
+            Ok(plan) => plan,
+            Err(e) => {
+                tracing::warn!("Failed to load plan for sync: {}", e);
+                None
+            }
+        }
+    }
+
+    /// Save a plan that came from the sync file and make it the active plan
+    fn apply_synced_plan(&mut self, plan: PlanFile, msg: String) {
+        if let Err(e) = self.services.plan_manager.save_plan(&plan) {
+            tracing::warn!("Failed to save synced plan: {}", e);
+            return;
+        }
+        self.runtime.active_plan = Some(plan);
+        self.runtime.chat.messages.push(("system".to_string(), msg));
+        self.ui.needs_redraw = true;
+    }
+}
@@ -0,0 +1,333 @@
+//! Change review popup keyboard handler
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Arc;
+
+use crossterm::event::{KeyCode, KeyModifiers};
+
+use crate::storage::{FileActivityTracker, StoredFileChanges};
+use crate::tools::git_identity::{GitIdentity, GitIdentityMode};
+use crate::tools::SharedFileChangeSink;
+use crate::tui::app::{App, Popup};
+use crate::tui::components::Toast;
+use crate::tui::popups::change_review::{FileContent, ReviewFile};
+
+impl App {
+    /// Open the review of every file changed in this session
+    pub fn open_change_review(&mut self) {
+        let Some(tracker) = self.file_tracker() else {
+            self.show_toast(Toast::warning("No changes yet - start a session first"));
+            return;
+        };
+        let snapshots = match tracker.snapshots() {
+            Ok(snapshots) => snapshots,
+            Err(e) => {
+                self.show_toast(Toast::warning(format!("Failed to load changes: {}", e)));
+                return;
+            }
+        };
+        // Written or edited files that couldn't be read before the change
+        let unsnapshotted = tracker.unsnapshotted_changes().unwrap_or_else(|e| {
+            tracing::warn!("Failed to load file activity: {}", e);
+            Vec::new()
+        });
+        let mut files: Vec<ReviewFile> = snapshots
+            .into_iter()
+            .map(|s| self.review_file(s.file_path, s.original.into()))
+            .chain(unsnapshotted.into_iter().map(|path| {
+                let original = FileContent::Unknown("wasn't snapshotted before the change".into());
+                self.review_file(path, original)
+            }))
+            .filter(ReviewFile::is_changed)
+            .collect();
+        files.sort_by(|a, b| a.display_path.cmp(&b.display_path));
+        self.ui.popups.change_review.open(files);
+        self.ui.popup = Popup::ChangeReview;
+    }
+
+    /// Handle change review popup keyboard events
+    pub fn handle_change_review_key(&mut self, code: KeyCode, modifiers: KeyModifiers) {
+        let popup = &mut self.ui.popups.change_review;
+
+        if let Some(ref mut message) = popup.message {
+            match code {
+                KeyCode::Esc => popup.message = None,
+                KeyCode::Enter => self.commit_changes(),
+                KeyCode::Backspace => {
+                    message.pop();
+                }
+                KeyCode::Char(c) if !modifiers.contains(KeyModifiers::CONTROL) => {
+                    message.push(c);
+                }
+                _ => {}
+            }
+            return;
+        }
+
+        match code {
+            KeyCode::Esc | KeyCode::Char('q') => self.ui.popup = Popup::None,
+            KeyCode::Up | KeyCode::Char('k') => popup.prev_hunk(),
+            KeyCode::Down | KeyCode::Char('j') => popup.next_hunk(),
+            KeyCode::Left | KeyCode::Char('h') | KeyCode::BackTab => popup.prev_file(),
+            KeyCode::Right | KeyCode::Char('l') | KeyCode::Tab => popup.next_file(),
+            KeyCode::PageUp => popup.scroll_up(10),
+            KeyCode::PageDown => popup.scroll_down(10),
+            KeyCode::Char('r') => self.revert_selected(false),
+            KeyCode::Char('R') => self.revert_selected(true),
+            KeyCode::Char('a') => self.accept_changes(),
+            KeyCode::Char('c') if !popup.files.is_empty() => {
+                popup.status = None;
+                popup.message = Some(self.runtime.session_title.clone().unwrap_or_default());
+            }
+            _ => {}
+        }
+    }
+
+    /// Sink recording the files tools touch in the current session
+    pub(crate) fn file_change_sink(&self) -> Option<SharedFileChangeSink> {
+        let db = self.services.transcript_db.clone()?;
+        let session_id = self.runtime.current_session_id.clone()?;
+        Some(Arc::new(StoredFileChanges::new(db, session_id)))
+    }
+
+    fn file_tracker(&self) -> Option<FileActivityTracker<'_>> {
+        let sm = self.services.session_manager.as_ref()?;
+        let session_id = self.runtime.current_session_id.clone()?;
+        Some(FileActivityTracker::new(sm.db(), session_id))
+    }
+
+    /// A snapshotted file with its current content from disk
+    fn review_file(&self, path: String, original: FileContent) -> ReviewFile {
+        let current = FileContent::read(&path);
+        let display_path = std::path::Path::new(&path)
+            .strip_prefix(&self.runtime.working_dir)
+            .map(|p| p.display().to_string())
+            .unwrap_or_else(|_| path.clone());
+        ReviewFile::new(path, display_path, original, current)
+    }
+
+    /// Undo the selected hunk, or the whole selected file
+    fn revert_selected(&mut self, whole_file: bool) {
+        let popup = &mut self.ui.popups.change_review;
+        let Some(file) = popup.selected() else {
+            return;
+        };
+        let reverted = if whole_file {
+            file.reverted_file()
+        } else {
+            file.reverted_hunk(popup.selected_hunk)
+        };
+        let result = match reverted {
+            Ok(Some(content)) => std::fs::write(&file.path, content),
+            Ok(None) if file.current == FileContent::Missing => Ok(()),
+            Ok(None) => std::fs::remove_file(&file.path),
+            Err(e) => {
+                popup.status = Some((e, true));
+                return;
+            }
+        };
+        let path = file.path.clone();
+        let original = file.original.clone();
+        if let Err(e) = result {
+            popup.status = Some((format!("Failed to revert {}: {}", path, e), true));
+            return;
+        }
+
+        let file = self.review_file(path, original);
+        let popup = &mut self.ui.popups.change_review;
+        popup.status = Some((format!("Reverted {}", file.display_path), false));
+        popup.update_file(file);
+    }
+
+    /// Make the current content of the listed files their new baseline, so
+    /// they drop out of the review
+    fn set_baselines(&self, files: &[ReviewFile]) -> anyhow::Result<()> {
+        let Some(tracker) = self.file_tracker() else {
+            return Ok(());
+        };
+        for file in files {
+            if let Some(content) = file.current.baseline() {
+                tracker.set_baseline(&file.path, content)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Keep every change: the current content becomes the new baseline
+    fn accept_changes(&mut self) {
+        let files = &self.ui.popups.change_review.files;
+        if let Err(e) = self.set_baselines(files) {
+            self.ui.popups.change_review.status = Some((format!("Failed to accept: {}", e), true));
+            return;
+        }
+        let count = files.len();
+        self.ui.popup = Popup::None;
+        self.show_toast(Toast::success(format!(
+            "Accepted changes to {} file(s)",
+            count
+        )));
+    }
+
+    /// Commit the reviewed files with the typed message
+    fn commit_changes(&mut self) {
+        let popup = &mut self.ui.popups.change_review;
+        let message = popup.message.take().unwrap_or_default();
+        if message.trim().is_empty() {
+            popup.status = Some(("Commit message is empty".to_string(), true));
+            return;
+        }
+        let working_dir = &self.runtime.working_dir;
+        // git refuses the whole commit if any path is outside the repo
+        let roots: Vec<PathBuf> = std::iter::once(working_dir.clone())
+            .chain(repo_top_level(working_dir))
+            .collect();
+        let (inside, outside): (Vec<&ReviewFile>, Vec<&ReviewFile>) = popup
+            .files
+            .iter()
+            .partition(|f| is_inside(&working_dir.join(&f.path), &roots));
+        if inside.is_empty() {
+            popup.status = Some((
+                "None of the changed files are in this repository".to_string(),
+                true,
+            ));
+            return;
+        }
+        let paths: Vec<String> = inside.iter().map(|f| f.path.clone()).collect();
+        let committed: Vec<ReviewFile> = inside.into_iter().cloned().collect();
+        let skipped: Vec<String> = outside.iter().map(|f| f.display_path.clone()).collect();
+        let identity = self
+            .services
+            .preferences
+            .as_ref()
+            .map(|prefs| prefs.get_git_identity())
+            .unwrap_or_default();
+
+        match git_commit(working_dir, &paths, &message, &identity) {
+            Ok(summary) => {
+                if let Err(e) = self.set_baselines(&committed) {
+                    tracing::warn!("Failed to update baselines after commit: {}", e);
+                }
+                if skipped.is_empty() {
+                    self.ui.popup = Popup::None;
+                    self.show_toast(Toast::success(summary));
+                } else {
+                    // Leave the skipped files in the review
+                    let popup = &mut self.ui.popups.change_review;
+                    let remaining = popup
+                        .files
+                        .iter()
+                        .filter(|f| !paths.contains(&f.path))
+                        .cloned()
+                        .collect();
+                    popup.open(remaining);
+                    popup.status = Some((
+                        format!(
+                            "{}; skipped files outside the repository: {}",
+                            summary,
+                            skipped.join(", ")
+                        ),
+                        false,
+                    ));
+                }
+            }
+            Err(e) => {
+                self.ui.popups.change_review.status = Some((e, true));
+            }
+        }
+    }
+}
+
+/// Top level of the git repository containing `working_dir`
+fn repo_top_level(working_dir: &Path) -> Option<PathBuf> {
+    let output = Command::new("git")
+        .current_dir(working_dir)
+        .args(["rev-parse", "--show-toplevel"])
+        .output()
+        .ok()?;
+    output.status.success().then(|| {
+        PathBuf::from(String::from_utf8_lossy(&output.stdout).trim_end_matches(['\n', '\r']))
+    })
+}
+
+/// Whether `path` is under one of `roots`, comparing real paths where
+/// they exist (a deleted file is checked through its directory)
+fn is_inside(path: &Path, roots: &[PathBuf]) -> bool {
+    let real = path
+        .canonicalize()
+        .ok()
+        .or_else(|| {
+            let parent = path.parent()?.canonicalize().ok()?;
+            Some(parent.join(path.file_name()?))
+        })
+        .unwrap_or_else(|| path.to_path_buf());
+    roots.iter().any(|root| {
+        let real_root = root.canonicalize().unwrap_or_else(|_| root.clone());
+        real.starts_with(&real_root)
+    })
+}
+
+/// Stage and commit `paths`, attributed according to `identity`. Returns
+/// git's one-line summary, or its error output
+fn git_commit(
+    working_dir: &Path,
+    paths: &[String],
+    message: &str,
+    identity: &GitIdentity,
+) -> Result<String, String> {
+    let run = |args: &[&str]| {
+        let mut command = Command::new("git");
+        command.current_dir(working_dir).args(args).args(["--"]);
+        command.args(paths);
+        command.envs(identity.env_vars());
+        let output = command
+            .output()
+            .map_err(|e| format!("Failed to run git: {}", e))?;
+        if output.status.success() {
+            Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+        } else {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            let error = if stderr.trim().is_empty() {
+                stdout
+            } else {
+                stderr
+            };
+            Err(error.lines().next().unwrap_or("git failed").to_string())
+        }
+    };
+
+    run(&["add", "-A"])?;
+    let trailer = identity.trailer_line();
+    let mut args = vec!["commit", "-m", message];
+    if identity.mode == GitIdentityMode::CoAuthor {
+        args.extend(["--trailer", &trailer]);
+    }
+    let output = run(&args)?;
+    Ok(output
+        .lines()
+        .next()
+        .unwrap_or("Committed changes")
+        .to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_paths_outside_the_repo_are_left_out() {
+        let repo = tempfile::tempdir().unwrap();
+        let other = tempfile::tempdir().unwrap();
+        std::fs::create_dir(repo.path().join("src")).unwrap();
+        std::fs::write(repo.path().join("src/lib.rs"), "").unwrap();
+        std::fs::write(other.path().join("out.txt"), "").unwrap();
+        let roots = [repo.path().to_path_buf()];
+
+        assert!(is_inside(&repo.path().join("src/lib.rs"), &roots));
+        // Deleted files are still matched through their directory
+        assert!(is_inside(&repo.path().join("src/gone.rs"), &roots));
+        assert!(!is_inside(&other.path().join("out.txt"), &roots));
+        assert!(!is_inside(&repo.path().join("../escape.txt"), &roots));
+    }
+}
@@ -0,0 +1,101 @@
+//! Docs browser popup keyboard handler
+
+use crossterm::event::KeyCode;
+
+use crate::tui::app::{App, Popup};
+use crate::tui::utils::DocsStatusUpdate;
+use krusty_core::tools::register_docs_search_tool;
+
+impl App {
+    /// Handle docs browser popup keyboard events
+    pub fn handle_docs_popup_key(&mut self, code: KeyCode) {
+        match code {
+            KeyCode::Esc => {
+                if self.ui.popups.docs.search_active {
+                    self.ui.popups.docs.toggle_search();
+                } else {
+                    self.ui.popup = Popup::None;
+                }
+            }
+            KeyCode::Up => self.ui.popups.docs.prev(),
+            KeyCode::Down => self.ui.popups.docs.next(),
+            KeyCode::Enter => self.docs_index_selected(),
+            KeyCode::Tab if self.ui.popups.docs.search_active => {
+                self.ui.popups.docs.next_provider();
+            }
+            KeyCode::Char('k') if !self.ui.popups.docs.search_active => {
+                self.ui.popups.docs.prev();
+            }
+            KeyCode::Char('j') if !self.ui.popups.docs.search_active => {
+                self.ui.popups.docs.next();
+            }
+            KeyCode::Char('/') if !self.ui.popups.docs.search_active => {
+                self.ui.popups.docs.toggle_search();
+            }
+            KeyCode::Backspace if self.ui.popups.docs.search_active => {
+                self.ui.popups.docs.backspace_search();
+            }
+            KeyCode::Char(c) if self.ui.popups.docs.search_active => {
+                self.ui.popups.docs.add_search_char(c);
+            }
+            _ => {}
+        }
+    }
+
+    /// Index the selected (or typed) package in the background
+    fn docs_index_selected(&mut self) {
+        if let Some(ref package) = self.ui.popups.docs.indexing {
+            self.ui
+                .popups
+                .docs
+                .set_status(format!("Already indexing {}...", package));
+            return;
+        }
+
+        let Some((provider_name, package)) = self.ui.popups.docs.selection() else {
+            return;
+        };
+        let Some(provider) = self
+            .ui
+            .popups
+            .docs
+            .providers
+            .iter()
+            .find(|p| p.name == provider_name)
+            .cloned()
+        else {
+            self.ui
+                .popups
+                .docs
+                .set_status(format!("Provider {} is not installed", provider_name));
+            return;
+        };
+
+        let docs = self.services.docs_manager.clone();
+        let registry = self.services.tool_registry.clone();
+        let status_tx = self.services.docs_status_tx.clone();
+
+        self.ui.popups.docs.indexing = Some(package.clone());
+        self.ui
+            .popups
+            .docs
+            .set_status(format!("Indexing {}...", package));
+
+        tokio::spawn(async move {
+            let update = match docs.index_package(&provider, &package).await {
+                Ok(entries) => {
+                    register_docs_search_tool(&registry, docs.clone()).await;
+                    DocsStatusUpdate::Indexed {
+                        provider: provider.name,
+                        package,
+                        entries,
+                    }
+                }
+                Err(e) => DocsStatusUpdate::Failed {
+                    message: format!("{:#}", e),
+                },
+            };
+            let _ = status_tx.send(update);
+        });
+    }
+}
@@ -4,16 +4,21 @@
 //! Each popup type has its own module for focused, testable handlers.
 
 mod auth;
+mod change_review;
+mod docs;
 mod file_preview;
 mod hooks;
 mod mcp;
 mod pinch;
 mod process;
+mod session_search;
 mod skills;
+mod transcript;
 
 use crossterm::event::{KeyCode, KeyModifiers};
 
 use crate::ai::client::AiClient;
+use crate::ai::routing::{ModelRole, ModelRoute};
 use crate::tui::app::{App, Popup};
 
 impl App {
@@ -23,6 +28,10 @@ impl App {
             Popup::Help => match code {
                 KeyCode::Esc => self.ui.popup = Popup::None,
                 KeyCode::Tab => self.ui.popups.help.next_tab(),
+                KeyCode::Up => self.ui.popups.help.scroll_up(1),
+                KeyCode::Down => self.ui.popups.help.scroll_down(1),
+                KeyCode::PageUp => self.ui.popups.help.scroll_up(10),
+                KeyCode::PageDown => self.ui.popups.help.scroll_down(10),
                 _ => {}
             },
             Popup::ThemeSelect => {
@@ -61,6 +70,9 @@ impl App {
             Popup::SessionList => {
                 self.handle_session_list_key(code);
             }
+            Popup::SessionSearch => {
+                self.handle_session_search_key(code, modifiers);
+            }
             Popup::Auth => {
                 self.handle_auth_popup_key(code, modifiers);
             }
@@ -82,6 +94,15 @@ impl App {
             Popup::Hooks => {
                 self.handle_hooks_popup_key(code);
             }
+            Popup::DocsBrowser => {
+                self.handle_docs_popup_key(code);
+            }
+            Popup::ChangeReview => {
+                self.handle_change_review_key(code, modifiers);
+            }
+            Popup::Transcript => {
+                self.handle_transcript_popup_key(code);
+            }
             Popup::None => {}
         }
     }
@@ -98,10 +119,20 @@ impl App {
             }
         } else {
             match code {
-                KeyCode::Esc => self.ui.popup = Popup::None,
+                KeyCode::Esc => {
+                    self.runtime.branches.resend = None;
+                    self.ui.popup = Popup::None;
+                }
                 KeyCode::Up | KeyCode::Char('k') => self.ui.popups.model.prev(),
                 KeyCode::Down | KeyCode::Char('j') => self.ui.popups.model.next(),
                 KeyCode::Char('i') | KeyCode::Char('/') => self.ui.popups.model.toggle_search(),
+                KeyCode::Tab => self.ui.popups.model.next_role(),
+                KeyCode::Delete | KeyCode::Backspace => {
+                    let role = self.ui.popups.model.role;
+                    if role != ModelRole::Main {
+                        self.set_model_route(role, None);
+                    }
+                }
                 KeyCode::Enter => self.confirm_model_selection(),
                 _ => {}
             }
@@ -112,6 +143,30 @@ impl App {
     fn confirm_model_selection(&mut self) {
         let metadata = self.ui.popups.model.get_selected_metadata().cloned();
 
+        // Side roles just get routed; the conversation stays on the main model
+        let role = self.ui.popups.model.role;
+        if role != ModelRole::Main {
+            if let Some(metadata) = metadata {
+                if !self.configured_providers().contains(&metadata.provider) {
+                    self.ui.popups.model.set_error(format!(
+                        "{} is not configured. Add credentials with /auth first.",
+                        metadata.provider
+                    ));
+                    return;
+                }
+                self.set_model_route(
+                    role,
+                    Some(ModelRoute {
+                        provider: metadata.provider,
+                        model: metadata.id,
+                    }),
+                );
+                self.runtime.branches.resend = None;
+                self.ui.popup = Popup::None;
+            }
+            return;
+        }
+
         if let Some(metadata) = metadata {
             // Check if current context exceeds new model's limit
             if self.runtime.context_tokens_used > metadata.context_window {
@@ -140,6 +195,8 @@ impl App {
                     if let Some(key) = &self.runtime.api_key {
                         self.runtime.ai_client = Some(AiClient::with_api_key(config, key.clone()));
                     }
+                    // Unrouted sub-agents follow the main client's provider
+                    futures::executor::block_on(self.register_explore_tool_if_client());
                 }
 
                 // Mark model as recently used
@@ -157,6 +214,8 @@ impl App {
                 }
 
                 self.ui.popup = Popup::None;
+                // `m` on an earlier message: send it again on a branch
+                self.resend_to_current_model();
             }
         }
     }
@@ -167,6 +226,7 @@ impl App {
             KeyCode::Esc => self.ui.popup = Popup::None,
             KeyCode::Up | KeyCode::Char('k') => self.ui.popups.session.prev(),
             KeyCode::Down | KeyCode::Char('j') => self.ui.popups.session.next(),
+            KeyCode::Char('/') => self.open_session_search(""),
             KeyCode::Char('d') | KeyCode::Delete => {
                 if let Some(session) = self.ui.popups.session.delete_selected() {
                     self.delete_session(&session.id);
@@ -0,0 +1,90 @@
+//! Session search popup keyboard handler
+
+use crossterm::event::{KeyCode, KeyModifiers};
+
+use crate::tui::app::{App, Popup, View};
+use crate::tui::popups::session_search::SEARCH_LIMIT;
+
+impl App {
+    /// Open the search popup, searching right away if a query is given
+    pub fn open_session_search(&mut self, query: &str) {
+        let current_dir = self.runtime.working_dir.to_string_lossy().into_owned();
+        self.ui
+            .popups
+            .session_search
+            .open(&current_dir, query.trim());
+        self.run_session_search();
+        self.ui.popup = Popup::SessionSearch;
+    }
+
+    /// Handle session search popup keyboard events
+    pub fn handle_session_search_key(&mut self, code: KeyCode, modifiers: KeyModifiers) {
+        let popup = &mut self.ui.popups.session_search;
+        match code {
+            KeyCode::Esc => self.ui.popup = Popup::None,
+            KeyCode::Up => popup.prev(),
+            KeyCode::Down => popup.next(),
+            KeyCode::Enter => self.open_search_hit(),
+            KeyCode::Tab => {
+                popup.next_range();
+                self.run_session_search();
+            }
+            KeyCode::Char('d') if modifiers.contains(KeyModifiers::CONTROL) => {
+                popup.toggle_scope();
+                self.run_session_search();
+            }
+            KeyCode::Backspace => {
+                popup.query.pop();
+                self.run_session_search();
+            }
+            KeyCode::Char(c) if !modifiers.contains(KeyModifiers::CONTROL) => {
+                popup.query.push(c);
+                self.run_session_search();
+            }
+            _ => {}
+        }
+    }
+
+    /// Re-run the search for the current query and filters
+    fn run_session_search(&mut self) {
+        let popup = &mut self.ui.popups.session_search;
+        let Some(sm) = &self.services.session_manager else {
+            popup.error = Some("Session storage is unavailable".to_string());
+            return;
+        };
+        match sm.search_messages(&popup.query, &popup.filter(), SEARCH_LIMIT) {
+            Ok(hits) => {
+                popup.error = None;
+                popup.set_hits(hits);
+            }
+            Err(e) => {
+                tracing::warn!("Session search failed: {}", e);
+                popup.error = Some(format!("Search failed: {}", e));
+                popup.set_hits(Vec::new());
+            }
+        }
+    }
+
+    /// Load the selected hit's session and scroll to the message
+    fn open_search_hit(&mut self) {
+        let Some(hit) = self.ui.popups.session_search.get_selected_hit().cloned() else {
+            return;
+        };
+        self.save_block_ui_states();
+        if let Err(e) = self.load_session(&hit.session_id) {
+            self.runtime.chat.messages.push((
+                "system".to_string(),
+                format!("Failed to load session: {}", e),
+            ));
+        } else {
+            if let Some(msg_idx) = self.display_index_for_message(hit.message_index) {
+                self.ui
+                    .scroll_system
+                    .scroll
+                    .request_scroll_to_message(msg_idx);
+            }
+            self.ui.pending_view_change = Some(View::Chat);
+        }
+        self.ui.popup = Popup::None;
+    }
+}
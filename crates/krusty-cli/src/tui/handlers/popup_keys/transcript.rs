@@ -0,0 +1,25 @@
+//! Transcript popup keyboard handler
+
+use crossterm::event::KeyCode;
+
+use crate::tui::app::{App, Popup};
+
+/// Lines moved by PgUp/PgDn
+const PAGE: usize = 10;
+
+impl App {
+    pub fn handle_transcript_popup_key(&mut self, code: KeyCode) {
+        let popup = &mut self.ui.popups.transcript;
+        match code {
+            KeyCode::Esc | KeyCode::Char('q') => self.ui.popup = Popup::None,
+            KeyCode::Up | KeyCode::Char('k') => popup.scroll_up(1),
+            KeyCode::Down | KeyCode::Char('j') => popup.scroll_down(1),
+            KeyCode::PageUp => popup.scroll_up(PAGE),
+            KeyCode::PageDown => popup.scroll_down(PAGE),
+            KeyCode::Home | KeyCode::Char('g') => popup.scroll_to_top(),
+            KeyCode::End | KeyCode::Char('G') => popup.scroll_to_bottom(),
+            KeyCode::Char('r') => self.reload_transcript(),
+            _ => {}
+        }
+    }
+}
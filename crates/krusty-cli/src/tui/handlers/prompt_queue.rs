@@ -0,0 +1,217 @@
+//! Queued prompts
+//!
+//! Prompts submitted while a turn is streaming or tools are running wait as
+//! chips above the input. `Inject` chips ride along with the next tool
+//! results (see `handle_tool_results`); the rest are sent one at a time as
+//! new turns once the agent is idle. Other slash commands wait for the
+//! turn to end, except `/queue`, which only touches the queue.
+
+use crossterm::event::{KeyCode, KeyModifiers};
+
+use crate::tui::app::{App, Popup, View};
+use crate::tui::components::Toast;
+use crate::tui::utils::Delivery;
+
+/// What happens to a line submitted while the agent is busy
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum BusySubmit {
+    /// Queue it as a prompt
+    Queue,
+    /// Run the command now
+    Run,
+    /// Refuse the command until the turn ends
+    Wait,
+}
+
+impl App {
+    /// Route a line submitted while the agent is busy
+    pub(crate) fn busy_submit(text: &str) -> BusySubmit {
+        if !text.starts_with('/') || Self::looks_like_file_path(text) {
+            BusySubmit::Queue
+        } else if text.split_whitespace().next() == Some("/queue") {
+            BusySubmit::Run
+        } else {
+            BusySubmit::Wait
+        }
+    }
+
+    /// Queue a prompt submitted while the agent is busy
+    pub(crate) fn queue_prompt(&mut self, text: String) {
+        self.runtime.prompt_queue.push(text);
+        self.ui.input.clear();
+        self.ui.autocomplete.hide();
+        tracing::info!(
+            queued = self.runtime.prompt_queue.items().len(),
+            "Queued prompt while agent is working"
+        );
+    }
+
+    /// Keys for the queued prompt chips. Returns true if the key was used
+    pub(crate) fn handle_prompt_queue_key(
+        &mut self,
+        code: KeyCode,
+        modifiers: KeyModifiers,
+    ) -> bool {
+        if self.runtime.prompt_queue.is_empty() || self.runtime.agent_steer.is_some() {
+            return false;
+        }
+        let input_empty = self.ui.input.content().is_empty();
+        let queue = &mut self.runtime.prompt_queue;
+
+        if queue.selected.is_none() {
+            if !modifiers.is_empty() || !input_empty {
+                return false;
+            }
+            match code {
+                // ↑ from an empty input picks the last chip
+                KeyCode::Up => queue.select_prev(),
+                // Enter on an empty input resumes a paused queue
+                KeyCode::Enter if queue.paused && !self.runtime.chat.is_busy() => {
+                    queue.paused = false;
+                }
+                _ => return false,
+            }
+            return true;
+        }
+
+        match code {
+            KeyCode::Up | KeyCode::Left => queue.select_prev(),
+            KeyCode::Down | KeyCode::Right => queue.select_next(),
+            KeyCode::Tab => queue.toggle_selected(),
+            KeyCode::Delete | KeyCode::Backspace => queue.remove_selected(),
+            KeyCode::Esc => queue.selected = None,
+            KeyCode::Enter => {
+                if !input_empty {
+                    self.show_toast(Toast::warning(
+                        "Send or clear the input to edit a queued prompt",
+                    ));
+                } else if let Some(text) = queue.edit_selected() {
+                    self.ui.input.insert_text(&text);
+                }
+            }
+            // Anything else goes back to typing in the input
+            _ => {
+                queue.selected = None;
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Send the next queued prompt once the agent is idle
+    pub(crate) fn poll_prompt_queue(&mut self) {
+        let queue = &mut self.runtime.prompt_queue;
+        if queue.is_empty() || queue.paused || queue.selected.is_some() {
+            return;
+        }
+        // Hold the queue while a chip is being edited so it keeps its place
+        if queue.is_editing() {
+            if !self.ui.input.content().is_empty() {
+                return;
+            }
+            queue.finish_edit();
+        }
+
+        let idle = !self.runtime.chat.is_busy()
+            && !self.runtime.streaming.is_ready_for_tools()
+            && !self.ui.decision_prompt.visible
+            && self.runtime.pending_task_checks == 0
+            && self.runtime.pending_tool_results.is_empty()
+            && !self.runtime.pending_auto_pinch
+            && !self.runtime.auto_pinch_in_progress
+            && self.runtime.agent_steer.is_none()
+            && self.ui.popup == Popup::None
+            && self.ui.view == View::Chat;
+        if !idle {
+            return;
+        }
+
+        if let Some(prompt) = self.runtime.prompt_queue.pop_next() {
+            tracing::info!(
+                remaining = self.runtime.prompt_queue.items().len(),
+                "Sending queued prompt"
+            );
+            self.handle_input_submit(prompt.text);
+            self.ui.needs_redraw = true;
+        }
+    }
+
+    /// `/queue [next|inject|clear]`: show the queue, set the delivery for new
+    /// prompts, or drop everything queued
+    pub(crate) fn handle_queue_command(&mut self, arg: Option<&str>) {
+        let queue = &mut self.runtime.prompt_queue;
+        let msg = match arg {
+            None => {
+                let mut msg = format!(
+                    "New prompts queued while the agent works are sent as '{}'.",
+                    queue.default_delivery.name()
+                );
+                if queue.is_empty() {
+                    msg.push_str(" Nothing is queued.");
+                } else {
+                    for (i, prompt) in queue.items().iter().enumerate() {
+                        msg.push_str(&format!(
+                            "\n  {}. [{}] {}",
+                            i + 1,
+                            prompt.delivery.name(),
+                            prompt.text.lines().next().unwrap_or("")
+                        ));
+                    }
+                    if queue.paused {
+                        msg.push_str(
+                            "\nThe queue is paused. Press Enter on an empty input to resume.",
+                        );
+                    }
+                }
+                msg
+            }
+            Some("clear") => {
+                let count = queue.items().len();
+                queue.clear();
+                format!("Dropped {} queued prompt(s).", count)
+            }
+            Some(name) => match Delivery::from_name(name) {
+                Some(delivery) => {
+                    queue.default_delivery = delivery;
+                    if let Some(ref prefs) = self.services.preferences {
+                        if let Err(e) = prefs.set_queue_delivery(delivery.name()) {
+                            tracing::warn!("Failed to save queue delivery: {}", e);
+                        }
+                    }
+                    match delivery {
+                        Delivery::NextTurn => {
+                            "Queued prompts will be sent as the next turn.".to_string()
+                        }
+                        Delivery::Inject => {
+                            "Queued prompts will be added to the next tool results.".to_string()
+                        }
+                    }
+                }
+                None => "Usage: /queue [next|inject|clear]".to_string(),
+            },
+        };
+        self.runtime.chat.messages.push(("system".to_string(), msg));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_busy_submit_lets_queue_commands_through() {
+        for (text, expected) in [
+            ("also check the tests", BusySubmit::Queue),
+            ("/home/me/notes.txt says hi", BusySubmit::Queue),
+            ("/screenshot.png", BusySubmit::Queue),
+            ("/queue", BusySubmit::Run),
+            ("/queue inject", BusySubmit::Run),
+            ("/queue clear", BusySubmit::Run),
+            ("/queued", BusySubmit::Wait),
+            ("/model", BusySubmit::Wait),
+            ("/plan run", BusySubmit::Wait),
+        ] {
+            assert_eq!(App::busy_submit(text), expected, "{}", text);
+        }
+    }
+}
@@ -8,7 +8,8 @@ use anyhow::Result;
 
 use crate::ai::client::AiClient;
 use crate::ai::providers::ProviderId;
-use crate::tools::{register_build_tool, register_explore_tool};
+use crate::ai::routing::{ModelRole, ModelRoute};
+use crate::tools::{register_build_tool, register_delegate_tool, register_explore_tool};
 use crate::tui::app::App;
 
 impl App {
@@ -39,7 +40,10 @@ impl App {
         Ok(())
     }
 
-    /// Register explore and build tools if client is available
+    /// Register explore, build and delegate tools if client is available
+    ///
+    /// Explore and build get clients for their routes, so call this again
+    /// after the routing or the main model changes.
     pub(crate) async fn register_explore_tool_if_client(&mut self) {
         let client = self.create_ai_client();
 
@@ -47,25 +51,40 @@ impl App {
             let client = Arc::new(client);
 
             // Register explore tool
-            register_explore_tool(
-                &self.services.tool_registry,
-                client.clone(),
-                self.runtime.cancellation.clone(),
-            )
-            .await;
+            if let Some(explore_client) = self.create_role_client(ModelRole::Explore) {
+                register_explore_tool(
+                    &self.services.tool_registry,
+                    Arc::new(explore_client),
+                    self.runtime.cancellation.clone(),
+                    self.routed_model(ModelRole::Explore),
+                )
+                .await;
+            }
 
             // Register build tool (The Kraken)
-            register_build_tool(
+            if let Some(build_client) = self.create_role_client(ModelRole::Build) {
+                register_build_tool(
+                    &self.services.tool_registry,
+                    Arc::new(build_client),
+                    self.runtime.cancellation.clone(),
+                    self.routed_model(ModelRole::Build),
+                )
+                .await;
+            }
+
+            // Register delegate tool for user-defined agents
+            register_delegate_tool(
                 &self.services.tool_registry,
                 client,
                 self.runtime.cancellation.clone(),
+                self.services.agents_manager.clone(),
             )
             .await;
 
-            // Update cached tools so API knows about explore and build
+            // Update cached tools so API knows about explore, build and delegate
             self.services.cached_ai_tools = self.services.tool_registry.get_ai_tools().await;
             tracing::info!(
-                "Registered explore and build tools, total tools: {}",
+                "Registered sub-agent tools, total tools: {}",
                 self.services.cached_ai_tools.len()
             );
         }
@@ -90,6 +109,81 @@ impl App {
             .map(|key| AiClient::with_api_key(config, key.clone()))
     }
 
+    /// Create a client for another model of the active provider
+    pub fn create_model_client(&self, model: &str) -> Option<AiClient> {
+        let config = crate::tui::auth::create_client_config(
+            self.runtime.active_provider,
+            model,
+            &self.services.credential_store,
+            &self.services.model_registry,
+        );
+        self.runtime
+            .api_key
+            .as_ref()
+            .map(|key| AiClient::with_api_key(config, key.clone()))
+    }
+
+    /// Create a client for a role, following its route when one is set
+    ///
+    /// Falls back to the main client if the routed provider has no
+    /// credentials.
+    pub fn create_role_client(&self, role: ModelRole) -> Option<AiClient> {
+        match self.usable_route(role) {
+            Some((route, key)) => {
+                let config = crate::tui::auth::create_client_config(
+                    route.provider,
+                    &route.model,
+                    &self.services.credential_store,
+                    &self.services.model_registry,
+                );
+                Some(AiClient::with_api_key(config, key))
+            }
+            None => self.create_ai_client(),
+        }
+    }
+
+    /// Model a role is routed to, if its route can be used
+    ///
+    /// `None` means the role runs on the main model.
+    pub fn routed_model(&self, role: ModelRole) -> Option<String> {
+        self.usable_route(role).map(|(route, _)| route.model)
+    }
+
+    /// A role's route together with credentials for its provider
+    fn usable_route(&self, role: ModelRole) -> Option<(ModelRoute, String)> {
+        let route = self.runtime.model_routing.route(role)?.clone();
+        let key = if route.provider == self.runtime.active_provider {
+            self.runtime.api_key.clone()
+        } else {
+            self.services.credential_store.get_auth(&route.provider)
+        };
+        match key {
+            Some(key) => Some((route, key)),
+            None => {
+                tracing::warn!(
+                    "No credentials for {} ({} route); using the main model",
+                    route.provider,
+                    role
+                );
+                None
+            }
+        }
+    }
+
+    /// Route a role to a model (or back to the main model) and apply it
+    pub fn set_model_route(&mut self, role: ModelRole, route: Option<ModelRoute>) {
+        self.runtime.model_routing.set_route(role, route);
+        if let Some(ref prefs) = self.services.preferences {
+            if let Err(e) = prefs.set_model_routing(&self.runtime.model_routing) {
+                tracing::warn!("Failed to save model routing: {}", e);
+            }
+        }
+        // Sub-agent tools hold their clients; rebuild them for the new route
+        if matches!(role, ModelRole::Explore | ModelRole::Build) {
+            futures::executor::block_on(self.register_explore_tool_if_client());
+        }
+    }
+
     /// Set API key for current provider and create client
     pub fn set_api_key(&mut self, key: String) {
         // Create client with provider config
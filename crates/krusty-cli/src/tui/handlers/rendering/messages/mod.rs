@@ -30,8 +30,8 @@ struct BlockPosition {
 }
 
 use ratatui::{
-    layout::Rect,
-    style::{Color, Style},
+    layout::{Alignment, Rect},
+    style::{Color, Modifier, Style},
     text::{Line, Span},
     widgets::{Block, BorderType, Borders, Paragraph},
     Frame,
@@ -45,7 +45,8 @@ use crate::tui::app::App;
 use crate::tui::blocks::{ClipContext, StreamBlock};
 use crate::tui::markdown::{apply_hyperlinks, apply_link_hover_style, RenderedMarkdown};
 use crate::tui::state::SelectionArea;
-use crate::tui::utils::wrap_line;
+use crate::tui::themes::Theme;
+use crate::tui::utils::{wrap_line, BranchLabel};
 
 use selection::{
     apply_selection_to_line, apply_selection_to_rendered_line, style_user_line_with_file_refs,
@@ -53,6 +54,7 @@ use selection::{
 
 /// Symbol prefixes for message types (with trailing space)
 const USER_SYMBOL: &str = "⤷ "; // Curved down-right arrow
+const SELECTED_USER_SYMBOL: &str = "▶ "; // Message picked for branching
 const ASSISTANT_SYMBOL: &str = "⬡ "; // Hollow hexagon
 /// Display width of message symbols (symbol char + space)
 /// Used to reduce wrap width so prepending symbol doesn't cause overflow
@@ -79,6 +81,33 @@ fn clear_area(buf: &mut ratatui::buffer::Buffer, area: Rect, bg_color: Color) {
     }
 }
 
+/// Right-aligned `‹ 2/3 ›` under a message with sibling branches
+fn branch_label_line(label: &BranchLabel, theme: &Theme) -> Line<'static> {
+    let arrow = |enabled: bool, symbol: &'static str| {
+        let color = if enabled {
+            theme.accent_color
+        } else {
+            theme.dim_color
+        };
+        Span::styled(symbol, Style::default().fg(color))
+    };
+    let mut spans = vec![
+        arrow(label.current > 1, "‹ "),
+        Span::styled(
+            format!("{}/{}", label.current, label.total),
+            Style::default().fg(theme.text_color),
+        ),
+        arrow(label.current < label.total, " ›"),
+    ];
+    if let Some(ref model) = label.model {
+        spans.push(Span::styled(
+            format!("  {}", model),
+            Style::default().fg(theme.dim_color),
+        ));
+    }
+    Line::from(spans).alignment(Alignment::Right)
+}
+
 impl App {
     /// Helper: add block position to tracking arrays (used in both passes)
     fn track_block_position(
@@ -377,6 +406,11 @@ impl App {
 
         let mut lines: Vec<Line> = Vec::with_capacity(total_lines.min(viewport_height * 3));
         let mut line_idx: usize = 0;
+        let selected_user_msg = self
+            .runtime
+            .branches
+            .selected_message()
+            .map(|m| m.display_idx);
         let mut message_line_offsets: Vec<(usize, usize)> = Vec::new(); // (msg_idx, base_line)
         thinking_idx = 0;
         bash_idx = 0;
@@ -634,10 +668,19 @@ impl App {
                             let final_line =
                                 if role == "user" && is_first_line_of_msg && wrap_idx == 0 {
                                     is_first_line_of_msg = false;
-                                    let symbol = Span::styled(
-                                        USER_SYMBOL,
-                                        Style::default().fg(self.ui.theme.accent_color),
-                                    );
+                                    let symbol = if selected_user_msg == Some(msg_idx) {
+                                        Span::styled(
+                                            SELECTED_USER_SYMBOL,
+                                            Style::default()
+                                                .fg(self.ui.theme.accent_color)
+                                                .add_modifier(Modifier::BOLD),
+                                        )
+                                    } else {
+                                        Span::styled(
+                                            USER_SYMBOL,
+                                            Style::default().fg(self.ui.theme.accent_color),
+                                        )
+                                    };
                                     let mut spans = vec![symbol];
                                     spans.extend(content_line.spans);
                                     Line::from(spans)
@@ -651,7 +694,11 @@ impl App {
                     }
                 }
             }
-            lines.push(Line::from("")); // Blank between messages
+            // Blank between messages, carrying the ‹ n/m › of branched messages
+            match self.runtime.branches.label_at(msg_idx) {
+                Some(label) => lines.push(branch_label_line(label, &self.ui.theme)),
+                None => lines.push(Line::from("")),
+            }
             line_idx += 1;
         }
 
@@ -27,7 +27,11 @@ impl App {
         // Render popup on top - use reference matching for short-lived borrows
         match &self.ui.popup {
             Popup::None => {}
-            Popup::Help => self.ui.popups.help.render(f, &self.ui.theme),
+            Popup::Help => self
+                .ui
+                .popups
+                .help
+                .render(f, &self.ui.theme, &self.ui.keymap),
             Popup::ThemeSelect => {
                 let theme_name = self.ui.theme_name.clone();
                 self.ui.popups.theme.render(f, &self.ui.theme, &theme_name)
@@ -37,8 +41,11 @@ impl App {
                 &self.ui.theme,
                 &self.runtime.current_model,
                 self.runtime.context_tokens_used,
+                &self.runtime.model_routing,
             ),
             Popup::SessionList => self.ui.popups.session.render(f, &self.ui.theme),
+            Popup::SessionSearch => self.ui.popups.session_search.render(f, &self.ui.theme),
+            Popup::ChangeReview => self.ui.popups.change_review.render(f, &self.ui.theme),
             Popup::Auth => self.ui.popups.auth.render(f, &self.ui.theme),
             Popup::ProcessList => self.ui.popups.process.render(f, &self.ui.theme),
             Popup::Pinch => self.ui.popups.pinch.render(f, &self.ui.theme),
@@ -46,6 +53,8 @@ impl App {
             Popup::SkillsBrowser => self.ui.popups.skills.render(f, &self.ui.theme),
             Popup::McpBrowser => self.ui.popups.mcp.render(f, &self.ui.theme),
             Popup::Hooks => self.ui.popups.hooks.render(f, &self.ui.theme),
+            Popup::DocsBrowser => self.ui.popups.docs.render(f, &self.ui.theme),
+            Popup::Transcript => self.ui.popups.transcript.render(f, &self.ui.theme),
         }
 
         // Render toasts on top of everything
@@ -31,7 +31,10 @@ impl App {
         // Pre-render markdown to cache (same as render_messages) to ensure consistent line counts
         self.ui.markdown_cache.check_width(wrap_width);
 
-        for (role, content) in &self.runtime.chat.messages {
+        for (msg_idx, (role, content)) in self.runtime.chat.messages.iter().enumerate() {
+            if self.ui.scroll_system.scroll.scroll_to_message == Some(msg_idx) {
+                self.ui.scroll_system.scroll.message_target_line = Some(total);
+            }
             if let Some((block_type, idx)) = indices.get_and_increment(role) {
                 // Handle block types
                 let height = match block_type {
@@ -13,10 +13,12 @@ use ratatui::{
 use crate::tui::app::App;
 use crate::tui::blocks::StreamBlock;
 use crate::tui::components::{
-    render_input_scrollbar, render_messages_scrollbar, render_plan_sidebar, render_plugin_window,
+    branch_hint_height, prompt_queue_height, render_branch_hint, render_input_scrollbar,
+    render_messages_scrollbar, render_plan_sidebar, render_plugin_window, render_prompt_queue,
     render_status_bar, render_toolbar, MIN_TERMINAL_WIDTH,
 };
 use crate::tui::state::SelectionArea;
+use crate::tui::utils::{truncate_ellipsis, SteerMode};
 
 impl App {
     /// Render the start menu view
@@ -283,11 +285,13 @@ impl App {
             .set_max_visible_lines(input_area.height.saturating_sub(2));
 
         // Get input selection if selecting in input area
+        // Mouse selection wins over a Vim visual selection or Emacs region
         let input_selection = if self.ui.scroll_system.selection.area == SelectionArea::Input {
             self.ui.scroll_system.selection.normalized()
         } else {
             None
-        };
+        }
+        .or_else(|| self.ui.input.mode_selection());
 
         // Border color changes to accent when thinking mode enabled (Tab toggle)
         let input_border_color = if self.runtime.thinking_enabled {
@@ -364,6 +368,7 @@ impl App {
         }
 
         // Status bar (no context tokens in start menu)
+        let edit_mode = self.ui.input.mode_label();
         render_status_bar(
             f,
             chunks[4],
@@ -373,6 +378,7 @@ impl App {
             None,
             self.runtime.running_process_count,
             self.runtime.running_process_elapsed,
+            edit_mode.as_deref(),
         );
     }
 
@@ -431,7 +437,12 @@ impl App {
             .map(|tp| tp.height(pinned_render_width, &self.ui.theme))
             .unwrap_or(0);
 
-        // Layout: toolbar, pinned (0 if none), messages, prompt (0 if none), input, status
+        // Queued prompt chips (0 if the queue is empty)
+        let queue_height = prompt_queue_height(&self.runtime.prompt_queue, area.width);
+        // Branch keys (0 unless picking or rewriting an earlier message)
+        let branch_height = branch_hint_height(&self.runtime.branches);
+
+        // Layout: toolbar, pinned (0 if none), messages, prompt (0 if none), queue, branch, input, status
         let chunks = Layout::default()
             .direction(Direction::Vertical)
             .constraints([
@@ -439,6 +450,8 @@ impl App {
                 Constraint::Length(pinned_height), // Pinned terminal (0 if none)
                 Constraint::Min(5),                // Messages
                 Constraint::Length(prompt_height), // Decision prompt (0 if none)
+                Constraint::Length(queue_height),  // Queued prompts (0 if none)
+                Constraint::Length(branch_height), // Branch hint (0 if none)
                 Constraint::Length(input_height),  // Input
                 Constraint::Length(1),             // Status bar
             ])
@@ -509,6 +522,7 @@ impl App {
             .scroll
             .update_max_scroll(msg_total_lines, msg_visible_height);
         self.ui.scroll_system.scroll.apply_scroll_to_bottom();
+        self.ui.scroll_system.scroll.apply_scroll_to_message();
 
         // NOW render messages with correct scroll position
         self.render_messages(f, messages_chunk);
@@ -541,26 +555,36 @@ impl App {
             self.ui.scroll_system.layout.prompt_area = None;
         }
 
-        // Input area (chunks[4] with prompt, was chunks[3])
-        let input_area = chunks[4];
+        // Queued prompts (chunks[4])
+        render_prompt_queue(f, chunks[4], &self.ui.theme, &self.runtime.prompt_queue);
+
+        // Branch hint (chunks[5])
+        render_branch_hint(f, chunks[5], &self.ui.theme, &self.runtime.branches);
+
+        // Input area (chunks[6])
+        let input_area = chunks[6];
         self.ui.scroll_system.layout.input_area = Some(input_area);
         self.ui
             .input
             .set_max_visible_lines(input_area.height.saturating_sub(2));
 
         // Get input selection if selecting in input area
+        // Mouse selection wins over a Vim visual selection or Emacs region
         let input_selection = if self.ui.scroll_system.selection.area == SelectionArea::Input {
             self.ui.scroll_system.selection.normalized()
         } else {
             None
-        };
+        }
+        .or_else(|| self.ui.input.mode_selection());
 
         // Border color changes to accent when thinking mode enabled (Tab toggle)
-        let input_border_color = if self.runtime.thinking_enabled {
-            self.ui.theme.accent_color
-        } else {
-            self.ui.theme.border_color
-        };
+        // or while writing to a sub-agent
+        let input_border_color =
+            if self.runtime.thinking_enabled || self.runtime.agent_steer.is_some() {
+                self.ui.theme.accent_color
+            } else {
+                self.ui.theme.border_color
+            };
         // Get hover range for file ref highlighting
         let hover_range = self
             .ui
@@ -582,6 +606,22 @@ impl App {
         );
         f.render_widget(input_widget, input_area);
 
+        // Say which sub-agent the input goes to
+        if let Some(steer) = &self.runtime.agent_steer {
+            let action = match steer.mode {
+                SteerMode::Restart => "New prompt for",
+                SteerMode::Note => "Note to",
+            };
+            let label = format!(" {} {} · Enter send · Esc cancel ", action, steer.name);
+            let max = input_area.width.saturating_sub(4) as usize;
+            f.buffer_mut().set_string(
+                input_area.x + 2,
+                input_area.y,
+                truncate_ellipsis(&label, max),
+                Style::default().fg(self.ui.theme.accent_color),
+            );
+        }
+
         // Render input scrollbar (1 column wide, always shows track, thumb when content overflows)
         let total_lines = self.ui.input.get_wrapped_lines_count();
         let visible_lines = self.ui.input.get_max_visible_lines() as usize;
@@ -634,15 +674,17 @@ impl App {
         } else {
             None
         };
+        let edit_mode = self.ui.input.mode_label();
         render_status_bar(
             f,
-            chunks[5],
+            chunks[7],
             &self.ui.theme,
             &self.runtime.current_model,
             &self.runtime.working_dir,
             context_tokens,
             self.runtime.running_process_count,
             self.runtime.running_process_elapsed,
+            edit_mode.as_deref(),
         );
 
         // Render sidebar content (plan and/or plugin window)
@@ -70,9 +70,9 @@ impl App {
         });
     }
 
-    /// Create AI client for title generation
+    /// Create AI client for title generation (follows the title route)
     fn create_title_client(&self) -> Option<AiClient> {
-        self.create_ai_client()
+        self.create_role_client(crate::ai::routing::ModelRole::Title)
     }
 
     /// Poll for AI-generated title updates
@@ -195,6 +195,7 @@ impl App {
         self.ui.block_ui.clear();
         self.runtime.tool_results.clear();
         self.runtime.chat.streaming_assistant_idx = None;
+        self.runtime.prompt_queue.clear();
         self.runtime.current_session_id = Some(session_id.to_string());
 
         // Load plan for this session (strict 1:1 linkage, no working_dir fallback)
@@ -298,6 +299,8 @@ impl App {
         self.runtime.context_tokens_used = stored_token_count
             .unwrap_or_else(|| Self::estimate_conversation_tokens(&self.runtime.chat.conversation));
 
+        self.refresh_branches();
+
         tracing::info!(
             "Loaded session {} with {} messages, {} blocks, ~{} tokens",
             session_id,
@@ -312,6 +315,35 @@ impl App {
         Ok(())
     }
 
+    /// Chat message showing the text of conversation message `conv_idx`
+    ///
+    /// Matches by text, counting earlier messages with the same text so
+    /// repeated prompts land on the right one.
+    pub fn display_index_for_message(&self, conv_idx: usize) -> Option<usize> {
+        let conversation = &self.runtime.chat.conversation;
+        let text = conversation
+            .get(conv_idx)?
+            .content
+            .iter()
+            .find_map(|c| match c {
+                Content::Text { text } if text != "." => Some(text),
+                _ => None,
+            })?;
+        let earlier = conversation[..conv_idx]
+            .iter()
+            .flat_map(|msg| &msg.content)
+            .filter(|c| matches!(c, Content::Text { text: t } if t == text))
+            .count();
+        self.runtime
+            .chat
+            .messages
+            .iter()
+            .enumerate()
+            .filter(|(_, (_, content))| content == text)
+            .nth(earlier)
+            .map(|(idx, _)| idx)
+    }
+
     /// Estimate token count for a conversation (rough approximation: ~4 chars per token)
     /// Used as fallback for legacy sessions without stored token count
     fn estimate_conversation_tokens(conversation: &[ModelMessage]) -> usize {
@@ -327,7 +327,7 @@ impl App {
         }
 
         // NOTE: ExploreBlock is created in spawn_tool_execution where we have the tool_use_id
-        if name == "Task" || name == "explore" {
+        if matches!(name.as_str(), "Task" | "explore" | "delegate") {
             tracing::info!(
                 "handle_tool_start: explore tool '{}' detected, block will be created on execution",
                 name
@@ -346,6 +346,7 @@ impl App {
                 | "processes"
                 | "Task"
                 | "explore"
+                | "delegate"
                 | "build"
                 | "AskUserQuestion"
                 | "task_start"         // Silent - updates plan sidebar
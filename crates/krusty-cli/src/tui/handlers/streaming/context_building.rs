@@ -6,6 +6,7 @@
 //! - Project instructions
 
 use crate::tui::app::{App, WorkMode};
+use crate::tui::input::Action;
 
 /// Sanitize plan titles for safe markdown embedding
 /// Escapes backticks and quotes that could break formatting
@@ -29,7 +30,8 @@ impl App {
     fn build_plan_mode_context(&self) -> String {
         let Some(plan) = &self.runtime.active_plan else {
             // In plan mode but no active plan - provide instructions with format
-            return r#"[PLAN MODE ACTIVE]
+            return format!(
+                r#"[PLAN MODE ACTIVE]
 
 You are in PLAN MODE. The user wants to create a plan before implementing.
 
@@ -50,6 +52,7 @@ When creating a plan, use this EXACT format (the system will auto-detect and sav
 
 - [ ] Task description here
   > Context: Implementation details or notes
+  > Check: cargo test -p my-crate
 - [ ] Another task
   - [ ] Subtask for complex items
 
@@ -65,9 +68,12 @@ Key formatting rules:
 - Tasks: `- [ ] Description` (pending), `- [x] Description` (completed), `- [>] Description` (in-progress), `- [~] Description` (blocked)
 - Context: `> Context: details` - optional implementation notes
 - Dependencies: `> Blocked-By: task_ids` - tasks that must complete first
+- Checks: `> Check: command` (must exit 0), `> Check: exists: path`, or `> Check: grep: regex in path` - optional, verified before the task can be completed
 - Subtasks: Indent 2 spaces for subtasks under a parent task
 
-After exploring the codebase, output your plan in this format. The user can exit plan mode with Ctrl+B to begin implementation."#.to_string();
+After exploring the codebase, output your plan in this format. The user can exit plan mode{} to begin implementation."#,
+                self.global_key_hint(Action::ToggleWorkMode)
+            );
         };
 
         // Build context from active plan (truncated if large)
@@ -99,13 +105,14 @@ In plan mode:
 ---
 
 When working on tasks, update progress by telling the user which task you're working on.
-The user can exit plan mode with Ctrl+B when ready to implement."#,
+The user can exit plan mode{} when ready to implement."#,
             sanitize_plan_title(&plan.title),
             completed,
             total,
             ready_count,
             blocked_count,
-            markdown
+            markdown,
+            self.global_key_hint(Action::ToggleWorkMode)
         )
     }
 
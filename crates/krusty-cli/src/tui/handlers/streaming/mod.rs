@@ -21,6 +21,7 @@ use crate::ai::types::{
 use crate::tools::{load_from_clipboard_rgba, load_from_path, load_from_url};
 use crate::tui::app::{App, View};
 use crate::tui::input::{has_image_references, parse_input, InputSegment};
+use crate::tui::utils::CompactionUpdate;
 
 /// Maximum number of files allowed per message
 const MAX_FILES_PER_MESSAGE: usize = 20;
@@ -42,6 +43,18 @@ impl App {
             return;
         }
 
+        // A new prompt ends the limits of a custom command
+        self.runtime.active_command = None;
+        if let Some(at) = self.runtime.branches.editing.take() {
+            self.submit_edited_message(at, text);
+            return;
+        }
+        self.submit_user_message(text, None);
+    }
+
+    /// Send a user message to the AI, starting a session if needed.
+    /// `display` replaces the text shown in the chat
+    pub(crate) fn submit_user_message(&mut self, text: String, display: Option<String>) {
         if self.ui.view == View::StartMenu {
             self.ui.view = View::Chat;
         }
@@ -73,7 +86,7 @@ impl App {
         self.runtime
             .chat
             .messages
-            .push(("user".to_string(), display_text));
+            .push(("user".to_string(), display.unwrap_or(display_text)));
         let user_msg = ModelMessage {
             role: Role::User,
             content: content_blocks,
@@ -159,7 +172,7 @@ impl App {
 
     /// Check if text looks like a file path rather than a slash command
     /// Returns true for paths like /home/user/file.pdf, false for /help
-    fn looks_like_file_path(text: &str) -> bool {
+    pub(crate) fn looks_like_file_path(text: &str) -> bool {
         // Get the first "word" (text before any space)
         let first_word = text.split_whitespace().next().unwrap_or(text);
 
@@ -319,9 +332,20 @@ impl App {
                     }],
                 },
             );
+            system_insert_count += 1;
         }
 
-        let client = match self.create_ai_client() {
+        // A custom command may pick another model of the active provider
+        let command_model = self
+            .runtime
+            .active_command
+            .as_ref()
+            .and_then(|c| c.model.clone());
+        let client = match command_model {
+            Some(model) => self.create_model_client(&model),
+            None => self.create_ai_client(),
+        };
+        let client = match client {
             Some(c) => c,
             None => {
                 self.runtime.chat.messages.push((
@@ -332,7 +356,10 @@ impl App {
             }
         };
 
-        let tools = self.services.cached_ai_tools.clone();
+        let mut tools = self.services.cached_ai_tools.clone();
+        if let Some(ref command) = self.runtime.active_command {
+            tools.retain(|tool| command.allows_tool(&tool.name));
+        }
         let tool_names: Vec<_> = tools.iter().map(|t| t.name.as_str()).collect();
         tracing::info!("Sending {} tools to API: {:?}", tools.len(), tool_names);
 
@@ -355,6 +382,13 @@ impl App {
             ..Default::default()
         };
 
+        // Compacted messages come back for the stored conversation
+        let mut compactor = self.take_compactor(&client);
+        let (compaction_tx, compaction_rx) = tokio::sync::oneshot::channel();
+        self.runtime.channels.compaction = Some(compaction_rx);
+        let session_id = self.runtime.current_session_id.clone();
+        let replaces = self.runtime.chat.conversation.len();
+
         self.runtime.cancellation.reset();
         let cancel_token = self.runtime.cancellation.child_token();
 
@@ -368,7 +402,23 @@ impl App {
                         error: "Interrupted by user".to_string()
                     });
                 }
-                result = client.call_streaming(conversation, &options) => {
+                result = async {
+                    // Compact in place once the conversation nears the context window
+                    let result = match compactor.compact(&client, &mut conversation).await {
+                        Ok(Some(report)) => {
+                            Ok(Some((conversation[system_insert_count..].to_vec(), report)))
+                        }
+                        Ok(None) => Ok(None),
+                        Err(e) => Err(e.to_string()),
+                    };
+                    let _ = compaction_tx.send(CompactionUpdate {
+                        session_id,
+                        replaces,
+                        compactor,
+                        result,
+                    });
+                    client.call_streaming(conversation, &options).await
+                } => {
                     match result {
                         Ok(mut api_rx) => {
                             loop {
@@ -2,14 +2,22 @@
 //!
 //! Handles the execution of AI tool calls and processing of results.
 
+use std::sync::Arc;
+
 use tokio::sync::{mpsc, oneshot};
 
-use crate::agent::subagent::AgentProgress;
+use crate::agent::subagent::{AgentControls, AgentProgress};
 use crate::ai::types::{AiToolCall, Content};
+use crate::storage::StoredTranscriptSink;
 use crate::tools::{ToolContext, ToolOutputChunk};
 use crate::tui::app::App;
 use crate::tui::components::{PromptOption, PromptQuestion};
 
+/// Tools that run sub-agents (explore and build blocks)
+fn is_subagent_tool(name: &str) -> bool {
+    matches!(name, "explore" | "Task" | "delegate" | "build")
+}
+
 impl App {
     /// Handle enter_plan_mode tool calls to switch modes
     pub(super) fn handle_enter_plan_mode_tools(&mut self, tool_calls: Vec<AiToolCall>) {
@@ -59,6 +67,7 @@ impl App {
     /// ENFORCES: Task must be InProgress (started) before it can be completed
     /// ENFORCES: Only ONE task per call (no batch completion)
     /// ENFORCES: Result parameter required
+    /// ENFORCES: Acceptance checks, if the task has any, must pass
     pub(super) fn handle_task_complete_tools(&mut self, tool_calls: Vec<AiToolCall>) {
         use crate::plan::TaskStatus;
         let mut results = Vec::new();
@@ -178,53 +187,211 @@ impl App {
                 }
             }
 
-            // Complete the task
-            if let Err(e) = plan.complete_task(task_id, &result_text) {
-                results.push(Content::ToolResult {
-                    tool_use_id: tool_call.id.clone(),
-                    output: serde_json::Value::String(format!("Error: {}", e)),
-                    is_error: Some(true),
-                });
+            // Tasks with acceptance checks complete once the checks pass
+            let checks = plan
+                .find_task(task_id)
+                .map(|t| t.checks.clone())
+                .unwrap_or_default();
+            if !checks.is_empty() {
+                self.spawn_task_checks(
+                    tool_call.id.clone(),
+                    task_id.to_string(),
+                    result_text,
+                    checks,
+                );
                 continue;
             }
 
-            if let Err(e) = self.services.plan_manager.save_plan(plan) {
-                tracing::error!("Failed to save plan after task completion: {}", e);
-            }
+            results.push(self.complete_plan_task(&tool_call.id, task_id, &result_text));
+        }
 
-            let (completed, total) = plan.progress();
-            let mut msg = format!(
-                "Completed task {}. Progress: {}/{}",
-                task_id, completed, total
-            );
+        if !results.is_empty() {
+            self.runtime.pending_tool_results.extend(results);
+        }
+    }
 
-            if completed == total {
-                msg.push_str("\n\nAll tasks complete. Plan finished.");
-            } else {
-                let ready = plan.get_ready_tasks();
-                if !ready.is_empty() {
-                    msg.push_str("\n\nReady to work on next:");
-                    for task in &ready {
-                        msg.push_str(&format!("\n  → Task {}: {}", task.id, task.description));
-                    }
-                    msg.push_str("\n\nPick one and call task_start immediately.");
-                } else {
-                    msg.push_str("\n\nNo tasks currently unblocked. Check dependencies.");
+    /// Mark an in-progress task complete and describe what's next
+    fn complete_plan_task(
+        &mut self,
+        tool_use_id: &str,
+        task_id: &str,
+        result_text: &str,
+    ) -> Content {
+        let Some(plan) = &mut self.runtime.active_plan else {
+            return Content::ToolResult {
+                tool_use_id: tool_use_id.to_string(),
+                output: serde_json::Value::String(
+                    "Error: No active plan. Create a plan first.".to_string(),
+                ),
+                is_error: Some(true),
+            };
+        };
+
+        if let Err(e) = plan.complete_task(task_id, result_text) {
+            return Content::ToolResult {
+                tool_use_id: tool_use_id.to_string(),
+                output: serde_json::Value::String(format!("Error: {}", e)),
+                is_error: Some(true),
+            };
+        }
+
+        if let Err(e) = self.services.plan_manager.save_plan(plan) {
+            tracing::error!("Failed to save plan after task completion: {}", e);
+        }
+
+        let (completed, total) = plan.progress();
+        let mut msg = format!(
+            "Completed task {}. Progress: {}/{}",
+            task_id, completed, total
+        );
+
+        if completed == total {
+            msg.push_str("\n\nAll tasks complete. Plan finished.");
+        } else {
+            let ready = plan.get_ready_tasks();
+            if !ready.is_empty() {
+                msg.push_str("\n\nReady to work on next:");
+                for task in &ready {
+                    msg.push_str(&format!("\n  → Task {}: {}", task.id, task.description));
                 }
+                msg.push_str("\n\nPick one and call task_start immediately.");
+            } else {
+                msg.push_str("\n\nNo tasks currently unblocked. Check dependencies.");
             }
+        }
 
-            tracing::info!("{}", msg);
+        tracing::info!("{}", msg);
 
-            results.push(Content::ToolResult {
-                tool_use_id: tool_call.id.clone(),
-                output: serde_json::Value::String(msg),
-                is_error: None,
+        Content::ToolResult {
+            tool_use_id: tool_use_id.to_string(),
+            output: serde_json::Value::String(msg),
+            is_error: None,
+        }
+    }
+
+    /// Run a task's acceptance checks in the background
+    ///
+    /// The task_complete result is delivered by `poll_task_checks`; until
+    /// then the turn is held open like any other running tool.
+    fn spawn_task_checks(
+        &mut self,
+        tool_use_id: String,
+        task_id: String,
+        result: String,
+        checks: Vec<crate::plan::TaskCheck>,
+    ) {
+        use crate::tui::utils::TaskCheckResult;
+
+        self.runtime.chat.messages.push((
+            "system".to_string(),
+            format!(
+                "Running {} acceptance check{} for task {}...",
+                checks.len(),
+                if checks.len() == 1 { "" } else { "s" },
+                task_id
+            ),
+        ));
+        self.runtime.pending_task_checks += 1;
+
+        // Commands run through the bash tool so its hooks can block them
+        let tool_registry = self.services.tool_registry.clone();
+        let ctx = ToolContext::with_process_registry(
+            self.runtime.working_dir.clone(),
+            self.runtime.process_registry.clone(),
+        );
+        let cancel = self.runtime.cancellation.child_token();
+        let tx = self.runtime.task_check_tx.clone();
+        tokio::spawn(async move {
+            let outcomes = tokio::select! {
+                outcomes = crate::plan::run_checks(&checks, &tool_registry, &ctx) => Some(outcomes),
+                _ = cancel.cancelled() => None,
+            };
+            let _ = tx.send(TaskCheckResult {
+                tool_use_id,
+                task_id,
+                result,
+                outcomes,
             });
+        });
+    }
+
+    /// Deliver task_complete results whose acceptance checks have finished
+    pub(crate) fn poll_task_checks(&mut self) {
+        let mut results = Vec::new();
+        while let Some(check) = self
+            .runtime
+            .channels
+            .task_checks
+            .as_mut()
+            .and_then(|rx| rx.try_recv().ok())
+        {
+            self.runtime.pending_task_checks = self.runtime.pending_task_checks.saturating_sub(1);
+            results.push(self.finish_checked_task(check));
         }
+        if results.is_empty() {
+            return;
+        }
+        self.ui.needs_redraw = true;
+        self.runtime.pending_tool_results.extend(results);
+
+        // Continue the turn once nothing else is outstanding. After an
+        // interrupt the results wait and go out with the next message.
+        if self.runtime.pending_task_checks == 0
+            && self.runtime.channels.tool_results.is_none()
+            && self.is_busy()
+        {
+            let results = std::mem::take(&mut self.runtime.pending_tool_results);
+            self.stop_tool_execution();
+            self.handle_tool_results(results);
+        }
+    }
 
-        if !results.is_empty() {
-            self.runtime.pending_tool_results.extend(results);
+    /// Complete a checked task if its checks passed, otherwise refuse
+    fn finish_checked_task(&mut self, check: crate::tui::utils::TaskCheckResult) -> Content {
+        let refuse = |message: String| Content::ToolResult {
+            tool_use_id: check.tool_use_id.clone(),
+            output: serde_json::Value::String(message),
+            is_error: Some(true),
+        };
+
+        let Some(outcomes) = check.outcomes else {
+            return refuse(format!(
+                "Error: Acceptance checks for task {} were interrupted. The task is still in progress.",
+                check.task_id
+            ));
+        };
+
+        if let Some(report) = crate::plan::format_failures(&outcomes) {
+            let failed = outcomes.iter().filter(|o| !o.passed).count();
+            self.runtime.chat.messages.push((
+                "system".to_string(),
+                format!(
+                    "✗ Task {}: {} of {} checks failed",
+                    check.task_id,
+                    failed,
+                    outcomes.len()
+                ),
+            ));
+            return refuse(format!(
+                "Error: Task {} was NOT completed. {}\n\nFix the problems, then call task_complete again.",
+                check.task_id, report
+            ));
         }
+
+        let mut content =
+            self.complete_plan_task(&check.tool_use_id, &check.task_id, &check.result);
+        if let Content::ToolResult {
+            output: serde_json::Value::String(msg),
+            is_error: None,
+            ..
+        } = &mut content
+        {
+            msg.push_str(&format!(
+                "\n\nAll {} acceptance checks passed.",
+                outcomes.len()
+            ));
+        }
+        content
     }
 
     /// Handle task_start tool calls to mark tasks as in-progress
@@ -259,12 +426,17 @@ impl App {
                     if let Err(e) = self.services.plan_manager.save_plan(plan) {
                         tracing::error!("Failed to save plan after task start: {}", e);
                     }
+                    let mut msg = format!("Started task {}. Status: in_progress", task_id);
+                    let checks = plan.find_task(task_id).map(|t| t.checks.as_slice());
+                    if let Some(checks) = checks.filter(|c| !c.is_empty()) {
+                        msg.push_str("\n\nAcceptance checks (run when you call task_complete):");
+                        for check in checks {
+                            msg.push_str(&format!("\n  - {}", check));
+                        }
+                    }
                     results.push(Content::ToolResult {
                         tool_use_id: tool_call.id.clone(),
-                        output: serde_json::Value::String(format!(
-                            "Started task {}. Status: in_progress",
-                            task_id
-                        )),
+                        output: serde_json::Value::String(msg),
                         is_error: None,
                     });
                 }
@@ -498,7 +670,7 @@ impl App {
         let has_action = tool_calls.iter().any(|t| {
             matches!(
                 t.name.as_str(),
-                "edit" | "write" | "bash" | "build" | "task_start" | "task_complete"
+                "edit" | "write" | "bash" | "build" | "delegate" | "task_start" | "task_complete"
             )
         });
         if has_action {
@@ -583,6 +755,12 @@ impl App {
             }
 
             if has_plan_tools {
+                if self.runtime.pending_task_checks > 0 {
+                    // Results follow from poll_task_checks
+                    self.stop_streaming();
+                    self.start_tool_execution();
+                    return;
+                }
                 let results = std::mem::take(&mut self.runtime.pending_tool_results);
                 if !results.is_empty() {
                     self.stop_streaming();
@@ -593,17 +771,17 @@ impl App {
             return;
         }
 
-        // Check if there's an explore/Task tool in the batch
+        // Check if there's an explore/Task/delegate tool in the batch
         let has_explore = tool_calls
             .iter()
-            .any(|t| t.name == "explore" || t.name == "Task");
+            .any(|t| matches!(t.name.as_str(), "explore" | "Task" | "delegate"));
         let has_build = tool_calls.iter().any(|t| t.name == "build");
 
         // If explore tool is present, queue non-explore tools for later
         let tools_to_execute = if has_explore {
             let (explore_tools, other_tools): (Vec<_>, Vec<_>) = tool_calls
                 .into_iter()
-                .partition(|t| t.name == "explore" || t.name == "Task");
+                .partition(|t| matches!(t.name.as_str(), "explore" | "Task" | "delegate"));
 
             if !other_tools.is_empty() {
                 tracing::info!(
@@ -653,6 +831,16 @@ impl App {
         // Create blocks for visual feedback
         self.create_tool_blocks(&tools_to_execute);
 
+        // Stop/restart/note controls for each sub-agent tool call's agents
+        let mut agent_controls: std::collections::HashMap<String, AgentControls> =
+            std::collections::HashMap::new();
+        for tool in &tools_to_execute {
+            if is_subagent_tool(&tool.name) {
+                let controls = self.register_agent_controls(&tool.id);
+                agent_controls.insert(tool.id.clone(), controls);
+            }
+        }
+
         // Clone what we need for the spawned task
         let tool_registry = self.services.tool_registry.clone();
         let process_registry = self.runtime.process_registry.clone();
@@ -660,6 +848,10 @@ impl App {
         let cancel_token = self.runtime.cancellation.child_token();
         let plan_mode = self.ui.work_mode == crate::tui::app::WorkMode::Plan;
         let current_model = self.runtime.current_model.clone();
+        let transcript_db = self.services.transcript_db.clone();
+        let session_id = self.runtime.current_session_id.clone();
+        let file_changes = self.file_change_sink();
+        let active_command = self.runtime.active_command.clone();
 
         tokio::spawn(async move {
             let mut tool_results: Vec<Content> = Vec::new();
@@ -680,6 +872,9 @@ impl App {
                         .with_skills_manager(skills_manager.clone())
                         .with_current_model(current_model.clone());
                 ctx.plan_mode = plan_mode;
+                if let Some(ref sink) = file_changes {
+                    ctx = ctx.with_file_changes(sink.clone());
+                }
 
                 if tool_name == "bash" {
                     ctx = ctx.with_output_stream(output_tx.clone(), tool_call.id.clone());
@@ -692,6 +887,14 @@ impl App {
                     }
                 }
 
+                if tool_name == "delegate" {
+                    // The agent definition carries its own timeout
+                    ctx.timeout = Some(std::time::Duration::from_secs(3600));
+                    if let Some(ref tx) = explore_progress_tx {
+                        ctx = ctx.with_explore_progress(tx.clone());
+                    }
+                }
+
                 if tool_name == "build" {
                     ctx.timeout = Some(std::time::Duration::from_secs(900));
                     if let Some(ref tx) = build_progress_tx {
@@ -699,6 +902,33 @@ impl App {
                     }
                 }
 
+                // Sub-agent tools record their agents' transcripts and can be steered
+                if is_subagent_tool(&tool_name) {
+                    if let Some(controls) = agent_controls.remove(&tool_call.id) {
+                        ctx = ctx.with_agent_controls(controls);
+                    }
+                    if let (Some(db), Some(session_id)) = (&transcript_db, &session_id) {
+                        ctx = ctx.with_transcript(Arc::new(StoredTranscriptSink::new(
+                            db.clone(),
+                            session_id.clone(),
+                            tool_call.id.clone(),
+                        )));
+                    }
+                }
+
+                // Hidden tools are refused too, in case the model names one
+                if let Some(output) = active_command
+                    .as_ref()
+                    .and_then(|c| c.refuse_tool(&tool_name))
+                {
+                    tool_results.push(Content::ToolResult {
+                        tool_use_id: tool_call.id.clone(),
+                        output: serde_json::Value::String(output),
+                        is_error: Some(true),
+                    });
+                    continue;
+                }
+
                 let result = tokio::select! {
                     _ = cancel_token.cancelled() => {
                         tracing::info!("Tool execution cancelled during {}", tool_name);
@@ -853,13 +1083,16 @@ impl App {
                 }
             }
 
-            if tool_name == "explore" || tool_name == "Task" {
+            if matches!(tool_name.as_str(), "explore" | "Task" | "delegate") {
                 let prompt = tool_call
                     .arguments
                     .get("prompt")
                     .and_then(|v| v.as_str())
-                    .unwrap_or("Exploring...")
-                    .to_string();
+                    .unwrap_or("Exploring...");
+                let prompt = match tool_call.arguments.get("agent").and_then(|v| v.as_str()) {
+                    Some(agent) if tool_name == "delegate" => format!("{}: {}", agent, prompt),
+                    _ => prompt.to_string(),
+                };
                 tracing::info!(
                     "spawn_tool_execution: creating ExploreBlock for '{}' with id={}",
                     tool_name,
@@ -947,6 +1180,7 @@ impl App {
                 self.update_bash_block(tool_use_id, output_str);
                 self.update_explore_block(tool_use_id, output_str);
                 self.update_build_block(tool_use_id, output_str);
+                self.release_agent_controls(tool_use_id);
             }
         }
 
@@ -967,6 +1201,17 @@ impl App {
             return;
         }
 
+        // Hold results while acceptance checks run; poll_task_checks sends them
+        if self.runtime.pending_task_checks > 0 {
+            tracing::info!(
+                "Acceptance checks running - deferring {} tool results",
+                all_results.len()
+            );
+            self.runtime.pending_tool_results = all_results;
+            self.start_tool_execution();
+            return;
+        }
+
         // If decision prompt is visible, defer tool results until user decides
         // This prevents the AI from continuing while waiting for user input
         if self.ui.decision_prompt.visible {
@@ -1005,6 +1250,9 @@ impl App {
             all_results.push(Content::Text { text: warning });
         }
 
+        // Prompts the user queued for this tool boundary
+        self.inject_queued_prompts(&mut all_results);
+
         // Add tool results to conversation
         let tool_result_msg = crate::ai::types::ModelMessage {
             role: crate::ai::types::Role::User,
@@ -1019,6 +1267,36 @@ impl App {
         self.send_to_ai();
     }
 
+    /// Add prompts queued for injection to the tool results, so the agent
+    /// reads them before its next step
+    fn inject_queued_prompts(&mut self, results: &mut Vec<Content>) {
+        if self.runtime.prompt_queue.paused {
+            return;
+        }
+        for prompt in self.runtime.prompt_queue.take_injections() {
+            match self.build_user_content(&prompt.text) {
+                Ok((blocks, display_text)) => {
+                    self.runtime
+                        .chat
+                        .messages
+                        .push(("user".to_string(), display_text));
+                    results.push(Content::Text {
+                        text: "[USER MESSAGE]\n\
+                               The user sent this while you were working. \
+                               Take it into account before your next step:"
+                            .to_string(),
+                    });
+                    results.extend(blocks);
+                }
+                Err(e) => self
+                    .runtime
+                    .chat
+                    .messages
+                    .push(("system".to_string(), format!("Error: {}", e))),
+            }
+        }
+    }
+
     /// Update ToolResultBlock with output
     fn update_tool_result_block(&mut self, tool_use_id: &str, output_str: &str) {
         for block in &mut self.runtime.blocks.tool_result {
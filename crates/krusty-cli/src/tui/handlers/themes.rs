@@ -1,18 +1,30 @@
 //! Theme management handlers
 //!
-//! Theme switching, preview, and persistence.
+//! Theme switching, preview, persistence, and user theme files.
 
+use std::io::Write;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
+use anyhow::{Context, Result};
+
+use crate::paths;
 use crate::tui::app::App;
+use crate::tui::themes::import::read_theme_file;
+use crate::tui::themes::user::{build_theme, theme_slug, themes_stamp};
 use crate::tui::themes::THEME_REGISTRY;
 
+/// How often the themes directory is checked for edits
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
 impl App {
     /// Set theme and persist to preferences
     pub fn set_theme(&mut self, name: &str) {
-        let theme = THEME_REGISTRY.get_or_default(name);
+        let theme = THEME_REGISTRY.read().get_or_default(name).clone();
         self.ui.theme = Arc::new(theme.clone());
         self.ui.theme_name = name.to_string();
+        self.ui.markdown_cache.clear();
 
         // Update menu animator with theme color
         let accent_rgb = theme.get_bubble_rgb();
@@ -28,9 +40,10 @@ impl App {
 
     /// Preview theme without saving to preferences (for live preview)
     pub fn preview_theme(&mut self, name: &str) {
-        let theme = THEME_REGISTRY.get_or_default(name);
+        let theme = THEME_REGISTRY.read().get_or_default(name).clone();
         self.ui.theme = Arc::new(theme.clone());
         self.ui.theme_name = name.to_string();
+        self.ui.markdown_cache.clear();
 
         // Update menu animator with theme color
         let accent_rgb = theme.get_bubble_rgb();
@@ -50,4 +63,130 @@ impl App {
             self.preview_theme(&original);
         }
     }
+
+    /// Reload user themes when files in the themes directory change
+    ///
+    /// The current theme is re-applied so edits show up right away.
+    pub(crate) fn poll_user_themes(&mut self) {
+        if self.runtime.themes_polled.elapsed() < POLL_INTERVAL {
+            return;
+        }
+        self.runtime.themes_polled = Instant::now();
+
+        let dir = paths::themes_dir();
+        let stamp = themes_stamp(&dir);
+        if stamp == self.runtime.themes_stamp {
+            return;
+        }
+        self.runtime.themes_stamp = stamp;
+
+        let errors = THEME_REGISTRY.write().load_user_themes(&dir);
+        for error in errors {
+            self.runtime
+                .chat
+                .messages
+                .push(("system".to_string(), error));
+        }
+        let current = self.ui.theme_name.clone();
+        self.preview_theme(&current);
+        self.ui.needs_redraw = true;
+    }
+
+    /// `/theme import [--force] <path>`: convert a theme file into
+    /// `~/.krusty/themes`, replacing a user theme of the same name only with
+    /// `--force`
+    pub(crate) fn handle_theme_import(&mut self, path: &str, force: bool) {
+        let msg = match self.import_theme(path, force) {
+            Ok((name, saved)) => {
+                self.set_theme(&name);
+                format!("Imported theme '{}' to {}.", name, saved.display())
+            }
+            Err(e) => format!("Theme import failed: {:#}", e),
+        };
+        self.runtime.chat.messages.push(("system".to_string(), msg));
+    }
+
+    fn import_theme(&mut self, path: &str, force: bool) -> Result<(String, PathBuf)> {
+        let source = match path.strip_prefix("~/") {
+            Some(rest) => dirs::home_dir().unwrap_or_default().join(rest),
+            None => self.runtime.working_dir.join(path),
+        };
+        let mut file = read_theme_file(&source)?;
+
+        let stem = source
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or_default();
+        let fallback = file
+            .display_name
+            .clone()
+            .unwrap_or_else(|| stem.to_string());
+        let name = theme_slug(file.name.as_deref().unwrap_or(&fallback));
+        file.name = Some(name.clone());
+
+        // Check it builds before writing anything
+        {
+            let registry = THEME_REGISTRY.read();
+            if registry.get(&name).is_some() && !registry.is_user_theme(&name) {
+                anyhow::bail!("'{}' is a built-in theme name", name);
+            }
+            build_theme(&file, &name, &|n| registry.get(n).cloned())?;
+        }
+
+        let dir = paths::themes_dir();
+        let saved = save_theme(&dir, &name, &toml::to_string(&file)?, force)?;
+
+        let errors = THEME_REGISTRY.write().load_user_themes(&dir);
+        self.runtime.themes_stamp = themes_stamp(&dir);
+        for error in errors {
+            self.runtime
+                .chat
+                .messages
+                .push(("system".to_string(), error));
+        }
+        Ok((name, saved))
+    }
+}
+
+/// Write a theme to `<dir>/<name>.toml`, refusing to replace an existing
+/// file unless `force` is set
+fn save_theme(dir: &Path, name: &str, contents: &str, force: bool) -> Result<PathBuf> {
+    std::fs::create_dir_all(dir).with_context(|| format!("creating {}", dir.display()))?;
+    let saved = dir.join(format!("{}.toml", name));
+    let mut options = std::fs::OpenOptions::new();
+    if force {
+        options.write(true).create(true).truncate(true);
+    } else {
+        options.write(true).create_new(true);
+    }
+    let mut out = match options.open(&saved) {
+        Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => anyhow::bail!(
+            "theme '{}' already exists at {}; use /theme import --force <file> to replace it",
+            name,
+            saved.display()
+        ),
+        result => result.with_context(|| format!("writing {}", saved.display()))?,
+    };
+    out.write_all(contents.as_bytes())
+        .with_context(|| format!("writing {}", saved.display()))?;
+    Ok(saved)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_save_theme_refuses_overwrite() {
+        let dir = tempfile::tempdir().unwrap();
+        let saved = save_theme(dir.path(), "dusk", "a = 1\n", false).unwrap();
+        assert_eq!(saved, dir.path().join("dusk.toml"));
+
+        let err = save_theme(dir.path(), "dusk", "a = 2\n", false).unwrap_err();
+        assert!(err.to_string().contains("already exists"));
+        assert_eq!(std::fs::read_to_string(&saved).unwrap(), "a = 1\n");
+
+        save_theme(dir.path(), "dusk", "a = 2\n", true).unwrap();
+        assert_eq!(std::fs::read_to_string(&saved).unwrap(), "a = 2\n");
+    }
 }
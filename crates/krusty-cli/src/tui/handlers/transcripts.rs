@@ -0,0 +1,70 @@
+//! Sub-agent transcripts
+//!
+//! Explore and build agents record their turns to the session database as
+//! they run; the ≡ control on an agent's row in their block opens the transcript.
+
+use std::sync::Arc;
+
+use crate::agent::subagent::{SharedTranscriptSink, TranscriptEntry};
+use crate::storage::{StoredTranscriptSink, TranscriptStore};
+use crate::tui::app::{App, Popup};
+
+impl App {
+    /// Sink for agents started by `tool_use_id` in the current session
+    pub(crate) fn transcript_sink(&self, tool_use_id: &str) -> Option<SharedTranscriptSink> {
+        let db = self.services.transcript_db.clone()?;
+        let session_id = self.runtime.current_session_id.clone()?;
+        Some(Arc::new(StoredTranscriptSink::new(
+            db,
+            session_id,
+            tool_use_id.to_string(),
+        )))
+    }
+
+    /// Open the transcript of one agent from an explore or build block
+    pub(crate) fn open_transcript(&mut self, tool_use_id: &str, task_id: &str, agent_name: &str) {
+        let Some(entries) = self.load_transcript(tool_use_id, task_id) else {
+            self.runtime.chat.messages.push((
+                "system".to_string(),
+                "No transcript is stored for this agent.".to_string(),
+            ));
+            return;
+        };
+        self.ui.popups.transcript.open(
+            tool_use_id.to_string(),
+            task_id.to_string(),
+            agent_name.to_string(),
+            entries,
+        );
+        self.ui.popup = Popup::Transcript;
+    }
+
+    /// Reload the open transcript (the agent may still be running)
+    pub(crate) fn reload_transcript(&mut self) {
+        let Some((tool_use_id, task_id)) = self
+            .ui
+            .popups
+            .transcript
+            .source()
+            .map(|(t, a)| (t.to_string(), a.to_string()))
+        else {
+            return;
+        };
+        if let Some(entries) = self.load_transcript(&tool_use_id, &task_id) {
+            self.ui.popups.transcript.refresh(entries);
+        }
+    }
+
+    fn load_transcript(&self, tool_use_id: &str, task_id: &str) -> Option<Vec<TranscriptEntry>> {
+        let db = self.services.transcript_db.as_ref()?;
+        let session_id = self.runtime.current_session_id.as_deref()?;
+        let db = db.lock().ok()?;
+        match TranscriptStore::new(&db).load(session_id, tool_use_id, task_id) {
+            Ok(entries) => Some(entries),
+            Err(e) => {
+                tracing::warn!("Failed to load transcript: {}", e);
+                None
+            }
+        }
+    }
+}
@@ -8,13 +8,17 @@ use ratatui::{
     Frame,
 };
 
+use std::borrow::Cow;
+
+use krusty_core::commands::CommandDefinition;
+
 use crate::tui::themes::Theme;
 
 #[derive(Debug, Clone)]
 pub struct CommandSuggestion {
-    pub primary: &'static str,
+    pub primary: Cow<'static, str>,
     pub aliases: Vec<&'static str>,
-    pub description: &'static str,
+    pub description: Cow<'static, str>,
 }
 
 /// Autocomplete popup for slash commands
@@ -44,6 +48,32 @@ impl AutocompletePopup {
         }
     }
 
+    /// Replace the custom commands offered after the built-ins. Names a
+    /// built-in command already uses are skipped
+    pub fn set_custom_commands(&mut self, commands: &[CommandDefinition]) {
+        let mut suggestions = get_all_commands();
+        for command in commands {
+            let taken = suggestions.iter().any(|s| {
+                s.primary.trim_start_matches('/') == command.name
+                    || s.aliases.contains(&command.name.as_str())
+            });
+            if taken {
+                continue;
+            }
+            let description = match command.argument_hint {
+                Some(ref hint) => format!("{} {}", command.description, hint),
+                None => command.description.clone(),
+            };
+            suggestions.push(CommandSuggestion {
+                primary: format!("/{}", command.name).into(),
+                aliases: vec![],
+                description: description.into(),
+            });
+        }
+        self.suggestions = suggestions;
+        self.filter();
+    }
+
     pub fn show(&mut self, query: &str) {
         self.query = query.to_string();
         self.visible = true;
@@ -155,14 +185,14 @@ impl AutocompletePopup {
                 }
 
                 spans.push(Span::styled(
-                    cmd.primary,
+                    cmd.primary.as_ref(),
                     Style::default()
                         .fg(theme.accent_color)
                         .add_modifier(Modifier::BOLD),
                 ));
                 spans.push(Span::raw("  "));
                 spans.push(Span::styled(
-                    cmd.description,
+                    cmd.description.as_ref(),
                     Style::default().fg(theme.text_color),
                 ));
 
@@ -235,79 +265,114 @@ fn fuzzy_match(text: &str, pattern: &str) -> Option<i32> {
 pub fn get_all_commands() -> Vec<CommandSuggestion> {
     vec![
         CommandSuggestion {
-            primary: "/home",
+            primary: "/home".into(),
+            aliases: vec![],
+            description: "Return to start menu".into(),
+        },
+        CommandSuggestion {
+            primary: "/load".into(),
+            aliases: vec![],
+            description: "Load previous session".into(),
+        },
+        CommandSuggestion {
+            primary: "/search".into(),
+            aliases: vec![],
+            description: "Search messages across all sessions".into(),
+        },
+        CommandSuggestion {
+            primary: "/changes".into(),
             aliases: vec![],
-            description: "Return to start menu",
+            description: "Review, revert or commit this session's file changes".into(),
         },
         CommandSuggestion {
-            primary: "/load",
+            primary: "/model".into(),
             aliases: vec![],
-            description: "Load previous session",
+            description: "Select AI model".into(),
         },
         CommandSuggestion {
-            primary: "/model",
+            primary: "/auth".into(),
             aliases: vec![],
-            description: "Select AI model",
+            description: "Manage API providers".into(),
         },
         CommandSuggestion {
-            primary: "/auth",
+            primary: "/init".into(),
             aliases: vec![],
-            description: "Manage API providers",
+            description: "Initialize project (create KRAB.md)".into(),
         },
         CommandSuggestion {
-            primary: "/init",
+            primary: "/theme".into(),
             aliases: vec![],
-            description: "Initialize project (create KRAB.md)",
+            description: "Change or import color theme".into(),
         },
         CommandSuggestion {
-            primary: "/theme",
+            primary: "/editmode".into(),
             aliases: vec![],
-            description: "Change color theme",
+            description: "Switch prompt editing mode (standard, vim, emacs)".into(),
         },
         CommandSuggestion {
-            primary: "/clear",
+            primary: "/queue".into(),
             aliases: vec![],
-            description: "Clear chat messages",
+            description: "Show queued prompts or set how they are sent".into(),
         },
         CommandSuggestion {
-            primary: "/pinch",
+            primary: "/clear".into(),
             aliases: vec![],
-            description: "Continue in new session with context",
+            description: "Clear chat messages".into(),
         },
         CommandSuggestion {
-            primary: "/cmd",
+            primary: "/pinch".into(),
             aliases: vec![],
-            description: "Show all controls",
+            description: "Continue in new session with context".into(),
         },
         CommandSuggestion {
-            primary: "/terminal",
+            primary: "/cmd".into(),
+            aliases: vec![],
+            description: "Show all controls".into(),
+        },
+        CommandSuggestion {
+            primary: "/terminal".into(),
             aliases: vec!["term", "shell"],
-            description: "Open interactive terminal",
+            description: "Open interactive terminal".into(),
         },
         CommandSuggestion {
-            primary: "/ps",
+            primary: "/ps".into(),
             aliases: vec!["processes"],
-            description: "View background processes",
+            description: "View background processes".into(),
+        },
+        CommandSuggestion {
+            primary: "/skills".into(),
+            aliases: vec![],
+            description: "Browse and manage skills".into(),
+        },
+        CommandSuggestion {
+            primary: "/agents".into(),
+            aliases: vec![],
+            description: "List and reload custom agents".into(),
+        },
+        CommandSuggestion {
+            primary: "/commands".into(),
+            aliases: vec![],
+            description: "List and reload custom commands".into(),
         },
         CommandSuggestion {
-            primary: "/skills",
+            primary: "/plan".into(),
             aliases: vec![],
-            description: "Browse and manage skills",
+            description: "View, run or manage active plan".into(),
         },
         CommandSuggestion {
-            primary: "/plan",
+            primary: "/mcp".into(),
             aliases: vec![],
-            description: "View or manage active plan",
+            description: "Browse and manage MCP servers".into(),
         },
         CommandSuggestion {
-            primary: "/mcp",
+            primary: "/docs".into(),
             aliases: vec![],
-            description: "Browse and manage MCP servers",
+            description: "Index package docs for docs_search".into(),
         },
         CommandSuggestion {
-            primary: "/hooks",
+            primary: "/hooks".into(),
             aliases: vec![],
-            description: "Configure tool execution hooks",
+            description: "Configure tool execution hooks".into(),
         },
     ]
 }
@@ -0,0 +1,668 @@
+//! Named key actions and `~/.krusty/keymap.toml`
+//!
+//! Shortcuts are looked up by context instead of being matched inline, so
+//! they can be rebound and checked for clashes. The file has one table per
+//! context; each entry replaces that action's default keys:
+//!
+//! ```toml
+//! [global]
+//! toggle_work_mode = "ctrl+g"
+//! open_model_select = ["ctrl+o", "f2"]
+//! open_process_list = []        # unbind
+//!
+//! [terminal]
+//! unfocus_terminal = "ctrl+]"
+//! ```
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+
+use anyhow::{anyhow, bail, Result};
+use crossterm::event::{KeyCode, KeyModifiers};
+use serde::Deserialize;
+
+/// Where a binding applies
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum KeyContext {
+    /// Start menu and chat, checked before the view's own keys
+    Global,
+    /// Start menu and chat input
+    Chat,
+    /// Any open popup
+    Popup,
+    /// Focused terminal pane (other keys go to the shell)
+    Terminal,
+    /// Focused plugin window (other keys go to the plugin)
+    Plugin,
+}
+
+pub const CONTEXTS: [KeyContext; 5] = [
+    KeyContext::Global,
+    KeyContext::Chat,
+    KeyContext::Popup,
+    KeyContext::Terminal,
+    KeyContext::Plugin,
+];
+
+impl KeyContext {
+    /// Table name in keymap.toml
+    pub fn name(self) -> &'static str {
+        match self {
+            KeyContext::Global => "global",
+            KeyContext::Chat => "chat",
+            KeyContext::Popup => "popup",
+            KeyContext::Terminal => "terminal",
+            KeyContext::Plugin => "plugin",
+        }
+    }
+
+    pub fn title(self) -> &'static str {
+        match self {
+            KeyContext::Global => "Global",
+            KeyContext::Chat => "Chat",
+            KeyContext::Popup => "Popups",
+            KeyContext::Terminal => "Terminal pane",
+            KeyContext::Plugin => "Plugin window",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        CONTEXTS.into_iter().find(|c| c.name() == name)
+    }
+
+    /// Whether keys of both contexts are live at the same time
+    fn overlaps(self, other: Self) -> bool {
+        self == other
+            || matches!(
+                (self, other),
+                (KeyContext::Global, KeyContext::Chat) | (KeyContext::Chat, KeyContext::Global)
+            )
+    }
+}
+
+/// Something a key can do
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Quit,
+    OpenProcessList,
+    TogglePlanSidebar,
+    TogglePluginWindow,
+    ToggleWorkMode,
+    OpenModelSelect,
+    OpenHelp,
+    ToggleThinking,
+    Interrupt,
+    ScrollUp,
+    ScrollDown,
+    OpenEditor,
+    SelectMessage,
+    ClosePopup,
+    UnfocusTerminal,
+    UnfocusPlugin,
+}
+
+/// An action's name, help text, contexts and default keys
+struct ActionInfo {
+    action: Action,
+    name: &'static str,
+    description: &'static str,
+    contexts: &'static [KeyContext],
+    defaults: &'static [&'static str],
+}
+
+const ACTIONS: &[ActionInfo] = &[
+    ActionInfo {
+        action: Action::Quit,
+        name: "quit",
+        description: "Quit",
+        contexts: &[KeyContext::Global, KeyContext::Terminal, KeyContext::Plugin],
+        defaults: &["ctrl+q"],
+    },
+    ActionInfo {
+        action: Action::ToggleWorkMode,
+        name: "toggle_work_mode",
+        description: "Toggle BUILD/PLAN mode",
+        contexts: &[KeyContext::Global],
+        defaults: &["ctrl+g"],
+    },
+    ActionInfo {
+        action: Action::TogglePlanSidebar,
+        name: "toggle_plan_sidebar",
+        description: "Toggle plan sidebar",
+        contexts: &[KeyContext::Global],
+        defaults: &["ctrl+t"],
+    },
+    ActionInfo {
+        action: Action::OpenProcessList,
+        name: "open_process_list",
+        description: "Open process list",
+        contexts: &[KeyContext::Global],
+        defaults: &["ctrl+b"],
+    },
+    ActionInfo {
+        action: Action::TogglePluginWindow,
+        name: "toggle_plugin_window",
+        description: "Toggle plugin window",
+        contexts: &[KeyContext::Global, KeyContext::Plugin],
+        defaults: &["ctrl+p"],
+    },
+    ActionInfo {
+        action: Action::OpenModelSelect,
+        name: "open_model_select",
+        description: "Select AI model",
+        contexts: &[KeyContext::Global],
+        defaults: &[],
+    },
+    ActionInfo {
+        action: Action::OpenHelp,
+        name: "open_help",
+        description: "Show commands and keys",
+        contexts: &[KeyContext::Global],
+        defaults: &["f1"],
+    },
+    ActionInfo {
+        action: Action::ToggleThinking,
+        name: "toggle_thinking",
+        description: "Toggle extended thinking",
+        contexts: &[KeyContext::Chat],
+        defaults: &["tab"],
+    },
+    ActionInfo {
+        action: Action::Interrupt,
+        name: "interrupt",
+        description: "Cancel AI response",
+        contexts: &[KeyContext::Chat],
+        defaults: &["esc"],
+    },
+    ActionInfo {
+        action: Action::ScrollUp,
+        name: "scroll_up",
+        description: "Scroll chat up",
+        contexts: &[KeyContext::Chat],
+        defaults: &["pageup"],
+    },
+    ActionInfo {
+        action: Action::ScrollDown,
+        name: "scroll_down",
+        description: "Scroll chat down",
+        contexts: &[KeyContext::Chat],
+        defaults: &["pagedown"],
+    },
+    ActionInfo {
+        action: Action::OpenEditor,
+        name: "open_editor",
+        description: "Edit prompt in $VISUAL/$EDITOR",
+        contexts: &[KeyContext::Chat],
+        defaults: &["ctrl+x"],
+    },
+    ActionInfo {
+        action: Action::SelectMessage,
+        name: "select_message",
+        description: "Pick an earlier message to edit, resend or switch branch",
+        contexts: &[KeyContext::Chat],
+        defaults: &["alt+up"],
+    },
+    ActionInfo {
+        action: Action::ClosePopup,
+        name: "close_popup",
+        description: "Close popup",
+        contexts: &[KeyContext::Popup],
+        defaults: &["esc"],
+    },
+    ActionInfo {
+        action: Action::UnfocusTerminal,
+        name: "unfocus_terminal",
+        description: "Leave terminal pane",
+        contexts: &[KeyContext::Terminal],
+        defaults: &["esc"],
+    },
+    ActionInfo {
+        action: Action::UnfocusPlugin,
+        name: "unfocus_plugin",
+        description: "Leave plugin window",
+        contexts: &[KeyContext::Plugin],
+        defaults: &["delete"],
+    },
+];
+
+/// Keys the chat input handles itself; bindings on them shadow the editor
+pub const EDITOR_KEYS: &[(&str, &str)] = &[
+    ("enter", "Send message"),
+    ("shift+enter", "New line"),
+    ("ctrl+j", "New line"),
+    ("ctrl+v", "Paste text or image"),
+    ("ctrl+c", "Clear input"),
+    ("ctrl+u", "Clear input"),
+    ("ctrl+w", "Delete word"),
+    ("ctrl+k", "Delete to end of line"),
+    ("ctrl+a", "Start of line"),
+    ("ctrl+e", "End of line"),
+];
+
+impl Action {
+    fn info(self) -> &'static ActionInfo {
+        ACTIONS
+            .iter()
+            .find(|info| info.action == self)
+            .expect("every action is listed in ACTIONS")
+    }
+
+    /// Name used in keymap.toml
+    pub fn name(self) -> &'static str {
+        self.info().name
+    }
+
+    pub fn description(self) -> &'static str {
+        self.info().description
+    }
+}
+
+/// A key with modifiers, e.g. `ctrl+shift+p`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyBinding {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl KeyBinding {
+    /// Normalized form of a key event, so `Char('P')` and `Char('p')` with
+    /// Shift compare equal
+    pub fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        let mut modifiers = modifiers
+            & (KeyModifiers::CONTROL
+                | KeyModifiers::ALT
+                | KeyModifiers::SHIFT
+                | KeyModifiers::SUPER);
+        let code = match code {
+            KeyCode::Char(c) if c.is_uppercase() => {
+                modifiers |= KeyModifiers::SHIFT;
+                KeyCode::Char(c.to_lowercase().next().unwrap_or(c))
+            }
+            KeyCode::Char(c) => {
+                // Shift is already part of symbols like '?'
+                if !c.is_alphabetic() {
+                    modifiers.remove(KeyModifiers::SHIFT);
+                }
+                KeyCode::Char(c)
+            }
+            KeyCode::BackTab => {
+                modifiers |= KeyModifiers::SHIFT;
+                KeyCode::Tab
+            }
+            other => other,
+        };
+        Self { code, modifiers }
+    }
+
+    /// Parse `ctrl+p`, `shift+enter`, `pagedown`, `f5`, `?`
+    pub fn parse(text: &str) -> Result<Self> {
+        let text = text.trim();
+        let (mods, key) = if text == "+" {
+            ("", "+")
+        } else if let Some(mods) = text.strip_suffix("++") {
+            (mods, "+")
+        } else {
+            text.rsplit_once('+').unwrap_or(("", text))
+        };
+
+        let mut modifiers = KeyModifiers::NONE;
+        for part in mods.split('+').filter(|p| !p.is_empty()) {
+            modifiers |= match part.to_lowercase().as_str() {
+                "ctrl" | "control" => KeyModifiers::CONTROL,
+                "alt" | "meta" | "option" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                "super" | "cmd" => KeyModifiers::SUPER,
+                other => bail!("unknown modifier '{}' in '{}'", other, text),
+            };
+        }
+
+        let mut chars = key.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) => KeyCode::Char(c),
+            _ => match key.to_lowercase().as_str() {
+                "esc" | "escape" => KeyCode::Esc,
+                "enter" | "return" => KeyCode::Enter,
+                "tab" => KeyCode::Tab,
+                "backtab" => KeyCode::BackTab,
+                "backspace" => KeyCode::Backspace,
+                "delete" | "del" => KeyCode::Delete,
+                "insert" | "ins" => KeyCode::Insert,
+                "home" => KeyCode::Home,
+                "end" => KeyCode::End,
+                "pageup" | "pgup" => KeyCode::PageUp,
+                "pagedown" | "pgdn" => KeyCode::PageDown,
+                "up" => KeyCode::Up,
+                "down" => KeyCode::Down,
+                "left" => KeyCode::Left,
+                "right" => KeyCode::Right,
+                "space" => KeyCode::Char(' '),
+                name => match name.strip_prefix('f').and_then(|n| n.parse::<u8>().ok()) {
+                    Some(n @ 1..=24) => KeyCode::F(n),
+                    _ => bail!("unknown key '{}'", key),
+                },
+            },
+        };
+        Ok(Self::new(code, modifiers))
+    }
+}
+
+impl fmt::Display for KeyBinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (modifier, label) in [
+            (KeyModifiers::CONTROL, "Ctrl+"),
+            (KeyModifiers::ALT, "Alt+"),
+            (KeyModifiers::SHIFT, "Shift+"),
+            (KeyModifiers::SUPER, "Super+"),
+        ] {
+            if self.modifiers.contains(modifier) {
+                f.write_str(label)?;
+            }
+        }
+        match self.code {
+            KeyCode::Char(' ') => f.write_str("Space"),
+            KeyCode::Char(c) => write!(f, "{}", c.to_uppercase()),
+            KeyCode::F(n) => write!(f, "F{}", n),
+            KeyCode::PageUp => f.write_str("PgUp"),
+            KeyCode::PageDown => f.write_str("PgDn"),
+            KeyCode::Delete => f.write_str("Del"),
+            other => write!(f, "{:?}", other),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Binding {
+    context: KeyContext,
+    key: KeyBinding,
+    action: Action,
+}
+
+/// One or several keys for an action in keymap.toml
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum KeysEntry {
+    One(String),
+    Many(Vec<String>),
+}
+
+/// Key bindings in effect
+#[derive(Debug, Clone)]
+pub struct Keymap {
+    bindings: Vec<Binding>,
+    /// Problems found while loading, including conflicts
+    problems: Vec<String>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        let mut keymap = Self {
+            bindings: Vec::new(),
+            problems: Vec::new(),
+        };
+        for info in ACTIONS {
+            for context in info.contexts {
+                keymap.bind_defaults(*context, info);
+            }
+        }
+        keymap
+    }
+}
+
+impl Keymap {
+    /// Defaults with the overrides in `path` applied (if it exists)
+    pub fn load(path: &Path) -> Self {
+        match std::fs::read_to_string(path) {
+            Ok(content) => Self::from_toml(&content),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Self::default(),
+            Err(e) => {
+                let mut keymap = Self::default();
+                keymap
+                    .problems
+                    .push(format!("Could not read {}: {}", path.display(), e));
+                keymap
+            }
+        }
+    }
+
+    /// Defaults with the overrides in a keymap.toml document applied
+    pub fn from_toml(content: &str) -> Self {
+        let mut keymap = Self::default();
+        let tables: BTreeMap<String, BTreeMap<String, KeysEntry>> = match toml::from_str(content) {
+            Ok(tables) => tables,
+            Err(e) => {
+                keymap.problems.push(format!("keymap.toml: {}", e));
+                return keymap;
+            }
+        };
+
+        for (context_name, entries) in tables {
+            let Some(context) = KeyContext::from_name(&context_name) else {
+                keymap
+                    .problems
+                    .push(format!("Unknown keymap context [{}]", context_name));
+                continue;
+            };
+            for (action_name, keys) in entries {
+                if let Err(e) = keymap.rebind(context, &action_name, keys) {
+                    keymap.problems.push(format!("[{}] {}", context.name(), e));
+                }
+            }
+        }
+
+        let conflicts = keymap.conflicts();
+        keymap.problems.extend(conflicts);
+        keymap
+    }
+
+    fn bind_defaults(&mut self, context: KeyContext, info: &ActionInfo) {
+        for key in info.defaults {
+            let key = KeyBinding::parse(key).expect("default keys parse");
+            self.bindings.push(Binding {
+                context,
+                key,
+                action: info.action,
+            });
+        }
+    }
+
+    /// Replace an action's keys in a context
+    fn rebind(&mut self, context: KeyContext, action_name: &str, keys: KeysEntry) -> Result<()> {
+        let info = ACTIONS
+            .iter()
+            .find(|info| info.name == action_name)
+            .ok_or_else(|| anyhow!("unknown action '{}'", action_name))?;
+        if !info.contexts.contains(&context) {
+            let valid: Vec<&str> = info.contexts.iter().map(|c| c.name()).collect();
+            bail!("{} only works in [{}]", action_name, valid.join("], ["));
+        }
+        let keys = match keys {
+            KeysEntry::One(key) => vec![key],
+            KeysEntry::Many(keys) => keys,
+        };
+        let keys = keys
+            .iter()
+            .map(|key| KeyBinding::parse(key))
+            .collect::<Result<Vec<_>>>()
+            .map_err(|e| anyhow!("{}: {}", action_name, e))?;
+
+        self.bindings
+            .retain(|b| !(b.context == context && b.action == info.action));
+        self.bindings.extend(keys.into_iter().map(|key| Binding {
+            context,
+            key,
+            action: info.action,
+        }));
+        Ok(())
+    }
+
+    /// Action bound to a key in one context
+    pub fn action(
+        &self,
+        context: KeyContext,
+        code: KeyCode,
+        modifiers: KeyModifiers,
+    ) -> Option<Action> {
+        let key = KeyBinding::new(code, modifiers);
+        self.bindings
+            .iter()
+            .find(|b| b.context == context && b.key == key)
+            .map(|b| b.action)
+    }
+
+    /// Keys bound to an action in a context
+    pub fn keys(&self, context: KeyContext, action: Action) -> Vec<KeyBinding> {
+        self.bindings
+            .iter()
+            .filter(|b| b.context == context && b.action == action)
+            .map(|b| b.key)
+            .collect()
+    }
+
+    /// Actions available in a context, in help order
+    pub fn actions(context: KeyContext) -> impl Iterator<Item = Action> {
+        ACTIONS
+            .iter()
+            .filter(move |info| info.contexts.contains(&context))
+            .map(|info| info.action)
+    }
+
+    /// Problems found while loading
+    pub fn problems(&self) -> &[String] {
+        &self.problems
+    }
+
+    /// Keys bound to different actions that are live at the same time, and
+    /// keys that take over one of the chat input's own
+    pub fn conflicts(&self) -> Vec<String> {
+        let mut conflicts = Vec::new();
+        for (i, a) in self.bindings.iter().enumerate() {
+            for b in &self.bindings[i + 1..] {
+                if a.key == b.key && a.action != b.action && a.context.overlaps(b.context) {
+                    conflicts.push(format!(
+                        "{} is bound to both {} [{}] and {} [{}]",
+                        a.key,
+                        a.action.name(),
+                        a.context.name(),
+                        b.action.name(),
+                        b.context.name()
+                    ));
+                }
+            }
+            if matches!(a.context, KeyContext::Global | KeyContext::Chat) {
+                let editor_key = EDITOR_KEYS.iter().find(|(key, _)| {
+                    KeyBinding::parse(key).is_ok_and(|editor_key| editor_key == a.key)
+                });
+                if let Some((_, what)) = editor_key {
+                    conflicts.push(format!(
+                        "{} is bound to {} [{}], replacing the input's \"{}\"",
+                        a.key,
+                        a.action.name(),
+                        a.context.name(),
+                        what
+                    ));
+                }
+            }
+        }
+        conflicts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_display() {
+        let key = KeyBinding::parse("ctrl+shift+P").unwrap();
+        assert_eq!(key.code, KeyCode::Char('p'));
+        assert_eq!(key.modifiers, KeyModifiers::CONTROL | KeyModifiers::SHIFT);
+        assert_eq!(key.to_string(), "Ctrl+Shift+P");
+        assert_eq!(KeyBinding::parse("pgdn").unwrap().to_string(), "PgDn");
+        assert_eq!(KeyBinding::parse("f5").unwrap().code, KeyCode::F(5));
+        assert_eq!(
+            KeyBinding::parse("ctrl++").unwrap().code,
+            KeyCode::Char('+')
+        );
+        assert!(KeyBinding::parse("hyper+x").is_err());
+        assert!(KeyBinding::parse("f99").is_err());
+
+        // Events match however the terminal reports shifted keys
+        assert_eq!(
+            KeyBinding::new(KeyCode::Char('P'), KeyModifiers::CONTROL),
+            KeyBinding::parse("ctrl+shift+p").unwrap()
+        );
+        assert_eq!(
+            KeyBinding::new(KeyCode::Char('?'), KeyModifiers::SHIFT),
+            KeyBinding::parse("?").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_defaults_have_no_conflicts() {
+        let keymap = Keymap::default();
+        assert!(keymap.conflicts().is_empty(), "{:?}", keymap.conflicts());
+        assert_eq!(
+            keymap.action(
+                KeyContext::Global,
+                KeyCode::Char('g'),
+                KeyModifiers::CONTROL
+            ),
+            Some(Action::ToggleWorkMode)
+        );
+        assert_eq!(
+            keymap.action(
+                KeyContext::Plugin,
+                KeyCode::Char('q'),
+                KeyModifiers::CONTROL
+            ),
+            Some(Action::Quit)
+        );
+        assert_eq!(
+            keymap.action(KeyContext::Chat, KeyCode::Char('g'), KeyModifiers::CONTROL),
+            None
+        );
+    }
+
+    #[test]
+    fn test_overrides_and_problems() {
+        let keymap = Keymap::from_toml(
+            r#"
+            [global]
+            open_model_select = ["ctrl+o", "F2"]
+            toggle_work_mode = "ctrl+b"
+            toggle_plan_sidebar = []
+            interrupt = "ctrl+x"
+            sparkle = "ctrl+s"
+
+            [chat]
+            scroll_up = "ctrl+w"
+
+            [terminal]
+            unfocus_terminal = "ctrl+]"
+
+            [sidebar]
+            "#,
+        );
+
+        assert_eq!(
+            keymap.action(KeyContext::Global, KeyCode::F(2), KeyModifiers::NONE),
+            Some(Action::OpenModelSelect)
+        );
+        assert!(keymap
+            .keys(KeyContext::Global, Action::TogglePlanSidebar)
+            .is_empty());
+        assert_eq!(
+            keymap.action(KeyContext::Terminal, KeyCode::Esc, KeyModifiers::NONE),
+            None
+        );
+
+        let problems = keymap.problems().join("\n");
+        // ctrl+b now does two things
+        assert!(problems.contains("Ctrl+B is bound to both"), "{}", problems);
+        assert!(problems.contains("replacing the input's \"Delete word\""));
+        assert!(problems.contains("interrupt only works in [chat]"));
+        assert!(problems.contains("unknown action 'sparkle'"));
+        assert!(problems.contains("[sidebar]"));
+    }
+}
@@ -4,13 +4,16 @@
 //! - Slash command autocomplete
 //! - File search with @ trigger
 //! - Image reference parsing
+//! - Rebindable shortcuts (keymap)
 
 pub mod autocomplete;
 pub mod file_search;
 pub mod image_parser;
+pub mod keymap;
 pub mod multi_line;
 
 pub use autocomplete::AutocompletePopup;
 pub use file_search::FileSearchPopup;
 pub use image_parser::{has_image_references, parse_input, InputSegment};
-pub use multi_line::{InputAction, MultiLineInput};
+pub use keymap::{Action, KeyContext, Keymap};
+pub use multi_line::{EditMode, InputAction, MultiLineInput};
@@ -0,0 +1,401 @@
+//! Emacs editing mode
+//!
+//! Emacs movement and kill/yank keys on top of the standard editor. Kills go
+//! to a kill ring (consecutive kills join into one entry), `C-y` yanks the
+//! latest and `M-y` cycles through older ones. Keys without an Emacs meaning
+//! fall through to the standard editor.
+
+use crossterm::event::{KeyCode, KeyModifiers};
+
+use super::editor::InputAction;
+use super::modes::{line_end, line_start};
+use super::MultiLineInput;
+
+/// Kill ring entries kept
+const KILL_RING_SIZE: usize = 30;
+
+#[derive(Debug, Default)]
+pub(crate) struct EmacsState {
+    /// Mark set with `C-Space` (char index)
+    pub(super) mark: Option<usize>,
+    /// Most recent kill last
+    kill_ring: Vec<String>,
+    /// The previous key killed text, so the next kill joins it
+    last_was_kill: bool,
+    /// Range and ring index of the last yank, for `M-y`
+    last_yank: Option<(usize, usize, usize)>,
+}
+
+fn is_word(c: char) -> bool {
+    c.is_alphanumeric()
+}
+
+fn forward_word(text: &[char], mut i: usize) -> usize {
+    while i < text.len() && !is_word(text[i]) {
+        i += 1;
+    }
+    while i < text.len() && is_word(text[i]) {
+        i += 1;
+    }
+    i
+}
+
+fn backward_word(text: &[char], mut i: usize) -> usize {
+    while i > 0 && !is_word(text[i - 1]) {
+        i -= 1;
+    }
+    while i > 0 && is_word(text[i - 1]) {
+        i -= 1;
+    }
+    i
+}
+
+impl MultiLineInput {
+    pub(super) fn handle_emacs_key(
+        &mut self,
+        code: KeyCode,
+        modifiers: KeyModifiers,
+    ) -> InputAction {
+        let ctrl = modifiers.contains(KeyModifiers::CONTROL);
+        let alt = modifiers.contains(KeyModifiers::ALT);
+        let was_kill = std::mem::take(&mut self.emacs.last_was_kill);
+        let last_yank = self.emacs.last_yank.take();
+        let text: Vec<char> = self.content.chars().collect();
+        let cur = self.char_cursor();
+
+        match code {
+            KeyCode::Char(c) if ctrl => match c {
+                'f' => return self.handle_key_impl(KeyCode::Right, KeyModifiers::NONE),
+                'b' => return self.handle_key_impl(KeyCode::Left, KeyModifiers::NONE),
+                'n' => return self.handle_key_impl(KeyCode::Down, KeyModifiers::NONE),
+                'p' => return self.handle_key_impl(KeyCode::Up, KeyModifiers::NONE),
+                'd' => {
+                    self.emacs.mark = None;
+                    return self.handle_key_impl(KeyCode::Delete, KeyModifiers::NONE);
+                }
+                'w' => match self.emacs.mark.take() {
+                    Some(mark) => {
+                        let mark = mark.min(text.len());
+                        self.kill(mark.min(cur), mark.max(cur), false, false);
+                    }
+                    None => self.kill(backward_word(&text, cur), cur, true, was_kill),
+                },
+                'k' => {
+                    let end = line_end(&text, cur);
+                    // At the end of a line, kill the newline
+                    let end = if end == cur {
+                        (cur + 1).min(text.len())
+                    } else {
+                        end
+                    };
+                    self.kill(cur, end, false, was_kill);
+                }
+                'u' => self.kill(line_start(&text, cur), cur, true, was_kill),
+                'y' => self.yank(cur, self.emacs.kill_ring.len().checked_sub(1)),
+                't' => self.transpose(&text, cur),
+                'g' => self.emacs.mark = None,
+                ' ' | '@' | '2' => self.emacs.mark = Some(cur),
+                '/' | '_' | '7' => {
+                    self.emacs.mark = None;
+                    self.undo();
+                }
+                _ => return self.handle_key_impl(code, modifiers),
+            },
+            KeyCode::Char(c) if alt => match c {
+                'f' => self.set_char_cursor(forward_word(&text, cur)),
+                'b' => self.set_char_cursor(backward_word(&text, cur)),
+                'd' => self.kill(cur, forward_word(&text, cur), false, was_kill),
+                'w' => {
+                    if let Some(mark) = self.emacs.mark.take() {
+                        let mark = mark.min(text.len());
+                        let copied: String = text[mark.min(cur)..mark.max(cur)].iter().collect();
+                        self.push_kill(copied);
+                    }
+                }
+                'y' => {
+                    if let Some((start, end, index)) = last_yank {
+                        let len = self.emacs.kill_ring.len();
+                        self.replace_chars(start, end, "");
+                        self.set_char_cursor(start);
+                        self.yank(start, Some((index + len - 1) % len));
+                    }
+                }
+                'u' | 'l' | 'c' => {
+                    let end = forward_word(&text, cur);
+                    let mut seen_word = false;
+                    let changed: String = text[cur..end]
+                        .iter()
+                        .map(|&ch| {
+                            let upper = match c {
+                                'u' => true,
+                                'c' => !seen_word && is_word(ch),
+                                _ => false,
+                            };
+                            seen_word |= is_word(ch);
+                            if upper {
+                                ch.to_uppercase().collect::<String>()
+                            } else {
+                                ch.to_lowercase().collect()
+                            }
+                        })
+                        .collect();
+                    self.replace_chars(cur, end, &changed);
+                    self.set_char_cursor(end);
+                }
+                '<' => self.set_char_cursor(0),
+                '>' => self.set_char_cursor(text.len()),
+                _ => return self.handle_key_impl(code, modifiers),
+            },
+            KeyCode::Backspace if alt => self.kill(backward_word(&text, cur), cur, true, was_kill),
+            _ => {
+                let action = self.handle_key_impl(code, modifiers);
+                if matches!(action, InputAction::ContentChanged) {
+                    self.emacs.mark = None;
+                }
+                return action;
+            }
+        }
+
+        if self.content.chars().ne(text.iter().copied()) {
+            InputAction::ContentChanged
+        } else {
+            InputAction::Continue
+        }
+    }
+
+    /// Kill `start..end` into the ring, joining the previous kill if the last
+    /// key was a kill (`prepend` for backward kills)
+    fn kill(&mut self, start: usize, end: usize, prepend: bool, append: bool) {
+        self.emacs.mark = None;
+        if start >= end {
+            self.emacs.last_was_kill = append;
+            return;
+        }
+        let killed = self.replace_chars(start, end, "");
+        self.set_char_cursor(start);
+        match self.emacs.kill_ring.last_mut() {
+            Some(last) if append => {
+                if prepend {
+                    last.insert_str(0, &killed);
+                } else {
+                    last.push_str(&killed);
+                }
+            }
+            _ => self.push_kill(killed),
+        }
+        self.emacs.last_was_kill = true;
+    }
+
+    fn push_kill(&mut self, text: String) {
+        self.emacs.kill_ring.push(text);
+        if self.emacs.kill_ring.len() > KILL_RING_SIZE {
+            self.emacs.kill_ring.remove(0);
+        }
+    }
+
+    /// Insert kill ring entry `index` at `at`
+    fn yank(&mut self, at: usize, index: Option<usize>) {
+        let Some(text) = index.and_then(|i| self.emacs.kill_ring.get(i)).cloned() else {
+            return;
+        };
+        self.emacs.mark = None;
+        self.replace_chars(at, at, &text);
+        let end = at + text.chars().count();
+        self.set_char_cursor(end);
+        self.emacs.last_yank = index.map(|i| (at, end, i));
+    }
+
+    /// Swap the characters around the cursor (the last two at a line end)
+    fn transpose(&mut self, text: &[char], cur: usize) {
+        let at_end = cur >= line_end(text, cur);
+        let (a, b) = if at_end {
+            if cur < line_start(text, cur) + 2 {
+                return;
+            }
+            (cur - 2, cur - 1)
+        } else {
+            if cur == line_start(text, cur) {
+                return;
+            }
+            (cur - 1, cur)
+        };
+        let swapped: String = [text[b], text[a]].iter().collect();
+        self.replace_chars(a, b + 1, &swapped);
+        self.set_char_cursor(b + 1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::EditMode;
+    use super::*;
+
+    fn emacs(content: &str) -> MultiLineInput {
+        let mut input = MultiLineInput::new(5);
+        input.set_edit_mode(EditMode::Emacs);
+        input.insert_text(content);
+        input
+    }
+
+    fn ctrl(input: &mut MultiLineInput, c: char) {
+        input.handle_key(KeyCode::Char(c), KeyModifiers::CONTROL);
+    }
+
+    fn meta(input: &mut MultiLineInput, c: char) {
+        input.handle_key(KeyCode::Char(c), KeyModifiers::ALT);
+    }
+
+    #[test]
+    fn test_kill_ring() {
+        let mut input = emacs("alpha beta gamma");
+        meta(&mut input, '<');
+        meta(&mut input, 'd');
+        meta(&mut input, 'd');
+        assert_eq!(input.content(), " gamma");
+        // Moving ends the run of kills, so this one gets its own entry
+        ctrl(&mut input, 'f');
+        ctrl(&mut input, 'k');
+        assert_eq!(input.content(), " ");
+        meta(&mut input, '>');
+        ctrl(&mut input, 'y');
+        assert_eq!(input.content(), " gamma");
+        meta(&mut input, 'y');
+        assert_eq!(input.content(), " alpha beta");
+        ctrl(&mut input, '/');
+        assert_eq!(input.content(), " gamma");
+    }
+
+    #[test]
+    fn test_words_mark_and_transpose() {
+        let mut input = emacs("hello big world");
+        meta(&mut input, 'b');
+        ctrl(&mut input, ' ');
+        meta(&mut input, 'b');
+        assert_eq!(input.mode_label().as_deref(), Some("EMACS MARK"));
+        meta(&mut input, 'w');
+        assert_eq!(input.mode_label().as_deref(), Some("EMACS"));
+        meta(&mut input, '>');
+        ctrl(&mut input, 'y');
+        assert_eq!(input.content(), "hello big worldbig ");
+        ctrl(&mut input, 'a');
+        meta(&mut input, 'c');
+        assert_eq!(input.content(), "Hello big worldbig ");
+        ctrl(&mut input, 'a');
+        ctrl(&mut input, 'f');
+        ctrl(&mut input, 't');
+        assert_eq!(input.content(), "eHllo big worldbig ");
+    }
+
+    /// Press keys written as `C-k`, `M-y`, `M-BS` or a single typed character
+    fn press(input: &mut MultiLineInput, keys: &[&str]) {
+        for key in keys {
+            match key.split_once('-') {
+                Some(("M", "BS")) => input.handle_key(KeyCode::Backspace, KeyModifiers::ALT),
+                Some(("C", "SPC")) => input.handle_key(KeyCode::Char(' '), KeyModifiers::CONTROL),
+                Some(("C", c)) => {
+                    input.handle_key(KeyCode::Char(c.parse().unwrap()), KeyModifiers::CONTROL)
+                }
+                Some(("M", c)) => {
+                    input.handle_key(KeyCode::Char(c.parse().unwrap()), KeyModifiers::ALT)
+                }
+                _ => input.handle_key(KeyCode::Char(key.parse().unwrap()), KeyModifiers::NONE),
+            };
+        }
+    }
+
+    /// Run each (content, keys, expected content, expected cursor) case with
+    /// the cursor starting at the end
+    fn check(cases: &[(&str, &[&str], &str, usize)]) {
+        for &(content, keys, expected, expected_cursor) in cases {
+            let mut input = emacs(content);
+            press(&mut input, keys);
+            assert_eq!(
+                (input.content(), input.char_cursor()),
+                (expected, expected_cursor),
+                "{keys:?} on {content:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_kill_ring_table() {
+        check(&[
+            ("alpha beta", &["C-a", "C-k"], "", 0),
+            ("alpha beta", &["C-a", "C-k", "C-y"], "alpha beta", 10),
+            ("a\nb", &["M-<", "C-f", "C-k"], "ab", 1),
+            ("a\nb", &["M-<", "C-k", "C-k", "C-k", "C-y"], "a\nb", 3),
+            (
+                "one two three",
+                &["M-BS", "M-BS", "C-y"],
+                "one two three",
+                13,
+            ),
+            ("one two", &["C-w"], "one ", 4),
+            ("one two", &["C-u"], "", 0),
+            ("a\nbc", &["C-u"], "a\n", 2),
+            ("hello world", &["M-<", "C-SPC", "M-f", "C-w"], " world", 0),
+            (
+                "hello world",
+                &["M-<", "C-SPC", "M-f", "C-w", "M->", "C-y"],
+                " worldhello",
+                11,
+            ),
+            (
+                "hello world",
+                &["M-<", "C-SPC", "M-f", "M-w", "M->", "C-y"],
+                "hello worldhello",
+                16,
+            ),
+            ("one two", &["C-w", "C-/"], "one two", 7),
+        ]);
+    }
+
+    #[test]
+    fn test_yank_pop() {
+        let kills: &[&str] = &["M-<", "M-d", "C-f", "M-d", "C-f", "M-d"];
+        let with = |extra: &[&'static str]| [kills, extra].concat();
+        check(&[
+            ("one two three", &with(&[]), "  ", 2),
+            ("one two three", &with(&["C-y"]), "  three", 7),
+            ("one two three", &with(&["C-y", "M-y"]), "  two", 5),
+            ("one two three", &with(&["C-y", "M-y", "M-y"]), "  one", 5),
+            (
+                "one two three",
+                &with(&["C-y", "M-y", "M-y", "M-y"]),
+                "  three",
+                7,
+            ),
+            ("one two three", &with(&["C-y", "C-b", "M-y"]), "  three", 6),
+            ("x", &["M-y"], "x", 1),
+            ("x", &["C-y"], "x", 1),
+        ]);
+    }
+
+    #[test]
+    fn test_edits_and_edge_cases() {
+        check(&[
+            ("ab", &["C-t"], "ba", 2),
+            ("abc", &["M-<", "C-f", "C-t"], "bac", 2),
+            ("ab", &["M-<", "C-t"], "ab", 0),
+            ("a", &["C-t"], "a", 1),
+            ("hello world", &["M-<", "M-u"], "HELLO world", 5),
+            ("HELLO world", &["M-<", "M-l"], "hello world", 5),
+            ("hello world", &["M-<", "M-c", "M-c"], "Hello World", 11),
+            ("", &["a", "b", "c", "C-/"], "", 0),
+            ("", &["C-k"], "", 0),
+            ("", &["C-w"], "", 0),
+            ("", &["C-u"], "", 0),
+            ("", &["M-d"], "", 0),
+            ("", &["M-BS"], "", 0),
+            ("", &["C-d"], "", 0),
+            ("", &["C-t"], "", 0),
+            ("", &["M-u"], "", 0),
+            ("", &["C-y"], "", 0),
+            ("", &["C-/"], "", 0),
+            ("a\nb", &["C-k"], "a\nb", 3),
+            ("a\nb", &["C-d"], "a\nb", 3),
+            ("a\nb", &["C-t"], "a\nb", 3),
+            ("a\nb", &["M-BS", "C-y"], "a\nb", 3),
+        ]);
+    }
+}
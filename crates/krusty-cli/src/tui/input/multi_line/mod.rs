@@ -5,12 +5,16 @@ use std::cell::RefCell;
 use crossterm::event::{KeyCode, KeyModifiers};
 
 mod editor;
+mod emacs;
+mod modes;
 mod patterns;
 mod renderer;
 mod viewport;
+mod vim;
 mod wrapper;
 
 pub use editor::InputAction;
+pub use modes::EditMode;
 pub(crate) use patterns::FILE_REF_PATTERN;
 
 /// Multi-line input handler with proper text wrapping and cursor management
@@ -29,6 +33,12 @@ pub struct MultiLineInput {
     pub(crate) max_visible_lines: u16,
     /// Cached wrapped lines (invalidated on content/width change)
     wrapped_lines_cache: RefCell<Option<Vec<String>>>,
+    /// Active key set
+    edit_mode: EditMode,
+    vim: vim::VimState,
+    emacs: emacs::EmacsState,
+    /// Undo history (Vim and Emacs modes)
+    history: modes::EditHistory,
 }
 
 impl MultiLineInput {
@@ -41,6 +51,10 @@ impl MultiLineInput {
             viewport_offset: 0,
             max_visible_lines,
             wrapped_lines_cache: RefCell::new(None),
+            edit_mode: EditMode::default(),
+            vim: Default::default(),
+            emacs: Default::default(),
+            history: Default::default(),
         }
     }
 
@@ -67,6 +81,9 @@ impl MultiLineInput {
         self.cursor_visual = (0, 0);
         self.viewport_offset = 0;
         self.invalidate_cache();
+        // A fresh prompt starts in insert mode with no region
+        self.vim.reset_mode();
+        self.emacs.mark = None;
     }
 
     pub fn content(&self) -> &str {
@@ -82,7 +99,7 @@ impl MultiLineInput {
 
     // Editor methods
     pub fn handle_key(&mut self, code: KeyCode, modifiers: KeyModifiers) -> InputAction {
-        self.handle_key_impl(code, modifiers)
+        self.handle_mode_key(code, modifiers)
     }
 
     pub fn insert_char(&mut self, ch: char) {
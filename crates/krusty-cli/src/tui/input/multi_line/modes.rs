@@ -0,0 +1,378 @@
+//! Editing modes and the pieces Vim and Emacs keys share
+//!
+//! Both modes work on character indices into the content (the prompt is
+//! short, so collecting it into a `Vec<char>` per command is cheap) and
+//! snapshot the content for undo.
+
+use crossterm::event::{KeyCode, KeyModifiers};
+
+use super::editor::InputAction;
+use super::MultiLineInput;
+
+/// Undo snapshots kept per input
+const MAX_UNDO: usize = 200;
+
+/// Key set used by the prompt editor
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum EditMode {
+    /// Plain editing (arrows, Ctrl+W, Ctrl+K, ...)
+    #[default]
+    Standard,
+    /// Modal editing: normal, insert and visual modes
+    Vim,
+    /// Emacs keys with a kill ring
+    Emacs,
+}
+
+impl EditMode {
+    pub const ALL: [EditMode; 3] = [EditMode::Standard, EditMode::Vim, EditMode::Emacs];
+
+    /// Name stored in preferences and used by `/editmode`
+    pub fn name(self) -> &'static str {
+        match self {
+            EditMode::Standard => "standard",
+            EditMode::Vim => "vim",
+            EditMode::Emacs => "emacs",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|m| m.name().eq_ignore_ascii_case(name.trim()))
+    }
+}
+
+/// Undo and redo snapshots of (content, cursor byte offset)
+#[derive(Debug, Default)]
+pub(crate) struct EditHistory {
+    undo: Vec<(String, usize)>,
+    redo: Vec<(String, usize)>,
+    /// The current group (an insert session, a run of typing) already has
+    /// a snapshot
+    group_open: bool,
+    /// The last key was undo or redo, which must not snapshot
+    restored: bool,
+}
+
+impl MultiLineInput {
+    pub fn edit_mode(&self) -> EditMode {
+        self.edit_mode
+    }
+
+    pub fn set_edit_mode(&mut self, mode: EditMode) {
+        self.edit_mode = mode;
+        self.vim = Default::default();
+        self.emacs.mark = None;
+        self.history = EditHistory::default();
+    }
+
+    /// Mode indicator for the status bar (`None` in standard mode)
+    pub fn mode_label(&self) -> Option<String> {
+        match self.edit_mode {
+            EditMode::Standard => None,
+            EditMode::Vim => Some(self.vim_label()),
+            EditMode::Emacs if self.emacs.mark.is_some() => Some("EMACS MARK".to_string()),
+            EditMode::Emacs => Some("EMACS".to_string()),
+        }
+    }
+
+    /// Whether the editing mode handles this key itself, ahead of keymap
+    /// shortcuts (Esc leaving insert mode, Emacs' Ctrl+B, ...)
+    pub fn claims_key(&self, code: KeyCode, modifiers: KeyModifiers) -> bool {
+        match self.edit_mode {
+            EditMode::Standard => false,
+            EditMode::Vim => self.vim_claims_key(code, modifiers),
+            EditMode::Emacs => emacs_claims_key(code, modifiers),
+        }
+    }
+
+    /// Visual-mode selection or Emacs region, as ((line, col), (line, col))
+    /// in wrapped-line coordinates with an exclusive end
+    pub fn mode_selection(&self) -> Option<((usize, usize), (usize, usize))> {
+        let (start, end) = match self.edit_mode {
+            EditMode::Standard => return None,
+            EditMode::Vim => self.vim_selection()?,
+            EditMode::Emacs => {
+                let mark = self.emacs.mark?;
+                let cursor = self.char_cursor();
+                (mark.min(cursor), mark.max(cursor))
+            }
+        };
+        // The renderer takes character columns
+        let lines = self.get_wrapped_lines();
+        let column = |(line, col): (usize, usize)| {
+            let chars = lines
+                .get(line)
+                .map_or(0, |l| l[..col.min(l.len())].chars().count());
+            (line, chars)
+        };
+        Some((
+            column(self.visual_position_of(self.byte_offset(start))),
+            column(self.visual_position_of(self.byte_offset(end))),
+        ))
+    }
+
+    /// Dispatch a key to the active mode, keeping undo snapshots
+    pub(super) fn handle_mode_key(
+        &mut self,
+        code: KeyCode,
+        modifiers: KeyModifiers,
+    ) -> InputAction {
+        let before = (self.content.clone(), self.cursor_position);
+        let action = match self.edit_mode {
+            EditMode::Standard => return self.handle_key_impl(code, modifiers),
+            EditMode::Vim => self.handle_vim_key(code, modifiers),
+            EditMode::Emacs => self.handle_emacs_key(code, modifiers),
+        };
+
+        // Typing continues an undo group; anything else starts a new one
+        let continues = match self.edit_mode {
+            EditMode::Vim => self.vim_in_insert(),
+            _ => {
+                matches!(code, KeyCode::Char(_))
+                    && !modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT)
+            }
+        };
+        if std::mem::take(&mut self.history.restored) {
+            self.history.group_open = false;
+        } else if self.content != before.0 {
+            if !(self.history.group_open && continues) {
+                self.history.undo.push(before);
+                if self.history.undo.len() > MAX_UNDO {
+                    self.history.undo.remove(0);
+                }
+                self.history.redo.clear();
+            }
+            self.history.group_open = continues;
+        } else if !continues {
+            self.history.group_open = false;
+        }
+        action
+    }
+
+    pub(super) fn undo(&mut self) -> bool {
+        let Some(snapshot) = self.history.undo.pop() else {
+            return false;
+        };
+        let current = (std::mem::take(&mut self.content), self.cursor_position);
+        self.history.redo.push(current);
+        self.restore(snapshot);
+        true
+    }
+
+    pub(super) fn redo(&mut self) -> bool {
+        let Some(snapshot) = self.history.redo.pop() else {
+            return false;
+        };
+        let current = (std::mem::take(&mut self.content), self.cursor_position);
+        self.history.undo.push(current);
+        self.restore(snapshot);
+        true
+    }
+
+    fn restore(&mut self, (content, cursor): (String, usize)) {
+        self.content = content;
+        self.history.restored = true;
+        self.invalidate_cache();
+        self.cursor_position = cursor.min(self.content.len());
+        self.update_visual_cursor();
+        self.ensure_cursor_visible();
+    }
+
+    /// Cursor as a character index
+    pub(super) fn char_cursor(&self) -> usize {
+        self.content[..self.cursor_position].chars().count()
+    }
+
+    /// Byte offset of a character index (the end for indices past it)
+    pub(super) fn byte_offset(&self, index: usize) -> usize {
+        self.content
+            .char_indices()
+            .nth(index)
+            .map_or(self.content.len(), |(b, _)| b)
+    }
+
+    /// Move the cursor to a character index
+    pub(super) fn set_char_cursor(&mut self, index: usize) {
+        self.cursor_position = self.byte_offset(index);
+        self.update_visual_cursor();
+        self.ensure_cursor_visible();
+    }
+
+    /// Replace the characters in `start..end` and return what was there
+    pub(super) fn replace_chars(&mut self, start: usize, end: usize, with: &str) -> String {
+        let (start_byte, end_byte) = (self.byte_offset(start), self.byte_offset(end));
+        let removed = self.content[start_byte..end_byte].to_string();
+        self.content.replace_range(start_byte..end_byte, with);
+        self.invalidate_cache();
+        self.cursor_position = self.cursor_position.min(self.content.len());
+        while !self.content.is_char_boundary(self.cursor_position) {
+            self.cursor_position -= 1;
+        }
+        removed
+    }
+}
+
+/// Start of the line containing `index`
+pub(super) fn line_start(text: &[char], index: usize) -> usize {
+    let index = index.min(text.len());
+    text[..index]
+        .iter()
+        .rposition(|&c| c == '\n')
+        .map_or(0, |p| p + 1)
+}
+
+/// End of the line containing `index` (its newline, or the end of text)
+pub(super) fn line_end(text: &[char], index: usize) -> usize {
+    let index = index.min(text.len());
+    text[index..]
+        .iter()
+        .position(|&c| c == '\n')
+        .map_or(text.len(), |p| index + p)
+}
+
+/// Emacs keys, which take precedence over keymap shortcuts
+fn emacs_claims_key(code: KeyCode, modifiers: KeyModifiers) -> bool {
+    let KeyCode::Char(c) = code else {
+        return code == KeyCode::Backspace && modifiers.contains(KeyModifiers::ALT);
+    };
+    if modifiers.contains(KeyModifiers::CONTROL) {
+        matches!(
+            c,
+            'f' | 'b'
+                | 'n'
+                | 'p'
+                | 'd'
+                | 'w'
+                | 'k'
+                | 'u'
+                | 'y'
+                | 't'
+                | 'g'
+                | ' '
+                | '@'
+                | '2'
+                | '/'
+                | '_'
+                | '7'
+        )
+    } else if modifiers.contains(KeyModifiers::ALT) {
+        matches!(c, 'f' | 'b' | 'd' | 'w' | 'y' | 'u' | 'l' | 'c' | '<' | '>')
+    } else {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_edit_mode_names() {
+        for mode in EditMode::ALL {
+            assert_eq!(EditMode::from_name(mode.name()), Some(mode));
+        }
+        assert_eq!(EditMode::from_name(" VIM "), Some(EditMode::Vim));
+        assert_eq!(EditMode::from_name("nano"), None);
+        assert_eq!(EditMode::from_name(""), None);
+    }
+
+    #[test]
+    fn test_line_bounds() {
+        let text: Vec<char> = "ab\ncd".chars().collect();
+        let trailing: Vec<char> = "ab\n".chars().collect();
+        let cases: &[(&[char], usize, usize, usize)] = &[
+            (&[], 0, 0, 0),
+            (&[], 5, 0, 0),
+            (&text, 0, 0, 2),
+            (&text, 2, 0, 2),
+            (&text, 3, 3, 5),
+            (&text, 5, 3, 5),
+            (&text, 99, 3, 5),
+            (&trailing, 2, 0, 2),
+            (&trailing, 3, 3, 3),
+        ];
+        for &(text, index, start, end) in cases {
+            assert_eq!(
+                (line_start(text, index), line_end(text, index)),
+                (start, end),
+                "{index} in {text:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_multibyte_offsets() {
+        let mut input = MultiLineInput::new(5);
+        input.insert_text("héllo wörld");
+        assert_eq!(input.byte_offset(2), 3);
+        assert_eq!(input.byte_offset(99), input.content().len());
+        assert_eq!(input.replace_chars(6, 11, "ü"), "wörld");
+        assert_eq!(input.content(), "héllo ü");
+        input.set_char_cursor(7);
+        assert_eq!(input.char_cursor(), 7);
+    }
+
+    #[test]
+    fn test_undo_groups_and_cap() {
+        let mut input = MultiLineInput::new(5);
+        input.set_edit_mode(EditMode::Emacs);
+        for c in "abc".chars() {
+            input.handle_key(KeyCode::Char(c), KeyModifiers::NONE);
+        }
+        // Moving closes the group, so the next run of typing gets its own
+        input.handle_key(KeyCode::Char('b'), KeyModifiers::CONTROL);
+        input.handle_key(KeyCode::Char('x'), KeyModifiers::NONE);
+        assert_eq!(input.content(), "abxc");
+        assert!(input.undo());
+        assert_eq!(input.content(), "abc");
+        assert!(input.redo());
+        assert_eq!(input.content(), "abxc");
+        assert!(input.undo() && input.undo());
+        assert_eq!(input.content(), "");
+        assert!(!input.undo());
+
+        input.clear();
+        input.set_edit_mode(EditMode::Emacs);
+        for _ in 0..MAX_UNDO + 5 {
+            input.handle_key(KeyCode::Char('x'), KeyModifiers::NONE);
+            input.handle_key(KeyCode::Char('b'), KeyModifiers::CONTROL);
+        }
+        while input.undo() {}
+        assert_eq!(input.content(), "xxxxx");
+    }
+
+    #[test]
+    fn test_set_edit_mode_clears_history() {
+        let mut input = MultiLineInput::new(5);
+        input.set_edit_mode(EditMode::Emacs);
+        input.handle_key(KeyCode::Char('x'), KeyModifiers::NONE);
+        input.set_edit_mode(EditMode::Vim);
+        assert!(!input.undo());
+        assert_eq!(input.mode_label().as_deref(), Some("INSERT"));
+        input.set_edit_mode(EditMode::Standard);
+        assert_eq!(input.mode_label(), None);
+    }
+
+    #[test]
+    fn test_emacs_claims_key() {
+        let cases = [
+            (KeyCode::Char('b'), KeyModifiers::CONTROL, true),
+            (KeyCode::Char('y'), KeyModifiers::CONTROL, true),
+            (KeyCode::Char('f'), KeyModifiers::ALT, true),
+            (KeyCode::Backspace, KeyModifiers::ALT, true),
+            (KeyCode::Char('c'), KeyModifiers::CONTROL, false),
+            (KeyCode::Char('b'), KeyModifiers::NONE, false),
+            (KeyCode::Backspace, KeyModifiers::NONE, false),
+            (KeyCode::Enter, KeyModifiers::NONE, false),
+        ];
+        for (code, modifiers, claimed) in cases {
+            assert_eq!(
+                emacs_claims_key(code, modifiers),
+                claimed,
+                "{code:?} {modifiers:?}"
+            );
+        }
+    }
+}
@@ -4,47 +4,40 @@ use super::MultiLineInput;
 
 impl MultiLineInput {
     pub(super) fn update_visual_cursor(&mut self) {
+        self.cursor_visual = self.visual_position_of(self.cursor_position);
+    }
+
+    /// Visual position (wrapped line, byte column) of a byte offset
+    pub(super) fn visual_position_of(&self, position: usize) -> (usize, usize) {
         let lines = self.get_wrapped_lines();
         let mut byte_pos = 0;
-        let mut found = false;
 
         for (line_idx, line) in lines.iter().enumerate() {
             let mut line_byte_pos = 0;
             for ch in line.chars() {
-                if byte_pos == self.cursor_position {
-                    self.cursor_visual = (line_idx, line_byte_pos);
-                    found = true;
-                    break;
+                if byte_pos == position {
+                    return (line_idx, line_byte_pos);
                 }
                 byte_pos += ch.len_utf8();
                 line_byte_pos += ch.len_utf8();
             }
 
-            if found {
-                break;
-            }
-
             // Account for newline - only if there's actually a newline character (not soft wrap)
             if line_idx < lines.len() - 1
                 && byte_pos < self.content.len()
                 && self.content.as_bytes().get(byte_pos) == Some(&b'\n')
             {
-                if byte_pos == self.cursor_position {
-                    self.cursor_visual = (line_idx, line.len());
-                    found = true;
-                    break;
+                if byte_pos == position {
+                    return (line_idx, line.len());
                 }
                 byte_pos += 1;
             }
         }
 
-        if !found {
-            // Cursor at end
-            if let Some(last_line) = lines.last() {
-                self.cursor_visual = (lines.len() - 1, last_line.len());
-            } else {
-                self.cursor_visual = (0, 0);
-            }
+        // Position at end
+        match lines.last() {
+            Some(last_line) => (lines.len() - 1, last_line.len()),
+            None => (0, 0),
         }
     }
 
@@ -0,0 +1,1560 @@
+//! Vim editing mode
+//!
+//! Normal-mode keys are collected until they form a command
+//! (`"a3dw`, `ci(`, `2fx`), which is then parsed and run. Arrow keys and
+//! Home/End are translated to their Vim equivalents; keys Vim has no use for
+//! (Enter, Ctrl+V, ...) fall through to the standard editor, so Enter still
+//! submits from normal mode.
+
+use std::collections::HashMap;
+
+use arboard::Clipboard;
+use crossterm::event::{KeyCode, KeyModifiers};
+
+use super::editor::InputAction;
+use super::modes::{line_end, line_start};
+use super::MultiLineInput;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum VimMode {
+    #[default]
+    Insert,
+    Normal,
+    Visual,
+    VisualLine,
+}
+
+/// Register contents
+#[derive(Debug, Clone)]
+struct Register {
+    text: String,
+    linewise: bool,
+}
+
+/// A change `.` can repeat: the command plus keys typed in insert mode
+#[derive(Debug, Clone)]
+struct Change {
+    command: Parsed,
+    inserted: Vec<(KeyCode, KeyModifiers)>,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct VimState {
+    mode: VimMode,
+    /// Keys typed towards the next command
+    pending: Vec<char>,
+    /// Other end of the visual selection (char index)
+    anchor: usize,
+    registers: HashMap<char, Register>,
+    /// Last f/F/t/T search, for `;` and `,`
+    last_find: Option<(char, char)>,
+    last_change: Option<Change>,
+    /// Insert-mode keys are being added to `last_change`
+    recording: bool,
+    /// `.` is replaying `last_change`
+    replaying: bool,
+}
+
+impl VimState {
+    /// Back to insert mode with nothing pending, keeping registers
+    pub(super) fn reset_mode(&mut self) {
+        self.mode = VimMode::Insert;
+        self.pending.clear();
+        self.recording = false;
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Motion {
+    key: char,
+    arg: Option<char>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Target {
+    Motion(Motion),
+    /// Text object: `i` or `a`, then the object key
+    Object(char, char),
+    /// Doubled operator (`dd`, `cc`, `yy`)
+    Line,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Command {
+    Motion(Motion),
+    Operator(char, Target),
+    Replace(char),
+    /// Visual mode text object selection
+    Object(char, char),
+    Simple(char),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Parsed {
+    register: Option<char>,
+    count: Option<usize>,
+    command: Command,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Parse<T> {
+    Incomplete,
+    Invalid,
+    Done(T),
+}
+
+/// Where a motion lands and how an operator treats the range up to it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Landing {
+    pos: usize,
+    inclusive: bool,
+    linewise: bool,
+}
+
+const NORMAL_SIMPLE: &str = "xXsSDCYpPJ~u.iaIAoOvV";
+const VISUAL_SIMPLE: &str = "dxcsy~uUpPJovV";
+
+fn is_register(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '"' | '_' | '+' | '*' | '-')
+}
+
+fn is_object(c: char) -> bool {
+    matches!(
+        c,
+        'w' | 'W' | '"' | '\'' | '`' | '(' | ')' | 'b' | '[' | ']' | '{' | '}' | 'B' | '<' | '>'
+    )
+}
+
+/// Read a count starting at `i` (a leading `0` is the motion, not a count)
+fn parse_count(keys: &[char], mut i: usize) -> (Option<usize>, usize) {
+    let start = i;
+    while i < keys.len() && keys[i].is_ascii_digit() && !(i == start && keys[i] == '0') {
+        i += 1;
+    }
+    let count = keys[start..i]
+        .iter()
+        .collect::<String>()
+        .parse::<usize>()
+        .ok()
+        .map(|n| n.min(10_000));
+    (count, i)
+}
+
+fn parse_motion(keys: &[char]) -> Parse<Motion> {
+    let Some(&key) = keys.first() else {
+        return Parse::Incomplete;
+    };
+    let motion = match key {
+        'h' | 'j' | 'k' | 'l' | 'w' | 'b' | 'e' | 'W' | 'B' | 'E' | '0' | '^' | '$' | 'G' | ';'
+        | ',' | '%' => Motion { key, arg: None },
+        'g' => match keys.get(1) {
+            None => return Parse::Incomplete,
+            Some('g') => Motion { key, arg: None },
+            Some(_) => return Parse::Invalid,
+        },
+        'f' | 'F' | 't' | 'T' => match keys.get(1) {
+            None => return Parse::Incomplete,
+            Some(&c) => Motion { key, arg: Some(c) },
+        },
+        _ => return Parse::Invalid,
+    };
+    let len = if key == 'g' || motion.arg.is_some() {
+        2
+    } else {
+        1
+    };
+    if keys.len() == len {
+        Parse::Done(motion)
+    } else {
+        Parse::Invalid
+    }
+}
+
+/// Parse pending keys into a command, in normal or visual mode
+fn parse(keys: &[char], visual: bool) -> Parse<Parsed> {
+    let mut i = 0;
+    let mut register = None;
+    if keys.first() == Some(&'"') {
+        match keys.get(1) {
+            None => return Parse::Incomplete,
+            Some(&c) if is_register(c) => register = Some(c),
+            Some(_) => return Parse::Invalid,
+        }
+        i = 2;
+    }
+    let (count, i) = parse_count(keys, i);
+    let Some(&key) = keys.get(i) else {
+        return Parse::Incomplete;
+    };
+    let rest = &keys[i + 1..];
+    let (command, count) = match key {
+        'd' | 'c' | 'y' if !visual => {
+            let (inner_count, j) = parse_count(rest, 0);
+            let Some(&t) = rest.get(j) else {
+                return Parse::Incomplete;
+            };
+            let target = if t == key {
+                if rest.len() > j + 1 {
+                    return Parse::Invalid;
+                }
+                Target::Line
+            } else if t == 'i' || t == 'a' {
+                match rest.get(j + 1) {
+                    None => return Parse::Incomplete,
+                    Some(&o) if is_object(o) && rest.len() == j + 2 => Target::Object(t, o),
+                    Some(_) => return Parse::Invalid,
+                }
+            } else {
+                match parse_motion(&rest[j..]) {
+                    Parse::Done(m) => Target::Motion(m),
+                    Parse::Incomplete => return Parse::Incomplete,
+                    Parse::Invalid => return Parse::Invalid,
+                }
+            };
+            let count = match (count, inner_count) {
+                (None, None) => None,
+                (a, b) => Some(a.unwrap_or(1) * b.unwrap_or(1)),
+            };
+            (Command::Operator(key, target), count)
+        }
+        'i' | 'a' if visual => match rest.first() {
+            None => return Parse::Incomplete,
+            Some(&o) if is_object(o) && rest.len() == 1 => (Command::Object(key, o), count),
+            Some(_) => return Parse::Invalid,
+        },
+        'r' => match rest {
+            [] => return Parse::Incomplete,
+            [c] => (Command::Replace(*c), count),
+            _ => return Parse::Invalid,
+        },
+        _ if (if visual { VISUAL_SIMPLE } else { NORMAL_SIMPLE }).contains(key) => {
+            if !rest.is_empty() {
+                return Parse::Invalid;
+            }
+            (Command::Simple(key), count)
+        }
+        _ => match parse_motion(&keys[i..]) {
+            Parse::Done(m) => (Command::Motion(m), count),
+            Parse::Incomplete => return Parse::Incomplete,
+            Parse::Invalid => return Parse::Invalid,
+        },
+    };
+    Parse::Done(Parsed {
+        register,
+        count,
+        command,
+    })
+}
+
+/// Word class: 0 whitespace, 1 word characters, 2 punctuation
+/// (`big` words put everything but whitespace in one class)
+fn class(c: char, big: bool) -> u8 {
+    if c.is_whitespace() {
+        0
+    } else if big || c.is_alphanumeric() || c == '_' {
+        1
+    } else {
+        2
+    }
+}
+
+fn next_word_start(text: &[char], mut i: usize, big: bool) -> usize {
+    if i >= text.len() {
+        return text.len();
+    }
+    let c = class(text[i], big);
+    if c != 0 {
+        while i < text.len() && class(text[i], big) == c {
+            i += 1;
+        }
+    }
+    while i < text.len() && class(text[i], big) == 0 {
+        i += 1;
+    }
+    i
+}
+
+fn word_end(text: &[char], mut i: usize, big: bool) -> usize {
+    i += 1;
+    while i < text.len() && class(text[i], big) == 0 {
+        i += 1;
+    }
+    if i >= text.len() {
+        return text.len().saturating_sub(1);
+    }
+    let c = class(text[i], big);
+    while i + 1 < text.len() && class(text[i + 1], big) == c {
+        i += 1;
+    }
+    i
+}
+
+fn prev_word_start(text: &[char], mut i: usize, big: bool) -> usize {
+    while i > 0 && class(text[i - 1], big) == 0 {
+        i -= 1;
+    }
+    if i == 0 {
+        return 0;
+    }
+    let c = class(text[i - 1], big);
+    while i > 0 && class(text[i - 1], big) == c {
+        i -= 1;
+    }
+    i
+}
+
+/// First non-blank character of the line containing `index`
+fn first_non_blank(text: &[char], index: usize) -> usize {
+    let (start, end) = (line_start(text, index), line_end(text, index));
+    (start..end)
+        .find(|&i| !text[i].is_whitespace())
+        .unwrap_or(end)
+}
+
+/// Start of the line `n` lines below (negative: above) the one containing
+/// `index`, or `None` if there is no such line
+fn line_offset(text: &[char], index: usize, n: isize) -> Option<usize> {
+    let mut start = line_start(text, index);
+    for _ in 0..n.unsigned_abs() {
+        if n > 0 {
+            let end = line_end(text, start);
+            if end >= text.len() {
+                return None;
+            }
+            start = end + 1;
+        } else {
+            if start == 0 {
+                return None;
+            }
+            start = line_start(text, start - 1);
+        }
+    }
+    Some(start)
+}
+
+/// Start of line number `n` (1-based), clamped to the last line
+fn nth_line(text: &[char], n: usize) -> usize {
+    let mut start = 0;
+    for _ in 1..n {
+        match line_offset(text, start, 1) {
+            Some(next) => start = next,
+            None => break,
+        }
+    }
+    start
+}
+
+/// f/F/t/T within the current line
+fn find_in_line(
+    text: &[char],
+    cur: usize,
+    key: char,
+    target: char,
+    count: usize,
+) -> Option<Landing> {
+    let (start, end) = (line_start(text, cur), line_end(text, cur));
+    let matches = |i: &usize| text[*i] == target;
+    if key == 'f' || key == 't' {
+        // `t` starting right before a match should skip it
+        let from = if key == 't' { cur + 2 } else { cur + 1 };
+        let i = (from.min(end)..end).filter(matches).nth(count - 1)?;
+        Some(Landing {
+            pos: if key == 't' { i - 1 } else { i },
+            inclusive: true,
+            linewise: false,
+        })
+    } else {
+        let from = if key == 'T' {
+            cur.saturating_sub(1)
+        } else {
+            cur
+        };
+        let i = (start..from.max(start))
+            .rev()
+            .filter(matches)
+            .nth(count - 1)?;
+        Some(Landing {
+            pos: if key == 'T' { i + 1 } else { i },
+            inclusive: false,
+            linewise: false,
+        })
+    }
+}
+
+fn bracket_pair(c: char) -> Option<(char, char)> {
+    match c {
+        '(' | ')' | 'b' => Some(('(', ')')),
+        '[' | ']' => Some(('[', ']')),
+        '{' | '}' | 'B' => Some(('{', '}')),
+        '<' | '>' => Some(('<', '>')),
+        _ => None,
+    }
+}
+
+/// Matching bracket for the bracket at `i`
+fn match_bracket(text: &[char], i: usize) -> Option<usize> {
+    let (open, close) = bracket_pair(text[i]).filter(|_| text[i] != 'b' && text[i] != 'B')?;
+    let mut depth = 0usize;
+    if text[i] == open {
+        for (j, &c) in text.iter().enumerate().skip(i) {
+            if c == open {
+                depth += 1;
+            } else if c == close {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(j);
+                }
+            }
+        }
+    } else {
+        for j in (0..=i).rev() {
+            if text[j] == close {
+                depth += 1;
+            } else if text[j] == open {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(j);
+                }
+            }
+        }
+    }
+    None
+}
+
+/// Range (exclusive end) of a text object around `cur`
+fn text_object(text: &[char], cur: usize, around: bool, object: char) -> Option<(usize, usize)> {
+    if text.is_empty() {
+        return None;
+    }
+    let cur = cur.min(text.len() - 1);
+    match object {
+        'w' | 'W' => {
+            let big = object == 'W';
+            let (ls, le) = (line_start(text, cur), line_end(text, cur));
+            if cur >= le {
+                return None;
+            }
+            let c = class(text[cur], big);
+            let mut start = cur;
+            while start > ls && class(text[start - 1], big) == c {
+                start -= 1;
+            }
+            let mut end = cur + 1;
+            while end < le && class(text[end], big) == c {
+                end += 1;
+            }
+            if around && c != 0 {
+                let trailing = end;
+                while end < le && class(text[end], big) == 0 {
+                    end += 1;
+                }
+                if end == trailing {
+                    while start > ls && class(text[start - 1], big) == 0 {
+                        start -= 1;
+                    }
+                }
+            }
+            Some((start, end))
+        }
+        '"' | '\'' | '`' => {
+            let (ls, le) = (line_start(text, cur), line_end(text, cur));
+            let quotes: Vec<usize> = (ls..le).filter(|&i| text[i] == object).collect();
+            let (open, close) = quotes
+                .chunks_exact(2)
+                .map(|p| (p[0], p[1]))
+                .find(|&(_, close)| cur <= close)?;
+            if around {
+                Some((open, close + 1))
+            } else {
+                Some((open + 1, close))
+            }
+        }
+        _ => {
+            let (open, close) = bracket_pair(object)?;
+            let start = if text[cur] == open {
+                cur
+            } else if text[cur] == close {
+                match_bracket(text, cur)?
+            } else {
+                let mut depth = 0usize;
+                let mut found = None;
+                for j in (0..cur).rev() {
+                    if text[j] == close {
+                        depth += 1;
+                    } else if text[j] == open {
+                        if depth == 0 {
+                            found = Some(j);
+                            break;
+                        }
+                        depth -= 1;
+                    }
+                }
+                found?
+            };
+            let end = match_bracket(text, start)?;
+            if around {
+                Some((start, end + 1))
+            } else {
+                Some((start + 1, end))
+            }
+        }
+    }
+}
+
+fn toggle_case(c: char) -> String {
+    if c.is_uppercase() {
+        c.to_lowercase().collect()
+    } else {
+        c.to_uppercase().collect()
+    }
+}
+
+impl MultiLineInput {
+    pub(super) fn vim_in_insert(&self) -> bool {
+        self.vim.mode == VimMode::Insert
+    }
+
+    fn vim_in_visual(&self) -> bool {
+        matches!(self.vim.mode, VimMode::Visual | VimMode::VisualLine)
+    }
+
+    pub(super) fn vim_label(&self) -> String {
+        let label = match self.vim.mode {
+            VimMode::Insert => "INSERT",
+            VimMode::Normal => "NORMAL",
+            VimMode::Visual => "VISUAL",
+            VimMode::VisualLine => "V-LINE",
+        };
+        if self.vim.pending.is_empty() {
+            label.to_string()
+        } else {
+            format!("{} {}", label, self.vim.pending.iter().collect::<String>())
+        }
+    }
+
+    pub(super) fn vim_claims_key(&self, code: KeyCode, modifiers: KeyModifiers) -> bool {
+        let plain = !modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT);
+        match self.vim.mode {
+            VimMode::Insert => code == KeyCode::Esc,
+            // A bare Esc in normal mode is left to the keymap (interrupt)
+            VimMode::Normal => match code {
+                KeyCode::Esc => !self.vim.pending.is_empty(),
+                KeyCode::Char('r') if modifiers.contains(KeyModifiers::CONTROL) => true,
+                KeyCode::Char(_) => plain,
+                _ => false,
+            },
+            VimMode::Visual | VimMode::VisualLine => {
+                code == KeyCode::Esc || (matches!(code, KeyCode::Char(_)) && plain)
+            }
+        }
+    }
+
+    /// Visual selection as a char range (exclusive end)
+    pub(super) fn vim_selection(&self) -> Option<(usize, usize)> {
+        if !self.vim_in_visual() {
+            return None;
+        }
+        let text: Vec<char> = self.content.chars().collect();
+        Some(self.visual_range(&text))
+    }
+
+    fn visual_range(&self, text: &[char]) -> (usize, usize) {
+        let cur = self.char_cursor();
+        let (a, b) = (self.vim.anchor.min(cur), self.vim.anchor.max(cur));
+        if self.vim.mode == VimMode::VisualLine {
+            (line_start(text, a), (line_end(text, b) + 1).min(text.len()))
+        } else {
+            (a, (b + 1).min(text.len()))
+        }
+    }
+
+    pub(super) fn handle_vim_key(&mut self, code: KeyCode, modifiers: KeyModifiers) -> InputAction {
+        if self.vim.mode == VimMode::Insert {
+            if code == KeyCode::Esc {
+                self.vim_leave_insert();
+                return InputAction::Continue;
+            }
+            if self.vim.recording && code != KeyCode::Enter {
+                if let Some(change) = self.vim.last_change.as_mut() {
+                    change.inserted.push((code, modifiers));
+                }
+            }
+            return self.handle_key_impl(code, modifiers);
+        }
+
+        let plain = !modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT);
+        let key = match code {
+            KeyCode::Esc => {
+                self.vim.pending.clear();
+                if self.vim_in_visual() {
+                    self.vim.mode = VimMode::Normal;
+                    self.vim_clamp_cursor();
+                }
+                return InputAction::Continue;
+            }
+            KeyCode::Char('r') if modifiers.contains(KeyModifiers::CONTROL) => {
+                self.vim.pending.clear();
+                return self.vim_changed_since(|this| {
+                    this.redo();
+                });
+            }
+            KeyCode::Char(c) if plain && c != '\n' && c != '\r' => c,
+            KeyCode::Left | KeyCode::Backspace => 'h',
+            KeyCode::Right => 'l',
+            KeyCode::Up => 'k',
+            KeyCode::Down => 'j',
+            KeyCode::Home => '0',
+            KeyCode::End => '$',
+            KeyCode::Delete => 'x',
+            _ => return self.handle_key_impl(code, modifiers),
+        };
+
+        self.vim.pending.push(key);
+        let visual = self.vim_in_visual();
+        match parse(&self.vim.pending, visual) {
+            Parse::Incomplete => InputAction::Continue,
+            Parse::Invalid => {
+                self.vim.pending.clear();
+                InputAction::Continue
+            }
+            Parse::Done(parsed) => {
+                self.vim.pending.clear();
+                self.vim_changed_since(|this| {
+                    if visual {
+                        this.vim_visual(parsed);
+                    } else {
+                        this.vim_normal(parsed);
+                    }
+                })
+            }
+        }
+    }
+
+    /// Run `f` and report whether the content changed
+    fn vim_changed_since(&mut self, f: impl FnOnce(&mut Self)) -> InputAction {
+        let before = self.content.clone();
+        f(self);
+        if self.content != before {
+            InputAction::ContentChanged
+        } else {
+            InputAction::Continue
+        }
+    }
+
+    fn vim_enter_insert(&mut self, at: usize) {
+        self.set_char_cursor(at);
+        self.vim.mode = VimMode::Insert;
+        self.vim.recording = !self.vim.replaying;
+    }
+
+    fn vim_leave_insert(&mut self) {
+        self.vim.mode = VimMode::Normal;
+        self.vim.recording = false;
+        let text: Vec<char> = self.content.chars().collect();
+        let cur = self.char_cursor();
+        if cur > line_start(&text, cur) {
+            self.set_char_cursor(cur - 1);
+        }
+    }
+
+    /// Keep the normal-mode cursor on a character, not past the line end
+    fn vim_clamp_cursor(&mut self) {
+        let text: Vec<char> = self.content.chars().collect();
+        let cur = self.char_cursor();
+        if cur == line_end(&text, cur) && cur > line_start(&text, cur) {
+            self.set_char_cursor(cur - 1);
+        }
+    }
+
+    fn record_change(&mut self, command: Parsed) {
+        if !self.vim.replaying {
+            self.vim.last_change = Some(Change {
+                command,
+                inserted: Vec::new(),
+            });
+        }
+    }
+
+    fn vim_normal(&mut self, parsed: Parsed) {
+        let text: Vec<char> = self.content.chars().collect();
+        let cur = self.char_cursor();
+        let n = parsed.count.unwrap_or(1);
+        match parsed.command {
+            Command::Motion(motion) => {
+                if let Some(landing) = self.resolve_motion(&text, cur, motion, parsed.count) {
+                    let pos = if motion.key == 'g' || motion.key == 'G' {
+                        first_non_blank(&text, landing.pos)
+                    } else {
+                        landing.pos
+                    };
+                    self.set_char_cursor(pos);
+                }
+            }
+            Command::Operator(op, target) => {
+                if op != 'y' {
+                    self.record_change(parsed);
+                }
+                self.vim_operator(&text, cur, parsed, op, target);
+            }
+            Command::Replace(c) => {
+                let end = line_end(&text, cur);
+                if cur + n <= end {
+                    self.record_change(parsed);
+                    let with = c.to_string().repeat(n);
+                    self.replace_chars(cur, cur + n, &with);
+                    self.set_char_cursor(cur + n - 1);
+                }
+            }
+            Command::Object(..) => {}
+            Command::Simple(key) => self.vim_simple(&text, cur, parsed, key),
+        }
+        if self.vim.mode == VimMode::Normal {
+            self.vim_clamp_cursor();
+        }
+    }
+
+    fn vim_simple(&mut self, text: &[char], cur: usize, parsed: Parsed, key: char) {
+        let n = parsed.count.unwrap_or(1);
+        let (ls, le) = (line_start(text, cur), line_end(text, cur));
+        // Shorthands for operator commands
+        let motion = |key| Target::Motion(Motion { key, arg: None });
+        let alias = match key {
+            'x' if cur < le => Some(('d', motion('l'))),
+            'X' if cur > ls => Some(('d', motion('h'))),
+            's' => Some(('c', motion('l'))),
+            'S' => Some(('c', Target::Line)),
+            'D' => Some(('d', motion('$'))),
+            'C' => Some(('c', motion('$'))),
+            'Y' => Some(('y', Target::Line)),
+            _ => None,
+        };
+        if let Some((op, target)) = alias {
+            if op != 'y' {
+                self.record_change(parsed);
+            }
+            self.vim_operator(text, cur, parsed, op, target);
+            return;
+        }
+        match key {
+            'p' | 'P' => {
+                self.record_change(parsed);
+                self.vim_put(text, cur, parsed.register, n, key == 'P');
+            }
+            'J' => {
+                self.record_change(parsed);
+                self.join_lines(cur, n.max(2) - 1);
+            }
+            '~' if cur < le => {
+                self.record_change(parsed);
+                let end = (cur + n).min(le);
+                let toggled: String = text[cur..end].iter().map(|&c| toggle_case(c)).collect();
+                self.replace_chars(cur, end, &toggled);
+                self.set_char_cursor(end);
+            }
+            'u' => {
+                for _ in 0..n {
+                    if !self.undo() {
+                        break;
+                    }
+                }
+            }
+            '.' => self.vim_repeat(parsed.count),
+            'i' | 'a' | 'I' | 'A' | 'o' | 'O' => {
+                self.record_change(parsed);
+                match key {
+                    'i' => self.vim_enter_insert(cur),
+                    'a' => self.vim_enter_insert((cur + 1).min(le)),
+                    'I' => self.vim_enter_insert(first_non_blank(text, cur)),
+                    'A' => self.vim_enter_insert(le),
+                    'o' => {
+                        self.replace_chars(le, le, "\n");
+                        self.vim_enter_insert(le + 1);
+                    }
+                    _ => {
+                        self.replace_chars(ls, ls, "\n");
+                        self.vim_enter_insert(ls);
+                    }
+                }
+            }
+            'v' | 'V' => {
+                self.vim.anchor = cur;
+                self.vim.mode = if key == 'v' {
+                    VimMode::Visual
+                } else {
+                    VimMode::VisualLine
+                };
+            }
+            _ => {}
+        }
+    }
+
+    fn vim_repeat(&mut self, count: Option<usize>) {
+        let Some(mut change) = self.vim.last_change.clone() else {
+            return;
+        };
+        if count.is_some() {
+            change.command.count = count;
+        }
+        self.vim.replaying = true;
+        self.vim_normal(change.command);
+        if self.vim.mode == VimMode::Insert {
+            for (code, modifiers) in change.inserted {
+                self.handle_key_impl(code, modifiers);
+            }
+            self.vim_leave_insert();
+        }
+        self.vim.replaying = false;
+    }
+
+    /// Resolve a motion from `cur`; `None` when it can't move
+    fn resolve_motion(
+        &mut self,
+        text: &[char],
+        cur: usize,
+        motion: Motion,
+        count: Option<usize>,
+    ) -> Option<Landing> {
+        let n = count.unwrap_or(1);
+        let (ls, le) = (line_start(text, cur), line_end(text, cur));
+        let exclusive = |pos| Landing {
+            pos,
+            inclusive: false,
+            linewise: false,
+        };
+        let inclusive = |pos| Landing {
+            pos,
+            inclusive: true,
+            linewise: false,
+        };
+        let linewise = |pos| Landing {
+            pos,
+            inclusive: false,
+            linewise: true,
+        };
+        let landing = match motion.key {
+            'h' => exclusive(cur.saturating_sub(n).max(ls)),
+            'l' => exclusive((cur + n).min(le)),
+            'j' | 'k' => {
+                // Counts past the first or last line go as far as they can
+                let step = if motion.key == 'j' { 1 } else { -1 };
+                let mut start = ls;
+                for _ in 0..n {
+                    match line_offset(text, start, step) {
+                        Some(next) => start = next,
+                        None => break,
+                    }
+                }
+                if start == ls {
+                    return None;
+                }
+                linewise((start + (cur - ls)).min(line_end(text, start)))
+            }
+            'w' | 'W' => {
+                let mut pos = cur;
+                for _ in 0..n {
+                    pos = next_word_start(text, pos, motion.key == 'W');
+                }
+                exclusive(pos)
+            }
+            'e' | 'E' => {
+                let mut pos = cur;
+                for _ in 0..n {
+                    pos = word_end(text, pos, motion.key == 'E');
+                }
+                inclusive(pos)
+            }
+            'b' | 'B' => {
+                let mut pos = cur;
+                for _ in 0..n {
+                    pos = prev_word_start(text, pos, motion.key == 'B');
+                }
+                exclusive(pos)
+            }
+            '0' => exclusive(ls),
+            '^' => exclusive(first_non_blank(text, cur)),
+            '$' => {
+                let start = line_offset(text, cur, n as isize - 1).unwrap_or(ls);
+                let end = line_end(text, start);
+                inclusive(end.saturating_sub(1).max(line_start(text, end)))
+            }
+            'g' => linewise(match count {
+                Some(line) => nth_line(text, line),
+                None => 0,
+            }),
+            'G' => linewise(match count {
+                Some(line) => nth_line(text, line),
+                None => line_start(text, text.len()),
+            }),
+            'f' | 'F' | 't' | 'T' => {
+                let target = motion.arg?;
+                self.vim.last_find = Some((motion.key, target));
+                find_in_line(text, cur, motion.key, target, n)?
+            }
+            ';' | ',' => {
+                let (key, target) = self.vim.last_find?;
+                let key = if motion.key == ',' {
+                    match key {
+                        'f' => 'F',
+                        'F' => 'f',
+                        't' => 'T',
+                        _ => 't',
+                    }
+                } else {
+                    key
+                };
+                find_in_line(text, cur, key, target, n)?
+            }
+            '%' => {
+                let from =
+                    (cur..le).find(|&i| matches!(text[i], '(' | ')' | '[' | ']' | '{' | '}'))?;
+                inclusive(match_bracket(text, from)?)
+            }
+            _ => return None,
+        };
+        Some(landing)
+    }
+
+    /// Char range (exclusive end) an operator acts on, and whether it is
+    /// linewise
+    fn operator_range(
+        &mut self,
+        text: &[char],
+        cur: usize,
+        op: char,
+        target: Target,
+        count: Option<usize>,
+    ) -> Option<(usize, usize, bool)> {
+        let n = count.unwrap_or(1);
+        match target {
+            Target::Line => {
+                let last = line_offset(text, cur, n as isize - 1)
+                    .unwrap_or_else(|| line_start(text, text.len()));
+                Some((line_start(text, cur), line_end(text, last), true))
+            }
+            Target::Object(kind, object) => {
+                let (start, end) = text_object(text, cur, kind == 'a', object)?;
+                Some((start, end, false))
+            }
+            Target::Motion(mut motion) => {
+                // `cw` changes to the end of the word, like `ce`
+                if op == 'c'
+                    && matches!(motion.key, 'w' | 'W')
+                    && text.get(cur).is_some_and(|c| !c.is_whitespace())
+                {
+                    motion.key = if motion.key == 'w' { 'e' } else { 'E' };
+                }
+                let landing = self.resolve_motion(text, cur, motion, count)?;
+                let (mut start, mut end) = (cur.min(landing.pos), cur.max(landing.pos));
+                if landing.linewise {
+                    return Some((line_start(text, start), line_end(text, end), true));
+                }
+                // Inclusive motions never take the newline (`d$` on an empty line)
+                if landing.inclusive && text.get(end).is_some_and(|&c| c != '\n') {
+                    end += 1;
+                }
+                // `dw` on the last word of a line stops at the line end
+                if matches!(motion.key, 'w' | 'W') {
+                    let le = line_end(text, cur);
+                    if end > le && le > cur {
+                        end = le;
+                    }
+                }
+                start = start.min(end);
+                Some((start, end, false))
+            }
+        }
+    }
+
+    /// Apply d/c/y to a target. Linewise ranges come without their newline
+    fn vim_operator(
+        &mut self,
+        text: &[char],
+        cur: usize,
+        parsed: Parsed,
+        op: char,
+        target: Target,
+    ) {
+        let Some((start, end, linewise)) = self.operator_range(text, cur, op, target, parsed.count)
+        else {
+            return;
+        };
+        self.vim_apply(text, start, end, linewise, op, parsed.register);
+        if op == 'y' && !linewise {
+            self.set_char_cursor(start.min(cur));
+        }
+    }
+
+    /// Delete, change or yank `start..end` (for linewise ranges `end` is the
+    /// end of the last line, before its newline)
+    fn vim_apply(
+        &mut self,
+        text: &[char],
+        start: usize,
+        end: usize,
+        linewise: bool,
+        op: char,
+        register: Option<char>,
+    ) {
+        let selected: String = text[start..end].iter().collect();
+        if !linewise {
+            self.store_register(register, selected, false, op == 'y');
+            match op {
+                'd' => {
+                    self.replace_chars(start, end, "");
+                    self.set_char_cursor(start);
+                }
+                'c' => {
+                    self.replace_chars(start, end, "");
+                    self.vim_enter_insert(start);
+                }
+                _ => {}
+            }
+            return;
+        }
+
+        self.store_register(register, format!("{}\n", selected), true, op == 'y');
+        match op {
+            'd' => {
+                // Take a newline with the lines: the following one, or the
+                // preceding one for the last line
+                let (from, to) = if end < text.len() {
+                    (start, end + 1)
+                } else {
+                    (start.saturating_sub(1), end)
+                };
+                self.replace_chars(from, to, "");
+                let text: Vec<char> = self.content.chars().collect();
+                let at = from.min(line_start(&text, text.len()));
+                self.set_char_cursor(first_non_blank(&text, at));
+            }
+            'c' => {
+                // Keep the indent of the first line
+                let indent = (start..end)
+                    .find(|&i| !text[i].is_whitespace())
+                    .unwrap_or(end);
+                self.replace_chars(indent, end, "");
+                self.vim_enter_insert(indent);
+            }
+            _ => {}
+        }
+    }
+
+    fn vim_put(
+        &mut self,
+        text: &[char],
+        cur: usize,
+        register: Option<char>,
+        n: usize,
+        before: bool,
+    ) {
+        let Some(reg) = self.read_register(register) else {
+            return;
+        };
+        if reg.linewise {
+            let lines = reg.text.strip_suffix('\n').unwrap_or(&reg.text);
+            let block = vec![lines; n].join("\n");
+            let at = if before {
+                let ls = line_start(text, cur);
+                self.replace_chars(ls, ls, &format!("{}\n", block));
+                ls
+            } else {
+                let le = line_end(text, cur);
+                self.replace_chars(le, le, &format!("\n{}", block));
+                le + 1
+            };
+            let text: Vec<char> = self.content.chars().collect();
+            self.set_char_cursor(first_non_blank(&text, at));
+        } else {
+            let insert = reg.text.repeat(n);
+            let at = if before || cur >= line_end(text, cur) {
+                cur
+            } else {
+                cur + 1
+            };
+            self.replace_chars(at, at, &insert);
+            self.set_char_cursor(at + insert.chars().count().saturating_sub(1));
+        }
+    }
+
+    /// Join `joins` following lines onto the current one
+    fn join_lines(&mut self, cur: usize, joins: usize) {
+        for _ in 0..joins {
+            let text: Vec<char> = self.content.chars().collect();
+            let le = line_end(&text, cur);
+            if le >= text.len() {
+                break;
+            }
+            let mut next = le + 1;
+            while next < text.len() && text[next] != '\n' && text[next].is_whitespace() {
+                next += 1;
+            }
+            let mut start = le;
+            while start > line_start(&text, cur) && text[start - 1] == ' ' {
+                start -= 1;
+            }
+            let glue = if start == line_start(&text, cur)
+                || next >= text.len()
+                || text[next] == '\n'
+                || text[next] == ')'
+            {
+                ""
+            } else {
+                " "
+            };
+            self.replace_chars(start, next, glue);
+            self.set_char_cursor(start);
+        }
+    }
+
+    fn vim_visual(&mut self, parsed: Parsed) {
+        let text: Vec<char> = self.content.chars().collect();
+        let cur = self.char_cursor();
+        let (start, end) = self.visual_range(&text);
+        let linewise = self.vim.mode == VimMode::VisualLine;
+        match parsed.command {
+            Command::Motion(motion) => {
+                if let Some(landing) = self.resolve_motion(&text, cur, motion, parsed.count) {
+                    self.set_char_cursor(landing.pos);
+                }
+                return;
+            }
+            Command::Object(kind, object) => {
+                if let Some((s, e)) = text_object(&text, cur, kind == 'a', object) {
+                    if e > s {
+                        self.vim.anchor = s;
+                        self.set_char_cursor(e - 1);
+                    }
+                }
+                return;
+            }
+            Command::Replace(c) => {
+                let replaced: String = text[start..end]
+                    .iter()
+                    .map(|&ch| if ch == '\n' { '\n' } else { c })
+                    .collect();
+                self.replace_chars(start, end, &replaced);
+                self.set_char_cursor(start);
+            }
+            Command::Operator(..) => {}
+            Command::Simple(key) => match key {
+                'o' => {
+                    let anchor = std::mem::replace(&mut self.vim.anchor, cur);
+                    self.set_char_cursor(anchor);
+                    return;
+                }
+                'v' | 'V' => {
+                    let mode = if key == 'v' {
+                        VimMode::Visual
+                    } else {
+                        VimMode::VisualLine
+                    };
+                    if self.vim.mode == mode {
+                        self.vim.mode = VimMode::Normal;
+                        self.vim_clamp_cursor();
+                    } else {
+                        self.vim.mode = mode;
+                    }
+                    return;
+                }
+                'd' | 'x' | 'c' | 's' | 'y' => {
+                    let op = match key {
+                        'x' => 'd',
+                        's' => 'c',
+                        other => other,
+                    };
+                    // Linewise ranges are handed over without their newline
+                    let end = if linewise && end > start && text.get(end - 1) == Some(&'\n') {
+                        end - 1
+                    } else {
+                        end
+                    };
+                    self.vim.mode = VimMode::Normal;
+                    self.vim_apply(&text, start, end, linewise, op, parsed.register);
+                    if op == 'y' {
+                        self.set_char_cursor(start);
+                    }
+                }
+                '~' | 'u' | 'U' => {
+                    let changed: String = text[start..end]
+                        .iter()
+                        .map(|&c| match key {
+                            'u' => c.to_lowercase().collect(),
+                            'U' => c.to_uppercase().collect(),
+                            _ => toggle_case(c),
+                        })
+                        .collect();
+                    self.replace_chars(start, end, &changed);
+                    self.set_char_cursor(start);
+                }
+                'p' | 'P' => {
+                    if let Some(reg) = self.read_register(parsed.register) {
+                        // The last line has no newline to replace
+                        let last_line = linewise && text.get(end.wrapping_sub(1)) != Some(&'\n');
+                        let insert = if linewise && !reg.linewise && !last_line {
+                            format!("{}\n", reg.text)
+                        } else if (!linewise || last_line) && reg.linewise {
+                            reg.text.trim_end_matches('\n').to_string()
+                        } else {
+                            reg.text
+                        };
+                        let mut replaced: String = text[start..end].iter().collect();
+                        if last_line {
+                            replaced.push('\n');
+                        }
+                        self.replace_chars(start, end, &insert);
+                        self.store_register(None, replaced, linewise, false);
+                        self.set_char_cursor(start);
+                    }
+                }
+                'J' => {
+                    let lines = text[start..end.saturating_sub(1).max(start)]
+                        .iter()
+                        .filter(|&&c| c == '\n')
+                        .count();
+                    self.join_lines(start, lines.max(1));
+                }
+                _ => return,
+            },
+        }
+        if self.vim.mode != VimMode::Insert {
+            self.vim.mode = VimMode::Normal;
+            self.vim_clamp_cursor();
+        }
+    }
+
+    /// Store deleted or yanked text in a register and the unnamed register
+    fn store_register(&mut self, register: Option<char>, text: String, linewise: bool, yank: bool) {
+        let reg = Register { text, linewise };
+        match register {
+            Some('_') => return,
+            Some(c @ ('+' | '*')) => {
+                if let Ok(mut clipboard) = Clipboard::new() {
+                    if let Err(e) = clipboard.set_text(reg.text.clone()) {
+                        tracing::warn!("Failed to copy register {} to clipboard: {}", c, e);
+                    }
+                }
+            }
+            Some(c) if c.is_ascii_uppercase() => {
+                let entry = self
+                    .vim
+                    .registers
+                    .entry(c.to_ascii_lowercase())
+                    .or_insert(Register {
+                        text: String::new(),
+                        linewise,
+                    });
+                entry.text.push_str(&reg.text);
+                entry.linewise |= linewise;
+                let appended = entry.clone();
+                self.vim.registers.insert('"', appended);
+                return;
+            }
+            Some(c) if c != '"' => {
+                self.vim.registers.insert(c, reg.clone());
+            }
+            _ if yank => {
+                self.vim.registers.insert('0', reg.clone());
+            }
+            _ => {}
+        }
+        self.vim.registers.insert('"', reg);
+    }
+
+    fn read_register(&self, register: Option<char>) -> Option<Register> {
+        match register {
+            Some('+' | '*') => {
+                let text = Clipboard::new().ok()?.get_text().ok()?;
+                Some(Register {
+                    linewise: text.ends_with('\n'),
+                    text,
+                })
+            }
+            Some(c) => self.vim.registers.get(&c.to_ascii_lowercase()).cloned(),
+            None => self.vim.registers.get(&'"').cloned(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::EditMode;
+    use super::*;
+
+    fn vim(content: &str, cursor: usize) -> MultiLineInput {
+        let mut input = MultiLineInput::new(5);
+        input.set_edit_mode(EditMode::Vim);
+        input.insert_text(content);
+        input.set_char_cursor(cursor);
+        input.handle_key(KeyCode::Esc, KeyModifiers::NONE);
+        input.set_char_cursor(cursor);
+        input
+    }
+
+    fn keys(input: &mut MultiLineInput, keys: &str) {
+        for c in keys.chars() {
+            match c {
+                '\x1b' => input.handle_key(KeyCode::Esc, KeyModifiers::NONE),
+                '\x12' => input.handle_key(KeyCode::Char('r'), KeyModifiers::CONTROL),
+                c => input.handle_key(KeyCode::Char(c), KeyModifiers::NONE),
+            };
+        }
+    }
+
+    #[test]
+    fn test_parse() {
+        let keys = |s: &str| s.chars().collect::<Vec<_>>();
+        assert_eq!(parse(&keys("d"), false), Parse::Incomplete);
+        assert_eq!(parse(&keys("2d3"), false), Parse::Incomplete);
+        assert_eq!(parse(&keys("dz"), false), Parse::Invalid);
+        let Parse::Done(p) = parse(&keys("\"a2d3w"), false) else {
+            panic!("expected a command");
+        };
+        assert_eq!(p.register, Some('a'));
+        assert_eq!(p.count, Some(6));
+        assert_eq!(
+            p.command,
+            Command::Operator(
+                'd',
+                Target::Motion(Motion {
+                    key: 'w',
+                    arg: None
+                })
+            )
+        );
+        assert_eq!(
+            parse(&keys("ci("), false),
+            Parse::Done(Parsed {
+                register: None,
+                count: None,
+                command: Command::Operator('c', Target::Object('i', '(')),
+            })
+        );
+        assert!(matches!(
+            parse(&keys("0"), false),
+            Parse::Done(Parsed {
+                command: Command::Motion(Motion { key: '0', .. }),
+                ..
+            })
+        ));
+        assert!(matches!(
+            parse(&keys("iw"), true),
+            Parse::Done(Parsed {
+                command: Command::Object('i', 'w'),
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_motions_and_objects() {
+        let text: Vec<char> = "foo.bar baz(qux, \"x y\")".chars().collect();
+        assert_eq!(next_word_start(&text, 0, false), 3);
+        assert_eq!(next_word_start(&text, 0, true), 8);
+        assert_eq!(word_end(&text, 0, false), 2);
+        assert_eq!(prev_word_start(&text, 8, false), 4);
+        assert_eq!(text_object(&text, 13, false, '('), Some((12, 22)));
+        assert_eq!(text_object(&text, 13, true, 'b'), Some((11, 23)));
+        assert_eq!(text_object(&text, 19, false, '"'), Some((18, 21)));
+        assert_eq!(text_object(&text, 5, true, 'w'), Some((4, 8)));
+        assert_eq!(match_bracket(&text, 11), Some(22));
+    }
+
+    #[test]
+    fn test_operators_and_registers() {
+        let mut input = vim("one two three", 0);
+        keys(&mut input, "dw");
+        assert_eq!(input.content(), "two three");
+        keys(&mut input, "\"ayiw$p");
+        assert_eq!(input.content(), "two threetwo");
+        keys(&mut input, "0cwsix\x1b");
+        assert_eq!(input.content(), "six threetwo");
+        keys(&mut input, "u");
+        assert_eq!(input.content(), "two threetwo");
+        keys(&mut input, "\"ap");
+        assert_eq!(input.content(), "ttwowo threetwo");
+
+        let mut input = vim("a\nb\nc", 0);
+        keys(&mut input, "ddp");
+        assert_eq!(input.content(), "b\na\nc");
+        keys(&mut input, "Gdd");
+        assert_eq!(input.content(), "b\na");
+        keys(&mut input, "ggJ");
+        assert_eq!(input.content(), "b a");
+    }
+
+    #[test]
+    fn test_dot_repeat_and_visual() {
+        let mut input = vim("x = 1; y = 2;", 0);
+        keys(&mut input, "f1r9;.");
+        assert_eq!(input.content(), "x = 9; y = 2;");
+        keys(&mut input, "0ciwfoo\x1bw.");
+        assert_eq!(input.content(), "foo foo 9; y = 2;");
+
+        let mut input = vim("hello world", 0);
+        keys(&mut input, "veU");
+        assert_eq!(input.content(), "HELLO world");
+        assert_eq!(input.mode_label().as_deref(), Some("NORMAL"));
+        keys(&mut input, "wviwd");
+        assert_eq!(input.content(), "HELLO ");
+        keys(&mut input, "2d");
+        assert_eq!(input.mode_label().as_deref(), Some("NORMAL 2d"));
+    }
+
+    /// Run each (content, cursor, keys, expected content, expected cursor)
+    /// case from a fresh normal-mode input
+    fn check(cases: &[(&str, usize, &str, &str, usize)]) {
+        for &(content, cursor, typed, expected, expected_cursor) in cases {
+            let mut input = vim(content, cursor);
+            keys(&mut input, typed);
+            assert_eq!(
+                (input.content(), input.char_cursor()),
+                (expected, expected_cursor),
+                "{typed:?} on {content:?} at {cursor}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_counts() {
+        check(&[
+            ("one two three four", 0, "3w", "one two three four", 14),
+            ("one two three four", 0, "2dw", "three four", 0),
+            ("one two three four", 0, "d2w", "three four", 0),
+            ("abcdef", 0, "3x", "def", 0),
+            ("abcdef", 5, "3X", "abf", 2),
+            ("abc", 0, "3rx", "xxx", 2),
+            ("abc", 0, "4rx", "abc", 0),
+            ("a-b-a-b", 0, "2fb", "a-b-a-b", 6),
+            ("a\nb\nc\nd", 0, "2dd", "c\nd", 0),
+            ("a\nb\nc", 0, "5dd", "", 0),
+            ("one\ntwo\nthree", 0, "2j", "one\ntwo\nthree", 8),
+            ("one\ntwo\nthree", 0, "9j", "one\ntwo\nthree", 8),
+            ("one\ntwo\nthree", 0, "2G", "one\ntwo\nthree", 4),
+            ("x", 0, "yl3p", "xxxx", 3),
+        ]);
+    }
+
+    #[test]
+    fn test_operator_motions() {
+        check(&[
+            ("foo bar baz", 0, "de", " bar baz", 0),
+            ("foo bar baz", 4, "d$", "foo ", 3),
+            ("foo bar baz", 4, "d0", "bar baz", 0),
+            ("foo bar baz", 8, "db", "foo baz", 4),
+            ("  foo", 4, "d^", "  o", 2),
+            ("a(b c)d", 1, "d%", "ad", 1),
+            ("foo.bar", 0, "dt.", ".bar", 0),
+            ("foo.bar", 0, "df.", "bar", 0),
+            ("foo.bar", 6, "dF.", "foor", 3),
+            ("foo.bar", 6, "dT.", "foo.r", 4),
+            ("one\ntwo", 0, "dw", "\ntwo", 0),
+            ("a\nb\nc", 0, "dj", "c", 0),
+            ("a\nb\nc", 4, "dk", "a", 0),
+            ("a\nb\nc", 2, "dG", "a", 0),
+            ("a\nb\nc", 2, "dgg", "c", 0),
+            ("one two", 0, "cwsix\x1b", "six two", 2),
+            ("  one\ntwo", 2, "ccx\x1b", "  x\ntwo", 2),
+        ]);
+    }
+
+    #[test]
+    fn test_text_objects() {
+        check(&[
+            ("foo bar baz", 5, "diw", "foo  baz", 4),
+            ("foo bar baz", 5, "daw", "foo baz", 4),
+            ("foo bar", 5, "daw", "foo", 2),
+            ("a.b-c d", 2, "diW", " d", 0),
+            ("call(a, b)", 6, "di(", "call()", 5),
+            ("call(a, b)", 6, "da(", "call", 3),
+            ("call(a, b)", 6, "ci)x\x1b", "call(x)", 5),
+            ("f(a(b)c)", 4, "di(", "f(a()c)", 4),
+            ("a [b {c} d] e", 6, "di[", "a [] e", 3),
+            ("a [b {c} d] e", 6, "diB", "a [b {} d] e", 6),
+            ("x <y> z", 3, "di<", "x <> z", 3),
+            ("say \"hi there\" now", 6, "di\"", "say \"\" now", 5),
+            ("say \"hi there\" now", 6, "da\"", "say  now", 4),
+            ("no brackets", 3, "di(", "no brackets", 3),
+            ("it's", 1, "di'", "it's", 1),
+        ]);
+    }
+
+    #[test]
+    fn test_visual_modes() {
+        check(&[
+            ("a\nb\nc", 0, "Vjd", "c", 0),
+            ("a\nb\nc", 2, "Vd", "a\nc", 2),
+            ("a\nb\nc", 4, "Vd", "a\nb", 2),
+            ("a\nb\nc", 0, "VjyGp", "a\nb\nc\na\nb", 6),
+            ("a\nb\nc", 0, "VjJ", "a b\nc", 1),
+            ("a\nb\nc", 0, "VjjJ", "a b c", 3),
+            ("a\nb\nc", 0, "yyjVp", "a\na\nc", 2),
+            ("a\nb\nc", 0, "yyjVpGp", "a\na\nc\nb", 6),
+            ("ab\ny", 0, "yljVp", "ab\na", 3),
+            ("a\ny", 0, "yyjVp", "a\na", 2),
+            ("a\ny", 0, "yyjVpkp", "a\ny\na", 2),
+            ("hello world", 0, "vlld", "lo world", 0),
+            ("hello world", 6, "vey0P", "worldhello world", 4),
+            ("hello", 0, "vllr-", "---lo", 0),
+            ("hello world", 0, "vecbye\x1b", "bye world", 2),
+            ("foo(bar)", 5, "vi(d", "foo()", 4),
+            ("", 0, "vd", "", 0),
+            ("", 0, "Vd", "", 0),
+        ]);
+    }
+
+    #[test]
+    fn test_registers() {
+        check(&[
+            ("one two", 0, "\"ayiww\"byiw0\"bP", "twoone two", 2),
+            ("one two", 0, "\"ayiww\"Ayiw$\"ap", "one twoonetwo", 12),
+            ("one two", 0, "yiwwdiw0\"0P", "oneone ", 2),
+            ("one two", 0, "yiww\"_diw0P", "oneone ", 2),
+            ("a\nb", 0, "\"qyyj\"qp", "a\nb\na", 4),
+            ("a\nb", 0, "\"qyyjdd\"qp", "a\na", 2),
+        ]);
+    }
+
+    #[test]
+    fn test_repeat_and_undo() {
+        check(&[
+            ("a b c d", 0, "x..", " c d", 0),
+            ("one two three", 0, "dw.", "three", 0),
+            ("abcdef", 0, "2x.", "ef", 0),
+            ("abcdef", 0, "x3.", "ef", 0),
+            ("a\nb\nc", 0, "dd.", "c", 0),
+            ("x", 0, "A!\x1b.", "x!!", 2),
+            ("a\nb", 0, "ox\x1bj.", "a\nx\nb\nx", 6),
+            ("ab", 0, "yl.", "ab", 0),
+            ("foo bar", 0, "~.", "FOo bar", 2),
+            ("one two", 0, "dwdwuu", "one two", 0),
+            ("one two", 0, "dwu\x12", "two", 0),
+            ("abc", 0, "ixy\x1bu", "abc", 0),
+            ("abc", 0, "x2u", "abc", 0),
+            ("abc", 0, "xux\x12", "bc", 0),
+        ]);
+    }
+
+    #[test]
+    fn test_empty_buffer_and_last_line() {
+        check(&[
+            ("", 0, "x", "", 0),
+            ("", 0, "dw", "", 0),
+            ("", 0, "dd", "", 0),
+            ("", 0, "p", "", 0),
+            ("", 0, "J", "", 0),
+            ("", 0, "diw", "", 0),
+            ("", 0, "3j", "", 0),
+            ("", 0, "~", "", 0),
+            ("", 0, ".", "", 0),
+            ("", 0, "u", "", 0),
+            ("a\nb", 2, "dd", "a", 0),
+            ("a\nb", 2, "D", "a\n", 2),
+            ("a\nb", 2, "yyP", "a\nb\nb", 2),
+            ("a\nb", 2, "yyp", "a\nb\nb", 4),
+            ("a\nb", 2, "J", "a\nb", 2),
+            ("a\nb", 2, "oc\x1b", "a\nb\nc", 4),
+            ("a\nb", 2, "A!\x1b", "a\nb!", 3),
+            ("a\nb", 2, "$x", "a\n", 2),
+        ]);
+    }
+}
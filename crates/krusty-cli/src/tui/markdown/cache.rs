@@ -100,6 +100,12 @@ impl MarkdownCache {
         }
     }
 
+    /// Drop everything rendered so far (colors depend on the theme)
+    pub fn clear(&mut self) {
+        self.cache.clear();
+        self.legacy_cache.clear();
+    }
+
     /// Get cached rendered markdown (from the links cache)
     pub fn get_rendered(&self, content_hash: u64, width: usize) -> Option<Arc<RenderedMarkdown>> {
         self.cache.get(&(content_hash, width)).cloned()
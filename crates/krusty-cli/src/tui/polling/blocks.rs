@@ -4,8 +4,9 @@
 
 use std::path::Path;
 
-use crate::plan::{PlanFile, PlanManager};
+use crate::plan::{PlanFile, PlanManager, PlanRunEvent, PlanRunSummary, PlanStatus, TaskStatus};
 use crate::tui::blocks::{BuildBlock, ExploreBlock, StreamBlock};
+use crate::tui::components::PlanSidebarState;
 use crate::tui::handlers::commands::generate_krab_from_exploration;
 use crate::tui::utils::AsyncChannels;
 
@@ -101,6 +102,192 @@ pub fn poll_build_progress(
     result
 }
 
+/// Poll /plan run events and builder progress
+///
+/// Mirrors task state changes onto the active plan, saves it, and keeps the
+/// sidebar's run status current. Completes the run's BuildBlock when the
+/// run finishes.
+pub fn poll_plan_run(
+    channels: &mut AsyncChannels,
+    build_blocks: &mut [BuildBlock],
+    active_plan: &mut Option<PlanFile>,
+    plan_manager: &PlanManager,
+    plan_run_id: &mut Option<String>,
+    sidebar: &mut PlanSidebarState,
+) -> PollResult {
+    let mut result = PollResult::new();
+
+    let Some(run_id) = plan_run_id.clone() else {
+        return result;
+    };
+    let run_block = |blocks: &mut [BuildBlock]| -> Option<usize> {
+        blocks
+            .iter()
+            .position(|b| b.tool_use_id() == Some(run_id.as_str()))
+    };
+
+    // Builder progress - route to the run's BuildBlock
+    if let Some(mut rx) = channels.plan_run_progress.take() {
+        loop {
+            match rx.try_recv() {
+                Ok(progress) => {
+                    result.needs_redraw = true;
+                    if let Some(idx) = run_block(build_blocks) {
+                        build_blocks[idx].update_progress(progress);
+                    }
+                }
+                Err(tokio::sync::mpsc::error::TryRecvError::Empty) => {
+                    channels.plan_run_progress = Some(rx);
+                    break;
+                }
+                Err(tokio::sync::mpsc::error::TryRecvError::Disconnected) => {
+                    break;
+                }
+            }
+        }
+    }
+
+    let Some(mut rx) = channels.plan_run.take() else {
+        return result;
+    };
+
+    loop {
+        match rx.try_recv() {
+            Ok(PlanRunEvent::Finished(summary)) => {
+                result.needs_redraw = true;
+                if let Some(idx) = run_block(build_blocks) {
+                    build_blocks[idx].complete(format!(
+                        "{}\n---\n**Plan Run**: {} tasks completed, {} remaining",
+                        summary.output,
+                        summary.completed.len(),
+                        summary.remaining
+                    ));
+                }
+                let message = finish_plan_run(&summary, active_plan, plan_manager, sidebar);
+                result = result.with_message("system", message);
+                *plan_run_id = None;
+                sidebar.run_status = None;
+                break;
+            }
+            Ok(event) => {
+                result.needs_redraw = true;
+                let Some(plan) = active_plan.as_mut() else {
+                    continue;
+                };
+
+                match &event {
+                    PlanRunEvent::TaskStarted { task_id } => {
+                        if let Err(e) = plan.start_task(task_id) {
+                            tracing::warn!(task_id = %task_id, "Plan run: {}", e);
+                        }
+                    }
+                    PlanRunEvent::TaskCompleted {
+                        task_id,
+                        result: task_result,
+                    } => {
+                        if let Err(e) = plan.complete_task(task_id, task_result) {
+                            tracing::warn!(task_id = %task_id, "Plan run: {}", e);
+                        }
+                        let (completed, total) = plan.progress();
+                        result = result.with_message(
+                            "system",
+                            format!("✓ Task {} complete ({}/{})", task_id, completed, total),
+                        );
+                    }
+                    PlanRunEvent::TaskFailed { task_id, error } => {
+                        if let Some(task) = plan.find_task_mut(task_id) {
+                            if task.status == TaskStatus::InProgress {
+                                task.status = TaskStatus::Pending;
+                            }
+                        }
+                        result = result.with_message(
+                            "system",
+                            format!("✗ Task {} failed: {}", task_id, error),
+                        );
+                    }
+                    PlanRunEvent::Finished(_) => {}
+                }
+
+                if let Err(e) = plan_manager.save_plan(plan) {
+                    tracing::warn!("Failed to save plan during plan run: {}", e);
+                }
+                sidebar.run_status = Some(plan_run_status(plan));
+            }
+            Err(tokio::sync::mpsc::error::TryRecvError::Empty) => {
+                channels.plan_run = Some(rx);
+                break;
+            }
+            Err(tokio::sync::mpsc::error::TryRecvError::Disconnected) => {
+                tracing::debug!("Plan run channel disconnected");
+                *plan_run_id = None;
+                sidebar.run_status = None;
+                break;
+            }
+        }
+    }
+
+    result
+}
+
+/// Sidebar status line for a running plan
+fn plan_run_status(plan: &PlanFile) -> String {
+    let running = plan
+        .phases
+        .iter()
+        .flat_map(|p| &p.tasks)
+        .filter(|t| t.status == TaskStatus::InProgress)
+        .count();
+    let (completed, total) = plan.progress();
+    format!(
+        "▶ Running · {} active · {}/{} done",
+        running, completed, total
+    )
+}
+
+/// Wrap up a finished plan run and describe the outcome
+fn finish_plan_run(
+    summary: &PlanRunSummary,
+    active_plan: &mut Option<PlanFile>,
+    plan_manager: &PlanManager,
+    sidebar: &mut PlanSidebarState,
+) -> String {
+    let Some(plan) = active_plan.as_mut() else {
+        return "Plan run finished.".to_string();
+    };
+
+    if plan.is_complete() {
+        plan.status = PlanStatus::Completed;
+        if let Err(e) = plan_manager.save_plan(plan) {
+            tracing::warn!("Failed to save completed plan: {}", e);
+        }
+        sidebar.start_collapse();
+        return format!(
+            "🎉 Plan '{}' complete! All {} tasks finished.",
+            plan.title,
+            plan.total_tasks()
+        );
+    }
+
+    let done = summary.completed.len();
+    if let Some((task_id, error)) = &summary.failed {
+        format!(
+            "Plan run stopped: task {} failed ({}). {} completed this run, {} left. \
+             Fix the problem and /plan run to continue.",
+            task_id, error, done, summary.remaining
+        )
+    } else if summary.cancelled {
+        format!(
+            "Plan run cancelled. {} completed this run, {} left.",
+            done, summary.remaining
+        )
+    } else {
+        format!(
+            "Plan run stopped: {} tasks are waiting on dependencies that can't be met.",
+            summary.remaining
+        )
+    }
+}
+
 /// Poll /init exploration progress and result
 ///
 /// Uses cached languages from /init start. Clears cache on completion.
@@ -0,0 +1,59 @@
+//! Docs status channel polling
+//!
+//! Handles updates from background docs suggest/index tasks.
+
+use crate::tui::popups::docs_browser::DocsBrowserPopup;
+use crate::tui::utils::{AsyncChannels, DocsStatusUpdate};
+
+use super::{PollAction, PollResult};
+
+/// Poll docs updates from background suggest/index tasks
+///
+/// Returns RefreshAiTools after a package is indexed, since the first
+/// indexed package registers the docs_search tool.
+pub fn poll_docs_status(
+    channels: &mut AsyncChannels,
+    docs_popup: &mut DocsBrowserPopup,
+) -> PollResult {
+    let mut result = PollResult::new();
+
+    let Some(mut rx) = channels.docs_status.take() else {
+        return result;
+    };
+
+    loop {
+        match rx.try_recv() {
+            Ok(update) => {
+                result.needs_redraw = true;
+                match update {
+                    DocsStatusUpdate::Suggestions { provider, packages } => {
+                        docs_popup.add_suggestions(&provider, packages);
+                    }
+                    DocsStatusUpdate::Indexed {
+                        provider,
+                        package,
+                        entries,
+                    } => {
+                        docs_popup.mark_indexed(&provider, &package, entries);
+                        docs_popup
+                            .set_status(format!("✓ Indexed {} ({} entries)", package, entries));
+                        result = result.with_action(PollAction::RefreshAiTools);
+                    }
+                    DocsStatusUpdate::Failed { message } => {
+                        docs_popup.indexing = None;
+                        docs_popup.set_status(format!("✗ {}", message));
+                    }
+                }
+            }
+            Err(tokio::sync::mpsc::error::TryRecvError::Empty) => {
+                channels.docs_status = Some(rx);
+                break;
+            }
+            Err(tokio::sync::mpsc::error::TryRecvError::Disconnected) => {
+                break;
+            }
+        }
+    }
+
+    result
+}
@@ -1,9 +1,13 @@
 //! MCP status channel polling
 //!
-//! Handles status updates from background MCP connection tasks.
+//! Handles status updates from background MCP connection tasks, and
+//! notifications, crashes and restarts published by the MCP manager.
+
+use tokio::sync::broadcast::error::TryRecvError;
 
 use crate::tui::popups::mcp_browser::McpBrowserPopup;
 use crate::tui::utils::AsyncChannels;
+use krusty_core::mcp::{McpEvent, McpNotification};
 
 use super::{PollAction, PollResult};
 
@@ -15,7 +19,7 @@ pub fn poll_mcp_status(
     channels: &mut AsyncChannels,
     mcp_popup: &mut McpBrowserPopup,
 ) -> PollResult {
-    let mut result = PollResult::new();
+    let mut result = poll_mcp_events(channels, mcp_popup);
 
     let Some(mut rx) = channels.mcp_status.take() else {
         return result;
@@ -53,3 +57,89 @@ pub fn poll_mcp_status(
 
     result
 }
+
+/// Poll manager events: surface server health in the popup status line and
+/// refresh tools when a server's tool list changed
+fn poll_mcp_events(channels: &mut AsyncChannels, mcp_popup: &mut McpBrowserPopup) -> PollResult {
+    let mut result = PollResult::new();
+
+    let Some(rx) = channels.mcp_events.as_mut() else {
+        return result;
+    };
+
+    let mut refresh_popup = false;
+    let mut refresh_tools = false;
+    loop {
+        let event = match rx.try_recv() {
+            Ok(event) => event,
+            Err(TryRecvError::Lagged(_)) => continue,
+            Err(TryRecvError::Empty) => break,
+            Err(TryRecvError::Closed) => {
+                channels.mcp_events = None;
+                break;
+            }
+        };
+
+        let status = match event {
+            McpEvent::Notification {
+                server,
+                notification,
+            } => match notification {
+                McpNotification::Progress { .. } => Some(format!("{}: {}", server, notification)),
+                McpNotification::LogMessage { ref level, .. }
+                    if matches!(
+                        level.as_str(),
+                        "warning" | "error" | "critical" | "alert" | "emergency"
+                    ) =>
+                {
+                    Some(format!("{}: {}", server, notification))
+                }
+                _ => None,
+            },
+            McpEvent::Exited { server, reason, .. } => {
+                refresh_popup = true;
+                Some(format!("✗ {} exited: {}", server, reason))
+            }
+            McpEvent::Restarting {
+                server,
+                attempt,
+                delay,
+            } => {
+                refresh_popup = true;
+                Some(format!(
+                    "Restarting {} in {}s (attempt {})",
+                    server,
+                    delay.as_secs(),
+                    attempt
+                ))
+            }
+            McpEvent::Restarted { server, tool_count } => {
+                refresh_popup = true;
+                Some(format!("✓ {} restarted ({} tools)", server, tool_count))
+            }
+            McpEvent::GaveUp { server, reason } => {
+                refresh_popup = true;
+                Some(format!("✗ {} stopped restarting: {}", server, reason))
+            }
+            McpEvent::ToolsChanged { .. } => {
+                refresh_popup = true;
+                refresh_tools = true;
+                None
+            }
+        };
+
+        if let Some(status) = status {
+            mcp_popup.set_status(status);
+            result.needs_redraw = true;
+        }
+    }
+
+    if refresh_popup {
+        result.needs_redraw = true;
+        result = result.with_action(PollAction::RefreshMcpPopup);
+    }
+    if refresh_tools {
+        result = result.with_action(PollAction::RefreshAiTools);
+    }
+    result
+}
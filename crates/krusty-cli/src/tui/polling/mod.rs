@@ -8,12 +8,16 @@
 
 mod bash;
 mod blocks;
+mod docs;
 mod mcp;
 mod oauth;
 mod processes;
 
 pub use bash::poll_bash_output;
-pub use blocks::{poll_build_progress, poll_explore_progress, poll_init_exploration};
+pub use blocks::{
+    poll_build_progress, poll_explore_progress, poll_init_exploration, poll_plan_run,
+};
+pub use docs::poll_docs_status;
 pub use mcp::poll_mcp_status;
 pub use oauth::poll_oauth_status;
 pub use processes::poll_background_processes;
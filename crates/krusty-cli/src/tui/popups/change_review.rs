@@ -0,0 +1,613 @@
+//! Change review popup - every file the session changed, as one diff
+//!
+//! Files are diffed against their content from before the session first
+//! touched them. Hunks can be reverted one at a time, and the remaining
+//! changes accepted or committed in one go. Changes made by shell commands
+//! aren't tracked, so they are not listed.
+
+use ratatui::{
+    layout::{Alignment, Constraint, Direction, Layout},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph},
+    Frame,
+};
+use similar::ChangeTag;
+
+use super::common::{
+    center_content, center_rect, popup_block, popup_title, render_popup_background, PopupSize,
+};
+use crate::tui::themes::Theme;
+use crate::tui::utils::{diff_hunks, revert_hunk, Hunk};
+
+/// What the review knows of a file's content
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FileContent {
+    /// The file doesn't exist
+    Missing,
+    Bytes(Vec<u8>),
+    /// Content the review can't know, and why
+    Unknown(String),
+}
+
+impl FileContent {
+    /// Read a file from disk; only "not found" counts as missing
+    pub fn read(path: &str) -> Self {
+        match std::fs::read(path) {
+            Ok(bytes) => Self::Bytes(bytes),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Self::Missing,
+            Err(e) => Self::Unknown(format!("can't be read: {}", e)),
+        }
+    }
+
+    /// Content to store as a baseline (`None` for a missing file)
+    pub fn baseline(&self) -> Option<Option<&[u8]>> {
+        match self {
+            Self::Missing => Some(None),
+            Self::Bytes(bytes) => Some(Some(bytes)),
+            Self::Unknown(_) => None,
+        }
+    }
+
+    /// Text to diff, empty for a missing file. None for binary or unknown
+    /// content
+    fn text(&self) -> Option<&str> {
+        match self {
+            Self::Missing => Some(""),
+            Self::Bytes(bytes) => std::str::from_utf8(bytes).ok(),
+            Self::Unknown(_) => None,
+        }
+    }
+}
+
+impl From<Option<Vec<u8>>> for FileContent {
+    fn from(content: Option<Vec<u8>>) -> Self {
+        content.map_or(Self::Missing, Self::Bytes)
+    }
+}
+
+/// A changed file with its diff
+#[derive(Debug, Clone)]
+pub struct ReviewFile {
+    /// Path as stored in the snapshot
+    pub path: String,
+    /// Path shown in the list, relative to the working directory
+    pub display_path: String,
+    /// Content before the session changed it
+    pub original: FileContent,
+    pub current: FileContent,
+    pub hunks: Vec<Hunk>,
+    pub added: usize,
+    pub removed: usize,
+}
+
+impl ReviewFile {
+    pub fn new(
+        path: String,
+        display_path: String,
+        original: FileContent,
+        current: FileContent,
+    ) -> Self {
+        let hunks = match (original.text(), current.text()) {
+            (Some(old), Some(new)) => diff_hunks(old, new),
+            _ => Vec::new(),
+        };
+        let (added, removed) = hunks.iter().fold((0, 0), |(a, r), hunk| {
+            let (added, removed) = hunk.counts();
+            (a + added, r + removed)
+        });
+        Self {
+            path,
+            display_path,
+            original,
+            current,
+            hunks,
+            added,
+            removed,
+        }
+    }
+
+    /// Whether the file differs from its original, or might
+    pub fn is_changed(&self) -> bool {
+        match (&self.original, &self.current) {
+            (FileContent::Unknown(_), _) | (_, FileContent::Unknown(_)) => true,
+            (original, current) => original != current,
+        }
+    }
+
+    /// Content that undoes the whole file; `None` deletes it
+    pub fn reverted_file(&self) -> Result<Option<Vec<u8>>, String> {
+        self.check_revertable()?;
+        Ok(match &self.original {
+            FileContent::Bytes(bytes) => Some(bytes.clone()),
+            _ => None,
+        })
+    }
+
+    /// Content that undoes hunk `index`; `None` deletes the file
+    pub fn reverted_hunk(&self, index: usize) -> Result<Option<Vec<u8>>, String> {
+        self.check_revertable()?;
+        let (Some(old), Some(new)) = (self.original.text(), self.current.text()) else {
+            return Err("Binary changes can only be reverted as a whole file (R)".to_string());
+        };
+        let reverted = revert_hunk(old, new, index);
+        // Only a file the session created is removed again
+        if self.original == FileContent::Missing && reverted.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(reverted.into_bytes()))
+    }
+
+    fn check_revertable(&self) -> Result<(), String> {
+        match (&self.original, &self.current) {
+            (FileContent::Unknown(why), _) => Err(format!(
+                "{} can't be reverted: its original {}",
+                self.display_path, why
+            )),
+            (_, FileContent::Unknown(why)) => Err(format!("{} {}", self.display_path, why)),
+            _ => Ok(()),
+        }
+    }
+
+    fn status(&self) -> &'static str {
+        match (&self.original, &self.current) {
+            (FileContent::Unknown(_), _) => "no snapshot, can't revert",
+            (_, FileContent::Unknown(_)) => "unreadable",
+            (FileContent::Missing, _) => "new",
+            (_, FileContent::Missing) => "deleted",
+            _ if self.hunks.is_empty() => "binary",
+            _ => "modified",
+        }
+    }
+}
+
+/// Change review popup state
+pub struct ChangeReviewPopup {
+    pub files: Vec<ReviewFile>,
+    pub selected_file: usize,
+    pub selected_hunk: usize,
+    /// First diff line shown
+    scroll: usize,
+    /// Commit message being typed, when committing
+    pub message: Option<String>,
+    /// Result of the last action, and whether it failed
+    pub status: Option<(String, bool)>,
+}
+
+impl Default for ChangeReviewPopup {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ChangeReviewPopup {
+    pub fn new() -> Self {
+        Self {
+            files: Vec::new(),
+            selected_file: 0,
+            selected_hunk: 0,
+            scroll: 0,
+            message: None,
+            status: None,
+        }
+    }
+
+    /// Show a fresh set of files, starting at the first
+    pub fn open(&mut self, files: Vec<ReviewFile>) {
+        *self = Self {
+            files,
+            ..Self::new()
+        };
+    }
+
+    /// Replace a file after it changed on disk, dropping it once it matches
+    /// its original again
+    pub fn update_file(&mut self, file: ReviewFile) {
+        let Some(idx) = self.files.iter().position(|f| f.path == file.path) else {
+            return;
+        };
+        if file.is_changed() {
+            self.files[idx] = file;
+        } else {
+            self.files.remove(idx);
+            self.selected_file = self.selected_file.min(self.files.len().saturating_sub(1));
+            self.selected_hunk = 0;
+            self.scroll = 0;
+        }
+        let hunks = self.selected().map_or(0, |f| f.hunks.len());
+        self.selected_hunk = self.selected_hunk.min(hunks.saturating_sub(1));
+    }
+
+    pub fn selected(&self) -> Option<&ReviewFile> {
+        self.files.get(self.selected_file)
+    }
+
+    pub fn next_file(&mut self) {
+        if self.selected_file + 1 < self.files.len() {
+            self.select_file(self.selected_file + 1);
+        }
+    }
+
+    pub fn prev_file(&mut self) {
+        if self.selected_file > 0 {
+            self.select_file(self.selected_file - 1);
+        }
+    }
+
+    fn select_file(&mut self, idx: usize) {
+        self.selected_file = idx;
+        self.selected_hunk = 0;
+        self.scroll = 0;
+    }
+
+    pub fn next_hunk(&mut self) {
+        let hunks = self.selected().map_or(0, |f| f.hunks.len());
+        if self.selected_hunk + 1 < hunks {
+            self.selected_hunk += 1;
+        } else {
+            // Past the last hunk, continue with the next file
+            self.next_file();
+        }
+    }
+
+    pub fn prev_hunk(&mut self) {
+        if self.selected_hunk > 0 {
+            self.selected_hunk -= 1;
+        } else if self.selected_file > 0 {
+            self.prev_file();
+            self.selected_hunk = self
+                .selected()
+                .map_or(0, |f| f.hunks.len().saturating_sub(1));
+        }
+    }
+
+    pub fn scroll_up(&mut self, lines: usize) {
+        self.scroll = self.scroll.saturating_sub(lines);
+    }
+
+    pub fn scroll_down(&mut self, lines: usize) {
+        self.scroll += lines;
+    }
+
+    /// Total lines added and removed across all files
+    fn totals(&self) -> (usize, usize) {
+        self.files
+            .iter()
+            .fold((0, 0), |(a, r), f| (a + f.added, r + f.removed))
+    }
+
+    pub fn render(&mut self, f: &mut Frame, theme: &Theme) {
+        let (w, h) = PopupSize::Large.dimensions();
+        let area = center_rect(w, h, f.area());
+        render_popup_background(f, area, theme);
+
+        let block = popup_block(theme);
+        let inner = block.inner(area);
+        f.render_widget(block, area);
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(3), // Title
+                Constraint::Length(2), // Summary
+                Constraint::Min(5),    // Files and diff
+                Constraint::Length(1), // Status or commit message
+                Constraint::Length(2), // Footer
+            ])
+            .split(inner);
+
+        let title =
+            Paragraph::new(popup_title("Review Changes", theme)).alignment(Alignment::Center);
+        f.render_widget(title, chunks[0]);
+
+        let (added, removed) = self.totals();
+        let untracked = Span::styled(
+            "  (changes made by shell commands are not listed)",
+            Style::default()
+                .fg(theme.dim_color)
+                .add_modifier(Modifier::ITALIC),
+        );
+        let summary = if self.files.is_empty() {
+            Line::from(vec![
+                Span::styled(
+                    "  No changes this session",
+                    Style::default()
+                        .fg(theme.dim_color)
+                        .add_modifier(Modifier::ITALIC),
+                ),
+                untracked,
+            ])
+        } else {
+            Line::from(vec![
+                Span::styled(
+                    format!("  {} file(s) changed  ", self.files.len()),
+                    Style::default().fg(theme.text_color),
+                ),
+                Span::styled(
+                    format!("+{}", added),
+                    Style::default().fg(theme.diff_add_color),
+                ),
+                Span::raw(" "),
+                Span::styled(
+                    format!("-{}", removed),
+                    Style::default().fg(theme.diff_remove_color),
+                ),
+                untracked,
+            ])
+        };
+        f.render_widget(Paragraph::new(summary), chunks[1]);
+
+        let body = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(30), Constraint::Percentage(70)])
+            .split(center_content(chunks[2], 2));
+        self.render_file_list(f, body[0], theme);
+        self.render_diff(f, body[1], theme);
+
+        let status = if let Some(ref message) = self.message {
+            Line::from(vec![
+                Span::styled(
+                    "  Commit message: ",
+                    Style::default().fg(theme.accent_color),
+                ),
+                Span::styled(message.as_str(), Style::default().fg(theme.text_color)),
+                Span::styled(
+                    "_",
+                    Style::default()
+                        .fg(theme.accent_color)
+                        .add_modifier(Modifier::SLOW_BLINK),
+                ),
+            ])
+        } else if let Some((ref text, failed)) = self.status {
+            let color = if failed {
+                theme.error_color
+            } else {
+                theme.success_color
+            };
+            Line::from(Span::styled(
+                format!("  {}", text),
+                Style::default().fg(color),
+            ))
+        } else {
+            Line::from("")
+        };
+        f.render_widget(Paragraph::new(status), chunks[3]);
+
+        let key = |k: &'static str| {
+            Span::styled(
+                k,
+                Style::default()
+                    .fg(theme.accent_color)
+                    .add_modifier(Modifier::BOLD),
+            )
+        };
+        let text = |t: &'static str| Span::styled(t, Style::default().fg(theme.text_color));
+        let footer = if self.message.is_some() {
+            Line::from(vec![
+                key("Enter"),
+                text(": commit  "),
+                key("Esc"),
+                text(": cancel"),
+            ])
+        } else {
+            Line::from(vec![
+                key("↑↓"),
+                text(": hunk  "),
+                key("←→"),
+                text(": file  "),
+                key("r"),
+                text(": revert hunk  "),
+                key("R"),
+                text(": revert file  "),
+                key("a"),
+                text(": accept all  "),
+                key("c"),
+                text(": commit  "),
+                key("Esc"),
+                text(": close"),
+            ])
+        };
+        f.render_widget(
+            Paragraph::new(footer).alignment(Alignment::Center),
+            chunks[4],
+        );
+    }
+
+    fn render_file_list(&self, f: &mut Frame, area: ratatui::layout::Rect, theme: &Theme) {
+        let block = Block::default()
+            .borders(Borders::RIGHT)
+            .border_style(Style::default().fg(theme.dim_color));
+        let inner = block.inner(area);
+        f.render_widget(block, area);
+
+        // Keep the selected file in view
+        let height = inner.height as usize;
+        let skip = (self.selected_file + 1).saturating_sub(height);
+        let width = inner.width as usize;
+
+        let lines: Vec<Line> = self
+            .files
+            .iter()
+            .enumerate()
+            .skip(skip)
+            .take(height)
+            .map(|(idx, file)| {
+                let is_selected = idx == self.selected_file;
+                let style = if is_selected {
+                    Style::default()
+                        .fg(theme.accent_color)
+                        .add_modifier(Modifier::BOLD)
+                } else {
+                    Style::default().fg(theme.text_color)
+                };
+                let prefix = if is_selected { "▶ " } else { "  " };
+                let counts = format!(" +{} -{}", file.added, file.removed);
+                let room = width.saturating_sub(prefix.len() + counts.len());
+                let name = shorten_path(&file.display_path, room);
+                Line::from(vec![
+                    Span::styled(prefix, style),
+                    Span::styled(name, style),
+                    Span::styled(counts, Style::default().fg(theme.dim_color)),
+                ])
+            })
+            .collect();
+        f.render_widget(Paragraph::new(lines), inner);
+    }
+
+    fn render_diff(&mut self, f: &mut Frame, area: ratatui::layout::Rect, theme: &Theme) {
+        let area = center_content(area, 1);
+        let Some(file) = self.files.get(self.selected_file) else {
+            return;
+        };
+
+        let mut lines: Vec<Line> = vec![
+            Line::from(vec![
+                Span::styled(
+                    file.display_path.clone(),
+                    Style::default()
+                        .fg(theme.text_color)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::styled(
+                    format!("  ({})", file.status()),
+                    Style::default().fg(theme.dim_color),
+                ),
+            ]),
+            Line::from(""),
+        ];
+        let mut selected_start = 0;
+        let mut selected_end = 0;
+
+        let note = match (&file.original, &file.current) {
+            (FileContent::Unknown(why), _) => Some(format!("Original {}", why)),
+            (_, FileContent::Unknown(why)) => Some(format!("File {}", why)),
+            _ if file.hunks.is_empty() => Some("Binary file changed".to_string()),
+            _ => None,
+        };
+        if let Some(note) = note {
+            lines.push(Line::from(Span::styled(
+                note,
+                Style::default()
+                    .fg(theme.dim_color)
+                    .add_modifier(Modifier::ITALIC),
+            )));
+        }
+
+        for (idx, hunk) in file.hunks.iter().enumerate() {
+            let is_selected = idx == self.selected_hunk;
+            if is_selected {
+                selected_start = lines.len();
+            }
+            let header_style = if is_selected {
+                Style::default()
+                    .fg(theme.accent_color)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(theme.dim_color)
+            };
+            let marker = if is_selected { "▶ " } else { "  " };
+            lines.push(Line::from(Span::styled(
+                format!("{}{}", marker, hunk.header),
+                header_style,
+            )));
+
+            for line in &hunk.lines {
+                let (sign, style) = match line.tag {
+                    ChangeTag::Insert => (
+                        '+',
+                        Style::default()
+                            .fg(theme.diff_add_color)
+                            .bg(theme.diff_add_bg_color),
+                    ),
+                    ChangeTag::Delete => (
+                        '-',
+                        Style::default()
+                            .fg(theme.diff_remove_color)
+                            .bg(theme.diff_remove_bg_color),
+                    ),
+                    ChangeTag::Equal => (' ', Style::default().fg(theme.diff_context_color)),
+                };
+                lines.push(Line::from(vec![
+                    Span::styled(
+                        format!("{:>5} ", line.line_num),
+                        Style::default().fg(theme.dim_color),
+                    ),
+                    Span::styled(
+                        format!("{}{}", sign, line.content.replace('\t', "    ")),
+                        style,
+                    ),
+                ]));
+            }
+            if is_selected {
+                selected_end = lines.len();
+            }
+            lines.push(Line::from(""));
+        }
+
+        // Keep the selected hunk in view, showing as much of it as fits
+        let height = area.height as usize;
+        if selected_start < self.scroll {
+            self.scroll = selected_start;
+        } else if selected_end > self.scroll + height {
+            self.scroll = selected_start.min(selected_end.saturating_sub(height));
+        }
+        self.scroll = self.scroll.min(lines.len().saturating_sub(1));
+
+        let diff = Paragraph::new(lines)
+            .style(Style::default().bg(theme.bg_color))
+            .scroll((self.scroll as u16, 0));
+        f.render_widget(diff, area);
+    }
+}
+
+/// Keep the end of a path that is too long, e.g. `…/handlers/mod.rs`
+fn shorten_path(path: &str, max: usize) -> String {
+    let len = path.chars().count();
+    if len <= max {
+        return path.to_string();
+    }
+    let tail: String = path.chars().skip(len + 1 - max.max(1)).collect();
+    format!("…{}", tail)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(original: FileContent, current: FileContent) -> ReviewFile {
+        ReviewFile::new("/p/f".into(), "f".into(), original, current)
+    }
+
+    #[test]
+    fn test_revert_never_deletes_existing_files() {
+        let edited = file(
+            FileContent::Bytes(b"keep\n".to_vec()),
+            FileContent::Bytes(Vec::new()),
+        );
+        assert_eq!(edited.reverted_hunk(0), Ok(Some(b"keep\n".to_vec())));
+        assert_eq!(edited.reverted_file(), Ok(Some(b"keep\n".to_vec())));
+
+        let created = file(FileContent::Missing, FileContent::Bytes(b"new\n".to_vec()));
+        assert_eq!(created.reverted_hunk(0), Ok(None));
+        assert_eq!(created.reverted_file(), Ok(None));
+
+        let unknown = file(
+            FileContent::Unknown("was not snapshotted".into()),
+            FileContent::Bytes(b"x\n".to_vec()),
+        );
+        assert!(unknown.is_changed());
+        assert!(unknown.reverted_hunk(0).is_err());
+        assert!(unknown.reverted_file().is_err());
+    }
+
+    #[test]
+    fn test_binary_files_revert_whole() {
+        let binary = file(
+            FileContent::Bytes(vec![0xff, 0x00]),
+            FileContent::Bytes(vec![0xfe]),
+        );
+        assert!(binary.hunks.is_empty());
+        assert!(binary.reverted_hunk(0).is_err());
+        assert_eq!(binary.reverted_file(), Ok(Some(vec![0xff, 0x00])));
+    }
+}
@@ -0,0 +1,404 @@
+//! Docs browser popup
+//!
+//! Pick packages to index from extension docs providers.
+
+use ratatui::{
+    layout::{Alignment, Constraint, Direction, Layout},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::Paragraph,
+    Frame,
+};
+
+use super::common::{
+    center_content, center_rect, popup_block, popup_title, render_popup_background,
+    scroll_indicator, PopupSize,
+};
+use crate::tui::themes::Theme;
+use krusty_core::docs::DocsProvider;
+use krusty_core::storage::DocsPackage;
+
+/// A package row: suggested by a provider, indexed, or both
+#[derive(Debug, Clone)]
+pub struct DocsPackageRow {
+    pub provider: String,
+    pub package: String,
+    /// Entry count if the package has been indexed
+    pub entry_count: Option<usize>,
+}
+
+/// Docs browser popup state
+pub struct DocsBrowserPopup {
+    pub providers: Vec<DocsProvider>,
+    pub rows: Vec<DocsPackageRow>,
+    /// Provider used when indexing a typed package name
+    pub provider_index: usize,
+    pub selected_index: usize,
+    pub scroll_offset: usize,
+    pub search_query: String,
+    pub search_active: bool,
+    /// Package currently being indexed
+    pub indexing: Option<String>,
+    pub status_message: Option<String>,
+}
+
+impl Default for DocsBrowserPopup {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DocsBrowserPopup {
+    pub fn new() -> Self {
+        Self {
+            providers: Vec::new(),
+            rows: Vec::new(),
+            provider_index: 0,
+            selected_index: 0,
+            scroll_offset: 0,
+            search_query: String::new(),
+            search_active: false,
+            indexing: None,
+            status_message: None,
+        }
+    }
+
+    /// Reset with the installed providers and already-indexed packages
+    pub fn set_packages(&mut self, providers: Vec<DocsProvider>, indexed: Vec<DocsPackage>) {
+        self.providers = providers;
+        self.rows = indexed
+            .into_iter()
+            .map(|p| DocsPackageRow {
+                provider: p.provider,
+                package: p.package,
+                entry_count: Some(p.entry_count),
+            })
+            .collect();
+        self.provider_index = 0;
+        self.selected_index = 0;
+        self.scroll_offset = 0;
+    }
+
+    /// Merge packages suggested by a provider (indexed rows are kept)
+    pub fn add_suggestions(&mut self, provider: &str, packages: Vec<String>) {
+        for package in packages {
+            let exists = self
+                .rows
+                .iter()
+                .any(|r| r.provider == provider && r.package == package);
+            if !exists {
+                self.rows.push(DocsPackageRow {
+                    provider: provider.to_string(),
+                    package,
+                    entry_count: None,
+                });
+            }
+        }
+    }
+
+    /// Record a finished index run
+    pub fn mark_indexed(&mut self, provider: &str, package: &str, entry_count: usize) {
+        self.indexing = None;
+        match self
+            .rows
+            .iter_mut()
+            .find(|r| r.provider == provider && r.package == package)
+        {
+            Some(row) => row.entry_count = Some(entry_count),
+            None => self.rows.push(DocsPackageRow {
+                provider: provider.to_string(),
+                package: package.to_string(),
+                entry_count: Some(entry_count),
+            }),
+        }
+    }
+
+    pub fn set_status(&mut self, msg: impl Into<String>) {
+        self.status_message = Some(msg.into());
+    }
+
+    /// Provider used for typed package names
+    pub fn active_provider(&self) -> Option<&DocsProvider> {
+        self.providers.get(self.provider_index)
+    }
+
+    /// Cycle the provider used for typed package names
+    pub fn next_provider(&mut self) {
+        if !self.providers.is_empty() {
+            self.provider_index = (self.provider_index + 1) % self.providers.len();
+        }
+    }
+
+    /// Navigate to next package
+    pub fn next(&mut self) {
+        let filtered = self.filtered_rows();
+        if self.selected_index < filtered.len().saturating_sub(1) {
+            self.selected_index += 1;
+            self.ensure_visible();
+        }
+    }
+
+    /// Navigate to previous package
+    pub fn prev(&mut self) {
+        if self.selected_index > 0 {
+            self.selected_index -= 1;
+            self.ensure_visible();
+        }
+    }
+
+    fn ensure_visible(&mut self) {
+        let visible_height = 12;
+        if self.selected_index < self.scroll_offset {
+            self.scroll_offset = self.selected_index;
+        } else if self.selected_index >= self.scroll_offset + visible_height {
+            self.scroll_offset = self.selected_index - visible_height + 1;
+        }
+    }
+
+    /// Toggle search mode
+    pub fn toggle_search(&mut self) {
+        self.search_active = !self.search_active;
+        if !self.search_active {
+            self.search_query.clear();
+            self.selected_index = 0;
+            self.scroll_offset = 0;
+        }
+    }
+
+    /// Add character to search query
+    pub fn add_search_char(&mut self, c: char) {
+        if self.search_active {
+            self.search_query.push(c);
+            self.selected_index = 0;
+            self.scroll_offset = 0;
+        }
+    }
+
+    /// Handle backspace in search
+    pub fn backspace_search(&mut self) {
+        if self.search_active {
+            self.search_query.pop();
+        }
+    }
+
+    /// Package to index on Enter: (provider, package)
+    ///
+    /// The highlighted row wins; with no match, the typed query is indexed
+    /// with the active provider.
+    pub fn selection(&self) -> Option<(String, String)> {
+        let filtered = self.filtered_rows();
+        if let Some(row) = filtered.get(self.selected_index) {
+            return Some((row.provider.clone(), row.package.clone()));
+        }
+        let package = self.search_query.trim();
+        if package.is_empty() {
+            return None;
+        }
+        self.active_provider()
+            .map(|p| (p.name.clone(), package.to_string()))
+    }
+
+    fn filtered_rows(&self) -> Vec<&DocsPackageRow> {
+        if self.search_query.is_empty() {
+            self.rows.iter().collect()
+        } else {
+            let query = self.search_query.to_lowercase();
+            self.rows
+                .iter()
+                .filter(|r| r.package.to_lowercase().contains(&query))
+                .collect()
+        }
+    }
+
+    /// Render the popup
+    pub fn render(&self, f: &mut Frame, theme: &Theme) {
+        let (w, h) = PopupSize::Large.dimensions();
+        let area = center_rect(w, h, f.area());
+        render_popup_background(f, area, theme);
+
+        let block = popup_block(theme);
+        let inner = block.inner(area);
+        f.render_widget(block, area);
+
+        let search_height = if self.search_active { 2 } else { 0 };
+        let status_height = if self.status_message.is_some() { 1 } else { 0 };
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(3),             // Title
+                Constraint::Length(search_height), // Search
+                Constraint::Min(5),                // Content
+                Constraint::Length(status_height), // Status
+                Constraint::Length(2),             // Footer
+            ])
+            .split(inner);
+
+        let visible_height = (chunks[2].height as usize).saturating_sub(2);
+
+        // Title
+        let indexed = self.rows.iter().filter(|r| r.entry_count.is_some()).count();
+        let title_text = format!("Docs ({} indexed)", indexed);
+        let title = Paragraph::new(popup_title(&title_text, theme)).alignment(Alignment::Center);
+        f.render_widget(title, chunks[0]);
+
+        // Search bar (doubles as the package name input)
+        if self.search_active {
+            let provider = self
+                .active_provider()
+                .map(|p| p.name.as_str())
+                .unwrap_or("none");
+            let search = Paragraph::new(Line::from(vec![
+                Span::styled("  Package: ", Style::default().fg(theme.accent_color)),
+                Span::styled(&self.search_query, Style::default().fg(theme.text_color)),
+                Span::styled(
+                    "_",
+                    Style::default()
+                        .fg(theme.accent_color)
+                        .add_modifier(Modifier::SLOW_BLINK),
+                ),
+                Span::styled(
+                    format!("  [{}]", provider),
+                    Style::default().fg(theme.dim_color),
+                ),
+            ]));
+            f.render_widget(search, chunks[1]);
+        }
+
+        let filtered = self.filtered_rows();
+        let mut lines: Vec<Line> = Vec::new();
+
+        if self.providers.is_empty() && self.rows.is_empty() {
+            lines.push(Line::from(vec![Span::styled(
+                "  No docs providers installed.",
+                Style::default().fg(theme.dim_color),
+            )]));
+            lines.push(Line::from(""));
+            lines.push(Line::from(vec![Span::styled(
+                "  Install a Zed extension that declares",
+                Style::default().fg(theme.text_color),
+            )]));
+            lines.push(Line::from(vec![Span::styled(
+                "  [indexed_docs_providers] in ~/.krusty/extensions/",
+                Style::default().fg(theme.dim_color),
+            )]));
+        } else if filtered.is_empty() {
+            let hint = if self.search_query.trim().is_empty() {
+                "  No packages yet. Press / and type a package name.".to_string()
+            } else {
+                format!("  Press Enter to index '{}'", self.search_query.trim())
+            };
+            lines.push(Line::from(vec![Span::styled(
+                hint,
+                Style::default().fg(theme.dim_color),
+            )]));
+        } else {
+            if self.scroll_offset > 0 {
+                lines.push(scroll_indicator("up", self.scroll_offset, theme));
+            }
+
+            for (display_idx, row) in filtered
+                .iter()
+                .enumerate()
+                .skip(self.scroll_offset)
+                .take(visible_height)
+            {
+                let is_selected = display_idx == self.selected_index;
+                let is_indexing = self.indexing.as_deref() == Some(row.package.as_str());
+
+                let (icon, icon_color) = if is_indexing {
+                    ("◐", theme.warning_color)
+                } else if row.entry_count.is_some() {
+                    ("●", theme.success_color)
+                } else {
+                    ("○", theme.dim_color)
+                };
+
+                let prefix = if is_selected { " › " } else { "   " };
+                let name_style = if is_selected {
+                    Style::default()
+                        .fg(theme.accent_color)
+                        .add_modifier(Modifier::BOLD)
+                } else {
+                    Style::default().fg(theme.text_color)
+                };
+
+                let detail = if is_indexing {
+                    "indexing...".to_string()
+                } else {
+                    match row.entry_count {
+                        Some(count) => format!("{} entries", count),
+                        None => "not indexed".to_string(),
+                    }
+                };
+
+                lines.push(Line::from(vec![
+                    Span::styled(prefix.to_string(), name_style),
+                    Span::styled(icon.to_string(), Style::default().fg(icon_color)),
+                    Span::raw(" "),
+                    Span::styled(row.package.clone(), name_style),
+                    Span::styled(
+                        format!(" [{}] ", row.provider),
+                        Style::default().fg(theme.dim_color),
+                    ),
+                    Span::styled(detail, Style::default().fg(theme.dim_color)),
+                ]));
+            }
+
+            let remaining = filtered
+                .len()
+                .saturating_sub(self.scroll_offset + visible_height);
+            if remaining > 0 {
+                lines.push(scroll_indicator("down", remaining, theme));
+            }
+        }
+
+        let content = Paragraph::new(lines).style(Style::default().bg(theme.bg_color));
+        f.render_widget(content, center_content(chunks[2], 4));
+
+        if let Some(ref status) = self.status_message {
+            let status = Paragraph::new(Line::from(Span::styled(
+                status.as_str(),
+                Style::default().fg(theme.dim_color),
+            )))
+            .alignment(Alignment::Center);
+            f.render_widget(status, chunks[3]);
+        }
+
+        // Footer
+        let key = |k: &'static str| {
+            Span::styled(
+                k,
+                Style::default()
+                    .fg(theme.accent_color)
+                    .add_modifier(Modifier::BOLD),
+            )
+        };
+        let text = |t: &'static str| Span::styled(t, Style::default().fg(theme.text_color));
+        let footer = if self.search_active {
+            Line::from(vec![
+                key("Enter"),
+                text(": index  "),
+                key("Tab"),
+                text(": provider  "),
+                key("Esc"),
+                text(": close search"),
+            ])
+        } else {
+            Line::from(vec![
+                key("/"),
+                text(": package  "),
+                key("↑↓"),
+                text(": nav  "),
+                key("Enter"),
+                text(": index  "),
+                key("Esc"),
+                text(": close"),
+            ])
+        };
+        f.render_widget(
+            Paragraph::new(footer).alignment(Alignment::Center),
+            chunks[4],
+        );
+    }
+}
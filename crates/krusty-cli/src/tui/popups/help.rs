@@ -11,6 +11,7 @@ use ratatui::{
 use super::common::{
     center_rect, popup_block, render_popup_background, scroll_indicator, PopupSize,
 };
+use crate::tui::input::keymap::{KeyBinding, Keymap, CONTEXTS, EDITOR_KEYS};
 use crate::tui::themes::Theme;
 
 /// Help popup state
@@ -38,7 +39,16 @@ impl HelpPopup {
         self.scroll_offset = 0;
     }
 
-    pub fn render(&self, f: &mut Frame, theme: &Theme) {
+    pub fn scroll_up(&mut self, lines: usize) {
+        self.scroll_offset = self.scroll_offset.saturating_sub(lines);
+    }
+
+    /// Scroll down (kept in range at the next render)
+    pub fn scroll_down(&mut self, lines: usize) {
+        self.scroll_offset += lines;
+    }
+
+    pub fn render(&mut self, f: &mut Frame, theme: &Theme, keymap: &Keymap) {
         let (w, h) = PopupSize::Large.dimensions();
         let area = center_rect(w, h, f.area());
         render_popup_background(f, area, theme);
@@ -85,13 +95,16 @@ impl HelpPopup {
         // Content based on tab with scroll indicators
         let all_content = match self.tab_index {
             0 => self.commands_content(theme),
-            1 => self.keybinds_content(theme),
+            1 => self.keybinds_content(theme, keymap),
             _ => vec![],
         };
 
         let total_lines = all_content.len();
         // Reserve space for scroll indicators
         let visible_height = (chunks[1].height as usize).saturating_sub(2);
+        self.scroll_offset = self
+            .scroll_offset
+            .min(total_lines.saturating_sub(visible_height));
 
         let mut display_lines: Vec<Line> = Vec::new();
 
@@ -128,6 +141,13 @@ impl HelpPopup {
                     .add_modifier(Modifier::BOLD),
             ),
             Span::styled(": switch tabs  ", Style::default().fg(theme.text_color)),
+            Span::styled(
+                "↑↓",
+                Style::default()
+                    .fg(theme.accent_color)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(": scroll  ", Style::default().fg(theme.text_color)),
             Span::styled(
                 "Esc",
                 Style::default()
@@ -146,14 +166,36 @@ impl HelpPopup {
         let commands = [
             ("/home", "Return to start menu"),
             ("/load", "Load previous session"),
+            ("/search [query]", "Search messages across sessions"),
+            ("/changes", "Review, revert or commit file changes"),
             ("/model", "Select AI model"),
             ("/auth", "Manage API providers"),
             ("/theme", "Change color theme"),
+            (
+                "/theme import <file>",
+                "Import a base16, VS Code or TOML theme (--force replaces one)",
+            ),
+            (
+                "/editmode [mode]",
+                "Switch prompt editing: standard, vim or emacs",
+            ),
+            (
+                "/queue [next|inject|clear]",
+                "Queued prompts: delivery or clear",
+            ),
             ("/clear", "Clear chat messages"),
             ("/pinch", "Compress context to new session"),
             ("/plan", "View/manage active plan"),
+            ("/plan run", "Execute the plan with builders"),
+            (
+                "/plan revisions",
+                "Plan history, /plan diff N, /plan restore N",
+            ),
             ("/mcp", "Browse and manage MCP servers"),
             ("/skills", "Browse skills"),
+            ("/agents", "List and reload custom agents"),
+            ("/commands", "List and reload custom commands"),
+            ("/docs", "Index and browse package docs"),
             ("/ps", "View background processes"),
             ("/terminal", "Open interactive terminal"),
             ("/init", "Generate KRAB.md"),
@@ -173,58 +215,76 @@ impl HelpPopup {
         lines
     }
 
-    fn keybinds_content(&self, theme: &Theme) -> Vec<Line<'static>> {
-        let mut lines = vec![Line::from("")];
-
-        let sections = [
-            (
-                "Global",
-                vec![
-                    ("Ctrl+Q", "Quit"),
-                    ("Ctrl+B", "Toggle BUILD/PLAN mode"),
-                    ("Ctrl+T", "Toggle plan sidebar"),
-                    ("Ctrl+P", "Open process list"),
-                    ("Tab", "Toggle extended thinking"),
-                    ("Esc", "Cancel AI / close popup"),
-                ],
-            ),
-            (
-                "Input",
-                vec![
-                    ("Enter", "Send message"),
-                    ("Shift+Enter", "New line"),
-                    ("Ctrl+V", "Paste text or image"),
-                    ("Ctrl+C", "Clear input"),
-                    ("Ctrl+W", "Delete word"),
-                    ("@", "Search files to attach"),
-                ],
-            ),
-            (
-                "Navigation",
-                vec![("↑/↓", "Autocomplete"), ("PgUp/Dn", "Scroll chat")],
-            ),
+    /// Key bindings as currently configured
+    fn keybinds_content(&self, theme: &Theme, keymap: &Keymap) -> Vec<Line<'static>> {
+        let mut lines = vec![
+            Line::from(Span::styled(
+                "  Rebind in ~/.krusty/keymap.toml by action name".to_string(),
+                Style::default().fg(theme.dim_color),
+            )),
+            Line::from(""),
         ];
 
-        for (section, bindings) in sections {
-            lines.push(Line::from(Span::styled(
-                format!("{}:", section),
-                Style::default()
-                    .fg(theme.title_color)
-                    .add_modifier(Modifier::BOLD),
-            )));
-
-            for (key, desc) in bindings {
-                lines.push(Line::from(vec![
-                    Span::styled(
-                        format!("  {:<14}", key),
-                        Style::default().fg(theme.accent_color),
-                    ),
-                    Span::styled(desc.to_string(), Style::default().fg(theme.text_color)),
-                ]));
+        let heading = |title: &str, color| {
+            Line::from(Span::styled(
+                format!("{}:", title),
+                Style::default().fg(color).add_modifier(Modifier::BOLD),
+            ))
+        };
+        let binding = |keys: String, desc: &str, name: &str| {
+            Line::from(vec![
+                Span::styled(
+                    format!("  {:<14}", keys),
+                    Style::default().fg(theme.accent_color),
+                ),
+                Span::styled(
+                    format!("{:<26}", desc),
+                    Style::default().fg(theme.text_color),
+                ),
+                Span::styled(name.to_string(), Style::default().fg(theme.dim_color)),
+            ])
+        };
+
+        for context in CONTEXTS {
+            lines.push(heading(context.title(), theme.title_color));
+            for action in Keymap::actions(context) {
+                let keys: Vec<String> = keymap
+                    .keys(context, action)
+                    .iter()
+                    .map(|k| k.to_string())
+                    .collect();
+                let keys = if keys.is_empty() {
+                    "-".to_string()
+                } else {
+                    keys.join(" / ")
+                };
+                lines.push(binding(keys, action.description(), action.name()));
             }
             lines.push(Line::from(""));
         }
 
+        // The input's own keys can't be rebound
+        lines.push(heading("Input", theme.title_color));
+        for (key, desc) in EDITOR_KEYS {
+            let key = KeyBinding::parse(key)
+                .map(|k| k.to_string())
+                .unwrap_or_default();
+            lines.push(binding(key, desc, ""));
+        }
+        lines.push(binding("@".to_string(), "Search files to attach", ""));
+        lines.push(binding("↑/↓".to_string(), "Autocomplete", ""));
+
+        if !keymap.problems().is_empty() {
+            lines.push(Line::from(""));
+            lines.push(heading("keymap.toml problems", theme.warning_color));
+            for problem in keymap.problems() {
+                lines.push(Line::from(Span::styled(
+                    format!("  {}", problem),
+                    Style::default().fg(theme.warning_color),
+                )));
+            }
+        }
+
         lines
     }
 }
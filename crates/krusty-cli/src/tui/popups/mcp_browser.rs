@@ -15,7 +15,10 @@ use super::common::{
     scroll_indicator, PopupSize,
 };
 use crate::tui::themes::Theme;
-use krusty_core::mcp::{McpServerInfo, McpServerStatus, McpToolDef};
+use krusty_core::mcp::{McpServerInfo, McpServerSource, McpServerStatus, McpToolDef};
+
+/// stderr lines shown for an expanded server
+const STDERR_LINES_SHOWN: usize = 5;
 
 /// MCP browser popup state
 pub struct McpBrowserPopup {
@@ -157,7 +160,11 @@ impl McpBrowserPopup {
                 Style::default().fg(theme.dim_color),
             )]));
             lines.push(Line::from(vec![Span::styled(
-                "  Create .mcp.json in your project root to add servers.",
+                "  Add servers to ~/.krusty/mcp.json, .mcp.json in your project root,",
+                Style::default().fg(theme.dim_color),
+            )]));
+            lines.push(Line::from(vec![Span::styled(
+                "  .krusty/mcp.local.json, or install an extension with a context server.",
                 Style::default().fg(theme.dim_color),
             )]));
         } else {
@@ -177,6 +184,16 @@ impl McpBrowserPopup {
                 lines.push(render_server_line(server, is_selected, theme));
                 displayed += 1;
 
+                if is_expanded {
+                    for line in render_config_lines(server, theme) {
+                        if displayed >= visible_height {
+                            break;
+                        }
+                        lines.push(line);
+                        displayed += 1;
+                    }
+                }
+
                 if is_expanded && displayed < visible_height {
                     for (tool_idx, tool) in server.tools.iter().enumerate() {
                         if displayed >= visible_height {
@@ -258,7 +275,9 @@ impl McpBrowserPopup {
 fn render_server_line<'a>(server: &McpServerInfo, is_selected: bool, theme: &Theme) -> Line<'a> {
     let (icon, icon_color) = match &server.status {
         McpServerStatus::Connected => ("●", theme.success_color),
+        McpServerStatus::Disabled => ("−", theme.dim_color),
         McpServerStatus::Disconnected => ("○", theme.dim_color),
+        McpServerStatus::Restarting(_) => ("◐", theme.warning_color),
         McpServerStatus::Error(_) => ("✗", theme.error_color),
     };
 
@@ -272,8 +291,14 @@ fn render_server_line<'a>(server: &McpServerInfo, is_selected: bool, theme: &The
     };
 
     let status_text = match &server.status {
+        McpServerStatus::Connected if server.filtered_tools > 0 => format!(
+            "{} tools ({} filtered)",
+            server.tool_count, server.filtered_tools
+        ),
         McpServerStatus::Connected => format!("{} tools", server.tool_count),
+        McpServerStatus::Disabled => "disabled".to_string(),
         McpServerStatus::Disconnected => "disconnected".to_string(),
+        McpServerStatus::Restarting(attempt) => format!("restarting (attempt {})", attempt),
         McpServerStatus::Error(e) => {
             let msg = server.error.as_deref().unwrap_or(e);
             if msg.len() > 35 {
@@ -284,7 +309,7 @@ fn render_server_line<'a>(server: &McpServerInfo, is_selected: bool, theme: &The
         }
     };
 
-    Line::from(vec![
+    let mut spans = vec![
         Span::styled(prefix.to_string(), name_style),
         Span::styled(icon.to_string(), Style::default().fg(icon_color)),
         Span::raw(" "),
@@ -293,11 +318,93 @@ fn render_server_line<'a>(server: &McpServerInfo, is_selected: bool, theme: &The
             format!(" ({})", server.server_type),
             Style::default().fg(theme.dim_color),
         ),
-        Span::styled(
-            format!(" - {}", status_text),
-            Style::default().fg(theme.dim_color),
-        ),
-    ])
+    ];
+
+    let source_color = match &server.source {
+        McpServerSource::Extension(_) => theme.accent_color,
+        McpServerSource::Config(_) => theme.dim_color,
+    };
+    spans.push(Span::styled(
+        format!(" [{}]", server.source),
+        Style::default().fg(source_color),
+    ));
+
+    spans.push(Span::styled(
+        format!(" - {}", status_text),
+        Style::default().fg(theme.dim_color),
+    ));
+
+    Line::from(spans)
+}
+
+/// Effective (merged) config for an expanded server
+fn render_config_lines<'a>(server: &McpServerInfo, theme: &Theme) -> Vec<Line<'a>> {
+    let mut details = vec![(
+        if server.server_type == "remote" {
+            "url"
+        } else {
+            "cmd"
+        },
+        server.summary.clone(),
+    )];
+    if let McpServerSource::Extension(id) = &server.source {
+        details.push(("from", id.clone()));
+    }
+    if let Some(ref cwd) = server.options.cwd {
+        details.push(("cwd", cwd.display().to_string()));
+    }
+    if let Some(timeout) = server.options.timeout {
+        details.push(("timeout", format!("{}s", timeout.as_secs())));
+    }
+    if let Some(ref allowed) = server.options.tools.allowed {
+        details.push(("allow", allowed.join(", ")));
+    }
+    if !server.options.tools.denied.is_empty() {
+        details.push(("deny", server.options.tools.denied.join(", ")));
+    }
+    if server.health.restarts > 0 {
+        details.push(("restarts", server.health.restarts.to_string()));
+    }
+    if let Some(ref exit) = server.health.last_exit {
+        details.push(("exit", exit.clone()));
+    }
+
+    let mut lines: Vec<Line<'a>> = details
+        .into_iter()
+        .map(|(label, value)| {
+            let value = if value.chars().count() > 50 {
+                format!("{}...", value.chars().take(47).collect::<String>())
+            } else {
+                value
+            };
+            Line::from(vec![
+                Span::styled(
+                    format!("      {:<8}", label),
+                    Style::default().fg(theme.dim_color),
+                ),
+                Span::styled(value, Style::default().fg(theme.text_color)),
+            ])
+        })
+        .collect();
+
+    // Last few stderr lines help diagnose crashing servers
+    let tail_start = server.stderr_tail.len().saturating_sub(STDERR_LINES_SHOWN);
+    for (idx, line) in server.stderr_tail[tail_start..].iter().enumerate() {
+        let label = if idx == 0 { "stderr" } else { "" };
+        let line = if line.chars().count() > 50 {
+            format!("{}...", line.chars().take(47).collect::<String>())
+        } else {
+            line.clone()
+        };
+        lines.push(Line::from(vec![
+            Span::styled(
+                format!("      {:<8}", label),
+                Style::default().fg(theme.dim_color),
+            ),
+            Span::styled(line, Style::default().fg(theme.dim_color)),
+        ]));
+    }
+    lines
 }
 
 fn render_tool_line<'a>(tool: &McpToolDef, is_last: bool, theme: &Theme) -> Line<'a> {
@@ -8,7 +8,9 @@
 //! - Theme-aware colors
 
 pub mod auth;
+pub mod change_review;
 pub mod common;
+pub mod docs_browser;
 pub mod file_preview;
 pub mod help;
 pub mod hooks;
@@ -18,5 +20,7 @@ pub mod pinch;
 pub mod process_list;
 pub mod scroll;
 pub mod session_list;
+pub mod session_search;
 pub mod skills_browser;
 pub mod theme_select;
+pub mod transcript;
@@ -2,6 +2,8 @@
 //!
 //! Displays models grouped by provider with search functionality.
 //! Supports recent models, rich metadata, and dynamic model lists.
+//! Tab switches the role being assigned (main, explore, build, title,
+//! summarizer) so side work can be routed to a different model.
 
 use ratatui::{
     layout::{Alignment, Constraint, Direction, Layout},
@@ -17,6 +19,7 @@ use super::common::{
 };
 use crate::ai::models::ModelMetadata;
 use crate::ai::providers::ProviderId;
+use crate::ai::routing::{ModelRole, ModelRouting};
 use crate::tui::themes::Theme;
 
 /// Entry in the model list
@@ -46,6 +49,8 @@ pub struct ModelSelectPopup {
     pub loading: bool,
     /// Error message if fetch failed
     pub error: Option<String>,
+    /// Role the selected model is assigned to
+    pub role: ModelRole,
 }
 
 impl Default for ModelSelectPopup {
@@ -64,9 +69,16 @@ impl ModelSelectPopup {
             entries: Vec::new(),
             loading: false,
             error: None,
+            role: ModelRole::Main,
         }
     }
 
+    /// Assign models to the next role
+    pub fn next_role(&mut self) {
+        self.role = self.role.next();
+        self.error = None;
+    }
+
     /// Set models from organized data (called from App)
     /// recent_models: Models recently used by user
     /// models_by_provider: Models grouped by provider
@@ -388,7 +400,14 @@ impl ModelSelectPopup {
         theme: &Theme,
         current_model: &str,
         context_tokens_used: usize,
+        routing: &ModelRouting,
     ) {
+        // Model the role being assigned currently runs on
+        let role_model = routing
+            .route(self.role)
+            .map(|r| r.model.as_str())
+            .unwrap_or(current_model);
+
         let (w, h) = PopupSize::Large.dimensions();
         let area = center_rect(w, h, f.area());
         render_popup_background(f, area, theme);
@@ -406,7 +425,7 @@ impl ModelSelectPopup {
                 Constraint::Length(3),             // Title
                 Constraint::Length(search_height), // Search
                 Constraint::Min(5),                // Content
-                Constraint::Length(2),             // Footer
+                Constraint::Length(3),             // Routes + footer
             ])
             .split(inner);
 
@@ -428,6 +447,11 @@ impl ModelSelectPopup {
         } else {
             format!("Models ({})", self.model_count())
         };
+        let title_text = if self.role == ModelRole::Main {
+            title_text
+        } else {
+            format!("{} · {} role", title_text, self.role)
+        };
         let title_lines = popup_title(&title_text, theme);
         let title = Paragraph::new(title_lines).alignment(Alignment::Center);
         f.render_widget(title, chunks[0]);
@@ -504,8 +528,10 @@ impl ModelSelectPopup {
                     }
                     ModelEntry::Model { metadata } => {
                         let is_selected = display_idx == self.selected_index;
-                        let is_current = metadata.id == current_model;
-                        let is_too_small = context_tokens_used > metadata.context_window;
+                        let is_current = metadata.id == role_model;
+                        // Only the main model has to fit the conversation
+                        let is_too_small = self.role == ModelRole::Main
+                            && context_tokens_used > metadata.context_window;
 
                         // Fixed-width prefix (6 chars)
                         let prefix = if is_selected {
@@ -589,6 +615,16 @@ impl ModelSelectPopup {
                                 .push(Span::styled(" ←", Style::default().fg(theme.success_color)));
                         }
 
+                        // Roles routed to this model
+                        let roles = routing.roles_for(metadata.provider, &metadata.id);
+                        if !roles.is_empty() {
+                            let labels: Vec<&str> = roles.iter().map(|r| r.label()).collect();
+                            spans.push(Span::styled(
+                                format!(" [{}]", labels.join(", ")),
+                                Style::default().fg(theme.accent_color),
+                            ));
+                        }
+
                         lines.push(Line::from(spans));
                     }
                 }
@@ -605,6 +641,37 @@ impl ModelSelectPopup {
         let content_area = center_content(chunks[2], 4);
         f.render_widget(content, content_area);
 
+        // Routes line: what each role runs on, the one being assigned highlighted
+        let mut route_spans = Vec::new();
+        for (i, role) in ModelRole::ALL.iter().enumerate() {
+            if i > 0 {
+                route_spans.push(Span::styled(" · ", Style::default().fg(theme.dim_color)));
+            }
+            let model = match role {
+                ModelRole::Main => current_model,
+                _ => routing
+                    .route(*role)
+                    .map(|r| r.model.as_str())
+                    .unwrap_or("main"),
+            };
+            let style = if *role == self.role {
+                Style::default()
+                    .fg(theme.accent_color)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(theme.dim_color)
+            };
+            route_spans.push(Span::styled(format!("{}: {}", role, model), style));
+        }
+        let footer_chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(1), Constraint::Length(2)])
+            .split(chunks[3]);
+        f.render_widget(
+            Paragraph::new(Line::from(route_spans)).alignment(Alignment::Center),
+            footer_chunks[0],
+        );
+
         // Footer
         let footer = if self.search_active {
             Paragraph::new(Line::from(vec![
@@ -640,6 +707,20 @@ impl ModelSelectPopup {
                         .add_modifier(Modifier::BOLD),
                 ),
                 Span::styled(": nav  ", Style::default().fg(theme.text_color)),
+                Span::styled(
+                    "Tab",
+                    Style::default()
+                        .fg(theme.accent_color)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::styled(": role  ", Style::default().fg(theme.text_color)),
+                Span::styled(
+                    "Del",
+                    Style::default()
+                        .fg(theme.accent_color)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::styled(": use main  ", Style::default().fg(theme.text_color)),
                 Span::styled(
                     "Enter",
                     Style::default()
@@ -649,6 +730,6 @@ impl ModelSelectPopup {
                 Span::styled(": select", Style::default().fg(theme.text_color)),
             ]))
         };
-        f.render_widget(footer.alignment(Alignment::Center), chunks[3]);
+        f.render_widget(footer.alignment(Alignment::Center), footer_chunks[1]);
     }
 }
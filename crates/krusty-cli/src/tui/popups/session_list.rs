@@ -201,6 +201,13 @@ impl SessionListPopup {
                     .add_modifier(Modifier::BOLD),
             ),
             Span::styled(": delete  ", Style::default().fg(theme.text_color)),
+            Span::styled(
+                "/",
+                Style::default()
+                    .fg(theme.accent_color)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(": search  ", Style::default().fg(theme.text_color)),
             Span::styled(
                 "Esc",
                 Style::default()
@@ -0,0 +1,320 @@
+//! Session search popup - full-text search across past sessions
+//!
+//! Searches the text of every saved message, narrowed by directory and date.
+//! Opening a result loads its session and scrolls to the message.
+
+use chrono::{DateTime, Duration, Local, Utc};
+use ratatui::{
+    layout::{Alignment, Constraint, Direction, Layout},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::Paragraph,
+    Frame,
+};
+
+use super::common::{
+    center_content, center_rect, popup_block, popup_title, render_popup_background,
+    scroll_indicator, PopupSize,
+};
+use super::scroll::ScrollState;
+use crate::storage::{MessageSearchFilter, MessageSearchHit};
+use crate::tui::themes::Theme;
+
+/// Most results fetched per query
+pub const SEARCH_LIMIT: usize = 100;
+
+/// Which sessions are searched
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchScope {
+    /// Sessions started in the current directory or below it
+    CurrentDirectory,
+    AllDirectories,
+}
+
+/// How far back messages are searched
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DateRange {
+    Any,
+    Today,
+    Week,
+    Month,
+    Year,
+}
+
+impl DateRange {
+    pub fn next(self) -> Self {
+        match self {
+            Self::Any => Self::Today,
+            Self::Today => Self::Week,
+            Self::Week => Self::Month,
+            Self::Month => Self::Year,
+            Self::Year => Self::Any,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Any => "any time",
+            Self::Today => "last 24 hours",
+            Self::Week => "last 7 days",
+            Self::Month => "last 30 days",
+            Self::Year => "last year",
+        }
+    }
+
+    fn since(self) -> Option<DateTime<Utc>> {
+        let days = match self {
+            Self::Any => return None,
+            Self::Today => 1,
+            Self::Week => 7,
+            Self::Month => 30,
+            Self::Year => 365,
+        };
+        Some(Utc::now() - Duration::days(days))
+    }
+}
+
+/// Session search popup state
+pub struct SessionSearchPopup {
+    pub query: String,
+    pub hits: Vec<MessageSearchHit>,
+    pub scope: SearchScope,
+    pub range: DateRange,
+    /// Directory used by [`SearchScope::CurrentDirectory`]
+    current_dir: String,
+    pub error: Option<String>,
+    scroll: ScrollState,
+}
+
+impl Default for SessionSearchPopup {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SessionSearchPopup {
+    pub fn new() -> Self {
+        Self {
+            query: String::new(),
+            hits: Vec::new(),
+            scope: SearchScope::CurrentDirectory,
+            range: DateRange::Any,
+            current_dir: String::new(),
+            error: None,
+            scroll: ScrollState::new(0),
+        }
+    }
+
+    /// Reset for a new search in `current_dir`, keeping the filters
+    pub fn open(&mut self, current_dir: &str, query: &str) {
+        self.current_dir = current_dir.to_string();
+        self.query = query.to_string();
+        self.set_hits(Vec::new());
+        self.error = None;
+    }
+
+    /// Filter for the current scope and date range
+    pub fn filter(&self) -> MessageSearchFilter {
+        MessageSearchFilter {
+            working_dir: (self.scope == SearchScope::CurrentDirectory)
+                .then(|| self.current_dir.clone()),
+            since: self.range.since(),
+            until: None,
+        }
+    }
+
+    pub fn set_hits(&mut self, hits: Vec<MessageSearchHit>) {
+        self.scroll = ScrollState::new(hits.len());
+        self.hits = hits;
+    }
+
+    pub fn toggle_scope(&mut self) {
+        self.scope = match self.scope {
+            SearchScope::CurrentDirectory => SearchScope::AllDirectories,
+            SearchScope::AllDirectories => SearchScope::CurrentDirectory,
+        };
+    }
+
+    pub fn next_range(&mut self) {
+        self.range = self.range.next();
+    }
+
+    pub fn next(&mut self) {
+        self.scroll.next();
+    }
+
+    pub fn prev(&mut self) {
+        self.scroll.prev();
+    }
+
+    pub fn get_selected_hit(&self) -> Option<&MessageSearchHit> {
+        self.hits.get(self.scroll.selected)
+    }
+
+    pub fn render(&mut self, f: &mut Frame, theme: &Theme) {
+        let (w, h) = PopupSize::Large.dimensions();
+        let area = center_rect(w, h, f.area());
+        render_popup_background(f, area, theme);
+
+        let block = popup_block(theme);
+        let inner = block.inner(area);
+        f.render_widget(block, area);
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(3), // Title
+                Constraint::Length(2), // Query and filters
+                Constraint::Min(5),    // Results
+                Constraint::Length(2), // Footer
+            ])
+            .split(inner);
+
+        // Each result takes three lines: title, snippet, blank
+        let visible_items = (chunks[2].height as usize).saturating_sub(2) / 3;
+        self.scroll.set_visible_height(visible_items);
+
+        let title =
+            Paragraph::new(popup_title("Search Sessions", theme)).alignment(Alignment::Center);
+        f.render_widget(title, chunks[0]);
+
+        let scope = match self.scope {
+            SearchScope::CurrentDirectory => "this directory",
+            SearchScope::AllDirectories => "all directories",
+        };
+        let search = Paragraph::new(Line::from(vec![
+            Span::styled("  Search: ", Style::default().fg(theme.accent_color)),
+            Span::styled(self.query.as_str(), Style::default().fg(theme.text_color)),
+            Span::styled(
+                "_",
+                Style::default()
+                    .fg(theme.accent_color)
+                    .add_modifier(Modifier::SLOW_BLINK),
+            ),
+            Span::styled(
+                format!("  [{} · {}]", scope, self.range.label()),
+                Style::default().fg(theme.dim_color),
+            ),
+        ]));
+        f.render_widget(search, chunks[1]);
+
+        let content_area = center_content(chunks[2], 4);
+        let width = content_area.width as usize;
+        let mut lines: Vec<Line> = Vec::new();
+
+        if let Some(ref error) = self.error {
+            lines.push(Line::from(Span::styled(
+                format!("  {}", error),
+                Style::default().fg(theme.error_color),
+            )));
+        } else if self.hits.is_empty() {
+            let hint = if self.query.trim().is_empty() {
+                "  Type to search messages from past sessions"
+            } else {
+                "  No matching messages"
+            };
+            lines.push(Line::from(Span::styled(
+                hint,
+                Style::default()
+                    .fg(theme.dim_color)
+                    .add_modifier(Modifier::ITALIC),
+            )));
+        } else {
+            let items_above = self.scroll.items_above();
+            if items_above > 0 {
+                lines.push(scroll_indicator("up", items_above, theme));
+            }
+
+            for idx in self.scroll.visible_range() {
+                let hit = &self.hits[idx];
+                let is_selected = self.scroll.is_selected(idx);
+                let title_style = if is_selected {
+                    Style::default()
+                        .fg(theme.accent_color)
+                        .add_modifier(Modifier::BOLD)
+                } else {
+                    Style::default().fg(theme.text_color)
+                };
+                let prefix = if is_selected { "▶ " } else { "  " };
+
+                let mut detail = format!(
+                    "  {}",
+                    hit.created_at
+                        .with_timezone(&Local)
+                        .format("%Y-%m-%d %H:%M")
+                );
+                if self.scope == SearchScope::AllDirectories {
+                    if let Some(ref dir) = hit.working_dir {
+                        detail.push_str(&format!("  {}", dir));
+                    }
+                }
+                lines.push(Line::from(vec![
+                    Span::styled(prefix, title_style),
+                    Span::styled(hit.session_title.clone(), title_style),
+                    Span::styled(detail, Style::default().fg(theme.dim_color)),
+                ]));
+
+                let mut spans = vec![Span::styled(
+                    format!("  {}: ", hit.role),
+                    Style::default().fg(theme.dim_color),
+                )];
+                let mut remaining = width.saturating_sub(4 + hit.role.len());
+                for (text, is_match) in hit.snippet_parts() {
+                    if remaining == 0 {
+                        break;
+                    }
+                    let text: String = text
+                        .chars()
+                        .map(|c| if c == '\n' || c == '\r' { ' ' } else { c })
+                        .take(remaining)
+                        .collect();
+                    remaining = remaining.saturating_sub(text.chars().count());
+                    let style = if is_match {
+                        Style::default()
+                            .fg(theme.accent_color)
+                            .add_modifier(Modifier::BOLD)
+                    } else {
+                        Style::default().fg(theme.text_color)
+                    };
+                    spans.push(Span::styled(text, style));
+                }
+                lines.push(Line::from(spans));
+                lines.push(Line::from(""));
+            }
+
+            let items_below = self.scroll.items_below();
+            if items_below > 0 {
+                lines.push(scroll_indicator("down", items_below, theme));
+            }
+        }
+
+        let content = Paragraph::new(lines).style(Style::default().bg(theme.bg_color));
+        f.render_widget(content, content_area);
+
+        let key = |k: &'static str| {
+            Span::styled(
+                k,
+                Style::default()
+                    .fg(theme.accent_color)
+                    .add_modifier(Modifier::BOLD),
+            )
+        };
+        let text = |t: &'static str| Span::styled(t, Style::default().fg(theme.text_color));
+        let footer = Line::from(vec![
+            key("↑↓"),
+            text(": navigate  "),
+            key("Enter"),
+            text(": open  "),
+            key("Tab"),
+            text(": dates  "),
+            key("^D"),
+            text(": directory  "),
+            key("Esc"),
+            text(": close"),
+        ]);
+        f.render_widget(
+            Paragraph::new(footer).alignment(Alignment::Center),
+            chunks[3],
+        );
+    }
+}
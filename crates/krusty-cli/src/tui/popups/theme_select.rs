@@ -40,7 +40,8 @@ impl ThemeSelectPopup {
     /// Open the popup and store the current theme name for potential restore
     pub fn open(&mut self, current_theme: &str) {
         self.original_theme_name = Some(current_theme.to_string());
-        let themes = THEME_REGISTRY.list();
+        let registry = THEME_REGISTRY.read();
+        let themes = registry.list();
         if let Some(idx) = themes.iter().position(|(name, _)| *name == current_theme) {
             self.selected_index = idx;
             self.ensure_visible();
@@ -53,7 +54,8 @@ impl ThemeSelectPopup {
     }
 
     pub fn next(&mut self) {
-        let themes = THEME_REGISTRY.list();
+        let registry = THEME_REGISTRY.read();
+        let themes = registry.list();
         if self.selected_index < themes.len() - 1 {
             self.selected_index += 1;
             self.ensure_visible();
@@ -80,7 +82,8 @@ impl ThemeSelectPopup {
     }
 
     pub fn get_selected_theme_name(&self) -> Option<String> {
-        let themes = THEME_REGISTRY.list();
+        let registry = THEME_REGISTRY.read();
+        let themes = registry.list();
         themes
             .get(self.selected_index)
             .map(|(name, _)| (*name).clone())
@@ -113,7 +116,8 @@ impl ThemeSelectPopup {
         f.render_widget(title, chunks[0]);
 
         // Theme list
-        let themes = THEME_REGISTRY.list();
+        let registry = THEME_REGISTRY.read();
+        let themes = registry.list();
         let mut lines: Vec<Line> = Vec::new();
 
         // Scroll up indicator
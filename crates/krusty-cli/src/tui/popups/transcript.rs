@@ -0,0 +1,266 @@
+//! Sub-agent transcript viewer
+//!
+//! Opened by clicking an agent row in an explore or build block. Shows the
+//! prompt, replies, tool calls and results the agent went through, as stored
+//! in the session's sub-agent transcripts.
+
+use ratatui::{
+    layout::{Alignment, Constraint, Direction, Layout},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::Paragraph,
+    Frame,
+};
+
+use super::common::{
+    center_rect, popup_block, popup_title, render_popup_background, scroll_indicator,
+};
+use crate::agent::subagent::{TranscriptEntry, TranscriptKind};
+use crate::tui::themes::Theme;
+
+/// Lines of a tool result shown before it is cut short
+const MAX_RESULT_LINES: usize = 12;
+
+/// Characters of tool input shown per call
+const MAX_INPUT_CHARS: usize = 400;
+
+/// Transcript popup state
+#[derive(Default)]
+pub struct TranscriptPopup {
+    /// Tool call and agent task being shown, for reloading
+    source: Option<(String, String)>,
+    agent_name: String,
+    model: String,
+    entries: Vec<TranscriptEntry>,
+    scroll_offset: usize,
+    /// Content lines at the last render, to keep scrolling in range
+    total_lines: usize,
+    visible_height: usize,
+}
+
+impl TranscriptPopup {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Show an agent's transcript
+    pub fn open(
+        &mut self,
+        tool_use_id: String,
+        task_id: String,
+        agent_name: String,
+        entries: Vec<TranscriptEntry>,
+    ) {
+        self.model = entries.first().map(|e| e.model.clone()).unwrap_or_default();
+        self.agent_name = agent_name;
+        self.source = Some((tool_use_id, task_id));
+        self.entries = entries;
+        self.scroll_offset = 0;
+    }
+
+    /// Tool call and agent task being shown
+    pub fn source(&self) -> Option<(&str, &str)> {
+        self.source
+            .as_ref()
+            .map(|(tool_use_id, task_id)| (tool_use_id.as_str(), task_id.as_str()))
+    }
+
+    /// Replace the entries (agent still running), keeping the scroll position
+    pub fn refresh(&mut self, entries: Vec<TranscriptEntry>) {
+        self.entries = entries;
+    }
+
+    pub fn scroll_up(&mut self, lines: usize) {
+        self.scroll_offset = self.scroll_offset.saturating_sub(lines);
+    }
+
+    pub fn scroll_down(&mut self, lines: usize) {
+        self.scroll_offset = (self.scroll_offset + lines).min(self.max_scroll());
+    }
+
+    pub fn scroll_to_top(&mut self) {
+        self.scroll_offset = 0;
+    }
+
+    pub fn scroll_to_bottom(&mut self) {
+        self.scroll_offset = self.max_scroll();
+    }
+
+    fn max_scroll(&self) -> usize {
+        self.total_lines.saturating_sub(self.visible_height)
+    }
+
+    pub fn render(&mut self, f: &mut Frame, theme: &Theme) {
+        // Transcripts are long; use most of the screen
+        let screen = f.area();
+        let area = center_rect(
+            screen.width.saturating_sub(8).clamp(40, 110),
+            screen.height.saturating_sub(4),
+            screen,
+        );
+        render_popup_background(f, area, theme);
+
+        let block = popup_block(theme);
+        let inner = block.inner(area);
+        f.render_widget(block, area);
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(4), // Title + agent line
+                Constraint::Min(5),    // Content
+                Constraint::Length(2), // Footer
+            ])
+            .split(inner);
+
+        let mut title_lines = popup_title(&format!("Transcript: {}", self.agent_name), theme);
+        if !self.model.is_empty() {
+            title_lines.push(Line::from(Span::styled(
+                self.model.clone(),
+                Style::default().fg(theme.dim_color),
+            )));
+        }
+        let title = Paragraph::new(title_lines).alignment(Alignment::Center);
+        f.render_widget(title, chunks[0]);
+
+        // Reserve space for scroll indicators
+        let width = chunks[1].width.saturating_sub(4) as usize;
+        let all_lines = self.content_lines(theme, width.max(20));
+        self.total_lines = all_lines.len();
+        self.visible_height = (chunks[1].height as usize).saturating_sub(2);
+        self.scroll_offset = self.scroll_offset.min(self.max_scroll());
+
+        let mut display_lines: Vec<Line> = Vec::new();
+        if self.scroll_offset > 0 {
+            display_lines.push(scroll_indicator("up", self.scroll_offset, theme));
+        }
+        display_lines.extend(
+            all_lines
+                .into_iter()
+                .skip(self.scroll_offset)
+                .take(self.visible_height),
+        );
+        let remaining = self
+            .total_lines
+            .saturating_sub(self.scroll_offset + self.visible_height);
+        if remaining > 0 {
+            display_lines.push(scroll_indicator("down", remaining, theme));
+        }
+
+        let content = Paragraph::new(display_lines).style(Style::default().bg(theme.bg_color));
+        f.render_widget(content, chunks[1]);
+
+        let key = |k: &str| {
+            Span::styled(
+                k.to_string(),
+                Style::default()
+                    .fg(theme.accent_color)
+                    .add_modifier(Modifier::BOLD),
+            )
+        };
+        let text = |t: &str| Span::styled(t.to_string(), Style::default().fg(theme.text_color));
+        let footer = Paragraph::new(Line::from(vec![
+            key("↑↓/PgUp/PgDn"),
+            text(": scroll  "),
+            key("r"),
+            text(": reload  "),
+            key("Esc"),
+            text(": close"),
+        ]))
+        .alignment(Alignment::Center);
+        f.render_widget(footer, chunks[2]);
+    }
+
+    /// All transcript lines, wrapped to `width`
+    fn content_lines(&self, theme: &Theme, width: usize) -> Vec<Line<'static>> {
+        let mut lines = Vec::new();
+        if self.entries.is_empty() {
+            lines.push(Line::from(Span::styled(
+                "  Nothing recorded for this agent yet".to_string(),
+                Style::default()
+                    .fg(theme.dim_color)
+                    .add_modifier(Modifier::ITALIC),
+            )));
+            return lines;
+        }
+
+        let label = |text: String, color| {
+            Line::from(Span::styled(
+                text,
+                Style::default().fg(color).add_modifier(Modifier::BOLD),
+            ))
+        };
+        let body = |lines: &mut Vec<Line<'static>>, content: &str, color| {
+            for line in textwrap::wrap(content, width.saturating_sub(2)) {
+                lines.push(Line::from(Span::styled(
+                    format!("  {}", line),
+                    Style::default().fg(color),
+                )));
+            }
+        };
+
+        let mut turn = None;
+        for entry in &self.entries {
+            if entry.kind != TranscriptKind::Outcome && turn != Some(entry.turn) {
+                if turn.is_some() {
+                    lines.push(Line::from(""));
+                }
+                if entry.turn > 0 {
+                    lines.push(Line::from(Span::styled(
+                        format!("── turn {} ", entry.turn),
+                        Style::default().fg(theme.border_color),
+                    )));
+                }
+                turn = Some(entry.turn);
+            }
+
+            let tool = entry.tool_name.as_deref().unwrap_or("tool");
+            match entry.kind {
+                TranscriptKind::Prompt => {
+                    lines.push(label("▸ Prompt".to_string(), theme.title_color));
+                    body(&mut lines, &entry.content, theme.text_color);
+                }
+                TranscriptKind::Text => {
+                    body(&mut lines, &entry.content, theme.text_color);
+                }
+                TranscriptKind::ToolCall => {
+                    lines.push(label(format!("→ {}", tool), theme.accent_color));
+                    let input: String = entry.content.chars().take(MAX_INPUT_CHARS).collect();
+                    body(&mut lines, &input, theme.dim_color);
+                }
+                TranscriptKind::ToolResult => {
+                    let color = if entry.is_error {
+                        theme.error_color
+                    } else {
+                        theme.dim_color
+                    };
+                    lines.push(label(format!("← {}", tool), color));
+                    let total = entry.content.lines().count();
+                    let shown: Vec<&str> = entry.content.lines().take(MAX_RESULT_LINES).collect();
+                    body(&mut lines, &shown.join("\n"), theme.dim_color);
+                    if total > MAX_RESULT_LINES {
+                        lines.push(Line::from(Span::styled(
+                            format!("  … {} more lines", total - MAX_RESULT_LINES),
+                            Style::default()
+                                .fg(theme.dim_color)
+                                .add_modifier(Modifier::ITALIC),
+                        )));
+                    }
+                }
+                TranscriptKind::Note => {
+                    lines.push(label("✎ Note".to_string(), theme.warning_color));
+                    body(&mut lines, &entry.content, theme.text_color);
+                }
+                TranscriptKind::Outcome => {
+                    lines.push(Line::from(""));
+                    if entry.is_error {
+                        lines.push(label(format!("✗ {}", entry.content), theme.error_color));
+                    } else {
+                        lines.push(label(format!("✓ {}", entry.content), theme.success_color));
+                    }
+                }
+            }
+        }
+        lines
+    }
+}
@@ -3,10 +3,12 @@
 //! Groups all popup controller states into a single component.
 
 use crate::tui::popups::{
-    auth::AuthPopup, file_preview::FilePreviewPopup, help::HelpPopup, hooks::HooksPopup,
+    auth::AuthPopup, change_review::ChangeReviewPopup, docs_browser::DocsBrowserPopup,
+    file_preview::FilePreviewPopup, help::HelpPopup, hooks::HooksPopup,
     mcp_browser::McpBrowserPopup, model_select::ModelSelectPopup, pinch::PinchPopup,
     process_list::ProcessListPopup, session_list::SessionListPopup,
-    skills_browser::SkillsBrowserPopup, theme_select::ThemeSelectPopup,
+    session_search::SessionSearchPopup, skills_browser::SkillsBrowserPopup,
+    theme_select::ThemeSelectPopup, transcript::TranscriptPopup,
 };
 
 /// All popup controller states grouped together
@@ -15,6 +17,7 @@ pub struct PopupState {
     pub theme: ThemeSelectPopup,
     pub model: ModelSelectPopup,
     pub session: SessionListPopup,
+    pub session_search: SessionSearchPopup,
     pub auth: AuthPopup,
     pub mcp: McpBrowserPopup,
     pub process: ProcessListPopup,
@@ -22,6 +25,9 @@ pub struct PopupState {
     pub file_preview: FilePreviewPopup,
     pub skills: SkillsBrowserPopup,
     pub hooks: HooksPopup,
+    pub docs: DocsBrowserPopup,
+    pub transcript: TranscriptPopup,
+    pub change_review: ChangeReviewPopup,
 }
 
 impl PopupState {
@@ -34,6 +40,7 @@ impl PopupState {
             theme: ThemeSelectPopup::new(),
             model: ModelSelectPopup::new(),
             session: SessionListPopup::new(),
+            session_search: SessionSearchPopup::new(),
             auth: AuthPopup::new(),
             mcp: McpBrowserPopup::new(),
             process: ProcessListPopup::new(),
@@ -41,6 +48,9 @@ impl PopupState {
             file_preview,
             skills: SkillsBrowserPopup::new(),
             hooks: HooksPopup::new(),
+            docs: DocsBrowserPopup::new(),
+            transcript: TranscriptPopup::new(),
+            change_review: ChangeReviewPopup::new(),
         }
     }
 }
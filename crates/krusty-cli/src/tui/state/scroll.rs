@@ -29,6 +29,10 @@ pub struct ScrollState {
     pub locked_to_messages: bool,
     /// Lock scroll during text selection (prevents block scroll capture)
     pub locked_for_selection: bool,
+    /// Chat message to bring to the top on a coming render
+    pub scroll_to_message: Option<usize>,
+    /// First line of that message, found while counting message lines
+    pub message_target_line: Option<usize>,
 }
 
 impl ScrollState {
@@ -41,6 +45,8 @@ impl ScrollState {
             scroll_to_bottom: false,
             locked_to_messages: false,
             locked_for_selection: false,
+            scroll_to_message: None,
+            message_target_line: None,
         }
     }
 
@@ -91,6 +97,22 @@ impl ScrollState {
         }
     }
 
+    /// Request scrolling to a chat message once its position is known
+    pub fn request_scroll_to_message(&mut self, msg_idx: usize) {
+        self.scroll_to_message = Some(msg_idx);
+        self.message_target_line = None;
+        self.scroll_to_bottom = false;
+        self.auto_scroll = false;
+    }
+
+    /// Apply a pending scroll-to-message request, if its line has been found
+    pub fn apply_scroll_to_message(&mut self) {
+        if let Some(line) = self.message_target_line.take() {
+            self.scroll_to_message = None;
+            self.scroll_to_line(line);
+        }
+    }
+
     // =========================================================================
     // Max Scroll Updates
     // =========================================================================
@@ -0,0 +1,107 @@
+//! Color fields of `Theme` by name
+//!
+//! Theme files refer to colors by their field names (`bg_color`, or `bg`
+//! for short). The list here is the single place those names are spelled.
+
+use ratatui::style::Color;
+
+use super::Theme;
+
+macro_rules! theme_color_fields {
+    ($($field:ident),* $(,)?) => {
+        /// Names of every color field of `Theme`, in declaration order
+        pub const COLOR_FIELDS: &[&str] = &[$(stringify!($field)),*];
+
+        /// A color field by name
+        pub fn color_field(theme: &Theme, name: &str) -> Option<Color> {
+            match name {
+                $(stringify!($field) => Some(theme.$field),)*
+                _ => None,
+            }
+        }
+
+        /// Mutable access to a color field by name
+        pub fn color_field_mut<'a>(theme: &'a mut Theme, name: &str) -> Option<&'a mut Color> {
+            match name {
+                $(stringify!($field) => Some(&mut theme.$field),)*
+                _ => None,
+            }
+        }
+    };
+}
+
+theme_color_fields!(
+    bg_color,
+    border_color,
+    title_color,
+    accent_color,
+    text_color,
+    success_color,
+    dim_color,
+    mode_view_color,
+    mode_chat_color,
+    mode_plan_color,
+    mode_bash_color,
+    mode_leader_color,
+    warning_color,
+    error_color,
+    code_bg_color,
+    cursor_color,
+    selection_bg_color,
+    selection_fg_color,
+    user_msg_color,
+    assistant_msg_color,
+    system_msg_color,
+    tool_msg_color,
+    info_color,
+    progress_color,
+    input_bg_color,
+    input_placeholder_color,
+    input_border_color,
+    user_msg_bg_color,
+    assistant_msg_bg_color,
+    system_msg_bg_color,
+    tool_msg_bg_color,
+    status_bar_bg_color,
+    scrollbar_bg_color,
+    scrollbar_fg_color,
+    scrollbar_hover_color,
+    logo_primary_color,
+    logo_secondary_color,
+    animation_color,
+    processing_color,
+    highlight_color,
+    bubble_color,
+    token_low_color,
+    token_medium_color,
+    token_high_color,
+    token_critical_color,
+    syntax_keyword_color,
+    syntax_function_color,
+    syntax_string_color,
+    syntax_number_color,
+    syntax_comment_color,
+    syntax_type_color,
+    syntax_variable_color,
+    syntax_operator_color,
+    syntax_punctuation_color,
+    diff_add_color,
+    diff_add_bg_color,
+    diff_remove_color,
+    diff_remove_bg_color,
+    diff_context_color,
+    line_number_color,
+    link_color,
+    running_color,
+);
+
+/// Field name for a theme file key (`bg` and `bg_color` both mean `bg_color`)
+pub fn field_name(key: &str) -> Option<&'static str> {
+    let key = key.trim().to_lowercase().replace('-', "_");
+    let full = if key.ends_with("_color") {
+        key
+    } else {
+        format!("{}_color", key)
+    };
+    COLOR_FIELDS.iter().copied().find(|f| *f == full)
+}
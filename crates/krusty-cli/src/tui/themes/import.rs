@@ -0,0 +1,418 @@
+//! Theme importers
+//!
+//! Converts base16 schemes and VS Code color themes into `ThemeFile`s, so
+//! they go through the same loading as hand-written theme files. Used when
+//! such files sit in `~/.krusty/themes` and by `/theme import`.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use anyhow::{anyhow, bail, Context, Result};
+use serde_json::Value;
+
+use super::user::ThemeFile;
+
+/// Read any supported theme file
+pub fn read_theme_file(path: &Path) -> Result<ThemeFile> {
+    let content =
+        std::fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+    let ext = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(str::to_lowercase)
+        .unwrap_or_default();
+    match ext.as_str() {
+        "toml" => Ok(toml::from_str(&content)?),
+        "yaml" | "yml" => from_base16(&content),
+        "json" => {
+            let value: Value = serde_json::from_str(&strip_jsonc(&content))?;
+            if is_vscode_theme(&value) {
+                from_vscode(&value)
+            } else {
+                Ok(serde_json::from_value(value)?)
+            }
+        }
+        _ => bail!("unsupported theme file type '.{}'", ext),
+    }
+}
+
+/// Theme colors for each base16 slot, following the base16 styling guide
+const BASE16_SLOTS: &[(&str, &[&str])] = &[
+    ("base00", &["bg"]),
+    ("base01", &["code_bg", "status_bar_bg", "input_bg"]),
+    ("base02", &["border", "selection_bg"]),
+    ("base03", &["dim", "syntax_comment", "line_number"]),
+    (
+        "base05",
+        &[
+            "text",
+            "selection_fg",
+            "syntax_operator",
+            "syntax_punctuation",
+        ],
+    ),
+    ("base08", &["error", "syntax_variable"]),
+    ("base09", &["syntax_number", "mode_bash"]),
+    ("base0A", &["warning", "syntax_type"]),
+    ("base0B", &["success", "syntax_string"]),
+    ("base0C", &["info"]),
+    ("base0D", &["title", "syntax_function", "link"]),
+    ("base0E", &["accent", "syntax_keyword", "cursor"]),
+];
+
+/// Convert a base16 scheme (YAML)
+///
+/// Reads both the classic layout (`base00:` … at the top level) and the
+/// newer one with a `palette:` map.
+pub fn from_base16(yaml: &str) -> Result<ThemeFile> {
+    let doc: serde_yaml::Value = serde_yaml::from_str(yaml)?;
+    let palette = doc.get("palette").unwrap_or(&doc);
+    let display_name = doc
+        .get("scheme")
+        .or_else(|| doc.get("name"))
+        .and_then(|v| v.as_str())
+        .map(str::to_string);
+
+    let mut colors = BTreeMap::new();
+    for (slot, fields) in BASE16_SLOTS {
+        let value = palette
+            .get(*slot)
+            .or_else(|| palette.get(slot.to_lowercase()))
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow!("base16 scheme is missing {}", slot))?;
+        let hex = format!("#{}", value.trim().trim_start_matches('#'));
+        for field in *fields {
+            colors.insert(field.to_string(), hex.clone());
+        }
+    }
+
+    Ok(ThemeFile {
+        display_name,
+        colors,
+        ..Default::default()
+    })
+}
+
+/// Whether a JSON document is a VS Code color theme rather than a theme file
+fn is_vscode_theme(value: &Value) -> bool {
+    value.get("tokenColors").is_some()
+        || value
+            .get("colors")
+            .and_then(|c| c.as_object())
+            .is_some_and(|c| c.keys().any(|k| k.contains('.')))
+}
+
+/// Workbench colors to try for each theme color, first match wins
+const VSCODE_COLORS: &[(&str, &[&str])] = &[
+    ("bg", &["editor.background"]),
+    ("text", &["editor.foreground", "foreground"]),
+    (
+        "border",
+        &[
+            "panel.border",
+            "editorGroup.border",
+            "sideBar.border",
+            "focusBorder",
+        ],
+    ),
+    (
+        "accent",
+        &[
+            "focusBorder",
+            "button.background",
+            "activityBarBadge.background",
+        ],
+    ),
+    (
+        "title",
+        &[
+            "textLink.foreground",
+            "editorLink.activeForeground",
+            "terminal.ansiBlue",
+        ],
+    ),
+    (
+        "success",
+        &[
+            "terminal.ansiGreen",
+            "gitDecoration.addedResourceForeground",
+        ],
+    ),
+    (
+        "dim",
+        &["editorLineNumber.foreground", "descriptionForeground"],
+    ),
+    (
+        "warning",
+        &["editorWarning.foreground", "terminal.ansiYellow"],
+    ),
+    (
+        "error",
+        &[
+            "editorError.foreground",
+            "errorForeground",
+            "terminal.ansiRed",
+        ],
+    ),
+    ("info", &["editorInfo.foreground", "terminal.ansiCyan"]),
+    (
+        "code_bg",
+        &["editorWidget.background", "sideBar.background"],
+    ),
+    ("cursor", &["editorCursor.foreground"]),
+    ("selection_bg", &["editor.selectionBackground"]),
+    ("input_bg", &["input.background"]),
+    ("status_bar_bg", &["statusBar.background"]),
+    ("link", &["textLink.foreground"]),
+    ("line_number", &["editorLineNumber.foreground"]),
+    (
+        "diff_add",
+        &[
+            "gitDecoration.addedResourceForeground",
+            "terminal.ansiGreen",
+        ],
+    ),
+    (
+        "diff_remove",
+        &[
+            "gitDecoration.deletedResourceForeground",
+            "terminal.ansiRed",
+        ],
+    ),
+];
+
+/// TextMate scopes to try for each syntax color, most specific first
+const VSCODE_SCOPES: &[(&str, &[&str])] = &[
+    ("syntax_comment", &["comment"]),
+    ("syntax_string", &["string"]),
+    ("syntax_number", &["constant.numeric", "constant"]),
+    ("syntax_operator", &["keyword.operator"]),
+    ("syntax_keyword", &["keyword.control", "keyword", "storage"]),
+    (
+        "syntax_function",
+        &["entity.name.function", "support.function"],
+    ),
+    (
+        "syntax_type",
+        &[
+            "entity.name.type",
+            "support.type",
+            "entity.name.class",
+            "storage.type",
+        ],
+    ),
+    ("syntax_variable", &["variable"]),
+    ("syntax_punctuation", &["punctuation"]),
+];
+
+/// Convert a VS Code color theme
+pub fn from_vscode(value: &Value) -> Result<ThemeFile> {
+    let workbench = value.get("colors").and_then(|c| c.as_object());
+    let mut colors = BTreeMap::new();
+
+    for (field, keys) in VSCODE_COLORS {
+        let color = keys.iter().find_map(|key| workbench?.get(*key)?.as_str());
+        if let Some(color) = color {
+            colors.insert(field.to_string(), color.to_string());
+        }
+    }
+
+    let rules = token_rules(value);
+    for (field, selectors) in VSCODE_SCOPES {
+        if let Some(color) = match_scope(&rules, selectors) {
+            colors.insert(field.to_string(), color);
+        }
+    }
+
+    if !colors.contains_key("bg") {
+        bail!("VS Code theme has no editor.background color");
+    }
+
+    Ok(ThemeFile {
+        display_name: value
+            .get("name")
+            .and_then(|v| v.as_str())
+            .map(str::to_string),
+        colors,
+        ..Default::default()
+    })
+}
+
+/// `(scopes, foreground)` of each `tokenColors` entry
+fn token_rules(value: &Value) -> Vec<(Vec<String>, String)> {
+    let Some(entries) = value.get("tokenColors").and_then(|t| t.as_array()) else {
+        return Vec::new();
+    };
+    entries
+        .iter()
+        .filter_map(|entry| {
+            let foreground = entry.get("settings")?.get("foreground")?.as_str()?;
+            let scopes: Vec<String> = match entry.get("scope")? {
+                Value::String(s) => s.split(',').map(|s| s.trim().to_string()).collect(),
+                Value::Array(a) => a
+                    .iter()
+                    .filter_map(|s| s.as_str().map(|s| s.trim().to_string()))
+                    .collect(),
+                _ => return None,
+            };
+            Some((scopes, foreground.to_string()))
+        })
+        .collect()
+}
+
+/// Color of the first rule covering one of `selectors`
+///
+/// An exact scope match beats a more specific one (`keyword` over
+/// `keyword.operator` when looking for keywords).
+fn match_scope(rules: &[(Vec<String>, String)], selectors: &[&str]) -> Option<String> {
+    for selector in selectors {
+        let prefix = format!("{}.", selector);
+        let exact = rules
+            .iter()
+            .find(|(scopes, _)| scopes.iter().any(|s| s == selector));
+        let nested = || {
+            rules
+                .iter()
+                .find(|(scopes, _)| scopes.iter().any(|s| s.starts_with(&prefix)))
+        };
+        if let Some((_, color)) = exact.or_else(nested) {
+            return Some(color.clone());
+        }
+    }
+    None
+}
+
+/// Drop `//` and `/* */` comments and trailing commas (VS Code themes are JSONC)
+fn strip_jsonc(input: &str) -> String {
+    let mut out = String::with_capacity(input.len());
+    let mut chars = input.chars().peekable();
+    let mut in_string = false;
+
+    while let Some(c) = chars.next() {
+        if in_string {
+            out.push(c);
+            match c {
+                '\\' => {
+                    if let Some(next) = chars.next() {
+                        out.push(next);
+                    }
+                }
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match (c, chars.peek()) {
+            ('"', _) => {
+                in_string = true;
+                out.push(c);
+            }
+            ('/', Some('/')) => {
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        out.push('\n');
+                        break;
+                    }
+                }
+            }
+            ('/', Some('*')) => {
+                chars.next();
+                let mut prev = ' ';
+                for c in chars.by_ref() {
+                    if prev == '*' && c == '/' {
+                        break;
+                    }
+                    prev = c;
+                }
+            }
+            (',', _) => {
+                // Keep the comma only if something other than a closer follows
+                let rest: String = chars.clone().collect();
+                let next = rest.trim_start().chars().next();
+                if !matches!(next, Some('}') | Some(']')) {
+                    out.push(c);
+                }
+            }
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_base16_scheme() {
+        let yaml = r#"
+scheme: "Ocean"
+author: "someone"
+base00: "2b303b"
+base01: "343d46"
+base02: "4f5b66"
+base03: "65737e"
+base04: "a7adba"
+base05: "c0c5ce"
+base06: "dfe1e8"
+base07: "eff1f5"
+base08: "bf616a"
+base09: "d08770"
+base0A: "ebcb8b"
+base0B: "a3be8c"
+base0C: "96b5b4"
+base0D: "8fa1b3"
+base0E: "b48ead"
+base0F: "ab7967"
+"#;
+        let file = from_base16(yaml).unwrap();
+        assert_eq!(file.display_name.as_deref(), Some("Ocean"));
+        assert_eq!(file.colors["bg"], "#2b303b");
+        assert_eq!(file.colors["syntax_keyword"], "#b48ead");
+        assert_eq!(file.colors["error"], "#bf616a");
+
+        assert!(from_base16("scheme: Broken\nbase00: \"000000\"\n").is_err());
+    }
+
+    #[test]
+    fn test_vscode_theme() {
+        let jsonc = r##"{
+            // Comments and trailing commas are allowed
+            "name": "Acme Night",
+            "type": "dark",
+            "colors": {
+                "editor.background": "#101418",
+                "editor.foreground": "#d0d0d0",
+                "focusBorder": "#ff7a00", /* accent */
+                "textLink.foreground": "#4fc1ff",
+            },
+            "tokenColors": [
+                { "scope": "keyword.operator", "settings": { "foreground": "#aaaaaa" } },
+                { "scope": ["keyword", "storage.modifier"], "settings": { "foreground": "#c792ea" } },
+                { "scope": "string.quoted.double", "settings": { "foreground": "#c3e88d" } },
+                { "scope": "comment", "settings": { "fontStyle": "italic" } },
+            ],
+        }"##;
+        let value: Value = serde_json::from_str(&strip_jsonc(jsonc)).unwrap();
+        assert!(is_vscode_theme(&value));
+
+        let file = from_vscode(&value).unwrap();
+        assert_eq!(file.display_name.as_deref(), Some("Acme Night"));
+        assert_eq!(file.colors["bg"], "#101418");
+        assert_eq!(file.colors["accent"], "#ff7a00");
+        assert_eq!(file.colors["link"], "#4fc1ff");
+        assert_eq!(file.colors["syntax_keyword"], "#c792ea");
+        assert_eq!(file.colors["syntax_operator"], "#aaaaaa");
+        assert_eq!(file.colors["syntax_string"], "#c3e88d");
+        // Rules without a foreground are skipped
+        assert!(!file.colors.contains_key("syntax_comment"));
+    }
+
+    #[test]
+    fn test_strip_jsonc_keeps_strings() {
+        let stripped = strip_jsonc(r#"{"url": "https://x.dev/a,b", "a": [1, 2,],}"#);
+        let value: Value = serde_json::from_str(&stripped).unwrap();
+        assert_eq!(value["url"], "https://x.dev/a,b");
+        assert_eq!(value["a"], serde_json::json!([1, 2]));
+    }
+}
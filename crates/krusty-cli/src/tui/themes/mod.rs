@@ -6,13 +6,24 @@ use ratatui::style::Color;
 
 pub mod base;
 pub mod definitions;
+mod fields;
+pub mod import;
 mod registry;
+pub mod user;
 
 use once_cell::sync::Lazy;
+use parking_lot::RwLock;
 pub use registry::ThemeRegistry;
 
-/// Global theme registry with all built-in themes
-pub static THEME_REGISTRY: Lazy<ThemeRegistry> = Lazy::new(ThemeRegistry::new);
+/// Global theme registry with the built-in themes and those in
+/// `~/.krusty/themes` (reloaded when the files change)
+pub static THEME_REGISTRY: Lazy<RwLock<ThemeRegistry>> = Lazy::new(|| {
+    let mut registry = ThemeRegistry::new();
+    for error in registry.load_user_themes(&crate::paths::themes_dir()) {
+        tracing::warn!("{}", error);
+    }
+    RwLock::new(registry)
+});
 
 /// A complete theme definition
 #[derive(Debug, Clone)]
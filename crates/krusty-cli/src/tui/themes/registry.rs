@@ -1,12 +1,17 @@
 //! Theme registry for discovering and accessing themes
 
+use super::import::read_theme_file;
+use super::user::build_theme;
 use super::Theme;
 use std::collections::HashMap;
+use std::path::Path;
 
 /// Registry of all available themes
 pub struct ThemeRegistry {
     themes: HashMap<String, Theme>,
     ordered_names: Vec<String>,
+    /// Names loaded from the themes directory, after the built-in ones
+    user_names: Vec<String>,
 }
 
 impl ThemeRegistry {
@@ -15,6 +20,7 @@ impl ThemeRegistry {
         let mut registry = Self {
             themes: HashMap::new(),
             ordered_names: Vec::new(),
+            user_names: Vec::new(),
         };
 
         // Register all themes from definitions module
@@ -69,6 +75,51 @@ impl ThemeRegistry {
         self.themes.insert(theme.name.clone(), theme);
     }
 
+    /// Load the theme files in `dir`, replacing previously loaded ones
+    ///
+    /// Files that fail to load are skipped; the returned messages say why.
+    /// Files may use earlier files' themes as their `base`.
+    pub fn load_user_themes(&mut self, dir: &Path) -> Vec<String> {
+        for name in self.user_names.drain(..) {
+            self.themes.remove(&name);
+            self.ordered_names.retain(|n| *n != name);
+        }
+
+        let mut errors = Vec::new();
+        for path in super::user::theme_files(dir) {
+            let stem = path
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .unwrap_or_default()
+                .to_string();
+            let theme = read_theme_file(&path)
+                .and_then(|file| build_theme(&file, &stem, &|name| self.get(name).cloned()));
+            match theme {
+                Ok(theme) if self.themes.contains_key(&theme.name) => errors.push(format!(
+                    "Theme {}: name '{}' is already taken",
+                    path.display(),
+                    theme.name
+                )),
+                Ok(theme) => {
+                    self.user_names.push(theme.name.clone());
+                    self.register(theme);
+                }
+                Err(e) => errors.push(format!("Theme {}: {:#}", path.display(), e)),
+            }
+        }
+        errors
+    }
+
+    /// Whether a theme came from the themes directory
+    pub fn is_user_theme(&self, name: &str) -> bool {
+        self.user_names.iter().any(|n| n == name)
+    }
+
+    /// Get a theme by name
+    pub fn get(&self, name: &str) -> Option<&Theme> {
+        self.themes.get(name)
+    }
+
     /// Get a theme by name, or the default theme
     pub fn get_or_default(&self, name: &str) -> &Theme {
         self.themes
@@ -96,3 +147,35 @@ impl Default for ThemeRegistry {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_user_themes() {
+        let dir = tempfile::tempdir().unwrap();
+        let builtins = ThemeRegistry::new().count();
+        std::fs::write(
+            dir.path().join("acme.toml"),
+            "display_name = \"Acme\"\nbase = \"nord\"\n[colors]\naccent = \"#ff7a00\"\n",
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("broken.toml"), "[colors]\nbg = \"nope\"\n").unwrap();
+        std::fs::write(dir.path().join("nord.toml"), "[colors]\nbg = \"#000000\"\n").unwrap();
+
+        let mut registry = ThemeRegistry::new();
+        let errors = registry.load_user_themes(dir.path());
+        assert_eq!(errors.len(), 2, "{:?}", errors);
+        assert!(registry.is_user_theme("acme"));
+        assert!(!registry.is_user_theme("nord"));
+        assert_eq!(registry.count(), builtins + 1);
+        assert_eq!(registry.list().last().unwrap().0, "acme");
+
+        // Reloading replaces what was loaded before
+        std::fs::remove_file(dir.path().join("acme.toml")).unwrap();
+        registry.load_user_themes(dir.path());
+        assert!(registry.get("acme").is_none());
+        assert_eq!(registry.count(), builtins);
+    }
+}
@@ -0,0 +1,273 @@
+//! User themes from `~/.krusty/themes`
+//!
+//! A theme file names any `Theme` colors it wants to set; the rest come
+//! from its `base` theme, or are derived from the colors it does set the
+//! same way `ThemeBuilder` fills in built-in themes:
+//!
+//! ```toml
+//! display_name = "Acme Dark"
+//! base = "tokyo_night"   # optional
+//!
+//! [colors]
+//! bg = "#101418"
+//! accent = "#ff7a00"
+//! syntax_keyword = "#c792ea"
+//! ```
+//!
+//! base16 schemes (`.yaml`) and VS Code color themes (`.json`) in the same
+//! directory are converted on load (see `import`).
+
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::SystemTime;
+
+use anyhow::{anyhow, Context, Result};
+use ratatui::style::Color;
+use serde::{Deserialize, Serialize};
+
+use super::base::ThemeBuilder;
+use super::fields::{color_field, color_field_mut, field_name};
+use super::Theme;
+
+/// Theme the core colors come from when a file sets neither them nor a base
+const FALLBACK_THEME: &str = "krusty";
+
+/// File extensions read from the themes directory
+pub const THEME_EXTENSIONS: &[&str] = &["toml", "json", "yaml", "yml"];
+
+/// Contents of a theme file
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ThemeFile {
+    /// Theme ID (defaults to the file name)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
+    /// Theme to take unset colors from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base: Option<String>,
+    /// Colors by `Theme` field name, with or without the `_color` suffix
+    #[serde(default)]
+    pub colors: BTreeMap<String, String>,
+}
+
+/// Colors `ThemeBuilder` expects every theme to set, in the order they are
+/// filled in when a file leaves them out. `None` takes the fallback theme's.
+const PRIMARY_FALLBACKS: &[(&str, Option<&str>)] = &[
+    ("bg_color", None),
+    ("border_color", None),
+    ("title_color", None),
+    ("accent_color", None),
+    ("text_color", None),
+    ("success_color", None),
+    ("dim_color", None),
+    ("warning_color", None),
+    ("error_color", None),
+    ("code_bg_color", Some("bg_color")),
+    ("mode_view_color", Some("accent_color")),
+    ("mode_chat_color", Some("title_color")),
+    ("mode_plan_color", Some("accent_color")),
+    ("mode_bash_color", Some("warning_color")),
+    ("mode_leader_color", Some("accent_color")),
+    ("cursor_color", Some("accent_color")),
+    ("selection_bg_color", Some("border_color")),
+    ("selection_fg_color", Some("text_color")),
+    ("user_msg_color", Some("success_color")),
+    ("assistant_msg_color", Some("accent_color")),
+    ("system_msg_color", Some("warning_color")),
+    ("tool_msg_color", Some("title_color")),
+    ("info_color", Some("title_color")),
+    ("progress_color", Some("accent_color")),
+];
+
+/// Parse a theme color: `#rrggbb`, `#rgb`, `#rrggbbaa` (alpha ignored),
+/// a color name (`red`, `lightblue`, `reset`) or a terminal color index
+pub fn parse_color(value: &str) -> Result<Color> {
+    let value = value.trim();
+    if let Some(hex) = value.strip_prefix('#') {
+        let hex = match hex.len() {
+            3 => hex.chars().flat_map(|c| [c, c]).collect(),
+            8 => hex[..6].to_string(),
+            _ => hex.to_string(),
+        };
+        if hex.len() == 6 {
+            if let Ok(rgb) = u32::from_str_radix(&hex, 16) {
+                return Ok(Color::Rgb((rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8));
+            }
+        }
+    }
+    Color::from_str(value).map_err(|_| anyhow!("invalid color '{}'", value))
+}
+
+/// Theme ID from a display name or file name
+pub fn theme_slug(name: &str) -> String {
+    let slug: String = name
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    slug.split('_')
+        .filter(|s| !s.is_empty())
+        .collect::<Vec<_>>()
+        .join("_")
+}
+
+/// Build a theme from a file's contents
+///
+/// `lookup` finds base themes by name (built-in and already loaded ones).
+pub fn build_theme(
+    file: &ThemeFile,
+    default_name: &str,
+    lookup: &dyn Fn(&str) -> Option<Theme>,
+) -> Result<Theme> {
+    let name = theme_slug(file.name.as_deref().unwrap_or(default_name));
+    if name.is_empty() {
+        return Err(anyhow!("theme has no name"));
+    }
+    let display_name = file.display_name.clone().unwrap_or_else(|| name.clone());
+
+    let mut colors = Vec::with_capacity(file.colors.len());
+    for (key, value) in &file.colors {
+        let field = field_name(key).ok_or_else(|| anyhow!("unknown color '{}'", key))?;
+        let color = parse_color(value).with_context(|| format!("color '{}'", key))?;
+        colors.push((field, color));
+    }
+
+    if let Some(base) = &file.base {
+        let mut theme = lookup(base).ok_or_else(|| anyhow!("unknown base theme '{}'", base))?;
+        theme.name = name;
+        theme.display_name = display_name;
+        for (field, color) in colors {
+            if let Some(slot) = color_field_mut(&mut theme, field) {
+                *slot = color;
+            }
+        }
+        return Ok(theme);
+    }
+
+    let fallback = lookup(FALLBACK_THEME).ok_or_else(|| anyhow!("fallback theme missing"))?;
+    let set: HashSet<&str> = colors.iter().map(|(field, _)| *field).collect();
+    Ok(ThemeBuilder::new(name, display_name)
+        .extended_colors(|theme| {
+            for (field, color) in &colors {
+                if let Some(slot) = color_field_mut(theme, field) {
+                    *slot = *color;
+                }
+            }
+            for (field, source) in PRIMARY_FALLBACKS {
+                if set.contains(field) {
+                    continue;
+                }
+                let color = match source {
+                    Some(source) => color_field(theme, source),
+                    None => color_field(&fallback, field),
+                };
+                if let (Some(color), Some(slot)) = (color, color_field_mut(theme, field)) {
+                    *slot = color;
+                }
+            }
+        })
+        // Everything else is derived from the primary colors
+        .build())
+}
+
+/// Theme files in `dir`, sorted by name
+pub fn theme_files(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut files: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| {
+            p.is_file()
+                && p.extension()
+                    .and_then(|e| e.to_str())
+                    .is_some_and(|e| THEME_EXTENSIONS.contains(&e.to_lowercase().as_str()))
+        })
+        .collect();
+    files.sort();
+    files
+}
+
+/// Modification times of the theme files, to notice edits
+pub fn themes_stamp(dir: &Path) -> Vec<(PathBuf, Option<SystemTime>)> {
+    theme_files(dir)
+        .into_iter()
+        .map(|path| {
+            let modified = std::fs::metadata(&path).and_then(|m| m.modified()).ok();
+            (path, modified)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tui::themes::ThemeRegistry;
+
+    fn lookup(name: &str) -> Option<Theme> {
+        ThemeRegistry::new().get(name).cloned()
+    }
+
+    #[test]
+    fn test_parse_color_forms() {
+        assert_eq!(parse_color("#ff8000").unwrap(), Color::Rgb(255, 128, 0));
+        assert_eq!(parse_color("#f80").unwrap(), Color::Rgb(255, 136, 0));
+        assert_eq!(parse_color("#ff800080").unwrap(), Color::Rgb(255, 128, 0));
+        assert_eq!(parse_color("lightblue").unwrap(), Color::LightBlue);
+        assert_eq!(parse_color("reset").unwrap(), Color::Reset);
+        assert!(parse_color("#zzzzzz").is_err());
+    }
+
+    #[test]
+    fn test_missing_colors_are_derived() {
+        let file: ThemeFile = toml::from_str(
+            r##"
+            display_name = "Acme"
+            [colors]
+            bg = "#101418"
+            accent_color = "#ff7a00"
+            "##,
+        )
+        .unwrap();
+        let theme = build_theme(&file, "acme", &lookup).unwrap();
+        let krusty = lookup("krusty").unwrap();
+
+        assert_eq!(theme.name, "acme");
+        assert_eq!(theme.display_name, "Acme");
+        assert_eq!(theme.bg_color, Color::Rgb(16, 20, 24));
+        // Unset core colors come from the fallback theme
+        assert_eq!(theme.text_color, krusty.text_color);
+        // Primary colors follow the ones that were set
+        assert_eq!(theme.code_bg_color, theme.bg_color);
+        assert_eq!(theme.cursor_color, Color::Rgb(255, 122, 0));
+        // Extended colors are derived by the builder
+        assert_eq!(theme.link_color, theme.accent_color);
+    }
+
+    #[test]
+    fn test_base_theme_and_errors() {
+        let file = ThemeFile {
+            base: Some("dracula".to_string()),
+            colors: BTreeMap::from([("error".to_string(), "#ff0000".to_string())]),
+            ..Default::default()
+        };
+        let theme = build_theme(&file, "My Dracula", &lookup).unwrap();
+        let dracula = lookup("dracula").unwrap();
+        assert_eq!(theme.name, "my_dracula");
+        assert_eq!(theme.error_color, Color::Rgb(255, 0, 0));
+        assert_eq!(theme.bg_color, dracula.bg_color);
+
+        let unknown = ThemeFile {
+            colors: BTreeMap::from([("sparkle".to_string(), "#ff0000".to_string())]),
+            ..Default::default()
+        };
+        assert!(build_theme(&unknown, "x", &lookup).is_err());
+        let bad_base = ThemeFile {
+            base: Some("nope".to_string()),
+            ..Default::default()
+        };
+        assert!(build_theme(&bad_base, "x", &lookup).is_err());
+    }
+}
@@ -0,0 +1,26 @@
+//! Sub-agent steering state
+//!
+//! While a note or a restart prompt for a running sub-agent is being
+//! written, the input box sends to that agent instead of the conversation.
+
+/// What submitting the input does for the steered agent
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SteerMode {
+    /// Stop the agent and run it again with the input as its prompt
+    Restart,
+    /// Add the input to the agent's conversation before its next turn
+    Note,
+}
+
+/// Input box redirected to one running sub-agent
+#[derive(Debug, Clone)]
+pub struct AgentSteer {
+    /// Tool call (or plan run) the agent belongs to
+    pub run_id: String,
+    pub task_id: String,
+    /// Agent display name
+    pub name: String,
+    pub mode: SteerMode,
+    /// Input that was there before, restored when steering ends
+    pub draft: String,
+}
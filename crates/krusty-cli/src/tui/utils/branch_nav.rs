@@ -0,0 +1,127 @@
+//! Conversation branch navigation
+//!
+//! Alt+↑ picks an earlier user message. From there ←/→ switch between the
+//! sibling branches at that message, Enter takes it back into the input and
+//! `m` resends it to another model. Submitting an edit or a resend forks the
+//! session before the message (see `handlers/branches.rs`).
+
+/// A user message in the chat
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UserMessage {
+    /// Index in the chat display
+    pub display_idx: usize,
+    /// Index in the conversation, which is also its stored message index
+    pub conv_idx: usize,
+}
+
+/// A message with sibling versions, shown as `‹ current/total ›`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BranchLabel {
+    pub display_idx: usize,
+    /// 1-based position of the shown version
+    pub current: usize,
+    pub total: usize,
+    /// Model the shown version's branch was started with
+    pub model: Option<String>,
+}
+
+#[derive(Debug, Default)]
+pub struct BranchNav {
+    /// User messages of the loaded conversation, oldest first
+    pub messages: Vec<UserMessage>,
+    /// Messages with more than one version
+    pub labels: Vec<BranchLabel>,
+    /// Position in `messages` of the message picked with Alt+↑
+    pub selected: Option<usize>,
+    /// Conversation index of the message being rewritten in the input
+    pub editing: Option<usize>,
+    /// Conversation index waiting for a model pick to be resent
+    pub resend: Option<usize>,
+}
+
+impl BranchNav {
+    /// Forget the loaded conversation's messages, selection and edit
+    pub fn reset(&mut self) {
+        self.messages.clear();
+        self.labels.clear();
+        self.selected = None;
+        self.editing = None;
+    }
+
+    /// Pick the most recent user message. False if there is none
+    pub fn select_last(&mut self) -> bool {
+        self.selected = self.messages.len().checked_sub(1);
+        self.selected.is_some()
+    }
+
+    pub fn select_prev(&mut self) {
+        if let Some(i) = self.selected {
+            self.selected = Some(i.saturating_sub(1));
+        }
+    }
+
+    pub fn select_next(&mut self) {
+        if let Some(i) = self.selected {
+            self.selected = Some((i + 1).min(self.messages.len().saturating_sub(1)));
+        }
+    }
+
+    /// Pick the message at conversation index `conv_idx`, if it exists
+    pub fn select_conversation_index(&mut self, conv_idx: usize) -> bool {
+        self.selected = self.messages.iter().position(|m| m.conv_idx == conv_idx);
+        self.selected.is_some()
+    }
+
+    pub fn selected_message(&self) -> Option<UserMessage> {
+        self.messages.get(self.selected?).copied()
+    }
+
+    pub fn label_at(&self, display_idx: usize) -> Option<&BranchLabel> {
+        self.labels.iter().find(|l| l.display_idx == display_idx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn nav(conv_indices: &[usize]) -> BranchNav {
+        BranchNav {
+            messages: conv_indices
+                .iter()
+                .enumerate()
+                .map(|(i, &conv_idx)| UserMessage {
+                    display_idx: i * 3,
+                    conv_idx,
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_selection_stays_in_bounds() {
+        let mut nav = nav(&[0, 2, 6]);
+        nav.select_next();
+        assert_eq!(nav.selected, None);
+
+        assert!(nav.select_last());
+        nav.select_next();
+        assert_eq!(nav.selected_message().unwrap().conv_idx, 6);
+        nav.select_prev();
+        nav.select_prev();
+        nav.select_prev();
+        assert_eq!(nav.selected_message().unwrap().conv_idx, 0);
+
+        assert!(!BranchNav::default().select_last());
+    }
+
+    #[test]
+    fn test_reselect_after_switching_branch() {
+        let mut nav = nav(&[0, 2, 6]);
+        assert!(nav.select_conversation_index(2));
+        assert_eq!(nav.selected_message().unwrap().display_idx, 3);
+        assert!(!nav.select_conversation_index(4));
+        assert_eq!(nav.selected, None);
+    }
+}
@@ -2,13 +2,36 @@
 //!
 //! Groups all async channel receivers used by the App for background tasks.
 
-use tokio::sync::{mpsc, oneshot};
+use tokio::sync::{broadcast, mpsc, oneshot};
 
 use crate::agent::subagent::AgentProgress;
-use crate::agent::SummarizationResult;
+use crate::agent::{CompactionReport, Compactor, SummarizationResult};
 use crate::ai::models::ModelMetadata;
-use crate::ai::types::Content;
+use crate::ai::types::{Content, ModelMessage};
+use crate::plan::{CheckOutcome, PlanRunEvent};
 use crate::tools::ToolOutputChunk;
+use krusty_core::commands::CommandDefinition;
+
+use super::McpUserRequest;
+
+/// Acceptance check results for a task_complete call
+pub struct TaskCheckResult {
+    pub tool_use_id: String,
+    pub task_id: String,
+    /// Result summary the AI gave for the task
+    pub result: String,
+    /// Check outcomes, or `None` if the checks were interrupted
+    pub outcomes: Option<Vec<CheckOutcome>>,
+}
+
+/// Custom slash command expanded in the background, ready to send
+pub struct CustomCommandUpdate {
+    pub command: CommandDefinition,
+    /// What the user typed (`/review 123`), shown in the chat
+    pub invocation: String,
+    /// Expanded prompt, or why expansion failed
+    pub result: Result<String, String>,
+}
 
 /// AI-generated title update
 pub struct TitleUpdate {
@@ -32,6 +55,16 @@ pub struct InitExplorationResult {
     pub error: Option<String>,
 }
 
+/// In-place compaction of the conversation, done before a request is sent
+pub struct CompactionUpdate {
+    pub session_id: Option<String>,
+    /// Number of leading conversation messages the compacted ones stand in for
+    pub replaces: usize,
+    /// Handed back so failed summaries stay backed off
+    pub compactor: Compactor,
+    pub result: Result<Option<(Vec<ModelMessage>, CompactionReport)>, String>,
+}
+
 /// AI-generated summarization result for pinch
 pub struct SummarizationUpdate {
     pub result: Result<SummarizationResult, String>,
@@ -43,6 +76,23 @@ pub struct McpStatusUpdate {
     pub message: String,
 }
 
+/// Docs provider update from background suggest/index tasks
+pub enum DocsStatusUpdate {
+    /// Packages a provider can index
+    Suggestions {
+        provider: String,
+        packages: Vec<String>,
+    },
+    /// A package finished indexing
+    Indexed {
+        provider: String,
+        package: String,
+        entries: usize,
+    },
+    /// Suggesting or indexing failed
+    Failed { message: String },
+}
+
 /// OAuth authentication status update from background tasks
 pub struct OAuthStatusUpdate {
     /// Provider being authenticated
@@ -68,18 +118,32 @@ pub struct DeviceCodeInfo {
 pub struct AsyncChannels {
     /// MCP status updates from background connection tasks
     pub mcp_status: Option<mpsc::UnboundedReceiver<McpStatusUpdate>>,
+    /// MCP notifications, crashes and restarts from the manager
+    pub mcp_events: Option<broadcast::Receiver<krusty_core::mcp::McpEvent>>,
+    /// Sampling/elicitation requests from MCP servers awaiting the user
+    pub mcp_requests: Option<mpsc::UnboundedReceiver<McpUserRequest>>,
     /// Streaming bash output receiver
     pub bash_output: Option<mpsc::UnboundedReceiver<ToolOutputChunk>>,
     /// Pending tool execution results receiver
     pub tool_results: Option<oneshot::Receiver<Vec<Content>>>,
+    /// Custom slash command expansion receiver
+    pub custom_command: Option<oneshot::Receiver<CustomCommandUpdate>>,
     /// AI-generated title update receiver
     pub title_update: Option<oneshot::Receiver<TitleUpdate>>,
+    /// Conversation compacted in place before the current request
+    pub compaction: Option<oneshot::Receiver<CompactionUpdate>>,
     /// AI-generated summarization result for pinch
     pub summarization: Option<oneshot::Receiver<SummarizationUpdate>>,
     /// Explore tool sub-agent progress updates
     pub explore_progress: Option<mpsc::UnboundedReceiver<AgentProgress>>,
     /// Build tool builder agent progress updates
     pub build_progress: Option<mpsc::UnboundedReceiver<AgentProgress>>,
+    /// /plan run task events
+    pub plan_run: Option<mpsc::UnboundedReceiver<PlanRunEvent>>,
+    /// /plan run builder progress updates
+    pub plan_run_progress: Option<mpsc::UnboundedReceiver<AgentProgress>>,
+    /// Acceptance check results for task_complete calls
+    pub task_checks: Option<mpsc::UnboundedReceiver<TaskCheckResult>>,
     /// OpenRouter model fetch result receiver
     pub openrouter_models: Option<oneshot::Receiver<Result<Vec<ModelMetadata>, String>>>,
     /// /init codebase exploration result receiver
@@ -88,6 +152,8 @@ pub struct AsyncChannels {
     pub init_progress: Option<mpsc::UnboundedReceiver<AgentProgress>>,
     /// Auto-updater status updates
     pub update_status: Option<mpsc::UnboundedReceiver<krusty_core::updater::UpdateStatus>>,
+    /// Docs provider suggest/index updates
+    pub docs_status: Option<mpsc::UnboundedReceiver<DocsStatusUpdate>>,
     /// OAuth authentication status updates
     pub oauth_status: Option<mpsc::UnboundedReceiver<OAuthStatusUpdate>>,
 }
@@ -0,0 +1,129 @@
+//! Diff hunks for the change review
+//!
+//! Splits the difference between a file's original and current content into
+//! unified-diff hunks, and reverts one hunk at a time by putting its original
+//! lines back while leaving every other change in place.
+
+use similar::{ChangeTag, DiffOp, TextDiff};
+
+/// Unchanged lines shown around each change
+const CONTEXT_RADIUS: usize = 3;
+
+/// A line of a hunk
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HunkLine {
+    pub tag: ChangeTag,
+    /// 1-based line number in the current content (original for removals)
+    pub line_num: usize,
+    pub content: String,
+}
+
+/// A group of nearby changes with their context
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hunk {
+    /// `@@ -a,b +c,d @@`
+    pub header: String,
+    pub lines: Vec<HunkLine>,
+}
+
+impl Hunk {
+    /// Number of added and removed lines
+    pub fn counts(&self) -> (usize, usize) {
+        self.lines
+            .iter()
+            .fold((0, 0), |(added, removed), line| match line.tag {
+                ChangeTag::Insert => (added + 1, removed),
+                ChangeTag::Delete => (added, removed + 1),
+                ChangeTag::Equal => (added, removed),
+            })
+    }
+}
+
+/// Hunks turning `old` into `new`
+pub fn diff_hunks(old: &str, new: &str) -> Vec<Hunk> {
+    let diff = TextDiff::from_lines(old, new);
+    let mut unified = diff.unified_diff();
+    unified
+        .context_radius(CONTEXT_RADIUS)
+        .iter_hunks()
+        .map(|hunk| Hunk {
+            header: hunk.header().to_string(),
+            lines: hunk
+                .iter_changes()
+                .map(|change| HunkLine {
+                    tag: change.tag(),
+                    line_num: change.new_index().or(change.old_index()).unwrap_or(0) + 1,
+                    content: change.value().trim_end_matches(['\n', '\r']).to_string(),
+                })
+                .collect(),
+        })
+        .collect()
+}
+
+/// `new` with hunk `index` of the diff from `old` undone
+pub fn revert_hunk(old: &str, new: &str, index: usize) -> String {
+    let diff = TextDiff::from_lines(old, new);
+    let reverted: Vec<DiffOp> = diff
+        .grouped_ops(CONTEXT_RADIUS)
+        .into_iter()
+        .nth(index)
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|op| !matches!(op, DiffOp::Equal { .. }))
+        .collect();
+    let old_lines = diff.old_slices();
+    let new_lines = diff.new_slices();
+
+    let mut out = String::with_capacity(new.len());
+    for op in diff.ops() {
+        let lines = if reverted.contains(op) {
+            &old_lines[op.old_range()]
+        } else {
+            &new_lines[op.new_range()]
+        };
+        lines.iter().for_each(|line| out.push_str(line));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn numbered(lines: std::ops::Range<usize>) -> String {
+        lines.map(|i| format!("line {}\n", i)).collect()
+    }
+
+    #[test]
+    fn test_distant_changes_make_separate_hunks() {
+        let old = numbered(1..30);
+        let new = old
+            .replace("line 3\n", "line three\n")
+            .replace("line 25\n", "");
+        let hunks = diff_hunks(&old, &new);
+        assert_eq!(hunks.len(), 2);
+        assert_eq!(hunks[0].header, "@@ -1,6 +1,6 @@");
+        assert_eq!(hunks[0].counts(), (1, 1));
+        assert_eq!(hunks[1].counts(), (0, 1));
+        assert!(diff_hunks(&old, &old).is_empty());
+    }
+
+    #[test]
+    fn test_revert_one_hunk() {
+        let old = numbered(1..30);
+        let new = old
+            .replace("line 3\n", "line three\n")
+            .replace("line 25\n", "");
+
+        let first_reverted = revert_hunk(&old, &new, 0);
+        assert_eq!(first_reverted, old.replace("line 25\n", ""));
+        let both_reverted = revert_hunk(&old, &first_reverted, 0);
+        assert_eq!(both_reverted, old);
+    }
+
+    #[test]
+    fn test_revert_new_file() {
+        assert_eq!(revert_hunk("", "fn main() {}\n", 0), "");
+        assert_eq!(revert_hunk("a\n", "a\n", 0), "a\n");
+    }
+}
@@ -0,0 +1,79 @@
+//! MCP client delegate
+//!
+//! Bridges sampling and elicitation requests from MCP servers to the UI.
+//! Requests are queued on a channel; the App shows them in the decision
+//! prompt and answers through the enclosed oneshot.
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use tokio::sync::{mpsc, oneshot};
+
+use krusty_core::mcp::{
+    CreateMessageParams, CreateMessageResult, ElicitAction, ElicitParams, ElicitResult,
+    McpClientDelegate,
+};
+
+/// A server request waiting for the user
+pub enum McpUserRequest {
+    /// Approve (and run) an LLM completion
+    Sampling {
+        server: String,
+        params: CreateMessageParams,
+        respond: oneshot::Sender<Result<CreateMessageResult, String>>,
+    },
+    /// Fill in a form
+    Elicitation {
+        server: String,
+        params: ElicitParams,
+        respond: oneshot::Sender<ElicitResult>,
+    },
+}
+
+/// Delegate that forwards server requests to the App
+pub struct McpPromptDelegate {
+    tx: mpsc::UnboundedSender<McpUserRequest>,
+}
+
+impl McpPromptDelegate {
+    pub fn new(tx: mpsc::UnboundedSender<McpUserRequest>) -> Self {
+        Self { tx }
+    }
+}
+
+#[async_trait]
+impl McpClientDelegate for McpPromptDelegate {
+    async fn create_message(
+        &self,
+        server: &str,
+        params: CreateMessageParams,
+    ) -> Result<CreateMessageResult> {
+        let (respond, rx) = oneshot::channel();
+        self.tx
+            .send(McpUserRequest::Sampling {
+                server: server.to_string(),
+                params,
+                respond,
+            })
+            .map_err(|_| anyhow!("UI is not running"))?;
+
+        rx.await
+            .map_err(|_| anyhow!("Sampling request was dismissed"))?
+            .map_err(|e| anyhow!(e))
+    }
+
+    async fn elicit(&self, server: &str, params: ElicitParams) -> Result<ElicitResult> {
+        let (respond, rx) = oneshot::channel();
+        self.tx
+            .send(McpUserRequest::Elicitation {
+                server: server.to_string(),
+                params,
+                respond,
+            })
+            .map_err(|_| anyhow!("UI is not running"))?;
+
+        Ok(rx.await.unwrap_or(ElicitResult {
+            action: ElicitAction::Cancel,
+            content: None,
+        }))
+    }
+}
@@ -2,15 +2,28 @@
 //!
 //! Common helper functions and types used throughout the TUI.
 
+mod agent_steer;
+mod branch_nav;
 mod channels;
+mod hunks;
+mod mcp_delegate;
+mod prompt_queue;
 mod syntax;
 mod text;
 mod title;
+mod worktree;
 
+pub use agent_steer::{AgentSteer, SteerMode};
+pub use branch_nav::{BranchLabel, BranchNav, UserMessage};
 pub use channels::{
-    AsyncChannels, DeviceCodeInfo, InitExplorationResult, McpStatusUpdate, OAuthStatusUpdate,
-    SummarizationUpdate, TitleUpdate,
+    AsyncChannels, CompactionUpdate, CustomCommandUpdate, DeviceCodeInfo, DocsStatusUpdate,
+    InitExplorationResult, McpStatusUpdate, OAuthStatusUpdate, SummarizationUpdate,
+    TaskCheckResult, TitleUpdate,
 };
+pub use hunks::{diff_hunks, revert_hunk, Hunk};
+pub use mcp_delegate::{McpPromptDelegate, McpUserRequest};
+pub use prompt_queue::{Delivery, PromptQueue};
 pub use syntax::highlight_code;
 pub use text::{count_wrapped_lines, truncate_ellipsis, wrap_line, wrap_text};
 pub use title::{TitleAction, TitleEditor};
+pub use worktree::AppWorktreeDelegate;
@@ -0,0 +1,204 @@
+//! Prompts queued while the agent is working
+//!
+//! Prompts submitted mid-turn wait here as chips above the input. Each is
+//! either injected with the next batch of tool results or sent as the next
+//! user turn, and can be edited or removed until then.
+
+/// When a queued prompt reaches the model
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Delivery {
+    /// Sent as its own user turn once the current one ends
+    #[default]
+    NextTurn,
+    /// Added to the next tool results, so the agent sees it mid-turn
+    Inject,
+}
+
+impl Delivery {
+    pub fn toggle(self) -> Self {
+        match self {
+            Delivery::NextTurn => Delivery::Inject,
+            Delivery::Inject => Delivery::NextTurn,
+        }
+    }
+
+    /// Name used on chips and by `/queue`
+    pub fn name(self) -> &'static str {
+        match self {
+            Delivery::NextTurn => "next",
+            Delivery::Inject => "inject",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        [Delivery::NextTurn, Delivery::Inject]
+            .into_iter()
+            .find(|d| d.name().eq_ignore_ascii_case(name.trim()))
+    }
+}
+
+/// A prompt waiting to be sent
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueuedPrompt {
+    pub text: String,
+    pub delivery: Delivery,
+}
+
+#[derive(Debug, Default)]
+pub struct PromptQueue {
+    items: Vec<QueuedPrompt>,
+    /// Chip picked with ↑ for editing or removal
+    pub selected: Option<usize>,
+    /// Slot and delivery of a prompt taken back into the input for editing
+    editing: Option<(usize, Delivery)>,
+    /// Delivery for newly queued prompts
+    pub default_delivery: Delivery,
+    /// Held after an interrupt until the user resumes
+    pub paused: bool,
+}
+
+impl PromptQueue {
+    pub fn items(&self) -> &[QueuedPrompt] {
+        &self.items
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Queue a prompt, back in its old slot if it was being edited
+    pub fn push(&mut self, text: String) {
+        let (index, delivery) = self
+            .editing
+            .take()
+            .unwrap_or((self.items.len(), self.default_delivery));
+        let index = index.min(self.items.len());
+        self.items.insert(index, QueuedPrompt { text, delivery });
+    }
+
+    /// Take the selected prompt out for editing in the input
+    pub fn edit_selected(&mut self) -> Option<String> {
+        let index = self.selected.take()?;
+        let prompt = self.items.remove(index);
+        self.editing = Some((index, prompt.delivery));
+        Some(prompt.text)
+    }
+
+    /// A chip was taken into the input and not queued again yet
+    pub fn is_editing(&self) -> bool {
+        self.editing.is_some()
+    }
+
+    /// Forget the slot of a prompt being edited (the input was sent elsewhere)
+    pub fn finish_edit(&mut self) {
+        self.editing = None;
+    }
+
+    pub fn remove_selected(&mut self) {
+        if let Some(index) = self.selected {
+            self.items.remove(index);
+            self.selected = if self.items.is_empty() {
+                None
+            } else {
+                Some(index.min(self.items.len() - 1))
+            };
+        }
+    }
+
+    pub fn toggle_selected(&mut self) {
+        if let Some(prompt) = self.selected.and_then(|i| self.items.get_mut(i)) {
+            prompt.delivery = prompt.delivery.toggle();
+        }
+    }
+
+    /// Move the selection left, starting from the last chip
+    pub fn select_prev(&mut self) {
+        self.selected = match self.selected {
+            _ if self.items.is_empty() => None,
+            None => Some(self.items.len() - 1),
+            Some(i) => Some(i.saturating_sub(1)),
+        };
+    }
+
+    /// Move the selection right; past the last chip it goes back to the input
+    pub fn select_next(&mut self) {
+        self.selected = self
+            .selected
+            .filter(|&i| i + 1 < self.items.len())
+            .map(|i| i + 1);
+    }
+
+    /// Remove and return the prompts to inject with tool results
+    pub fn take_injections(&mut self) -> Vec<QueuedPrompt> {
+        self.selected = None;
+        let (inject, keep) = std::mem::take(&mut self.items)
+            .into_iter()
+            .partition(|p| p.delivery == Delivery::Inject);
+        self.items = keep;
+        inject
+    }
+
+    /// Remove and return the prompt for the next turn. Prompts meant for
+    /// injection that missed a tool boundary go this way too
+    pub fn pop_next(&mut self) -> Option<QueuedPrompt> {
+        if self.items.is_empty() {
+            return None;
+        }
+        self.selected = None;
+        Some(self.items.remove(0))
+    }
+
+    pub fn clear(&mut self) {
+        self.items.clear();
+        self.selected = None;
+        self.editing = None;
+        self.paused = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn queue(texts: &[&str]) -> PromptQueue {
+        let mut queue = PromptQueue::default();
+        for text in texts {
+            queue.push(text.to_string());
+        }
+        queue
+    }
+
+    fn texts(queue: &PromptQueue) -> Vec<&str> {
+        queue.items().iter().map(|p| p.text.as_str()).collect()
+    }
+
+    #[test]
+    fn test_edit_returns_to_slot() {
+        let mut queue = queue(&["a", "b", "c"]);
+        queue.select_prev();
+        queue.select_prev();
+        queue.toggle_selected();
+        assert_eq!(queue.edit_selected().as_deref(), Some("b"));
+        assert_eq!(texts(&queue), ["a", "c"]);
+        queue.push("B".to_string());
+        assert_eq!(texts(&queue), ["a", "B", "c"]);
+        assert_eq!(queue.items()[1].delivery, Delivery::Inject);
+        queue.push("d".to_string());
+        assert_eq!(queue.items()[3].delivery, Delivery::NextTurn);
+    }
+
+    #[test]
+    fn test_injections_and_next_turn() {
+        let mut queue = queue(&["a", "b", "c"]);
+        queue.selected = Some(1);
+        queue.toggle_selected();
+        let injected = queue.take_injections();
+        assert_eq!(injected.len(), 1);
+        assert_eq!(injected[0].text, "b");
+        assert_eq!(queue.pop_next().map(|p| p.text).as_deref(), Some("a"));
+        queue.selected = Some(0);
+        queue.remove_selected();
+        assert!(queue.is_empty());
+        assert_eq!(queue.selected, None);
+    }
+}
@@ -1,10 +1,16 @@
 //! Syntax highlighting using syntect
+//!
+//! syntect only tells us which kind of token each piece of code is; the
+//! colors come from the active Krusty theme's `syntax_*` fields, so code
+//! follows built-in and user themes alike.
 
 use once_cell::sync::Lazy;
-use ratatui::style::{Color, Style};
+use ratatui::style::{Color, Modifier, Style};
 use ratatui::text::Span;
 use syntect::easy::HighlightLines;
-use syntect::highlighting::{FontStyle, ThemeSet};
+use syntect::highlighting::{
+    self, FontStyle, ScopeSelectors, StyleModifier, ThemeItem, ThemeSettings,
+};
 use syntect::parsing::SyntaxSet;
 use syntect::util::LinesWithEndings;
 
@@ -13,8 +19,127 @@ use crate::tui::themes::Theme;
 /// Global syntax set - loaded once at startup
 static SYNTAX_SET: Lazy<SyntaxSet> = Lazy::new(SyntaxSet::load_defaults_newlines);
 
-/// Global theme set for syntect (we'll use our own theme mapping instead)
-static THEME_SET: Lazy<ThemeSet> = Lazy::new(ThemeSet::load_defaults);
+/// Token kinds, each with a theme color
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Role {
+    Text,
+    Comment,
+    String,
+    Number,
+    Keyword,
+    Operator,
+    Function,
+    Type,
+    Variable,
+    Punctuation,
+}
+
+const ROLES: [Role; 10] = [
+    Role::Text,
+    Role::Comment,
+    Role::String,
+    Role::Number,
+    Role::Keyword,
+    Role::Operator,
+    Role::Function,
+    Role::Type,
+    Role::Variable,
+    Role::Punctuation,
+];
+
+impl Role {
+    /// Placeholder color syntect reports for this role
+    fn marker(self) -> highlighting::Color {
+        let index = ROLES.iter().position(|r| *r == self).unwrap_or(0) as u8;
+        highlighting::Color {
+            r: index,
+            g: 0,
+            b: 0,
+            a: 0xff,
+        }
+    }
+
+    fn from_marker(color: highlighting::Color) -> Role {
+        ROLES.get(color.r as usize).copied().unwrap_or(Role::Text)
+    }
+
+    fn color(self, theme: &Theme) -> Color {
+        match self {
+            Role::Text => theme.text_color,
+            Role::Comment => theme.syntax_comment_color,
+            Role::String => theme.syntax_string_color,
+            Role::Number => theme.syntax_number_color,
+            Role::Keyword => theme.syntax_keyword_color,
+            Role::Operator => theme.syntax_operator_color,
+            Role::Function => theme.syntax_function_color,
+            Role::Type => theme.syntax_type_color,
+            Role::Variable => theme.syntax_variable_color,
+            Role::Punctuation => theme.syntax_punctuation_color,
+        }
+    }
+}
+
+/// Scope selectors for each role; the most specific match wins
+const SCOPE_ROLES: &[(&str, Role, bool)] = &[
+    (
+        "comment, punctuation.definition.comment",
+        Role::Comment,
+        true,
+    ),
+    (
+        "string, punctuation.definition.string, constant.character.escape",
+        Role::String,
+        false,
+    ),
+    (
+        "constant.numeric, constant.language, constant.character, support.constant",
+        Role::Number,
+        false,
+    ),
+    ("keyword, storage, entity.name.tag", Role::Keyword, false),
+    ("keyword.operator", Role::Operator, false),
+    (
+        "entity.name.function, support.function, variable.function, meta.function-call",
+        Role::Function,
+        false,
+    ),
+    (
+        "entity.name.type, entity.name.class, entity.name.struct, entity.name.enum, \
+         entity.name.trait, support.type, support.class, entity.other.inherited-class, \
+         entity.other.attribute-name",
+        Role::Type,
+        false,
+    ),
+    ("variable, variable.parameter", Role::Variable, false),
+    ("punctuation", Role::Punctuation, false),
+];
+
+/// syntect theme that colors each token with its role's marker
+static ROLE_THEME: Lazy<highlighting::Theme> = Lazy::new(|| {
+    let scopes = SCOPE_ROLES
+        .iter()
+        .filter_map(|(selectors, role, italic)| {
+            let scope = selectors.parse::<ScopeSelectors>().ok()?;
+            Some(ThemeItem {
+                scope,
+                style: StyleModifier {
+                    foreground: Some(role.marker()),
+                    background: None,
+                    font_style: italic.then_some(FontStyle::ITALIC),
+                },
+            })
+        })
+        .collect();
+    highlighting::Theme {
+        name: Some("krusty-roles".to_string()),
+        author: None,
+        settings: ThemeSettings {
+            foreground: Some(Role::Text.marker()),
+            ..Default::default()
+        },
+        scopes,
+    }
+});
 
 /// Highlight a code block and return styled spans for each line
 pub fn highlight_code(code: &str, lang: &str, theme: &Theme) -> Vec<Vec<Span<'static>>> {
@@ -24,9 +149,7 @@ pub fn highlight_code(code: &str, lang: &str, theme: &Theme) -> Vec<Vec<Span<'st
         .or_else(|| SYNTAX_SET.find_syntax_by_extension(lang))
         .unwrap_or_else(|| SYNTAX_SET.find_syntax_plain_text());
 
-    // Use base16-ocean.dark as a reasonable default for scope detection
-    let syntect_theme = &THEME_SET.themes["base16-ocean.dark"];
-    let mut highlighter = HighlightLines::new(syntax, syntect_theme);
+    let mut highlighter = HighlightLines::new(syntax, &ROLE_THEME);
 
     let mut result = Vec::new();
 
@@ -38,15 +161,14 @@ pub fn highlight_code(code: &str, lang: &str, theme: &Theme) -> Vec<Vec<Span<'st
         let spans: Vec<Span<'static>> = ranges
             .into_iter()
             .map(|(style, text)| {
-                // Map syntect style to our theme colors
-                let color = map_syntect_color_to_theme(style.foreground, theme);
+                let color = Role::from_marker(style.foreground).color(theme);
                 let mut ratatui_style = Style::default().fg(color);
 
                 if style.font_style.contains(FontStyle::BOLD) {
-                    ratatui_style = ratatui_style.add_modifier(ratatui::style::Modifier::BOLD);
+                    ratatui_style = ratatui_style.add_modifier(Modifier::BOLD);
                 }
                 if style.font_style.contains(FontStyle::ITALIC) {
-                    ratatui_style = ratatui_style.add_modifier(ratatui::style::Modifier::ITALIC);
+                    ratatui_style = ratatui_style.add_modifier(Modifier::ITALIC);
                 }
 
                 // Remove trailing newline from text for cleaner output
@@ -66,52 +188,31 @@ pub fn highlight_code(code: &str, lang: &str, theme: &Theme) -> Vec<Vec<Span<'st
     result
 }
 
-/// Map syntect's color to our theme colors based on the base16-ocean palette
-/// This gives us semantic highlighting that respects the user's theme
-fn map_syntect_color_to_theme(syntect_color: syntect::highlighting::Color, theme: &Theme) -> Color {
-    // base16-ocean.dark palette colors (what syntect will output):
-    // base00 = #2b303b (background)
-    // base01 = #343d46 (lighter bg)
-    // base02 = #4f5b66 (selection)
-    // base03 = #65737e (comments)
-    // base04 = #a7adba (dark fg)
-    // base05 = #c0c5ce (default fg)
-    // base06 = #dfe1e8 (light fg)
-    // base07 = #eff1f5 (lightest fg)
-    // base08 = #bf616a (red - errors, variables)
-    // base09 = #d08770 (orange - numbers)
-    // base0A = #ebcb8b (yellow - classes)
-    // base0B = #a3be8c (green - strings)
-    // base0C = #96b5b4 (cyan - support)
-    // base0D = #8fa1b3 (blue - functions)
-    // base0E = #b48ead (purple - keywords)
-    // base0F = #ab7967 (brown - deprecated)
-
-    let (r, g, b) = (syntect_color.r, syntect_color.g, syntect_color.b);
-
-    // Match against known base16-ocean colors and map to our theme
-    match (r, g, b) {
-        // Comments (gray)
-        (101, 115, 126) => theme.syntax_comment_color,
-        // Strings (green)
-        (163, 190, 140) => theme.syntax_string_color,
-        // Numbers (orange)
-        (208, 135, 112) => theme.syntax_number_color,
-        // Keywords (purple)
-        (180, 142, 173) => theme.syntax_keyword_color,
-        // Functions (blue)
-        (143, 161, 179) => theme.syntax_function_color,
-        // Types/Classes (yellow)
-        (235, 203, 139) => theme.syntax_type_color,
-        // Variables (red)
-        (191, 97, 106) => theme.syntax_variable_color,
-        // Cyan (support/constants)
-        (150, 181, 180) => theme.syntax_type_color,
-        // Operators and punctuation (light gray)
-        (192, 197, 206) | (167, 173, 186) => theme.syntax_punctuation_color,
-        // Default foreground colors
-        (223, 225, 232) | (239, 241, 245) => theme.text_color,
-        // For any other color, use text color to maintain theme consistency
-        _ => theme.text_color,
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tui::themes::ThemeRegistry;
+
+    fn color_of(spans: &[Span], text: &str) -> Option<Color> {
+        spans
+            .iter()
+            .find(|s| s.content.trim() == text)
+            .and_then(|s| s.style.fg)
+    }
+
+    #[test]
+    fn test_highlighting_follows_theme() {
+        let registry = ThemeRegistry::new();
+        let code = "fn main() { let x = \"hi\"; // note\n}";
+        for name in ["krusty", "dracula", "terminal"] {
+            let theme = registry.get(name).unwrap();
+            let lines = highlight_code(code, "rust", theme);
+            let first = &lines[0];
+            assert_eq!(color_of(first, "fn"), Some(theme.syntax_keyword_color));
+            assert_eq!(color_of(first, "main"), Some(theme.syntax_function_color));
+            assert_eq!(color_of(first, "hi"), Some(theme.syntax_string_color));
+            assert_eq!(color_of(first, "note"), Some(theme.syntax_comment_color));
+            assert_eq!(color_of(first, "="), Some(theme.syntax_operator_color));
+        }
     }
 }
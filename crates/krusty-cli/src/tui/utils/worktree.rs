@@ -1,14 +1,15 @@
 //! WorktreeDelegate implementation for App
 //!
 //! Provides the worktree interface that WASM extensions need to interact
-//! with the filesystem and environment.
+//! with the filesystem and environment. The CLI has a single worktree, so
+//! the same delegate also serves as the extension's project.
 
 use anyhow::Result;
 use async_trait::async_trait;
 use std::path::PathBuf;
 use std::sync::Arc;
 
-use crate::extensions::types::WorktreeDelegate;
+use crate::extensions::types::{ProjectDelegate, WorktreeDelegate};
 
 /// Implementation of WorktreeDelegate for the TUI app
 pub struct AppWorktreeDelegate {
@@ -57,3 +58,9 @@ impl WorktreeDelegate for AppWorktreeDelegate {
         std::env::vars().collect()
     }
 }
+
+impl ProjectDelegate for AppWorktreeDelegate {
+    fn worktree_ids(&self) -> Vec<u64> {
+        vec![self.id]
+    }
+}
@@ -2,19 +2,19 @@
 //!
 //! This is the core ACP agent that handles all protocol methods.
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 use agent_client_protocol::{
     Agent, AgentCapabilities, AuthenticateRequest, AuthenticateResponse, AvailableCommand,
-    AvailableCommandsUpdate, CancelNotification, ClientCapabilities, ContentBlock,
-    Error as AcpSchemaError, ExtNotification, ExtRequest, ExtResponse, Implementation,
-    InitializeRequest, InitializeResponse, LoadSessionRequest, LoadSessionResponse,
+    AvailableCommandInput, AvailableCommandsUpdate, CancelNotification, ClientCapabilities,
+    ContentBlock, Error as AcpSchemaError, ExtNotification, ExtRequest, ExtResponse,
+    Implementation, InitializeRequest, InitializeResponse, LoadSessionRequest, LoadSessionResponse,
     McpCapabilities, ModelId, ModelInfo as AcpModelInfo, NewSessionRequest, NewSessionResponse,
     PromptCapabilities, PromptRequest, PromptResponse, Result as AcpResult, SessionCapabilities,
     SessionId, SessionMode, SessionModeState, SessionModelState, SessionNotification,
     SessionUpdate, SetSessionModeRequest, SetSessionModeResponse, SetSessionModelRequest,
-    SetSessionModelResponse,
+    SetSessionModelResponse, TextContent, UnstructuredCommandInput,
 };
 use tokio::sync::{mpsc, RwLock};
 use tracing::{debug, error, info, warn};
@@ -25,8 +25,9 @@ use super::processor::PromptProcessor;
 use super::session::{SessionManager, SessionState};
 use crate::ai::openrouter;
 use crate::ai::providers::{get_provider, ProviderId};
+use crate::commands::{CommandDefinition, CommandsManager};
 use crate::storage::credentials::CredentialStore;
-use crate::tools::ToolRegistry;
+use crate::tools::{ToolContext, ToolRegistry};
 
 /// ACP protocol version supported by this agent (10 is current)
 #[allow(dead_code)]
@@ -58,8 +59,7 @@ pub struct KrustyAgent {
     /// Available model configurations from all providers
     /// (model_id, provider, actual_model_id, api_key, display_name)
     available_models: RwLock<Vec<(String, ProviderId, String, String, String)>>,
-    /// Working directory (reserved for future use)
-    #[allow(dead_code)]
+    /// Working directory (fallback for sessions without one)
     cwd: PathBuf,
 }
 
@@ -319,22 +319,43 @@ impl KrustyAgent {
         self.api_key.read().await.clone()
     }
 
-    /// Get available slash commands
-    pub fn get_available_commands(&self) -> Vec<AvailableCommand> {
-        vec![
+    /// Get available slash commands: the built-ins plus custom commands from
+    /// `~/.krusty/commands` and `<cwd>/.krusty/commands`
+    pub fn get_available_commands(&self, cwd: &Path) -> Vec<AvailableCommand> {
+        let mut commands = vec![
             AvailableCommand::new("compact", "Summarize the conversation to reduce context"),
             AvailableCommand::new("clear", "Clear the conversation history"),
             AvailableCommand::new("help", "Show available commands and usage"),
             AvailableCommand::new("model", "Show or change the current AI model"),
             AvailableCommand::new("mode", "Switch between code and plan modes"),
-        ]
+        ];
+        for custom in CommandsManager::for_working_dir(cwd).list_commands() {
+            // Built-ins win over custom commands with the same name
+            if commands.iter().any(|c| c.name == custom.name) {
+                continue;
+            }
+            let mut command = AvailableCommand::new(custom.name, custom.description);
+            if let Some(hint) = custom.argument_hint {
+                command = command.input(AvailableCommandInput::Unstructured(
+                    UnstructuredCommandInput::new(hint),
+                ));
+            }
+            commands.push(command);
+        }
+        commands
     }
 
     /// Send available commands notification to the client
     pub async fn send_available_commands(&self, session_id: &SessionId) {
         let notification_tx = self.notification_tx.read().await;
         if let Some(tx) = notification_tx.as_ref() {
-            let commands = self.get_available_commands();
+            let cwd = self
+                .sessions
+                .get_session(session_id)
+                .map(|session| session.cwd.clone())
+                .unwrap_or_else(|_| self.cwd.clone());
+            let commands = self.get_available_commands(&cwd);
+            let count = commands.len();
             let update = AvailableCommandsUpdate::new(commands);
             let notification = SessionNotification::new(
                 session_id.clone(),
@@ -343,10 +364,7 @@ impl KrustyAgent {
             if let Err(e) = tx.send(notification).await {
                 warn!("Failed to send available commands: {}", e);
             } else {
-                info!(
-                    "Sent {} available commands",
-                    self.get_available_commands().len()
-                );
+                info!("Sent {} available commands", count);
             }
         }
     }
@@ -579,6 +597,44 @@ impl Agent for KrustyAgent {
             return Err(AcpSchemaError::invalid_params());
         }
 
+        // Expand a custom slash command into its prompt
+        let (prompt, command) = match find_custom_command(&prompt_text, &session.cwd) {
+            Some((command, arguments)) => {
+                info!("Expanding custom command /{}", command.name);
+                // Headless: nobody can confirm a project command's shell spans
+                if command.needs_shell_confirmation() {
+                    let msg = format!(
+                        "/{} runs shell commands from this project; run it in the Krusty TUI to confirm them",
+                        command.name
+                    );
+                    warn!("{}", msg);
+                    return Err(AcpSchemaError::invalid_params().data(serde_json::Value::from(msg)));
+                }
+                let ctx = ToolContext {
+                    working_dir: session.cwd.clone(),
+                    ..Default::default()
+                };
+                let expanded = command
+                    .expand(&arguments, &self.tools, &ctx)
+                    .await
+                    .map_err(|e| {
+                        warn!("Custom command /{} failed: {}", command.name, e);
+                        AcpSchemaError::invalid_params()
+                            .data(serde_json::Value::from(e.to_string()))
+                    })?;
+                // Keep attached resources; the text becomes the expanded prompt
+                let mut prompt = vec![ContentBlock::Text(TextContent::new(expanded))];
+                prompt.extend(
+                    request
+                        .prompt
+                        .into_iter()
+                        .filter(|block| !matches!(block, ContentBlock::Text(_))),
+                );
+                (prompt, Some(command))
+            }
+            None => (request.prompt, None),
+        };
+
         // Get the notification channel
         let notification_tx = self.notification_tx.read().await;
         let Some(tx) = notification_tx.as_ref() else {
@@ -592,7 +648,7 @@ impl Agent for KrustyAgent {
         // Process the prompt with the PromptProcessor
         let processor = self.processor.read().await;
         let stop_reason = processor
-            .process_prompt(&session, request.prompt, &bridge)
+            .process_prompt(&session, prompt, command.as_ref(), &bridge)
             .await
             .map_err(|e| {
                 error!("Prompt processing error: {}", e);
@@ -676,6 +732,16 @@ impl Agent for KrustyAgent {
     }
 }
 
+/// Custom command named by a `/name args` prompt, with its arguments
+fn find_custom_command(prompt_text: &str, cwd: &Path) -> Option<(CommandDefinition, String)> {
+    let rest = prompt_text.trim_start().strip_prefix('/')?;
+    let (name, arguments) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+    let command = CommandsManager::for_working_dir(cwd)
+        .get_command(name)?
+        .clone();
+    Some((command, arguments.to_string()))
+}
+
 /// Extract text content from ACP content blocks
 fn extract_prompt_text(content: &[ContentBlock]) -> String {
     content
@@ -17,11 +17,13 @@ use agent_client_protocol::{
 use anyhow::Result;
 use tracing::{debug, error, info, warn};
 
+use crate::agent::compaction::{CompactionConfig, CompactionThresholds, Compactor};
 use crate::ai::client::{AiClient, AiClientConfig, CallOptions};
 use crate::ai::format_detection::detect_api_format;
 use crate::ai::providers::{get_provider, AuthHeader, ProviderId};
 use crate::ai::streaming::StreamPart;
 use crate::ai::types::{AiToolCall, Content, FinishReason};
+use crate::commands::CommandDefinition;
 use crate::tools::git_identity::{GitIdentity, GitIdentityMode};
 use crate::tools::{ToolContext, ToolRegistry, ToolResult};
 
@@ -119,16 +121,31 @@ impl PromptProcessor {
     /// with tool results until the AI responds without requesting more tools.
     ///
     /// Returns the stop reason when processing completes
+    ///
+    /// `command` is the custom slash command the prompt was expanded from; its
+    /// tool allowlist and model apply to the whole exchange.
     pub async fn process_prompt<C: AcpClient>(
         &self,
         session: &SessionState,
         prompt: Vec<AcpContent>,
+        command: Option<&CommandDefinition>,
         connection: &C,
     ) -> Result<StopReason, AcpError> {
         let ai_client = self.ai_client.as_ref().ok_or_else(|| {
             AcpError::NotAuthenticated("AI client not initialized - authenticate first".into())
         })?;
 
+        // A custom command may pick another model of the same provider
+        let command_client = command.and_then(|c| c.model.as_deref()).map(|model| {
+            let config = AiClientConfig {
+                model: model.to_string(),
+                api_format: detect_api_format(ai_client.provider_id(), model),
+                ..ai_client.config().clone()
+            };
+            Arc::new(AiClient::new(config, ai_client.api_key().to_string()))
+        });
+        let ai_client = command_client.as_ref().unwrap_or(ai_client);
+
         // Convert initial ACP content to Krusty messages and add to history
         // Handle Text, Resource (embedded files), and ResourceLink (file references)
         let initial_content: Vec<Content> =
@@ -141,7 +158,16 @@ impl PromptProcessor {
         }
 
         // Get tool definitions for the AI
-        let tool_defs = self.tools.get_ai_tools().await;
+        let mut tool_defs = self.tools.get_ai_tools().await;
+        if let Some(command) = command {
+            tool_defs.retain(|tool| command.allows_tool(&tool.name));
+        }
+
+        let mut compactor = Compactor::new(CompactionConfig::for_model_with(
+            ai_client.provider_id(),
+            &ai_client.config().model,
+            &CompactionThresholds::load(),
+        ));
 
         // Agentic loop - continue until AI stops requesting tools
         const MAX_ITERATIONS: usize = 50; // Safety limit
@@ -153,8 +179,14 @@ impl PromptProcessor {
 
             info!("Agentic loop iteration {}", iteration + 1);
 
-            // Get current conversation history
-            let messages = session.history().await;
+            // Get current conversation history, compacted in place once it
+            // nears the model's context window
+            let mut messages = session.history().await;
+            match compactor.compact(ai_client, &mut messages).await {
+                Ok(Some(_)) => session.replace_messages(messages.clone()).await,
+                Ok(None) => {}
+                Err(e) => warn!("Context compaction failed: {}", e),
+            }
 
             // Set up call options
             let options = CallOptions {
@@ -266,7 +298,7 @@ impl PromptProcessor {
             }
 
             // Execute tool calls and add results to history
-            self.execute_tool_calls(session, pending_tool_calls, connection)
+            self.execute_tool_calls(session, pending_tool_calls, command, connection)
                 .await?;
 
             // Loop continues - AI will be called again with tool results in history
@@ -277,10 +309,13 @@ impl PromptProcessor {
     }
 
     /// Execute tool calls and stream their results
+    ///
+    /// Calls to tools outside `command`'s allowlist fail without running.
     async fn execute_tool_calls<C: AcpClient>(
         &self,
         session: &SessionState,
         tool_calls: Vec<AiToolCall>,
+        command: Option<&CommandDefinition>,
         connection: &C,
     ) -> Result<StopReason, AcpError> {
         let mut ctx = ToolContext {
@@ -293,6 +328,9 @@ impl PromptProcessor {
                 ctx = ctx.with_git_identity(identity.clone());
             }
         }
+        if let Some(sink) = session.file_change_sink().await {
+            ctx = ctx.with_file_changes(sink);
+        }
 
         for tool_call in tool_calls {
             if session.is_cancelled() {
@@ -313,10 +351,18 @@ impl PromptProcessor {
             }
 
             // Execute the tool
-            let result = self
-                .tools
-                .execute(&tool_call.name, tool_call.arguments.clone(), &ctx)
-                .await;
+            let refusal = command.and_then(|c| c.refuse_tool(&tool_call.name));
+            let result = match refusal {
+                Some(output) => Some(ToolResult {
+                    output,
+                    is_error: true,
+                }),
+                None => {
+                    self.tools
+                        .execute(&tool_call.name, tool_call.arguments.clone(), &ctx)
+                        .await
+                }
+            };
 
             // Send tool call result
             let (update, output_for_history, is_error_for_history) = match &result {
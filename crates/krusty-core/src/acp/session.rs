@@ -7,7 +7,7 @@
 //! - Cancellation state
 //! - Optional persistence to SQLite via storage::SessionManager
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Arc;
 
@@ -19,7 +19,8 @@ use tracing::{debug, info, warn};
 use super::error::AcpError;
 use crate::ai::types::{ModelMessage, Role};
 use crate::storage::SessionManager as StorageSessionManager;
-use crate::tools::ToolContext;
+use crate::storage::{FileAccess, FileActivityTracker};
+use crate::tools::{FileChangeSink, SharedFileChangeSink, ToolContext};
 
 /// Thread-safe wrapper for storage session manager
 ///
@@ -27,6 +28,35 @@ use crate::tools::ToolContext;
 /// acquiring the lock in async contexts.
 pub type StorageHandle = Arc<Mutex<StorageSessionManager>>;
 
+/// Records file changes in an ACP session's stored session
+struct StoredSessionFileChanges {
+    storage: StorageHandle,
+    session_id: String,
+}
+
+#[async_trait::async_trait]
+impl FileChangeSink for StoredSessionFileChanges {
+    async fn before_change(&self, path: &Path) {
+        let storage = self.storage.lock().await;
+        let tracker = FileActivityTracker::new(storage.db(), self.session_id.clone());
+        if let Err(e) = tracker.snapshot_file(path) {
+            warn!("Failed to snapshot {}: {}", path.display(), e);
+        }
+    }
+
+    async fn record(&self, path: &Path, access: FileAccess) {
+        let storage = self.storage.lock().await;
+        let tracker = FileActivityTracker::new(storage.db(), self.session_id.clone());
+        if let Err(e) = tracker.record(&path.display().to_string(), access) {
+            warn!(
+                "Failed to record file activity for {}: {}",
+                path.display(),
+                e
+            );
+        }
+    }
+}
+
 /// Session state for a single ACP session
 pub struct SessionState {
     /// Session identifier
@@ -225,11 +255,29 @@ impl SessionState {
         self.storage_session_id.read().await.clone()
     }
 
+    /// Sink that records the files this session's tools touch, if the
+    /// session is stored
+    pub async fn file_change_sink(&self) -> Option<SharedFileChangeSink> {
+        let storage = self.storage.clone()?;
+        let session_id = self.get_storage_session_id().await?;
+        Some(Arc::new(StoredSessionFileChanges {
+            storage,
+            session_id,
+        }))
+    }
+
     /// Get all messages
     pub async fn get_messages(&self) -> Vec<ModelMessage> {
         self.messages.read().await.clone()
     }
 
+    /// Replace the in-memory history (after compaction)
+    ///
+    /// Stored messages are left as they are, so the full session stays on disk.
+    pub async fn replace_messages(&self, messages: Vec<ModelMessage>) {
+        *self.messages.write().await = messages;
+    }
+
     /// Clear messages (for session reset)
     pub async fn clear_messages(&self) {
         self.messages.write().await.clear();
@@ -0,0 +1,597 @@
+//! Worktree isolation for builder swarms
+//!
+//! Each builder works in its own `git worktree` on a scratch branch, so
+//! builders never see each other's half-written files and a bad builder
+//! can't damage the user's tree. When the swarm finishes, every builder's
+//! changes are committed on its branch and then either applied to the
+//! user's working tree or left on the branch for review.
+//!
+//! Worktrees start from a snapshot of the working tree (`git stash create`),
+//! so uncommitted changes to tracked files are visible to builders.
+//! Untracked files are not.
+//!
+//! Merged files are reported to the session's file change sink as they are
+//! applied, so they show up in change review like direct edits.
+
+use anyhow::{anyhow, Result};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+use tracing::{debug, info, warn};
+
+use crate::storage::FileAccess;
+use crate::tools::SharedFileChangeSink;
+
+/// Branch prefix for builder scratch branches
+const BRANCH_PREFIX: &str = "krusty/build";
+
+/// Identity used for scratch commits when the repo has none configured
+const FALLBACK_NAME: &str = "Krusty";
+const FALLBACK_EMAIL: &str = "krusty@localhost";
+
+/// One builder's worktree
+#[derive(Debug, Clone)]
+pub struct BuilderWorktree {
+    pub builder_id: String,
+    pub branch: String,
+    /// Worktree root
+    pub path: PathBuf,
+    /// Directory the builder works in (same subdirectory as the user's)
+    pub working_dir: PathBuf,
+}
+
+/// What happened to a builder's changes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BuilderOutcome {
+    /// Applied to the user's working tree
+    Merged { files: Vec<String> },
+    /// The builder changed nothing
+    Unchanged,
+    /// Didn't apply cleanly; changes are left on the branch
+    Conflict { files: Vec<String>, branch: String },
+    /// Left on the branch for review (merge-back disabled)
+    Kept { branch: String, stat: String },
+    /// The builder failed (or its changes couldn't be collected); work is left on the branch
+    Discarded { branch: String },
+}
+
+/// Outcome for one builder
+#[derive(Debug, Clone)]
+pub struct BuilderReport {
+    pub builder_id: String,
+    pub outcome: BuilderOutcome,
+}
+
+/// A set of builder worktrees sharing one base snapshot
+pub struct WorktreeSwarm {
+    repo_root: PathBuf,
+    /// Commit the worktrees were created from
+    base: String,
+    /// Directory holding this swarm's worktrees
+    swarm_dir: PathBuf,
+    worktrees: Vec<BuilderWorktree>,
+    finished: bool,
+}
+
+impl WorktreeSwarm {
+    /// Create one worktree per builder from the current working tree
+    pub async fn create(working_dir: &Path, builder_ids: &[String]) -> Result<Self> {
+        let repo_root = PathBuf::from(
+            git(working_dir, &["rev-parse", "--show-toplevel"])
+                .await
+                .map_err(|_| anyhow!("Worktree isolation needs a git repository"))?,
+        );
+        git(&repo_root, &["rev-parse", "--verify", "HEAD"])
+            .await
+            .map_err(|_| anyhow!("Worktree isolation needs at least one commit"))?;
+
+        // Snapshot uncommitted changes without touching the tree or stash list
+        let snapshot = git(&repo_root, &["stash", "create"]).await?;
+        let base = if snapshot.is_empty() {
+            git(&repo_root, &["rev-parse", "HEAD"]).await?
+        } else {
+            snapshot
+        };
+
+        let common_dir = PathBuf::from(git(&repo_root, &["rev-parse", "--git-common-dir"]).await?);
+        let common_dir = if common_dir.is_absolute() {
+            common_dir
+        } else {
+            repo_root.join(common_dir)
+        };
+        let swarm_id = uuid::Uuid::new_v4().simple().to_string()[..8].to_string();
+        let swarm_dir = common_dir.join("krusty-worktrees").join(&swarm_id);
+
+        // Keep the user's subdirectory inside each worktree
+        let relative = working_dir
+            .canonicalize()
+            .ok()
+            .and_then(|dir| {
+                dir.strip_prefix(&repo_root)
+                    .ok()
+                    .map(|rel| rel.to_path_buf())
+            })
+            .unwrap_or_default();
+
+        let mut swarm = Self {
+            repo_root,
+            base,
+            swarm_dir,
+            worktrees: Vec::new(),
+            finished: false,
+        };
+
+        for builder_id in builder_ids {
+            let branch = format!("{}-{}-{}", BRANCH_PREFIX, swarm_id, builder_id);
+            let path = swarm.swarm_dir.join(builder_id);
+            let path_str = path.to_string_lossy().into_owned();
+            // On failure, Drop removes the worktrees created so far
+            git(
+                &swarm.repo_root,
+                &[
+                    "worktree",
+                    "add",
+                    "-q",
+                    "-b",
+                    &branch,
+                    &path_str,
+                    &swarm.base,
+                ],
+            )
+            .await?;
+            debug!(builder = %builder_id, path = %path.display(), "Created builder worktree");
+
+            swarm.worktrees.push(BuilderWorktree {
+                builder_id: builder_id.clone(),
+                branch,
+                working_dir: path.join(&relative),
+                path,
+            });
+        }
+
+        info!(
+            builders = swarm.worktrees.len(),
+            base = %swarm.base,
+            "Created builder worktrees"
+        );
+        Ok(swarm)
+    }
+
+    /// Worktree for a builder
+    pub fn worktree(&self, builder_id: &str) -> Option<&BuilderWorktree> {
+        self.worktrees.iter().find(|w| w.builder_id == builder_id)
+    }
+
+    /// Commit each builder's work, then merge it back or keep it on its branch
+    ///
+    /// Builders are merged in order; a builder whose patch no longer applies
+    /// (because an earlier builder or the user touched the same lines) is
+    /// reported as a conflict and left on its branch. Worktrees are removed
+    /// either way, and branches of merged or unchanged builders are deleted.
+    /// Files a merge changes are reported to `file_changes`.
+    pub async fn finish(
+        mut self,
+        succeeded: impl Fn(&str) -> bool,
+        merge_back: bool,
+        file_changes: Option<&SharedFileChangeSink>,
+    ) -> Vec<BuilderReport> {
+        self.finished = true;
+        let identity = commit_identity(&self.repo_root).await;
+        let worktrees = std::mem::take(&mut self.worktrees);
+        let mut reports = Vec::with_capacity(worktrees.len());
+
+        for worktree in &worktrees {
+            let outcome = match self
+                .settle(
+                    worktree,
+                    succeeded(&worktree.builder_id),
+                    merge_back,
+                    &identity,
+                    file_changes,
+                )
+                .await
+            {
+                Ok(outcome) => outcome,
+                Err(e) => {
+                    warn!(builder = %worktree.builder_id, error = %e, "Failed to collect builder changes");
+                    BuilderOutcome::Discarded {
+                        branch: worktree.branch.clone(),
+                    }
+                }
+            };
+
+            self.remove_worktree(&worktree.path).await;
+            if matches!(
+                outcome,
+                BuilderOutcome::Merged { .. } | BuilderOutcome::Unchanged
+            ) {
+                let _ = git(&self.repo_root, &["branch", "-D", &worktree.branch]).await;
+            }
+
+            reports.push(BuilderReport {
+                builder_id: worktree.builder_id.clone(),
+                outcome,
+            });
+        }
+
+        let _ = std::fs::remove_dir(&self.swarm_dir);
+        let _ = git(&self.repo_root, &["worktree", "prune"]).await;
+        reports
+    }
+
+    /// Commit one builder's work and decide what to do with it
+    async fn settle(
+        &self,
+        worktree: &BuilderWorktree,
+        succeeded: bool,
+        merge_back: bool,
+        identity: &[String],
+        file_changes: Option<&SharedFileChangeSink>,
+    ) -> Result<BuilderOutcome> {
+        git(&worktree.path, &["add", "-A"]).await?;
+        let has_changes = !git_ok(&worktree.path, &["diff", "--cached", "--quiet"]).await?;
+        if !has_changes {
+            return Ok(BuilderOutcome::Unchanged);
+        }
+
+        let message = format!("{}: krusty builder changes", worktree.builder_id);
+        let mut args: Vec<&str> = identity.iter().map(|s| s.as_str()).collect();
+        args.extend(["commit", "-q", "--no-verify", "-m", &message]);
+        git(&worktree.path, &args).await?;
+
+        if !succeeded {
+            return Ok(BuilderOutcome::Discarded {
+                branch: worktree.branch.clone(),
+            });
+        }
+
+        let range = format!("{}..{}", self.base, worktree.branch);
+        if !merge_back {
+            let stat = git(&self.repo_root, &["diff", "--stat", &range]).await?;
+            return Ok(BuilderOutcome::Kept {
+                branch: worktree.branch.clone(),
+                stat,
+            });
+        }
+
+        let files: Vec<String> = git(&self.repo_root, &["diff", "--name-only", &range])
+            .await?
+            .lines()
+            .map(|l| l.to_string())
+            .collect();
+        let patch = git_raw(&self.repo_root, &["diff", "--binary", &range]).await?;
+
+        if let Err(stderr) = git_stdin(&self.repo_root, &["apply", "--check", "-"], &patch).await {
+            let conflicted = conflicted_files(&stderr);
+            return Ok(BuilderOutcome::Conflict {
+                files: if conflicted.is_empty() {
+                    files
+                } else {
+                    conflicted
+                },
+                branch: worktree.branch.clone(),
+            });
+        }
+        // Keep the originals so the merge can be reviewed and reverted
+        let paths: Vec<PathBuf> = files.iter().map(|f| self.repo_root.join(f)).collect();
+        if let Some(sink) = file_changes {
+            for path in &paths {
+                sink.before_change(path).await;
+            }
+        }
+        git_stdin(&self.repo_root, &["apply", "-"], &patch)
+            .await
+            .map_err(|e| anyhow!(e))?;
+        if let Some(sink) = file_changes {
+            for path in &paths {
+                sink.record(path, FileAccess::Write).await;
+            }
+        }
+
+        Ok(BuilderOutcome::Merged { files })
+    }
+
+    async fn remove_worktree(&self, path: &Path) {
+        let path = path.to_string_lossy();
+        if let Err(e) = git(&self.repo_root, &["worktree", "remove", "--force", &path]).await {
+            warn!(path = %path, error = %e, "Failed to remove builder worktree");
+        }
+    }
+}
+
+impl Drop for WorktreeSwarm {
+    /// Cancelled swarms: remove the worktrees, keep the branches
+    fn drop(&mut self) {
+        if self.finished {
+            return;
+        }
+        for worktree in &self.worktrees {
+            let _ = std::process::Command::new("git")
+                .arg("-C")
+                .arg(&self.repo_root)
+                .args(["worktree", "remove", "--force"])
+                .arg(&worktree.path)
+                .output();
+        }
+        let _ = std::fs::remove_dir(&self.swarm_dir);
+    }
+}
+
+/// `-c` overrides for scratch commits when no identity is configured
+async fn commit_identity(repo_root: &Path) -> Vec<String> {
+    let configured = git(repo_root, &["config", "user.email"])
+        .await
+        .is_ok_and(|email| !email.is_empty());
+    if configured {
+        Vec::new()
+    } else {
+        vec![
+            "-c".to_string(),
+            format!("user.name={}", FALLBACK_NAME),
+            "-c".to_string(),
+            format!("user.email={}", FALLBACK_EMAIL),
+        ]
+    }
+}
+
+/// Paths `git apply --check` complained about
+fn conflicted_files(stderr: &str) -> Vec<String> {
+    let mut files: Vec<String> = Vec::new();
+    for line in stderr.lines() {
+        let Some(rest) = line.strip_prefix("error: ") else {
+            continue;
+        };
+        let path = if let Some(target) = rest.strip_prefix("patch failed: ") {
+            // "patch failed: src/lib.rs:12"
+            target
+                .rsplit_once(':')
+                .map(|(path, _)| path)
+                .unwrap_or(target)
+        } else if let Some((path, _)) = rest.split_once(": ") {
+            // "src/lib.rs: does not match index", "x.rs: already exists in working directory"
+            path
+        } else {
+            continue;
+        };
+        if !files.iter().any(|f| f == path) {
+            files.push(path.to_string());
+        }
+    }
+    files
+}
+
+/// Run git and return trimmed stdout
+async fn git(dir: &Path, args: &[&str]) -> Result<String> {
+    let stdout = git_raw(dir, args).await?;
+    Ok(String::from_utf8_lossy(&stdout).trim().to_string())
+}
+
+/// Run git and return stdout bytes as is
+async fn git_raw(dir: &Path, args: &[&str]) -> Result<Vec<u8>> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .await?;
+    if !output.status.success() {
+        return Err(anyhow!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(output.stdout)
+}
+
+/// Run git for its exit status (e.g. `diff --quiet`)
+async fn git_ok(dir: &Path, args: &[&str]) -> Result<bool> {
+    let status = Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .await?;
+    Ok(status.success())
+}
+
+/// Run git with input on stdin; errors carry git's stderr
+async fn git_stdin(dir: &Path, args: &[&str], input: &[u8]) -> std::result::Result<(), String> {
+    let mut child = Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| e.to_string())?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(input).await.map_err(|e| e.to_string())?;
+    }
+    let output = child.wait_with_output().await.map_err(|e| e.to_string())?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).into_owned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use std::sync::{Arc, Mutex};
+    use tempfile::tempdir;
+
+    /// Keeps each file's content before a change and the recorded accesses
+    #[derive(Default)]
+    struct Recorder {
+        originals: Mutex<Vec<(PathBuf, Option<String>)>>,
+        recorded: Mutex<Vec<(PathBuf, FileAccess)>>,
+    }
+
+    #[async_trait]
+    impl crate::tools::FileChangeSink for Recorder {
+        async fn before_change(&self, path: &Path) {
+            let content = std::fs::read_to_string(path).ok();
+            self.originals
+                .lock()
+                .unwrap()
+                .push((path.to_path_buf(), content));
+        }
+
+        async fn record(&self, path: &Path, access: FileAccess) {
+            self.recorded
+                .lock()
+                .unwrap()
+                .push((path.to_path_buf(), access));
+        }
+    }
+
+    fn run(dir: &Path, args: &[&str]) {
+        let status = std::process::Command::new("git")
+            .args(args)
+            .current_dir(dir)
+            .output()
+            .unwrap()
+            .status;
+        assert!(status.success(), "git {:?} failed", args);
+    }
+
+    fn init_repo(dir: &Path) {
+        run(dir, &["init", "-q"]);
+        run(dir, &["config", "user.email", "test@example.com"]);
+        run(dir, &["config", "user.name", "Test"]);
+        std::fs::write(dir.join("shared.txt"), "one\ntwo\nthree\n").unwrap();
+        run(dir, &["add", "-A"]);
+        run(dir, &["commit", "-q", "-m", "init"]);
+    }
+
+    fn ids(ids: &[&str]) -> Vec<String> {
+        ids.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[tokio::test]
+    async fn test_merge_back_and_conflict() {
+        let temp = tempdir().unwrap();
+        let repo = temp.path().canonicalize().unwrap();
+        init_repo(&repo);
+        // Uncommitted change is part of the snapshot
+        std::fs::write(repo.join("shared.txt"), "one\ntwo\nthree\nfour\n").unwrap();
+
+        let swarm = WorktreeSwarm::create(&repo, &ids(&["builder-0", "builder-1", "builder-2"]))
+            .await
+            .unwrap();
+        let w0 = swarm.worktree("builder-0").unwrap().clone();
+        let w1 = swarm.worktree("builder-1").unwrap().clone();
+        let w2 = swarm.worktree("builder-2").unwrap().clone();
+        assert_eq!(
+            std::fs::read_to_string(w0.path.join("shared.txt")).unwrap(),
+            "one\ntwo\nthree\nfour\n"
+        );
+
+        // builder-0 adds a file and edits line 2; builder-1 edits line 2 too
+        std::fs::write(w0.working_dir.join("a.txt"), "from builder 0\n").unwrap();
+        std::fs::write(w0.path.join("shared.txt"), "one\nTWO\nthree\nfour\n").unwrap();
+        std::fs::write(w1.path.join("shared.txt"), "one\n2\nthree\nfour\n").unwrap();
+
+        let recorder = Arc::new(Recorder::default());
+        let sink: SharedFileChangeSink = recorder.clone();
+        let reports = swarm.finish(|_| true, true, Some(&sink)).await;
+        assert_eq!(
+            reports[0].outcome,
+            BuilderOutcome::Merged {
+                files: vec!["a.txt".to_string(), "shared.txt".to_string()]
+            }
+        );
+        assert_eq!(
+            reports[1].outcome,
+            BuilderOutcome::Conflict {
+                files: vec!["shared.txt".to_string()],
+                branch: w1.branch.clone()
+            }
+        );
+        assert_eq!(reports[2].outcome, BuilderOutcome::Unchanged);
+
+        assert_eq!(
+            std::fs::read_to_string(repo.join("shared.txt")).unwrap(),
+            "one\nTWO\nthree\nfour\n"
+        );
+        assert!(repo.join("a.txt").exists());
+        assert!(!w0.path.exists() && !w1.path.exists() && !w2.path.exists());
+
+        // Only the merged builder's files are reported, with their originals
+        assert_eq!(
+            *recorder.originals.lock().unwrap(),
+            vec![
+                (repo.join("a.txt"), None),
+                (
+                    repo.join("shared.txt"),
+                    Some("one\ntwo\nthree\nfour\n".to_string())
+                ),
+            ]
+        );
+        assert_eq!(
+            *recorder.recorded.lock().unwrap(),
+            vec![
+                (repo.join("a.txt"), FileAccess::Write),
+                (repo.join("shared.txt"), FileAccess::Write),
+            ]
+        );
+
+        // Only the conflicting builder's branch survives
+        let branches = git(&repo, &["branch", "--list", "krusty/*"]).await.unwrap();
+        assert!(branches.contains(&w1.branch));
+        assert!(!branches.contains(&w0.branch));
+    }
+
+    #[tokio::test]
+    async fn test_keep_branches_and_failed_builders() {
+        let temp = tempdir().unwrap();
+        let repo = temp.path().canonicalize().unwrap();
+        init_repo(&repo);
+
+        let swarm = WorktreeSwarm::create(&repo, &ids(&["builder-0", "builder-1"]))
+            .await
+            .unwrap();
+        let w0 = swarm.worktree("builder-0").unwrap().clone();
+        let w1 = swarm.worktree("builder-1").unwrap().clone();
+        std::fs::write(w0.path.join("new.txt"), "hello\n").unwrap();
+        std::fs::write(w1.path.join("broken.txt"), "half done\n").unwrap();
+
+        let reports = swarm.finish(|id| id != "builder-1", false, None).await;
+        match &reports[0].outcome {
+            BuilderOutcome::Kept { branch, stat } => {
+                assert_eq!(branch, &w0.branch);
+                assert!(stat.contains("new.txt"));
+            }
+            other => panic!("unexpected outcome {:?}", other),
+        }
+        assert_eq!(
+            reports[1].outcome,
+            BuilderOutcome::Discarded {
+                branch: w1.branch.clone()
+            }
+        );
+        // Nothing reaches the working tree without merge-back
+        assert!(!repo.join("new.txt").exists());
+        assert!(!repo.join("broken.txt").exists());
+    }
+
+    #[tokio::test]
+    async fn test_requires_git_repo() {
+        let temp = tempdir().unwrap();
+        assert!(WorktreeSwarm::create(temp.path(), &ids(&["builder-0"]))
+            .await
+            .is_err());
+    }
+
+    #[test]
+    fn test_conflicted_files() {
+        let stderr = "error: patch failed: src/lib.rs:12\n\
+                      error: src/lib.rs: patch does not apply\n\
+                      error: new.rs: already exists in working directory\n";
+        assert_eq!(conflicted_files(stderr), vec!["src/lib.rs", "new.rs"]);
+    }
+}
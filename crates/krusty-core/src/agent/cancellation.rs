@@ -22,6 +22,11 @@ impl AgentCancellation {
         self.token.cancel();
     }
 
+    /// Whether cancellation was requested
+    pub fn is_cancelled(&self) -> bool {
+        self.token.is_cancelled()
+    }
+
     /// Get a child token for a subtask
     pub fn child_token(&self) -> CancellationToken {
         self.token.child_token()
@@ -0,0 +1,565 @@
+//! In-place context compaction
+//!
+//! Long agent loops outgrow the model's context window. Pinch starts a new
+//! session; compaction instead rewrites the message vector in place, so a
+//! loop can keep going. Large tool outputs from older turns are elided
+//! first. If that is not enough, the older turns are replaced by a summary
+//! from `summarizer::generate_summary`. Recent turns are always kept
+//! verbatim, and a tool result is never separated from its tool call.
+//! When compaction starts can be set per model in `~/.krusty/compaction.toml`.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use anyhow::Result;
+use serde::Deserialize;
+use serde_json::Value;
+use tracing::{info, warn};
+
+use super::summarizer::{generate_summary, SummarizationResult};
+use crate::ai::client::AiClient;
+use crate::ai::providers::{get_provider, ProviderId};
+use crate::ai::types::{Content, ModelMessage, Role};
+
+/// Context window assumed for models the provider list doesn't know
+const DEFAULT_CONTEXT_WINDOW: usize = 128_000;
+
+/// Rough token cost of an image or document block
+const ATTACHMENT_TOKENS: usize = 1_500;
+
+/// When and how far to compact
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompactionConfig {
+    /// The model's context window in tokens
+    pub context_window: usize,
+    /// Estimated tokens at which compaction starts
+    pub threshold_tokens: usize,
+    /// Estimated tokens to get under once compacting
+    pub target_tokens: usize,
+    /// Messages at the end that are never touched
+    pub keep_recent: usize,
+    /// Older tool outputs longer than this many characters are elided
+    pub max_stale_output_chars: usize,
+    /// Keep the first message verbatim (a sub-agent's task prompt)
+    pub keep_first: bool,
+}
+
+impl CompactionConfig {
+    /// Share of the context window at which compaction starts
+    pub const DEFAULT_THRESHOLD: f32 = 0.75;
+
+    /// Compact at 75% of `context_window`, down to half of it
+    pub fn for_context_window(context_window: usize) -> Self {
+        Self {
+            context_window,
+            threshold_tokens: (context_window as f32 * Self::DEFAULT_THRESHOLD) as usize,
+            target_tokens: context_window / 2,
+            keep_recent: 10,
+            max_stale_output_chars: 2_000,
+            keep_first: false,
+        }
+    }
+
+    /// Config for a model, using its `ModelInfo::context_window`
+    pub fn for_model(provider: ProviderId, model: &str) -> Self {
+        let context_window = get_provider(provider)
+            .and_then(|p| p.models.iter().find(|m| m.id == model))
+            .map(|m| m.context_window)
+            .unwrap_or(DEFAULT_CONTEXT_WINDOW);
+        Self::for_context_window(context_window)
+    }
+
+    /// Config for a model, starting at the user's threshold for it if set
+    pub fn for_model_with(
+        provider: ProviderId,
+        model: &str,
+        thresholds: &CompactionThresholds,
+    ) -> Self {
+        let config = Self::for_model(provider, model);
+        match thresholds.ratio_for(model) {
+            Some(ratio) => config.with_threshold_ratio(ratio),
+            None => config,
+        }
+    }
+
+    /// Start compacting at `ratio` of the context window instead
+    pub fn with_threshold_ratio(mut self, ratio: f32) -> Self {
+        self.threshold_tokens = (self.context_window as f32 * ratio.clamp(0.1, 1.0)) as usize;
+        self.target_tokens = self.target_tokens.min(self.threshold_tokens * 2 / 3);
+        self
+    }
+
+    /// Number of trailing messages kept verbatim
+    pub fn with_keep_recent(mut self, keep_recent: usize) -> Self {
+        self.keep_recent = keep_recent;
+        self
+    }
+
+    /// Keep the first message verbatim
+    pub fn with_keep_first(mut self, keep_first: bool) -> Self {
+        self.keep_first = keep_first;
+        self
+    }
+}
+
+/// Compaction thresholds set by the user
+///
+/// Read from `~/.krusty/compaction.toml`, as shares of the context window:
+///
+/// ```toml
+/// threshold = 0.8
+///
+/// [models]
+/// "claude-opus-4-5" = 0.6
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CompactionThresholds {
+    /// Threshold for every model without its own entry
+    pub threshold: Option<f32>,
+    /// Thresholds by model ID
+    pub models: HashMap<String, f32>,
+}
+
+impl CompactionThresholds {
+    /// Thresholds from `~/.krusty/compaction.toml`
+    pub fn load() -> Self {
+        Self::load_from(&crate::paths::compaction_config_path())
+    }
+
+    /// Thresholds from `path`; none if it is missing or can't be parsed
+    pub fn load_from(path: &Path) -> Self {
+        let content = match std::fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Self::default(),
+            Err(e) => {
+                warn!("Could not read {}: {}", path.display(), e);
+                return Self::default();
+            }
+        };
+        toml::from_str(&content).unwrap_or_else(|e| {
+            warn!("Ignoring {}: {}", path.display(), e);
+            Self::default()
+        })
+    }
+
+    /// Share of the context window at which `model` starts compacting
+    pub fn ratio_for(&self, model: &str) -> Option<f32> {
+        self.models.get(model).copied().or(self.threshold)
+    }
+}
+
+/// What a compaction did
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CompactionReport {
+    pub tokens_before: usize,
+    pub tokens_after: usize,
+    /// Tool outputs replaced by a placeholder
+    pub outputs_elided: usize,
+    /// Messages replaced by the summary (0 if eliding was enough)
+    pub messages_summarized: usize,
+}
+
+/// Rough token count of a message vector (about 4 characters per token)
+pub fn estimate_tokens(messages: &[ModelMessage]) -> usize {
+    let mut chars = 0;
+    let mut attachments = 0;
+    for content in messages.iter().flat_map(|m| &m.content) {
+        match content {
+            Content::Text { text } => chars += text.len(),
+            Content::ToolUse { name, input, .. } => chars += name.len() + input.to_string().len(),
+            Content::ToolResult { output, .. } => {
+                chars += match output {
+                    Value::String(s) => s.len(),
+                    other => other.to_string().len(),
+                }
+            }
+            Content::Thinking { thinking, .. } => chars += thinking.len(),
+            Content::RedactedThinking { data } => chars += data.len(),
+            Content::Image { .. } | Content::Document { .. } => attachments += 1,
+        }
+    }
+    chars / 4 + attachments * ATTACHMENT_TOKENS
+}
+
+/// Compacts one conversation across the turns of a loop
+///
+/// Remembers failed summaries: after one fails, no summary is tried again
+/// until the conversation has grown by another tenth of the context window
+/// (doubling with each failure in a row). Eliding outputs needs no model
+/// call and still runs every turn.
+#[derive(Debug, Clone)]
+pub struct Compactor {
+    config: CompactionConfig,
+    failures: u32,
+    retry_at_tokens: usize,
+}
+
+impl Compactor {
+    pub fn new(config: CompactionConfig) -> Self {
+        Self {
+            config,
+            failures: 0,
+            retry_at_tokens: 0,
+        }
+    }
+
+    pub fn config(&self) -> &CompactionConfig {
+        &self.config
+    }
+
+    /// Compact `messages` if they are over the configured threshold
+    ///
+    /// Returns `None` when nothing needed doing. Fails only if a summary was
+    /// needed and could not be generated; older outputs may have been elided
+    /// by then, which leaves the messages valid.
+    pub async fn compact(
+        &mut self,
+        client: &AiClient,
+        messages: &mut Vec<ModelMessage>,
+    ) -> Result<Option<CompactionReport>> {
+        let config = &self.config;
+        let tokens_before = estimate_tokens(messages);
+        if tokens_before < config.threshold_tokens {
+            return Ok(None);
+        }
+
+        let mut report = CompactionReport {
+            tokens_before,
+            outputs_elided: elide_stale_outputs(messages, config),
+            ..Default::default()
+        };
+
+        let tokens = estimate_tokens(messages);
+        if tokens > config.target_tokens && self.summary_due(tokens) {
+            if let Some(range) = summary_range(messages, config) {
+                let older: Vec<ModelMessage> = messages[range.clone()]
+                    .iter()
+                    .filter(|m| m.role != Role::System)
+                    .cloned()
+                    .collect();
+                let summary =
+                    match generate_summary(client, &older, None, &[], &[], None, None).await {
+                        Ok(summary) => summary,
+                        Err(e) => {
+                            self.record_failure(tokens);
+                            return Err(e);
+                        }
+                    };
+                self.failures = 0;
+                self.retry_at_tokens = 0;
+                report.messages_summarized = older.len();
+                replace_with_summary(messages, range, &format_summary(&summary));
+            }
+        }
+
+        report.tokens_after = estimate_tokens(messages);
+        info!(
+            before = report.tokens_before,
+            after = report.tokens_after,
+            elided = report.outputs_elided,
+            summarized = report.messages_summarized,
+            "Compacted context"
+        );
+        Ok(Some(report))
+    }
+
+    /// Whether a summary may be tried at `tokens`
+    fn summary_due(&self, tokens: usize) -> bool {
+        tokens >= self.retry_at_tokens
+    }
+
+    /// Hold off summarizing after a failure at `tokens`
+    fn record_failure(&mut self, tokens: usize) {
+        self.failures += 1;
+        let step = self.config.context_window / 10;
+        let backoff = step.saturating_mul(1 << (self.failures - 1).min(4));
+        self.retry_at_tokens = tokens.saturating_add(backoff);
+    }
+}
+
+/// Replace large tool outputs outside the recent messages with a placeholder
+///
+/// Returns how many outputs were elided.
+pub fn elide_stale_outputs(messages: &mut [ModelMessage], config: &CompactionConfig) -> usize {
+    let stale = messages.len().saturating_sub(config.keep_recent);
+    let mut elided = 0;
+    for message in &mut messages[..stale] {
+        for content in &mut message.content {
+            if let Content::ToolResult {
+                output: Value::String(output),
+                ..
+            } = content
+            {
+                if output.len() > config.max_stale_output_chars {
+                    *output = format!(
+                        "[Output elided to save context: {} lines, {} chars. Run the tool again if it is still needed.]",
+                        output.lines().count(),
+                        output.len()
+                    );
+                    elided += 1;
+                }
+            }
+        }
+    }
+    elided
+}
+
+/// Whether a message carries tool results (and so must follow its tool calls)
+fn has_tool_results(message: &ModelMessage) -> bool {
+    message.role == Role::Tool
+        || message
+            .content
+            .iter()
+            .any(|c| matches!(c, Content::ToolResult { .. }))
+}
+
+/// Messages to fold into the summary
+///
+/// The range ends where the kept messages begin, moved later if needed so
+/// they don't start with tool results whose calls would be summarized away.
+fn summary_range(
+    messages: &[ModelMessage],
+    config: &CompactionConfig,
+) -> Option<std::ops::Range<usize>> {
+    let start = usize::from(config.keep_first).min(messages.len());
+    let mut end = messages.len().saturating_sub(config.keep_recent).max(start);
+    while end < messages.len() && has_tool_results(&messages[end]) {
+        end += 1;
+    }
+    // Always leave a message to continue from
+    if end >= messages.len() || end - start < 2 {
+        return None;
+    }
+    Some(start..end)
+}
+
+/// Swap the messages in `range` for a user message holding `summary`
+///
+/// System messages in the range are kept. The summary joins a neighbouring
+/// user message rather than sitting next to one.
+fn replace_with_summary(
+    messages: &mut Vec<ModelMessage>,
+    range: std::ops::Range<usize>,
+    summary: &str,
+) {
+    let tail = messages.split_off(range.end);
+    let older = messages.split_off(range.start);
+
+    messages.extend(older.into_iter().filter(|m| m.role == Role::System));
+    let text = Content::Text {
+        text: summary.to_string(),
+    };
+    match messages.last_mut() {
+        Some(last) if last.role == Role::User => last.content.push(text),
+        _ => messages.push(ModelMessage {
+            role: Role::User,
+            content: vec![text],
+        }),
+    }
+
+    let mut tail = tail.into_iter();
+    if let Some(next) = tail.next() {
+        match messages.last_mut() {
+            Some(last) if last.role == Role::User && next.role == Role::User => {
+                last.content.extend(next.content);
+            }
+            _ => messages.push(next),
+        }
+    }
+    messages.extend(tail);
+}
+
+/// Summary as the text the agent sees in place of the older turns
+fn format_summary(summary: &SummarizationResult) -> String {
+    let mut text = format!(
+        "[Earlier turns were compacted to save context. Summary:]\n\n{}",
+        summary.work_summary
+    );
+    for (heading, items) in [
+        ("Key decisions", &summary.key_decisions),
+        ("Pending", &summary.pending_tasks),
+        ("Important files", &summary.important_files),
+    ] {
+        if !items.is_empty() {
+            text.push_str(&format!("\n\n{}:", heading));
+            for item in items {
+                text.push_str(&format!("\n- {}", item));
+            }
+        }
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(role: Role, text: &str) -> ModelMessage {
+        ModelMessage {
+            role,
+            content: vec![Content::Text {
+                text: text.to_string(),
+            }],
+        }
+    }
+
+    fn tool_turn(id: &str, output: &str) -> [ModelMessage; 2] {
+        [
+            ModelMessage {
+                role: Role::Assistant,
+                content: vec![Content::ToolUse {
+                    id: id.to_string(),
+                    name: "read".to_string(),
+                    input: serde_json::json!({"file_path": "src/lib.rs"}),
+                }],
+            },
+            ModelMessage {
+                role: Role::User,
+                content: vec![Content::ToolResult {
+                    tool_use_id: id.to_string(),
+                    output: Value::String(output.to_string()),
+                    is_error: None,
+                }],
+            },
+        ]
+    }
+
+    fn conversation(turns: usize, output: &str) -> Vec<ModelMessage> {
+        let mut messages = vec![text(Role::User, "Fix the bug")];
+        for i in 0..turns {
+            messages.extend(tool_turn(&format!("t{}", i), output));
+        }
+        messages
+    }
+
+    #[test]
+    fn test_config_from_context_window() {
+        let config = CompactionConfig::for_context_window(200_000);
+        assert_eq!(config.threshold_tokens, 150_000);
+        assert_eq!(config.target_tokens, 100_000);
+
+        let config = config.with_threshold_ratio(0.5);
+        assert_eq!(config.threshold_tokens, 100_000);
+        assert!(config.target_tokens < config.threshold_tokens);
+
+        let unknown = CompactionConfig::for_model(ProviderId::MiniMax, "no-such-model");
+        assert_eq!(unknown.threshold_tokens, 96_000);
+    }
+
+    #[test]
+    fn test_thresholds_per_model() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("compaction.toml");
+        assert_eq!(
+            CompactionThresholds::load_from(&path),
+            CompactionThresholds::default()
+        );
+
+        std::fs::write(&path, "threshold = 0.5\n\n[models]\n\"big-model\" = 0.9\n").unwrap();
+        let thresholds = CompactionThresholds::load_from(&path);
+        assert_eq!(thresholds.ratio_for("big-model"), Some(0.9));
+        assert_eq!(thresholds.ratio_for("other-model"), Some(0.5));
+
+        let config =
+            CompactionConfig::for_model_with(ProviderId::MiniMax, "no-such-model", &thresholds);
+        assert_eq!(config.threshold_tokens, 64_000);
+        let config = CompactionConfig::for_model_with(
+            ProviderId::MiniMax,
+            "no-such-model",
+            &CompactionThresholds::default(),
+        );
+        assert_eq!(config.threshold_tokens, 96_000);
+
+        // Mistakes fall back to the defaults
+        std::fs::write(&path, "treshold = 0.5\n").unwrap();
+        assert_eq!(
+            CompactionThresholds::load_from(&path),
+            CompactionThresholds::default()
+        );
+    }
+
+    #[test]
+    fn test_elide_only_stale_large_outputs() {
+        let big = "line\n".repeat(1_000);
+        let mut messages = conversation(6, &big);
+        let config = CompactionConfig::for_context_window(10_000).with_keep_recent(4);
+
+        let before = estimate_tokens(&messages);
+        assert_eq!(elide_stale_outputs(&mut messages, &config), 4);
+        assert!(estimate_tokens(&messages) < before);
+
+        // The last two tool results are recent and untouched
+        let outputs: Vec<&Value> = messages
+            .iter()
+            .flat_map(|m| &m.content)
+            .filter_map(|c| match c {
+                Content::ToolResult { output, .. } => Some(output),
+                _ => None,
+            })
+            .collect();
+        assert!(outputs[0].as_str().unwrap().starts_with("[Output elided"));
+        assert_eq!(outputs[5].as_str().unwrap(), big);
+    }
+
+    #[test]
+    fn test_summary_keeps_tool_pairs_and_first_message() {
+        let messages = conversation(5, "ok");
+        // 11 messages; keeping 3 would start the tail on a tool result
+        let config = CompactionConfig::for_context_window(10_000)
+            .with_keep_recent(3)
+            .with_keep_first(true);
+        let range = summary_range(&messages, &config).unwrap();
+        assert_eq!(range, 1..9);
+        assert!(!has_tool_results(&messages[range.end]));
+
+        let mut compacted = messages;
+        replace_with_summary(&mut compacted, range, "summary");
+        assert_eq!(compacted.len(), 3);
+        // The summary joins the kept prompt instead of a second user message
+        assert_eq!(compacted[0].role, Role::User);
+        assert_eq!(compacted[0].content.len(), 2);
+        assert_eq!(compacted[1].role, Role::Assistant);
+        assert!(has_tool_results(&compacted[2]));
+    }
+
+    #[test]
+    fn test_summary_keeps_system_messages() {
+        let mut messages = vec![text(Role::System, "workspace context")];
+        messages.extend(conversation(4, "ok"));
+        messages.push(text(Role::Assistant, "Done"));
+        messages.push(text(Role::User, "Now add a test"));
+        let config = CompactionConfig::for_context_window(10_000).with_keep_recent(2);
+
+        let range = summary_range(&messages, &config).unwrap();
+        replace_with_summary(&mut messages, range, "summary");
+
+        assert_eq!(messages[0].role, Role::System);
+        assert_eq!(messages[1].role, Role::User);
+        assert_eq!(messages[2].role, Role::Assistant);
+        assert_eq!(messages.last().unwrap().role, Role::User);
+    }
+
+    #[test]
+    fn test_failed_summary_backs_off() {
+        let mut compactor = Compactor::new(CompactionConfig::for_context_window(100_000));
+        assert!(compactor.summary_due(80_000));
+
+        compactor.record_failure(80_000);
+        assert!(!compactor.summary_due(80_000));
+        assert!(!compactor.summary_due(89_999));
+        assert!(compactor.summary_due(90_000));
+
+        // A second failure in a row waits twice as long
+        compactor.record_failure(90_000);
+        assert!(!compactor.summary_due(109_999));
+        assert!(compactor.summary_due(110_000));
+    }
+
+    #[test]
+    fn test_nothing_to_summarize_when_short() {
+        let messages = conversation(1, "ok");
+        let config = CompactionConfig::for_context_window(10_000).with_keep_recent(10);
+        assert!(summary_range(&messages, &config).is_none());
+    }
+}
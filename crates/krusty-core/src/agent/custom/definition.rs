@@ -0,0 +1,275 @@
+//! Custom agent definitions and parsing
+
+use anyhow::{anyhow, Result};
+use serde::Deserialize;
+use std::path::PathBuf;
+
+use crate::agent::constants::subagent;
+
+/// Tools an agent gets when its file doesn't list any
+const DEFAULT_TOOLS: &[&str] = &["read", "glob", "grep"];
+
+/// Default wall-clock limit for one delegated run
+const DEFAULT_TIMEOUT_SECS: u64 = 300;
+
+/// Tools never exposed to custom agents
+///
+/// These are either answered by the UI (questions, plan tracking) or would
+/// spawn nested agents.
+const RESERVED_TOOLS: &[&str] = &[
+    "delegate",
+    "explore",
+    "build",
+    "AskUserQuestion",
+    "task_start",
+    "task_complete",
+    "add_subtask",
+    "set_dependency",
+    "enter_plan_mode",
+];
+
+/// Where the agent definition comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AgentSource {
+    /// Global agents from ~/.krusty/agents/
+    Global,
+    /// Project-specific agents from .krusty/agents/
+    Project,
+}
+
+/// Tool allowlist, either a YAML list or a comma-separated string
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub(crate) enum ToolList {
+    List(Vec<String>),
+    Csv(String),
+}
+
+impl ToolList {
+    pub(crate) fn into_vec(self) -> Vec<String> {
+        match self {
+            Self::List(tools) => tools,
+            Self::Csv(tools) => tools.split(',').map(|t| t.trim().to_string()).collect(),
+        }
+    }
+}
+
+/// YAML frontmatter from an agent file
+#[derive(Debug, Clone, Deserialize)]
+struct AgentFrontmatter {
+    #[serde(default)]
+    name: Option<String>,
+    description: String,
+    #[serde(default)]
+    tools: Option<ToolList>,
+    #[serde(default)]
+    model: Option<String>,
+    #[serde(default)]
+    max_turns: Option<usize>,
+    #[serde(default)]
+    timeout: Option<u64>,
+}
+
+/// A named sub-agent defined in markdown
+#[derive(Debug, Clone)]
+pub struct AgentDefinition {
+    pub name: String,
+    pub description: String,
+    /// Allowed tool names; a trailing `*` matches by prefix (e.g. `mcp__github_*`)
+    pub tools: Vec<String>,
+    /// Model override (must be served by the active provider)
+    pub model: Option<String>,
+    pub max_turns: usize,
+    /// Wall-clock limit for one run, in seconds
+    pub timeout_secs: u64,
+    pub source: AgentSource,
+    /// Path to the agent file
+    pub path: PathBuf,
+    /// Markdown body, used as the system prompt
+    pub system_prompt: String,
+}
+
+impl AgentDefinition {
+    /// Parse an agent file
+    ///
+    /// `name` in the frontmatter is optional and defaults to the file stem.
+    pub fn parse(content: &str, path: PathBuf, source: AgentSource) -> Result<Self> {
+        let (frontmatter, body) = parse_frontmatter(content)?;
+
+        let name = match frontmatter.name {
+            Some(name) => name,
+            None => path
+                .file_stem()
+                .map(|s| s.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+        };
+        if name.is_empty()
+            || !name
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+        {
+            return Err(anyhow!(
+                "Agent name must contain only lowercase letters, numbers, and hyphens"
+            ));
+        }
+        if frontmatter.description.is_empty() {
+            return Err(anyhow!("Agent description cannot be empty"));
+        }
+        if body.is_empty() {
+            return Err(anyhow!(
+                "Agent file needs a system prompt after the frontmatter"
+            ));
+        }
+
+        let tools = frontmatter
+            .tools
+            .map(ToolList::into_vec)
+            .unwrap_or_else(|| DEFAULT_TOOLS.iter().map(|t| t.to_string()).collect())
+            .into_iter()
+            .filter(|t| !t.is_empty())
+            .collect();
+
+        Ok(Self {
+            name,
+            description: frontmatter.description,
+            tools,
+            model: frontmatter.model.filter(|m| !m.is_empty()),
+            max_turns: frontmatter.max_turns.unwrap_or(subagent::MAX_TURNS).max(1),
+            timeout_secs: frontmatter.timeout.unwrap_or(DEFAULT_TIMEOUT_SECS).max(1),
+            source,
+            path,
+            system_prompt: body,
+        })
+    }
+
+    /// Whether the agent may call the given tool
+    pub fn allows_tool(&self, tool_name: &str) -> bool {
+        if RESERVED_TOOLS.contains(&tool_name) {
+            return false;
+        }
+        self.tools
+            .iter()
+            .any(|pattern| tool_matches(pattern, tool_name))
+    }
+}
+
+/// Match a tool name against an allowlist entry; a trailing `*` matches by prefix
+pub(crate) fn tool_matches(pattern: &str, tool_name: &str) -> bool {
+    match pattern.strip_suffix('*') {
+        Some(prefix) => tool_name.starts_with(prefix),
+        None => pattern == tool_name,
+    }
+}
+
+/// Split YAML frontmatter from the markdown body
+fn parse_frontmatter(content: &str) -> Result<(AgentFrontmatter, String)> {
+    let content = content.trim();
+
+    if !content.starts_with("---") {
+        return Err(anyhow!("Agent file must start with YAML frontmatter (---)"));
+    }
+
+    let rest = &content[3..];
+    let end_pos = rest
+        .find("\n---")
+        .ok_or_else(|| anyhow!("Missing closing frontmatter delimiter (---)"))?;
+
+    let yaml_content = rest[..end_pos].trim();
+    let body = rest[end_pos + 4..].trim();
+
+    let frontmatter: AgentFrontmatter = serde_yaml::from_str(yaml_content)
+        .map_err(|e| anyhow!("Failed to parse agent frontmatter: {}", e))?;
+
+    Ok((frontmatter, body.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_agent() {
+        let content = r#"---
+name: reviewer
+description: Reviews changes for bugs
+tools: [read, grep, "mcp__github_*"]
+model: claude-haiku-4-5
+max_turns: 12
+timeout: 120
+---
+
+You are a careful code reviewer.
+"#;
+
+        let agent = AgentDefinition::parse(
+            content,
+            PathBuf::from("/a/reviewer.md"),
+            AgentSource::Global,
+        )
+        .unwrap();
+        assert_eq!(agent.name, "reviewer");
+        assert_eq!(agent.model.as_deref(), Some("claude-haiku-4-5"));
+        assert_eq!(agent.max_turns, 12);
+        assert_eq!(agent.timeout_secs, 120);
+        assert_eq!(agent.system_prompt, "You are a careful code reviewer.");
+
+        assert!(agent.allows_tool("read"));
+        assert!(agent.allows_tool("mcp__github_list_prs"));
+        assert!(!agent.allows_tool("write"));
+        assert!(!agent.allows_tool("glob"));
+    }
+
+    #[test]
+    fn test_defaults_and_csv_tools() {
+        let content = "---\ndescription: Writes tests\ntools: read, write, bash\n---\nWrite tests.";
+        let agent = AgentDefinition::parse(
+            content,
+            PathBuf::from("/a/test-writer.md"),
+            AgentSource::Project,
+        )
+        .unwrap();
+        assert_eq!(agent.name, "test-writer");
+        assert_eq!(agent.tools, vec!["read", "write", "bash"]);
+        assert_eq!(agent.max_turns, subagent::MAX_TURNS);
+        assert_eq!(agent.timeout_secs, DEFAULT_TIMEOUT_SECS);
+
+        let minimal = "---\ndescription: Looks around\n---\nExplore.";
+        let agent =
+            AgentDefinition::parse(minimal, PathBuf::from("/a/scout.md"), AgentSource::Global)
+                .unwrap();
+        assert!(agent.allows_tool("grep"));
+        assert!(!agent.allows_tool("bash"));
+    }
+
+    #[test]
+    fn test_reserved_tools_never_allowed() {
+        let content = "---\ndescription: Everything\ntools: [\"*\"]\n---\nDo it all.";
+        let agent =
+            AgentDefinition::parse(content, PathBuf::from("/a/all.md"), AgentSource::Global)
+                .unwrap();
+        assert!(agent.allows_tool("bash"));
+        assert!(!agent.allows_tool("delegate"));
+        assert!(!agent.allows_tool("AskUserQuestion"));
+    }
+
+    #[test]
+    fn test_invalid_agents() {
+        let bad_name = "---\nname: Bad Name\ndescription: x\n---\nBody";
+        assert!(
+            AgentDefinition::parse(bad_name, PathBuf::from("/a/x.md"), AgentSource::Global)
+                .is_err()
+        );
+
+        let no_body = "---\ndescription: x\n---\n";
+        assert!(
+            AgentDefinition::parse(no_body, PathBuf::from("/a/x.md"), AgentSource::Global).is_err()
+        );
+
+        assert!(AgentDefinition::parse(
+            "You are an agent.",
+            PathBuf::from("/a/x.md"),
+            AgentSource::Global
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,165 @@
+//! Agents manager - discovery and lookup of custom agent definitions
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::{debug, info};
+
+use super::definition::{AgentDefinition, AgentSource};
+
+/// Manages custom agent discovery and access
+pub struct AgentsManager {
+    /// Global agents directory (~/.krusty/agents/)
+    global_dir: PathBuf,
+    /// Project-specific agents directory (.krusty/agents/)
+    project_dir: Option<PathBuf>,
+    /// Cached agents (name -> definition)
+    cache: HashMap<String, AgentDefinition>,
+    /// Whether cache is populated
+    cache_valid: bool,
+}
+
+impl AgentsManager {
+    /// Create a new AgentsManager
+    ///
+    /// Project agents override global agents with the same name, as with skills.
+    pub fn new(global_dir: PathBuf, project_dir: Option<PathBuf>) -> Self {
+        Self {
+            global_dir,
+            project_dir,
+            cache: HashMap::new(),
+            cache_valid: false,
+        }
+    }
+
+    /// Refresh the agents cache
+    pub fn refresh(&mut self) {
+        self.cache.clear();
+
+        // Load project agents first (highest priority)
+        if let Some(ref project_dir) = self.project_dir {
+            for agent in load_agents_from_dir(project_dir, AgentSource::Project) {
+                self.cache.insert(agent.name.clone(), agent);
+            }
+        }
+
+        // Load global agents (don't override project agents)
+        for agent in load_agents_from_dir(&self.global_dir, AgentSource::Global) {
+            self.cache.entry(agent.name.clone()).or_insert(agent);
+        }
+
+        self.cache_valid = true;
+        info!("Loaded {} custom agents", self.cache.len());
+    }
+
+    /// Ensure cache is populated
+    fn ensure_cache(&mut self) {
+        if !self.cache_valid {
+            self.refresh();
+        }
+    }
+
+    /// List all available agents, sorted by name
+    pub fn list_agents(&mut self) -> Vec<AgentDefinition> {
+        self.ensure_cache();
+        let mut agents: Vec<AgentDefinition> = self.cache.values().cloned().collect();
+        agents.sort_by(|a, b| a.name.cmp(&b.name));
+        agents
+    }
+
+    /// Get an agent by name
+    pub fn get_agent(&mut self, name: &str) -> Option<&AgentDefinition> {
+        self.ensure_cache();
+        self.cache.get(name)
+    }
+
+    /// Get the global agents directory path
+    pub fn global_dir(&self) -> &PathBuf {
+        &self.global_dir
+    }
+
+    /// Get the project agents directory path
+    pub fn project_dir(&self) -> Option<&PathBuf> {
+        self.project_dir.as_ref()
+    }
+}
+
+/// Load all `*.md` agent files from a directory
+fn load_agents_from_dir(dir: &Path, source: AgentSource) -> Vec<AgentDefinition> {
+    let mut agents = Vec::new();
+
+    let Ok(entries) = fs::read_dir(dir) else {
+        return agents;
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if !path.is_file() || path.extension().is_none_or(|ext| ext != "md") {
+            continue;
+        }
+
+        let content = match fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(e) => {
+                debug!("Failed to read agent file {:?}: {}", path, e);
+                continue;
+            }
+        };
+
+        match AgentDefinition::parse(&content, path.clone(), source) {
+            Ok(agent) => {
+                debug!("Loaded agent: {} from {:?}", agent.name, path);
+                agents.push(agent);
+            }
+            Err(e) => {
+                debug!("Failed to load agent from {:?}: {}", path, e);
+            }
+        }
+    }
+
+    agents.sort_by(|a, b| a.name.cmp(&b.name));
+    agents
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_project_overrides_global() {
+        let temp = tempdir().unwrap();
+        let global_dir = temp.path().join("global");
+        let project_dir = temp.path().join("project");
+
+        for (dir, desc) in [
+            (&global_dir, "Global version"),
+            (&project_dir, "Project version"),
+        ] {
+            std::fs::create_dir_all(dir).unwrap();
+            std::fs::write(
+                dir.join("reviewer.md"),
+                format!("---\ndescription: {}\n---\nReview the code.", desc),
+            )
+            .unwrap();
+        }
+        std::fs::write(
+            global_dir.join("test-writer.md"),
+            "---\ndescription: Writes tests\n---\nWrite tests.",
+        )
+        .unwrap();
+        // Not markdown, and an invalid file: both skipped
+        std::fs::write(global_dir.join("notes.txt"), "ignored").unwrap();
+        std::fs::write(global_dir.join("broken.md"), "no frontmatter").unwrap();
+
+        let mut manager = AgentsManager::new(global_dir, Some(project_dir));
+
+        let names: Vec<String> = manager.list_agents().into_iter().map(|a| a.name).collect();
+        assert_eq!(names, vec!["reviewer", "test-writer"]);
+
+        let reviewer = manager.get_agent("reviewer").unwrap();
+        assert_eq!(reviewer.description, "Project version");
+        assert_eq!(reviewer.source, AgentSource::Project);
+        assert!(manager.get_agent("broken").is_none());
+    }
+}
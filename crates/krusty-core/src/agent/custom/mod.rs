@@ -0,0 +1,27 @@
+//! User-defined sub-agents
+//!
+//! Named agents are defined as markdown files in two locations:
+//! - Global: `~/.krusty/agents/*.md`
+//! - Project: `.krusty/agents/*.md` (overrides global agents with the same name)
+//!
+//! ```yaml
+//! ---
+//! name: reviewer            # optional, defaults to the file name
+//! description: Reviews diffs for bugs and style issues
+//! tools: [read, glob, grep, bash, "mcp__github_*"]
+//! model: claude-sonnet-4-5  # optional, defaults to the current model
+//! max_turns: 20
+//! timeout: 300              # seconds for the whole run
+//! ---
+//!
+//! You are a code reviewer. [system prompt...]
+//! ```
+//!
+//! The `delegate` tool runs them through the sub-agent loop.
+
+mod definition;
+mod manager;
+
+pub(crate) use definition::{tool_matches, ToolList};
+pub use definition::{AgentDefinition, AgentSource};
+pub use manager::AgentsManager;
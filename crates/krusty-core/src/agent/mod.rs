@@ -16,19 +16,25 @@
 //! ## Pinch (Context Continuation)
 //! - `PinchContext` - Structured context for session transitions
 //! - `SummarizationResult` - Output from summarization agent
+//! - `compaction` - In-place compaction of long agent loops
 //!
 //! ## Sub-agents
 //! - `SubAgentPool` - Concurrent execution of lightweight agents
 //! - `SubAgentTask` - Task configuration for sub-agents
+//! - `AgentsManager` - User-defined agents from `.krusty/agents/*.md`
 //!
 //! ## Builder Swarm (Octopod)
 //! - `SharedBuildContext` - Coordination for builder agents
 //! - Type registry, file locks, conventions
+//! - `WorktreeSwarm` - Optional per-builder git worktrees with merge-back
 
 pub mod build_context;
+pub mod build_isolation;
 pub mod cache;
 pub mod cancellation;
+pub mod compaction;
 pub mod constants;
+pub mod custom;
 pub mod event_bus;
 pub mod events;
 pub mod hooks;
@@ -40,6 +46,8 @@ pub mod user_hooks;
 
 pub use build_context::SharedBuildContext;
 pub use cancellation::AgentCancellation;
+pub use compaction::{CompactionConfig, CompactionReport, CompactionThresholds, Compactor};
+pub use custom::{AgentDefinition, AgentsManager};
 pub use event_bus::AgentEventBus;
 pub use events::{AgentEvent, InterruptReason};
 pub use hooks::{LoggingHook, PlanModeHook, SafetyHook};
@@ -0,0 +1,275 @@
+//! Per-agent control of running sub-agents
+//!
+//! Each agent a pool runs gets its own cancellation token (a child of the
+//! pool's), registered in `AgentControls` under its task ID with an inbox.
+//! Stopping an agent cancels only that token. Notes sent to the inbox are
+//! added to the agent's conversation before its next turn. A restart stops
+//! the current attempt and runs the agent again with a new prompt, in the
+//! same slot of the pool run.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tokio_util::sync::CancellationToken;
+use tracing::info;
+
+use super::types::{SubAgentResult, SubAgentTask};
+
+/// Notes waiting for an agent's next turn
+#[derive(Debug, Default)]
+pub struct AgentInbox {
+    notes: Mutex<Vec<String>>,
+}
+
+impl AgentInbox {
+    fn push(&self, note: String) {
+        if let Ok(mut notes) = self.notes.lock() {
+            notes.push(note);
+        }
+    }
+
+    /// Take all pending notes
+    pub(crate) fn take(&self) -> Vec<String> {
+        self.notes
+            .lock()
+            .map(|mut notes| std::mem::take(&mut *notes))
+            .unwrap_or_default()
+    }
+}
+
+/// A running attempt of one agent
+struct ControlledAgent {
+    prompt: String,
+    cancel: CancellationToken,
+    inbox: Arc<AgentInbox>,
+    /// Prompt to run again with once the current attempt stops
+    restart: Option<String>,
+}
+
+/// Stop, restart or send notes to the agents of one pool run
+#[derive(Clone, Default)]
+pub struct AgentControls {
+    agents: Arc<Mutex<HashMap<String, ControlledAgent>>>,
+}
+
+impl AgentControls {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether any agent is still running
+    pub fn has_running(&self) -> bool {
+        self.agents.lock().map(|a| !a.is_empty()).unwrap_or(false)
+    }
+
+    /// Whether this agent is still running
+    pub fn is_running(&self, task_id: &str) -> bool {
+        self.agents
+            .lock()
+            .map(|a| a.contains_key(task_id))
+            .unwrap_or(false)
+    }
+
+    /// Prompt the agent's current attempt is working on
+    pub fn prompt(&self, task_id: &str) -> Option<String> {
+        let agents = self.agents.lock().ok()?;
+        agents.get(task_id).map(|a| a.prompt.clone())
+    }
+
+    /// Stop one agent; returns false if it is not running
+    pub fn stop(&self, task_id: &str) -> bool {
+        self.with_agent(task_id, |agent| {
+            agent.restart = None;
+            agent.cancel.cancel();
+        })
+    }
+
+    /// Queue a note for the agent's next turn; returns false if it is not running
+    pub fn send_note(&self, task_id: &str, note: impl Into<String>) -> bool {
+        let note = note.into();
+        self.with_agent(task_id, |agent| agent.inbox.push(note))
+    }
+
+    /// Stop the agent and run it again with `prompt`; returns false if it is not running
+    pub fn restart(&self, task_id: &str, prompt: impl Into<String>) -> bool {
+        let prompt = prompt.into();
+        self.with_agent(task_id, |agent| {
+            agent.restart = Some(prompt);
+            agent.cancel.cancel();
+        })
+    }
+
+    fn with_agent(&self, task_id: &str, f: impl FnOnce(&mut ControlledAgent)) -> bool {
+        let Ok(mut agents) = self.agents.lock() else {
+            return false;
+        };
+        match agents.get_mut(task_id) {
+            Some(agent) => {
+                f(agent);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Register an attempt of an agent and return its inbox
+    fn start(&self, task: &SubAgentTask, cancel: CancellationToken) -> Arc<AgentInbox> {
+        let inbox = Arc::new(AgentInbox::default());
+        if let Ok(mut agents) = self.agents.lock() {
+            agents.insert(
+                task.id.clone(),
+                ControlledAgent {
+                    prompt: task.prompt.clone(),
+                    cancel,
+                    inbox: inbox.clone(),
+                    restart: None,
+                },
+            );
+        }
+        inbox
+    }
+
+    /// The restart prompt if one was asked for, otherwise unregister the agent
+    fn finish(&self, task_id: &str) -> Option<String> {
+        let mut agents = self.agents.lock().ok()?;
+        let restart = agents.get_mut(task_id).and_then(|a| a.restart.take());
+        if restart.is_none() {
+            agents.remove(task_id);
+        }
+        restart
+    }
+}
+
+/// Run one agent, running it again each time it is restarted
+///
+/// Without `controls` the agent runs once on `cancel`. With them, each
+/// attempt gets a child token of `cancel`, so cancelling the whole pool
+/// still stops it.
+pub(crate) async fn run_controlled<F, Fut>(
+    controls: Option<&AgentControls>,
+    mut task: SubAgentTask,
+    cancel: CancellationToken,
+    mut run: F,
+) -> SubAgentResult
+where
+    F: FnMut(SubAgentTask, CancellationToken, Option<Arc<AgentInbox>>) -> Fut,
+    Fut: Future<Output = SubAgentResult>,
+{
+    let Some(controls) = controls else {
+        return run(task, cancel, None).await;
+    };
+
+    loop {
+        let attempt = cancel.child_token();
+        let inbox = controls.start(&task, attempt.clone());
+        let result = run(task.clone(), attempt, Some(inbox)).await;
+        match controls.finish(&task.id) {
+            Some(prompt) if !cancel.is_cancelled() => {
+                info!(task_id = %task.id, "Restarting sub-agent with a new prompt");
+                task.prompt = prompt;
+            }
+            _ => return result,
+        }
+    }
+}
+
+/// Run an agent until it finishes or `limit` passes
+///
+/// At the deadline `cancel` is cancelled and the run is still awaited, so the
+/// agent's controls are released and its outcome recorded. Returns the result
+/// and whether the deadline was hit.
+pub(crate) async fn run_with_deadline<Fut>(
+    run: Fut,
+    cancel: &CancellationToken,
+    limit: Duration,
+) -> (SubAgentResult, bool)
+where
+    Fut: Future<Output = SubAgentResult>,
+{
+    tokio::pin!(run);
+    tokio::select! {
+        result = &mut run => (result, false),
+        _ = tokio::time::sleep(limit) => {
+            cancel.cancel();
+            (run.await, true)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(task: &SubAgentTask, cancelled: bool) -> SubAgentResult {
+        SubAgentResult {
+            task_id: task.id.clone(),
+            success: !cancelled,
+            output: task.prompt.clone(),
+            files_examined: vec![],
+            duration_ms: 0,
+            turns_used: 1,
+            error: cancelled.then(|| "Cancelled".to_string()),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_restart_runs_again_and_stop_ends() {
+        let controls = AgentControls::new();
+        let task = SubAgentTask::new("builder-0", "first prompt");
+        let mut attempts = 0;
+
+        let result = run_controlled(
+            Some(&controls),
+            task,
+            CancellationToken::new(),
+            |task, cancel, inbox| {
+                attempts += 1;
+                let controls = controls.clone();
+                async move {
+                    let inbox = inbox.unwrap();
+                    if task.prompt == "first prompt" {
+                        assert!(controls.send_note("builder-0", "look at lib.rs"));
+                        assert_eq!(inbox.take(), vec!["look at lib.rs".to_string()]);
+                        assert!(controls.restart("builder-0", "second prompt"));
+                    } else {
+                        assert_eq!(controls.prompt("builder-0").unwrap(), "second prompt");
+                        assert!(controls.stop("builder-0"));
+                    }
+                    result(&task, cancel.is_cancelled())
+                }
+            },
+        )
+        .await;
+
+        assert_eq!(attempts, 2);
+        assert_eq!(result.output, "second prompt");
+        assert_eq!(result.error.as_deref(), Some("Cancelled"));
+        assert!(!controls.has_running());
+        assert!(!controls.stop("builder-0"));
+    }
+
+    #[tokio::test]
+    async fn test_deadline_cancels_and_releases_controls() {
+        let controls = AgentControls::new();
+        let task = SubAgentTask::new("custom-0", "never finishes");
+        let cancel = CancellationToken::new();
+
+        let run = run_controlled(Some(&controls), task, cancel.clone(), |task, cancel, _| {
+            let controls = controls.clone();
+            async move {
+                assert!(controls.is_running("custom-0"));
+                cancel.cancelled().await;
+                result(&task, true)
+            }
+        });
+        let (result, timed_out) = run_with_deadline(run, &cancel, Duration::from_millis(20)).await;
+
+        assert!(timed_out);
+        assert!(cancel.is_cancelled());
+        assert_eq!(result.error.as_deref(), Some("Cancelled"));
+        assert!(!controls.has_running());
+        assert!(!controls.stop("custom-0"));
+    }
+}
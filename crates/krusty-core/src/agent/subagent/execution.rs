@@ -1,6 +1,6 @@
 //! Sub-agent execution loop
 //!
-//! Unified agentic loop for both explorer and builder agents.
+//! Unified agentic loop for explorer, builder and user-defined agents.
 
 use serde_json::{json, Value};
 use std::sync::Arc;
@@ -11,13 +11,18 @@ use tracing::{info, warn};
 
 use crate::agent::build_context::SharedBuildContext;
 use crate::agent::cache::SharedExploreCache;
+use crate::agent::compaction::{CompactionConfig, CompactionThresholds, Compactor};
 use crate::agent::constants::subagent;
+use crate::agent::custom::AgentDefinition;
 use crate::ai::client::AiClient;
 use crate::ai::retry::{with_retry, RetryConfig};
 use crate::ai::types::{AiTool, Content, ModelMessage, Role};
-use crate::tools::registry::{ToolContext, ToolResult};
+use crate::tools::file_changes::{self, SharedFileChangeSink};
+use crate::tools::registry::{ToolContext, ToolRegistry, ToolResult};
 
+use super::control::AgentInbox;
 use super::tools::{BuilderTools, SubAgentTools};
+use super::transcript::{SharedTranscriptSink, TranscriptEntry, TranscriptKind};
 use super::types::{
     AgentProgress, AgentProgressStatus, SubAgentApiError, SubAgentResult, SubAgentTask, ToolCall,
 };
@@ -39,6 +44,11 @@ pub(crate) trait AgentConfig: Send + Sync {
     /// Max tokens for API calls
     fn max_tokens(&self) -> usize;
 
+    /// Turn limit before forcing completion
+    fn max_turns(&self) -> usize {
+        subagent::MAX_TURNS
+    }
+
     /// Get tool definitions for AI
     fn get_ai_tools(&self) -> Vec<AiTool>;
 
@@ -226,6 +236,147 @@ impl AgentConfig for BuilderConfig {
     }
 }
 
+/// Custom agent configuration - user-defined prompt and tool allowlist
+pub(crate) struct CustomAgentConfig {
+    system_prompt: String,
+    max_turns: usize,
+    timeout_secs: u64,
+    tools: Vec<AiTool>,
+    registry: Arc<ToolRegistry>,
+    /// Parent tool context (sandbox, plan mode, managers, sinks) passed on to tools
+    parent: ToolContext,
+}
+
+impl CustomAgentConfig {
+    pub async fn new(
+        task: &SubAgentTask,
+        definition: &AgentDefinition,
+        registry: Arc<ToolRegistry>,
+        parent: &ToolContext,
+    ) -> Self {
+        let mut tools: Vec<AiTool> = registry
+            .get_ai_tools()
+            .await
+            .into_iter()
+            .filter(|t| definition.allows_tool(&t.name))
+            .collect();
+        tools.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Self {
+            system_prompt: format!(
+                "{}\n\n## Working Directory\n{}",
+                definition.system_prompt,
+                task.working_dir.display()
+            ),
+            max_turns: definition.max_turns,
+            timeout_secs: definition.timeout_secs,
+            tools,
+            registry,
+            parent: parent.inherit(),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl AgentConfig for CustomAgentConfig {
+    fn system_prompt(&self, _turn: usize) -> String {
+        self.system_prompt.clone()
+    }
+
+    fn timeout_secs(&self) -> u64 {
+        self.timeout_secs
+    }
+
+    fn api_call_timeout(&self) -> Duration {
+        crate::agent::constants::timeouts::BUILDER_API_CALL
+    }
+
+    fn max_tokens(&self) -> usize {
+        16384
+    }
+
+    fn max_turns(&self) -> usize {
+        self.max_turns
+    }
+
+    fn get_ai_tools(&self) -> Vec<AiTool> {
+        self.tools.clone()
+    }
+
+    async fn execute_tool(
+        &self,
+        name: &str,
+        params: Value,
+        ctx: &ToolContext,
+    ) -> Option<ToolResult> {
+        // The allowlist is enforced here too, not just in the advertised tools
+        if !self.tools.iter().any(|t| t.name == name) {
+            return None;
+        }
+        let mut tool_ctx = self.parent.inherit();
+        tool_ctx.working_dir = ctx.working_dir.clone();
+        tool_ctx.timeout = ctx.timeout;
+        self.registry.execute(name, params, &tool_ctx).await
+    }
+
+    fn update_progress(&self, _progress: &mut AgentProgress) {
+        // Tools run through the registry; nothing agent-specific to report
+    }
+
+    fn cleanup(&self) {
+        // No shared state to release
+    }
+}
+
+/// Where a running agent reports to and takes notes from
+#[derive(Clone, Default)]
+pub(crate) struct AgentLinks {
+    pub progress_tx: Option<mpsc::UnboundedSender<AgentProgress>>,
+    pub transcript: Option<SharedTranscriptSink>,
+    pub inbox: Option<Arc<AgentInbox>>,
+    pub file_changes: Option<SharedFileChangeSink>,
+}
+
+impl AgentLinks {
+    pub fn with_inbox(&self, inbox: Option<Arc<AgentInbox>>) -> Self {
+        Self {
+            inbox,
+            ..self.clone()
+        }
+    }
+}
+
+/// Writes a run's steps to its transcript sink, if it has one
+struct Recorder<'a> {
+    sink: Option<SharedTranscriptSink>,
+    task: &'a SubAgentTask,
+    model: &'a str,
+}
+
+impl Recorder<'_> {
+    fn record(
+        &self,
+        turn: usize,
+        kind: TranscriptKind,
+        tool_name: Option<&str>,
+        content: String,
+        is_error: bool,
+    ) {
+        if let Some(ref sink) = self.sink {
+            sink.record(TranscriptEntry {
+                task_id: self.task.id.clone(),
+                agent_name: self.task.name.clone(),
+                model: self.model.to_string(),
+                turn,
+                kind,
+                tool_name: tool_name.map(str::to_string),
+                content,
+                is_error,
+            });
+        }
+    }
+}
+
 /// Unified agentic loop - replaces separate explorer/builder implementations
 pub(crate) async fn execute_agent_loop<C: AgentConfig>(
     client: &AiClient,
@@ -233,8 +384,46 @@ pub(crate) async fn execute_agent_loop<C: AgentConfig>(
     model: &str,
     cancellation: CancellationToken,
     config: &C,
-    progress_tx: Option<mpsc::UnboundedSender<AgentProgress>>,
+    links: AgentLinks,
+) -> SubAgentResult {
+    let recorder = Recorder {
+        sink: links.transcript.clone(),
+        task,
+        model,
+    };
+    recorder.record(0, TranscriptKind::Prompt, None, task.prompt.clone(), false);
+
+    let result = run_agent_loop(client, task, model, cancellation, config, links, &recorder).await;
+
+    let outcome = match result.error {
+        Some(ref e) => e.clone(),
+        None => format!("Completed in {} turns", result.turns_used),
+    };
+    recorder.record(
+        result.turns_used,
+        TranscriptKind::Outcome,
+        None,
+        outcome,
+        !result.success,
+    );
+    result
+}
+
+async fn run_agent_loop<C: AgentConfig>(
+    client: &AiClient,
+    task: &SubAgentTask,
+    model: &str,
+    cancellation: CancellationToken,
+    config: &C,
+    links: AgentLinks,
+    recorder: &Recorder<'_>,
 ) -> SubAgentResult {
+    let AgentLinks {
+        progress_tx,
+        inbox,
+        file_changes,
+        ..
+    } = links;
     let start = Instant::now();
     let task_id = task.id.clone();
     let task_name = task.name.clone();
@@ -244,9 +433,14 @@ pub(crate) async fn execute_agent_loop<C: AgentConfig>(
 
     let ctx = ToolContext {
         working_dir: task.working_dir.clone(),
+        sandbox_root: task.sandbox_root.clone(),
         timeout: Some(Duration::from_secs(config.timeout_secs())),
         ..Default::default()
     };
+    // Files in a builder's worktree only reach the project when merged back,
+    // which reports them (see `WorktreeSwarm::finish`), so only agents working
+    // in the project itself report their changes
+    let file_changes = file_changes.filter(|_| task.sandbox_root.is_none());
 
     let mut messages: Vec<ModelMessage> = vec![ModelMessage {
         role: Role::User,
@@ -255,6 +449,17 @@ pub(crate) async fn execute_agent_loop<C: AgentConfig>(
         }],
     }];
 
+    // The task prompt stays verbatim; older turns are compacted around it
+    let mut compactor = Compactor::new(
+        CompactionConfig::for_model_with(
+            client.provider_id(),
+            model,
+            &CompactionThresholds::load(),
+        )
+        .with_keep_first(true)
+        .with_keep_recent(8),
+    );
+
     let mut files_examined: Vec<String> = vec![];
     let mut turns = 0;
     let mut total_tool_calls = 0;
@@ -317,11 +522,11 @@ pub(crate) async fn execute_agent_loop<C: AgentConfig>(
         turns += 1;
 
         // Enforce max turn limit to prevent infinite loops
-        if turns > subagent::MAX_TURNS {
+        if turns > config.max_turns() {
             warn!(
                 task_id = %task_id,
                 turns = turns,
-                max_turns = subagent::MAX_TURNS,
+                max_turns = config.max_turns(),
                 "Sub-agent exceeded max turns, forcing completion"
             );
             send_progress(
@@ -343,6 +548,35 @@ pub(crate) async fn execute_agent_loop<C: AgentConfig>(
             };
         }
 
+        // Notes sent during the last turn join the pending user message
+        if let Some(notes) = inbox.as_ref().map(|inbox| inbox.take()) {
+            if !notes.is_empty() {
+                info!(task_id = %task_id, count = notes.len(), "Agent received notes");
+                for note in &notes {
+                    recorder.record(turns, TranscriptKind::Note, None, note.clone(), false);
+                }
+                let text = notes
+                    .iter()
+                    .map(|note| format!("[Note from the user] {}", note))
+                    .collect::<Vec<_>>()
+                    .join("\n\n");
+                match messages.last_mut() {
+                    Some(last) if last.role == Role::User => {
+                        last.content.push(Content::Text { text });
+                    }
+                    _ => messages.push(ModelMessage {
+                        role: Role::User,
+                        content: vec![Content::Text { text }],
+                    }),
+                }
+            }
+        }
+
+        // Summarize or trim older turns before the context fills up
+        if let Err(e) = compactor.compact(client, &mut messages).await {
+            warn!(task_id = %task_id, "Context compaction failed: {}", e);
+        }
+
         // Get system prompt (may be dynamic for builders)
         let system_prompt = config.system_prompt(turns);
 
@@ -432,6 +666,9 @@ pub(crate) async fn execute_agent_loop<C: AgentConfig>(
         }
 
         let (text_parts, tool_calls, stop_reason) = parse_response(&response);
+        for text in &text_parts {
+            recorder.record(turns, TranscriptKind::Text, None, text.clone(), false);
+        }
 
         if !text_parts.is_empty() {
             final_output = text_parts.join("\n");
@@ -490,6 +727,13 @@ pub(crate) async fn execute_agent_loop<C: AgentConfig>(
                 }
             }
 
+            recorder.record(
+                turns,
+                TranscriptKind::ToolCall,
+                Some(&tc.name),
+                tc.input.to_string(),
+                false,
+            );
             last_action = config.format_action(&tc.name, &tc.input);
             send_progress(
                 AgentProgressStatus::Running,
@@ -499,12 +743,27 @@ pub(crate) async fn execute_agent_loop<C: AgentConfig>(
                 config,
             );
 
+            let tracked = file_changes::before_tool(
+                file_changes.as_ref(),
+                &tc.name,
+                &tc.input,
+                &ctx.working_dir,
+            )
+            .await;
             let result = config.execute_tool(&tc.name, tc.input.clone(), &ctx).await;
+            file_changes::after_tool(tracked, result.as_ref().is_none_or(|r| r.is_error)).await;
 
             let (output, is_error) = match result {
                 Some(r) => (r.output, r.is_error),
                 None => (format!("Unknown tool: {}", tc.name), true),
             };
+            recorder.record(
+                turns,
+                TranscriptKind::ToolResult,
+                Some(&tc.name),
+                output.clone(),
+                is_error,
+            );
 
             tool_results.push(Content::ToolResult {
                 tool_use_id: tc.id.clone(),
@@ -713,57 +972,41 @@ Build your component, then summarize what you created with file paths."#,
     )
 }
 
-/// Execute a sub-agent with agentic tool loop (no progress reporting)
-pub(crate) async fn execute_subagent_with_tools(
+/// Execute an explorer agent
+pub(crate) async fn execute_explorer(
     client: &AiClient,
     task: SubAgentTask,
     model: &str,
     cancellation: CancellationToken,
     cache: Arc<SharedExploreCache>,
+    links: AgentLinks,
 ) -> SubAgentResult {
     tracing::debug!(task_id = %task.id, model = %model, "Starting sub-agent");
     let config = ExplorerConfig::new(task.clone(), cache);
-    execute_agent_loop(client, &task, model, cancellation, &config, None).await
+    execute_agent_loop(client, &task, model, cancellation, &config, links).await
 }
 
-/// Execute a sub-agent with progress reporting
-pub(crate) async fn execute_subagent_with_progress(
+/// Execute a builder agent
+pub(crate) async fn execute_builder(
     client: &AiClient,
     task: SubAgentTask,
     model: &str,
     cancellation: CancellationToken,
-    cache: Arc<SharedExploreCache>,
-    progress_tx: mpsc::UnboundedSender<AgentProgress>,
+    context: Arc<SharedBuildContext>,
+    links: AgentLinks,
 ) -> SubAgentResult {
-    let config = ExplorerConfig::new(task.clone(), cache);
-    execute_agent_loop(
-        client,
-        &task,
-        model,
-        cancellation,
-        &config,
-        Some(progress_tx),
-    )
-    .await
+    let config = BuilderConfig::new(task.clone(), context);
+    execute_agent_loop(client, &task, model, cancellation, &config, links).await
 }
 
-/// Execute a builder agent with progress reporting
-pub(crate) async fn execute_builder_with_progress(
+/// Execute a user-defined agent
+pub(crate) async fn execute_custom_agent(
     client: &AiClient,
     task: SubAgentTask,
     model: &str,
     cancellation: CancellationToken,
-    context: Arc<SharedBuildContext>,
-    progress_tx: mpsc::UnboundedSender<AgentProgress>,
+    config: &CustomAgentConfig,
+    links: AgentLinks,
 ) -> SubAgentResult {
-    let config = BuilderConfig::new(task.clone(), context);
-    execute_agent_loop(
-        client,
-        &task,
-        model,
-        cancellation,
-        &config,
-        Some(progress_tx),
-    )
-    .await
+    execute_agent_loop(client, &task, model, cancellation, config, links).await
 }
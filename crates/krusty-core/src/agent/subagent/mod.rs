@@ -1,20 +1,28 @@
 //! Sub-agent system for parallel task execution
 //!
 //! Enables spawning lightweight agents to explore the codebase.
-//! Sub-agents have read-only access: glob, grep, read.
+//! Explorers have read-only access: glob, grep, read.
 //! They cannot modify files or execute arbitrary commands.
+//! Builders and user-defined agents (see `agent::custom`) get the tools
+//! they are configured with.
 //!
 //! ## Provider-Agnostic Design
 //! Sub-agents use the user's current model by default. Set override_model
-//! when creating SubAgentPool to use the same model as the main agent.
+//! when creating SubAgentPool to use the same model as the main agent, or
+//! pass a client and model from the explore/build route (`ai::routing`) to
+//! run them on a different model or provider.
 //!
 //! ## Module Structure
 //! - `types`: Core data types (progress, models, tasks, results)
 //! - `tools`: Tool implementations for explorers and builders
 //! - `execution`: Agent loop and API communication
+//! - `transcript`: Per-agent record of every turn, for later inspection
+//! - `control`: Per-agent stop, restart and notes while a pool runs
 
+mod control;
 mod execution;
 mod tools;
+mod transcript;
 mod types;
 
 use std::sync::Arc;
@@ -32,18 +40,26 @@ const DEFAULT_STAGGER_MS: u64 = 100;
 
 use crate::agent::build_context::SharedBuildContext;
 use crate::agent::cache::SharedExploreCache;
+use crate::agent::custom::AgentDefinition;
 use crate::agent::AgentCancellation;
 use crate::ai::client::AiClient;
+use crate::tools::file_changes::SharedFileChangeSink;
+use crate::tools::registry::{ToolContext, ToolRegistry};
 
 // Re-export public types
+pub use control::AgentControls;
 pub use tools::BuilderTools;
+pub use transcript::{
+    MemoryTranscript, SharedTranscriptSink, TranscriptEntry, TranscriptKind, TranscriptSink,
+};
 pub use types::{
     AgentProgress, AgentProgressStatus, SubAgentApiError, SubAgentResult, SubAgentTask,
 };
 
 // Internal execution functions
+use control::{run_controlled, run_with_deadline};
 use execution::{
-    execute_builder_with_progress, execute_subagent_with_progress, execute_subagent_with_tools,
+    execute_builder, execute_custom_agent, execute_explorer, AgentLinks, CustomAgentConfig,
 };
 
 /// Pool for managing concurrent sub-agent execution
@@ -56,6 +72,12 @@ pub struct SubAgentPool {
     override_model: Option<String>,
     /// Delay between spawning agents (prevents rate limit storms)
     stagger_delay: Duration,
+    /// Where agents record their transcripts
+    transcript: Option<SharedTranscriptSink>,
+    /// Per-agent stop/restart/notes for the UI
+    controls: Option<AgentControls>,
+    /// Where agents report the files they touch
+    file_changes: Option<SharedFileChangeSink>,
 }
 
 impl SubAgentPool {
@@ -69,6 +91,9 @@ impl SubAgentPool {
             cache: Arc::new(SharedExploreCache::new()),
             override_model: None,
             stagger_delay: Duration::from_millis(DEFAULT_STAGGER_MS),
+            transcript: None,
+            controls: None,
+            file_changes: None,
         }
     }
 
@@ -92,6 +117,24 @@ impl SubAgentPool {
         self
     }
 
+    /// Record each agent's turns to a transcript sink
+    pub fn with_transcript(mut self, transcript: Option<SharedTranscriptSink>) -> Self {
+        self.transcript = transcript;
+        self
+    }
+
+    /// Report the files agents read, write and edit
+    pub fn with_file_changes(mut self, sink: Option<SharedFileChangeSink>) -> Self {
+        self.file_changes = sink;
+        self
+    }
+
+    /// Let agents be stopped, restarted or sent notes individually
+    pub fn with_controls(mut self, controls: Option<AgentControls>) -> Self {
+        self.controls = controls;
+        self
+    }
+
     /// Get the model to use for sub-agent tasks
     ///
     /// Returns the override_model (user's current model). This must be set
@@ -137,6 +180,12 @@ impl SubAgentPool {
             let cache = cache.clone();
             let task_id = task.id.clone();
             let resolved_model = self.resolve_model();
+            let links = AgentLinks {
+                transcript: self.transcript.clone(),
+                file_changes: self.file_changes.clone(),
+                ..Default::default()
+            };
+            let controls = self.controls.clone();
 
             let handle = tokio::spawn(async move {
                 debug!(task_id = %task_id, "SubAgent: Acquiring semaphore permit");
@@ -187,8 +236,17 @@ impl SubAgentPool {
 
                 info!(task_id = %task_id, model = %resolved_model, "SubAgent: Starting execution");
                 let result =
-                    execute_subagent_with_tools(&client, task, &resolved_model, cancel, cache)
-                        .await;
+                    run_controlled(controls.as_ref(), task, cancel, |task, cancel, inbox| {
+                        execute_explorer(
+                            &client,
+                            task,
+                            &resolved_model,
+                            cancel,
+                            cache.clone(),
+                            links.with_inbox(inbox),
+                        )
+                    })
+                    .await;
                 info!(task_id = %result.task_id, success = result.success, "SubAgent: Execution complete");
                 result
             });
@@ -261,8 +319,14 @@ impl SubAgentPool {
             let cancel = cancellation.child_token();
             let cache = cache.clone();
             let task_id = task.id.clone();
-            let progress_tx = progress_tx.clone();
             let resolved_model = self.resolve_model();
+            let links = AgentLinks {
+                progress_tx: Some(progress_tx.clone()),
+                transcript: self.transcript.clone(),
+                file_changes: self.file_changes.clone(),
+                inbox: None,
+            };
+            let controls = self.controls.clone();
 
             let handle = tokio::spawn(async move {
                 let _permit = match timeout(SEMAPHORE_TIMEOUT, sem.acquire()).await {
@@ -308,14 +372,16 @@ impl SubAgentPool {
                     };
                 }
 
-                execute_subagent_with_progress(
-                    &client,
-                    task,
-                    &resolved_model,
-                    cancel,
-                    cache,
-                    progress_tx,
-                )
+                run_controlled(controls.as_ref(), task, cancel, |task, cancel, inbox| {
+                    execute_explorer(
+                        &client,
+                        task,
+                        &resolved_model,
+                        cancel,
+                        cache.clone(),
+                        links.with_inbox(inbox),
+                    )
+                })
                 .await
             });
 
@@ -381,8 +447,14 @@ impl SubAgentPool {
             let cancel = cancellation.child_token();
             let context = context.clone();
             let task_id = task.id.clone();
-            let progress_tx = progress_tx.clone();
             let resolved_model = self.resolve_model();
+            let links = AgentLinks {
+                progress_tx: Some(progress_tx.clone()),
+                transcript: self.transcript.clone(),
+                file_changes: self.file_changes.clone(),
+                inbox: None,
+            };
+            let controls = self.controls.clone();
 
             let handle = tokio::spawn(async move {
                 let _permit = match timeout(SEMAPHORE_TIMEOUT, sem.acquire()).await {
@@ -428,14 +500,16 @@ impl SubAgentPool {
                     };
                 }
 
-                execute_builder_with_progress(
-                    &client,
-                    task,
-                    &resolved_model,
-                    cancel,
-                    context,
-                    progress_tx,
-                )
+                run_controlled(controls.as_ref(), task, cancel, |task, cancel, inbox| {
+                    execute_builder(
+                        &client,
+                        task,
+                        &resolved_model,
+                        cancel,
+                        context.clone(),
+                        links.with_inbox(inbox),
+                    )
+                })
                 .await
             });
 
@@ -466,4 +540,61 @@ impl SubAgentPool {
         info!("SubAgentPool: Builders complete | {}", stats);
         results
     }
+
+    /// Run a user-defined agent with tools from the registry
+    ///
+    /// Uses the definition's model if set, and its timeout for the whole run.
+    /// Progress goes to the parent context's explore channel, if any.
+    pub async fn execute_custom(
+        &self,
+        task: SubAgentTask,
+        definition: &AgentDefinition,
+        registry: Arc<ToolRegistry>,
+        parent_ctx: &ToolContext,
+    ) -> SubAgentResult {
+        let task_id = task.id.clone();
+        let model = definition
+            .model
+            .clone()
+            .unwrap_or_else(|| self.resolve_model());
+        let config = CustomAgentConfig::new(&task, definition, registry, parent_ctx).await;
+        let cancel = self.cancellation.child_token();
+        let limit = Duration::from_secs(definition.timeout_secs);
+
+        info!(
+            task_id = %task_id,
+            agent = %definition.name,
+            model = %model,
+            "SubAgentPool: Running custom agent"
+        );
+
+        let links = AgentLinks {
+            progress_tx: parent_ctx.explore_progress_tx.clone(),
+            transcript: self.transcript.clone(),
+            file_changes: self.file_changes.clone(),
+            inbox: None,
+        };
+        let run = run_controlled(
+            self.controls.as_ref(),
+            task,
+            cancel.clone(),
+            |task, cancel, inbox| {
+                execute_custom_agent(
+                    &self.client,
+                    task,
+                    &model,
+                    cancel,
+                    &config,
+                    links.with_inbox(inbox),
+                )
+            },
+        );
+        let (mut result, timed_out) = run_with_deadline(run, &cancel, limit).await;
+        if timed_out {
+            warn!(task_id = %task_id, agent = %definition.name, "Custom agent timed out");
+            result.success = false;
+            result.error = Some(format!("Timed out after {}s", definition.timeout_secs));
+        }
+        result
+    }
 }
@@ -0,0 +1,112 @@
+//! Sub-agent transcripts
+//!
+//! The agent loop prunes old turns to keep its context small, and only the
+//! final `SubAgentResult` is handed back. To see why an agent did something,
+//! the loop writes every prompt, reply, tool call and tool result to a
+//! `TranscriptSink` as it happens. `storage::StoredTranscriptSink` keeps
+//! them in SQLite under the parent session and tool call.
+
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+/// What a transcript entry records
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TranscriptKind {
+    /// The task prompt the agent was given
+    Prompt,
+    /// Text the agent wrote
+    Text,
+    /// A tool call; `content` is the JSON input
+    ToolCall,
+    /// A tool result
+    ToolResult,
+    /// A note sent to the agent while it ran
+    Note,
+    /// How the run ended
+    Outcome,
+}
+
+impl TranscriptKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TranscriptKind::Prompt => "prompt",
+            TranscriptKind::Text => "text",
+            TranscriptKind::ToolCall => "tool_call",
+            TranscriptKind::ToolResult => "tool_result",
+            TranscriptKind::Note => "note",
+            TranscriptKind::Outcome => "outcome",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "prompt" => Some(TranscriptKind::Prompt),
+            "text" => Some(TranscriptKind::Text),
+            "tool_call" => Some(TranscriptKind::ToolCall),
+            "tool_result" => Some(TranscriptKind::ToolResult),
+            "note" => Some(TranscriptKind::Note),
+            "outcome" => Some(TranscriptKind::Outcome),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for TranscriptKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// One step of a sub-agent's conversation
+#[derive(Debug, Clone, PartialEq)]
+pub struct TranscriptEntry {
+    /// Sub-agent task ID (matches `AgentProgress::task_id`)
+    pub task_id: String,
+    /// Sub-agent display name
+    pub agent_name: String,
+    /// Model the agent ran on
+    pub model: String,
+    /// Turn the entry belongs to (0 for the prompt)
+    pub turn: usize,
+    pub kind: TranscriptKind,
+    /// Tool name for tool calls and results
+    pub tool_name: Option<String>,
+    pub content: String,
+    pub is_error: bool,
+}
+
+/// Receives transcript entries as a sub-agent runs
+///
+/// Called from the agent's task, so implementations should be quick and
+/// must not fail the run; errors are theirs to log.
+pub trait TranscriptSink: Send + Sync {
+    fn record(&self, entry: TranscriptEntry);
+}
+
+/// Shared sink handed to sub-agent pools and tool contexts
+pub type SharedTranscriptSink = Arc<dyn TranscriptSink>;
+
+/// Sink that keeps entries in memory
+#[derive(Debug, Default)]
+pub struct MemoryTranscript {
+    entries: Mutex<Vec<TranscriptEntry>>,
+}
+
+impl MemoryTranscript {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Entries recorded so far, in order
+    pub fn entries(&self) -> Vec<TranscriptEntry> {
+        self.entries.lock().map(|e| e.clone()).unwrap_or_default()
+    }
+}
+
+impl TranscriptSink for MemoryTranscript {
+    fn record(&self, entry: TranscriptEntry) {
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.push(entry);
+        }
+    }
+}
@@ -129,6 +129,8 @@ pub struct SubAgentTask {
     pub plan_task_id: Option<String>,
     /// Whether thinking/reasoning is enabled for this agent
     pub thinking_enabled: bool,
+    /// Restrict file tools to this directory (e.g. a builder's worktree)
+    pub sandbox_root: Option<PathBuf>,
 }
 
 impl SubAgentTask {
@@ -142,6 +144,7 @@ impl SubAgentTask {
             working_dir: std::env::current_dir().unwrap_or_else(|_| PathBuf::from(".")),
             plan_task_id: None,
             thinking_enabled: false, // Default off for sub-agents
+            sandbox_root: None,
         }
     }
 
@@ -165,6 +168,11 @@ impl SubAgentTask {
         self
     }
 
+    pub fn with_sandbox(mut self, root: PathBuf) -> Self {
+        self.sandbox_root = Some(root);
+        self
+    }
+
     pub(crate) fn system_prompt(&self) -> String {
         format!(
             r#"You are a codebase explorer. Your task is to systematically investigate the codebase and answer questions.
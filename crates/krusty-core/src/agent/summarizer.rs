@@ -260,10 +260,11 @@ fn summarize_tool_input(tool_name: &str, input: &serde_json::Value) -> String {
     }
 }
 
-/// Generate a summary using the user's current model
+/// Generate a summary using the client's model
 ///
 /// For Anthropic: Uses extended thinking for deep analysis
-/// For all providers: Uses the current model from the client
+/// For all providers: Uses the client's model, which is the summarizer
+/// route if one is set and the user's current model otherwise
 pub async fn generate_summary(
     client: &AiClient,
     conversation: &[ModelMessage],
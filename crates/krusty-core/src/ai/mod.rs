@@ -18,6 +18,7 @@ pub mod openrouter;
 pub mod parsers;
 pub mod providers;
 pub mod reasoning;
+pub mod routing;
 pub mod sse;
 pub mod stream_buffer;
 pub mod streaming;
@@ -28,4 +29,5 @@ pub mod types;
 // Re-export main types from new module
 pub use client::{AiClient, AiClientConfig, CallOptions, KRUSTY_SYSTEM_PROMPT};
 
+pub use routing::{ModelRole, ModelRoute, ModelRouting};
 pub use title::{generate_pinch_title, generate_title};
@@ -0,0 +1,158 @@
+//! Per-role model routing
+//!
+//! The main conversation runs on the model the user picked, and by default
+//! everything else does too. Routing lets cheaper or faster models (possibly
+//! from another provider) handle the side work: explore and build
+//! sub-agents, session titles and pinch summaries. Each routed role gets its
+//! own `AiClient` built from its route.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use super::providers::ProviderId;
+
+/// Work that can be routed to its own model
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ModelRole {
+    /// The main conversation (always the selected model)
+    Main,
+    /// Read-only explore sub-agents
+    Explore,
+    /// Builder sub-agents (build tool and `/plan run`)
+    Build,
+    /// Session title generation
+    Title,
+    /// Pinch summarization
+    Summarizer,
+}
+
+impl ModelRole {
+    /// All roles, in display order
+    pub const ALL: [ModelRole; 5] = [
+        ModelRole::Main,
+        ModelRole::Explore,
+        ModelRole::Build,
+        ModelRole::Title,
+        ModelRole::Summarizer,
+    ];
+
+    /// Short label for the UI
+    pub fn label(&self) -> &'static str {
+        match self {
+            ModelRole::Main => "Main",
+            ModelRole::Explore => "Explore",
+            ModelRole::Build => "Build",
+            ModelRole::Title => "Title",
+            ModelRole::Summarizer => "Summarizer",
+        }
+    }
+
+    /// The role after this one, wrapping around
+    pub fn next(&self) -> ModelRole {
+        let idx = Self::ALL.iter().position(|r| r == self).unwrap_or(0);
+        Self::ALL[(idx + 1) % Self::ALL.len()]
+    }
+}
+
+impl fmt::Display for ModelRole {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.label())
+    }
+}
+
+/// Provider and model a role runs on
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModelRoute {
+    pub provider: ProviderId,
+    pub model: String,
+}
+
+/// Routes for roles that don't follow the main model
+///
+/// `Main` is never stored: it is whatever model the user selected.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModelRouting {
+    #[serde(default)]
+    routes: BTreeMap<ModelRole, ModelRoute>,
+}
+
+impl ModelRouting {
+    /// Route for a role, or `None` if it follows the main model
+    pub fn route(&self, role: ModelRole) -> Option<&ModelRoute> {
+        self.routes.get(&role)
+    }
+
+    /// Route a role to a model, or back to the main model with `None`
+    pub fn set_route(&mut self, role: ModelRole, route: Option<ModelRoute>) {
+        match route {
+            Some(route) if role != ModelRole::Main => {
+                self.routes.insert(role, route);
+            }
+            _ => {
+                self.routes.remove(&role);
+            }
+        }
+    }
+
+    /// Provider and model a role resolves to, given the main selection
+    pub fn resolve(&self, role: ModelRole, main: &ModelRoute) -> ModelRoute {
+        self.route(role).cloned().unwrap_or_else(|| main.clone())
+    }
+
+    /// Roles routed to this model
+    pub fn roles_for(&self, provider: ProviderId, model: &str) -> Vec<ModelRole> {
+        self.routes
+            .iter()
+            .filter(|(_, route)| route.provider == provider && route.model == model)
+            .map(|(role, _)| *role)
+            .collect()
+    }
+
+    /// Whether every role follows the main model
+    pub fn is_empty(&self) -> bool {
+        self.routes.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_routes_resolve_and_roundtrip() {
+        let main = ModelRoute {
+            provider: ProviderId::OpenAI,
+            model: "gpt-5".to_string(),
+        };
+        let cheap = ModelRoute {
+            provider: ProviderId::MiniMax,
+            model: "MiniMax-M2".to_string(),
+        };
+
+        let mut routing = ModelRouting::default();
+        routing.set_route(ModelRole::Title, Some(cheap.clone()));
+        routing.set_route(ModelRole::Explore, Some(cheap.clone()));
+        // Main can't be routed away from the selected model
+        routing.set_route(ModelRole::Main, Some(cheap.clone()));
+
+        assert_eq!(routing.resolve(ModelRole::Title, &main), cheap);
+        assert_eq!(routing.resolve(ModelRole::Build, &main), main);
+        assert_eq!(routing.resolve(ModelRole::Main, &main), main);
+        assert_eq!(
+            routing.roles_for(ProviderId::MiniMax, "MiniMax-M2"),
+            vec![ModelRole::Explore, ModelRole::Title]
+        );
+
+        let json = serde_json::to_string(&routing).unwrap();
+        let parsed: ModelRouting = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, routing);
+
+        routing.set_route(ModelRole::Title, None);
+        routing.set_route(ModelRole::Explore, None);
+        assert!(routing.is_empty());
+        assert_eq!(ModelRole::Summarizer.next(), ModelRole::Main);
+    }
+}
@@ -1,6 +1,7 @@
 //! AI-powered session title generation
 //!
-//! Uses the user's current model for provider-agnostic title generation.
+//! Uses the client's model: the title route if one is set, otherwise the
+//! user's current model.
 
 use super::client::AiClient;
 
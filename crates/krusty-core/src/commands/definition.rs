@@ -0,0 +1,209 @@
+//! Custom command definitions and parsing
+
+use anyhow::{anyhow, Result};
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+use crate::agent::custom::{tool_matches, ToolList};
+
+/// Where the command definition comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommandSource {
+    /// Global commands from ~/.krusty/commands/
+    Global,
+    /// Project-specific commands from .krusty/commands/
+    Project,
+}
+
+/// YAML frontmatter from a command file (all keys optional)
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct CommandFrontmatter {
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default, alias = "argument_hint")]
+    argument_hint: Option<String>,
+    #[serde(default, alias = "allowed_tools")]
+    allowed_tools: Option<ToolList>,
+    #[serde(default)]
+    model: Option<String>,
+}
+
+/// A slash command defined in markdown
+#[derive(Debug, Clone)]
+pub struct CommandDefinition {
+    /// Name without the leading slash
+    pub name: String,
+    pub description: String,
+    /// Shown after the name in autocomplete (e.g. `<pr-number>`)
+    pub argument_hint: Option<String>,
+    /// Tools offered and run while the command runs; `None` keeps all of
+    /// them. A trailing `*` matches by prefix (e.g. `mcp__github_*`)
+    pub allowed_tools: Option<Vec<String>>,
+    /// Model override (must be served by the active provider)
+    pub model: Option<String>,
+    pub source: CommandSource,
+    /// Path to the command file
+    pub path: PathBuf,
+    /// Markdown body, expanded into the prompt
+    pub template: String,
+}
+
+impl CommandDefinition {
+    /// Parse a command file; the name comes from the file stem
+    pub fn parse(content: &str, path: PathBuf, source: CommandSource) -> Result<Self> {
+        let name = path
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        if name.is_empty()
+            || !name
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_')
+        {
+            return Err(anyhow!(
+                "Command name must contain only lowercase letters, numbers, hyphens and underscores"
+            ));
+        }
+
+        let (frontmatter, body) = split_frontmatter(content, &path)?;
+        if body.is_empty() {
+            return Err(anyhow!("Command file has no prompt"));
+        }
+
+        let description = frontmatter
+            .description
+            .filter(|d| !d.trim().is_empty())
+            .unwrap_or_else(|| first_line(&body));
+
+        Ok(Self {
+            name,
+            description,
+            argument_hint: frontmatter.argument_hint.filter(|h| !h.is_empty()),
+            allowed_tools: frontmatter.allowed_tools.map(|tools| {
+                tools
+                    .into_vec()
+                    .into_iter()
+                    .filter(|t| !t.is_empty())
+                    .collect()
+            }),
+            model: frontmatter.model.filter(|m| !m.is_empty()),
+            source,
+            path,
+            template: body,
+        })
+    }
+
+    /// Whether the template uses arguments, so picking it from autocomplete
+    /// waits for them instead of running it
+    pub fn takes_arguments(&self) -> bool {
+        self.argument_hint.is_some()
+            || self.template.contains("$ARGUMENTS")
+            || self.template.contains("$1")
+    }
+
+    /// Whether the tool is offered while this command runs
+    pub fn allows_tool(&self, tool_name: &str) -> bool {
+        self.allowed_tools
+            .as_ref()
+            .is_none_or(|tools| tools.iter().any(|pattern| tool_matches(pattern, tool_name)))
+    }
+
+    /// Error result for a call to a tool outside the allowlist, which a
+    /// model can still name even though it was never offered
+    pub fn refuse_tool(&self, tool_name: &str) -> Option<String> {
+        (!self.allows_tool(tool_name)).then(|| {
+            format!(
+                "Tool '{}' is not allowed while /{} runs (allowed-tools)",
+                tool_name, self.name
+            )
+        })
+    }
+}
+
+/// Split optional YAML frontmatter from the markdown body
+fn split_frontmatter(content: &str, path: &Path) -> Result<(CommandFrontmatter, String)> {
+    let content = content.trim();
+    let Some(rest) = content.strip_prefix("---") else {
+        return Ok((CommandFrontmatter::default(), content.to_string()));
+    };
+
+    let end_pos = rest
+        .find("\n---")
+        .ok_or_else(|| anyhow!("Missing closing frontmatter delimiter (---)"))?;
+    let yaml_content = rest[..end_pos].trim();
+    let body = rest[end_pos + 4..].trim();
+
+    let frontmatter = if yaml_content.is_empty() {
+        CommandFrontmatter::default()
+    } else {
+        serde_yaml::from_str(yaml_content)
+            .map_err(|e| anyhow!("Failed to parse frontmatter in {}: {}", path.display(), e))?
+    };
+    Ok((frontmatter, body.to_string()))
+}
+
+/// First non-empty line of the body, without heading markers
+fn first_line(body: &str) -> String {
+    body.lines()
+        .map(|line| line.trim_start_matches('#').trim())
+        .find(|line| !line.is_empty())
+        .unwrap_or_default()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_command() {
+        let content = r#"---
+description: Review a pull request
+argument-hint: <pr-number>
+allowed-tools: [read, grep, "mcp__github_*"]
+model: claude-haiku-4-5
+---
+
+Review PR #$1.
+"#;
+        let command = CommandDefinition::parse(
+            content,
+            PathBuf::from("/p/.krusty/commands/review.md"),
+            CommandSource::Project,
+        )
+        .unwrap();
+        assert_eq!(command.name, "review");
+        assert_eq!(command.description, "Review a pull request");
+        assert_eq!(command.argument_hint.as_deref(), Some("<pr-number>"));
+        assert_eq!(command.model.as_deref(), Some("claude-haiku-4-5"));
+        assert_eq!(command.template, "Review PR #$1.");
+        assert!(command.allows_tool("mcp__github_get_pr"));
+        assert!(!command.allows_tool("write"));
+        assert!(command.refuse_tool("mcp__github_get_pr").is_none());
+        assert!(command.refuse_tool("write").is_some());
+    }
+
+    #[test]
+    fn test_without_frontmatter() {
+        let command = CommandDefinition::parse(
+            "# Write the changelog\n\nSummarize commits since the last tag.",
+            PathBuf::from("/a/changelog.md"),
+            CommandSource::Global,
+        )
+        .unwrap();
+        assert_eq!(command.description, "Write the changelog");
+        assert!(command.allowed_tools.is_none());
+        assert!(command.allows_tool("write"));
+
+        let bad_name =
+            CommandDefinition::parse("Body", PathBuf::from("/a/Fix It.md"), CommandSource::Global);
+        assert!(bad_name.is_err());
+        let empty = CommandDefinition::parse(
+            "---\ndescription: x\n---\n",
+            PathBuf::from("/a/x.md"),
+            CommandSource::Global,
+        );
+        assert!(empty.is_err());
+    }
+}
@@ -0,0 +1,180 @@
+//! Commands manager - discovery and lookup of custom slash commands
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::{debug, info};
+
+use super::definition::{CommandDefinition, CommandSource};
+
+/// Manages custom command discovery and access
+pub struct CommandsManager {
+    /// Global commands directory (~/.krusty/commands/)
+    global_dir: PathBuf,
+    /// Project-specific commands directory (.krusty/commands/)
+    project_dir: Option<PathBuf>,
+    /// Cached commands (name -> definition)
+    cache: HashMap<String, CommandDefinition>,
+    /// Whether cache is populated
+    cache_valid: bool,
+}
+
+impl CommandsManager {
+    /// Create a new CommandsManager
+    ///
+    /// Project commands override global commands with the same name, as with
+    /// skills and agents.
+    pub fn new(global_dir: PathBuf, project_dir: Option<PathBuf>) -> Self {
+        Self {
+            global_dir,
+            project_dir,
+            cache: HashMap::new(),
+            cache_valid: false,
+        }
+    }
+
+    /// Manager for a working directory, reading `~/.krusty/commands` and
+    /// `<working_dir>/.krusty/commands`
+    pub fn for_working_dir(working_dir: &Path) -> Self {
+        Self::new(
+            crate::paths::config_dir().join("commands"),
+            Some(working_dir.join(".krusty").join("commands")),
+        )
+    }
+
+    /// Refresh the commands cache
+    pub fn refresh(&mut self) {
+        self.cache.clear();
+
+        // Load project commands first (highest priority)
+        if let Some(ref project_dir) = self.project_dir {
+            for command in load_commands_from_dir(project_dir, CommandSource::Project) {
+                self.cache.insert(command.name.clone(), command);
+            }
+        }
+
+        // Load global commands (don't override project commands)
+        for command in load_commands_from_dir(&self.global_dir, CommandSource::Global) {
+            self.cache.entry(command.name.clone()).or_insert(command);
+        }
+
+        self.cache_valid = true;
+        info!("Loaded {} custom commands", self.cache.len());
+    }
+
+    /// Ensure cache is populated
+    fn ensure_cache(&mut self) {
+        if !self.cache_valid {
+            self.refresh();
+        }
+    }
+
+    /// List all available commands, sorted by name
+    pub fn list_commands(&mut self) -> Vec<CommandDefinition> {
+        self.ensure_cache();
+        let mut commands: Vec<CommandDefinition> = self.cache.values().cloned().collect();
+        commands.sort_by(|a, b| a.name.cmp(&b.name));
+        commands
+    }
+
+    /// Get a command by name (without the leading slash)
+    pub fn get_command(&mut self, name: &str) -> Option<&CommandDefinition> {
+        self.ensure_cache();
+        self.cache.get(name)
+    }
+
+    /// Get an already loaded command by name, without loading the cache
+    pub fn loaded_command(&self, name: &str) -> Option<&CommandDefinition> {
+        self.cache.get(name)
+    }
+
+    /// Get the global commands directory path
+    pub fn global_dir(&self) -> &PathBuf {
+        &self.global_dir
+    }
+
+    /// Get the project commands directory path
+    pub fn project_dir(&self) -> Option<&PathBuf> {
+        self.project_dir.as_ref()
+    }
+}
+
+/// Load all `*.md` command files from a directory
+fn load_commands_from_dir(dir: &Path, source: CommandSource) -> Vec<CommandDefinition> {
+    let mut commands = Vec::new();
+
+    let Ok(entries) = fs::read_dir(dir) else {
+        return commands;
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if !path.is_file() || path.extension().is_none_or(|ext| ext != "md") {
+            continue;
+        }
+
+        let content = match fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(e) => {
+                debug!("Failed to read command file {:?}: {}", path, e);
+                continue;
+            }
+        };
+
+        match CommandDefinition::parse(&content, path.clone(), source) {
+            Ok(command) => {
+                debug!("Loaded command: /{} from {:?}", command.name, path);
+                commands.push(command);
+            }
+            Err(e) => {
+                debug!("Failed to load command from {:?}: {}", path, e);
+            }
+        }
+    }
+
+    commands.sort_by(|a, b| a.name.cmp(&b.name));
+    commands
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_project_overrides_global() {
+        let temp = tempdir().unwrap();
+        let global_dir = temp.path().join("global");
+        let project_dir = temp.path().join("project");
+
+        for (dir, desc) in [
+            (&global_dir, "Global review"),
+            (&project_dir, "Project review"),
+        ] {
+            std::fs::create_dir_all(dir).unwrap();
+            std::fs::write(
+                dir.join("review.md"),
+                format!("---\ndescription: {}\n---\nReview $ARGUMENTS", desc),
+            )
+            .unwrap();
+        }
+        std::fs::write(global_dir.join("changelog.md"), "Write the changelog.").unwrap();
+        std::fs::write(global_dir.join("notes.txt"), "ignored").unwrap();
+        std::fs::write(global_dir.join("empty.md"), "").unwrap();
+
+        let mut manager = CommandsManager::new(global_dir, Some(project_dir));
+        assert!(manager.loaded_command("review").is_none());
+        let names: Vec<String> = manager
+            .list_commands()
+            .into_iter()
+            .map(|c| c.name)
+            .collect();
+        assert_eq!(names, vec!["changelog", "review"]);
+
+        let review = manager.get_command("review").unwrap();
+        assert_eq!(review.description, "Project review");
+        assert_eq!(review.source, CommandSource::Project);
+        assert!(manager.get_command("empty").is_none());
+        assert!(manager.loaded_command("changelog").is_some());
+    }
+}
@@ -0,0 +1,35 @@
+//! Custom slash commands
+//!
+//! Reusable prompts defined as markdown files in two locations:
+//! - Global: `~/.krusty/commands/*.md`
+//! - Project: `.krusty/commands/*.md` (overrides global commands with the same name)
+//!
+//! The file name is the command name (`review.md` is `/review`). Frontmatter
+//! is optional:
+//!
+//! ```yaml
+//! ---
+//! description: Review a pull request   # defaults to the first line of the body
+//! argument-hint: <pr-number>
+//! allowed-tools: [read, grep, bash]     # tools offered while it runs (default: all)
+//! model: claude-haiku-4-5               # defaults to the current model
+//! ---
+//!
+//! Review PR #$1. Current diff:
+//!
+//! !`gh pr diff $1`
+//!
+//! Follow the guidelines in @docs/review.md
+//! ```
+//!
+//! When invoked, `$ARGUMENTS` becomes everything after the command name and
+//! `$1`, `$2`, ... the individual (shell-quoted) arguments. Then
+//! `` !`cmd` `` is replaced with the command's output and `@path` with the
+//! contents of that file.
+
+mod definition;
+mod manager;
+mod template;
+
+pub use definition::{CommandDefinition, CommandSource};
+pub use manager::CommandsManager;
@@ -0,0 +1,395 @@
+//! Command template expansion: arguments, `` !`cmd` `` output and `@file` contents
+//!
+//! Shell commands run through the `bash` tool, so the safety hook and user
+//! `PreToolUse` hooks see them like any command the agent runs.
+
+use anyhow::{anyhow, Result};
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use super::definition::{CommandDefinition, CommandSource};
+use crate::tools::{ToolContext, ToolRegistry};
+
+/// Wall-clock limit for one `` !`cmd` ``
+const SHELL_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Extra time the registry allows on top of the bash tool's own timeout
+const TOOL_TIMEOUT_SLACK: Duration = Duration::from_secs(5);
+
+/// Longest shell output kept (the head is kept)
+const MAX_OUTPUT_CHARS: usize = 20_000;
+
+/// Largest file `@path` includes in full
+const MAX_FILE_BYTES: usize = 100_000;
+
+impl CommandDefinition {
+    /// Build the prompt for an invocation with `arguments` (the text after
+    /// the command name). Shell commands run through `registry`'s `bash`
+    /// tool; they and `@` paths resolve in `ctx.working_dir`
+    ///
+    /// Callers confirm project commands that `needs_shell_confirmation`
+    /// before expanding them.
+    pub async fn expand(
+        &self,
+        arguments: &str,
+        registry: &ToolRegistry,
+        ctx: &ToolContext,
+    ) -> Result<String> {
+        let arguments = Arguments::parse(arguments.trim());
+        let shell = Shell { registry, ctx };
+        let mut used = false;
+        let mut prompt = interpolate(&self.template, &shell, &arguments, &mut used).await?;
+        // Arguments a template never mentions are appended, so they aren't lost
+        if !used && !arguments.all.is_empty() {
+            prompt.push_str("\n\n");
+            prompt.push_str(arguments.all);
+        }
+        Ok(prompt)
+    }
+
+    /// Commands in the template's `` !`cmd` `` spans, in order
+    pub fn shell_commands(&self) -> Vec<&str> {
+        let mut commands = Vec::new();
+        let mut rest = self.template.as_str();
+        while let Some(pos) = rest.find("!`") {
+            let Some(end) = rest[pos + 2..].find('`') else {
+                break;
+            };
+            commands.push(&rest[pos + 2..pos + 2 + end]);
+            rest = &rest[pos + 2 + end + 1..];
+        }
+        commands
+    }
+
+    /// Whether the user must agree before the template's shell commands
+    /// run. Project commands come with the repository, so anyone who can
+    /// commit to it could otherwise run commands on every invocation
+    pub fn needs_shell_confirmation(&self) -> bool {
+        self.source == CommandSource::Project && !self.shell_commands().is_empty()
+    }
+}
+
+/// Where `` !`cmd` `` spans run
+struct Shell<'a> {
+    registry: &'a ToolRegistry,
+    ctx: &'a ToolContext,
+}
+
+/// The text after the command name, whole and split like a shell would
+struct Arguments<'a> {
+    all: &'a str,
+    positional: Vec<String>,
+}
+
+impl<'a> Arguments<'a> {
+    fn parse(all: &'a str) -> Self {
+        let positional = shell_words::split(all)
+            .unwrap_or_else(|_| all.split_whitespace().map(String::from).collect());
+        Self { all, positional }
+    }
+}
+
+/// Replace `$ARGUMENTS` and `$1`..`$N` in template text, setting `used`
+/// when any appear
+fn substitute_arguments(text: &str, arguments: &Arguments, used: &mut bool) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(pos) = rest.find('$') {
+        out.push_str(&rest[..pos]);
+        let after = &rest[pos + 1..];
+        if let Some(tail) = after.strip_prefix("ARGUMENTS") {
+            out.push_str(arguments.all);
+            *used = true;
+            rest = tail;
+            continue;
+        }
+        let digits = after.chars().take_while(|c| c.is_ascii_digit()).count();
+        match after[..digits].parse::<usize>() {
+            Ok(n) if n > 0 => {
+                out.push_str(arguments.positional.get(n - 1).map_or("", String::as_str));
+                *used = true;
+                rest = &after[digits..];
+            }
+            _ => {
+                out.push('$');
+                rest = after;
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Expand the template in one pass: arguments in its text, `` !`cmd` ``
+/// with the command's output and `@path` with the file. Only the
+/// template's own text is scanned; arguments reach shell commands as
+/// positional parameters, never as part of the command
+async fn interpolate(
+    template: &str,
+    shell: &Shell<'_>,
+    arguments: &Arguments<'_>,
+    used: &mut bool,
+) -> Result<String> {
+    let working_dir = &shell.ctx.working_dir;
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(pos) = rest.find(['!', '@']) {
+        let (before, from) = rest.split_at(pos);
+        out.push_str(&substitute_arguments(before, arguments, used));
+        let offset = template.len() - from.len();
+        let at_word_start = template[..offset]
+            .chars()
+            .next_back()
+            .is_none_or(char::is_whitespace);
+
+        if let Some(command) = from.strip_prefix("!`") {
+            let end = command
+                .find('`')
+                .ok_or_else(|| anyhow!("Unclosed !` in command template"))?;
+            let command = &command[..end];
+            *used |= substitute_arguments(command, arguments, &mut false) != command;
+            out.push_str(&run_shell(command, shell, arguments).await?);
+            rest = &from[2 + end + 1..];
+            continue;
+        }
+
+        if from.starts_with('@') && at_word_start {
+            let token_end = from.find(char::is_whitespace).unwrap_or(from.len());
+            let token = &from[1..token_end];
+            // `@README.md,` still means the file
+            let path = token.trim_end_matches(['.', ',', ';', ':', ')', '!', '?']);
+            if let Some(file) = resolve_include(path, working_dir)? {
+                out.push_str(&include_file(path, &file)?);
+                rest = &from[1 + path.len()..];
+                continue;
+            }
+        }
+
+        out.push_str(&from[..1]);
+        rest = &from[1..];
+    }
+    out.push_str(&substitute_arguments(rest, arguments, used));
+    Ok(out)
+}
+
+/// Run `command` through the bash tool. `$1`.. are the arguments and
+/// `$ARGUMENTS` is all of them, set as shell-quoted parameters first
+async fn run_shell(command: &str, shell: &Shell<'_>, arguments: &Arguments<'_>) -> Result<String> {
+    let script = format!(
+        "set -- {}\nARGUMENTS={}; export ARGUMENTS\n{}",
+        shell_words::join(&arguments.positional),
+        shell_words::quote(arguments.all),
+        command
+    );
+    let params = json!({
+        "command": script,
+        "timeout": SHELL_TIMEOUT.as_millis() as u64,
+        "description": "Custom command shell interpolation",
+    });
+    let mut ctx = shell.ctx.inherit();
+    ctx.timeout = Some(SHELL_TIMEOUT + TOOL_TIMEOUT_SLACK);
+
+    let result = shell
+        .registry
+        .execute("bash", params, &ctx)
+        .await
+        .ok_or_else(|| anyhow!("Can't run `{}`: the bash tool is not available", command))?;
+
+    // The bash tool reports `{output, exitCode, killed}`; a blocked command
+    // and other errors are plain text
+    let report: Value = serde_json::from_str(&result.output).unwrap_or(Value::Null);
+    let Some(output) = report["output"].as_str() else {
+        return Err(anyhow!("`{}` failed: {}", command, result.output));
+    };
+    if report["killed"] == Value::Bool(true) {
+        return Err(anyhow!(
+            "`{}` timed out after {}s",
+            command,
+            SHELL_TIMEOUT.as_secs()
+        ));
+    }
+
+    let mut text = output.trim_end().to_string();
+    if text.chars().count() > MAX_OUTPUT_CHARS {
+        text = text.chars().take(MAX_OUTPUT_CHARS).collect();
+        text.push_str("\n... (output truncated)");
+    }
+    match report["exitCode"].as_i64() {
+        Some(0) => {}
+        Some(code) => text.push_str(&format!("\n(`{}` exited with {})", command, code)),
+        None => text.push_str(&format!("\n(`{}` exited with signal)", command)),
+    }
+    Ok(text)
+}
+
+/// The file `@path` names, if it exists. Files outside `working_dir`
+/// (absolute paths, `..` or symlinks out of it) are refused
+fn resolve_include(path: &str, working_dir: &Path) -> Result<Option<PathBuf>> {
+    let file = working_dir.join(path);
+    if path.is_empty() || !file.is_file() {
+        return Ok(None);
+    }
+    let root = working_dir.canonicalize()?;
+    let file = file.canonicalize()?;
+    if !file.starts_with(&root) {
+        return Err(anyhow!("@{} is outside the working directory", path));
+    }
+    Ok(Some(file))
+}
+
+fn include_file(path: &str, file: &Path) -> Result<String> {
+    let bytes = std::fs::read(file).map_err(|e| anyhow!("Failed to read @{}: {}", path, e))?;
+    let mut content = String::from_utf8_lossy(&bytes[..bytes.len().min(MAX_FILE_BYTES)])
+        .trim_end()
+        .to_string();
+    if bytes.len() > MAX_FILE_BYTES {
+        content.push_str("\n... (file truncated)");
+    }
+    Ok(format!(
+        "\n<file path=\"{}\">\n{}\n</file>\n",
+        path, content
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::CommandSource;
+
+    fn command(template: &str) -> CommandDefinition {
+        CommandDefinition::parse(template, "test.md".into(), CommandSource::Project).unwrap()
+    }
+
+    async fn try_expand(template: &str, arguments: &str, working_dir: &Path) -> Result<String> {
+        let registry = ToolRegistry::new();
+        registry
+            .register(std::sync::Arc::new(crate::tools::implementations::BashTool))
+            .await;
+        let ctx = ToolContext {
+            working_dir: working_dir.to_path_buf(),
+            ..Default::default()
+        };
+        command(template).expand(arguments, &registry, &ctx).await
+    }
+
+    async fn expand(template: &str, arguments: &str, working_dir: &Path) -> String {
+        try_expand(template, arguments, working_dir).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_substitute_arguments() {
+        let dir = Path::new("/");
+        assert_eq!(
+            expand(
+                "Fix issue #$1 ($2): $ARGUMENTS",
+                "123 \"needs triage\"",
+                dir
+            )
+            .await,
+            "Fix issue #123 (needs triage): 123 \"needs triage\""
+        );
+        assert_eq!(expand("Costs $5 and $", "", dir).await, "Costs  and $");
+        assert_eq!(
+            expand("Write the changelog.", "since v1.2", dir).await,
+            "Write the changelog.\n\nsince v1.2"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_interpolate_shell_and_files() {
+        let temp = tempfile::tempdir().unwrap();
+        std::fs::write(temp.path().join("notes.md"), "be kind\n").unwrap();
+
+        let text = "Status: !`echo ok`\nRead @notes.md, not me@notes.md or @missing.md";
+        let expanded = expand(text, "", temp.path()).await;
+        assert_eq!(
+            expanded,
+            "Status: ok\nRead \n<file path=\"notes.md\">\nbe kind\n</file>\n, \
+             not me@notes.md or @missing.md"
+        );
+
+        assert!(try_expand("!`echo", "", temp.path()).await.is_err());
+        let failed = expand("!`exit 3`", "", temp.path()).await;
+        assert!(failed.contains("exited with 3"));
+    }
+
+    #[tokio::test]
+    async fn test_files_outside_working_dir_are_refused() {
+        let temp = tempfile::tempdir().unwrap();
+        let project = temp.path().join("project");
+        std::fs::create_dir(&project).unwrap();
+        std::fs::write(temp.path().join("secret.txt"), "hunter2\n").unwrap();
+        let absolute = temp.path().join("secret.txt");
+
+        for template in [
+            format!("Read @{}", absolute.display()),
+            "Read @../secret.txt".to_string(),
+        ] {
+            let result = try_expand(&template, "", &project).await;
+            let error = result.expect_err(&template).to_string();
+            assert!(error.contains("outside the working directory"), "{}", error);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_arguments_are_never_run() {
+        let temp = tempfile::tempdir().unwrap();
+        let arguments = "\"$(touch pwned)\" '!`touch pwned2`' \"it's\"";
+        let template = "Args: $ARGUMENTS\n\
+                        First: !`printf '%s' \"$1\"`\n\
+                        Second: $2\n\
+                        Third: !`printf '%s' \"$3\"`\n\
+                        All: !`printf '%s' \"$ARGUMENTS\"`";
+
+        let expanded = expand(template, arguments, temp.path()).await;
+        assert_eq!(
+            expanded,
+            format!(
+                "Args: {0}\nFirst: $(touch pwned)\nSecond: !`touch pwned2`\nThird: it's\nAll: {0}",
+                arguments
+            )
+        );
+        assert!(!temp.path().join("pwned").exists());
+        assert!(!temp.path().join("pwned2").exists());
+    }
+
+    #[tokio::test]
+    async fn test_shell_needs_confirmation_and_the_bash_tool() {
+        let project = command("Branch: !`git branch --show-current`, log: !`git log -1`");
+        assert_eq!(
+            project.shell_commands(),
+            vec!["git branch --show-current", "git log -1"]
+        );
+        assert!(project.needs_shell_confirmation());
+        assert!(!command("Read @notes.md").needs_shell_confirmation());
+        let global =
+            CommandDefinition::parse("!`date`", "date.md".into(), CommandSource::Global).unwrap();
+        assert!(!global.needs_shell_confirmation());
+
+        // Without the bash tool nothing runs
+        let temp = tempfile::tempdir().unwrap();
+        let ctx = ToolContext {
+            working_dir: temp.path().to_path_buf(),
+            ..Default::default()
+        };
+        let result = command("!`touch ran`")
+            .expand("", &ToolRegistry::new(), &ctx)
+            .await;
+        assert!(result.is_err());
+        assert!(!temp.path().join("ran").exists());
+    }
+
+    #[tokio::test]
+    async fn test_output_is_not_scanned_for_arguments() {
+        let temp = tempfile::tempdir().unwrap();
+        std::fs::write(temp.path().join("price.md"), "costs $1\n").unwrap();
+
+        let expanded = expand("!`printf '%s%s' '$' ARGUMENTS` @price.md", "x", temp.path()).await;
+        assert_eq!(
+            expanded,
+            "$ARGUMENTS \n<file path=\"price.md\">\ncosts $1\n</file>\n\n\nx"
+        );
+    }
+}
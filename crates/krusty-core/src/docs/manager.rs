@@ -0,0 +1,186 @@
+//! Docs manager - runs extension docs providers and queries the index
+
+use anyhow::{anyhow, Context, Result};
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tracing::info;
+
+use crate::extensions::types::KeyValueStoreDelegate;
+use crate::extensions::wasm_host::WasmExtension;
+use crate::extensions::{installed_extensions, WasmHost};
+use crate::storage::{Database, DocsPackage, DocsSearchHit, DocsStore, SharedDatabase};
+
+/// A docs provider declared by an installed extension
+#[derive(Debug, Clone)]
+pub struct DocsProvider {
+    /// Provider name (e.g. "rustdoc")
+    pub name: String,
+    /// Id of the extension that provides it
+    pub extension_id: String,
+    extension_dir: PathBuf,
+}
+
+/// Manages docs indexing and search
+pub struct DocsManager {
+    db: SharedDatabase,
+    host: Option<Arc<WasmHost>>,
+    extensions_dir: PathBuf,
+    /// Loaded extensions by id, so repeated indexing doesn't recompile
+    loaded: Mutex<HashMap<String, Arc<WasmExtension>>>,
+}
+
+impl DocsManager {
+    /// Create a docs manager with shared database
+    pub fn with_shared_db(
+        db: SharedDatabase,
+        host: Option<Arc<WasmHost>>,
+        extensions_dir: PathBuf,
+    ) -> Self {
+        Self {
+            db,
+            host,
+            extensions_dir,
+            loaded: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Create a docs manager with database path (creates new connection)
+    pub fn new(
+        db_path: PathBuf,
+        host: Option<Arc<WasmHost>>,
+        extensions_dir: PathBuf,
+    ) -> Result<Self> {
+        let db = Database::shared(&db_path)?;
+        Ok(Self::with_shared_db(db, host, extensions_dir))
+    }
+
+    /// List docs providers from installed extensions
+    pub fn providers(&self) -> Vec<DocsProvider> {
+        installed_extensions(&self.extensions_dir)
+            .into_iter()
+            .flat_map(|(dir, manifest)| {
+                manifest
+                    .indexed_docs_providers
+                    .keys()
+                    .map(|name| DocsProvider {
+                        name: name.clone(),
+                        extension_id: manifest.id.clone(),
+                        extension_dir: dir.clone(),
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
+    /// Ask a provider which packages it can index
+    pub async fn suggest_packages(&self, provider: &DocsProvider) -> Result<Vec<String>> {
+        let extension = self.load(provider).await?;
+        extension
+            .suggest_docs_packages(Arc::from(provider.name.as_str()))
+            .await
+    }
+
+    /// Index a package's docs, replacing any previous index for it
+    ///
+    /// Entries are collected first and only swapped in once indexing
+    /// succeeds, so a failed re-index keeps the docs already there.
+    /// Returns the number of entries indexed.
+    pub async fn index_package(&self, provider: &DocsProvider, package: &str) -> Result<usize> {
+        let extension = self.load(provider).await?;
+
+        let staging = Arc::new(StagingKeyValueStore::default());
+        let kv_store: Arc<dyn KeyValueStoreDelegate> = staging.clone();
+        extension
+            .index_docs(
+                Arc::from(provider.name.as_str()),
+                Arc::from(package),
+                kv_store,
+            )
+            .await
+            .with_context(|| format!("Failed to index docs for {}", package))?;
+
+        let entries = staging.take();
+        let count =
+            self.with_store(|store| store.replace_package(&provider.name, package, &entries))?;
+        info!(
+            "Indexed {} docs entries for {} ({})",
+            count, package, provider.name
+        );
+        Ok(count)
+    }
+
+    /// Full-text search over indexed docs
+    pub fn search(
+        &self,
+        query: &str,
+        package: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<DocsSearchHit>> {
+        self.with_store(|store| store.search(query, package, limit))
+    }
+
+    /// Get the full docs for an entry key
+    pub fn get_entry(&self, key: &str, package: Option<&str>) -> Result<Option<String>> {
+        self.with_store(|store| store.get_entry(key, package))
+    }
+
+    /// List packages that have been indexed
+    pub fn indexed_packages(&self) -> Result<Vec<DocsPackage>> {
+        self.with_store(|store| store.list_packages())
+    }
+
+    fn with_store<T>(&self, f: impl FnOnce(&DocsStore) -> Result<T>) -> Result<T> {
+        let db = self.db.lock().map_err(|e| anyhow!("Lock error: {}", e))?;
+        f(&DocsStore::new(&db))
+    }
+
+    async fn load(&self, provider: &DocsProvider) -> Result<Arc<WasmExtension>> {
+        let host = self
+            .host
+            .as_ref()
+            .ok_or_else(|| anyhow!("Extension host is not available"))?;
+
+        let mut loaded = self.loaded.lock().await;
+        if let Some(extension) = loaded.get(&provider.extension_id) {
+            return Ok(extension.clone());
+        }
+
+        let extension = Arc::new(
+            host.load_extension_from_dir(&provider.extension_dir)
+                .await
+                .with_context(|| format!("Failed to load extension {}", provider.extension_id))?,
+        );
+        loaded.insert(provider.extension_id.clone(), extension.clone());
+        Ok(extension)
+    }
+}
+
+/// Key-value store handed to `index-docs`, collecting entries until the
+/// index is complete
+#[derive(Default)]
+struct StagingKeyValueStore {
+    entries: std::sync::Mutex<Vec<(String, String)>>,
+}
+
+impl StagingKeyValueStore {
+    fn take(&self) -> Vec<(String, String)> {
+        self.entries
+            .lock()
+            .map(|mut entries| std::mem::take(&mut *entries))
+            .unwrap_or_default()
+    }
+}
+
+#[async_trait]
+impl KeyValueStoreDelegate for StagingKeyValueStore {
+    async fn insert(&self, key: String, docs: String) -> Result<()> {
+        self.entries
+            .lock()
+            .map_err(|e| anyhow!("Lock error: {}", e))?
+            .push((key, docs));
+        Ok(())
+    }
+}
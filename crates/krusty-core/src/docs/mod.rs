@@ -0,0 +1,9 @@
+//! Indexed documentation
+//!
+//! Extensions declaring `[indexed_docs_providers.<name>]` can suggest
+//! packages and index their docs through the `index-docs` export. Indexed
+//! entries land in SQLite and are searchable by the `docs_search` tool.
+
+mod manager;
+
+pub use manager::{DocsManager, DocsProvider};
@@ -6,7 +6,7 @@ use anyhow::{bail, Context as _, Result};
 use semver::Version;
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 /// The schema version of the [`ExtensionManifest`].
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy, Serialize, Deserialize)]
@@ -178,6 +178,34 @@ pub struct AgentServerManifestEntry {
     // Agent server configuration - currently empty in most extensions
 }
 
+/// List installed extensions under `extensions_dir`
+///
+/// Each extension lives in its own subdirectory with an extension.toml.
+/// The host's "work" directory and unparseable manifests are skipped.
+pub fn installed_extensions(extensions_dir: &Path) -> Vec<(PathBuf, ExtensionManifest)> {
+    let Ok(entries) = std::fs::read_dir(extensions_dir) else {
+        return Vec::new();
+    };
+
+    let mut extensions = Vec::new();
+    for entry in entries.flatten() {
+        let dir = entry.path();
+        if !dir.is_dir() || dir.file_name().is_some_and(|n| n == "work") {
+            continue;
+        }
+        let Ok(content) = std::fs::read_to_string(dir.join("extension.toml")) else {
+            continue;
+        };
+        match toml::from_str::<ExtensionManifest>(&content) {
+            Ok(manifest) => extensions.push((dir, manifest)),
+            Err(e) => tracing::warn!("Skipping extension at {:?}: {}", dir, e),
+        }
+    }
+
+    extensions.sort_by(|a, b| a.1.id.cmp(&b.1.id));
+    extensions
+}
+
 /// Parse the zed:api-version from WASM bytes
 pub fn parse_wasm_extension_version(extension_id: &str, wasm_bytes: &[u8]) -> Result<Version> {
     let mut version = None;
@@ -213,3 +241,35 @@ fn parse_wasm_extension_version_custom_section(data: &[u8]) -> Option<Version> {
         None
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_installed_extensions_skips_work_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let ext_dir = dir.path().join("postgres-context-server");
+        std::fs::create_dir_all(&ext_dir).unwrap();
+        std::fs::write(
+            ext_dir.join("extension.toml"),
+            r#"
+id = "postgres-context-server"
+name = "Postgres Context Server"
+version = "0.1.0"
+
+[context_servers.postgres-context-server]
+"#,
+        )
+        .unwrap();
+        std::fs::create_dir_all(dir.path().join("work").join("postgres-context-server")).unwrap();
+
+        let installed = installed_extensions(dir.path());
+        assert_eq!(installed.len(), 1);
+        assert_eq!(installed[0].1.id, "postgres-context-server");
+        assert!(installed[0]
+            .1
+            .context_servers
+            .contains_key("postgres-context-server"));
+    }
+}
@@ -31,3 +31,16 @@ pub trait WorktreeDelegate: Send + Sync {
     fn which(&self, binary_name: &str) -> Option<String>;
     fn shell_env(&self) -> Vec<(String, String)>;
 }
+
+/// Delegate trait for project operations (host provides to extensions)
+///
+/// Context servers receive a project handle rather than a single worktree.
+pub trait ProjectDelegate: Send + Sync {
+    fn worktree_ids(&self) -> Vec<u64>;
+}
+
+/// Delegate trait for the key-value store extensions write indexed docs into
+#[async_trait]
+pub trait KeyValueStoreDelegate: Send + Sync {
+    async fn insert(&self, key: String, docs: String) -> Result<()>;
+}
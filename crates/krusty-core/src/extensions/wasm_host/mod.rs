@@ -414,6 +414,83 @@ impl WasmExtension {
         })
         .await?
     }
+
+    /// Get the context server (MCP) command from the extension
+    ///
+    /// Relative command paths are resolved against the extension's work
+    /// directory, since that is where extensions download their binaries.
+    pub async fn context_server_command(
+        &self,
+        context_server_id: Arc<str>,
+        project: Arc<dyn ProjectDelegate>,
+        work_dir: PathBuf,
+    ) -> Result<Command> {
+        self.call(|extension, store| {
+            async move {
+                let resource = store.data_mut().table.push(project)?;
+                let mut command = extension
+                    .call_context_server_command(store, &context_server_id, resource)
+                    .await?
+                    .map_err(|err| store.data().extension_error(err))?;
+                command.command = resolve_command_path(&work_dir, &command.command);
+                Ok(command)
+            }
+            .boxed()
+        })
+        .await?
+    }
+
+    /// Suggest packages the extension can index docs for
+    pub async fn suggest_docs_packages(&self, provider: Arc<str>) -> Result<Vec<String>> {
+        self.call(|extension, store| {
+            async move {
+                let packages = extension
+                    .call_suggest_docs_packages(store, &provider)
+                    .await?
+                    .map_err(|err| store.data().extension_error(err))?;
+                Ok(packages)
+            }
+            .boxed()
+        })
+        .await?
+    }
+
+    /// Index a package's docs into the given key-value store
+    pub async fn index_docs(
+        &self,
+        provider: Arc<str>,
+        package_name: Arc<str>,
+        kv_store: Arc<dyn KeyValueStoreDelegate>,
+    ) -> Result<()> {
+        self.call(|extension, store| {
+            async move {
+                let resource = store.data_mut().table.push(kv_store)?;
+                extension
+                    .call_index_docs(store, &provider, &package_name, resource)
+                    .await?
+                    .map_err(|err| store.data().extension_error(err))?;
+                Ok(())
+            }
+            .boxed()
+        })
+        .await?
+    }
+}
+
+/// Resolve a command returned by an extension
+///
+/// Bare program names (e.g. `npx`) are looked up on PATH as-is; relative
+/// paths (e.g. `node_modules/.bin/server`) are joined to the extension's
+/// work directory.
+fn resolve_command_path(work_dir: &Path, command: &str) -> String {
+    let path = Path::new(command);
+    if path.is_absolute() || path.components().count() <= 1 {
+        return command.to_string();
+    }
+    work_dir
+        .join(normalize_path(path))
+        .to_string_lossy()
+        .into_owned()
 }
 
 /// Normalize a path, removing `.` and `..` components
@@ -15,7 +15,9 @@ mod since_v0_5_0;
 mod since_v0_6_0;
 mod since_v0_8_0;
 
-use crate::extensions::types::{LanguageServerName, WorktreeDelegate};
+use crate::extensions::types::{
+    KeyValueStoreDelegate, LanguageServerName, ProjectDelegate, WorktreeDelegate,
+};
 use crate::extensions::wasm_host::{wasm_engine, WasmState};
 use anyhow::{Context as _, Result};
 use semver::Version;
@@ -241,4 +243,105 @@ impl Extension {
                 .map(|r| r.map(Into::into)),
         }
     }
+
+    pub async fn call_context_server_command(
+        &self,
+        store: &mut Store<WasmState>,
+        context_server_id: &str,
+        project: Resource<Arc<dyn ProjectDelegate>>,
+    ) -> Result<Result<Command, String>> {
+        match self {
+            Extension::V0_8_0(ext) => {
+                ext.call_context_server_command(store, context_server_id, project)
+                    .await
+            }
+            Extension::V0_6_0(ext) => {
+                ext.call_context_server_command(store, context_server_id, project)
+                    .await
+            }
+            Extension::V0_5_0(ext) => {
+                ext.call_context_server_command(store, context_server_id, project)
+                    .await
+            }
+            Extension::V0_4_0(ext) => {
+                ext.call_context_server_command(store, context_server_id, project)
+                    .await
+            }
+            Extension::V0_3_0(ext) => {
+                ext.call_context_server_command(store, context_server_id, project)
+                    .await
+            }
+            Extension::V0_2_0(ext) => ext
+                .call_context_server_command(store, context_server_id, project)
+                .await
+                .map(|r| r.map(Into::into)),
+            Extension::V0_1_0(_)
+            | Extension::V0_0_6(_)
+            | Extension::V0_0_4(_)
+            | Extension::V0_0_1(_) => Ok(Err(
+                "context servers require extension API v0.2.0 or later".to_string(),
+            )),
+        }
+    }
+
+    pub async fn call_suggest_docs_packages(
+        &self,
+        store: &mut Store<WasmState>,
+        provider: &str,
+    ) -> Result<Result<Vec<String>, String>> {
+        match self {
+            Extension::V0_8_0(ext) => ext.call_suggest_docs_packages(store, provider).await,
+            Extension::V0_6_0(ext) => ext.call_suggest_docs_packages(store, provider).await,
+            Extension::V0_5_0(ext) => ext.call_suggest_docs_packages(store, provider).await,
+            Extension::V0_4_0(ext) => ext.call_suggest_docs_packages(store, provider).await,
+            Extension::V0_3_0(ext) => ext.call_suggest_docs_packages(store, provider).await,
+            Extension::V0_2_0(ext) => ext.call_suggest_docs_packages(store, provider).await,
+            Extension::V0_1_0(ext) => ext.call_suggest_docs_packages(store, provider).await,
+            Extension::V0_0_6(_) | Extension::V0_0_4(_) | Extension::V0_0_1(_) => Ok(Err(
+                "docs providers require extension API v0.1.0 or later".to_string(),
+            )),
+        }
+    }
+
+    pub async fn call_index_docs(
+        &self,
+        store: &mut Store<WasmState>,
+        provider: &str,
+        package_name: &str,
+        kv_store: Resource<Arc<dyn KeyValueStoreDelegate>>,
+    ) -> Result<Result<(), String>> {
+        match self {
+            Extension::V0_8_0(ext) => {
+                ext.call_index_docs(store, provider, package_name, kv_store)
+                    .await
+            }
+            Extension::V0_6_0(ext) => {
+                ext.call_index_docs(store, provider, package_name, kv_store)
+                    .await
+            }
+            Extension::V0_5_0(ext) => {
+                ext.call_index_docs(store, provider, package_name, kv_store)
+                    .await
+            }
+            Extension::V0_4_0(ext) => {
+                ext.call_index_docs(store, provider, package_name, kv_store)
+                    .await
+            }
+            Extension::V0_3_0(ext) => {
+                ext.call_index_docs(store, provider, package_name, kv_store)
+                    .await
+            }
+            Extension::V0_2_0(ext) => {
+                ext.call_index_docs(store, provider, package_name, kv_store)
+                    .await
+            }
+            Extension::V0_1_0(ext) => {
+                ext.call_index_docs(store, provider, package_name, kv_store)
+                    .await
+            }
+            Extension::V0_0_6(_) | Extension::V0_0_4(_) | Extension::V0_0_1(_) => Ok(Err(
+                "docs providers require extension API v0.1.0 or later".to_string(),
+            )),
+        }
+    }
 }
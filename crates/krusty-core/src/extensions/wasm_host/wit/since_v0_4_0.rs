@@ -1,7 +1,7 @@
 //! WIT bindings for extension API v0.4.0
 
 use super::since_v0_8_0 as latest;
-use crate::extensions::types::WorktreeDelegate;
+use crate::extensions::types::{KeyValueStoreDelegate, ProjectDelegate, WorktreeDelegate};
 use crate::extensions::wasm_host::WasmState;
 use anyhow::Result;
 use semver::Version;
@@ -16,6 +16,8 @@ wasmtime::component::bindgen!({
     path: "src/extensions/wit/since_v0.4.0",
     with: {
         "worktree": ExtensionWorktree,
+        "key-value-store": ExtensionKeyValueStore,
+        "project": ExtensionProject,
         "zed:extension/github": latest::zed::extension::github,
         "zed:extension/platform": latest::zed::extension::platform,
         "zed:extension/nodejs": latest::zed::extension::nodejs,
@@ -27,6 +29,8 @@ wasmtime::component::bindgen!({
 });
 
 pub type ExtensionWorktree = Arc<dyn WorktreeDelegate>;
+pub type ExtensionKeyValueStore = Arc<dyn KeyValueStoreDelegate>;
+pub type ExtensionProject = Arc<dyn ProjectDelegate>;
 
 pub fn linker() -> &'static Linker<WasmState> {
     static LINKER: OnceLock<Linker<WasmState>> = OnceLock::new();
@@ -107,27 +111,30 @@ impl HostWorktree for WasmState {
 }
 
 impl HostProject for WasmState {
-    async fn worktree_ids(&mut self, _project: Resource<Project>) -> wasmtime::Result<Vec<u64>> {
-        Ok(vec![])
+    async fn worktree_ids(
+        &mut self,
+        project: Resource<ExtensionProject>,
+    ) -> wasmtime::Result<Vec<u64>> {
+        latest::HostProject::worktree_ids(self, project).await
     }
 
-    async fn drop(&mut self, _project: Resource<Project>) -> wasmtime::Result<()> {
-        Ok(())
+    async fn drop(&mut self, project: Resource<ExtensionProject>) -> wasmtime::Result<()> {
+        latest::HostProject::drop(self, project).await
     }
 }
 
 impl HostKeyValueStore for WasmState {
     async fn insert(
         &mut self,
-        _store: Resource<KeyValueStore>,
-        _key: String,
-        _value: String,
+        kv_store: Resource<ExtensionKeyValueStore>,
+        key: String,
+        value: String,
     ) -> wasmtime::Result<Result<(), String>> {
-        Ok(Ok(()))
+        latest::HostKeyValueStore::insert(self, kv_store, key, value).await
     }
 
-    async fn drop(&mut self, _store: Resource<KeyValueStore>) -> wasmtime::Result<()> {
-        Ok(())
+    async fn drop(&mut self, kv_store: Resource<ExtensionKeyValueStore>) -> wasmtime::Result<()> {
+        latest::HostKeyValueStore::drop(self, kv_store).await
     }
 }
 
@@ -4,19 +4,24 @@
 //! - Multi-provider AI clients
 //! - Tool execution framework
 //! - Session and preference storage
+//! - Custom slash commands from markdown templates
 //! - MCP (Model Context Protocol) support
+//! - Indexed docs from extension docs providers
 //! - ACP (Agent Client Protocol) server for editor integration
 
 pub mod acp;
 pub mod agent;
 pub mod ai;
 pub mod auth;
+pub mod commands;
 pub mod constants;
+pub mod docs;
 pub mod extensions;
 pub mod mcp;
 pub mod paths;
 pub mod plan;
 pub mod process;
+pub mod secrets;
 pub mod skills;
 pub mod storage;
 pub mod tools;
@@ -1,43 +1,106 @@
 //! MCP Client for local stdio servers
 //!
 //! Handles JSON-RPC communication with a single MCP server.
-//! Uses a background receive loop to avoid race conditions.
+//! Uses a background receive loop to avoid race conditions. The loop also
+//! forwards server notifications, and reports when the connection is lost,
+//! as [`McpClientEvent`]s. Requests from the server (sampling, elicitation)
+//! are answered through an optional [`McpClientDelegate`]. While any of
+//! those are open, our own requests don't time out, since the server may be
+//! waiting on the user before it can answer them.
 
 use anyhow::{anyhow, Result};
 use serde_json::Value;
 use std::collections::HashMap;
 use std::path::Path;
-use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicI64, Ordering};
 use std::sync::Arc;
-use tokio::sync::{mpsc, oneshot, RwLock};
-use tracing::{debug, error, info};
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot, watch, RwLock};
+use tokio::time::Instant;
+use tracing::{debug, error, info, warn};
 
 use super::config::McpServerConfig;
 use super::protocol::{
-    ClientCapabilities, ClientInfo, InitializeParams, InitializeResult, McpRequest, McpResponse,
-    McpToolDef, McpToolResult, ToolCallParams, ToolCallResult, ToolsListResult,
+    ClientCapabilities, ClientInfo, EmptyCapability, InitializeParams, InitializeResult,
+    McpNotification, McpReply, McpRequest, McpResponse, McpToolDef, McpToolResult, ToolCallParams,
+    ToolCallResult, ToolsListResult,
 };
+use super::sampling::McpClientDelegate;
 use super::transport::StdioTransport;
 
-const PROTOCOL_VERSION: &str = "2024-11-05";
+/// Protocol version we ask for on initialize
+const PROTOCOL_VERSION: &str = "2025-06-18";
+/// Versions a server may answer with instead
+const SUPPORTED_PROTOCOL_VERSIONS: &[&str] = &["2025-06-18", "2025-03-26", "2024-11-05"];
+/// First version with `elicitation/create`
+const ELICITATION_PROTOCOL_VERSION: &str = "2025-06-18";
 const REQUEST_TIMEOUT_SECS: u64 = 30;
 
+/// Event from a client's receive loop
+#[derive(Debug, Clone)]
+pub enum McpClientEvent {
+    /// Server sent a notification
+    Notification(McpNotification),
+    /// Connection was lost (server exited or closed stdout)
+    ///
+    /// Not sent when the client is dropped; the channel just closes.
+    Exited(String),
+}
+
+type PendingRequests = RwLock<HashMap<i64, oneshot::Sender<Result<Value>>>>;
+
+/// Number of server requests still being answered
+type OpenServerRequests = watch::Sender<usize>;
+
+/// JSON-RPC error code for unsupported methods
+const METHOD_NOT_FOUND: i64 = -32601;
+/// JSON-RPC error code for malformed params
+const INVALID_PARAMS: i64 = -32602;
+/// JSON-RPC error code for failures while handling a request
+const INTERNAL_ERROR: i64 = -32603;
+
+/// A request the server sent to us
+#[derive(Debug)]
+struct ServerRequest {
+    id: Value,
+    method: String,
+    params: Option<Value>,
+}
+
 /// MCP client for a local server
 pub struct McpClient {
     name: String,
     transport: Arc<StdioTransport>,
     next_id: AtomicI64,
     /// Pending request handlers
-    pending: Arc<RwLock<HashMap<i64, oneshot::Sender<Result<Value>>>>>,
+    pending: Arc<PendingRequests>,
+    /// Receive loop events, until taken by a subscriber
+    events: std::sync::Mutex<Option<mpsc::UnboundedReceiver<McpClientEvent>>>,
     /// Cached tools
     tools: RwLock<Vec<McpToolDef>>,
     /// Shutdown signal
     shutdown_tx: Option<mpsc::Sender<()>>,
+    /// How long to wait for each response
+    request_timeout: Duration,
+    /// Server requests being answered; pauses our request timeouts
+    open_server_requests: Arc<OpenServerRequests>,
+    /// Whether server requests can be answered (advertised on initialize)
+    has_delegate: bool,
+    /// Whether the negotiated protocol version has elicitation
+    elicitation: Arc<AtomicBool>,
 }
 
 impl McpClient {
     /// Connect to a local MCP server
-    pub async fn connect(name: &str, config: &McpServerConfig, working_dir: &Path) -> Result<Self> {
+    ///
+    /// With a delegate, sampling and elicitation requests from the server
+    /// are handed to it; without one they are rejected.
+    pub async fn connect(
+        name: &str,
+        config: &McpServerConfig,
+        working_dir: &Path,
+        delegate: Option<Arc<dyn McpClientDelegate>>,
+    ) -> Result<Self> {
         let McpServerConfig::Local { command, args, env } = config else {
             return Err(anyhow!("McpClient only handles local servers"));
         };
@@ -46,15 +109,20 @@ impl McpClient {
 
         let transport = Arc::new(StdioTransport::spawn(command, args, env, working_dir).await?);
 
-        let pending: Arc<RwLock<HashMap<i64, oneshot::Sender<Result<Value>>>>> =
-            Arc::new(RwLock::new(HashMap::new()));
+        let pending: Arc<PendingRequests> = Arc::new(RwLock::new(HashMap::new()));
 
         let (shutdown_tx, mut shutdown_rx) = mpsc::channel::<()>(1);
+        let (events_tx, events_rx) = mpsc::unbounded_channel();
 
         // Start background receive loop
         let recv_transport = Arc::clone(&transport);
         let recv_pending = Arc::clone(&pending);
         let recv_name = name.to_string();
+        let has_delegate = delegate.is_some();
+        let open_server_requests = Arc::new(watch::Sender::new(0));
+        let recv_open = Arc::clone(&open_server_requests);
+        let elicitation = Arc::new(AtomicBool::new(false));
+        let recv_elicitation = Arc::clone(&elicitation);
 
         tokio::spawn(async move {
             loop {
@@ -66,8 +134,21 @@ impl McpClient {
                     result = recv_transport.receive() => {
                         match result {
                             Ok(message) => {
-                                if let Err(e) = handle_message(&message, &recv_pending).await {
-                                    error!("MCP {} message error: {}", recv_name, e);
+                                match handle_message(&message, &recv_pending, &events_tx).await {
+                                    Ok(Some(request)) => {
+                                        // Requests may wait on the user; answer off the loop
+                                        recv_open.send_modify(|open| *open += 1);
+                                        tokio::spawn(answer_request(
+                                            recv_name.clone(),
+                                            request,
+                                            Arc::clone(&recv_transport),
+                                            delegate.clone(),
+                                            Arc::clone(&recv_open),
+                                            recv_elicitation.load(Ordering::SeqCst),
+                                        ));
+                                    }
+                                    Ok(None) => {}
+                                    Err(e) => error!("MCP {} message error: {}", recv_name, e),
                                 }
                             }
                             Err(e) => {
@@ -77,6 +158,7 @@ impl McpClient {
                                 for (_, tx) in pending.drain() {
                                     let _ = tx.send(Err(anyhow!("Connection lost")));
                                 }
+                                let _ = events_tx.send(McpClientEvent::Exited(e.to_string()));
                                 break;
                             }
                         }
@@ -90,20 +172,38 @@ impl McpClient {
             transport,
             next_id: AtomicI64::new(1),
             pending,
+            events: std::sync::Mutex::new(Some(events_rx)),
             tools: RwLock::new(Vec::new()),
             shutdown_tx: Some(shutdown_tx),
+            request_timeout: Duration::from_secs(REQUEST_TIMEOUT_SECS),
+            open_server_requests,
+            has_delegate,
+            elicitation,
         };
 
         Ok(client)
     }
 
+    /// Override the default request timeout
+    pub fn with_request_timeout(mut self, timeout: Duration) -> Self {
+        self.request_timeout = timeout;
+        self
+    }
+
     /// Initialize the MCP connection (required before using tools)
+    ///
+    /// Elicitation is advertised with a delegate, but only accepted from
+    /// servers that answer with a protocol version that has it.
     pub async fn initialize(&self) -> Result<InitializeResult> {
         info!("Initializing MCP connection for {}", self.name);
 
         let params = InitializeParams {
             protocol_version: PROTOCOL_VERSION.to_string(),
-            capabilities: ClientCapabilities::default(),
+            capabilities: ClientCapabilities {
+                sampling: self.has_delegate.then(EmptyCapability::default),
+                elicitation: self.has_delegate.then(EmptyCapability::default),
+                ..Default::default()
+            },
             client_info: ClientInfo {
                 name: "krusty".to_string(),
                 version: env!("CARGO_PKG_VERSION").to_string(),
@@ -123,6 +223,16 @@ impl McpClient {
             "MCP {} initialized (protocol: {})",
             self.name, result.protocol_version
         );
+        if !SUPPORTED_PROTOCOL_VERSIONS.contains(&result.protocol_version.as_str()) {
+            warn!(
+                "MCP {} answered with unknown protocol version {}",
+                self.name, result.protocol_version
+            );
+        }
+        self.elicitation.store(
+            self.has_delegate && supports_elicitation(&result.protocol_version),
+            Ordering::SeqCst,
+        );
 
         // Send initialized notification
         self.notify("notifications/initialized", None).await?;
@@ -184,7 +294,25 @@ impl McpClient {
         self.transport.is_alive().await
     }
 
+    /// Kill the server process if it is still running
+    pub async fn kill(&self) {
+        self.transport.kill().await;
+    }
+
+    /// Last lines the server wrote to stderr
+    pub fn stderr_tail(&self) -> Vec<String> {
+        self.transport.stderr_tail()
+    }
+
+    /// Take the receive loop's event stream (only one subscriber per client)
+    pub fn take_events(&self) -> Option<mpsc::UnboundedReceiver<McpClientEvent>> {
+        self.events.lock().ok().and_then(|mut events| events.take())
+    }
+
     /// Send a request and wait for response
+    ///
+    /// The timeout is paused while the server has requests of its own open,
+    /// and starts over once they are answered.
     async fn request<R: for<'de> serde::Deserialize<'de>>(
         &self,
         method: &str,
@@ -197,24 +325,38 @@ impl McpClient {
         debug!("MCP {} request [{}]: {}", self.name, id, method);
 
         // Create response channel
-        let (tx, rx) = oneshot::channel();
+        let (tx, mut rx) = oneshot::channel();
         self.pending.write().await.insert(id, tx);
 
         // Send request
         self.transport.send(&json).await?;
 
         // Wait for response with timeout
-        let result =
-            tokio::time::timeout(std::time::Duration::from_secs(REQUEST_TIMEOUT_SECS), rx).await;
+        let mut open = self.open_server_requests.subscribe();
+        let deadline = tokio::time::sleep(self.request_timeout);
+        tokio::pin!(deadline);
+        let result = loop {
+            let answering = *open.borrow_and_update() > 0;
+            tokio::select! {
+                result = &mut rx => break Some(result),
+                _ = &mut deadline, if !answering => break None,
+                Ok(()) = open.changed() => {
+                    deadline.as_mut().reset(Instant::now() + self.request_timeout);
+                }
+            }
+        };
 
         match result {
-            Ok(Ok(Ok(value))) => Ok(serde_json::from_value(value)?),
-            Ok(Ok(Err(e))) => Err(e),
-            Ok(Err(_)) => Err(anyhow!("Request cancelled")),
-            Err(_) => {
+            Some(Ok(Ok(value))) => Ok(serde_json::from_value(value)?),
+            Some(Ok(Err(e))) => Err(e),
+            Some(Err(_)) => Err(anyhow!("Request cancelled")),
+            None => {
                 // Remove pending request on timeout
                 self.pending.write().await.remove(&id);
-                Err(anyhow!("Request timed out after {}s", REQUEST_TIMEOUT_SECS))
+                Err(anyhow!(
+                    "Request timed out after {}s",
+                    self.request_timeout.as_secs()
+                ))
             }
         }
     }
@@ -251,14 +393,27 @@ impl Drop for McpClient {
 }
 
 /// Handle an incoming message (called by receive loop)
+///
+/// Responses and notifications are dispatched here; server requests are
+/// returned for the caller to answer.
 async fn handle_message(
     message: &str,
-    pending: &RwLock<HashMap<i64, oneshot::Sender<Result<Value>>>>,
-) -> Result<()> {
+    pending: &PendingRequests,
+    events: &mpsc::UnboundedSender<McpClientEvent>,
+) -> Result<Option<ServerRequest>> {
     let response: McpResponse = serde_json::from_str(message)?;
 
+    // A request from the server has both an id and a method
+    if let (Some(id), Some(method)) = (&response.id, &response.method) {
+        return Ok(Some(ServerRequest {
+            id: id.clone(),
+            method: method.clone(),
+            params: response.params,
+        }));
+    }
+
     // Check if it's a response to a request
-    if let Some(id) = response.id {
+    if let Some(id) = response.id.as_ref().and_then(|id| id.as_i64()) {
         let mut pending = pending.write().await;
         if let Some(tx) = pending.remove(&id) {
             if let Some(error) = response.error {
@@ -267,14 +422,228 @@ async fn handle_message(
                 let _ = tx.send(Ok(response.result.unwrap_or(Value::Null)));
             }
         }
-        return Ok(());
+        return Ok(None);
     }
 
     // Handle notifications (server → client)
-    if let Some(method) = &response.method {
+    if let Some(method) = response.method {
         debug!("MCP notification: {}", method);
-        // We don't need to handle notifications currently
+        let notification = McpNotification::parse(&method, response.params);
+        let _ = events.send(McpClientEvent::Notification(notification));
+    }
+
+    Ok(None)
+}
+
+/// Answer a server request through the delegate and send the reply
+async fn answer_request(
+    server: String,
+    request: ServerRequest,
+    transport: Arc<StdioTransport>,
+    delegate: Option<Arc<dyn McpClientDelegate>>,
+    open: Arc<OpenServerRequests>,
+    elicitation: bool,
+) {
+    debug!("MCP {} server request: {}", server, request.method);
+    let reply = dispatch_request(&server, request, delegate.as_deref(), elicitation).await;
+    match serde_json::to_string(&reply) {
+        Ok(json) => {
+            if let Err(e) = transport.send(&json).await {
+                error!("MCP {} failed to reply: {}", server, e);
+            }
+        }
+        Err(e) => error!("MCP {} failed to encode reply: {}", server, e),
+    }
+    open.send_modify(|open| *open = open.saturating_sub(1));
+}
+
+/// Whether a negotiated protocol version has elicitation
+///
+/// Versions are dates, so they compare as strings.
+fn supports_elicitation(protocol_version: &str) -> bool {
+    protocol_version >= ELICITATION_PROTOCOL_VERSION
+}
+
+async fn dispatch_request(
+    server: &str,
+    request: ServerRequest,
+    delegate: Option<&dyn McpClientDelegate>,
+    elicitation: bool,
+) -> McpReply {
+    let ServerRequest { id, method, params } = request;
+    let params = params.unwrap_or(Value::Null);
+
+    let result = match (method.as_str(), delegate) {
+        ("ping", _) => Ok(serde_json::json!({})),
+        ("sampling/createMessage", Some(delegate)) => match serde_json::from_value(params) {
+            Ok(params) => delegate
+                .create_message(server, params)
+                .await
+                .and_then(|r| Ok(serde_json::to_value(r)?)),
+            Err(e) => return McpReply::error(id, INVALID_PARAMS, e.to_string()),
+        },
+        ("elicitation/create", Some(delegate)) if elicitation => {
+            match serde_json::from_value(params) {
+                Ok(params) => delegate
+                    .elicit(server, params)
+                    .await
+                    .and_then(|r| Ok(serde_json::to_value(r)?)),
+                Err(e) => return McpReply::error(id, INVALID_PARAMS, e.to_string()),
+            }
+        }
+        _ => {
+            return McpReply::error(
+                id,
+                METHOD_NOT_FOUND,
+                format!("Method not supported: {}", method),
+            )
+        }
+    };
+
+    match result {
+        Ok(value) => McpReply::result(id, value),
+        Err(e) => McpReply::error(id, INTERNAL_ERROR, e.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mcp::protocol::{
+        format_mcp_result, CreateMessageParams, CreateMessageResult, ElicitParams, ElicitResult,
+        McpContent,
+    };
+
+    #[tokio::test]
+    async fn test_handle_message_routes_responses_and_notifications() {
+        let pending: PendingRequests = RwLock::new(HashMap::new());
+        let (events_tx, mut events_rx) = mpsc::unbounded_channel();
+
+        let (tx, rx) = oneshot::channel();
+        pending.write().await.insert(1, tx);
+        handle_message(
+            r#"{"jsonrpc":"2.0","id":1,"result":{"ok":true}}"#,
+            &pending,
+            &events_tx,
+        )
+        .await
+        .unwrap();
+        assert_eq!(rx.await.unwrap().unwrap()["ok"], true);
+
+        let request = handle_message(
+            r#"{"jsonrpc":"2.0","id":"s-1","method":"sampling/createMessage","params":{}}"#,
+            &pending,
+            &events_tx,
+        )
+        .await
+        .unwrap()
+        .expect("server request");
+        assert_eq!(request.id, serde_json::json!("s-1"));
+
+        // Without a delegate, sampling is rejected but ping still works
+        let reply = dispatch_request("test", request, None, true).await;
+        assert_eq!(reply.error.unwrap().code, METHOD_NOT_FOUND);
+        let ping = ServerRequest {
+            id: Value::from(2),
+            method: "ping".to_string(),
+            params: None,
+        };
+        assert!(dispatch_request("test", ping, None, true)
+            .await
+            .result
+            .is_some());
+
+        handle_message(
+            r#"{"jsonrpc":"2.0","method":"notifications/tools/list_changed"}"#,
+            &pending,
+            &events_tx,
+        )
+        .await
+        .unwrap();
+        assert!(matches!(
+            events_rx.try_recv(),
+            Ok(McpClientEvent::Notification(
+                McpNotification::ToolsListChanged
+            ))
+        ));
     }
 
-    Ok(())
+    /// Answers sampling slowly, as a user taking their time would
+    struct SlowDelegate;
+
+    #[async_trait::async_trait]
+    impl McpClientDelegate for SlowDelegate {
+        async fn create_message(
+            &self,
+            _server: &str,
+            _params: CreateMessageParams,
+        ) -> Result<CreateMessageResult> {
+            tokio::time::sleep(Duration::from_millis(300)).await;
+            Ok(CreateMessageResult {
+                role: "assistant".to_string(),
+                content: McpContent::Text {
+                    text: "slow answer".to_string(),
+                },
+                model: "test".to_string(),
+                stop_reason: None,
+            })
+        }
+
+        async fn elicit(&self, _server: &str, _params: ElicitParams) -> Result<ElicitResult> {
+            Err(anyhow!("not used"))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_elicitation_needs_negotiated_version() {
+        assert!(supports_elicitation("2025-06-18"));
+        assert!(!supports_elicitation("2025-03-26"));
+        assert!(!supports_elicitation("2024-11-05"));
+
+        let elicit = || ServerRequest {
+            id: Value::from(3),
+            method: "elicitation/create".to_string(),
+            params: Some(serde_json::json!({"message": "Name?"})),
+        };
+        // The test delegate fails elicitation, so reaching it is an internal error
+        let reply = dispatch_request("test", elicit(), Some(&SlowDelegate), false).await;
+        assert_eq!(reply.error.unwrap().code, METHOD_NOT_FOUND);
+        let reply = dispatch_request("test", elicit(), Some(&SlowDelegate), true).await;
+        assert_eq!(reply.error.unwrap().code, INTERNAL_ERROR);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_request_waits_while_server_request_is_answered() {
+        // Asks for sampling before answering the tool call, and says
+        // whether the answer came back
+        let script = r#"
+            read -r call
+            printf '%s\n' '{"jsonrpc":"2.0","id":"s-1","method":"sampling/createMessage","params":{"messages":[{"role":"user","content":{"type":"text","text":"hi"}}]}}'
+            read -r reply
+            case "$reply" in
+                *'slow answer'*) text=answered ;;
+                *) text=unanswered ;;
+            esac
+            printf '{"jsonrpc":"2.0","id":1,"result":{"content":[{"type":"text","text":"%s"}]}}\n' "$text"
+            cat >/dev/null
+        "#;
+        let config = McpServerConfig::Local {
+            command: "sh".to_string(),
+            args: vec!["-c".to_string(), script.to_string()],
+            env: HashMap::new(),
+        };
+        let client = McpClient::connect(
+            "slow",
+            &config,
+            &std::env::temp_dir(),
+            Some(Arc::new(SlowDelegate)),
+        )
+        .await
+        .unwrap()
+        .with_request_timeout(Duration::from_millis(100));
+
+        let result = client.call_tool("ask", Value::Null).await.unwrap();
+        assert_eq!(format_mcp_result(&result), "answered");
+    }
 }
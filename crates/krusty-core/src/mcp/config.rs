@@ -1,42 +1,183 @@
 //! MCP configuration parsing
 //!
-//! Parses .mcp.json files. Supports two server types:
+//! Parses layered MCP config files, later layers overriding earlier ones:
+//! 1. Global: `~/.krusty/mcp.json`
+//! 2. Project: `<project>/.mcp.json` (usually committed)
+//! 3. Local: `<project>/.krusty/mcp.local.json` (personal overrides)
+//!
+//! Supports two server types:
 //! - Local (stdio): Spawns a local process, we act as MCP client
 //! - Remote (url): Passed to Anthropic API's MCP Connector
+//!
+//! A later layer only needs the fields it changes, so `{"disabled": true}`
+//! in the local layer turns off a server a teammate added to `.mcp.json`.
 
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// A config file layer, in increasing precedence
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum McpConfigLayer {
+    /// ~/.krusty/mcp.json
+    Global,
+    /// <project>/.mcp.json
+    Project,
+    /// <project>/.krusty/mcp.local.json
+    Local,
+}
+
+impl McpConfigLayer {
+    /// Config file locations for a project, lowest precedence first
+    pub fn paths(working_dir: &Path) -> Vec<(McpConfigLayer, PathBuf)> {
+        vec![
+            (McpConfigLayer::Global, crate::paths::mcp_config_path()),
+            (McpConfigLayer::Project, working_dir.join(".mcp.json")),
+            (
+                McpConfigLayer::Local,
+                working_dir.join(".krusty").join("mcp.local.json"),
+            ),
+        ]
+    }
+}
+
+impl std::fmt::Display for McpConfigLayer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            McpConfigLayer::Global => write!(f, "global"),
+            McpConfigLayer::Project => write!(f, "project"),
+            McpConfigLayer::Local => write!(f, "local"),
+        }
+    }
+}
 
-/// MCP configuration from .mcp.json
+/// MCP configuration merged from all layers
 #[derive(Debug, Clone, Default, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct McpConfig {
     #[serde(default)]
     pub mcp_servers: HashMap<String, McpServerConfigRaw>,
+    /// Layers that mention each server, lowest precedence first
+    #[serde(skip)]
+    pub origins: HashMap<String, Vec<McpConfigLayer>>,
 }
 
 /// Raw server configuration from JSON
-#[derive(Debug, Clone, Deserialize)]
-#[serde(untagged)]
-pub enum McpServerConfigRaw {
-    /// Local server (spawns process, stdio transport)
-    Local {
-        command: String,
-        #[serde(default)]
-        args: Vec<String>,
-        #[serde(default)]
-        env: HashMap<String, String>,
-    },
-    /// Remote server (passed to Anthropic MCP Connector API)
-    Remote {
-        #[serde(rename = "type")]
-        server_type: String, // Must be "url"
-        url: String,
-        #[serde(default)]
-        authorization_token: Option<String>,
-    },
+///
+/// Every field is optional so a higher layer can override just part of
+/// a server defined in a lower one.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct McpServerConfigRaw {
+    /// Local server: command to spawn (stdio transport)
+    #[serde(default)]
+    pub command: Option<String>,
+    #[serde(default)]
+    pub args: Option<Vec<String>>,
+    /// Merged key-by-key across layers
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    /// Remote server: must be "url"
+    #[serde(default, rename = "type")]
+    pub server_type: Option<String>,
+    #[serde(default)]
+    pub url: Option<String>,
+    #[serde(default, rename = "authorization_token", alias = "authorizationToken")]
+    pub authorization_token: Option<String>,
+    /// Skip this server entirely
+    #[serde(default)]
+    pub disabled: Option<bool>,
+    /// Working directory for local servers (relative to the project root)
+    #[serde(default)]
+    pub cwd: Option<String>,
+    /// Request timeout in seconds
+    #[serde(default)]
+    pub timeout: Option<u64>,
+    /// Only expose these tools
+    #[serde(default)]
+    pub allowed_tools: Option<Vec<String>>,
+    /// Never expose these tools
+    #[serde(default)]
+    pub denied_tools: Option<Vec<String>>,
+}
+
+impl McpServerConfigRaw {
+    /// Whether this entry defines a server (rather than only overriding options)
+    pub fn has_definition(&self) -> bool {
+        self.command.is_some() || self.url.is_some()
+    }
+
+    /// Apply a higher-precedence entry on top of this one
+    pub fn merge(&mut self, other: McpServerConfigRaw) {
+        // Switching transport replaces the old definition wholesale
+        if other.command.is_some() {
+            self.server_type = None;
+            self.url = None;
+            self.authorization_token = None;
+            self.command = other.command;
+        }
+        if other.url.is_some() {
+            self.command = None;
+            self.args = None;
+            self.url = other.url;
+        }
+        if other.args.is_some() {
+            self.args = other.args;
+        }
+        self.env.extend(other.env);
+        if other.server_type.is_some() {
+            self.server_type = other.server_type;
+        }
+        if other.authorization_token.is_some() {
+            self.authorization_token = other.authorization_token;
+        }
+        if other.disabled.is_some() {
+            self.disabled = other.disabled;
+        }
+        if other.cwd.is_some() {
+            self.cwd = other.cwd;
+        }
+        if other.timeout.is_some() {
+            self.timeout = other.timeout;
+        }
+        if other.allowed_tools.is_some() {
+            self.allowed_tools = other.allowed_tools;
+        }
+        if other.denied_tools.is_some() {
+            self.denied_tools = other.denied_tools;
+        }
+    }
+
+    /// Resolve transport-independent options
+    pub fn options(&self, working_dir: &Path) -> McpServerOptions {
+        McpServerOptions {
+            disabled: self.disabled.unwrap_or(false),
+            cwd: self.cwd.as_deref().map(|cwd| resolve_cwd(cwd, working_dir)),
+            timeout: self.timeout.map(Duration::from_secs),
+            tools: McpToolFilter {
+                allowed: self.allowed_tools.clone(),
+                denied: self.denied_tools.clone().unwrap_or_default(),
+            },
+        }
+    }
+}
+
+/// Resolve a configured cwd: `~/` is the home dir, relative paths are
+/// relative to the project root
+fn resolve_cwd(cwd: &str, working_dir: &Path) -> PathBuf {
+    if let Some(rest) = cwd.strip_prefix("~/") {
+        if let Some(home) = dirs::home_dir() {
+            return home.join(rest);
+        }
+    }
+    let path = Path::new(cwd);
+    if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        working_dir.join(path)
+    }
 }
 
 /// Resolved server configuration
@@ -55,6 +196,43 @@ pub enum McpServerConfig {
     },
 }
 
+/// Per-server settings that apply on top of the transport config
+#[derive(Debug, Clone, Default)]
+pub struct McpServerOptions {
+    pub disabled: bool,
+    /// Working directory for the server process (defaults to project root)
+    pub cwd: Option<PathBuf>,
+    /// Request timeout (defaults to 30s)
+    pub timeout: Option<Duration>,
+    pub tools: McpToolFilter,
+}
+
+/// Tool allow/deny lists for a server
+#[derive(Debug, Clone, Default)]
+pub struct McpToolFilter {
+    /// When set, only these tools are exposed
+    pub allowed: Option<Vec<String>>,
+    /// These tools are never exposed (wins over `allowed`)
+    pub denied: Vec<String>,
+}
+
+impl McpToolFilter {
+    /// Check whether a tool passes the filter
+    pub fn allows(&self, tool: &str) -> bool {
+        if self.denied.iter().any(|t| t == tool) {
+            return false;
+        }
+        match &self.allowed {
+            Some(allowed) => allowed.iter().any(|t| t == tool),
+            None => true,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.allowed.is_none() && self.denied.is_empty()
+    }
+}
+
 /// Remote server config for Anthropic API
 #[derive(Debug, Clone, Serialize)]
 pub struct RemoteMcpServer {
@@ -81,91 +259,127 @@ impl McpServerConfig {
             McpServerConfig::Remote { .. } => "remote",
         }
     }
+
+    /// One-line description for the UI (command line or URL)
+    pub fn summary(&self) -> String {
+        match self {
+            McpServerConfig::Local { command, args, .. } => std::iter::once(command.as_str())
+                .chain(args.iter().map(String::as_str))
+                .collect::<Vec<_>>()
+                .join(" "),
+            McpServerConfig::Remote { url, .. } => url.clone(),
+        }
+    }
 }
 
 impl McpConfig {
-    /// Load config from .mcp.json in project root
+    /// Load and merge all config layers for a project
     pub async fn load(working_dir: &Path) -> Result<Self> {
-        let config_path = working_dir.join(".mcp.json");
+        Self::load_layers(&McpConfigLayer::paths(working_dir)).await
+    }
 
-        if !config_path.exists() {
-            tracing::debug!("No .mcp.json found at {:?}", config_path);
-            return Ok(Self::default());
-        }
+    /// Load and merge the given config files, lowest precedence first
+    ///
+    /// Missing files are skipped; a file that fails to parse is an error.
+    pub async fn load_layers(layers: &[(McpConfigLayer, PathBuf)]) -> Result<Self> {
+        let mut merged = Self::default();
 
-        let content = tokio::fs::read_to_string(&config_path)
-            .await
-            .with_context(|| format!("Failed to read {:?}", config_path))?;
+        for (layer, path) in layers {
+            if !path.exists() {
+                tracing::debug!("No {} MCP config at {:?}", layer, path);
+                continue;
+            }
 
-        let config: McpConfig = serde_json::from_str(&content)
-            .with_context(|| format!("Failed to parse {:?}", config_path))?;
+            let content = tokio::fs::read_to_string(path)
+                .await
+                .with_context(|| format!("Failed to read {:?}", path))?;
 
-        tracing::info!(
-            "Loaded MCP config with {} servers from {:?}",
-            config.mcp_servers.len(),
-            config_path
-        );
+            let config: McpConfig = serde_json::from_str(&content)
+                .with_context(|| format!("Failed to parse {:?}", path))?;
+
+            tracing::info!(
+                "Loaded {} MCP config with {} servers from {:?}",
+                layer,
+                config.mcp_servers.len(),
+                path
+            );
+
+            merged.merge(*layer, config);
+        }
 
-        Ok(config)
+        Ok(merged)
     }
 
-    /// Get resolved server configurations
+    /// Merge a higher-precedence layer into this config
+    pub fn merge(&mut self, layer: McpConfigLayer, other: McpConfig) {
+        for (name, raw) in other.mcp_servers {
+            self.origins.entry(name.clone()).or_default().push(layer);
+            self.mcp_servers.entry(name).or_default().merge(raw);
+        }
+    }
+
+    /// Options for every configured name, including option-only entries
+    /// that target servers defined elsewhere (e.g. by an extension)
+    pub fn options(&self, working_dir: &Path) -> HashMap<String, McpServerOptions> {
+        self.mcp_servers
+            .iter()
+            .map(|(name, raw)| (name.clone(), raw.options(working_dir)))
+            .collect()
+    }
+
+    /// Get resolved server configurations (including disabled servers)
+    ///
+    /// Entries without a command or url only carry options and are skipped.
     pub async fn servers(&self) -> HashMap<String, McpServerConfig> {
         let mut result = HashMap::new();
         for (name, raw) in &self.mcp_servers {
-            let config = match raw {
-                McpServerConfigRaw::Local { command, args, env } => {
-                    let mut expanded_env = HashMap::new();
-                    for (k, v) in env {
-                        expanded_env.insert(k.clone(), expand_env_var(v).await);
-                    }
-                    McpServerConfig::Local {
-                        command: command.clone(),
-                        args: args.clone(),
-                        env: expanded_env,
-                    }
+            let config = if let Some(ref command) = raw.command {
+                let mut expanded_env = HashMap::new();
+                for (k, v) in &raw.env {
+                    expanded_env.insert(k.clone(), expand_env_var(v).await);
                 }
-                McpServerConfigRaw::Remote {
-                    url,
-                    authorization_token,
-                    ..
-                } => {
-                    let token = match authorization_token {
-                        Some(t) => Some(expand_env_var(t).await),
-                        None => None,
-                    };
-                    McpServerConfig::Remote {
-                        url: url.clone(),
-                        authorization_token: token,
-                    }
+                McpServerConfig::Local {
+                    command: command.clone(),
+                    args: raw.args.clone().unwrap_or_default(),
+                    env: expanded_env,
                 }
+            } else if let Some(ref url) = raw.url {
+                let token = match raw.authorization_token {
+                    Some(ref t) => Some(expand_env_var(t).await),
+                    None => None,
+                };
+                McpServerConfig::Remote {
+                    url: url.clone(),
+                    authorization_token: token,
+                }
+            } else {
+                continue;
             };
             result.insert(name.clone(), config);
         }
         result
     }
 
-    /// Get remote servers formatted for Anthropic API's MCP Connector
+    /// Get enabled remote servers formatted for Anthropic API's MCP Connector
     pub async fn remote_servers_for_api(&self) -> Vec<RemoteMcpServer> {
         let mut result = Vec::new();
         for (name, raw) in &self.mcp_servers {
-            if let McpServerConfigRaw::Remote {
-                url,
-                authorization_token,
-                ..
-            } = raw
-            {
-                let token = match authorization_token {
-                    Some(t) => Some(expand_env_var(t).await),
-                    None => None,
-                };
-                result.push(RemoteMcpServer {
-                    server_type: "url".to_string(),
-                    url: url.clone(),
-                    name: name.clone(),
-                    authorization_token: token,
-                });
+            if raw.disabled == Some(true) || raw.command.is_some() {
+                continue;
             }
+            let Some(ref url) = raw.url else {
+                continue;
+            };
+            let token = match raw.authorization_token {
+                Some(ref t) => Some(expand_env_var(t).await),
+                None => None,
+            };
+            result.push(RemoteMcpServer {
+                server_type: "url".to_string(),
+                url: url.clone(),
+                name: name.clone(),
+                authorization_token: token,
+            });
         }
         result
     }
@@ -320,6 +534,127 @@ mod tests {
         ));
     }
 
+    fn parse(json: &str) -> McpConfig {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_layers_merge_partial_overrides() {
+        let mut config = McpConfig::default();
+        config.merge(
+            McpConfigLayer::Global,
+            parse(
+                r#"{"mcpServers": {
+                    "search": {"command": "search-mcp", "env": {"A": "1"}},
+                    "docs": {"command": "docs-mcp"}
+                }}"#,
+            ),
+        );
+        config.merge(
+            McpConfigLayer::Project,
+            parse(r#"{"mcpServers": {"search": {"args": ["--fast"], "timeout": 5}}}"#),
+        );
+        config.merge(
+            McpConfigLayer::Local,
+            parse(
+                r#"{"mcpServers": {
+                    "search": {"env": {"B": "2"}},
+                    "docs": {"disabled": true}
+                }}"#,
+            ),
+        );
+
+        assert_eq!(
+            config.origins["search"],
+            vec![
+                McpConfigLayer::Global,
+                McpConfigLayer::Project,
+                McpConfigLayer::Local
+            ]
+        );
+
+        let servers = config.servers().await;
+        let Some(McpServerConfig::Local { command, args, env }) = servers.get("search") else {
+            panic!("search should be a local server");
+        };
+        assert_eq!(command, "search-mcp");
+        assert_eq!(args, &vec!["--fast".to_string()]);
+        assert_eq!(env.len(), 2);
+
+        let options = config.options(Path::new("/project"));
+        assert_eq!(options["search"].timeout, Some(Duration::from_secs(5)));
+        assert!(options["docs"].disabled);
+        assert!(!options["search"].disabled);
+    }
+
+    #[tokio::test]
+    async fn test_switching_transport_replaces_definition() {
+        let mut config = McpConfig::default();
+        config.merge(
+            McpConfigLayer::Project,
+            parse(r#"{"mcpServers": {"api": {"command": "api-mcp", "args": ["-v"]}}}"#),
+        );
+        config.merge(
+            McpConfigLayer::Local,
+            parse(r#"{"mcpServers": {"api": {"type": "url", "url": "https://mcp.example.com"}}}"#),
+        );
+
+        let servers = config.servers().await;
+        assert!(matches!(
+            servers.get("api"),
+            Some(McpServerConfig::Remote { .. })
+        ));
+        assert_eq!(config.remote_servers_for_api().await.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_option_only_entry_has_no_definition() {
+        let config = parse(r#"{"mcpServers": {"ext-server": {"deniedTools": ["delete"]}}}"#);
+        assert!(config.servers().await.is_empty());
+        let options = config.options(Path::new("/project"));
+        assert!(!options["ext-server"].tools.allows("delete"));
+    }
+
+    #[tokio::test]
+    async fn test_load_layers_skips_missing_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = dir.path().join(".mcp.json");
+        std::fs::write(&project, r#"{"mcpServers": {"a": {"command": "a-mcp"}}}"#).unwrap();
+
+        let config = McpConfig::load_layers(&[
+            (McpConfigLayer::Global, dir.path().join("missing.json")),
+            (McpConfigLayer::Project, project),
+        ])
+        .await
+        .unwrap();
+
+        assert_eq!(config.origins["a"], vec![McpConfigLayer::Project]);
+    }
+
+    #[test]
+    fn test_tool_filter() {
+        let filter = McpToolFilter {
+            allowed: Some(vec!["read".to_string(), "write".to_string()]),
+            denied: vec!["write".to_string()],
+        };
+        assert!(filter.allows("read"));
+        assert!(!filter.allows("write"));
+        assert!(!filter.allows("delete"));
+        assert!(McpToolFilter::default().allows("anything"));
+    }
+
+    #[test]
+    fn test_resolve_cwd_relative_to_project() {
+        assert_eq!(
+            resolve_cwd("tools/server", Path::new("/project")),
+            PathBuf::from("/project/tools/server")
+        );
+        assert_eq!(
+            resolve_cwd("/opt/mcp", Path::new("/project")),
+            PathBuf::from("/opt/mcp")
+        );
+    }
+
     #[tokio::test]
     async fn test_expand_env_var() {
         // Test that direct values pass through
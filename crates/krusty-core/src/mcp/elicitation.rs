@@ -0,0 +1,218 @@
+//! Elicitation form fields
+//!
+//! `elicitation/create` requests carry a flat JSON schema whose properties
+//! are strings, numbers, booleans or enums. Frontends turn each property
+//! into a question and convert the answers back into JSON values here.
+
+use serde_json::{Map, Value};
+
+use super::protocol::ElicitParams;
+
+/// Kind of value a field accepts
+#[derive(Debug, Clone, PartialEq)]
+pub enum ElicitFieldKind {
+    Text,
+    Number {
+        integer: bool,
+    },
+    Boolean,
+    /// One of `values`, displayed with `labels`
+    Enum {
+        values: Vec<String>,
+        labels: Vec<String>,
+    },
+}
+
+/// A single property from the requested schema
+#[derive(Debug, Clone)]
+pub struct ElicitField {
+    pub name: String,
+    /// Human label (schema `title`, else the property name)
+    pub title: String,
+    pub description: Option<String>,
+    pub kind: ElicitFieldKind,
+    pub required: bool,
+}
+
+impl ElicitParams {
+    /// Form fields, required ones first
+    ///
+    /// JSON objects don't keep key order here, so fields are otherwise
+    /// sorted by property name.
+    pub fn fields(&self) -> Vec<ElicitField> {
+        let required: Vec<&str> = self
+            .requested_schema
+            .get("required")
+            .and_then(|r| r.as_array())
+            .map(|r| r.iter().filter_map(|v| v.as_str()).collect())
+            .unwrap_or_default();
+
+        let Some(properties) = self
+            .requested_schema
+            .get("properties")
+            .and_then(|p| p.as_object())
+        else {
+            return Vec::new();
+        };
+
+        let mut fields: Vec<ElicitField> = properties
+            .iter()
+            .map(|(name, schema)| {
+                let str_field = |key: &str| schema.get(key).and_then(|v| v.as_str());
+                let kind = if let Some(values) = schema.get("enum").and_then(|e| e.as_array()) {
+                    let values: Vec<String> = values
+                        .iter()
+                        .map(|v| {
+                            v.as_str()
+                                .map(String::from)
+                                .unwrap_or_else(|| v.to_string())
+                        })
+                        .collect();
+                    let labels = schema
+                        .get("enumNames")
+                        .and_then(|n| n.as_array())
+                        .filter(|n| n.len() == values.len())
+                        .map(|n| {
+                            n.iter()
+                                .map(|v| v.as_str().unwrap_or_default().to_string())
+                                .collect()
+                        })
+                        .unwrap_or_else(|| values.clone());
+                    ElicitFieldKind::Enum { values, labels }
+                } else {
+                    match str_field("type") {
+                        Some("boolean") => ElicitFieldKind::Boolean,
+                        Some("integer") => ElicitFieldKind::Number { integer: true },
+                        Some("number") => ElicitFieldKind::Number { integer: false },
+                        _ => ElicitFieldKind::Text,
+                    }
+                };
+
+                ElicitField {
+                    name: name.clone(),
+                    title: str_field("title").unwrap_or(name).to_string(),
+                    description: str_field("description").map(String::from),
+                    kind,
+                    required: required.contains(&name.as_str()),
+                }
+            })
+            .collect();
+        fields.sort_by_key(|f| !f.required);
+        fields
+    }
+}
+
+impl ElicitField {
+    /// Convert a typed answer into a JSON value for this field
+    ///
+    /// Returns None when the text can't be converted (e.g. "abc" for a
+    /// number, or a value outside an enum).
+    pub fn parse_value(&self, text: &str) -> Option<Value> {
+        let text = text.trim();
+        match &self.kind {
+            ElicitFieldKind::Text => Some(Value::String(text.to_string())),
+            ElicitFieldKind::Number { integer: true } => text.parse::<i64>().ok().map(Value::from),
+            ElicitFieldKind::Number { integer: false } => text.parse::<f64>().ok().map(Value::from),
+            ElicitFieldKind::Boolean => match text.to_lowercase().as_str() {
+                "yes" | "y" | "true" => Some(Value::Bool(true)),
+                "no" | "n" | "false" => Some(Value::Bool(false)),
+                _ => None,
+            },
+            ElicitFieldKind::Enum { values, labels } => values
+                .iter()
+                .zip(labels)
+                .find(|(value, label)| value.as_str() == text || label.as_str() == text)
+                .map(|(value, _)| Value::String(value.clone())),
+        }
+    }
+}
+
+/// Collect answered fields into the `content` object of an elicitation result
+///
+/// Unanswered optional fields are left out. Returns the names of required
+/// fields that are missing, if any.
+pub fn elicit_content(
+    fields: &[ElicitField],
+    answers: &[Option<Value>],
+) -> Result<Value, Vec<String>> {
+    let mut content = Map::new();
+    let mut missing = Vec::new();
+    for (idx, field) in fields.iter().enumerate() {
+        match answers.get(idx).cloned().flatten() {
+            Some(value) => {
+                content.insert(field.name.clone(), value);
+            }
+            None if field.required => missing.push(field.title.clone()),
+            None => {}
+        }
+    }
+    if missing.is_empty() {
+        Ok(Value::Object(content))
+    } else {
+        Err(missing)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn params() -> ElicitParams {
+        serde_json::from_value(json!({
+            "message": "Configure the deploy",
+            "requestedSchema": {
+                "type": "object",
+                "properties": {
+                    "env": {"type": "string", "enum": ["prod", "dev"], "enumNames": ["Production", "Development"]},
+                    "replicas": {"type": "integer", "title": "Replicas"},
+                    "confirm": {"type": "boolean"},
+                    "note": {"type": "string"}
+                },
+                "required": ["env", "replicas"]
+            }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_fields_from_schema() {
+        let fields = params().fields();
+        assert_eq!(fields.len(), 4);
+
+        let env = fields.iter().find(|f| f.name == "env").unwrap();
+        assert!(env.required);
+        assert_eq!(env.parse_value("Production"), Some(json!("prod")));
+        assert_eq!(env.parse_value("staging"), None);
+
+        let replicas = fields.iter().find(|f| f.name == "replicas").unwrap();
+        assert_eq!(replicas.title, "Replicas");
+        assert_eq!(replicas.parse_value(" 3 "), Some(json!(3)));
+        assert_eq!(replicas.parse_value("three"), None);
+
+        let confirm = fields.iter().find(|f| f.name == "confirm").unwrap();
+        assert_eq!(confirm.kind, ElicitFieldKind::Boolean);
+        assert_eq!(confirm.parse_value("yes"), Some(json!(true)));
+    }
+
+    #[test]
+    fn test_elicit_content_requires_required_fields() {
+        let fields = params().fields();
+        let names: Vec<&str> = fields.iter().map(|f| f.name.as_str()).collect();
+        let mut answers: Vec<Option<Value>> = vec![None; fields.len()];
+
+        let env = names.iter().position(|n| *n == "env").unwrap();
+        answers[env] = Some(json!("dev"));
+        assert_eq!(
+            elicit_content(&fields, &answers),
+            Err(vec!["Replicas".to_string()])
+        );
+
+        let replicas = names.iter().position(|n| *n == "replicas").unwrap();
+        answers[replicas] = Some(json!(2));
+        assert_eq!(
+            elicit_content(&fields, &answers),
+            Ok(json!({"env": "dev", "replicas": 2}))
+        );
+    }
+}
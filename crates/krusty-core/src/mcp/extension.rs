@@ -0,0 +1,97 @@
+//! MCP servers provided by Zed extensions
+//!
+//! Zed extensions declare `[context_servers.<id>]` in extension.toml and
+//! export `context-server-command`. We resolve that command through the
+//! WASM host and run the result as a regular local stdio server.
+
+use std::path::Path;
+use std::sync::Arc;
+use tracing::{info, warn};
+
+use super::config::McpServerConfig;
+use crate::extensions::types::ProjectDelegate;
+use crate::extensions::{installed_extensions, WasmHost};
+
+/// A context server resolved from an installed extension
+#[derive(Debug, Clone)]
+pub struct ExtensionContextServer {
+    /// Context server id (used as the MCP server name)
+    pub name: String,
+    /// Id of the extension that provides it
+    pub extension_id: String,
+    /// Resolved local server configuration
+    pub config: McpServerConfig,
+}
+
+/// Check whether any installed extension declares a context server
+///
+/// Only reads manifests, so it is cheap enough to call before deciding
+/// whether to spin up the WASM host at all.
+pub fn has_extension_servers(extensions_dir: &Path) -> bool {
+    installed_extensions(extensions_dir)
+        .iter()
+        .any(|(_, manifest)| !manifest.context_servers.is_empty())
+}
+
+/// Resolve the commands for every context server declared by installed extensions
+///
+/// Extensions that fail to load or resolve are logged and skipped so one
+/// broken extension doesn't keep the others from starting.
+pub async fn resolve_extension_servers(
+    host: &Arc<WasmHost>,
+    extensions_dir: &Path,
+    project: Arc<dyn ProjectDelegate>,
+) -> Vec<ExtensionContextServer> {
+    let mut servers = Vec::new();
+
+    for (dir, manifest) in installed_extensions(extensions_dir) {
+        if manifest.context_servers.is_empty() {
+            continue;
+        }
+
+        let extension = match host.load_extension_from_dir(&dir).await {
+            Ok(ext) => ext,
+            Err(e) => {
+                warn!("Failed to load extension {}: {:#}", manifest.id, e);
+                continue;
+            }
+        };
+
+        let work_dir = host.work_dir.join(&manifest.id);
+        for server_id in manifest.context_servers.keys() {
+            let command = extension
+                .context_server_command(
+                    Arc::from(server_id.as_str()),
+                    project.clone(),
+                    work_dir.clone(),
+                )
+                .await;
+
+            match command {
+                Ok(command) => {
+                    info!(
+                        "Extension {} provides MCP server {}: {} {:?}",
+                        manifest.id, server_id, command.command, command.args
+                    );
+                    servers.push(ExtensionContextServer {
+                        name: server_id.clone(),
+                        extension_id: manifest.id.clone(),
+                        config: McpServerConfig::Local {
+                            command: command.command,
+                            args: command.args,
+                            env: command.env.into_iter().collect(),
+                        },
+                    });
+                }
+                Err(e) => {
+                    warn!(
+                        "Extension {} failed to resolve context server {}: {:#}",
+                        manifest.id, server_id, e
+                    );
+                }
+            }
+        }
+    }
+
+    servers
+}
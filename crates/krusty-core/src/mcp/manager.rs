@@ -2,46 +2,166 @@
 //!
 //! Simple manager for local stdio servers. Remote servers are handled
 //! by passing them to the Anthropic API's MCP Connector.
+//!
+//! Per-server options from the layered config (disabled, cwd, timeout,
+//! tool allow/deny lists) are applied here.
+//!
+//! Each connected client's notifications and exits are published as
+//! [`McpEvent`]s. The supervisor (see [`McpManager::spawn_supervisor`])
+//! restarts crashed servers with exponential backoff and keeps the tool
+//! registry in sync when a server's tool list changes.
 
 use anyhow::Result;
 use serde_json::Value;
 use std::collections::HashMap;
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
-use tokio::sync::RwLock;
-use tracing::{info, warn};
-
-use super::client::McpClient;
-use super::config::{McpConfig, McpServerConfig, RemoteMcpServer};
-use super::protocol::{McpToolDef, McpToolResult};
+use std::time::{Duration, Instant};
+use tokio::sync::{broadcast, RwLock};
+use tracing::{debug, info, warn};
+
+use crate::extensions::types::ProjectDelegate;
+use crate::extensions::WasmHost;
+use crate::tools::ToolRegistry;
+
+use super::client::{McpClient, McpClientEvent};
+use super::config::{
+    McpConfig, McpConfigLayer, McpServerConfig, McpServerOptions, RemoteMcpServer,
+};
+use super::extension::{resolve_extension_servers, ExtensionContextServer};
+use super::protocol::{McpNotification, McpToolDef, McpToolResult};
+use super::sampling::McpClientDelegate;
+use super::tool::reregister_server_tools;
+
+/// Restart attempts before giving up on a crashing server
+const MAX_RESTART_ATTEMPTS: u32 = 5;
+/// Delay before the first restart; doubles with each attempt
+const RESTART_BASE_DELAY: Duration = Duration::from_secs(1);
+/// Upper bound for the restart delay
+const RESTART_MAX_DELAY: Duration = Duration::from_secs(30);
+/// A server that stayed up this long gets a fresh restart budget
+const STABLE_UPTIME: Duration = Duration::from_secs(60);
+/// Capacity of the event broadcast channel
+const EVENT_CHANNEL_CAPACITY: usize = 256;
+
+/// Backoff delay for a 1-based restart attempt
+fn restart_delay(attempt: u32) -> Duration {
+    let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+    RESTART_BASE_DELAY
+        .saturating_mul(factor)
+        .min(RESTART_MAX_DELAY)
+}
 
 /// Server status
 #[derive(Debug, Clone, PartialEq)]
 pub enum McpServerStatus {
+    Disabled,
     Disconnected,
     Connected,
+    /// Crashed; waiting to retry (1-based attempt)
+    Restarting(u32),
     Error(String),
 }
 
 impl std::fmt::Display for McpServerStatus {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
+            McpServerStatus::Disabled => write!(f, "disabled"),
             McpServerStatus::Disconnected => write!(f, "disconnected"),
             McpServerStatus::Connected => write!(f, "connected"),
+            McpServerStatus::Restarting(attempt) => write!(f, "restarting (attempt {})", attempt),
             McpServerStatus::Error(e) => write!(f, "error: {}", e),
         }
     }
 }
 
+/// Where a server definition came from
+#[derive(Debug, Clone, PartialEq)]
+pub enum McpServerSource {
+    /// Config files that define or override it, lowest precedence first
+    Config(Vec<McpConfigLayer>),
+    /// Context server provided by an installed extension (extension id)
+    Extension(String),
+}
+
+impl std::fmt::Display for McpServerSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            McpServerSource::Config(layers) => {
+                let layers: Vec<String> = layers.iter().map(|l| l.to_string()).collect();
+                write!(f, "{}", layers.join("+"))
+            }
+            McpServerSource::Extension(_) => write!(f, "extension"),
+        }
+    }
+}
+
+/// Health of a local server, tracked across restarts
+#[derive(Debug, Clone, Default)]
+pub struct McpServerHealth {
+    /// Successful automatic restarts this session
+    pub restarts: u32,
+    /// Restart attempt currently scheduled (1-based)
+    pub restarting: Option<u32>,
+    /// Why the server last exited unexpectedly
+    pub last_exit: Option<String>,
+    /// stderr captured when the server last exited
+    pub stderr_tail: Vec<String>,
+    /// Consecutive restart attempts since the server was last stable
+    attempts: u32,
+    connected_at: Option<Instant>,
+    /// Generation of the connected client, to tell its exit from a stale one
+    generation: u64,
+}
+
+/// Event published by the manager
+#[derive(Debug, Clone)]
+pub enum McpEvent {
+    /// A server sent a notification
+    Notification {
+        server: String,
+        notification: McpNotification,
+    },
+    /// A server exited unexpectedly
+    Exited {
+        server: String,
+        reason: String,
+        /// Connection that exited; each connect starts a new generation
+        generation: u64,
+    },
+    /// A restart is scheduled after `delay`
+    Restarting {
+        server: String,
+        attempt: u32,
+        delay: Duration,
+    },
+    /// A server came back after a restart
+    Restarted { server: String, tool_count: usize },
+    /// A server kept failing and won't be restarted automatically
+    GaveUp { server: String, reason: String },
+    /// A server's tools were re-registered
+    ToolsChanged { server: String, tool_count: usize },
+}
+
 /// Server info for UI
 #[derive(Debug, Clone)]
 pub struct McpServerInfo {
     pub name: String,
     pub server_type: String, // "stdio" or "remote"
+    pub source: McpServerSource,
+    /// Command line or URL
+    pub summary: String,
+    pub options: McpServerOptions,
     pub status: McpServerStatus,
     pub tool_count: usize,
     pub tools: Vec<McpToolDef>,
+    /// Tools hidden by the allow/deny lists
+    pub filtered_tools: usize,
     pub error: Option<String>,
+    pub health: McpServerHealth,
+    /// Recent stderr from the running process, or from the last crash
+    pub stderr_tail: Vec<String>,
 }
 
 /// MCP Manager
@@ -52,6 +172,20 @@ pub struct McpManager {
     configs: RwLock<HashMap<String, McpServerConfig>>,
     /// Remote servers (for API)
     remote_servers: RwLock<Vec<RemoteMcpServer>>,
+    /// Servers provided by extensions (merged into configs unless shadowed)
+    extension_servers: RwLock<Vec<ExtensionContextServer>>,
+    /// Origin of each configured server
+    sources: RwLock<HashMap<String, McpServerSource>>,
+    /// Per-server options by name (may target extension servers)
+    options: RwLock<HashMap<String, McpServerOptions>>,
+    /// Restart and crash bookkeeping per local server
+    health: RwLock<HashMap<String, McpServerHealth>>,
+    /// Notifications and lifecycle events for subscribers
+    events: broadcast::Sender<McpEvent>,
+    /// Answers sampling/elicitation requests from servers
+    delegate: RwLock<Option<Arc<dyn McpClientDelegate>>>,
+    /// Generation for the next client connection
+    next_generation: AtomicU64,
     /// Working directory
     working_dir: PathBuf,
 }
@@ -62,38 +196,111 @@ impl McpManager {
             clients: RwLock::new(HashMap::new()),
             configs: RwLock::new(HashMap::new()),
             remote_servers: RwLock::new(Vec::new()),
+            extension_servers: RwLock::new(Vec::new()),
+            sources: RwLock::new(HashMap::new()),
+            options: RwLock::new(HashMap::new()),
+            health: RwLock::new(HashMap::new()),
+            events: broadcast::channel(EVENT_CHANNEL_CAPACITY).0,
+            delegate: RwLock::new(None),
+            next_generation: AtomicU64::new(1),
             working_dir,
         }
     }
 
-    /// Load configuration from .mcp.json
+    /// Set the handler for sampling and elicitation requests
+    ///
+    /// Applies to servers connected afterwards.
+    pub async fn set_client_delegate(&self, delegate: Arc<dyn McpClientDelegate>) {
+        *self.delegate.write().await = Some(delegate);
+    }
+
+    /// Subscribe to notifications and lifecycle events
+    pub fn subscribe(&self) -> broadcast::Receiver<McpEvent> {
+        self.events.subscribe()
+    }
+
+    /// Load configuration from the global, project and local config files
     pub async fn load_config(&self) -> Result<()> {
         let config = McpConfig::load(&self.working_dir).await?;
 
-        let mut configs = self.configs.write().await;
-        *configs = config.servers().await;
+        {
+            let mut configs = self.configs.write().await;
+            *configs = config.servers().await;
 
-        // Store remote servers for API
-        *self.remote_servers.write().await = config.remote_servers_for_api().await;
+            *self.sources.write().await = configs
+                .keys()
+                .map(|name| {
+                    let layers = config.origins.get(name).cloned().unwrap_or_default();
+                    (name.clone(), McpServerSource::Config(layers))
+                })
+                .collect();
 
-        let local_count = configs.values().filter(|c| c.is_local()).count();
-        let remote_count = configs.values().filter(|c| c.is_remote()).count();
+            *self.options.write().await = config.options(&self.working_dir);
 
-        info!(
-            "Loaded MCP config: {} local, {} remote servers",
-            local_count, remote_count
-        );
+            // Store remote servers for API
+            *self.remote_servers.write().await = config.remote_servers_for_api().await;
+
+            let local_count = configs.values().filter(|c| c.is_local()).count();
+            let remote_count = configs.values().filter(|c| c.is_remote()).count();
 
+            info!(
+                "Loaded MCP config: {} local, {} remote servers",
+                local_count, remote_count
+            );
+        }
+
+        self.merge_extension_servers().await;
         Ok(())
     }
 
-    /// Connect to all local servers in parallel
+    /// Resolve context servers from installed extensions and add them
+    ///
+    /// Returns the number of extension servers that were added. Servers
+    /// with the same name in a config file take precedence.
+    pub async fn load_extension_servers(
+        &self,
+        host: &Arc<WasmHost>,
+        extensions_dir: &std::path::Path,
+        project: Arc<dyn ProjectDelegate>,
+    ) -> usize {
+        let servers = resolve_extension_servers(host, extensions_dir, project).await;
+        *self.extension_servers.write().await = servers;
+        self.merge_extension_servers().await
+    }
+
+    /// Merge extension servers into configs, skipping names already configured
+    async fn merge_extension_servers(&self) -> usize {
+        let extension_servers = self.extension_servers.read().await;
+        let mut configs = self.configs.write().await;
+        let mut sources = self.sources.write().await;
+
+        let mut added = 0;
+        for server in extension_servers.iter() {
+            if matches!(sources.get(&server.name), Some(McpServerSource::Config(_))) {
+                info!(
+                    "MCP server {} from extension {} is shadowed by MCP config",
+                    server.name, server.extension_id
+                );
+                continue;
+            }
+            configs.insert(server.name.clone(), server.config.clone());
+            sources.insert(
+                server.name.clone(),
+                McpServerSource::Extension(server.extension_id.clone()),
+            );
+            added += 1;
+        }
+        added
+    }
+
+    /// Connect to all enabled local servers in parallel
     pub async fn connect_all(&self) -> Result<()> {
         let configs: Vec<_> = {
             let configs = self.configs.read().await;
+            let options = self.options.read().await;
             configs
                 .iter()
-                .filter(|(_, c)| c.is_local())
+                .filter(|(n, c)| c.is_local() && !options.get(*n).is_some_and(|o| o.disabled))
                 .map(|(n, c)| (n.clone(), c.clone()))
                 .collect()
         };
@@ -131,7 +338,14 @@ impl McpManager {
     }
 
     /// Connect to a specific local server
+    ///
+    /// Cancels any pending automatic restart for it.
     pub async fn connect(&self, name: &str) -> Result<()> {
+        self.cancel_restart(name).await;
+        self.connect_client(name).await
+    }
+
+    async fn connect_client(&self, name: &str) -> Result<()> {
         let config = {
             let configs = self.configs.read().await;
             configs.get(name).cloned()
@@ -148,11 +362,44 @@ impl McpManager {
             ));
         }
 
+        let options = self.server_options(name).await;
+        if options.disabled {
+            return Err(anyhow::anyhow!("Server {} is disabled in MCP config", name));
+        }
+
         // Disconnect first if already connected
-        self.disconnect(name).await;
+        self.remove_client(name).await;
 
         // Connect
-        let client = McpClient::connect(name, &config, &self.working_dir).await?;
+        let cwd = options.cwd.as_deref().unwrap_or(&self.working_dir);
+        let delegate = self.delegate.read().await.clone();
+        let mut client = McpClient::connect(name, &config, cwd, delegate).await?;
+        if let Some(timeout) = options.timeout {
+            client = client.with_request_timeout(timeout);
+        }
+
+        // Forward notifications and exits before the server can send any
+        let generation = self.next_generation.fetch_add(1, Ordering::Relaxed);
+        if let Some(mut client_events) = client.take_events() {
+            let events = self.events.clone();
+            let server = name.to_string();
+            tokio::spawn(async move {
+                while let Some(event) = client_events.recv().await {
+                    let event = match event {
+                        McpClientEvent::Notification(notification) => McpEvent::Notification {
+                            server: server.clone(),
+                            notification,
+                        },
+                        McpClientEvent::Exited(reason) => McpEvent::Exited {
+                            server: server.clone(),
+                            reason,
+                            generation,
+                        },
+                    };
+                    let _ = events.send(event);
+                }
+            });
+        }
 
         // Initialize
         client.initialize().await?;
@@ -162,32 +409,268 @@ impl McpManager {
 
         let client = Arc::new(client);
         self.clients.write().await.insert(name.to_string(), client);
+        {
+            let mut health = self.health.write().await;
+            let health = health.entry(name.to_string()).or_default();
+            health.connected_at = Some(Instant::now());
+            health.generation = generation;
+        }
 
         info!("Connected to MCP server: {}", name);
         Ok(())
     }
 
     /// Disconnect from a server
+    ///
+    /// Cancels any pending automatic restart for it.
     pub async fn disconnect(&self, name: &str) {
+        self.cancel_restart(name).await;
+        self.remove_client(name).await;
+    }
+
+    async fn remove_client(&self, name: &str) {
         if self.clients.write().await.remove(name).is_some() {
             info!("Disconnected from MCP server: {}", name);
         }
     }
 
-    /// Get all tools from connected local servers
+    async fn cancel_restart(&self, name: &str) {
+        if let Some(health) = self.health.write().await.get_mut(name) {
+            health.restarting = None;
+            health.last_exit = None;
+            health.attempts = 0;
+        }
+    }
+
+    /// Health of a local server (defaults when it never connected)
+    pub async fn server_health(&self, name: &str) -> McpServerHealth {
+        self.health
+            .read()
+            .await
+            .get(name)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Start the background task that restarts crashed servers and keeps
+    /// `registry` in sync with each server's tools
+    pub fn spawn_supervisor(self: &Arc<Self>, registry: Arc<ToolRegistry>) {
+        let manager = Arc::downgrade(self);
+        let mut events = self.subscribe();
+
+        tokio::spawn(async move {
+            loop {
+                let event = match events.recv().await {
+                    Ok(event) => event,
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        warn!("MCP supervisor skipped {} events", skipped);
+                        continue;
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                };
+                let Some(manager) = manager.upgrade() else {
+                    break;
+                };
+
+                match event {
+                    McpEvent::Exited {
+                        server,
+                        reason,
+                        generation,
+                    } => {
+                        let registry = registry.clone();
+                        tokio::spawn(async move {
+                            manager
+                                .handle_exit(&server, generation, reason, &registry)
+                                .await;
+                        });
+                    }
+                    McpEvent::Notification {
+                        server,
+                        notification: McpNotification::ToolsListChanged,
+                    } => {
+                        let registry = registry.clone();
+                        tokio::spawn(async move {
+                            manager.refresh_tools(&server, &registry).await;
+                        });
+                    }
+                    McpEvent::Notification {
+                        server,
+                        notification: notification @ McpNotification::LogMessage { .. },
+                    } => {
+                        info!("MCP {}: {}", server, notification);
+                    }
+                    _ => {}
+                }
+            }
+            debug!("MCP supervisor stopped");
+        });
+    }
+
+    /// Re-list a server's tools after `notifications/tools/list_changed`
+    async fn refresh_tools(self: Arc<Self>, server: &str, registry: &ToolRegistry) {
+        let Some(client) = self.get_client(server).await else {
+            return;
+        };
+        if let Err(e) = client.list_tools().await {
+            warn!("Failed to refresh tools for MCP server {}: {}", server, e);
+            return;
+        }
+        let tool_count = reregister_server_tools(self.clone(), registry, server).await;
+        info!("MCP {} tools changed ({} tools)", server, tool_count);
+        let _ = self.events.send(McpEvent::ToolsChanged {
+            server: server.to_string(),
+            tool_count,
+        });
+    }
+
+    /// Drop a crashed client and restart it with backoff
+    ///
+    /// The reader sees stdout close before the child is reaped, so the exit
+    /// is matched to the connected client by `generation` rather than by
+    /// whether its process still runs.
+    async fn handle_exit(
+        self: Arc<Self>,
+        server: &str,
+        generation: u64,
+        reason: String,
+        registry: &ToolRegistry,
+    ) {
+        // Ignore exits from clients that were already replaced or removed
+        let current = self
+            .health
+            .read()
+            .await
+            .get(server)
+            .is_some_and(|h| h.generation == generation);
+        let dead = match self.clients.read().await.get(server) {
+            Some(client) if current => client.clone(),
+            _ => return,
+        };
+        warn!("MCP server {} exited: {}", server, reason);
+        dead.kill().await;
+
+        {
+            let mut health = self.health.write().await;
+            let health = health.entry(server.to_string()).or_default();
+            if health
+                .connected_at
+                .is_some_and(|at| at.elapsed() >= STABLE_UPTIME)
+            {
+                health.attempts = 0;
+            }
+            health.connected_at = None;
+            health.last_exit = Some(reason.clone());
+            health.stderr_tail = dead.stderr_tail();
+        }
+        self.remove_client(server).await;
+        reregister_server_tools(self.clone(), registry, server).await;
+        let _ = self.events.send(McpEvent::ToolsChanged {
+            server: server.to_string(),
+            tool_count: 0,
+        });
+
+        if self.server_options(server).await.disabled {
+            return;
+        }
+
+        loop {
+            let attempt = {
+                let mut health = self.health.write().await;
+                let health = health.entry(server.to_string()).or_default();
+                if health.attempts >= MAX_RESTART_ATTEMPTS {
+                    health.restarting = None;
+                    let reason = health.last_exit.clone().unwrap_or(reason);
+                    warn!(
+                        "Giving up on MCP server {} after {} restart attempts",
+                        server, health.attempts
+                    );
+                    let _ = self.events.send(McpEvent::GaveUp {
+                        server: server.to_string(),
+                        reason,
+                    });
+                    return;
+                }
+                health.attempts += 1;
+                health.restarting = Some(health.attempts);
+                health.attempts
+            };
+
+            let delay = restart_delay(attempt);
+            info!(
+                "Restarting MCP server {} in {:?} (attempt {})",
+                server, delay, attempt
+            );
+            let _ = self.events.send(McpEvent::Restarting {
+                server: server.to_string(),
+                attempt,
+                delay,
+            });
+            tokio::time::sleep(delay).await;
+
+            // Manual connect/disconnect while waiting takes over
+            let cancelled = self
+                .health
+                .read()
+                .await
+                .get(server)
+                .is_none_or(|h| h.restarting != Some(attempt));
+            if cancelled || self.clients.read().await.contains_key(server) {
+                return;
+            }
+
+            match self.connect_client(server).await {
+                Ok(()) => {
+                    if let Some(health) = self.health.write().await.get_mut(server) {
+                        health.restarts += 1;
+                        health.restarting = None;
+                    }
+                    let tool_count = reregister_server_tools(self.clone(), registry, server).await;
+                    info!("MCP server {} restarted ({} tools)", server, tool_count);
+                    let _ = self.events.send(McpEvent::Restarted {
+                        server: server.to_string(),
+                        tool_count,
+                    });
+                    return;
+                }
+                Err(e) => {
+                    warn!("Restart of MCP server {} failed: {}", server, e);
+                    if let Some(health) = self.health.write().await.get_mut(server) {
+                        health.last_exit = Some(e.to_string());
+                    }
+                }
+            }
+        }
+    }
+
+    /// Get all tools from connected local servers that pass their tool filter
     pub async fn get_all_tools(&self) -> Vec<(String, McpToolDef)> {
         let clients = self.clients.read().await;
+        let options = self.options.read().await;
         let mut tools = Vec::new();
 
         for (name, client) in clients.iter() {
+            let filter = options.get(name).map(|o| &o.tools);
             for tool in client.get_tools().await {
-                tools.push((name.clone(), tool));
+                if filter.is_none_or(|f| f.allows(&tool.name)) {
+                    tools.push((name.clone(), tool));
+                }
             }
         }
 
         tools
     }
 
+    /// Options for a server (defaults when none are configured)
+    pub async fn server_options(&self, name: &str) -> McpServerOptions {
+        self.options
+            .read()
+            .await
+            .get(name)
+            .cloned()
+            .unwrap_or_default()
+    }
+
     /// Call a tool on a local server
     pub async fn call_tool(
         &self,
@@ -195,6 +678,14 @@ impl McpManager {
         tool: &str,
         arguments: Value,
     ) -> Result<McpToolResult> {
+        if !self.server_options(server).await.tools.allows(tool) {
+            return Err(anyhow::anyhow!(
+                "Tool {} is not allowed on server {}",
+                tool,
+                server
+            ));
+        }
+
         let clients = self.clients.read().await;
         let client = clients
             .get(server)
@@ -207,13 +698,29 @@ impl McpManager {
     pub async fn list_servers(&self) -> Vec<McpServerInfo> {
         let configs = self.configs.read().await;
         let clients = self.clients.read().await;
+        let sources = self.sources.read().await;
+        let all_options = self.options.read().await;
+        let all_health = self.health.read().await;
 
         let mut servers = Vec::new();
 
         for (name, config) in configs.iter() {
-            let (status, tool_count, tools, error) = if config.is_local() {
+            let options = all_options.get(name).cloned().unwrap_or_default();
+            let health = all_health.get(name).cloned().unwrap_or_default();
+            let mut stderr_tail = health.stderr_tail.clone();
+            let mut filtered_tools = 0;
+            let (status, tool_count, tools, error) = if options.disabled {
+                (McpServerStatus::Disabled, 0, Vec::new(), None)
+            } else if config.is_local() {
                 if let Some(client) = clients.get(name) {
-                    let t = client.get_tools().await;
+                    stderr_tail = client.stderr_tail();
+                    let all = client.get_tools().await;
+                    let total = all.len();
+                    let t: Vec<_> = all
+                        .into_iter()
+                        .filter(|tool| options.tools.allows(&tool.name))
+                        .collect();
+                    filtered_tools = total - t.len();
                     if client.is_alive().await {
                         (McpServerStatus::Connected, t.len(), t, None)
                     } else {
@@ -224,6 +731,20 @@ impl McpManager {
                             Some("Process died".to_string()),
                         )
                     }
+                } else if let Some(attempt) = health.restarting {
+                    (
+                        McpServerStatus::Restarting(attempt),
+                        0,
+                        Vec::new(),
+                        health.last_exit.clone(),
+                    )
+                } else if let Some(ref exit) = health.last_exit {
+                    (
+                        McpServerStatus::Error(exit.clone()),
+                        0,
+                        Vec::new(),
+                        Some(exit.clone()),
+                    )
                 } else {
                     (McpServerStatus::Disconnected, 0, Vec::new(), None)
                 }
@@ -235,10 +756,19 @@ impl McpManager {
             servers.push(McpServerInfo {
                 name: name.clone(),
                 server_type: config.transport_type().to_string(),
+                source: sources
+                    .get(name)
+                    .cloned()
+                    .unwrap_or(McpServerSource::Config(Vec::new())),
+                summary: config.summary(),
+                options,
                 status,
                 tool_count,
                 tools,
+                filtered_tools,
                 error,
+                health,
+                stderr_tail,
             });
         }
 
@@ -261,3 +791,70 @@ impl McpManager {
         self.clients.read().await.get(name).cloned()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_restart_delay_backs_off_to_cap() {
+        assert_eq!(restart_delay(1), Duration::from_secs(1));
+        assert_eq!(restart_delay(2), Duration::from_secs(2));
+        assert_eq!(restart_delay(4), Duration::from_secs(8));
+        assert_eq!(restart_delay(6), RESTART_MAX_DELAY);
+        assert_eq!(restart_delay(40), RESTART_MAX_DELAY);
+    }
+
+    fn local(command: &str) -> McpServerConfig {
+        McpServerConfig::Local {
+            command: command.to_string(),
+            args: Vec::new(),
+            env: HashMap::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_config_servers_shadow_extension_servers() {
+        let manager = McpManager::new(PathBuf::from("/project"));
+        manager
+            .configs
+            .write()
+            .await
+            .insert("github".into(), local("my-github-mcp"));
+        manager.sources.write().await.insert(
+            "github".into(),
+            McpServerSource::Config(vec![McpConfigLayer::Project]),
+        );
+        *manager.extension_servers.write().await = vec![
+            ExtensionContextServer {
+                name: "github".into(),
+                extension_id: "zed-github".into(),
+                config: local("extension-github-mcp"),
+            },
+            ExtensionContextServer {
+                name: "postgres".into(),
+                extension_id: "zed-postgres".into(),
+                config: local("extension-postgres-mcp"),
+            },
+        ];
+
+        assert_eq!(manager.merge_extension_servers().await, 1);
+
+        let configs = manager.configs.read().await;
+        let command = |name: &str| match &configs[name] {
+            McpServerConfig::Local { command, .. } => command.clone(),
+            McpServerConfig::Remote { url, .. } => url.clone(),
+        };
+        assert_eq!(command("github"), "my-github-mcp");
+        assert_eq!(command("postgres"), "extension-postgres-mcp");
+        let sources = manager.sources.read().await;
+        assert_eq!(
+            sources["github"],
+            McpServerSource::Config(vec![McpConfigLayer::Project])
+        );
+        assert_eq!(
+            sources["postgres"],
+            McpServerSource::Extension("zed-postgres".into())
+        );
+    }
+}
@@ -4,16 +4,33 @@
 //! - Local (stdio): We spawn the process and act as MCP client
 //! - Remote (url): Passed to Anthropic API's MCP Connector
 //!
+//! Local servers come from the layered MCP config (~/.krusty/mcp.json,
+//! .mcp.json, .krusty/mcp.local.json) or from installed Zed extensions
+//! that provide context servers.
+//!
 //! Local servers are managed here. Remote servers are passed to the API.
 
 mod client;
 mod config;
+mod elicitation;
+mod extension;
 mod manager;
 mod protocol;
+mod sampling;
 pub mod tool;
 mod transport;
 
-pub use config::{McpConfig, McpServerConfig, RemoteMcpServer};
-pub use manager::{McpManager, McpServerInfo, McpServerStatus};
-pub use protocol::{McpContent, McpToolDef, McpToolResult};
+pub use config::{
+    McpConfig, McpConfigLayer, McpServerConfig, McpServerOptions, McpToolFilter, RemoteMcpServer,
+};
+pub use elicitation::{elicit_content, ElicitField, ElicitFieldKind};
+pub use extension::{has_extension_servers, ExtensionContextServer};
+pub use manager::{
+    McpEvent, McpManager, McpServerHealth, McpServerInfo, McpServerSource, McpServerStatus,
+};
+pub use protocol::{
+    CreateMessageParams, CreateMessageResult, ElicitAction, ElicitParams, ElicitResult, McpContent,
+    McpNotification, McpToolDef, McpToolResult, SamplingMessage,
+};
+pub use sampling::{sample_with_client, sampling_prompt, McpClientDelegate};
 pub use tool::McpTool;
@@ -26,20 +26,63 @@ impl McpRequest {
     }
 }
 
-/// JSON-RPC response
+/// JSON-RPC message from the server: a response, notification or request
+///
+/// Server requests (e.g. `sampling/createMessage`) carry both `id` and
+/// `method`; their ids may be strings, so `id` is kept as raw JSON.
 #[derive(Debug, Deserialize)]
 pub struct McpResponse {
     #[serde(rename = "jsonrpc")]
     pub _jsonrpc: String,
-    pub id: Option<i64>,
+    pub id: Option<Value>,
     pub result: Option<Value>,
     pub error: Option<McpError>,
     /// For notifications
     #[serde(default)]
     pub method: Option<String>,
-    /// Notification params (protocol field, not directly used)
+    /// Notification or request params
     #[serde(default)]
-    pub _params: Option<Value>,
+    pub params: Option<Value>,
+}
+
+/// JSON-RPC response to a server request
+#[derive(Debug, Serialize)]
+pub struct McpReply {
+    pub jsonrpc: &'static str,
+    pub id: Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<McpReplyError>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct McpReplyError {
+    pub code: i64,
+    pub message: String,
+}
+
+impl McpReply {
+    pub fn result(id: Value, result: Value) -> Self {
+        Self {
+            jsonrpc: "2.0",
+            id,
+            result: Some(result),
+            error: None,
+        }
+    }
+
+    pub fn error(id: Value, code: i64, message: impl Into<String>) -> Self {
+        Self {
+            jsonrpc: "2.0",
+            id,
+            result: None,
+            error: Some(McpReplyError {
+                code,
+                message: message.into(),
+            }),
+        }
+    }
 }
 
 /// JSON-RPC error
@@ -52,6 +95,86 @@ pub struct McpError {
     pub _data: Option<Value>,
 }
 
+/// Server → client notification
+#[derive(Debug, Clone, PartialEq)]
+pub enum McpNotification {
+    /// `notifications/tools/list_changed`
+    ToolsListChanged,
+    /// `notifications/progress`
+    Progress {
+        token: Value,
+        progress: f64,
+        total: Option<f64>,
+        message: Option<String>,
+    },
+    /// `notifications/message`
+    LogMessage {
+        level: String,
+        logger: Option<String>,
+        data: Value,
+    },
+    /// Anything we don't interpret
+    Other {
+        method: String,
+        params: Option<Value>,
+    },
+}
+
+impl McpNotification {
+    /// Interpret a notification by method name
+    pub fn parse(method: &str, params: Option<Value>) -> Self {
+        let field = |name: &str| params.as_ref().and_then(|p| p.get(name)).cloned();
+        match method {
+            "notifications/tools/list_changed" => Self::ToolsListChanged,
+            "notifications/progress" => Self::Progress {
+                token: field("progressToken").unwrap_or(Value::Null),
+                progress: field("progress").and_then(|v| v.as_f64()).unwrap_or(0.0),
+                total: field("total").and_then(|v| v.as_f64()),
+                message: field("message").and_then(|v| v.as_str().map(String::from)),
+            },
+            "notifications/message" => Self::LogMessage {
+                level: field("level")
+                    .and_then(|v| v.as_str().map(String::from))
+                    .unwrap_or_else(|| "info".to_string()),
+                logger: field("logger").and_then(|v| v.as_str().map(String::from)),
+                data: field("data").unwrap_or(Value::Null),
+            },
+            _ => Self::Other {
+                method: method.to_string(),
+                params,
+            },
+        }
+    }
+}
+
+impl std::fmt::Display for McpNotification {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::ToolsListChanged => write!(f, "tools changed"),
+            Self::Progress {
+                progress,
+                total,
+                message,
+                ..
+            } => {
+                match total {
+                    Some(total) => write!(f, "{}/{}", progress, total)?,
+                    None => write!(f, "{}", progress)?,
+                }
+                if let Some(message) = message {
+                    write!(f, " {}", message)?;
+                }
+                Ok(())
+            }
+            Self::LogMessage { level, data, .. } => match data.as_str() {
+                Some(text) => write!(f, "[{}] {}", level, text),
+                None => write!(f, "[{}] {}", level, data),
+            },
+            Self::Other { method, .. } => write!(f, "{}", method),
+        }
+    }
+}
+
 /// MCP tool definition from tools/list
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct McpToolDef {
@@ -69,8 +192,8 @@ pub struct McpToolResult {
     pub is_error: bool,
 }
 
-/// Content types returned by MCP tools
-#[derive(Debug, Clone, Deserialize)]
+/// Content types returned by MCP tools (also used in sampling messages)
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "lowercase")]
 pub enum McpContent {
     Text {
@@ -86,6 +209,18 @@ pub enum McpContent {
         #[serde(default)]
         text: Option<String>,
     },
+    Audio {
+        data: String,
+        #[serde(rename = "mimeType")]
+        mime_type: String,
+    },
+    /// Link to a resource the server can read (protocol 2025-06-18)
+    #[serde(rename = "resource_link")]
+    ResourceLink {
+        uri: String,
+        #[serde(default)]
+        name: Option<String>,
+    },
 }
 
 impl std::fmt::Display for McpContent {
@@ -93,6 +228,11 @@ impl std::fmt::Display for McpContent {
         match self {
             McpContent::Text { text } => write!(f, "{}", text),
             McpContent::Image { mime_type, .. } => write!(f, "[Image: {}]", mime_type),
+            McpContent::Audio { mime_type, .. } => write!(f, "[Audio: {}]", mime_type),
+            McpContent::ResourceLink { uri, name } => match name {
+                Some(name) => write!(f, "{} ({})", name, uri),
+                None => write!(f, "{}", uri),
+            },
             McpContent::Resource { uri, text } => {
                 if let Some(t) = text {
                     write!(f, "{}\n{}", uri, t)
@@ -118,8 +258,18 @@ pub struct InitializeParams {
 pub struct ClientCapabilities {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub roots: Option<RootsCapability>,
+    /// Server may ask us for LLM completions (`sampling/createMessage`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sampling: Option<EmptyCapability>,
+    /// Server may ask the user for structured input (`elicitation/create`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub elicitation: Option<EmptyCapability>,
 }
 
+/// Capability without options, serialized as `{}`
+#[derive(Debug, Default, Serialize)]
+pub struct EmptyCapability {}
+
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RootsCapability {
@@ -201,6 +351,65 @@ impl From<ToolCallResult> for McpToolResult {
     }
 }
 
+/// `sampling/createMessage` params
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateMessageParams {
+    pub messages: Vec<SamplingMessage>,
+    #[serde(default)]
+    pub system_prompt: Option<String>,
+    #[serde(default)]
+    pub max_tokens: Option<usize>,
+    /// Model hints and priorities (advisory; we use the active model)
+    #[serde(default)]
+    pub model_preferences: Option<Value>,
+}
+
+/// A message in a sampling request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SamplingMessage {
+    pub role: String,
+    pub content: McpContent,
+}
+
+/// `sampling/createMessage` result
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateMessageResult {
+    pub role: String,
+    pub content: McpContent,
+    pub model: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stop_reason: Option<String>,
+}
+
+/// `elicitation/create` params
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ElicitParams {
+    pub message: String,
+    /// Flat JSON schema object with primitive properties
+    #[serde(default)]
+    pub requested_schema: Value,
+}
+
+/// What the user did with an elicitation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ElicitAction {
+    Accept,
+    Decline,
+    Cancel,
+}
+
+/// `elicitation/create` result
+#[derive(Debug, Clone, Serialize)]
+pub struct ElicitResult {
+    pub action: ElicitAction,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content: Option<Value>,
+}
+
 /// Format MCP tool result for display
 pub fn format_mcp_result(result: &McpToolResult) -> String {
     result
@@ -210,3 +419,45 @@ pub fn format_mcp_result(result: &McpToolResult) -> String {
         .collect::<Vec<_>>()
         .join("\n")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_notifications() {
+        assert_eq!(
+            McpNotification::parse("notifications/tools/list_changed", None),
+            McpNotification::ToolsListChanged
+        );
+
+        let progress = McpNotification::parse(
+            "notifications/progress",
+            Some(json!({"progressToken": 7, "progress": 3, "total": 10, "message": "indexing"})),
+        );
+        assert_eq!(progress.to_string(), "3/10 indexing");
+
+        let log = McpNotification::parse(
+            "notifications/message",
+            Some(json!({"level": "warning", "data": "disk almost full"})),
+        );
+        assert_eq!(log.to_string(), "[warning] disk almost full");
+
+        assert!(matches!(
+            McpNotification::parse("notifications/resources/updated", None),
+            McpNotification::Other { .. }
+        ));
+    }
+
+    #[test]
+    fn test_parse_newer_content_types() {
+        let content: Vec<McpContent> = serde_json::from_value(json!([
+            {"type": "audio", "data": "AAAA", "mimeType": "audio/wav"},
+            {"type": "resource_link", "uri": "file:///notes.md", "name": "notes.md"}
+        ]))
+        .unwrap();
+        assert_eq!(content[0].to_string(), "[Audio: audio/wav]");
+        assert_eq!(content[1].to_string(), "notes.md (file:///notes.md)");
+    }
+}
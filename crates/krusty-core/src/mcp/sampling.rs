@@ -0,0 +1,114 @@
+//! Server → client requests: sampling and elicitation
+//!
+//! Servers may ask the client for an LLM completion (`sampling/createMessage`)
+//! or for structured input from the user (`elicitation/create`). Both need a
+//! frontend (approval, forms), so they go through an [`McpClientDelegate`]
+//! set on the manager. Without one, the capabilities aren't advertised.
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+
+use super::protocol::{
+    CreateMessageParams, CreateMessageResult, ElicitParams, ElicitResult, McpContent,
+};
+use crate::ai::client::AiClient;
+
+/// Default completion budget when a server doesn't set `maxTokens`
+const DEFAULT_SAMPLING_MAX_TOKENS: usize = 1024;
+
+/// Frontend hooks for requests that MCP servers send to the client
+#[async_trait]
+pub trait McpClientDelegate: Send + Sync {
+    /// Ask the user to approve a sampling request, then run it
+    async fn create_message(
+        &self,
+        server: &str,
+        params: CreateMessageParams,
+    ) -> Result<CreateMessageResult>;
+
+    /// Ask the user to fill in an elicitation form
+    async fn elicit(&self, server: &str, params: ElicitParams) -> Result<ElicitResult>;
+}
+
+/// Run an approved sampling request with the given client's model
+///
+/// Multi-message requests are flattened into a single prompt, since the
+/// simple completion path takes one user message.
+pub async fn sample_with_client(
+    client: &AiClient,
+    params: &CreateMessageParams,
+) -> Result<CreateMessageResult> {
+    let prompt = sampling_prompt(params)?;
+    let model = client.config().model.clone();
+    let system = params.system_prompt.as_deref().unwrap_or_default();
+    let max_tokens = params.max_tokens.unwrap_or(DEFAULT_SAMPLING_MAX_TOKENS);
+
+    let text = client
+        .call_simple(&model, system, &prompt, max_tokens)
+        .await?;
+
+    Ok(CreateMessageResult {
+        role: "assistant".to_string(),
+        content: McpContent::Text { text },
+        model,
+        stop_reason: Some("endTurn".to_string()),
+    })
+}
+
+/// Build the prompt text for a sampling request
+///
+/// A lone user message is passed through; longer conversations are
+/// rendered as a role-labelled transcript.
+pub fn sampling_prompt(params: &CreateMessageParams) -> Result<String> {
+    let text_of = |content: &McpContent| match content {
+        McpContent::Text { text } => text.clone(),
+        other => other.to_string(),
+    };
+
+    match params.messages.as_slice() {
+        [] => Err(anyhow!("Sampling request has no messages")),
+        [only] if only.role == "user" => Ok(text_of(&only.content)),
+        messages => Ok(messages
+            .iter()
+            .map(|m| {
+                let role = if m.role == "assistant" {
+                    "Assistant"
+                } else {
+                    "User"
+                };
+                format!("{}: {}", role, text_of(&m.content))
+            })
+            .collect::<Vec<_>>()
+            .join("\n\n")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn params(messages: serde_json::Value) -> CreateMessageParams {
+        serde_json::from_value(json!({ "messages": messages, "maxTokens": 100 })).unwrap()
+    }
+
+    #[test]
+    fn test_sampling_prompt() {
+        let single = params(json!([
+            {"role": "user", "content": {"type": "text", "text": "Summarize this"}}
+        ]));
+        assert_eq!(sampling_prompt(&single).unwrap(), "Summarize this");
+
+        let conversation = params(json!([
+            {"role": "user", "content": {"type": "text", "text": "Hi"}},
+            {"role": "assistant", "content": {"type": "text", "text": "Hello"}},
+            {"role": "user", "content": {"type": "text", "text": "Translate it"}}
+        ]));
+        assert_eq!(
+            sampling_prompt(&conversation).unwrap(),
+            "User: Hi\n\nAssistant: Hello\n\nUser: Translate it"
+        );
+
+        assert!(sampling_prompt(&params(json!([]))).is_err());
+    }
+}
@@ -26,7 +26,7 @@ pub struct McpTool {
 impl McpTool {
     pub fn new(server_name: String, definition: McpToolDef, manager: Arc<McpManager>) -> Self {
         let tool_name = definition.name.clone();
-        let full_name = format!("mcp__{}_{}", server_name, tool_name);
+        let full_name = format!("{}{}", tool_prefix(&server_name), tool_name);
 
         Self {
             server_name,
@@ -78,6 +78,38 @@ impl Tool for McpTool {
     }
 }
 
+/// Registry name prefix for a server's tools
+pub fn tool_prefix(server_name: &str) -> String {
+    format!("mcp__{}_", server_name)
+}
+
+/// Replace a server's registered tools with its current tool list
+///
+/// Returns the number of tools registered for the server. Other servers
+/// whose names share the prefix (e.g. `git` and `git_hub`) are re-registered
+/// too, since `unregister_by_prefix` removes their tools as well.
+pub async fn reregister_server_tools(
+    manager: Arc<McpManager>,
+    registry: &crate::tools::ToolRegistry,
+    server: &str,
+) -> usize {
+    let prefix = tool_prefix(server);
+    registry.unregister_by_prefix(&prefix).await;
+
+    let mut count = 0;
+    for (server_name, tool_def) in manager.get_all_tools().await {
+        if !tool_prefix(&server_name).starts_with(&prefix) {
+            continue;
+        }
+        if server_name == server {
+            count += 1;
+        }
+        let mcp_tool = Arc::new(McpTool::new(server_name, tool_def, manager.clone()));
+        registry.register(mcp_tool).await;
+    }
+    count
+}
+
 /// Register all MCP tools from connected servers
 pub async fn register_mcp_tools(manager: Arc<McpManager>, registry: &crate::tools::ToolRegistry) {
     let tools = manager.get_all_tools().await;
@@ -4,18 +4,24 @@
 //! Each message is a JSON object followed by a newline.
 
 use anyhow::{anyhow, Result};
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::path::Path;
 use std::process::Stdio;
+use std::sync::Arc;
 use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
 use tokio::process::{Child, ChildStdin, ChildStdout, Command};
 use tokio::sync::Mutex;
 
+/// Number of stderr lines kept for the UI
+const STDERR_TAIL_LINES: usize = 50;
+
 /// Stdio transport for MCP servers
 pub struct StdioTransport {
     stdin: Mutex<ChildStdin>,
     stdout: Mutex<BufReader<ChildStdout>>,
     child: Mutex<Child>,
+    /// Last lines the server wrote to stderr
+    stderr_tail: Arc<std::sync::Mutex<VecDeque<String>>>,
 }
 
 impl StdioTransport {
@@ -29,18 +35,8 @@ impl StdioTransport {
         tracing::info!("Spawning MCP server: {} {:?}", command, args);
         for (k, v) in env {
             // Mask API keys in logs
-            let masked = if k.contains("API_KEY") || k.contains("TOKEN") {
-                format!(
-                    "{}...{}",
-                    &v.chars().take(8).collect::<String>(),
-                    &v.chars()
-                        .rev()
-                        .take(4)
-                        .collect::<String>()
-                        .chars()
-                        .rev()
-                        .collect::<String>()
-                )
+            let masked = if crate::secrets::is_secret_name(k) {
+                crate::secrets::mask_secret(v)
             } else {
                 v.clone()
             };
@@ -73,13 +69,41 @@ impl StdioTransport {
         let stdin = child.stdin.take().ok_or_else(|| anyhow!("No stdin"))?;
         let stdout = child.stdout.take().ok_or_else(|| anyhow!("No stdout"))?;
 
+        // Drain stderr so the server can't block on a full pipe, keeping a tail
+        let stderr_tail = Arc::new(std::sync::Mutex::new(VecDeque::new()));
+        if let Some(stderr) = child.stderr.take() {
+            let tail = Arc::clone(&stderr_tail);
+            let command = command.to_string();
+            tokio::spawn(async move {
+                let mut lines = BufReader::new(stderr).lines();
+                while let Ok(Some(line)) = lines.next_line().await {
+                    tracing::debug!("MCP {} stderr: {}", command, line);
+                    if let Ok(mut tail) = tail.lock() {
+                        if tail.len() == STDERR_TAIL_LINES {
+                            tail.pop_front();
+                        }
+                        tail.push_back(line);
+                    }
+                }
+            });
+        }
+
         Ok(Self {
             stdin: Mutex::new(stdin),
             stdout: Mutex::new(BufReader::new(stdout)),
             child: Mutex::new(child),
+            stderr_tail,
         })
     }
 
+    /// Last lines written to stderr, oldest first
+    pub fn stderr_tail(&self) -> Vec<String> {
+        self.stderr_tail
+            .lock()
+            .map(|tail| tail.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
     /// Send a JSON-RPC message (newline-delimited JSON)
     pub async fn send(&self, message: &str) -> Result<()> {
         let mut stdin = self.stdin.lock().await;
@@ -135,4 +159,14 @@ impl StdioTransport {
         let mut child = self.child.lock().await;
         matches!(child.try_wait(), Ok(None))
     }
+
+    /// Kill the process and reap it
+    pub async fn kill(&self) {
+        let mut child = self.child.lock().await;
+        if matches!(child.try_wait(), Ok(None)) {
+            if let Err(e) = child.kill().await {
+                tracing::warn!("Failed to kill MCP server process: {}", e);
+            }
+        }
+    }
 }
@@ -41,6 +41,27 @@ pub fn ensure_plans_dir() -> std::io::Result<PathBuf> {
     Ok(dir)
 }
 
+/// Get the user themes directory (~/.krusty/themes)
+pub fn themes_dir() -> PathBuf {
+    config_dir().join("themes")
+}
+
+/// Get the keymap file (~/.krusty/keymap.toml)
+pub fn keymap_path() -> PathBuf {
+    config_dir().join("keymap.toml")
+}
+
+/// Get the compaction thresholds file (~/.krusty/compaction.toml)
+pub fn compaction_config_path() -> PathBuf {
+    config_dir().join("compaction.toml")
+}
+
+/// Get the global MCP config file (~/.krusty/mcp.json)
+/// Servers here are available in every project
+pub fn mcp_config_path() -> PathBuf {
+    config_dir().join("mcp.json")
+}
+
 /// Get the MCP keys file (~/.krusty/tokens/mcp_keys.json)
 /// Used for storing API keys for MCP servers
 pub fn mcp_keys_path() -> PathBuf {
@@ -0,0 +1,316 @@
+//! Acceptance checks on plan tasks
+//!
+//! A task can carry checks that must pass before it counts as done. In the
+//! plan markdown they sit under the task as `> Check:` lines:
+//!
+//! ```text
+//! - [ ] Task 1.1: Add the parser
+//!   > Check: cargo test -p parser
+//!   > Check: exists: src/parser.rs
+//!   > Check: grep: fn parse\( in src/parser.rs
+//! ```
+//!
+//! Plain text is a shell command that must exit 0. `exists:` takes a path,
+//! `grep:` a regex and the file or directory to search (`<regex> in <path>`).
+//! Paths are relative to the working directory.
+//!
+//! Checks can come from a synced plan file that anyone may edit, so commands
+//! run through the `bash` tool and its pre-tool hooks (the safety hook and
+//! user `PreToolUse` hooks), like any command the agent runs.
+
+use std::fmt;
+use std::path::Path;
+use std::time::Duration;
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::tools::{ToolContext, ToolRegistry};
+
+/// Wall-clock limit for one command check (the bash tool's maximum)
+const COMMAND_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// Extra time the registry allows on top of the bash tool's own timeout
+const TOOL_TIMEOUT_SLACK: Duration = Duration::from_secs(5);
+
+/// Longest command output kept for a failed check (the tail is kept)
+const MAX_OUTPUT_CHARS: usize = 4000;
+
+/// A verification that must pass before a task is complete
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TaskCheck {
+    /// Shell command that must exit 0
+    Command { run: String },
+    /// Path that must exist
+    FileExists { path: String },
+    /// Regex that must match somewhere in a file or directory
+    Grep { pattern: String, path: String },
+}
+
+impl TaskCheck {
+    /// Parse the text after `Check:`; `None` if it is empty
+    pub fn parse(spec: &str) -> Option<Self> {
+        let spec = spec.trim();
+        if spec.is_empty() {
+            return None;
+        }
+
+        if let Some(path) = spec.strip_prefix("exists:") {
+            return Some(Self::FileExists {
+                path: path.trim().to_string(),
+            });
+        }
+        if let Some(rest) = spec.strip_prefix("grep:") {
+            let (pattern, path) = rest.rsplit_once(" in ").unwrap_or((rest, "."));
+            return Some(Self::Grep {
+                pattern: pattern.trim().to_string(),
+                path: path.trim().to_string(),
+            });
+        }
+        let run = spec.strip_prefix("run:").unwrap_or(spec).trim();
+        Some(Self::Command {
+            run: run.to_string(),
+        })
+    }
+}
+
+impl fmt::Display for TaskCheck {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            // Commands that look like another kind need the explicit prefix
+            Self::Command { run }
+                if ["exists:", "grep:", "run:"]
+                    .iter()
+                    .any(|prefix| run.starts_with(prefix)) =>
+            {
+                write!(f, "run: {}", run)
+            }
+            Self::Command { run } => write!(f, "{}", run),
+            Self::FileExists { path } => write!(f, "exists: {}", path),
+            Self::Grep { pattern, path } => write!(f, "grep: {} in {}", pattern, path),
+        }
+    }
+}
+
+/// Result of running one check
+#[derive(Debug, Clone)]
+pub struct CheckOutcome {
+    pub check: TaskCheck,
+    pub passed: bool,
+    /// Why it failed, or command output
+    pub output: String,
+}
+
+/// Run checks in order against `ctx.working_dir`
+///
+/// Command checks are executed by the `bash` tool in `registry`, so its
+/// pre-tool hooks can block them.
+pub async fn run_checks(
+    checks: &[TaskCheck],
+    registry: &ToolRegistry,
+    ctx: &ToolContext,
+) -> Vec<CheckOutcome> {
+    let working_dir = ctx.working_dir.as_path();
+    let mut outcomes = Vec::with_capacity(checks.len());
+    for check in checks {
+        let (passed, output) = match check {
+            TaskCheck::Command { run } => run_command(run, registry, ctx).await,
+            TaskCheck::FileExists { path } => {
+                if working_dir.join(path).exists() {
+                    (true, String::new())
+                } else {
+                    (false, format!("{} does not exist", path))
+                }
+            }
+            TaskCheck::Grep { pattern, path } => {
+                let root = working_dir.join(path);
+                let pattern = pattern.clone();
+                tokio::task::spawn_blocking(move || grep_path(&pattern, &root))
+                    .await
+                    .unwrap_or_else(|e| (false, format!("grep check panicked: {}", e)))
+            }
+        };
+        tracing::debug!(check = %check, passed, "Plan task check");
+        outcomes.push(CheckOutcome {
+            check: check.clone(),
+            passed,
+            output,
+        });
+    }
+    outcomes
+}
+
+/// Report for failing checks, or `None` when all passed
+pub fn format_failures(outcomes: &[CheckOutcome]) -> Option<String> {
+    let failed: Vec<&CheckOutcome> = outcomes.iter().filter(|o| !o.passed).collect();
+    if failed.is_empty() {
+        return None;
+    }
+
+    let mut report = format!(
+        "{} of {} acceptance checks failed:",
+        failed.len(),
+        outcomes.len()
+    );
+    for outcome in failed {
+        report.push_str(&format!("\n\n✗ {}", outcome.check));
+        if !outcome.output.is_empty() {
+            report.push_str(&format!("\n{}", outcome.output));
+        }
+    }
+    Some(report)
+}
+
+async fn run_command(run: &str, registry: &ToolRegistry, ctx: &ToolContext) -> (bool, String) {
+    let params = json!({
+        "command": run,
+        "timeout": COMMAND_TIMEOUT.as_millis() as u64,
+        "description": "Plan acceptance check",
+    });
+    let mut ctx = ctx.inherit();
+    ctx.timeout = Some(COMMAND_TIMEOUT + TOOL_TIMEOUT_SLACK);
+
+    let Some(result) = registry.execute("bash", params, &ctx).await else {
+        return (false, "bash tool is not available".to_string());
+    };
+
+    // The bash tool reports `{output, exitCode, killed}`; hooks that block
+    // the command and other errors are plain text
+    let report: Value = serde_json::from_str(&result.output).unwrap_or(Value::Null);
+    let text = report["output"].as_str().unwrap_or(&result.output);
+    let text = tail(text.trim(), MAX_OUTPUT_CHARS);
+    if !result.is_error {
+        return (true, text);
+    }
+    match report["exitCode"].as_i64() {
+        Some(code) if report["killed"] != Value::Bool(true) => {
+            (false, format!("exit {}\n{}", code, text))
+        }
+        _ => (false, text),
+    }
+}
+
+/// Search a file, or every non-ignored file under a directory
+fn grep_path(pattern: &str, root: &Path) -> (bool, String) {
+    let re = match Regex::new(pattern) {
+        Ok(re) => re,
+        Err(e) => return (false, format!("invalid pattern: {}", e)),
+    };
+    if !root.exists() {
+        return (false, format!("{} does not exist", root.display()));
+    }
+
+    let found = ignore::WalkBuilder::new(root)
+        .build()
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_some_and(|t| t.is_file()))
+        .any(|entry| {
+            std::fs::read_to_string(entry.path())
+                .map(|content| re.is_match(&content))
+                .unwrap_or(false)
+        });
+
+    if found {
+        (true, String::new())
+    } else {
+        (false, format!("no match for /{}/", pattern))
+    }
+}
+
+/// Last `max` characters of `text`
+fn tail(text: &str, max: usize) -> String {
+    let count = text.chars().count();
+    if count <= max {
+        return text.to_string();
+    }
+    let rest: String = text.chars().skip(count - max).collect();
+    format!("...{}", rest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_display_roundtrip() {
+        let cases = [
+            "cargo test -p foo",
+            "exists: src/lib.rs",
+            "grep: fn parse\\( in src/parser.rs",
+            "run: grep: not really a grep",
+        ];
+        for case in cases {
+            let check = TaskCheck::parse(case).unwrap();
+            assert_eq!(check.to_string(), case);
+            assert_eq!(TaskCheck::parse(&check.to_string()), Some(check));
+        }
+
+        assert_eq!(
+            TaskCheck::parse("grep: TODO"),
+            Some(TaskCheck::Grep {
+                pattern: "TODO".to_string(),
+                path: ".".to_string()
+            })
+        );
+        assert_eq!(TaskCheck::parse("   "), None);
+    }
+
+    async fn bash_registry() -> ToolRegistry {
+        let registry = ToolRegistry::new();
+        registry
+            .register(std::sync::Arc::new(crate::tools::implementations::BashTool))
+            .await;
+        registry
+    }
+
+    fn context(dir: &Path) -> ToolContext {
+        ToolContext {
+            working_dir: dir.to_path_buf(),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_run_checks() {
+        let registry = bash_registry().await;
+        let dir = tempfile::tempdir().unwrap();
+        let ctx = context(dir.path());
+        std::fs::create_dir(dir.path().join("src")).unwrap();
+        std::fs::write(dir.path().join("src/lib.rs"), "pub fn parse() {}\n").unwrap();
+
+        let checks = [
+            TaskCheck::parse("exists: src/lib.rs").unwrap(),
+            TaskCheck::parse("grep: fn parse\\( in src").unwrap(),
+            TaskCheck::parse("test -f src/lib.rs").unwrap(),
+        ];
+        let outcomes = run_checks(&checks, &registry, &ctx).await;
+        assert!(outcomes.iter().all(|o| o.passed));
+        assert_eq!(format_failures(&outcomes), None);
+
+        let checks = [
+            TaskCheck::parse("exists: src/missing.rs").unwrap(),
+            TaskCheck::parse("grep: fn render in src/lib.rs").unwrap(),
+            TaskCheck::parse("echo broken; exit 3").unwrap(),
+        ];
+        let outcomes = run_checks(&checks, &registry, &ctx).await;
+        assert!(outcomes.iter().all(|o| !o.passed));
+        let report = format_failures(&outcomes).unwrap();
+        assert!(report.starts_with("3 of 3 acceptance checks failed"));
+        assert!(report.contains("exit 3\nbroken"));
+        assert!(report.contains("src/missing.rs does not exist"));
+    }
+
+    #[tokio::test]
+    async fn test_command_checks_go_through_hooks() {
+        let mut registry = bash_registry().await;
+        registry.add_pre_hook(std::sync::Arc::new(crate::agent::SafetyHook::new()));
+        let dir = tempfile::tempdir().unwrap();
+
+        let checks = [TaskCheck::parse("sudo true").unwrap()];
+        let outcomes = run_checks(&checks, &registry, &context(dir.path())).await;
+        assert!(!outcomes[0].passed);
+        assert!(outcomes[0].output.starts_with("Blocked:"));
+    }
+}
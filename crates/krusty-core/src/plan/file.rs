@@ -14,6 +14,7 @@
 //! ## Phase 1: [Phase Name]
 //!
 //! - [ ] Task 1.1: Description
+//!   > Check: cargo test -p foo
 //! - [x] Task 1.2: Completed task
 //!
 //! ## Phase 2: [Phase Name]
@@ -27,6 +28,8 @@ use regex::Regex;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
+use super::checks::TaskCheck;
+
 // ============================================================================
 // Static regex patterns for task completion detection (compiled once)
 // ============================================================================
@@ -211,6 +214,9 @@ pub struct PlanTask {
     /// Child task IDs (for hierarchy)
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub children: Vec<String>,
+    /// Acceptance checks that must pass before the task is complete
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub checks: Vec<TaskCheck>,
     /// Priority (1 = highest)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub priority: Option<u8>,
@@ -235,6 +241,7 @@ impl PlanTask {
             blocked_by: Vec::new(),
             blocks: Vec::new(),
             children: Vec::new(),
+            checks: Vec::new(),
             priority: None,
             created_at: Some(Utc::now()),
             completed_at: None,
@@ -258,6 +265,7 @@ impl PlanTask {
             blocked_by: Vec::new(),
             blocks: Vec::new(),
             children: Vec::new(),
+            checks: Vec::new(),
             priority: None,
             created_at: Some(Utc::now()),
             completed_at: None,
@@ -308,6 +316,11 @@ impl PlanTask {
             ));
         }
 
+        // Add acceptance checks
+        for check in &self.checks {
+            lines.push(format!("{}  > Check: {}", indent, check));
+        }
+
         // Add result line if completed
         if let Some(ref result) = self.result {
             if let Some(ts) = self.completed_at {
@@ -1000,6 +1013,8 @@ impl PlanFile {
                                 .map(|s| s.trim().to_string())
                                 .filter(|s| !s.is_empty())
                                 .collect();
+                        } else if let Some(check) = meta.strip_prefix("Check:") {
+                            last_task.checks.extend(TaskCheck::parse(check));
                         }
                     }
                 }
@@ -1186,6 +1201,8 @@ impl PlanFile {
                                 .map(|s| s.trim().to_string())
                                 .filter(|s| !s.is_empty())
                                 .collect();
+                        } else if let Some(check) = meta.strip_prefix("Check:") {
+                            last_task.checks.extend(TaskCheck::parse(check));
                         }
                     }
                 }
@@ -1476,6 +1493,30 @@ mod tests {
         assert!(parsed.notes.is_some());
     }
 
+    #[test]
+    fn test_task_checks_roundtrip() {
+        let response = "# Plan: Parser\n\n## Phase 1: Build\n\n\
+            - [ ] Add the parser\n  > Check: cargo test -p parser\n  > Check: exists: src/parser.rs\n\
+            - [ ] Document it\n";
+        let plan = PlanFile::try_parse_from_response(response).unwrap();
+        let task = plan.find_task("1.1").unwrap();
+        assert_eq!(
+            task.checks,
+            vec![
+                TaskCheck::Command {
+                    run: "cargo test -p parser".to_string()
+                },
+                TaskCheck::FileExists {
+                    path: "src/parser.rs".to_string()
+                },
+            ]
+        );
+        assert!(plan.find_task("1.2").unwrap().checks.is_empty());
+
+        let parsed = PlanFile::from_markdown(&plan.to_markdown()).unwrap();
+        assert_eq!(parsed.find_task("1.1").unwrap().checks, task.checks);
+    }
+
     #[test]
     fn test_try_parse_from_response() {
         let response = r#"
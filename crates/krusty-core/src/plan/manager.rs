@@ -4,15 +4,17 @@
 //! - Strict 1:1 session-plan relationship
 //! - Automatic plan deletion on session delete (CASCADE)
 //! - CRUD operations for plans
+//! - Revision history with diffs and version-checked restore
 //! - Backward-compatible file operations for migration
 
 use anyhow::Result;
 use chrono::Utc;
+use similar::TextDiff;
 use std::path::PathBuf;
 
 use super::file::{PlanFile, PlanStatus};
 use crate::paths;
-use crate::storage::{Database, PlanStore, SharedDatabase};
+use crate::storage::{Database, PlanRevision, PlanStore, SharedDatabase};
 
 /// Manages plans with SQLite storage
 pub struct PlanManager {
@@ -82,6 +84,16 @@ impl PlanManager {
         store.has_plan(session_id)
     }
 
+    /// Current version of a session's plan, without loading or parsing it
+    pub fn plan_version(&self, session_id: &str) -> Result<Option<u64>> {
+        let db = self
+            .db
+            .lock()
+            .map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
+        let store = PlanStore::new(&db);
+        store.plan_version(session_id)
+    }
+
     /// Update plan content for a session
     pub fn update_plan(&self, session_id: &str, plan: &PlanFile) -> Result<()> {
         let db = self
@@ -124,6 +136,99 @@ impl PlanManager {
         self.save_plan_for_session(session_id, plan)
     }
 
+    /// List revisions of a session's plan, newest first
+    pub fn list_revisions(&self, session_id: &str) -> Result<Vec<PlanRevision>> {
+        let db = self
+            .db
+            .lock()
+            .map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
+        let store = PlanStore::new(&db);
+        store.list_revisions(session_id)
+    }
+
+    /// One revision of a session's plan
+    pub fn get_revision(&self, session_id: &str, version: u64) -> Result<Option<PlanRevision>> {
+        let db = self
+            .db
+            .lock()
+            .map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
+        let store = PlanStore::new(&db);
+        store.get_revision(session_id, version)
+    }
+
+    /// Unified markdown diff between a revision and the one before it
+    ///
+    /// Returns `None` if the revision does not exist. The first revision is
+    /// diffed against an empty plan.
+    pub fn revision_diff(&self, session_id: &str, version: u64) -> Result<Option<String>> {
+        let db = self
+            .db
+            .lock()
+            .map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
+        let store = PlanStore::new(&db);
+
+        let Some(revision) = store.get_revision(session_id, version)? else {
+            return Ok(None);
+        };
+        let previous = match version.checked_sub(1) {
+            Some(prev) if prev > 0 => store.get_revision(session_id, prev)?,
+            _ => None,
+        };
+        let old = previous.as_ref().map(|r| r.content.as_str()).unwrap_or("");
+
+        let diff = TextDiff::from_lines(old, revision.content.as_str())
+            .unified_diff()
+            .context_radius(3)
+            .header(
+                &format!("revision {}", version.saturating_sub(1)),
+                &format!("revision {}", version),
+            )
+            .to_string();
+        Ok(Some(diff))
+    }
+
+    /// Restore an earlier revision as the session's current plan
+    ///
+    /// `expected_version` is the plan version the caller last saw; the
+    /// restore is refused if the plan was saved since. The restored plan is
+    /// itself saved as a new revision, so a restore can be undone too.
+    pub fn restore_revision(
+        &self,
+        session_id: &str,
+        version: u64,
+        expected_version: u64,
+    ) -> Result<PlanFile> {
+        let db = self
+            .db
+            .lock()
+            .map_err(|e| anyhow::anyhow!("Lock error: {}", e))?;
+        let store = PlanStore::new(&db);
+
+        let current = store
+            .get_plan_for_session(session_id)?
+            .ok_or_else(|| anyhow::anyhow!("Session has no plan"))?;
+        if !current.version_matches(expected_version) {
+            anyhow::bail!(
+                "Plan changed since revision {} (now at revision {})",
+                expected_version,
+                current.version
+            );
+        }
+        let revision = store
+            .get_revision(session_id, version)?
+            .ok_or_else(|| anyhow::anyhow!("No revision {}", version))?;
+
+        let mut plan = PlanFile::from_markdown(&revision.content)
+            .map_err(|e| anyhow::anyhow!("Failed to parse revision {}: {}", version, e))?;
+        plan.session_id = Some(session_id.to_string());
+        store.upsert_plan(session_id, &plan)?;
+
+        tracing::info!(session_id = %session_id, version, "Restored plan revision");
+        store
+            .get_plan_for_session(session_id)?
+            .ok_or_else(|| anyhow::anyhow!("Plan vanished after restore"))
+    }
+
     /// List completed plans for a working directory (for history)
     ///
     /// Queries the database for completed plans where the linked session
@@ -427,4 +532,43 @@ mod tests {
         manager.abandon_plan("session-123").unwrap();
         assert!(!manager.has_plan("session-123"));
     }
+
+    #[test]
+    fn test_revisions_diff_and_restore() {
+        let (manager, _temp) = setup_test_manager();
+
+        let mut plan = manager.create_plan("Test", "session-123", None).unwrap();
+        plan.add_phase("Phase 1").add_task("Task one");
+        manager.save_plan(&plan).unwrap();
+        // Saving an unchanged plan does not add a revision
+        manager.save_plan(&plan).unwrap();
+        plan.add_phase("Phase 2").add_task("Bad merge");
+        manager.update_plan("session-123", &plan).unwrap();
+
+        let revisions = manager.list_revisions("session-123").unwrap();
+        let versions: Vec<u64> = revisions.iter().map(|r| r.version).collect();
+        assert_eq!(versions, vec![3, 2, 1]);
+        assert!(!revisions[0].content.contains("Version:"));
+
+        let current = manager.get_plan("session-123").unwrap().unwrap();
+        assert_eq!(current.version, 3);
+
+        let diff = manager.revision_diff("session-123", 3).unwrap().unwrap();
+        assert!(diff.contains("+## Phase 2: Phase 2"));
+        assert!(!diff.contains("-## Phase 1"));
+        assert!(manager.revision_diff("session-123", 9).unwrap().is_none());
+
+        // A stale expected version is refused
+        assert!(manager.restore_revision("session-123", 2, 2).is_err());
+
+        let restored = manager.restore_revision("session-123", 2, 3).unwrap();
+        assert_eq!(restored.version, 4);
+        assert_eq!(restored.phases.len(), 1);
+        assert_eq!(restored.session_id.as_deref(), Some("session-123"));
+        assert_eq!(manager.list_revisions("session-123").unwrap().len(), 4);
+
+        // Abandoning drops the history with the plan
+        manager.abandon_plan("session-123").unwrap();
+        assert!(manager.list_revisions("session-123").unwrap().is_empty());
+    }
 }
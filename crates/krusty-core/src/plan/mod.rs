@@ -6,6 +6,10 @@
 //! - Plan mode restricts editing tools until approved
 //! - Integrates with pinch for context preservation
 //! - Automatic cleanup on session deletion (CASCADE)
+//! - Tasks can carry acceptance checks that gate completion
+//! - `PlanRunner` executes an approved plan through builder agents
+//! - Revision history with diffs and version-checked restore
+//! - Optional two-way sync with a markdown file in the repo (`PlanSync`)
 //!
 //! ## Migration
 //!
@@ -13,8 +17,14 @@
 //! to the database on first access. The file-based format is still supported
 //! for export/import.
 
+mod checks;
 mod file;
 mod manager;
+mod runner;
+mod sync;
 
+pub use checks::{format_failures, run_checks, CheckOutcome, TaskCheck};
 pub use file::{PlanFile, PlanPhase, PlanStatus, PlanTask, TaskStatus};
 pub use manager::PlanManager;
+pub use runner::{PlanRunEvent, PlanRunSummary, PlanRunner, DEFAULT_MAX_PARALLEL};
+pub use sync::{resolve_conflict, PlanSync, SyncEvent, SyncResolution};
@@ -0,0 +1,592 @@
+//! Plan runner - execute an approved plan in dependency order
+//!
+//! Repeatedly takes the plan's ready tasks and hands each one to a builder
+//! agent, keeping at most `max_parallel` builders in flight. Results are
+//! recorded with [`PlanFile::complete_task`]. The first failure stops new
+//! dispatches; the run ends once the builders still in flight finish.
+
+use std::collections::HashSet;
+use std::future::Future;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use futures::stream::{FuturesUnordered, StreamExt};
+use tokio::sync::mpsc;
+use tracing::{info, warn};
+
+use super::checks::{format_failures, run_checks};
+use super::file::{PlanFile, TaskStatus};
+use crate::agent::subagent::{
+    AgentControls, AgentProgress, SharedTranscriptSink, SubAgentPool, SubAgentResult, SubAgentTask,
+};
+use crate::agent::{AgentCancellation, SharedBuildContext};
+use crate::ai::client::AiClient;
+use crate::tools::file_changes::SharedFileChangeSink;
+use crate::tools::{ToolContext, ToolRegistry};
+
+/// Default number of builders running at once
+pub const DEFAULT_MAX_PARALLEL: usize = 3;
+
+/// Longest result summary recorded on a task
+const MAX_RESULT_CHARS: usize = 500;
+
+/// Progress of a plan run
+#[derive(Debug, Clone)]
+pub enum PlanRunEvent {
+    /// A builder picked up the task
+    TaskStarted { task_id: String },
+    /// The builder finished; `result` is what was recorded on the task
+    TaskCompleted { task_id: String, result: String },
+    /// The builder failed or was cancelled
+    TaskFailed { task_id: String, error: String },
+    /// The run is over
+    Finished(PlanRunSummary),
+}
+
+/// Outcome of a plan run
+#[derive(Debug, Clone, Default)]
+pub struct PlanRunSummary {
+    /// Tasks completed during this run, in completion order
+    pub completed: Vec<String>,
+    /// First task that failed, with its error
+    pub failed: Option<(String, String)>,
+    /// Whether the run was cancelled
+    pub cancelled: bool,
+    /// Tasks still open when the run stopped
+    pub remaining: usize,
+    /// Builder outputs as `## Builder:` sections (BuildBlock layout)
+    pub output: String,
+}
+
+impl PlanRunSummary {
+    /// Whether every task of the plan is now done
+    pub fn succeeded(&self) -> bool {
+        self.failed.is_none() && !self.cancelled && self.remaining == 0
+    }
+}
+
+/// Runs a plan's tasks through builder agents
+pub struct PlanRunner {
+    pool: SubAgentPool,
+    cancellation: AgentCancellation,
+    working_dir: PathBuf,
+    /// Runs acceptance check commands through the bash tool and its hooks
+    tools: Arc<ToolRegistry>,
+    max_parallel: usize,
+}
+
+impl PlanRunner {
+    pub fn new(
+        client: Arc<AiClient>,
+        cancellation: AgentCancellation,
+        tools: Arc<ToolRegistry>,
+        working_dir: PathBuf,
+    ) -> Self {
+        Self {
+            pool: SubAgentPool::new(client, cancellation.clone()),
+            cancellation,
+            working_dir,
+            tools,
+            max_parallel: DEFAULT_MAX_PARALLEL,
+        }
+    }
+
+    /// Cap the number of builders running at once (at least 1)
+    pub fn with_max_parallel(mut self, max: usize) -> Self {
+        self.max_parallel = max.max(1);
+        self
+    }
+
+    /// Set the model builders use (see `SubAgentPool::with_override_model`)
+    pub fn with_model(mut self, model: Option<String>) -> Self {
+        self.pool = self.pool.with_override_model(model);
+        self
+    }
+
+    /// Record builder transcripts (see `SubAgentPool::with_transcript`)
+    pub fn with_transcript(mut self, transcript: Option<SharedTranscriptSink>) -> Self {
+        self.pool = self.pool.with_transcript(transcript);
+        self
+    }
+
+    /// Report the files builders touch (see `SubAgentPool::with_file_changes`)
+    pub fn with_file_changes(mut self, sink: Option<SharedFileChangeSink>) -> Self {
+        self.pool = self.pool.with_file_changes(sink);
+        self
+    }
+
+    /// Let builders be stopped, restarted or sent notes individually
+    pub fn with_controls(mut self, controls: Option<AgentControls>) -> Self {
+        self.pool = self.pool.with_controls(controls);
+        self
+    }
+
+    /// Run the plan until it is complete, a task fails, or the run is cancelled
+    ///
+    /// Task state changes are applied to the returned plan and mirrored as
+    /// `events`; builder progress goes to `progress_tx` for the build block.
+    pub async fn run(
+        &self,
+        plan: PlanFile,
+        events: mpsc::UnboundedSender<PlanRunEvent>,
+        progress_tx: mpsc::UnboundedSender<AgentProgress>,
+    ) -> PlanFile {
+        let context = Arc::new(SharedBuildContext::new());
+        let pool = &self.pool;
+
+        let dispatch = |task: SubAgentTask| {
+            let context = context.clone();
+            let progress_tx = progress_tx.clone();
+            async move {
+                let task_id = task.id.clone();
+                pool.execute_builders(vec![task], context, progress_tx)
+                    .await
+                    .pop()
+                    .unwrap_or_else(|| SubAgentResult {
+                        task_id,
+                        success: false,
+                        output: String::new(),
+                        files_examined: vec![],
+                        duration_ms: 0,
+                        turns_used: 0,
+                        error: Some("Builder returned no result".to_string()),
+                    })
+            }
+        };
+
+        run_plan(
+            plan,
+            &self.tools,
+            &ToolContext {
+                working_dir: self.working_dir.clone(),
+                ..Default::default()
+            },
+            self.max_parallel,
+            &self.cancellation,
+            &events,
+            dispatch,
+        )
+        .await
+    }
+}
+
+/// Scheduling loop, generic over how a task is executed
+async fn run_plan<F, Fut>(
+    mut plan: PlanFile,
+    tools: &ToolRegistry,
+    checks_ctx: &ToolContext,
+    max_parallel: usize,
+    cancellation: &AgentCancellation,
+    events: &mpsc::UnboundedSender<PlanRunEvent>,
+    dispatch: F,
+) -> PlanFile
+where
+    F: Fn(SubAgentTask) -> Fut,
+    Fut: Future<Output = SubAgentResult>,
+{
+    let mut summary = PlanRunSummary::default();
+    let mut in_flight: HashSet<String> = HashSet::new();
+    let mut running = FuturesUnordered::new();
+
+    info!(
+        plan = %plan.title,
+        max_parallel,
+        "Plan run: starting"
+    );
+
+    loop {
+        if cancellation.is_cancelled() {
+            summary.cancelled = true;
+        }
+
+        if summary.failed.is_none() && !summary.cancelled {
+            let free = max_parallel.saturating_sub(in_flight.len());
+            for task_id in next_ready_tasks(&plan, &in_flight, free) {
+                if let Err(e) = plan.start_task(&task_id) {
+                    warn!(task_id = %task_id, error = %e, "Plan run: cannot start task");
+                    continue;
+                }
+
+                let task =
+                    SubAgentTask::new(format!("task-{}", task_id), builder_prompt(&plan, &task_id))
+                        .with_name(format!("Task {}", task_id))
+                        .with_working_dir(checks_ctx.working_dir.clone());
+
+                in_flight.insert(task_id.clone());
+                let _ = events.send(PlanRunEvent::TaskStarted {
+                    task_id: task_id.clone(),
+                });
+
+                let checks = plan
+                    .find_task(&task_id)
+                    .map(|t| t.checks.clone())
+                    .unwrap_or_default();
+                let fut = dispatch(task);
+                running.push(async move {
+                    let mut result = fut.await;
+                    // A builder's work only counts once the task's checks pass
+                    if result.success && !checks.is_empty() {
+                        let outcomes = run_checks(&checks, tools, checks_ctx).await;
+                        if let Some(report) = format_failures(&outcomes) {
+                            result.success = false;
+                            result.error = Some(report);
+                        }
+                    }
+                    (task_id, result)
+                });
+            }
+        }
+
+        let Some((task_id, result)) = running.next().await else {
+            break;
+        };
+        in_flight.remove(&task_id);
+
+        if result.success {
+            let recorded = summarize_output(&result.output);
+            if let Err(e) = plan.complete_task(&task_id, &recorded) {
+                warn!(task_id = %task_id, error = %e, "Plan run: cannot record result");
+            }
+            summary.output.push_str(&format!(
+                "\n## Builder: {}\n{}\n",
+                result.task_id, result.output
+            ));
+            summary.completed.push(task_id.clone());
+            let _ = events.send(PlanRunEvent::TaskCompleted {
+                task_id,
+                result: recorded,
+            });
+        } else {
+            let error = result.error.unwrap_or_else(|| "unknown error".to_string());
+
+            // Put the task back so a later run picks it up again
+            if let Some(task) = plan.find_task_mut(&task_id) {
+                task.status = TaskStatus::Pending;
+            }
+            let _ = events.send(PlanRunEvent::TaskFailed {
+                task_id: task_id.clone(),
+                error: error.clone(),
+            });
+
+            // Builders interrupted by a cancel are not failures
+            if !cancellation.is_cancelled() && summary.failed.is_none() {
+                warn!(task_id = %task_id, error = %error, "Plan run: task failed, stopping");
+                summary.failed = Some((task_id, error));
+            }
+        }
+    }
+
+    summary.remaining = plan.total_tasks().saturating_sub(plan.completed_tasks());
+    info!(
+        completed = summary.completed.len(),
+        remaining = summary.remaining,
+        failed = summary.failed.is_some(),
+        cancelled = summary.cancelled,
+        "Plan run: finished"
+    );
+    let _ = events.send(PlanRunEvent::Finished(summary));
+    plan
+}
+
+/// Ready tasks not already running, up to `limit`
+///
+/// A task with open subtasks waits for them, so parents run after children.
+fn next_ready_tasks(plan: &PlanFile, in_flight: &HashSet<String>, limit: usize) -> Vec<String> {
+    plan.get_ready_tasks()
+        .into_iter()
+        .filter(|t| !t.completed && !in_flight.contains(&t.id))
+        .filter(|t| {
+            t.children.iter().all(|child| {
+                plan.find_task(child)
+                    .is_none_or(|c| c.completed || c.status == TaskStatus::Completed)
+            })
+        })
+        .take(limit)
+        .map(|t| t.id.clone())
+        .collect()
+}
+
+/// Prompt for the builder working on one task
+fn builder_prompt(plan: &PlanFile, task_id: &str) -> String {
+    let Some(task) = plan.find_task(task_id) else {
+        return String::new();
+    };
+
+    let mut prompt = format!(
+        "You are carrying out task {} of the plan \"{}\".\n\nTASK: {}\n",
+        task.id, plan.title, task.description
+    );
+
+    if let Some(context) = &task.context {
+        prompt.push_str(&format!("\nCONTEXT:\n{}\n", context));
+    }
+
+    let prerequisites: Vec<String> = task
+        .blocked_by
+        .iter()
+        .filter_map(|id| plan.find_task(id))
+        .map(|t| match &t.result {
+            Some(result) => format!("- {} {}: {}", t.id, t.description, result),
+            None => format!("- {} {}", t.id, t.description),
+        })
+        .collect();
+    if !prerequisites.is_empty() {
+        prompt.push_str(&format!(
+            "\nCOMPLETED PREREQUISITES:\n{}\n",
+            prerequisites.join("\n")
+        ));
+    }
+
+    if !task.checks.is_empty() {
+        let checks: Vec<String> = task.checks.iter().map(|c| format!("- {}", c)).collect();
+        prompt.push_str(&format!(
+            "\nACCEPTANCE CHECKS (run after you finish; the task only counts as done if they pass):\n{}\n",
+            checks.join("\n")
+        ));
+    }
+
+    prompt.push_str(
+        "\nOther builders may be working on other tasks of this plan at the same time. \
+         Stay within this task's scope and don't undo changes you didn't make.\n\
+         When you are done, end with a short summary of what you changed.",
+    );
+    prompt
+}
+
+/// Result recorded on the task: the builder's closing paragraph
+fn summarize_output(output: &str) -> String {
+    let last = output
+        .trim()
+        .rsplit("\n\n")
+        .map(str::trim)
+        .find(|p| !p.is_empty())
+        .unwrap_or("Completed by builder");
+
+    if last.chars().count() > MAX_RESULT_CHARS {
+        let truncated: String = last.chars().take(MAX_RESULT_CHARS).collect();
+        format!("{}...", truncated.trim_end())
+    } else {
+        last.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plan::TaskCheck;
+    use std::path::Path;
+    use std::sync::Mutex;
+
+    fn result(task: &SubAgentTask, success: bool) -> SubAgentResult {
+        SubAgentResult {
+            task_id: task.id.clone(),
+            success,
+            output: format!("Worked on it.\n\nDone with {}", task.id),
+            files_examined: vec![],
+            duration_ms: 1,
+            turns_used: 1,
+            error: (!success).then(|| "boom".to_string()),
+        }
+    }
+
+    fn context(dir: &Path) -> ToolContext {
+        ToolContext {
+            working_dir: dir.to_path_buf(),
+            ..Default::default()
+        }
+    }
+
+    /// 1.1 and 1.2 are independent; 2.1 needs both
+    fn diamond_plan() -> PlanFile {
+        let mut plan = PlanFile::new("Diamond");
+        let phase = plan.add_phase("One");
+        phase.add_task("Left");
+        phase.add_task("Right");
+        plan.add_phase("Two").add_task("Join");
+        plan.add_dependency("2.1", "1.1").unwrap();
+        plan.add_dependency("2.1", "1.2").unwrap();
+        plan
+    }
+
+    fn drain(rx: &mut mpsc::UnboundedReceiver<PlanRunEvent>) -> Vec<PlanRunEvent> {
+        let mut events = Vec::new();
+        while let Ok(event) = rx.try_recv() {
+            events.push(event);
+        }
+        events
+    }
+
+    #[tokio::test]
+    async fn test_runs_in_dependency_order() {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let cancellation = AgentCancellation::new();
+        let order = Mutex::new(Vec::new());
+
+        let plan = run_plan(
+            diamond_plan(),
+            &ToolRegistry::new(),
+            &context(Path::new("/tmp")),
+            2,
+            &cancellation,
+            &tx,
+            |task| {
+                order.lock().unwrap().push(task.id.clone());
+                async move { result(&task, true) }
+            },
+        )
+        .await;
+
+        assert!(plan.is_complete());
+        assert_eq!(
+            plan.find_task("2.1").unwrap().result.as_deref(),
+            Some("Done with task-2.1")
+        );
+        let order = order.into_inner().unwrap();
+        assert_eq!(order.last().map(String::as_str), Some("task-2.1"));
+
+        let events = drain(&mut rx);
+        let Some(PlanRunEvent::Finished(summary)) = events.last() else {
+            panic!("run should end with Finished");
+        };
+        assert!(summary.succeeded());
+        assert_eq!(summary.completed.len(), 3);
+        assert!(summary.output.contains("## Builder: task-1.1"));
+    }
+
+    #[tokio::test]
+    async fn test_respects_concurrency_cap() {
+        let (tx, _rx) = mpsc::unbounded_channel();
+        let cancellation = AgentCancellation::new();
+        // (running now, most running at once)
+        let running = Mutex::new((0usize, 0usize));
+
+        for cap in [1, 2] {
+            *running.lock().unwrap() = (0, 0);
+            let running = &running;
+            let plan = run_plan(
+                diamond_plan(),
+                &ToolRegistry::new(),
+                &context(Path::new("/tmp")),
+                cap,
+                &cancellation,
+                &tx,
+                |task| {
+                    let mut r = running.lock().unwrap();
+                    r.0 += 1;
+                    r.1 = r.1.max(r.0);
+                    drop(r);
+                    async move {
+                        tokio::task::yield_now().await;
+                        running.lock().unwrap().0 -= 1;
+                        result(&task, true)
+                    }
+                },
+            )
+            .await;
+
+            assert!(plan.is_complete());
+            assert_eq!(running.lock().unwrap().1, cap);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_stops_on_failure() {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let cancellation = AgentCancellation::new();
+
+        let plan = run_plan(
+            diamond_plan(),
+            &ToolRegistry::new(),
+            &context(Path::new("/tmp")),
+            1,
+            &cancellation,
+            &tx,
+            |task| {
+                let success = task.id != "task-1.1";
+                async move { result(&task, success) }
+            },
+        )
+        .await;
+
+        // 1.1 failed first, so nothing else was dispatched
+        let failed = plan.find_task("1.1").unwrap();
+        assert_eq!(failed.status, TaskStatus::Pending);
+        assert!(!plan.find_task("1.2").unwrap().completed);
+
+        let events = drain(&mut rx);
+        let Some(PlanRunEvent::Finished(summary)) = events.last() else {
+            panic!("run should end with Finished");
+        };
+        assert_eq!(
+            summary.failed,
+            Some(("1.1".to_string(), "boom".to_string()))
+        );
+        assert_eq!(summary.remaining, 3);
+        assert!(!summary.succeeded());
+    }
+
+    #[tokio::test]
+    async fn test_failing_check_fails_task() {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let cancellation = AgentCancellation::new();
+        let dir = tempfile::tempdir().unwrap();
+
+        let mut plan = diamond_plan();
+        plan.find_task_mut("1.1")
+            .unwrap()
+            .checks
+            .extend(TaskCheck::parse("exists: parser.rs"));
+
+        let plan = run_plan(
+            plan,
+            &ToolRegistry::new(),
+            &context(dir.path()),
+            2,
+            &cancellation,
+            &tx,
+            |task| async move { result(&task, true) },
+        )
+        .await;
+
+        assert!(!plan.find_task("1.1").unwrap().completed);
+        assert!(plan.find_task("1.2").unwrap().completed);
+
+        let events = drain(&mut rx);
+        let Some(PlanRunEvent::Finished(summary)) = events.last() else {
+            panic!("run should end with Finished");
+        };
+        let (task_id, error) = summary.failed.clone().unwrap();
+        assert_eq!(task_id, "1.1");
+        assert!(error.contains("parser.rs does not exist"));
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_run_dispatches_nothing() {
+        let (tx, _rx) = mpsc::unbounded_channel();
+        let cancellation = AgentCancellation::new();
+        cancellation.cancel();
+
+        let plan = run_plan(
+            diamond_plan(),
+            &ToolRegistry::new(),
+            &context(Path::new("/tmp")),
+            2,
+            &cancellation,
+            &tx,
+            |task| async move { result(&task, true) },
+        )
+        .await;
+        assert_eq!(plan.completed_tasks(), 0);
+    }
+
+    #[test]
+    fn test_summarize_output() {
+        assert_eq!(
+            summarize_output("Step one.\n\nAdded the parser.\n"),
+            "Added the parser."
+        );
+        assert_eq!(summarize_output("   "), "Completed by builder");
+        let long = "x".repeat(MAX_RESULT_CHARS + 10);
+        assert_eq!(summarize_output(&long).len(), MAX_RESULT_CHARS + 3);
+    }
+}
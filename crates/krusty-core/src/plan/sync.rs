@@ -0,0 +1,419 @@
+//! Two-way sync between a plan and a markdown file in the repo
+//!
+//! With sync enabled for a working directory, the active plan is written to
+//! a repo file such as `docs/plan.md` whenever it changes, so it can be
+//! reviewed like any other file. Edits made to that file outside Krusty are
+//! picked up on the next `poll`: if the file was edited on top of the plan's
+//! current version it replaces the plan, otherwise both sides changed and the
+//! caller has to pick one (see `SyncResolution`). A file that doesn't parse
+//! is left alone until it does, so a half-finished edit is never overwritten.
+//! An older file found when sync starts is only replaced if it matches a
+//! stored revision of the plan; otherwise it is treated as a conflict.
+
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use anyhow::{Context, Result};
+
+use super::file::PlanFile;
+
+/// What `PlanSync::poll` found
+#[derive(Debug)]
+pub enum SyncEvent {
+    /// Nothing to do
+    Idle,
+    /// The plan was written to the file
+    Written,
+    /// The file was edited on top of the current plan; this is the new plan
+    Updated(PlanFile),
+    /// The file was edited, but the plan also changed since the file's version
+    Conflict(PlanFile),
+    /// The file was edited but no longer parses as a plan
+    Invalid(String),
+}
+
+/// How to settle a sync conflict
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncResolution {
+    /// Replace the plan with the file
+    UseFile,
+    /// Keep the plan and overwrite the file
+    KeepPlan,
+    /// Merge the file's phases, tasks and completions into the plan
+    Merge,
+}
+
+/// Keeps one plan file in step with the active plan
+#[derive(Debug)]
+pub struct PlanSync {
+    path: PathBuf,
+    /// File content as last written or read, to tell external edits apart
+    synced_content: Option<String>,
+    /// Session and version of the plan last written
+    synced_version: Option<(String, u64)>,
+    /// Modification time and size of the file when last read or written
+    file_stamp: Option<(SystemTime, u64)>,
+    /// The file holds an external edit that doesn't parse yet
+    unparsed_edit: bool,
+}
+
+impl PlanSync {
+    /// Sync with `path` (relative paths are resolved against `working_dir`)
+    pub fn new(working_dir: &Path, path: impl AsRef<Path>) -> Self {
+        Self {
+            path: working_dir.join(path),
+            synced_content: None,
+            synced_version: None,
+            file_stamp: None,
+            unparsed_edit: false,
+        }
+    }
+
+    /// Absolute path of the synced file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Whether `poll` could find anything to do: the plan moved past the
+    /// version last synced, or the file changed on disk
+    ///
+    /// Cheap enough to call often, so the stored plan only has to be loaded
+    /// when this returns true.
+    pub fn needs_poll(&self, session_id: &str, version: u64) -> bool {
+        let file_changed = file_stamp(&self.path) != self.file_stamp;
+        // Nothing is written over an unparsed edit, so only the file matters
+        if self.unparsed_edit {
+            return file_changed;
+        }
+        let synced = self
+            .synced_version
+            .as_ref()
+            .is_some_and(|(s, v)| s == session_id && *v == version);
+        !synced || file_changed
+    }
+
+    /// Compare the file with the stored plan and bring them in step
+    ///
+    /// `stored` must be the plan as last saved (its `version` is the
+    /// database revision), and `revision` looks up the stored markdown of an
+    /// earlier version. External edits are checked before writing, so an
+    /// unseen edit is never overwritten.
+    pub fn poll(
+        &mut self,
+        stored: &PlanFile,
+        revision: impl FnOnce(u64) -> Option<String>,
+    ) -> Result<SyncEvent> {
+        self.file_stamp = file_stamp(&self.path);
+        let disk = match std::fs::read_to_string(&self.path) {
+            Ok(content) => Some(content),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+            Err(e) => return Err(e).with_context(|| format!("reading {}", self.path.display())),
+        };
+
+        let first_seen = self.synced_content.is_none();
+        match disk {
+            Some(content) if self.synced_content.as_deref() != Some(content.as_str()) => {
+                self.synced_content = Some(content.clone());
+                let file_plan = match PlanFile::from_markdown(&content) {
+                    Ok(plan) => plan,
+                    Err(e) => {
+                        self.unparsed_edit = true;
+                        return Ok(SyncEvent::Invalid(e));
+                    }
+                };
+                self.unparsed_edit = false;
+                let file_plan = adopt(stored, file_plan);
+                if content == stored.to_markdown() {
+                    // Same plan, e.g. after a checkout; nothing to apply
+                    self.synced_version = Some(session_version(stored));
+                    return Ok(SyncEvent::Idle);
+                }
+                if first_seen
+                    && file_plan.version < stored.version
+                    && revision(file_plan.version).as_deref()
+                        == Some(revision_body(&content, file_plan.version).as_str())
+                {
+                    // Left over from an earlier version of this plan, unedited
+                    self.write(stored)?;
+                    return Ok(SyncEvent::Written);
+                }
+                return Ok(if stored.version_matches(file_plan.version) {
+                    SyncEvent::Updated(file_plan)
+                } else {
+                    SyncEvent::Conflict(file_plan)
+                });
+            }
+            // Keep a half-finished edit until it parses or the file is removed
+            Some(_) if self.unparsed_edit => return Ok(SyncEvent::Idle),
+            Some(_) if self.synced_version == Some(session_version(stored)) => {
+                return Ok(SyncEvent::Idle);
+            }
+            _ => {}
+        }
+
+        self.write(stored)?;
+        Ok(SyncEvent::Written)
+    }
+
+    /// Write the plan to the file, replacing whatever is there
+    pub fn write(&mut self, plan: &PlanFile) -> Result<()> {
+        let content = plan.to_markdown();
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&self.path, &content)
+            .with_context(|| format!("writing {}", self.path.display()))?;
+        tracing::debug!(path = %self.path.display(), version = plan.version, "Wrote synced plan");
+        self.synced_content = Some(content);
+        self.synced_version = Some(session_version(plan));
+        self.file_stamp = file_stamp(&self.path);
+        self.unparsed_edit = false;
+        Ok(())
+    }
+
+    /// Force the next `poll` to rewrite the file (after `KeepPlan`)
+    pub fn mark_stale(&mut self) {
+        self.synced_version = None;
+    }
+}
+
+/// Settle a conflict between the stored plan and the file's plan
+///
+/// Returns the plan to save; for `KeepPlan` that is the stored plan, and
+/// the caller should `mark_stale` so the file is rewritten.
+pub fn resolve_conflict(
+    stored: &PlanFile,
+    file_plan: &PlanFile,
+    resolution: SyncResolution,
+) -> PlanFile {
+    match resolution {
+        SyncResolution::UseFile => adopt(stored, file_plan.clone()),
+        SyncResolution::KeepPlan => stored.clone(),
+        SyncResolution::Merge => {
+            let mut merged = stored.clone();
+            merged.merge_from(file_plan);
+            merged
+        }
+    }
+}
+
+/// Take over identity fields that belong to this Krusty session, not the file
+fn adopt(stored: &PlanFile, mut file_plan: PlanFile) -> PlanFile {
+    file_plan.session_id = stored.session_id.clone();
+    file_plan.working_dir = stored.working_dir.clone();
+    file_plan.created_at = stored.created_at;
+    file_plan
+}
+
+/// File content as kept in a plan revision, which has no version line
+fn revision_body(content: &str, version: u64) -> String {
+    content.replacen(&format!("Version: {}\n", version), "", 1)
+}
+
+fn session_version(plan: &PlanFile) -> (String, u64) {
+    (plan.session_id.clone().unwrap_or_default(), plan.version)
+}
+
+/// Modification time and size of a file, `None` if it can't be read
+fn file_stamp(path: &Path) -> Option<(SystemTime, u64)> {
+    let metadata = std::fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stored_plan(version: u64) -> PlanFile {
+        let mut plan = PlanFile::new("Sync");
+        plan.session_id = Some("session-1".to_string());
+        plan.add_phase("Phase 1").add_task("First");
+        plan.version = version;
+        plan
+    }
+
+    #[test]
+    fn test_write_then_pick_up_edit() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut sync = PlanSync::new(dir.path(), "docs/plan.md");
+        let plan = stored_plan(1);
+
+        assert!(matches!(
+            sync.poll(&plan, |_| None).unwrap(),
+            SyncEvent::Written
+        ));
+        assert!(matches!(
+            sync.poll(&plan, |_| None).unwrap(),
+            SyncEvent::Idle
+        ));
+
+        // Edit made on top of version 1 is applied
+        let edited = std::fs::read_to_string(sync.path())
+            .unwrap()
+            .replace("- [ ] Task 1.1", "- [x] Task 1.1");
+        std::fs::write(sync.path(), &edited).unwrap();
+        match sync.poll(&plan, |_| None).unwrap() {
+            SyncEvent::Updated(updated) => {
+                assert!(updated.find_task("1.1").unwrap().completed);
+                assert_eq!(updated.session_id.as_deref(), Some("session-1"));
+            }
+            other => panic!("expected update, got {:?}", other),
+        }
+
+        // Saving the update bumps the version, which is written back
+        let mut saved = plan.clone();
+        saved.check_task("1.1");
+        saved.version = 2;
+        assert!(matches!(
+            sync.poll(&saved, |_| None).unwrap(),
+            SyncEvent::Written
+        ));
+        assert!(std::fs::read_to_string(sync.path())
+            .unwrap()
+            .contains("Version: 2"));
+    }
+
+    #[test]
+    fn test_stale_edit_is_a_conflict() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut sync = PlanSync::new(dir.path(), "plan.md");
+        let plan = stored_plan(1);
+        sync.poll(&plan, |_| None).unwrap();
+
+        // The plan moves on to version 2 while the file still says version 1
+        let mut newer = plan.clone();
+        newer.add_phase("Phase 2").add_task("Second");
+        newer.version = 2;
+        let stale = std::fs::read_to_string(sync.path())
+            .unwrap()
+            .replace("First", "First, edited");
+        std::fs::write(sync.path(), &stale).unwrap();
+
+        let SyncEvent::Conflict(file_plan) = sync.poll(&newer, |_| None).unwrap() else {
+            panic!("expected conflict");
+        };
+        let merged = resolve_conflict(&newer, &file_plan, SyncResolution::Merge);
+        assert_eq!(merged.phases.len(), 2);
+        assert_eq!(
+            merged.find_task("1.1").unwrap().description,
+            "First, edited"
+        );
+
+        let kept = resolve_conflict(&newer, &file_plan, SyncResolution::KeepPlan);
+        sync.mark_stale();
+        assert!(matches!(
+            sync.poll(&kept, |_| None).unwrap(),
+            SyncEvent::Written
+        ));
+
+        std::fs::write(sync.path(), "not a plan").unwrap();
+        assert!(matches!(
+            sync.poll(&kept, |_| None).unwrap(),
+            SyncEvent::Invalid(_)
+        ));
+        // An invalid file is reported once, then left alone until edited again
+        assert!(matches!(
+            sync.poll(&kept, |_| None).unwrap(),
+            SyncEvent::Idle
+        ));
+    }
+
+    #[test]
+    fn test_unparsed_edit_is_not_overwritten() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut sync = PlanSync::new(dir.path(), "plan.md");
+        let plan = stored_plan(1);
+        sync.poll(&plan, |_| None).unwrap();
+
+        std::fs::write(sync.path(), "# Half-written plan\n- [ ").unwrap();
+        assert!(matches!(
+            sync.poll(&plan, |_| None).unwrap(),
+            SyncEvent::Invalid(_)
+        ));
+
+        // The plan changes while the edit is unfinished: the file is kept
+        let mut newer = plan.clone();
+        newer.version = 2;
+        assert!(!sync.needs_poll("session-1", 2));
+        assert!(matches!(
+            sync.poll(&newer, |_| None).unwrap(),
+            SyncEvent::Idle
+        ));
+        assert_eq!(
+            std::fs::read_to_string(sync.path()).unwrap(),
+            "# Half-written plan\n- [ "
+        );
+
+        // Removing the file hands it back to the plan
+        std::fs::remove_file(sync.path()).unwrap();
+        assert!(matches!(
+            sync.poll(&newer, |_| None).unwrap(),
+            SyncEvent::Written
+        ));
+    }
+
+    /// Revision lookup holding `plan` as its stored revision
+    fn revisions(plan: &PlanFile) -> impl FnOnce(u64) -> Option<String> {
+        let version = plan.version;
+        let mut body = plan.clone();
+        body.version = 0;
+        let body = body.to_markdown();
+        move |v| (v == version).then_some(body)
+    }
+
+    #[test]
+    fn test_older_file_found_at_start_is_overwritten() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("plan.md");
+        let older = stored_plan(1);
+        std::fs::write(&path, older.to_markdown()).unwrap();
+
+        let mut newer = stored_plan(3);
+        newer.add_phase("Phase 2").add_task("Second");
+        let mut sync = PlanSync::new(dir.path(), "plan.md");
+        assert!(matches!(
+            sync.poll(&newer, revisions(&older)).unwrap(),
+            SyncEvent::Written
+        ));
+        assert!(std::fs::read_to_string(&path)
+            .unwrap()
+            .contains("Version: 3"));
+    }
+
+    #[test]
+    fn test_older_file_edited_while_closed_is_a_conflict() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("plan.md");
+        let older = stored_plan(1);
+        // A teammate edited the file on top of version 1
+        let edited = older.to_markdown().replace("First", "First, reworded");
+        std::fs::write(&path, &edited).unwrap();
+
+        let mut newer = stored_plan(3);
+        newer.add_phase("Phase 2").add_task("Second");
+        let mut sync = PlanSync::new(dir.path(), "plan.md");
+        let SyncEvent::Conflict(file_plan) = sync.poll(&newer, revisions(&older)).unwrap() else {
+            panic!("expected conflict");
+        };
+        assert_eq!(
+            file_plan.find_task("1.1").unwrap().description,
+            "First, reworded"
+        );
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), edited);
+    }
+
+    #[test]
+    fn test_needs_poll_tracks_version_and_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut sync = PlanSync::new(dir.path(), "plan.md");
+        let plan = stored_plan(1);
+        assert!(sync.needs_poll("session-1", 1));
+
+        sync.poll(&plan, |_| None).unwrap();
+        assert!(!sync.needs_poll("session-1", 1));
+        assert!(sync.needs_poll("session-1", 2));
+
+        std::fs::write(sync.path(), "edited elsewhere, and longer than before").unwrap();
+        assert!(sync.needs_poll("session-1", 1));
+    }
+}
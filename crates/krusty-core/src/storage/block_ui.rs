@@ -4,11 +4,12 @@
 
 use anyhow::Result;
 use rusqlite::params;
+use serde::{Deserialize, Serialize};
 
 use super::database::Database;
 
 /// Block UI state for session restoration
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BlockUiState {
     pub block_id: String,
     pub collapsed: bool,
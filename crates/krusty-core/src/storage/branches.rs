@@ -0,0 +1,321 @@
+//! Conversation branches
+//!
+//! Editing an earlier user message (or resending it to another model) forks
+//! the session: the fork gets a copy of the messages before that point and
+//! `fork_of`/`fork_index` pointing at the session it branched from. The
+//! sessions that differ at message `k` are siblings, shown as `‹ 2/3 ›`.
+//!
+//! Forks of forks are attached to the session that actually introduced the
+//! variant, so every version of message `k` ends up in one sibling group no
+//! matter which branch it was forked from.
+
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+use rusqlite::{params, OptionalExtension};
+
+use super::database::Database;
+
+/// The versions of one message across sibling branches
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BranchSiblings {
+    /// Message index the siblings differ at
+    pub message_index: usize,
+    /// Session IDs, the original first, then forks oldest first
+    pub sessions: Vec<String>,
+    /// Model each of `sessions` was started with
+    pub models: Vec<Option<String>>,
+    /// Position of the queried session's version in `sessions`
+    pub current: usize,
+}
+
+/// SQLite-backed branch storage
+pub struct BranchStore<'a> {
+    db: &'a Database,
+}
+
+impl<'a> BranchStore<'a> {
+    pub fn new(db: &'a Database) -> Self {
+        Self { db }
+    }
+
+    /// Fork `session_id` before message `at_message`: the new session gets
+    /// copies of messages `0..at_message` and nothing after
+    pub fn fork_session(
+        &self,
+        session_id: &str,
+        at_message: usize,
+        model: Option<&str>,
+    ) -> Result<String> {
+        let (title, working_dir, user_id): (String, Option<String>, Option<String>) = self
+            .db
+            .conn()
+            .query_row(
+                "SELECT title, working_dir, user_id FROM sessions WHERE id = ?1",
+                [session_id],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .optional()?
+            .ok_or_else(|| anyhow!("Session {} not found", session_id))?;
+
+        let (anchor, _) = self.variant_at(session_id, at_message)?;
+        let id = uuid::Uuid::new_v4().to_string();
+        let now = Utc::now().to_rfc3339();
+
+        let tx = self.db.conn().unchecked_transaction()?;
+        tx.execute(
+            "INSERT INTO sessions
+                 (id, title, created_at, updated_at, model, working_dir, user_id, fork_of, fork_index)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![
+                id,
+                title,
+                now,
+                now,
+                model,
+                working_dir,
+                user_id,
+                anchor,
+                at_message as i64
+            ],
+        )?;
+
+        let mut stmt = tx.prepare(
+            "SELECT id, role, content, created_at FROM messages
+             WHERE session_id = ?1 ORDER BY id LIMIT ?2",
+        )?;
+        let rows = stmt
+            .query_map(params![session_id, at_message as i64], |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, String>(3)?,
+                ))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        drop(stmt);
+        if rows.len() < at_message {
+            return Err(anyhow!(
+                "Session has {} messages, can't fork at message {}",
+                rows.len(),
+                at_message
+            ));
+        }
+        for (source_id, role, content, created_at) in rows {
+            tx.execute(
+                "INSERT INTO messages (session_id, role, content, created_at)
+                 VALUES (?1, ?2, ?3, ?4)",
+                params![id, role, content, created_at],
+            )?;
+            // Copy the search index entry too, so the prefix stays searchable
+            tx.execute(
+                "INSERT INTO messages_fts (rowid, text)
+                 SELECT ?1, text FROM messages_fts WHERE rowid = ?2",
+                params![tx.last_insert_rowid(), source_id],
+            )?;
+        }
+        tx.commit()?;
+
+        tracing::info!(
+            session_id = %id,
+            parent = %session_id,
+            at_message,
+            "Forked session"
+        );
+        Ok(id)
+    }
+
+    /// The sibling versions of message `message_index` as seen from `session_id`
+    pub fn siblings(&self, session_id: &str, message_index: usize) -> Result<BranchSiblings> {
+        let (anchor, variant) = self.variant_at(session_id, message_index)?;
+
+        // A deleted original leaves its forks as the only versions
+        let anchor_model: Option<Option<String>> = self
+            .db
+            .conn()
+            .query_row(
+                "SELECT model FROM sessions WHERE id = ?1",
+                [&anchor],
+                |row| row.get(0),
+            )
+            .optional()?;
+        let mut sessions = Vec::new();
+        let mut models = Vec::new();
+        if let Some(model) = anchor_model {
+            sessions.push(anchor.clone());
+            models.push(model);
+        }
+        let mut stmt = self.db.conn().prepare(
+            "SELECT id, model FROM sessions WHERE fork_of = ?1 AND fork_index = ?2
+             ORDER BY created_at, rowid",
+        )?;
+        let forks = stmt.query_map(params![anchor, message_index as i64], |row| {
+            Ok((row.get(0)?, row.get(1)?))
+        })?;
+        for fork in forks {
+            let (id, model) = fork?;
+            sessions.push(id);
+            models.push(model);
+        }
+
+        let current = sessions.iter().position(|s| *s == variant).unwrap_or(0);
+        Ok(BranchSiblings {
+            message_index,
+            sessions,
+            models,
+            current,
+        })
+    }
+
+    /// Every message of `session_id` that has more than one version
+    pub fn branch_points(&self, session_id: &str) -> Result<Vec<BranchSiblings>> {
+        // Candidates: where this session and its ancestors were forked, and
+        // where anything was forked from them
+        let mut candidates = Vec::new();
+        let mut current = Some(session_id.to_string());
+        while let Some(id) = current {
+            let mut stmt = self
+                .db
+                .conn()
+                .prepare("SELECT DISTINCT fork_index FROM sessions WHERE fork_of = ?1")?;
+            for index in stmt.query_map([&id], |row| row.get::<_, i64>(0))? {
+                candidates.push(index? as usize);
+            }
+            let (fork_of, fork_index) = self.fork_link(&id)?;
+            if let Some(index) = fork_index {
+                candidates.push(index);
+            }
+            current = fork_of;
+        }
+        candidates.sort_unstable();
+        candidates.dedup();
+
+        let mut points = Vec::new();
+        for index in candidates {
+            let siblings = self.siblings(session_id, index)?;
+            if siblings.sessions.len() > 1 {
+                points.push(siblings);
+            }
+        }
+        Ok(points)
+    }
+
+    /// `(anchor, variant)` for message `index`: the session whose forks at
+    /// `index` are the siblings, and the one among them `session_id` follows
+    fn variant_at(&self, session_id: &str, index: usize) -> Result<(String, String)> {
+        let mut current = session_id.to_string();
+        loop {
+            match self.fork_link(&current)? {
+                (Some(parent), Some(fork_index)) if fork_index == index => {
+                    return Ok((parent, current));
+                }
+                // Forked after `index`: its message there is the parent's
+                (Some(parent), Some(fork_index)) if fork_index > index => current = parent,
+                _ => return Ok((current.clone(), current)),
+            }
+        }
+    }
+
+    fn fork_link(&self, session_id: &str) -> Result<(Option<String>, Option<usize>)> {
+        let link: Option<(Option<String>, Option<i64>)> = self
+            .db
+            .conn()
+            .query_row(
+                "SELECT fork_of, fork_index FROM sessions WHERE id = ?1",
+                [session_id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()?;
+        Ok(link
+            .map(|(fork_of, index)| (fork_of, index.map(|i| i as usize)))
+            .unwrap_or_default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::storage::SessionManager;
+
+    fn create_manager() -> (SessionManager, TempDir) {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let db = Database::new(&temp_dir.path().join("test.db")).expect("Failed to create db");
+        (SessionManager::new(db), temp_dir)
+    }
+
+    fn add_messages(sm: &SessionManager, session_id: &str, texts: &[&str]) {
+        for (i, text) in texts.iter().enumerate() {
+            let role = if i % 2 == 0 { "user" } else { "assistant" };
+            let json = format!(r#"[{{"type":"text","text":"{}"}}]"#, text);
+            sm.save_message(session_id, role, &json).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_fork_copies_prefix() {
+        let (sm, _temp) = create_manager();
+        let original = sm.create_session("Fix bug", None, Some("/p")).unwrap();
+        add_messages(&sm, &original, &["one", "reply", "two", "reply"]);
+
+        let fork = sm.fork_session(&original, 2, Some("other-model")).unwrap();
+        assert_eq!(
+            sm.branch_siblings(&fork, 2).unwrap().models,
+            vec![None, Some("other-model".to_string())]
+        );
+        let messages = sm.load_session_messages(&fork).unwrap();
+        assert_eq!(messages.len(), 2);
+        assert!(messages[0].1.contains("one"));
+        assert_eq!(
+            sm.get_session(&fork)
+                .unwrap()
+                .unwrap()
+                .working_dir
+                .as_deref(),
+            Some("/p")
+        );
+
+        // Both keep their text searchable
+        let hits = sm.search_messages("one", &Default::default(), 10).unwrap();
+        assert_eq!(hits.len(), 2);
+
+        assert!(sm.fork_session(&original, 9, None).is_err());
+    }
+
+    #[test]
+    fn test_siblings_across_nested_forks() {
+        let (sm, _temp) = create_manager();
+        let a = sm.create_session("A", None, None).unwrap();
+        add_messages(&sm, &a, &["q1", "r1", "q2", "r2"]);
+
+        // B edits message 2 of A; C edits message 2 of B (same group);
+        // D edits message 0 of B (B shares A's message 0, so D is A's sibling)
+        let b = sm.fork_session(&a, 2, None).unwrap();
+        add_messages(&sm, &b, &["q2 edited", "r2"]);
+        let c = sm.fork_session(&b, 2, None).unwrap();
+        let d = sm.fork_session(&b, 0, None).unwrap();
+
+        let at_2 = sm.branch_siblings(&c, 2).unwrap();
+        assert_eq!(at_2.sessions, vec![a.clone(), b.clone(), c]);
+        assert_eq!(at_2.current, 2);
+        assert_eq!(at_2.models, vec![None, None, None]);
+
+        let at_0 = sm.branch_siblings(&b, 0).unwrap();
+        assert_eq!(at_0.sessions, vec![a, d.clone()]);
+        assert_eq!(at_0.current, 0);
+
+        let points: Vec<(usize, usize, usize)> = sm
+            .branch_points(&b)
+            .unwrap()
+            .into_iter()
+            .map(|p| (p.message_index, p.current, p.sessions.len()))
+            .collect();
+        assert_eq!(points, vec![(0, 0, 2), (2, 1, 3)]);
+        assert!(sm
+            .branch_points(&d)
+            .unwrap()
+            .iter()
+            .all(|p| p.message_index == 0));
+    }
+}
@@ -7,7 +7,7 @@ use std::sync::{Arc, Mutex};
 use tracing::info;
 
 /// Current schema version
-const SCHEMA_VERSION: i32 = 12;
+const SCHEMA_VERSION: i32 = 18;
 
 /// Shared database handle for connection reuse
 ///
@@ -530,6 +530,167 @@ impl Database {
             self.set_schema_version_tx(&tx, 12)?;
         }
 
+        // Migration 13: Indexed docs from extension docs providers
+        if current_version < 13 {
+            info!("Running migration 13: Indexed docs");
+            tx.execute_batch(
+                r#"
+                -- Packages that have been indexed, per docs provider
+                CREATE TABLE IF NOT EXISTS docs_packages (
+                    provider TEXT NOT NULL,
+                    package TEXT NOT NULL,
+                    entry_count INTEGER NOT NULL DEFAULT 0,
+                    indexed_at TEXT NOT NULL,
+                    PRIMARY KEY (provider, package)
+                );
+
+                -- Key-value entries written by the extension's index-docs export
+                CREATE TABLE IF NOT EXISTS docs_entries (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    provider TEXT NOT NULL,
+                    package TEXT NOT NULL,
+                    key TEXT NOT NULL,
+                    content TEXT NOT NULL,
+                    UNIQUE (provider, package, key)
+                );
+
+                CREATE INDEX IF NOT EXISTS idx_docs_entries_package ON docs_entries(provider, package);
+
+                -- Full-text index over entries (external content, kept in sync by triggers)
+                CREATE VIRTUAL TABLE IF NOT EXISTS docs_fts USING fts5(
+                    key, content, content='docs_entries', content_rowid='id'
+                );
+
+                CREATE TRIGGER IF NOT EXISTS docs_entries_ai AFTER INSERT ON docs_entries BEGIN
+                    INSERT INTO docs_fts(rowid, key, content) VALUES (new.id, new.key, new.content);
+                END;
+
+                CREATE TRIGGER IF NOT EXISTS docs_entries_ad AFTER DELETE ON docs_entries BEGIN
+                    INSERT INTO docs_fts(docs_fts, rowid, key, content)
+                    VALUES ('delete', old.id, old.key, old.content);
+                END;
+
+                CREATE TRIGGER IF NOT EXISTS docs_entries_au AFTER UPDATE ON docs_entries BEGIN
+                    INSERT INTO docs_fts(docs_fts, rowid, key, content)
+                    VALUES ('delete', old.id, old.key, old.content);
+                    INSERT INTO docs_fts(rowid, key, content) VALUES (new.id, new.key, new.content);
+                END;
+                "#,
+            )?;
+            self.set_schema_version_tx(&tx, 13)?;
+        }
+
+        // Migration 14: Plan revisions
+        if current_version < 14 {
+            info!("Running migration 14: Plan revisions");
+            tx.execute_batch(
+                r#"
+                -- Current revision number of each plan
+                ALTER TABLE plans ADD COLUMN version INTEGER NOT NULL DEFAULT 0;
+
+                -- Every saved revision of a plan, oldest first by version
+                CREATE TABLE IF NOT EXISTS plan_revisions (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    plan_id TEXT NOT NULL,
+                    version INTEGER NOT NULL,
+                    title TEXT NOT NULL,
+                    status TEXT NOT NULL,
+                    content TEXT NOT NULL,
+                    created_at TEXT NOT NULL,
+                    FOREIGN KEY (plan_id) REFERENCES plans(id) ON DELETE CASCADE,
+                    UNIQUE (plan_id, version)
+                );
+
+                CREATE INDEX IF NOT EXISTS idx_plan_revisions_plan ON plan_revisions(plan_id, version);
+                "#,
+            )?;
+            self.set_schema_version_tx(&tx, 14)?;
+        }
+
+        // Migration 15: Sub-agent transcripts
+        if current_version < 15 {
+            info!("Running migration 15: Sub-agent transcripts");
+            tx.execute_batch(
+                r#"
+                -- Every step of explore/build sub-agents, by parent tool call
+                CREATE TABLE IF NOT EXISTS subagent_transcripts (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    session_id TEXT NOT NULL,
+                    tool_use_id TEXT NOT NULL,
+                    task_id TEXT NOT NULL,
+                    agent_name TEXT NOT NULL,
+                    model TEXT NOT NULL,
+                    turn INTEGER NOT NULL,
+                    kind TEXT NOT NULL,
+                    tool_name TEXT,
+                    content TEXT NOT NULL,
+                    is_error INTEGER NOT NULL DEFAULT 0,
+                    created_at TEXT NOT NULL,
+                    FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE CASCADE
+                );
+
+                CREATE INDEX IF NOT EXISTS idx_subagent_transcripts_call
+                    ON subagent_transcripts(session_id, tool_use_id, task_id, id);
+                "#,
+            )?;
+            self.set_schema_version_tx(&tx, 15)?;
+        }
+
+        // Migration 16: Message search
+        if current_version < 16 {
+            info!("Running migration 16: Message search");
+            tx.execute_batch(
+                r#"
+                -- Text of each message, by messages.id (filled in by save_message)
+                CREATE VIRTUAL TABLE IF NOT EXISTS messages_fts USING fts5(
+                    text, tokenize = 'porter unicode61'
+                );
+
+                -- Also covers messages removed by ON DELETE CASCADE
+                CREATE TRIGGER IF NOT EXISTS messages_fts_ad AFTER DELETE ON messages BEGIN
+                    DELETE FROM messages_fts WHERE rowid = old.id;
+                END;
+                "#,
+            )?;
+            super::messages::index_existing_messages(&tx)?;
+            self.set_schema_version_tx(&tx, 16)?;
+        }
+
+        // Migration 17: Conversation branches
+        if current_version < 17 {
+            info!("Running migration 17: Conversation branches");
+            tx.execute_batch(
+                r#"
+                -- Session this one was forked from, and the message it replaced
+                ALTER TABLE sessions ADD COLUMN fork_of TEXT;
+                ALTER TABLE sessions ADD COLUMN fork_index INTEGER;
+
+                CREATE INDEX IF NOT EXISTS idx_sessions_fork ON sessions(fork_of, fork_index);
+                "#,
+            )?;
+            self.set_schema_version_tx(&tx, 17)?;
+        }
+
+        // Migration 18: File snapshots
+        if current_version < 18 {
+            info!("Running migration 18: File snapshots");
+            tx.execute_batch(
+                r#"
+                -- Content of each file before the session first changed it
+                -- (original is NULL if the session created the file)
+                CREATE TABLE IF NOT EXISTS file_snapshots (
+                    session_id TEXT NOT NULL,
+                    file_path TEXT NOT NULL,
+                    original BLOB,
+                    created_at TEXT NOT NULL,
+                    PRIMARY KEY (session_id, file_path),
+                    FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE CASCADE
+                );
+                "#,
+            )?;
+            self.set_schema_version_tx(&tx, 18)?;
+        }
+
         tx.commit()?;
 
         info!("Migrations complete");
@@ -0,0 +1,306 @@
+//! Indexed documentation storage
+//!
+//! Extensions that provide docs (`indexed_docs_providers` in extension.toml)
+//! write entries through a key-value store during `index-docs`. Entries are
+//! stored per provider and package, with an FTS5 index for search.
+
+use anyhow::Result;
+use chrono::Utc;
+use rusqlite::params;
+
+use super::database::Database;
+
+/// An indexed package
+#[derive(Debug, Clone)]
+pub struct DocsPackage {
+    pub provider: String,
+    pub package: String,
+    pub entry_count: usize,
+    pub indexed_at: String,
+}
+
+/// A full-text search hit
+#[derive(Debug, Clone)]
+pub struct DocsSearchHit {
+    pub provider: String,
+    pub package: String,
+    pub key: String,
+    pub snippet: String,
+}
+
+/// SQLite-backed docs storage
+pub struct DocsStore<'a> {
+    db: &'a Database,
+}
+
+impl<'a> DocsStore<'a> {
+    pub fn new(db: &'a Database) -> Self {
+        Self { db }
+    }
+
+    /// Insert or replace a single entry
+    pub fn insert_entry(
+        &self,
+        provider: &str,
+        package: &str,
+        key: &str,
+        content: &str,
+    ) -> Result<()> {
+        // Upsert rather than INSERT OR REPLACE: REPLACE deletes without firing
+        // the delete trigger, which would leave stale rows in docs_fts.
+        self.db.conn().execute(
+            "INSERT INTO docs_entries (provider, package, key, content)
+             VALUES (?1, ?2, ?3, ?4)
+             ON CONFLICT(provider, package, key) DO UPDATE SET content = excluded.content",
+            params![provider, package, key, content],
+        )?;
+        Ok(())
+    }
+
+    /// Remove all entries for a package (before re-indexing)
+    pub fn clear_package(&self, provider: &str, package: &str) -> Result<()> {
+        self.db.conn().execute(
+            "DELETE FROM docs_entries WHERE provider = ?1 AND package = ?2",
+            params![provider, package],
+        )?;
+        self.db.conn().execute(
+            "DELETE FROM docs_packages WHERE provider = ?1 AND package = ?2",
+            params![provider, package],
+        )?;
+        Ok(())
+    }
+
+    /// Replace a package's entries with a freshly indexed set in one
+    /// transaction, so a failed re-index never leaves it half-cleared
+    ///
+    /// Returns the number of entries stored.
+    pub fn replace_package(
+        &self,
+        provider: &str,
+        package: &str,
+        entries: &[(String, String)],
+    ) -> Result<usize> {
+        let tx = self.db.conn().unchecked_transaction()?;
+        self.clear_package(provider, package)?;
+        for (key, content) in entries {
+            self.insert_entry(provider, package, key, content)?;
+        }
+        let count = self.mark_indexed(provider, package)?;
+        tx.commit()?;
+        Ok(count)
+    }
+
+    /// Record that a package finished indexing
+    pub fn mark_indexed(&self, provider: &str, package: &str) -> Result<usize> {
+        let count: usize = self.db.conn().query_row(
+            "SELECT COUNT(*) FROM docs_entries WHERE provider = ?1 AND package = ?2",
+            params![provider, package],
+            |row| row.get(0),
+        )?;
+        self.db.conn().execute(
+            "INSERT INTO docs_packages (provider, package, entry_count, indexed_at)
+             VALUES (?1, ?2, ?3, ?4)
+             ON CONFLICT(provider, package) DO UPDATE SET
+                 entry_count = excluded.entry_count,
+                 indexed_at = excluded.indexed_at",
+            params![provider, package, count, Utc::now().to_rfc3339()],
+        )?;
+        Ok(count)
+    }
+
+    /// List indexed packages, alphabetically
+    pub fn list_packages(&self) -> Result<Vec<DocsPackage>> {
+        let mut stmt = self.db.conn().prepare(
+            "SELECT provider, package, entry_count, indexed_at
+             FROM docs_packages ORDER BY package, provider",
+        )?;
+        let packages = stmt.query_map([], |row| {
+            Ok(DocsPackage {
+                provider: row.get(0)?,
+                package: row.get(1)?,
+                entry_count: row.get(2)?,
+                indexed_at: row.get(3)?,
+            })
+        })?;
+        packages.collect::<Result<Vec<_>, _>>().map_err(Into::into)
+    }
+
+    /// Get the full content of an entry by key
+    ///
+    /// When `package` is None, the first package containing the key wins.
+    pub fn get_entry(&self, key: &str, package: Option<&str>) -> Result<Option<String>> {
+        let result = self.db.conn().query_row(
+            "SELECT content FROM docs_entries
+             WHERE key = ?1 AND (?2 IS NULL OR package = ?2)
+             ORDER BY package LIMIT 1",
+            params![key, package],
+            |row| row.get(0),
+        );
+        match result {
+            Ok(content) => Ok(Some(content)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Full-text search across indexed entries, best matches first
+    pub fn search(
+        &self,
+        query: &str,
+        package: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<DocsSearchHit>> {
+        let Some(fts_query) = fts_query(query) else {
+            return Ok(Vec::new());
+        };
+
+        let mut stmt = self.db.conn().prepare(
+            "SELECT e.provider, e.package, e.key,
+                    snippet(docs_fts, 1, '', '', '…', 24)
+             FROM docs_fts
+             JOIN docs_entries e ON e.id = docs_fts.rowid
+             WHERE docs_fts MATCH ?1 AND (?2 IS NULL OR e.package = ?2)
+             ORDER BY bm25(docs_fts, 10.0, 1.0)
+             LIMIT ?3",
+        )?;
+        let hits = stmt.query_map(params![fts_query, package, limit], |row| {
+            Ok(DocsSearchHit {
+                provider: row.get(0)?,
+                package: row.get(1)?,
+                key: row.get(2)?,
+                snippet: row.get(3)?,
+            })
+        })?;
+        hits.collect::<Result<Vec<_>, _>>().map_err(Into::into)
+    }
+}
+
+/// Turn free text into an FTS5 query
+///
+/// Each word becomes a quoted prefix term so punctuation in queries like
+/// `serde::Deserialize` can't be parsed as FTS5 syntax.
+pub(super) fn fts_query(query: &str) -> Option<String> {
+    let terms: Vec<String> = query
+        .split(|c: char| !c.is_alphanumeric() && c != '_')
+        .filter(|t| !t.is_empty())
+        .map(|t| format!("\"{}\"*", t))
+        .collect();
+    if terms.is_empty() {
+        None
+    } else {
+        Some(terms.join(" "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::*;
+
+    fn create_test_db() -> (Database, TempDir) {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let db_path = temp_dir.path().join("test.db");
+        let db = Database::new(&db_path).expect("Failed to create database");
+        (db, temp_dir)
+    }
+
+    #[test]
+    fn test_fts_query_quotes_terms() {
+        assert_eq!(
+            fts_query("serde::Deserialize").as_deref(),
+            Some("\"serde\"* \"Deserialize\"*")
+        );
+        assert_eq!(fts_query(" :: "), None);
+    }
+
+    #[test]
+    fn test_index_and_search() {
+        let (db, _temp) = create_test_db();
+        let store = DocsStore::new(&db);
+
+        store
+            .insert_entry(
+                "rustdoc",
+                "serde",
+                "serde::Deserialize",
+                "A data structure that can be deserialized",
+            )
+            .unwrap();
+        store
+            .insert_entry(
+                "rustdoc",
+                "serde",
+                "serde::Serialize",
+                "A data structure that can be serialized",
+            )
+            .unwrap();
+        store
+            .insert_entry(
+                "rustdoc",
+                "tokio",
+                "tokio::spawn",
+                "Spawns a new asynchronous task",
+            )
+            .unwrap();
+        assert_eq!(store.mark_indexed("rustdoc", "serde").unwrap(), 2);
+
+        let hits = store.search("deserialize", None, 10).unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].key, "serde::Deserialize");
+
+        let hits = store.search("task", Some("serde"), 10).unwrap();
+        assert!(hits.is_empty());
+
+        let packages = store.list_packages().unwrap();
+        assert_eq!(packages.len(), 1);
+        assert_eq!(packages[0].entry_count, 2);
+    }
+
+    #[test]
+    fn test_reindex_keeps_fts_in_sync() {
+        let (db, _temp) = create_test_db();
+        let store = DocsStore::new(&db);
+
+        store
+            .insert_entry("rustdoc", "serde", "serde::Value", "old text")
+            .unwrap();
+        store
+            .insert_entry("rustdoc", "serde", "serde::Value", "new text")
+            .unwrap();
+        assert!(store.search("old", None, 10).unwrap().is_empty());
+        assert_eq!(store.search("new", None, 10).unwrap().len(), 1);
+
+        store.clear_package("rustdoc", "serde").unwrap();
+        assert!(store.search("new", None, 10).unwrap().is_empty());
+        assert!(store.get_entry("serde::Value", None).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_replace_package_swaps_entries() {
+        let (db, _temp) = create_test_db();
+        let store = DocsStore::new(&db);
+
+        store
+            .insert_entry("rustdoc", "serde", "serde::Old", "removed upstream")
+            .unwrap();
+        store
+            .insert_entry("rustdoc", "tokio", "tokio::spawn", "untouched")
+            .unwrap();
+        let entries = vec![
+            (
+                "serde::Value".to_string(),
+                "any valid JSON value".to_string(),
+            ),
+            ("serde::Map".to_string(), "a JSON object".to_string()),
+        ];
+        assert_eq!(
+            store.replace_package("rustdoc", "serde", &entries).unwrap(),
+            2
+        );
+
+        assert!(store.get_entry("serde::Old", None).unwrap().is_none());
+        assert_eq!(store.search("json", None, 10).unwrap().len(), 2);
+        assert!(store.get_entry("tokio::spawn", None).unwrap().is_some());
+    }
+}
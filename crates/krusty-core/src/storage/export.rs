@@ -0,0 +1,675 @@
+//! Session export and import
+//!
+//! A [`SessionExport`] holds everything needed to rebuild a session
+//! elsewhere: its messages as structured content, block UI state and plan.
+//! It round-trips through JSON (`import` restores it into another database)
+//! and renders to Markdown or standalone HTML for reading and sharing.
+
+use std::collections::HashMap;
+use std::fmt::Write as _;
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use rusqlite::{params, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use similar::TextDiff;
+
+use super::block_ui::BlockUiState;
+use super::database::Database;
+use super::plans::PlanStore;
+use crate::ai::types::Content;
+use crate::plan::PlanFile;
+use crate::secrets::mask_json_secrets;
+
+/// Format version written to JSON exports
+pub const EXPORT_VERSION: u32 = 1;
+
+/// A session with everything needed to restore it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionExport {
+    pub version: u32,
+    pub exported_at: DateTime<Utc>,
+    pub session: ExportedSession,
+    pub messages: Vec<ExportedMessage>,
+    #[serde(default)]
+    pub block_ui: Vec<BlockUiState>,
+    #[serde(default)]
+    pub plan: Option<PlanFile>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportedSession {
+    pub id: String,
+    pub title: String,
+    pub created_at: String,
+    pub updated_at: String,
+    pub model: Option<String>,
+    pub working_dir: Option<String>,
+    pub token_count: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportedMessage {
+    pub role: String,
+    pub content: Vec<Content>,
+    pub created_at: String,
+}
+
+impl SessionExport {
+    /// Mask API keys, tokens and passwords in every message, title and plan
+    pub fn mask_secrets(&mut self) -> Result<()> {
+        let mut value = serde_json::to_value(&*self)?;
+        mask_json_secrets(&mut value);
+        *self = serde_json::from_value(value)?;
+        Ok(())
+    }
+
+    /// Render as Markdown: text as-is, thinking and tool calls folded in
+    /// `<details>`, edits as diffs
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "# {}\n", self.session.title);
+        for (label, value) in self.details() {
+            let _ = writeln!(out, "- **{}:** {}", label, value);
+        }
+
+        let tool_names = self.tool_names();
+        for message in &self.messages {
+            let heading = match message_heading(message) {
+                Some(heading) => heading,
+                None => continue,
+            };
+            let _ = write!(out, "\n---\n\n### {}\n\n", heading);
+            for content in &message.content {
+                match content {
+                    Content::Text { text } if text.trim() != "." => {
+                        let _ = writeln!(out, "{}\n", text.trim_end());
+                    }
+                    Content::Thinking { thinking, .. } => {
+                        let _ = writeln!(
+                            out,
+                            "<details>\n<summary>Thinking</summary>\n\n{}\n\n</details>\n",
+                            thinking.trim_end()
+                        );
+                    }
+                    Content::RedactedThinking { .. } => {
+                        out.push_str("*Thinking redacted*\n\n");
+                    }
+                    Content::Image { .. } => out.push_str("*[image]*\n\n"),
+                    Content::Document { .. } => out.push_str("*[document]*\n\n"),
+                    Content::ToolUse { name, input, .. } => {
+                        let _ = writeln!(
+                            out,
+                            "<details>\n<summary>Tool: <code>{}</code>{}</summary>\n",
+                            escape(name),
+                            escape(&tool_summary(input))
+                        );
+                        out.push_str(&fence(&pretty_json(input), "json"));
+                        out.push_str("\n</details>\n\n");
+                        if let Some(diff) = tool_diff(name, input) {
+                            out.push_str(&fence(&diff, "diff"));
+                            out.push('\n');
+                        }
+                    }
+                    Content::ToolResult {
+                        tool_use_id,
+                        output,
+                        is_error,
+                    } => {
+                        let name = tool_names.get(tool_use_id.as_str()).copied();
+                        let _ = writeln!(
+                            out,
+                            "<details>\n<summary>{} of <code>{}</code></summary>\n",
+                            if is_error == &Some(true) {
+                                "Error"
+                            } else {
+                                "Result"
+                            },
+                            escape(name.unwrap_or("tool"))
+                        );
+                        out.push_str(&fence(&output_text(output), ""));
+                        out.push_str("\n</details>\n\n");
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        if let Some(ref plan) = self.plan {
+            let _ = write!(
+                out,
+                "\n---\n\n## Plan\n\n{}\n",
+                plan.to_markdown().trim_end()
+            );
+        }
+        out
+    }
+
+    /// Render as a standalone HTML page
+    pub fn to_html(&self) -> String {
+        let mut body = String::new();
+        let _ = writeln!(
+            body,
+            "<h1>{}</h1>\n<ul class=\"meta\">",
+            escape(&self.session.title)
+        );
+        for (label, value) in self.details() {
+            let _ = writeln!(
+                body,
+                "<li><b>{}:</b> {}</li>",
+                label,
+                escape(&value.replace('`', ""))
+            );
+        }
+        body.push_str("</ul>\n");
+
+        let tool_names = self.tool_names();
+        for message in &self.messages {
+            let heading = match message_heading(message) {
+                Some(heading) => heading,
+                None => continue,
+            };
+            let _ = writeln!(
+                body,
+                "<section class=\"{}\">\n<h3>{}</h3>",
+                escape(&message.role),
+                heading
+            );
+            for content in &message.content {
+                match content {
+                    Content::Text { text } if text.trim() != "." => {
+                        let _ = writeln!(
+                            body,
+                            "<div class=\"text\">{}</div>",
+                            escape(text.trim_end())
+                        );
+                    }
+                    Content::Thinking { thinking, .. } => {
+                        let _ = writeln!(
+                            body,
+                            "<details class=\"thinking\"><summary>Thinking</summary><div class=\"text\">{}</div></details>",
+                            escape(thinking.trim_end())
+                        );
+                    }
+                    Content::RedactedThinking { .. } => {
+                        body.push_str("<p class=\"note\">Thinking redacted</p>\n");
+                    }
+                    Content::Image { .. } => body.push_str("<p class=\"note\">[image]</p>\n"),
+                    Content::Document { .. } => body.push_str("<p class=\"note\">[document]</p>\n"),
+                    Content::ToolUse { name, input, .. } => {
+                        let _ = writeln!(
+                            body,
+                            "<details class=\"tool\"><summary>Tool: <code>{}</code>{}</summary><pre>{}</pre></details>",
+                            escape(name),
+                            escape(&tool_summary(input)),
+                            escape(&pretty_json(input))
+                        );
+                        if let Some(diff) = tool_diff(name, input) {
+                            body.push_str("<pre class=\"diff\">");
+                            for line in diff.lines() {
+                                let class = match line.chars().next() {
+                                    Some('+') if !line.starts_with("+++") => "add",
+                                    Some('-') if !line.starts_with("---") => "del",
+                                    Some('@') => "hunk",
+                                    _ => "ctx",
+                                };
+                                let _ = writeln!(
+                                    body,
+                                    "<span class=\"{}\">{}</span>",
+                                    class,
+                                    escape(line)
+                                );
+                            }
+                            body.push_str("</pre>\n");
+                        }
+                    }
+                    Content::ToolResult {
+                        tool_use_id,
+                        output,
+                        is_error,
+                    } => {
+                        let name = tool_names.get(tool_use_id.as_str()).copied();
+                        let _ = writeln!(
+                            body,
+                            "<details class=\"result{}\"><summary>{} of <code>{}</code></summary><pre>{}</pre></details>",
+                            if is_error == &Some(true) { " error" } else { "" },
+                            if is_error == &Some(true) { "Error" } else { "Result" },
+                            escape(name.unwrap_or("tool")),
+                            escape(&output_text(output))
+                        );
+                    }
+                    _ => {}
+                }
+            }
+            body.push_str("</section>\n");
+        }
+
+        if let Some(ref plan) = self.plan {
+            let _ = writeln!(
+                body,
+                "<section class=\"plan\">\n<h2>Plan</h2>\n<pre>{}</pre>\n</section>",
+                escape(plan.to_markdown().trim_end())
+            );
+        }
+
+        format!(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>{}</style>\n</head>\n<body>\n{}</body>\n</html>\n",
+            escape(&self.session.title),
+            HTML_STYLE,
+            body
+        )
+    }
+
+    /// Session details listed under the title
+    fn details(&self) -> Vec<(&'static str, String)> {
+        let session = &self.session;
+        let mut details = vec![("Session", format!("`{}`", session.id))];
+        if let Some(ref dir) = session.working_dir {
+            details.push(("Directory", format!("`{}`", dir)));
+        }
+        if let Some(ref model) = session.model {
+            details.push(("Model", model.clone()));
+        }
+        details.push(("Created", session.created_at.clone()));
+        details.push(("Updated", session.updated_at.clone()));
+        details
+    }
+
+    /// Tool names by tool call ID, to label results
+    fn tool_names(&self) -> HashMap<&str, &str> {
+        self.messages
+            .iter()
+            .flat_map(|m| &m.content)
+            .filter_map(|c| match c {
+                Content::ToolUse { id, name, .. } => Some((id.as_str(), name.as_str())),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+const HTML_STYLE: &str = "\
+body{font-family:system-ui,sans-serif;max-width:920px;margin:2em auto;padding:0 1em;color:#222}\
+section{border-top:1px solid #ddd;padding:.5em 0}\
+section.user h3{color:#2457a6}section.assistant h3{color:#7a3fa0}\
+.meta{color:#666}.note{color:#888;font-style:italic}\
+.text{white-space:pre-wrap}\
+pre{background:#f6f8fa;padding:.6em;overflow-x:auto;white-space:pre-wrap}\
+details{margin:.4em 0}summary{cursor:pointer;color:#555}\
+.result.error summary{color:#b00}\
+.diff .add{color:#1a7f37}.diff .del{color:#cf222e}.diff .hunk{color:#8250df}";
+
+/// Heading for a message, or None for one with nothing to show
+fn message_heading(message: &ExportedMessage) -> Option<&'static str> {
+    let has_visible = message.content.iter().any(|c| match c {
+        Content::Text { text } => text.trim() != ".",
+        _ => true,
+    });
+    if !has_visible {
+        return None;
+    }
+    let only_results = message
+        .content
+        .iter()
+        .all(|c| matches!(c, Content::ToolResult { .. }));
+    Some(match message.role.as_str() {
+        _ if only_results => "Tool results",
+        "user" => "User",
+        "assistant" => "Assistant",
+        "system" => "System",
+        _ => "Tool",
+    })
+}
+
+/// Short hint next to a tool name: the file or command it worked on
+fn tool_summary(input: &Value) -> String {
+    ["file_path", "path", "command", "pattern", "query"]
+        .iter()
+        .find_map(|key| input.get(*key).and_then(Value::as_str))
+        .map(|s| {
+            let first = s.lines().next().unwrap_or("");
+            format!(" {}", first)
+        })
+        .unwrap_or_default()
+}
+
+/// Unified diff for file-changing tool calls
+fn tool_diff(name: &str, input: &Value) -> Option<String> {
+    let path = input.get("file_path").and_then(Value::as_str)?;
+    let (old, new) = match name {
+        "edit" => (
+            input.get("old_string").and_then(Value::as_str)?,
+            input.get("new_string").and_then(Value::as_str)?,
+        ),
+        "write" => ("", input.get("content").and_then(Value::as_str)?),
+        _ => return None,
+    };
+    let diff = TextDiff::from_lines(old, new)
+        .unified_diff()
+        .context_radius(3)
+        .header(path, path)
+        .to_string();
+    Some(diff)
+}
+
+fn output_text(output: &Value) -> String {
+    match output {
+        Value::String(s) => s.clone(),
+        other => pretty_json(other),
+    }
+}
+
+fn pretty_json(value: &Value) -> String {
+    serde_json::to_string_pretty(value).unwrap_or_else(|_| value.to_string())
+}
+
+/// Fenced code block longer than any backtick run inside `text`
+fn fence(text: &str, lang: &str) -> String {
+    let longest = text.split(|c| c != '`').map(str::len).max().unwrap_or(0);
+    let ticks = "`".repeat((longest + 1).max(3));
+    format!("{}{}\n{}\n{}\n", ticks, lang, text.trim_end(), ticks)
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Parse stored message content: a content array, a single block, or
+/// plain text from older sessions
+fn parse_content(content_json: &str) -> Vec<Content> {
+    serde_json::from_str::<Vec<Content>>(content_json)
+        .or_else(|_| serde_json::from_str::<Content>(content_json).map(|c| vec![c]))
+        .unwrap_or_else(|_| {
+            vec![Content::Text {
+                text: content_json.to_string(),
+            }]
+        })
+}
+
+/// SQLite-backed session export and import
+pub struct ExportStore<'a> {
+    db: &'a Database,
+}
+
+impl<'a> ExportStore<'a> {
+    pub fn new(db: &'a Database) -> Self {
+        Self { db }
+    }
+
+    /// Gather a session for export
+    pub fn export(&self, session_id: &str) -> Result<SessionExport> {
+        let conn = self.db.conn();
+        let session = conn
+            .query_row(
+                "SELECT id, title, created_at, updated_at, model, working_dir, token_count
+                 FROM sessions WHERE id = ?1",
+                [session_id],
+                |row| {
+                    Ok(ExportedSession {
+                        id: row.get(0)?,
+                        title: row.get(1)?,
+                        created_at: row.get(2)?,
+                        updated_at: row.get(3)?,
+                        model: row.get(4)?,
+                        working_dir: row.get(5)?,
+                        token_count: row.get::<_, Option<i64>>(6)?.map(|n| n as usize),
+                    })
+                },
+            )
+            .optional()?
+            .ok_or_else(|| anyhow!("Session {} not found", session_id))?;
+
+        let mut stmt = conn.prepare(
+            "SELECT role, content, created_at FROM messages
+             WHERE session_id = ?1 ORDER BY id",
+        )?;
+        let messages = stmt
+            .query_map([session_id], |row| {
+                Ok(ExportedMessage {
+                    role: row.get(0)?,
+                    content: parse_content(&row.get::<_, String>(1)?),
+                    created_at: row.get(2)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(SessionExport {
+            version: EXPORT_VERSION,
+            exported_at: Utc::now(),
+            session,
+            messages,
+            block_ui: super::block_ui::BlockUiStore::new(self.db).load_block_ui_states(session_id),
+            plan: PlanStore::new(self.db).get_plan_for_session(session_id)?,
+        })
+    }
+
+    /// Restore an export as a new session and return its ID. The exported
+    /// ID is kept unless this database already has it. `working_dir`
+    /// replaces the exported directory
+    pub fn import(&self, export: &SessionExport, working_dir: Option<&str>) -> Result<String> {
+        if export.version > EXPORT_VERSION {
+            return Err(anyhow!(
+                "Export format {} is newer than this version of krusty supports ({})",
+                export.version,
+                EXPORT_VERSION
+            ));
+        }
+
+        let conn = self.db.conn();
+        let taken = conn
+            .query_row(
+                "SELECT 1 FROM sessions WHERE id = ?1",
+                [&export.session.id],
+                |_| Ok(()),
+            )
+            .optional()?
+            .is_some();
+        let id = if taken {
+            uuid::Uuid::new_v4().to_string()
+        } else {
+            export.session.id.clone()
+        };
+        let session = &export.session;
+
+        let tx = conn.unchecked_transaction()?;
+        tx.execute(
+            "INSERT INTO sessions (id, title, created_at, updated_at, model, working_dir, token_count)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                id,
+                session.title,
+                session.created_at,
+                session.updated_at,
+                session.model,
+                working_dir.or(session.working_dir.as_deref()),
+                session.token_count.map(|n| n as i64),
+            ],
+        )?;
+        for message in &export.messages {
+            let content_json = serde_json::to_string(&message.content)?;
+            tx.execute(
+                "INSERT INTO messages (session_id, role, content, created_at)
+                 VALUES (?1, ?2, ?3, ?4)",
+                params![id, message.role, content_json, message.created_at],
+            )?;
+            super::messages::index_message(&tx, tx.last_insert_rowid(), &content_json)?;
+        }
+        for state in &export.block_ui {
+            tx.execute(
+                "INSERT OR REPLACE INTO block_ui_state
+                     (session_id, block_id, block_type, collapsed, scroll_offset)
+                 VALUES (?1, ?2, '', ?3, ?4)",
+                params![
+                    id,
+                    state.block_id,
+                    state.collapsed as i32,
+                    state.scroll_offset as i32
+                ],
+            )?;
+        }
+        if let Some(ref plan) = export.plan {
+            let mut plan = plan.clone();
+            plan.session_id = Some(id.clone());
+            super::plans::upsert_plan_in(&tx, &id, &plan)?;
+        }
+        tx.commit()?;
+
+        tracing::info!(
+            session_id = %id,
+            messages = export.messages.len(),
+            "Imported session"
+        );
+        Ok(id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::storage::SessionManager;
+
+    fn create_manager() -> (SessionManager, TempDir) {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let db = Database::new(&temp_dir.path().join("test.db")).expect("Failed to create db");
+        (SessionManager::new(db), temp_dir)
+    }
+
+    fn sample_session(sm: &SessionManager) -> String {
+        let id = sm
+            .create_session("Fix login", Some("test-model"), Some("/p"))
+            .unwrap();
+        sm.save_message(
+            &id,
+            "user",
+            r#"[{"type":"text","text":"Please use ANTHROPIC_API_KEY=sk-ant-REDACTED"}]"#,
+        )
+        .unwrap();
+        sm.save_message(
+            &id,
+            "assistant",
+            r#"[{"type":"thinking","thinking":"Look at auth","signature":"s"},
+                {"type":"tool_use","id":"t1","name":"edit","input":{"file_path":"src/auth.rs","old_string":"let a = 1;\n","new_string":"let a = 2;\n"}}]"#,
+        )
+        .unwrap();
+        sm.save_message(
+            &id,
+            "tool",
+            r#"[{"type":"tool_result","tool_use_id":"t1","output":"Edited src/auth.rs"}]"#,
+        )
+        .unwrap();
+        sm.save_block_ui_state(&id, "t1", true, 3).unwrap();
+        id
+    }
+
+    #[test]
+    fn test_export_renders_markdown_and_html() {
+        let (sm, _temp) = create_manager();
+        let id = sample_session(&sm);
+        let mut export = sm.export_session(&id).unwrap();
+        export.mask_secrets().unwrap();
+
+        let md = export.to_markdown();
+        assert!(md.starts_with("# Fix login"));
+        assert!(md.contains("### User"));
+        assert!(md.contains("sk-ant-a...stuv"));
+        assert!(!md.contains("abcdefghijklmnopqrstuv"));
+        assert!(md.contains("<summary>Thinking</summary>"));
+        assert!(md.contains("-let a = 1;\n+let a = 2;"));
+        assert!(md.contains("Result of <code>edit</code>"));
+
+        let html = export.to_html();
+        assert!(html.contains("<span class=\"add\">+let a = 2;</span>"));
+        assert!(html.contains("<h3>Tool results</h3>"));
+    }
+
+    #[test]
+    fn test_markdown_escapes_tool_summaries() {
+        let (sm, _temp) = create_manager();
+        let id = sm.create_session("Tags", None, None).unwrap();
+        sm.save_message(
+            &id,
+            "assistant",
+            r#"[{"type":"tool_use","id":"t1","name":"<b>bash</b>","input":{"command":"echo '</summary><script>x</script>'"}}]"#,
+        )
+        .unwrap();
+        sm.save_message(
+            &id,
+            "tool",
+            r#"[{"type":"tool_result","tool_use_id":"t1","output":"ok"}]"#,
+        )
+        .unwrap();
+
+        let md = sm.export_session(&id).unwrap().to_markdown();
+        assert!(md.contains(
+            "<summary>Tool: <code>&lt;b&gt;bash&lt;/b&gt;</code> echo '&lt;/summary&gt;&lt;script&gt;x&lt;/script&gt;'</summary>"
+        ));
+        assert!(md.contains("Result of <code>&lt;b&gt;bash&lt;/b&gt;</code>"));
+    }
+
+    #[test]
+    fn test_import_round_trip() {
+        let (sm, _temp) = create_manager();
+        let id = sample_session(&sm);
+        let json = serde_json::to_string(&sm.export_session(&id).unwrap()).unwrap();
+        let export: SessionExport = serde_json::from_str(&json).unwrap();
+
+        let (other, _temp2) = create_manager();
+        let imported = other.import_session(&export, None).unwrap();
+        assert_eq!(imported, id);
+        assert_eq!(other.load_session_messages(&id).unwrap().len(), 3);
+        assert_eq!(other.load_block_ui_states(&id).len(), 1);
+        assert_eq!(
+            other
+                .search_messages("please", &Default::default(), 10)
+                .unwrap()
+                .len(),
+            1
+        );
+
+        // A second import into the same database gets a fresh ID
+        let again = other.import_session(&export, Some("/q")).unwrap();
+        assert_ne!(again, id);
+        assert_eq!(
+            other
+                .get_session(&again)
+                .unwrap()
+                .unwrap()
+                .working_dir
+                .as_deref(),
+            Some("/q")
+        );
+    }
+
+    #[test]
+    fn test_import_is_atomic_with_plan() {
+        let (sm, _temp) = create_manager();
+        let id = sample_session(&sm);
+        let mut export = sm.export_session(&id).unwrap();
+        export.plan = Some(PlanFile::new("Fix login"));
+
+        let (other, _temp2) = create_manager();
+        let imported = other.import_session(&export, None).unwrap();
+        let plan = PlanStore::new(other.db())
+            .get_plan_for_session(&imported)
+            .unwrap()
+            .unwrap();
+        assert_eq!(plan.title, "Fix login");
+
+        // A plan that can't be stored rolls back the whole import
+        let (broken, _temp3) = create_manager();
+        broken
+            .db()
+            .conn()
+            .execute_batch("DROP TABLE plan_revisions")
+            .unwrap();
+        assert!(broken.import_session(&export, None).is_err());
+        assert!(broken.get_session(&id).unwrap().is_none());
+        assert!(broken.load_session_messages(&id).unwrap().is_empty());
+    }
+}
@@ -2,12 +2,21 @@
 //!
 //! Tracks read/write/edit operations on files during a session
 //! to determine which files are most important for context preservation.
+//! Files the session changes also keep a snapshot of their content from
+//! before the first change, so the change review can diff and revert them.
+//! Tools report to [`StoredFileChanges`] from every agent loop.
 
-use anyhow::Result;
+use std::io::ErrorKind;
+use std::path::Path;
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 use rusqlite::params;
+use rusqlite::types::ValueRef;
 
-use super::database::Database;
+use super::database::{Database, SharedDatabase};
+use crate::tools::FileChangeSink;
 
 /// File access activity for importance scoring
 #[derive(Debug, Clone)]
@@ -42,6 +51,32 @@ impl FileActivity {
     }
 }
 
+/// Kind of file operation recorded in file_activity
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileAccess {
+    Read,
+    Write,
+    Edit,
+}
+
+impl FileAccess {
+    fn column(self) -> &'static str {
+        match self {
+            Self::Read => "read_count",
+            Self::Write => "write_count",
+            Self::Edit => "edit_count",
+        }
+    }
+}
+
+/// Content of a file before the session first changed it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileSnapshot {
+    pub file_path: String,
+    /// None if the file did not exist yet
+    pub original: Option<Vec<u8>>,
+}
+
 /// A file ranked by importance with reasons
 #[derive(Debug, Clone)]
 pub struct RankedFile {
@@ -88,6 +123,110 @@ impl<'a> FileActivityTracker<'a> {
         Self { db, session_id }
     }
 
+    /// Count one operation on a file
+    pub fn record(&self, file_path: &str, access: FileAccess) -> Result<()> {
+        let column = access.column();
+        self.db.conn().execute(
+            &format!(
+                "INSERT INTO file_activity (session_id, file_path, {column}, last_accessed)
+                 VALUES (?1, ?2, 1, ?3)
+                 ON CONFLICT(session_id, file_path) DO UPDATE SET
+                    {column} = {column} + 1,
+                    last_accessed = excluded.last_accessed"
+            ),
+            params![&self.session_id, file_path, Utc::now().to_rfc3339()],
+        )?;
+        Ok(())
+    }
+
+    /// Keep the content of `path` before the session first changes it
+    ///
+    /// A file that doesn't exist is kept as `None`, so reverting deletes it
+    /// again. A file that exists but can't be read is an error and gets no
+    /// snapshot: the review then lists it without offering to revert it.
+    pub fn snapshot_file(&self, path: &Path) -> Result<()> {
+        let original = match std::fs::read(path) {
+            Ok(bytes) => Some(bytes),
+            Err(e) if e.kind() == ErrorKind::NotFound => None,
+            Err(e) => return Err(anyhow!("Can't read {}: {}", path.display(), e)),
+        };
+        self.snapshot_original(&path.display().to_string(), original.as_deref())
+    }
+
+    /// Keep `original` as the content of a file before it is changed. Only
+    /// the first snapshot of each file is kept
+    pub fn snapshot_original(&self, file_path: &str, original: Option<&[u8]>) -> Result<()> {
+        self.db.conn().execute(
+            "INSERT OR IGNORE INTO file_snapshots (session_id, file_path, original, created_at)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![
+                &self.session_id,
+                file_path,
+                original,
+                Utc::now().to_rfc3339()
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Files changed in the session with their original content, by path
+    pub fn snapshots(&self) -> Result<Vec<FileSnapshot>> {
+        let mut stmt = self.db.conn().prepare(
+            "SELECT file_path, original FROM file_snapshots
+             WHERE session_id = ?1 ORDER BY file_path",
+        )?;
+        let snapshots = stmt.query_map([&self.session_id], |row| {
+            let original = match row.get_ref(1)? {
+                ValueRef::Null => None,
+                ValueRef::Blob(bytes) | ValueRef::Text(bytes) => Some(bytes.to_vec()),
+                other => {
+                    return Err(rusqlite::Error::InvalidColumnType(
+                        1,
+                        "original".to_string(),
+                        other.data_type(),
+                    ))
+                }
+            };
+            Ok(FileSnapshot {
+                file_path: row.get(0)?,
+                original,
+            })
+        })?;
+        snapshots.collect::<Result<Vec<_>, _>>().map_err(Into::into)
+    }
+
+    /// Files written or edited in the session that have no snapshot, because
+    /// they couldn't be read before the change
+    pub fn unsnapshotted_changes(&self) -> Result<Vec<String>> {
+        let mut stmt = self.db.conn().prepare(
+            "SELECT file_path FROM file_activity a
+             WHERE session_id = ?1 AND (write_count > 0 OR edit_count > 0)
+               AND NOT EXISTS (
+                   SELECT 1 FROM file_snapshots s
+                   WHERE s.session_id = a.session_id AND s.file_path = a.file_path
+               )
+             ORDER BY file_path",
+        )?;
+        let paths = stmt.query_map([&self.session_id], |row| row.get(0))?;
+        paths.collect::<Result<Vec<_>, _>>().map_err(Into::into)
+    }
+
+    /// Accept the current content of a file (`None` if it no longer exists)
+    /// as its new baseline
+    pub fn set_baseline(&self, file_path: &str, content: Option<&[u8]>) -> Result<()> {
+        self.db.conn().execute(
+            "INSERT OR REPLACE INTO file_snapshots (session_id, file_path, original, created_at)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![
+                &self.session_id,
+                file_path,
+                content,
+                Utc::now().to_rfc3339()
+            ],
+        )?;
+        Ok(())
+    }
+
     /// Get all file activities for the session
     pub fn get_all_activities(&self) -> Result<Vec<FileActivity>> {
         let mut stmt = self.db.conn().prepare(
@@ -190,3 +329,131 @@ impl<'a> FileActivityTracker<'a> {
             .collect()
     }
 }
+
+/// Sink that stores the files tools touch in a session's file activity
+pub struct StoredFileChanges {
+    db: SharedDatabase,
+    session_id: String,
+}
+
+impl StoredFileChanges {
+    pub fn new(db: SharedDatabase, session_id: String) -> Self {
+        Self { db, session_id }
+    }
+
+    fn with_tracker(&self, f: impl FnOnce(&FileActivityTracker) -> Result<()>) {
+        let Ok(db) = self.db.lock() else {
+            tracing::warn!("File activity database lock poisoned");
+            return;
+        };
+        if let Err(e) = f(&FileActivityTracker::new(&db, self.session_id.clone())) {
+            tracing::warn!(session_id = %self.session_id, "Failed to track file: {}", e);
+        }
+    }
+}
+
+#[async_trait]
+impl FileChangeSink for StoredFileChanges {
+    async fn before_change(&self, path: &Path) {
+        self.with_tracker(|tracker| tracker.snapshot_file(path));
+    }
+
+    async fn record(&self, path: &Path, access: FileAccess) {
+        self.with_tracker(|tracker| tracker.record(&path.display().to_string(), access));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::storage::SessionManager;
+
+    fn create_manager() -> (SessionManager, TempDir) {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let db = Database::new(&temp_dir.path().join("test.db")).expect("Failed to create db");
+        (SessionManager::new(db), temp_dir)
+    }
+
+    #[test]
+    fn test_record_counts_operations() {
+        let (sm, _temp) = create_manager();
+        let id = sm.create_session("Edit files", None, None).unwrap();
+        let tracker = FileActivityTracker::new(sm.db(), id);
+
+        tracker.record("/p/a.rs", FileAccess::Read).unwrap();
+        tracker.record("/p/a.rs", FileAccess::Edit).unwrap();
+        tracker.record("/p/a.rs", FileAccess::Edit).unwrap();
+        tracker.record("/p/b.rs", FileAccess::Write).unwrap();
+
+        let mut activities = tracker.get_all_activities().unwrap();
+        activities.sort_by(|a, b| a.file_path.cmp(&b.file_path));
+        assert_eq!(activities.len(), 2);
+        assert_eq!((activities[0].read_count, activities[0].edit_count), (1, 2));
+        assert_eq!(activities[1].write_count, 1);
+    }
+
+    #[test]
+    fn test_snapshot_keeps_first_original() {
+        let (sm, _temp) = create_manager();
+        let id = sm.create_session("Edit files", None, None).unwrap();
+        let tracker = FileActivityTracker::new(sm.db(), id);
+
+        tracker.snapshot_original("/p/a.rs", Some(b"v1")).unwrap();
+        tracker.snapshot_original("/p/a.rs", Some(b"v2")).unwrap();
+        tracker.snapshot_original("/p/new.rs", None).unwrap();
+        assert_eq!(
+            tracker.snapshots().unwrap(),
+            vec![
+                FileSnapshot {
+                    file_path: "/p/a.rs".into(),
+                    original: Some(b"v1".to_vec()),
+                },
+                FileSnapshot {
+                    file_path: "/p/new.rs".into(),
+                    original: None,
+                },
+            ]
+        );
+
+        tracker.set_baseline("/p/a.rs", Some(b"v3")).unwrap();
+        assert_eq!(
+            tracker.snapshots().unwrap()[0].original.as_deref(),
+            Some(&b"v3"[..])
+        );
+    }
+
+    #[test]
+    fn test_snapshot_file_only_treats_missing_as_new() {
+        let (sm, temp) = create_manager();
+        let id = sm.create_session("Edit files", None, None).unwrap();
+        let tracker = FileActivityTracker::new(sm.db(), id);
+
+        let binary = temp.path().join("logo.bin");
+        std::fs::write(&binary, [0xff, 0xfe, 0x00, 0x80]).unwrap();
+        tracker.snapshot_file(&binary).unwrap();
+        let missing = temp.path().join("new.rs");
+        tracker.snapshot_file(&missing).unwrap();
+
+        // Exists but can't be read as a file: no snapshot rather than "new"
+        let unreadable = temp.path().join("dir");
+        std::fs::create_dir(&unreadable).unwrap();
+        assert!(tracker.snapshot_file(&unreadable).is_err());
+        tracker
+            .record(&unreadable.display().to_string(), FileAccess::Write)
+            .unwrap();
+
+        let snapshots = tracker.snapshots().unwrap();
+        assert_eq!(snapshots.len(), 2);
+        assert_eq!(
+            snapshots[0].original.as_deref(),
+            Some(&[0xff, 0xfe, 0x00, 0x80][..])
+        );
+        assert_eq!(snapshots[1].original, None);
+        assert_eq!(
+            tracker.unsnapshotted_changes().unwrap(),
+            vec![unreadable.display().to_string()]
+        );
+    }
+}
@@ -1,12 +1,66 @@
 //! Message persistence storage
 //!
-//! Handles saving and loading messages for sessions.
+//! Handles saving and loading messages for sessions, and full-text search
+//! over their text (tool calls, tool output and thinking aren't indexed).
 
 use anyhow::Result;
-use chrono::Utc;
-use rusqlite::params;
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection};
 
 use super::database::Database;
+use super::docs::fts_query;
+use crate::ai::types::Content;
+
+/// Marks the start of a match in [`MessageSearchHit::snippet`]
+pub const MATCH_START: char = '\u{2}';
+/// Marks the end of a match in [`MessageSearchHit::snippet`]
+pub const MATCH_END: char = '\u{3}';
+
+/// Narrows a message search
+#[derive(Debug, Clone, Default)]
+pub struct MessageSearchFilter {
+    /// Sessions started in this directory or below it
+    pub working_dir: Option<String>,
+    /// Messages sent at or after this time
+    pub since: Option<DateTime<Utc>>,
+    /// Messages sent before this time
+    pub until: Option<DateTime<Utc>>,
+}
+
+/// A message matching a search
+#[derive(Debug, Clone)]
+pub struct MessageSearchHit {
+    pub session_id: String,
+    pub session_title: String,
+    pub working_dir: Option<String>,
+    /// Position of the message in its session (0 = first)
+    pub message_index: usize,
+    pub role: String,
+    pub created_at: DateTime<Utc>,
+    /// Text around the match, with matches between [`MATCH_START`] and [`MATCH_END`]
+    pub snippet: String,
+}
+
+impl MessageSearchHit {
+    /// Split the snippet into `(text, is_match)` runs for highlighting
+    pub fn snippet_parts(&self) -> Vec<(&str, bool)> {
+        let mut parts = Vec::new();
+        let mut rest = self.snippet.as_str();
+        while let Some(start) = rest.find(MATCH_START) {
+            if start > 0 {
+                parts.push((&rest[..start], false));
+            }
+            let matched = &rest[start + MATCH_START.len_utf8()..];
+            let end = matched.find(MATCH_END).unwrap_or(matched.len());
+            parts.push((&matched[..end], true));
+            rest = matched.get(end + MATCH_END.len_utf8()..).unwrap_or("");
+        }
+        if !rest.is_empty() {
+            parts.push((rest, false));
+        }
+        parts
+    }
+}
 
 /// Message persistence store
 pub struct MessageStore<'a> {
@@ -29,6 +83,11 @@ impl<'a> MessageStore<'a> {
              VALUES (?1, ?2, ?3, ?4)",
             params![session_id, role, content_json, now],
         )?;
+        index_message(
+            self.db.conn(),
+            self.db.conn().last_insert_rowid(),
+            content_json,
+        )?;
 
         // Update session timestamp
         self.db.conn().execute(
@@ -98,6 +157,109 @@ impl<'a> MessageStore<'a> {
             .execute("DELETE FROM messages WHERE session_id = ?1", [session_id])?;
         Ok(())
     }
+
+    /// Full-text search across the text of all sessions, best matches first
+    pub fn search(
+        &self,
+        query: &str,
+        filter: &MessageSearchFilter,
+        limit: usize,
+    ) -> Result<Vec<MessageSearchHit>> {
+        let Some(fts_query) = fts_query(query) else {
+            return Ok(Vec::new());
+        };
+
+        let mut stmt = self.db.conn().prepare(
+            "SELECT m.session_id, s.title, s.working_dir, m.role, m.created_at,
+                    (SELECT COUNT(*) FROM messages p
+                     WHERE p.session_id = m.session_id AND p.id < m.id),
+                    snippet(messages_fts, 0, ?6, ?7, '…', 16)
+             FROM messages_fts
+             JOIN messages m ON m.id = messages_fts.rowid
+             JOIN sessions s ON s.id = m.session_id
+             WHERE messages_fts MATCH ?1
+               AND (?2 IS NULL OR s.working_dir = ?2
+                    OR substr(s.working_dir, 1, length(?2) + 1) = ?2 || '/')
+               AND (?3 IS NULL OR m.created_at >= ?3)
+               AND (?4 IS NULL OR m.created_at < ?4)
+             ORDER BY bm25(messages_fts)
+             LIMIT ?5",
+        )?;
+        let hits = stmt.query_map(
+            params![
+                fts_query,
+                filter
+                    .working_dir
+                    .as_deref()
+                    .map(|d| d.trim_end_matches('/')),
+                filter.since.map(|t| t.to_rfc3339()),
+                filter.until.map(|t| t.to_rfc3339()),
+                limit,
+                MATCH_START.to_string(),
+                MATCH_END.to_string(),
+            ],
+            |row| {
+                let created_at: String = row.get(4)?;
+                let message_index: i64 = row.get(5)?;
+                Ok(MessageSearchHit {
+                    session_id: row.get(0)?,
+                    session_title: row.get(1)?,
+                    working_dir: row.get(2)?,
+                    role: row.get(3)?,
+                    created_at: DateTime::parse_from_rfc3339(&created_at)
+                        .map(|dt| dt.with_timezone(&Utc))
+                        .unwrap_or_else(|_| Utc::now()),
+                    message_index: message_index as usize,
+                    snippet: row.get(6)?,
+                })
+            },
+        )?;
+        hits.collect::<Result<Vec<_>, _>>().map_err(Into::into)
+    }
+}
+
+/// Text of a stored message that search covers: its text blocks, or the
+/// whole content for legacy plain-text messages
+fn searchable_text(content_json: &str) -> String {
+    let content = serde_json::from_str::<Vec<Content>>(content_json)
+        .or_else(|_| serde_json::from_str::<Content>(content_json).map(|c| vec![c]));
+    match content {
+        Ok(content) => content
+            .iter()
+            .filter_map(|c| match c {
+                // "." is filler for role alternation
+                Content::Text { text } if text != "." => Some(text.as_str()),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+        Err(_) => content_json.to_string(),
+    }
+}
+
+/// Add a message's text to the search index
+pub(super) fn index_message(conn: &Connection, message_id: i64, content_json: &str) -> Result<()> {
+    let text = searchable_text(content_json);
+    if !text.trim().is_empty() {
+        conn.execute(
+            "INSERT INTO messages_fts (rowid, text) VALUES (?1, ?2)",
+            params![message_id, text],
+        )?;
+    }
+    Ok(())
+}
+
+/// Index messages saved before search existed (run by the migration)
+pub(super) fn index_existing_messages(conn: &Connection) -> Result<()> {
+    let mut stmt = conn.prepare("SELECT id, content FROM messages ORDER BY id")?;
+    let rows = stmt.query_map([], |row| {
+        Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+    })?;
+    for row in rows {
+        let (id, content_json) = row?;
+        index_message(conn, id, &content_json)?;
+    }
+    Ok(())
 }
 
 #[cfg(test)]
@@ -107,7 +269,7 @@ mod tests {
 
     use crate::storage::Database;
 
-    use super::MessageStore;
+    use super::{MessageSearchFilter, MessageStore};
 
     /// Helper to create a temporary database for testing
     fn create_test_db() -> (Database, TempDir) {
@@ -154,4 +316,75 @@ mod tests {
         assert_eq!(messages[0].0, "user");
         assert_eq!(messages[1].0, "assistant");
     }
+
+    #[test]
+    fn test_search_messages() {
+        let (db, _temp) = create_test_db();
+        let store = MessageStore::new(&db);
+
+        let now = Utc::now().to_rfc3339();
+        for (id, dir) in [("a", "/work/app"), ("b", "/work/application")] {
+            db.conn()
+                .execute(
+                    "INSERT INTO sessions (id, title, created_at, updated_at, working_dir)
+                     VALUES (?1, ?2, ?3, ?4, ?5)",
+                    rusqlite::params![id, format!("Session {id}"), now, now, dir],
+                )
+                .expect("Failed to create session");
+        }
+        store
+            .save_message("a", "user", r#"[{"type":"text","text":"Hello"}]"#)
+            .unwrap();
+        store
+            .save_message(
+                "a",
+                "assistant",
+                r#"[{"type":"text","text":"The migration bug was an off-by-one."},
+                    {"type":"tool_use","id":"t1","name":"read","input":{"path":"migrations.rs"}}]"#,
+            )
+            .unwrap();
+        store
+            .save_message("b", "user", "Fix the migrations, legacy plain text")
+            .unwrap();
+
+        let hits = store
+            .search("migration bug", &MessageSearchFilter::default(), 10)
+            .unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].session_id, "a");
+        assert_eq!(hits[0].message_index, 1);
+        assert!(hits[0].snippet_parts().contains(&("migration", true)));
+
+        // Stemming matches "migrations"; the directory filter is path-aware
+        let all = store
+            .search("migration", &MessageSearchFilter::default(), 10)
+            .unwrap();
+        assert_eq!(all.len(), 2);
+        let filter = MessageSearchFilter {
+            working_dir: Some("/work/app/".to_string()),
+            ..Default::default()
+        };
+        let in_dir = store.search("migration", &filter, 10).unwrap();
+        assert_eq!(in_dir.len(), 1);
+        assert_eq!(in_dir[0].session_id, "a");
+
+        let filter = MessageSearchFilter {
+            since: Some(Utc::now() + chrono::Duration::days(1)),
+            ..Default::default()
+        };
+        assert!(store.search("migration", &filter, 10).unwrap().is_empty());
+        // Tool input isn't indexed
+        assert!(store
+            .search("migrations.rs read", &MessageSearchFilter::default(), 10)
+            .unwrap()
+            .is_empty());
+
+        db.conn()
+            .execute("DELETE FROM sessions WHERE id = 'a'", [])
+            .unwrap();
+        let after_delete = store
+            .search("migration", &MessageSearchFilter::default(), 10)
+            .unwrap();
+        assert_eq!(after_delete.len(), 1);
+    }
 }
@@ -1,33 +1,47 @@
 //! Persistence layer
 //!
 //! SQLite-based storage for:
-//! - Session storage and management
+//! - Session storage and management, with full-text message search
+//! - Conversation branches (forks at an edited message)
+//! - Session export (JSON, Markdown, HTML) and import
 //! - Plan storage with session linkage
 //! - User preferences
 //! - File activity tracking for context
 //! - API credentials
+//! - Indexed docs from extension docs providers
+//! - Sub-agent transcripts
 
 use std::time::{SystemTime, UNIX_EPOCH};
 
 mod agent_state;
 mod block_ui;
+mod branches;
 pub mod credentials;
 mod database;
+mod docs;
+mod export;
 mod file_activity;
 mod messages;
 mod plans;
 mod preferences;
 mod sessions;
+mod transcripts;
 
 pub use agent_state::AgentState;
 pub use block_ui::BlockUiState;
+pub use branches::{BranchSiblings, BranchStore};
 pub use credentials::CredentialStore;
 pub use database::{Database, SharedDatabase};
-pub use file_activity::{FileActivityTracker, RankedFile};
-pub use messages::MessageStore;
-pub use plans::{PlanStore, PlanSummary};
+pub use docs::{DocsPackage, DocsSearchHit, DocsStore};
+pub use export::{ExportStore, ExportedMessage, ExportedSession, SessionExport, EXPORT_VERSION};
+pub use file_activity::{
+    FileAccess, FileActivityTracker, FileSnapshot, RankedFile, StoredFileChanges,
+};
+pub use messages::{MessageSearchFilter, MessageSearchHit, MessageStore, MATCH_END, MATCH_START};
+pub use plans::{PlanRevision, PlanStore, PlanSummary};
 pub use preferences::Preferences;
 pub use sessions::{SessionInfo, SessionManager};
+pub use transcripts::{StoredTranscriptSink, TranscriptStore};
 
 /// Get current Unix timestamp in seconds
 #[inline]
@@ -4,10 +4,11 @@
 //! - 1:1 session-plan relationship (enforced by UNIQUE constraint)
 //! - Automatic plan deletion on session delete (CASCADE)
 //! - CRUD operations for plans
+//! - Revision history (`plan_revisions`), one row per changed save
 
 use anyhow::Result;
 use chrono::{DateTime, Utc};
-use rusqlite::params;
+use rusqlite::{params, Connection, OptionalExtension};
 
 use super::database::Database;
 use crate::plan::{PlanFile, PlanStatus};
@@ -25,46 +26,20 @@ impl<'a> PlanStore<'a> {
 
     /// Create or update plan for a session
     ///
-    /// If session already has a plan, it will be replaced.
+    /// If session already has a plan, it will be replaced. A save that
+    /// changes the plan is kept as a new revision and bumps its version.
     /// Returns the plan ID.
     pub fn upsert_plan(&self, session_id: &str, plan: &PlanFile) -> Result<String> {
-        let now = Utc::now().to_rfc3339();
-        let plan_id = uuid::Uuid::new_v4().to_string();
-        let content = plan.to_markdown();
-        let status = plan.status.to_string();
-
-        // Use INSERT OR REPLACE to handle existing plans
-        self.db.conn().execute(
-            "INSERT OR REPLACE INTO plans (id, session_id, title, status, content, created_at, updated_at)
-             VALUES (
-                 COALESCE((SELECT id FROM plans WHERE session_id = ?1), ?2),
-                 ?1, ?3, ?4, ?5,
-                 COALESCE((SELECT created_at FROM plans WHERE session_id = ?1), ?6),
-                 ?6
-             )",
-            params![session_id, plan_id, plan.title, status, content, now],
-        )?;
-
-        // Get the actual plan ID (either new or existing)
-        let actual_id: String = self.db.conn().query_row(
-            "SELECT id FROM plans WHERE session_id = ?1",
-            [session_id],
-            |row| row.get(0),
-        )?;
-
-        tracing::info!(
-            session_id = %session_id,
-            plan_id = %actual_id,
-            "Upserted plan"
-        );
-
-        Ok(actual_id)
+        let tx = self.db.conn().unchecked_transaction()?;
+        let plan_id = upsert_plan_in(&tx, session_id, plan)?;
+        tx.commit()?;
+        Ok(plan_id)
     }
 
     /// Get plan for a session
     pub fn get_plan_for_session(&self, session_id: &str) -> Result<Option<PlanFile>> {
         let result = self.db.conn().query_row(
-            "SELECT title, status, content, created_at
+            "SELECT title, status, content, created_at, version
              FROM plans WHERE session_id = ?1",
             [session_id],
             |row| {
@@ -73,6 +48,7 @@ impl<'a> PlanStore<'a> {
                     status: row.get(1)?,
                     content: row.get(2)?,
                     created_at: row.get(3)?,
+                    version: row.get(4)?,
                 })
             },
         );
@@ -85,6 +61,7 @@ impl<'a> PlanStore<'a> {
                 // Override with DB values (in case markdown parsing has different values)
                 plan.title = row.title;
                 plan.session_id = Some(session_id.to_string());
+                plan.version = row.version;
 
                 if let Ok(status) = row.status.parse::<PlanStatus>() {
                     plan.status = status;
@@ -136,6 +113,19 @@ impl<'a> PlanStore<'a> {
             .is_ok()
     }
 
+    /// Current version of a session's plan, without loading it
+    pub fn plan_version(&self, session_id: &str) -> Result<Option<u64>> {
+        self.db
+            .conn()
+            .query_row(
+                "SELECT version FROM plans WHERE session_id = ?1",
+                [session_id],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(Into::into)
+    }
+
     /// Update plan status
     pub fn update_status(&self, session_id: &str, status: PlanStatus) -> Result<()> {
         let now = Utc::now().to_rfc3339();
@@ -147,17 +137,44 @@ impl<'a> PlanStore<'a> {
     }
 
     /// Update plan content (full markdown)
+    ///
+    /// Does nothing if the session has no plan. Changes are kept as a new
+    /// revision, like `upsert_plan`.
     pub fn update_content(&self, session_id: &str, plan: &PlanFile) -> Result<()> {
-        let now = Utc::now().to_rfc3339();
-        let content = plan.to_markdown();
-        let status = plan.status.to_string();
+        if self.has_plan(session_id) {
+            self.upsert_plan(session_id, plan)?;
+        }
+        Ok(())
+    }
 
-        self.db.conn().execute(
-            "UPDATE plans SET title = ?1, status = ?2, content = ?3, updated_at = ?4
-             WHERE session_id = ?5",
-            params![plan.title, status, content, now, session_id],
+    /// List revisions of a session's plan, newest first
+    pub fn list_revisions(&self, session_id: &str) -> Result<Vec<PlanRevision>> {
+        let mut stmt = self.db.conn().prepare(
+            "SELECT r.version, r.title, r.status, r.content, r.created_at
+             FROM plan_revisions r
+             JOIN plans p ON r.plan_id = p.id
+             WHERE p.session_id = ?1
+             ORDER BY r.version DESC",
         )?;
-        Ok(())
+
+        let revisions = stmt.query_map([session_id], PlanRevision::from_row)?;
+        revisions.collect::<Result<Vec<_>, _>>().map_err(Into::into)
+    }
+
+    /// Get one revision of a session's plan
+    pub fn get_revision(&self, session_id: &str, version: u64) -> Result<Option<PlanRevision>> {
+        self.db
+            .conn()
+            .query_row(
+                "SELECT r.version, r.title, r.status, r.content, r.created_at
+                 FROM plan_revisions r
+                 JOIN plans p ON r.plan_id = p.id
+                 WHERE p.session_id = ?1 AND r.version = ?2",
+                params![session_id, version],
+                PlanRevision::from_row,
+            )
+            .optional()
+            .map_err(Into::into)
     }
 
     /// List all plans (for migration/debugging)
@@ -187,12 +204,127 @@ impl<'a> PlanStore<'a> {
     }
 }
 
+/// Upsert a session's plan on `conn`, which callers run inside a
+/// transaction (see [`PlanStore::upsert_plan`])
+pub(super) fn upsert_plan_in(
+    conn: &Connection,
+    session_id: &str,
+    plan: &PlanFile,
+) -> Result<String> {
+    let now = Utc::now().to_rfc3339();
+    let existing: Option<(String, u64)> = conn
+        .query_row(
+            "SELECT id, version FROM plans WHERE session_id = ?1",
+            [session_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()?;
+
+    // Revisions are stored without the version line so identical plans
+    // compare equal and diffs only show real changes
+    let body = revision_body(plan);
+    let latest: Option<String> = match &existing {
+        Some((plan_id, _)) => conn
+            .query_row(
+                "SELECT content FROM plan_revisions WHERE plan_id = ?1
+                 ORDER BY version DESC LIMIT 1",
+                [plan_id],
+                |row| row.get(0),
+            )
+            .optional()?,
+        None => None,
+    };
+    let current_version = existing.as_ref().map(|(_, v)| *v).unwrap_or(0);
+    let changed = latest.as_deref() != Some(body.as_str());
+    let version = if changed {
+        current_version + 1
+    } else {
+        current_version
+    };
+
+    let mut stored = plan.clone();
+    stored.version = version;
+    let content = stored.to_markdown();
+    let status = plan.status.to_string();
+
+    let plan_id = match existing {
+        Some((plan_id, _)) => {
+            conn.execute(
+                "UPDATE plans SET title = ?1, status = ?2, content = ?3, version = ?4, updated_at = ?5
+                 WHERE id = ?6",
+                params![plan.title, status, content, version, now, plan_id],
+            )?;
+            plan_id
+        }
+        None => {
+            let plan_id = uuid::Uuid::new_v4().to_string();
+            conn.execute(
+                "INSERT INTO plans (id, session_id, title, status, content, version, created_at, updated_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?7)",
+                params![plan_id, session_id, plan.title, status, content, version, now],
+            )?;
+            plan_id
+        }
+    };
+
+    if changed {
+        conn.execute(
+            "INSERT INTO plan_revisions (plan_id, version, title, status, content, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![plan_id, version, plan.title, status, body, now],
+        )?;
+    }
+
+    tracing::info!(
+        session_id = %session_id,
+        plan_id = %plan_id,
+        version,
+        "Upserted plan"
+    );
+
+    Ok(plan_id)
+}
+
 /// Internal row type for plan queries
 struct PlanRow {
     title: String,
     status: String,
     content: String,
     created_at: String,
+    version: u64,
+}
+
+/// Plan markdown as stored in a revision (without the version line)
+fn revision_body(plan: &PlanFile) -> String {
+    let mut plan = plan.clone();
+    plan.version = 0;
+    plan.to_markdown()
+}
+
+/// One saved revision of a plan
+#[derive(Debug, Clone)]
+pub struct PlanRevision {
+    pub version: u64,
+    pub title: String,
+    pub status: PlanStatus,
+    /// Plan markdown at this revision
+    pub content: String,
+    pub created_at: String,
+}
+
+impl PlanRevision {
+    fn from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<Self> {
+        Ok(Self {
+            version: row.get(0)?,
+            title: row.get(1)?,
+            status: row
+                .get::<_, String>(2)?
+                .parse()
+                .unwrap_or(PlanStatus::InProgress),
+            content: row.get(3)?,
+            created_at: row.get(4)?,
+        })
+    }
 }
 
 /// Summary of a plan for listing
@@ -4,6 +4,7 @@ use anyhow::Result;
 use rusqlite::params;
 
 use crate::ai::models::ModelMetadata;
+use crate::ai::routing::ModelRouting;
 use crate::tools::git_identity::GitIdentity;
 
 use super::{database::Database, unix_timestamp};
@@ -94,6 +95,28 @@ impl Preferences {
         self.set("theme", theme)
     }
 
+    /// Get prompt editing mode (defaults to "standard")
+    pub fn get_edit_mode(&self) -> String {
+        self.get("edit_mode")
+            .unwrap_or_else(|| "standard".to_string())
+    }
+
+    /// Save prompt editing mode
+    pub fn set_edit_mode(&self, mode: &str) -> Result<()> {
+        self.set("edit_mode", mode)
+    }
+
+    /// Get delivery for prompts queued while the agent works (defaults to "next")
+    pub fn get_queue_delivery(&self) -> String {
+        self.get("queue_delivery")
+            .unwrap_or_else(|| "next".to_string())
+    }
+
+    /// Save delivery for queued prompts
+    pub fn set_queue_delivery(&self, delivery: &str) -> Result<()> {
+        self.set("queue_delivery", delivery)
+    }
+
     /// Get recently used model IDs
     pub fn get_recent_models(&self) -> Vec<String> {
         self.get("recent_models")
@@ -178,4 +201,31 @@ impl Preferences {
         let json = serde_json::to_string(identity)?;
         self.set("git_identity", &json)
     }
+
+    /// Get per-role model routes (defaults to every role on the main model)
+    pub fn get_model_routing(&self) -> ModelRouting {
+        self.get("model_routing")
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default()
+    }
+
+    /// Save per-role model routes
+    pub fn set_model_routing(&self, routing: &ModelRouting) -> Result<()> {
+        let json = serde_json::to_string(routing)?;
+        self.set("model_routing", &json)
+    }
+
+    /// Get the repo file the plan is synced with for a working directory
+    pub fn get_plan_sync_path(&self, working_dir: &str) -> Option<String> {
+        self.get(&format!("plan_sync:{}", working_dir))
+    }
+
+    /// Enable plan file sync for a working directory, or disable it with `None`
+    pub fn set_plan_sync_path(&self, working_dir: &str, path: Option<&str>) -> Result<()> {
+        let key = format!("plan_sync:{}", working_dir);
+        match path {
+            Some(path) => self.set(&key, path),
+            None => self.delete(&key),
+        }
+    }
 }
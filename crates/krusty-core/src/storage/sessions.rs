@@ -305,6 +305,12 @@ impl SessionManager {
             params![session_id],
         )?;
 
+        // Clear file_snapshots for this session
+        self.db.conn().execute(
+            "DELETE FROM file_snapshots WHERE session_id = ?1",
+            params![session_id],
+        )?;
+
         // Clear block_ui_state for this session
         self.db.conn().execute(
             "DELETE FROM block_ui_state WHERE session_id = ?1",
@@ -332,6 +338,16 @@ impl SessionManager {
         super::messages::MessageStore::new(&self.db).load_session_messages(session_id)
     }
 
+    /// Full-text search across messages of all sessions, best matches first
+    pub fn search_messages(
+        &self,
+        query: &str,
+        filter: &super::messages::MessageSearchFilter,
+        limit: usize,
+    ) -> Result<Vec<super::messages::MessageSearchHit>> {
+        super::messages::MessageStore::new(&self.db).search(query, filter, limit)
+    }
+
     /// Generate a title from the first message content
     /// Truncates at word boundaries for cleaner display
     /// Uses char-based indexing for UTF-8 safety
@@ -438,6 +454,52 @@ impl SessionManager {
         Ok(id)
     }
 
+    // =========================================================================
+    // Branches
+    // =========================================================================
+
+    /// Fork a session before `at_message`, copying the messages before it
+    pub fn fork_session(
+        &self,
+        session_id: &str,
+        at_message: usize,
+        model: Option<&str>,
+    ) -> Result<String> {
+        super::branches::BranchStore::new(&self.db).fork_session(session_id, at_message, model)
+    }
+
+    /// The sibling versions of a message, as seen from `session_id`
+    pub fn branch_siblings(
+        &self,
+        session_id: &str,
+        message_index: usize,
+    ) -> Result<super::branches::BranchSiblings> {
+        super::branches::BranchStore::new(&self.db).siblings(session_id, message_index)
+    }
+
+    /// Every message of a session that has more than one version
+    pub fn branch_points(&self, session_id: &str) -> Result<Vec<super::branches::BranchSiblings>> {
+        super::branches::BranchStore::new(&self.db).branch_points(session_id)
+    }
+
+    // =========================================================================
+    // Export and Import
+    // =========================================================================
+
+    /// Gather a session with its messages, block UI state and plan
+    pub fn export_session(&self, session_id: &str) -> Result<super::export::SessionExport> {
+        super::export::ExportStore::new(&self.db).export(session_id)
+    }
+
+    /// Restore an exported session, optionally into another working directory
+    pub fn import_session(
+        &self,
+        export: &super::export::SessionExport,
+        working_dir: Option<&str>,
+    ) -> Result<String> {
+        super::export::ExportStore::new(&self.db).import(export, working_dir)
+    }
+
     // =========================================================================
     // Agent State Tracking (for background execution)
     // =========================================================================
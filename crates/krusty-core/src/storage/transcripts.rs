@@ -0,0 +1,195 @@
+//! Sub-agent transcript storage
+//!
+//! Explore and build sub-agents record every step (see
+//! `agent::subagent::TranscriptSink`). Steps are stored under the session and
+//! the tool call that started the agents, and are deleted with the session.
+
+use anyhow::Result;
+use chrono::Utc;
+use rusqlite::params;
+
+use super::database::{Database, SharedDatabase};
+use crate::agent::subagent::{TranscriptEntry, TranscriptKind, TranscriptSink};
+
+/// SQLite-backed sub-agent transcript storage
+pub struct TranscriptStore<'a> {
+    db: &'a Database,
+}
+
+impl<'a> TranscriptStore<'a> {
+    /// Create a new transcript store with database reference
+    pub fn new(db: &'a Database) -> Self {
+        Self { db }
+    }
+
+    /// Append one step of an agent started by `tool_use_id`
+    pub fn append(
+        &self,
+        session_id: &str,
+        tool_use_id: &str,
+        entry: &TranscriptEntry,
+    ) -> Result<()> {
+        self.db.conn().execute(
+            "INSERT INTO subagent_transcripts
+                (session_id, tool_use_id, task_id, agent_name, model, turn, kind, tool_name,
+                 content, is_error, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+            params![
+                session_id,
+                tool_use_id,
+                entry.task_id,
+                entry.agent_name,
+                entry.model,
+                entry.turn as i64,
+                entry.kind.as_str(),
+                entry.tool_name,
+                entry.content,
+                entry.is_error,
+                Utc::now().to_rfc3339(),
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// All steps of one agent, in the order they happened
+    pub fn load(
+        &self,
+        session_id: &str,
+        tool_use_id: &str,
+        task_id: &str,
+    ) -> Result<Vec<TranscriptEntry>> {
+        let mut stmt = self.db.conn().prepare(
+            "SELECT task_id, agent_name, model, turn, kind, tool_name, content, is_error
+             FROM subagent_transcripts
+             WHERE session_id = ?1 AND tool_use_id = ?2 AND task_id = ?3
+             ORDER BY id",
+        )?;
+        let rows = stmt.query_map(params![session_id, tool_use_id, task_id], |row| {
+            let kind: String = row.get(4)?;
+            Ok((
+                TranscriptEntry {
+                    task_id: row.get(0)?,
+                    agent_name: row.get(1)?,
+                    model: row.get(2)?,
+                    turn: row.get::<_, i64>(3)? as usize,
+                    kind: TranscriptKind::Text,
+                    tool_name: row.get(5)?,
+                    content: row.get(6)?,
+                    is_error: row.get(7)?,
+                },
+                kind,
+            ))
+        })?;
+
+        let mut entries = Vec::new();
+        for row in rows {
+            let (mut entry, kind) = row?;
+            let Some(kind) = TranscriptKind::parse(&kind) else {
+                tracing::warn!(kind = %kind, "Skipping transcript entry of unknown kind");
+                continue;
+            };
+            entry.kind = kind;
+            entries.push(entry);
+        }
+        Ok(entries)
+    }
+}
+
+/// Transcript sink that writes to the database as agents run
+pub struct StoredTranscriptSink {
+    db: SharedDatabase,
+    session_id: String,
+    tool_use_id: String,
+}
+
+impl StoredTranscriptSink {
+    /// Store transcripts of agents started by `tool_use_id` in `session_id`
+    pub fn new(db: SharedDatabase, session_id: String, tool_use_id: String) -> Self {
+        Self {
+            db,
+            session_id,
+            tool_use_id,
+        }
+    }
+}
+
+impl TranscriptSink for StoredTranscriptSink {
+    fn record(&self, entry: TranscriptEntry) {
+        let Ok(db) = self.db.lock() else {
+            tracing::warn!("Transcript database lock poisoned");
+            return;
+        };
+        if let Err(e) =
+            TranscriptStore::new(&db).append(&self.session_id, &self.tool_use_id, &entry)
+        {
+            tracing::warn!(task_id = %entry.task_id, "Failed to store transcript entry: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::*;
+
+    fn entry(task_id: &str, turn: usize, kind: TranscriptKind, content: &str) -> TranscriptEntry {
+        TranscriptEntry {
+            task_id: task_id.to_string(),
+            agent_name: "builder".to_string(),
+            model: "test-model".to_string(),
+            turn,
+            kind,
+            tool_name: (kind == TranscriptKind::ToolCall).then(|| "edit".to_string()),
+            content: content.to_string(),
+            is_error: false,
+        }
+    }
+
+    #[test]
+    fn test_append_and_load_per_agent() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let db = Database::shared(&temp_dir.path().join("test.db")).unwrap();
+        let now = Utc::now().to_rfc3339();
+        db.lock()
+            .unwrap()
+            .conn()
+            .execute(
+                "INSERT INTO sessions (id, title, created_at, updated_at)
+                 VALUES ('s1', 'Test', ?1, ?1)",
+                [now],
+            )
+            .unwrap();
+
+        let sink = StoredTranscriptSink::new(db.clone(), "s1".to_string(), "call-1".to_string());
+        sink.record(entry("a", 0, TranscriptKind::Prompt, "Fix the bug"));
+        sink.record(entry("b", 0, TranscriptKind::Prompt, "Other agent"));
+        sink.record(entry("a", 1, TranscriptKind::ToolCall, r#"{"file":"x"}"#));
+        sink.record(entry(
+            "a",
+            1,
+            TranscriptKind::Outcome,
+            "Completed in 1 turns",
+        ));
+
+        let db = db.lock().unwrap();
+        let store = TranscriptStore::new(&db);
+        let steps = store.load("s1", "call-1", "a").unwrap();
+        assert_eq!(
+            steps.iter().map(|e| e.kind).collect::<Vec<_>>(),
+            vec![
+                TranscriptKind::Prompt,
+                TranscriptKind::ToolCall,
+                TranscriptKind::Outcome
+            ]
+        );
+        assert_eq!(steps[1].tool_name.as_deref(), Some("edit"));
+        assert!(store.load("s1", "call-2", "a").unwrap().is_empty());
+
+        // Transcripts go with their session
+        db.conn()
+            .execute("DELETE FROM sessions WHERE id = 's1'", [])
+            .unwrap();
+        assert!(store.load("s1", "call-1", "b").unwrap().is_empty());
+    }
+}
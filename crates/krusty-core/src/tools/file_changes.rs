@@ -0,0 +1,101 @@
+//! File change tracking for tool calls
+//!
+//! Every agent loop (the main one, sub-agents and ACP) reports the files its
+//! `read`, `write` and `edit` calls touch to a [`FileChangeSink`]. Before a
+//! file is first changed, the sink keeps its content so the session's changes
+//! can be reviewed and reverted later. Files merged back from builder
+//! worktrees are reported the same way. Files changed by `bash` commands are
+//! not tracked: a command's effects can't be known before it runs.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde_json::Value;
+
+use crate::storage::FileAccess;
+
+/// Receives the files tools touch
+///
+/// Called from the tool's task, so implementations must not fail the call;
+/// errors are theirs to log.
+#[async_trait]
+pub trait FileChangeSink: Send + Sync {
+    /// `path` is about to be written or edited
+    async fn before_change(&self, path: &Path);
+
+    /// A tool call on `path` succeeded
+    async fn record(&self, path: &Path, access: FileAccess);
+}
+
+/// Shared sink handed to tool contexts and sub-agent pools
+pub type SharedFileChangeSink = Arc<dyn FileChangeSink>;
+
+/// A tool call on a file, reported to the sink once it finishes
+pub(crate) struct TrackedAccess {
+    sink: SharedFileChangeSink,
+    path: PathBuf,
+    access: FileAccess,
+}
+
+/// File operation a tool call performs, with the absolute path it targets
+pub(crate) fn file_access(
+    name: &str,
+    params: &Value,
+    working_dir: &Path,
+) -> Option<(FileAccess, PathBuf)> {
+    let access = match name {
+        "read" => FileAccess::Read,
+        "write" => FileAccess::Write,
+        "edit" => FileAccess::Edit,
+        _ => return None,
+    };
+    let path = Path::new(params.get("file_path")?.as_str()?);
+    Some((access, working_dir.join(path)))
+}
+
+/// Call before running a tool. Keeps the original of files about to change
+pub(crate) async fn before_tool(
+    sink: Option<&SharedFileChangeSink>,
+    name: &str,
+    params: &Value,
+    working_dir: &Path,
+) -> Option<TrackedAccess> {
+    let sink = sink?;
+    let (access, path) = file_access(name, params, working_dir)?;
+    if access != FileAccess::Read {
+        sink.before_change(&path).await;
+    }
+    Some(TrackedAccess {
+        sink: sink.clone(),
+        path,
+        access,
+    })
+}
+
+/// Call after the tool ran; only successful calls are counted
+pub(crate) async fn after_tool(tracked: Option<TrackedAccess>, is_error: bool) {
+    if let (Some(tracked), false) = (tracked, is_error) {
+        tracked.sink.record(&tracked.path, tracked.access).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_file_access_resolves_paths() {
+        let dir = Path::new("/project");
+        assert_eq!(
+            file_access("edit", &json!({"file_path": "src/main.rs"}), dir),
+            Some((FileAccess::Edit, PathBuf::from("/project/src/main.rs")))
+        );
+        assert_eq!(
+            file_access("write", &json!({"file_path": "/tmp/out.txt"}), dir),
+            Some((FileAccess::Write, PathBuf::from("/tmp/out.txt")))
+        );
+        assert_eq!(file_access("bash", &json!({"command": "ls"}), dir), None);
+    }
+}
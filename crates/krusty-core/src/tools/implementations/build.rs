@@ -2,13 +2,17 @@
 //!
 //! This tool spawns a team of Opus agents that work together to build code.
 //! Builders coordinate via SharedBuildContext to share types, modules, and file locks.
+//! With `isolation: "worktree"`, each builder gets its own git worktree instead
+//! and the results are merged back (or left on branches) when the swarm ends.
 
 use async_trait::async_trait;
 use serde::Deserialize;
 use serde_json::{json, Value};
+use std::collections::HashSet;
 use std::sync::Arc;
 use tracing::{debug, info, warn};
 
+use crate::agent::build_isolation::{BuilderOutcome, BuilderReport, WorktreeSwarm};
 use crate::agent::subagent::{SubAgentPool, SubAgentTask};
 use crate::agent::{AgentCancellation, SharedBuildContext};
 use crate::ai::client::AiClient;
@@ -18,6 +22,8 @@ use crate::tools::registry::{Tool, ToolContext, ToolResult};
 pub struct BuildTool {
     client: Arc<AiClient>,
     cancellation: AgentCancellation,
+    /// Model routed to this role; `None` follows the session's current model
+    model: Option<String>,
 }
 
 impl BuildTool {
@@ -25,8 +31,15 @@ impl BuildTool {
         Self {
             client,
             cancellation,
+            model: None,
         }
     }
+
+    /// Run sub-agents on a routed model (`client` must be built for it)
+    pub fn with_model(mut self, model: Option<String>) -> Self {
+        self.model = model;
+        self
+    }
 }
 
 #[derive(Deserialize)]
@@ -50,6 +63,28 @@ struct Params {
     /// Index i maps to components[i]
     #[serde(default)]
     task_ids: Option<Vec<String>>,
+
+    /// How builders share the working tree; unknown values are rejected
+    #[serde(default)]
+    isolation: Option<Isolation>,
+
+    /// Worktree mode: apply results to the working tree (default) or keep them on branches
+    #[serde(default = "default_merge_back")]
+    merge_back: bool,
+}
+
+/// Accepted `isolation` values
+#[derive(Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "lowercase")]
+enum Isolation {
+    /// One working tree with file locks (the default)
+    Shared,
+    /// One git worktree per builder
+    Worktree,
+}
+
+fn default_merge_back() -> bool {
+    true
 }
 
 #[async_trait]
@@ -68,7 +103,9 @@ impl Tool for BuildTool {
          2-3 for tightly coupled components (shared files), \
          5-10 for independent components (separate files). \
          Default: matches component count (natural parallelism). \
-         Builders coordinate via file locking - more concurrency is fine if components don't share files."
+         Builders coordinate via file locking - more concurrency is fine if components don't share files. \
+         In a git repo, isolation='worktree' gives each builder its own worktree and merges the results \
+         back at the end; conflicting builders are reported and their changes left on a branch."
     }
 
     fn parameters_schema(&self) -> Value {
@@ -94,6 +131,15 @@ impl Tool for BuildTool {
                     "description": "Max parallel builders. Default: component count. Use 2-3 for tightly coupled code (shared files), 5-10 for independent modules.",
                     "minimum": 1,
                     "maximum": 20
+                },
+                "isolation": {
+                    "type": "string",
+                    "enum": ["shared", "worktree"],
+                    "description": "shared (default): builders edit the working tree directly, coordinated by file locks. worktree: each builder works in its own git worktree; use for risky or overlapping changes."
+                },
+                "merge_back": {
+                    "type": "boolean",
+                    "description": "Worktree mode only. true (default): apply each builder's changes to the working tree. false: leave them on scratch branches and report per-builder diffs."
                 }
             },
             "required": ["prompt"],
@@ -196,6 +242,28 @@ impl Tool for BuildTool {
             );
         }
 
+        // Worktree isolation: point each builder at its own worktree
+        let isolated = params.isolation == Some(Isolation::Worktree);
+        let swarm = if isolated {
+            let builder_ids: Vec<String> = tasks.iter().map(|t| t.id.clone()).collect();
+            match WorktreeSwarm::create(&ctx.working_dir, &builder_ids).await {
+                Ok(swarm) => {
+                    for task in &mut tasks {
+                        if let Some(worktree) = swarm.worktree(&task.id) {
+                            task.working_dir = worktree.working_dir.clone();
+                            task.sandbox_root = Some(worktree.path.clone());
+                        }
+                    }
+                    Some(swarm)
+                }
+                Err(e) => {
+                    return ToolResult::error(format!("Cannot isolate builders: {}", e));
+                }
+            }
+        } else {
+            None
+        };
+
         info!("Build tool: Created {} builder tasks", tasks.len());
         for (i, task) in tasks.iter().enumerate() {
             debug!("Builder {}: id={}, name={}", i, task.id, task.name);
@@ -204,7 +272,10 @@ impl Tool for BuildTool {
         // Create pool and execute with build context
         let pool = SubAgentPool::new(self.client.clone(), self.cancellation.clone())
             .with_concurrency(concurrency)
-            .with_override_model(ctx.current_model.clone());
+            .with_override_model(self.model.clone().or_else(|| ctx.current_model.clone()))
+            .with_transcript(ctx.transcript.clone())
+            .with_file_changes(ctx.file_changes.clone())
+            .with_controls(ctx.agent_controls.clone());
 
         info!(
             "Build tool: Starting Kraken with max_concurrency={} (components={})",
@@ -223,6 +294,27 @@ impl Tool for BuildTool {
 
         info!("Build tool: Kraken returned {} results", results.len());
 
+        // Collect worktree results before reporting
+        let reports = match swarm {
+            Some(swarm) => {
+                let succeeded: HashSet<&str> = results
+                    .iter()
+                    .filter(|r| r.success)
+                    .map(|r| r.task_id.as_str())
+                    .collect();
+                Some(
+                    swarm
+                        .finish(
+                            |id| succeeded.contains(id),
+                            params.merge_back,
+                            ctx.file_changes.as_ref(),
+                        )
+                        .await,
+                )
+            }
+            None => None,
+        };
+
         // Get final stats from context
         let stats = context.stats();
 
@@ -250,34 +342,42 @@ impl Tool for BuildTool {
         // Add summary with build stats
         let mut summary = format!(
             "\n---\n**Build Complete**: {} builders, {} turns, {}ms\n\
-             **Changes**: +{} -{} lines, {} files\n\
-             **Locks**: {} contentions",
+             **Changes**: +{} -{} lines, {} files",
             results.len(),
             total_turns,
             total_duration_ms,
             stats.lines_added,
             stats.lines_removed,
             stats.files_modified,
-            stats.lock_contentions,
         );
 
-        // Add lock wait time info if significant
-        if stats.total_lock_wait_ms > 0 {
+        if let Some(reports) = &reports {
+            // Isolated builders never contend for locks; report merge results instead
+            summary.push_str(&format_worktree_reports(reports));
+        } else {
             summary.push_str(&format!(
-                ", {:.1}s total wait",
-                stats.total_lock_wait_ms as f64 / 1000.0
+                "\n**Locks**: {} contentions",
+                stats.lock_contentions
             ));
-        }
 
-        // Report high contention files
-        if !stats.high_contention_files.is_empty() {
-            summary.push_str("\n**High Contention Files**:");
-            for (path, duration) in &stats.high_contention_files {
-                let filename = path
-                    .file_name()
-                    .map(|n| n.to_string_lossy().to_string())
-                    .unwrap_or_else(|| path.display().to_string());
-                summary.push_str(&format!(" {} ({:.1}s)", filename, duration.as_secs_f64()));
+            // Add lock wait time info if significant
+            if stats.total_lock_wait_ms > 0 {
+                summary.push_str(&format!(
+                    ", {:.1}s total wait",
+                    stats.total_lock_wait_ms as f64 / 1000.0
+                ));
+            }
+
+            // Report high contention files
+            if !stats.high_contention_files.is_empty() {
+                summary.push_str("\n**High Contention Files**:");
+                for (path, duration) in &stats.high_contention_files {
+                    let filename = path
+                        .file_name()
+                        .map(|n| n.to_string_lossy().to_string())
+                        .unwrap_or_else(|| path.display().to_string());
+                    summary.push_str(&format!(" {} ({:.1}s)", filename, duration.as_secs_f64()));
+                }
             }
         }
 
@@ -294,3 +394,88 @@ impl Tool for BuildTool {
         }
     }
 }
+
+/// Summarize what happened to each builder's worktree
+fn format_worktree_reports(reports: &[BuilderReport]) -> String {
+    let merged = reports
+        .iter()
+        .filter(|r| matches!(r.outcome, BuilderOutcome::Merged { .. }))
+        .count();
+    let conflicts = reports
+        .iter()
+        .filter(|r| matches!(r.outcome, BuilderOutcome::Conflict { .. }))
+        .count();
+
+    let mut out = format!(
+        "\n**Worktrees**: {} merged, {} conflicts",
+        merged, conflicts
+    );
+    for report in reports {
+        let line = match &report.outcome {
+            BuilderOutcome::Merged { files } => format!("merged ({})", files.join(", ")),
+            BuilderOutcome::Unchanged => "no changes".to_string(),
+            BuilderOutcome::Conflict { files, branch } => format!(
+                "CONFLICT in {} - not applied; changes are on branch `{}`. \
+                 Merge it manually or redo the change on the current files",
+                files.join(", "),
+                branch
+            ),
+            BuilderOutcome::Kept { branch, stat } => {
+                format!("on branch `{}`\n```\n{}\n```", branch, stat)
+            }
+            BuilderOutcome::Discarded { branch } => {
+                format!(
+                    "failed - not applied; partial work is on branch `{}`",
+                    branch
+                )
+            }
+        };
+        out.push_str(&format!("\n- {}: {}", report.builder_id, line));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_isolation_values() {
+        let parse = |isolation: Value| {
+            serde_json::from_value::<Params>(json!({"prompt": "p", "isolation": isolation}))
+                .map(|p| p.isolation)
+        };
+        assert_eq!(parse(json!("worktree")).unwrap(), Some(Isolation::Worktree));
+        assert_eq!(parse(json!("shared")).unwrap(), Some(Isolation::Shared));
+        assert_eq!(parse(Value::Null).unwrap(), None);
+
+        let err = parse(json!("sandbox")).unwrap_err().to_string();
+        assert!(err.contains("sandbox"), "{err}");
+        assert!(err.contains("`shared` or `worktree`"), "{err}");
+    }
+
+    #[test]
+    fn test_format_worktree_reports() {
+        let reports = vec![
+            BuilderReport {
+                builder_id: "builder-0".to_string(),
+                outcome: BuilderOutcome::Merged {
+                    files: vec!["src/a.rs".to_string()],
+                },
+            },
+            BuilderReport {
+                builder_id: "builder-1".to_string(),
+                outcome: BuilderOutcome::Conflict {
+                    files: vec!["src/lib.rs".to_string()],
+                    branch: "krusty/build-x-builder-1".to_string(),
+                },
+            },
+        ];
+
+        let text = format_worktree_reports(&reports);
+        assert!(text.starts_with("\n**Worktrees**: 1 merged, 1 conflicts"));
+        assert!(text.contains("- builder-0: merged (src/a.rs)"));
+        assert!(text.contains("- builder-1: CONFLICT in src/lib.rs"));
+        assert!(text.contains("`krusty/build-x-builder-1`"));
+    }
+}
@@ -0,0 +1,146 @@
+//! Delegate tool - Run a user-defined sub-agent
+//!
+//! Agents are defined in `~/.krusty/agents/*.md` or `.krusty/agents/*.md`
+//! (see `agent::custom`). Each one brings its own system prompt, tool
+//! allowlist, model, turn limit and timeout.
+
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::sync::{Arc, Weak};
+use tokio::sync::RwLock;
+use tracing::info;
+
+use crate::agent::custom::{AgentDefinition, AgentsManager};
+use crate::agent::subagent::{SubAgentPool, SubAgentTask};
+use crate::agent::AgentCancellation;
+use crate::ai::client::AiClient;
+use crate::tools::parse_params;
+use crate::tools::registry::{Tool, ToolContext, ToolRegistry, ToolResult};
+
+/// Delegate tool for running named agents
+pub struct DelegateTool {
+    client: Arc<AiClient>,
+    cancellation: AgentCancellation,
+    /// Registry the agents' tools come from (weak: the registry owns this tool)
+    registry: Weak<ToolRegistry>,
+    agents: Arc<RwLock<AgentsManager>>,
+    /// Agent names, for the parameter schema
+    agent_names: Vec<String>,
+    /// Description listing the agents available at registration time
+    description: String,
+}
+
+impl DelegateTool {
+    pub fn new(
+        client: Arc<AiClient>,
+        cancellation: AgentCancellation,
+        registry: &Arc<ToolRegistry>,
+        agents: Arc<RwLock<AgentsManager>>,
+        definitions: &[AgentDefinition],
+    ) -> Self {
+        let listing: Vec<String> = definitions
+            .iter()
+            .map(|a| format!("- {}: {}", a.name, a.description))
+            .collect();
+        let description = format!(
+            "Hand a task to one of the user's specialised sub-agents. The agent works \
+             on its own with its own instructions and tools, then returns its final answer. \
+             Give it a complete, self-contained prompt - it cannot see this conversation.\n\n\
+             Available agents:\n{}",
+            listing.join("\n")
+        );
+
+        Self {
+            client,
+            cancellation,
+            registry: Arc::downgrade(registry),
+            agents,
+            agent_names: definitions.iter().map(|a| a.name.clone()).collect(),
+            description,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct Params {
+    /// Name of the agent to run
+    agent: String,
+    /// Task for the agent
+    prompt: String,
+}
+
+#[async_trait]
+impl Tool for DelegateTool {
+    fn name(&self) -> &str {
+        "delegate"
+    }
+
+    fn description(&self) -> &str {
+        &self.description
+    }
+
+    fn parameters_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "agent": {
+                    "type": "string",
+                    "enum": self.agent_names,
+                    "description": "Name of the agent to run"
+                },
+                "prompt": {
+                    "type": "string",
+                    "description": "The complete task for the agent, including any context it needs"
+                }
+            },
+            "required": ["agent", "prompt"],
+            "additionalProperties": false
+        })
+    }
+
+    async fn execute(&self, params: Value, ctx: &ToolContext) -> ToolResult {
+        let params = match parse_params::<Params>(params) {
+            Ok(p) => p,
+            Err(e) => return e,
+        };
+
+        let Some(definition) = self.agents.write().await.get_agent(&params.agent).cloned() else {
+            return ToolResult::error(format!(
+                "Unknown agent '{}'. Available: {}",
+                params.agent,
+                self.agent_names.join(", ")
+            ));
+        };
+        let Some(registry) = self.registry.upgrade() else {
+            return ToolResult::error("Tool registry is no longer available");
+        };
+
+        info!(agent = %definition.name, "Delegate tool: running agent");
+
+        let task = SubAgentTask::new(definition.name.clone(), params.prompt)
+            .with_working_dir(ctx.working_dir.clone());
+        // Custom agents run their tools through the registry with a context
+        // inherited from `ctx`, which reports file changes itself
+        let pool = SubAgentPool::new(self.client.clone(), self.cancellation.clone())
+            .with_override_model(ctx.current_model.clone())
+            .with_transcript(ctx.transcript.clone())
+            .with_controls(ctx.agent_controls.clone());
+        let result = pool.execute_custom(task, &definition, registry, ctx).await;
+
+        if !result.success {
+            return ToolResult::error(format!(
+                "Agent '{}' failed: {}",
+                definition.name,
+                result.error.as_deref().unwrap_or("unknown error")
+            ));
+        }
+
+        // Same layout as explore, so the UI can show it in an explore block
+        let output = format!(
+            "\n## Agent: {}\n{}\n\n---\n**Summary**: {} turns, {}ms",
+            result.task_id, result.output, result.turns_used, result.duration_ms
+        );
+        ToolResult::success(output)
+    }
+}
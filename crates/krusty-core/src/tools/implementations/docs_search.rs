@@ -0,0 +1,117 @@
+//! Docs search tool - Full-text search over docs indexed from extension providers
+
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+use crate::docs::DocsManager;
+use crate::tools::registry::Tool;
+use crate::tools::{parse_params, ToolContext, ToolResult};
+
+const DEFAULT_LIMIT: usize = 10;
+const MAX_LIMIT: usize = 50;
+
+pub struct DocsSearchTool {
+    docs_manager: Arc<DocsManager>,
+}
+
+impl DocsSearchTool {
+    pub fn new(docs_manager: Arc<DocsManager>) -> Self {
+        Self { docs_manager }
+    }
+}
+
+#[derive(Deserialize)]
+struct Params {
+    /// Search terms
+    #[serde(default)]
+    query: Option<String>,
+    /// Restrict to a single indexed package
+    #[serde(default)]
+    package: Option<String>,
+    /// Fetch the full docs for an exact entry key
+    #[serde(default)]
+    key: Option<String>,
+    #[serde(default)]
+    limit: Option<usize>,
+}
+
+#[async_trait]
+impl Tool for DocsSearchTool {
+    fn name(&self) -> &str {
+        "docs_search"
+    }
+
+    fn description(&self) -> &str {
+        "Search indexed package documentation (indexed by the user via /docs). Pass `query` for a full-text search that returns matching entry keys with snippets, then pass `key` to read the full docs for one entry. Use `package` to restrict results to one package."
+    }
+
+    fn parameters_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "query": {
+                    "type": "string",
+                    "description": "Search terms (e.g., 'deserialize borrowed str')"
+                },
+                "package": {
+                    "type": "string",
+                    "description": "Optional: only search this package (e.g., 'serde')"
+                },
+                "key": {
+                    "type": "string",
+                    "description": "Optional: exact entry key from a previous search to read in full"
+                },
+                "limit": {
+                    "type": "integer",
+                    "description": "Maximum number of results (default 10, max 50)"
+                }
+            },
+            "additionalProperties": false
+        })
+    }
+
+    async fn execute(&self, params: Value, _ctx: &ToolContext) -> ToolResult {
+        let params = match parse_params::<Params>(params) {
+            Ok(p) => p,
+            Err(e) => return e,
+        };
+
+        if let Some(ref key) = params.key {
+            return match self.docs_manager.get_entry(key, params.package.as_deref()) {
+                Ok(Some(content)) => ToolResult::success(content),
+                Ok(None) => ToolResult::error(format!("No docs entry with key '{}'", key)),
+                Err(e) => ToolResult::error(format!("Failed to read docs: {}", e)),
+            };
+        }
+
+        let Some(query) = params.query.filter(|q| !q.trim().is_empty()) else {
+            return ToolResult::error("Either 'query' or 'key' is required");
+        };
+
+        let limit = params.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+        let hits = match self
+            .docs_manager
+            .search(&query, params.package.as_deref(), limit)
+        {
+            Ok(hits) => hits,
+            Err(e) => return ToolResult::error(format!("Docs search failed: {}", e)),
+        };
+
+        if hits.is_empty() {
+            return ToolResult::success(format!("No docs found for '{}'", query));
+        }
+
+        let mut output = String::new();
+        for hit in hits {
+            output.push_str(&format!(
+                "## {} ({})\n{}\n\n",
+                hit.key,
+                hit.package,
+                hit.snippet.trim()
+            ));
+        }
+        ToolResult::success(output.trim_end().to_string())
+    }
+}
@@ -18,6 +18,8 @@ use crate::tools::registry::{Tool, ToolContext, ToolResult};
 pub struct ExploreTool {
     client: Arc<AiClient>,
     cancellation: AgentCancellation,
+    /// Model routed to this role; `None` follows the session's current model
+    model: Option<String>,
 }
 
 impl ExploreTool {
@@ -25,8 +27,15 @@ impl ExploreTool {
         Self {
             client,
             cancellation,
+            model: None,
         }
     }
+
+    /// Run sub-agents on a routed model (`client` must be built for it)
+    pub fn with_model(mut self, model: Option<String>) -> Self {
+        self.model = model;
+        self
+    }
 }
 
 #[derive(Deserialize)]
@@ -158,7 +167,10 @@ impl Tool for ExploreTool {
         // Create pool and execute (with progress if channel available)
         let pool = SubAgentPool::new(self.client.clone(), self.cancellation.clone())
             .with_concurrency(params.max_concurrency)
-            .with_override_model(ctx.current_model.clone());
+            .with_override_model(self.model.clone().or_else(|| ctx.current_model.clone()))
+            .with_transcript(ctx.transcript.clone())
+            .with_file_changes(ctx.file_changes.clone())
+            .with_controls(ctx.agent_controls.clone());
 
         info!(
             "Explore tool: Starting pool execution with max_concurrency={}",
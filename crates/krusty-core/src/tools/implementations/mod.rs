@@ -10,7 +10,9 @@
 //! - processes: Manage background processes
 //! - explore: Spawn parallel sub-agents for deep codebase exploration
 //! - build: Spawn parallel Opus builder agents (The Kraken)
+//! - delegate: Run a user-defined agent from .krusty/agents/
 //! - skill: Invoke skills for specialized instructions
+//! - docs_search: Search docs indexed from extension docs providers
 //! - ask_user: Interactive user prompts (handled by UI)
 //! - task_complete: Mark plan tasks as complete with result (handled by UI)
 //! - task_start: Mark task as in-progress (handled by UI)
@@ -22,6 +24,8 @@ pub mod add_subtask;
 pub mod ask_user;
 pub mod bash;
 pub mod build;
+pub mod delegate;
+pub mod docs_search;
 pub mod edit;
 pub mod explore;
 pub mod glob;
@@ -39,6 +43,8 @@ pub use add_subtask::AddSubtaskTool;
 pub use ask_user::AskUserQuestionTool;
 pub use bash::BashTool;
 pub use build::BuildTool;
+pub use delegate::DelegateTool;
+pub use docs_search::DocsSearchTool;
 pub use edit::EditTool;
 pub use explore::ExploreTool;
 pub use glob::GlobTool;
@@ -53,9 +59,12 @@ pub use task_start::TaskStartTool;
 pub use write::WriteTool;
 
 use std::sync::Arc;
+use tokio::sync::RwLock;
 
+use crate::agent::custom::AgentsManager;
 use crate::agent::AgentCancellation;
 use crate::ai::client::AiClient;
+use crate::docs::DocsManager;
 use crate::tools::registry::ToolRegistry;
 
 /// Register all built-in tools (except explore which needs client)
@@ -95,26 +104,77 @@ pub async fn register_acp_tools(registry: &ToolRegistry) {
 
 /// Register the explore tool (requires AI client)
 ///
-/// Call this after authentication when the client is available.
+/// Call this after authentication when the client is available. Pass the
+/// routed `model` (with a client built for it) to keep explorers off the
+/// main model; `None` follows the session's current model.
 pub async fn register_explore_tool(
     registry: &ToolRegistry,
     client: Arc<AiClient>,
     cancellation: AgentCancellation,
+    model: Option<String>,
 ) {
     registry
-        .register(Arc::new(ExploreTool::new(client, cancellation)))
+        .register(Arc::new(
+            ExploreTool::new(client, cancellation).with_model(model),
+        ))
         .await;
 }
 
 /// Register the build tool (The Kraken - parallel Opus builders)
 ///
-/// Call this after authentication when the client is available.
+/// Call this after authentication when the client is available. `model`
+/// works as for `register_explore_tool`.
 pub async fn register_build_tool(
     registry: &ToolRegistry,
     client: Arc<AiClient>,
     cancellation: AgentCancellation,
+    model: Option<String>,
 ) {
     registry
-        .register(Arc::new(BuildTool::new(client, cancellation)))
+        .register(Arc::new(
+            BuildTool::new(client, cancellation).with_model(model),
+        ))
+        .await;
+}
+
+/// Register the delegate tool for user-defined agents
+///
+/// Re-reads the agent files; with none defined, the tool is removed.
+/// Returns the number of agents available.
+pub async fn register_delegate_tool(
+    registry: &Arc<ToolRegistry>,
+    client: Arc<AiClient>,
+    cancellation: AgentCancellation,
+    agents: Arc<RwLock<AgentsManager>>,
+) -> usize {
+    let definitions = {
+        let mut manager = agents.write().await;
+        manager.refresh();
+        manager.list_agents()
+    };
+
+    if definitions.is_empty() {
+        registry.unregister("delegate").await;
+        return 0;
+    }
+
+    registry
+        .register(Arc::new(DelegateTool::new(
+            client,
+            cancellation,
+            registry,
+            agents,
+            &definitions,
+        )))
+        .await;
+    definitions.len()
+}
+
+/// Register the docs search tool (requires docs manager)
+///
+/// Call this once at least one package has been indexed.
+pub async fn register_docs_search_tool(registry: &ToolRegistry, docs_manager: Arc<DocsManager>) {
+    registry
+        .register(Arc::new(DocsSearchTool::new(docs_manager)))
         .await;
 }
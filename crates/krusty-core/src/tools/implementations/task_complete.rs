@@ -4,6 +4,7 @@
 //! ENFORCES: Task must be InProgress (use task_start first)
 //! ENFORCES: One task at a time (no batch completion)
 //! ENFORCES: Result parameter required
+//! ENFORCES: The task's acceptance checks (`> Check:` lines) must pass
 
 use async_trait::async_trait;
 use serde_json::{json, Value};
@@ -19,7 +20,7 @@ impl Tool for TaskCompleteTool {
     }
 
     fn description(&self) -> &str {
-        "Complete ONE task that you have started with task_start. The task MUST be in-progress. Provide a specific result describing what you accomplished for THIS task."
+        "Complete ONE task that you have started with task_start. The task MUST be in-progress. Provide a specific result describing what you accomplished for THIS task. If the task has acceptance checks they are run first; a failing check keeps the task in progress and its output is returned."
     }
 
     fn parameters_schema(&self) -> Value {
@@ -10,11 +10,12 @@ use std::time::{Duration, Instant};
 use tokio::sync::{mpsc, RwLock};
 
 use crate::agent::hooks::{HookResult, PostToolHook, PreToolHook};
-use crate::agent::subagent::AgentProgress;
+use crate::agent::subagent::{AgentControls, AgentProgress, SharedTranscriptSink};
 use crate::ai::types::AiTool;
 use crate::mcp::McpManager;
 use crate::process::ProcessRegistry;
 use crate::skills::SkillsManager;
+use crate::tools::file_changes::{self, SharedFileChangeSink};
 use crate::tools::git_identity::GitIdentity;
 
 /// Default tool execution timeout (2 minutes)
@@ -87,6 +88,12 @@ pub struct ToolContext {
     pub current_model: Option<String>,
     /// Git identity for commit attribution
     pub git_identity: Option<GitIdentity>,
+    /// Where sub-agents started by this call record their transcripts
+    pub transcript: Option<SharedTranscriptSink>,
+    /// Per-agent controls for sub-agents started by this call
+    pub agent_controls: Option<AgentControls>,
+    /// Where files touched by this call and its sub-agents are reported
+    pub file_changes: Option<SharedFileChangeSink>,
 }
 
 impl Default for ToolContext {
@@ -106,6 +113,9 @@ impl Default for ToolContext {
             build_progress_tx: None,
             current_model: None,
             git_identity: None,
+            transcript: None,
+            agent_controls: None,
+            file_changes: None,
         }
     }
 }
@@ -182,6 +192,48 @@ impl ToolContext {
         self
     }
 
+    /// Record transcripts of sub-agents started by this call
+    pub fn with_transcript(mut self, transcript: SharedTranscriptSink) -> Self {
+        self.transcript = Some(transcript);
+        self
+    }
+
+    /// Let the UI stop, restart or message sub-agents started by this call
+    pub fn with_agent_controls(mut self, controls: AgentControls) -> Self {
+        self.agent_controls = Some(controls);
+        self
+    }
+
+    /// Report the files this call and its sub-agents touch
+    pub fn with_file_changes(mut self, sink: SharedFileChangeSink) -> Self {
+        self.file_changes = Some(sink);
+        self
+    }
+
+    /// Copy the shareable parts of this context for a nested agent
+    ///
+    /// The transcript sink, agent controls and file change sink carry over,
+    /// so agents started from nested calls stay recorded and controllable.
+    /// Streaming channels and the tool use ID belong to the parent call.
+    pub fn inherit(&self) -> Self {
+        Self {
+            working_dir: self.working_dir.clone(),
+            sandbox_root: self.sandbox_root.clone(),
+            user_id: self.user_id.clone(),
+            process_registry: self.process_registry.clone(),
+            skills_manager: self.skills_manager.clone(),
+            mcp_manager: self.mcp_manager.clone(),
+            timeout: self.timeout,
+            plan_mode: self.plan_mode,
+            current_model: self.current_model.clone(),
+            git_identity: self.git_identity.clone(),
+            transcript: self.transcript.clone(),
+            agent_controls: self.agent_controls.clone(),
+            file_changes: self.file_changes.clone(),
+            ..Default::default()
+        }
+    }
+
     /// Resolve a path relative to working directory (absolute paths pass through)
     pub fn resolve_path(&self, path: &str) -> std::path::PathBuf {
         let p = std::path::PathBuf::from(path);
@@ -378,6 +430,13 @@ impl ToolRegistry {
             .collect()
     }
 
+    /// Unregister a tool by name
+    pub async fn unregister(&self, name: &str) {
+        if self.tools.write().await.remove(name).is_some() {
+            tracing::debug!("Unregistered tool: {}", name);
+        }
+    }
+
     /// Unregister all tools with names starting with the given prefix
     pub async fn unregister_by_prefix(&self, prefix: &str) {
         let mut tools = self.tools.write().await;
@@ -420,6 +479,10 @@ impl ToolRegistry {
             }
         }
 
+        let tracked =
+            file_changes::before_tool(ctx.file_changes.as_ref(), name, &params, &ctx.working_dir)
+                .await;
+
         // Execute the tool with timeout
         let result = match tokio::time::timeout(timeout, tool.execute(params.clone(), ctx)).await {
             Ok(result) => result,
@@ -441,6 +504,7 @@ impl ToolRegistry {
         };
 
         let duration = start.elapsed();
+        file_changes::after_tool(tracked, result.is_error).await;
 
         // Run post-hooks - they can inspect/log but we don't modify results (yet)
         for hook in &self.post_hooks {
@@ -487,6 +551,49 @@ mod tests {
         );
     }
 
+    struct NoFileChanges;
+
+    #[async_trait]
+    impl crate::tools::FileChangeSink for NoFileChanges {
+        async fn before_change(&self, _path: &std::path::Path) {}
+        async fn record(&self, _path: &std::path::Path, _access: crate::storage::FileAccess) {}
+    }
+
+    #[test]
+    fn test_inherit_keeps_sinks_and_drops_call_channels() {
+        let transcript: SharedTranscriptSink =
+            Arc::new(crate::agent::subagent::MemoryTranscript::new());
+        let file_changes: SharedFileChangeSink = Arc::new(NoFileChanges);
+        let (output_tx, _output_rx) = mpsc::unbounded_channel();
+        let (progress_tx, _progress_rx) = mpsc::unbounded_channel();
+        let parent = ToolContext {
+            working_dir: PathBuf::from("/project"),
+            plan_mode: true,
+            tool_use_id: Some("toolu_1".into()),
+            output_tx: Some(output_tx),
+            explore_progress_tx: Some(progress_tx.clone()),
+            build_progress_tx: Some(progress_tx),
+            transcript: Some(transcript.clone()),
+            agent_controls: Some(AgentControls::new()),
+            file_changes: Some(file_changes.clone()),
+            ..Default::default()
+        };
+
+        let child = parent.inherit();
+        assert_eq!(child.working_dir, PathBuf::from("/project"));
+        assert!(child.plan_mode);
+        assert!(Arc::ptr_eq(child.transcript.as_ref().unwrap(), &transcript));
+        assert!(Arc::ptr_eq(
+            child.file_changes.as_ref().unwrap(),
+            &file_changes
+        ));
+        assert!(child.agent_controls.is_some());
+        assert!(child.tool_use_id.is_none());
+        assert!(child.output_tx.is_none());
+        assert!(child.explore_progress_tx.is_none());
+        assert!(child.build_progress_tx.is_none());
+    }
+
     #[tokio::test]
     async fn test_tool_result_success() {
         let result = ToolResult::success("Test output");